@@ -0,0 +1,134 @@
+//! Accrues compound interest on a deposit account for the slots elapsed since
+//! its last accrual.
+//!
+//! The float part of the computation is confined to one number: the annual
+//! rate is converted to a rate over the elapsed slots by [`rate_convert`],
+//! which compounds through the crate's deterministic `ln`/`exp`, and that rate
+//! is quantized to [`RATE_DECIMALS`] digits. Applying it to the integer
+//! principal is then an exact `mul_div` rounded down, so a deposit never earns
+//! more than its rate allows and every validator credits the same amount.
+
+use solana_floats::error::FloatError;
+use solana_floats::float_ext::SolanaFloatExt;
+use solana_floats::mul_div::mul_div;
+use solana_floats::quantize::{RoundingMode, pow10};
+use solana_floats::rates::{Compounding, RateUnit, TARGET_SLOTS_PER_YEAR, rate_convert};
+use solana_program::{
+    account_info::{AccountInfo, next_account_info},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+#[cfg(not(feature = "no-entrypoint"))]
+use solana_program::entrypoint;
+
+#[cfg(not(feature = "no-entrypoint"))]
+entrypoint!(process_instruction);
+
+/// Fractional digits the rate over the elapsed slots is quantized to.
+pub const RATE_DECIMALS: u32 = 12;
+
+/// The state of a deposit account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deposit {
+    /// Principal plus the interest credited so far, in base units.
+    pub principal: u64,
+    /// Slot of the last accrual.
+    pub last_slot: u64,
+}
+
+impl Deposit {
+    /// Size of the serialized form: `principal: u64 | last_slot: u64`.
+    pub const PACKED_LEN: usize = 16;
+
+    pub fn pack(&self) -> [u8; Self::PACKED_LEN] {
+        let mut dst = [0u8; Self::PACKED_LEN];
+        dst[0..8].copy_from_slice(&self.principal.to_le_bytes());
+        dst[8..16].copy_from_slice(&self.last_slot.to_le_bytes());
+        dst
+    }
+
+    pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::PACKED_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            principal: u64::from_le_bytes(src[0..8].try_into().unwrap()),
+            last_slot: u64::from_le_bytes(src[8..16].try_into().unwrap()),
+        })
+    }
+}
+
+/// Interest earned by `principal` over `slots` at `annual_rate` compounded
+/// (an APY, `0.05` for 5%), rounded down, with [`TARGET_SLOTS_PER_YEAR`]
+/// slots per year.
+///
+/// Fails with `NaNOperand` for a NaN rate, `OutOfRange` for a negative one
+/// and `Overflow` if the interest does not fit in a u64.
+pub fn accrue(principal: u64, annual_rate: f64, slots: u64) -> Result<u64, FloatError> {
+    if annual_rate < 0.0 {
+        return Err(FloatError::OutOfRange);
+    }
+    if slots == 0 {
+        return Ok(0);
+    }
+    let period_rate = rate_convert(
+        annual_rate,
+        RateUnit::PerYear,
+        RateUnit::PerEpoch(slots),
+        TARGET_SLOTS_PER_YEAR,
+        Compounding::Compounded,
+    )?;
+    let scaled_rate = period_rate.to_scaled(RATE_DECIMALS)?;
+    mul_div(
+        principal,
+        scaled_rate,
+        pow10(RATE_DECIMALS)?,
+        RoundingMode::Floor,
+    )
+}
+
+// Layout: [annual_rate: f64]
+// Accounts: [deposit (writable, owned by this program)]
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let annual_rate = f64::from_le_bytes(
+        instruction_data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?,
+    );
+    let account_info_iter = &mut accounts.iter();
+    let deposit_account = next_account_info(account_info_iter)?;
+    if deposit_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut deposit = Deposit::unpack(&deposit_account.try_borrow_data()?)?;
+
+    let slot = Clock::get()?.slot;
+    let elapsed = slot.saturating_sub(deposit.last_slot);
+    let interest = accrue(deposit.principal, annual_rate, elapsed)
+        .map_err(|_| ProgramError::InvalidArgument)?;
+    deposit.principal = deposit
+        .principal
+        .checked_add(interest)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    deposit.last_slot = deposit.last_slot.max(slot);
+    deposit_account
+        .try_borrow_mut_data()?
+        .copy_from_slice(&deposit.pack());
+    msg!(
+        "Accrue: {} interest over {} slots, principal now {}",
+        interest,
+        elapsed,
+        deposit.principal
+    );
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use interest_accrual::*;
+use solana_floats::error::FloatError;
+use solana_floats::rates::TARGET_SLOTS_PER_YEAR;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+#[cfg(test)]
+mod interest_accrual_tests {
+    use super::*;
+
+    const PRINCIPAL: u64 = 1_000_000_000_000;
+
+    // Runs one accrual at `slot` on a deposit owned by `owner` (the program if
+    // None) and returns the deposit afterwards
+    async fn accrue_at_slot(
+        deposit: Deposit,
+        annual_rate: f64,
+        slot: u64,
+        owner: Option<Pubkey>,
+    ) -> Result<Deposit, TransactionError> {
+        let program_id = Pubkey::new_unique();
+        let deposit_address = Pubkey::new_unique();
+        let mut program_test = ProgramTest::new(
+            "interest_accrual",
+            program_id,
+            processor!(process_instruction),
+        );
+        program_test.add_account(
+            deposit_address,
+            Account {
+                lamports: 1_000_000_000,
+                data: deposit.pack().to_vec(),
+                owner: owner.unwrap_or(program_id),
+                ..Account::default()
+            },
+        );
+        let mut context = program_test.start_with_context().await;
+        context.warp_to_slot(slot).unwrap();
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &annual_rate.to_le_bytes(),
+            vec![AccountMeta::new(deposit_address, false)],
+        );
+        let blockhash = context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|err| err.unwrap())?;
+        let account = context
+            .banks_client
+            .get_account(deposit_address)
+            .await
+            .unwrap()
+            .unwrap();
+        Ok(Deposit::unpack(&account.data).unwrap())
+    }
+
+    #[test]
+    fn test_one_year_earns_the_annual_rate() {
+        // A year is not a whole number of slots; 0.98 slots short of one,
+        // 1.05^(78_892_314 / 78_892_314.984) - 1 of the principal accrues
+        let interest = accrue(PRINCIPAL, 0.05, TARGET_SLOTS_PER_YEAR as u64).unwrap();
+        assert!(interest.abs_diff(49_999_999_361) <= 1, "{}", interest);
+        // Two half-year accruals compound to the same
+        let half = accrue(PRINCIPAL, 0.05, TARGET_SLOTS_PER_YEAR as u64 / 2).unwrap();
+        let second = accrue(PRINCIPAL + half, 0.05, TARGET_SLOTS_PER_YEAR as u64 / 2).unwrap();
+        assert!((half + second).abs_diff(interest) <= 2);
+    }
+
+    #[test]
+    fn test_accrue_edge_cases() {
+        assert_eq!(accrue(PRINCIPAL, 0.05, 0), Ok(0));
+        assert_eq!(accrue(PRINCIPAL, 0.0, 1_000), Ok(0));
+        // A single slot at 5% still earns something on a large principal
+        assert!(accrue(PRINCIPAL, 0.05, 1).unwrap() > 0);
+        assert_eq!(accrue(PRINCIPAL, -0.01, 1_000), Err(FloatError::OutOfRange));
+        assert_eq!(
+            accrue(PRINCIPAL, f64::NAN, 1_000),
+            Err(FloatError::NaNOperand)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_accrues_over_elapsed_slots() {
+        let before = Deposit {
+            principal: PRINCIPAL,
+            last_slot: 0,
+        };
+        let after = accrue_at_slot(before, 0.05, 100_000, None).await.unwrap();
+        assert!(after.last_slot >= 100_000);
+        assert_eq!(
+            after.principal,
+            PRINCIPAL + accrue(PRINCIPAL, 0.05, after.last_slot).unwrap()
+        );
+        assert!(after.principal > PRINCIPAL);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_foreign_deposits_and_bad_rates() {
+        let deposit = Deposit {
+            principal: PRINCIPAL,
+            last_slot: 0,
+        };
+        assert_eq!(
+            accrue_at_slot(deposit, 0.05, 10, Some(Pubkey::new_unique())).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::IncorrectProgramId
+            ))
+        );
+        assert_eq!(
+            accrue_at_slot(deposit, -0.05, 10, None).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidArgument
+            ))
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Combines price quotes from several oracles into one price.
+//!
+//! Quotes whose confidence interval is wider than a caller-chosen share of
+//! their price are dropped, and the price and confidence of the rest are each
+//! taken as a median, so a single stale or manipulated feed cannot move the
+//! result. The medians use [`median_inplace`], whose comparisons depend only
+//! on the input values, so every validator selects the same quotes.
+
+use solana_floats::bps::Bps;
+use solana_floats::error::FloatError;
+use solana_floats::portfolio::PriceWithConf;
+use solana_floats::stats::median_inplace;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program::set_return_data,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+
+#[cfg(not(feature = "no-entrypoint"))]
+use solana_program::entrypoint;
+
+#[cfg(not(feature = "no-entrypoint"))]
+entrypoint!(process_instruction);
+
+/// `ProgramError::Custom` code for fewer accepted quotes than required.
+pub const NOT_ENOUGH_QUOTES: u32 = 0;
+
+/// Size of one quote in the instruction data: `price: f64 | conf: f64`.
+pub const QUOTE_LEN: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aggregate {
+    pub quote: PriceWithConf,
+    /// Number of quotes that passed the confidence filter.
+    pub accepted: usize,
+}
+
+/// The median price and confidence of the quotes whose confidence is at most
+/// `max_conf` of their price, or `None` if fewer than `min_quotes` (and at
+/// least one) pass.
+///
+/// Fails with `NaNOperand` for NaN quotes and `OutOfRange` for infinite
+/// fields, a negative confidence or a price that is not positive.
+pub fn aggregate(
+    quotes: &[PriceWithConf],
+    max_conf: Bps,
+    min_quotes: usize,
+) -> Result<Option<Aggregate>, FloatError> {
+    let mut prices = Vec::with_capacity(quotes.len());
+    let mut confs = Vec::with_capacity(quotes.len());
+    for quote in quotes {
+        let quote = PriceWithConf::new(quote.price, quote.conf)?;
+        if quote.price <= 0.0 {
+            return Err(FloatError::OutOfRange);
+        }
+        if quote.conf <= quote.price * max_conf.as_fraction() {
+            prices.push(quote.price);
+            confs.push(quote.conf);
+        }
+    }
+    if prices.is_empty() || prices.len() < min_quotes {
+        return Ok(None);
+    }
+    Ok(Some(Aggregate {
+        quote: PriceWithConf {
+            price: median_inplace(&mut prices)?,
+            conf: median_inplace(&mut confs)?,
+        },
+        accepted: prices.len(),
+    }))
+}
+
+/// Instruction data for an aggregation:
+/// `max_conf_bps: u16 | min_quotes: u8 | quote*`, all little-endian.
+pub fn encode_aggregate(max_conf: Bps, min_quotes: u8, quotes: &[PriceWithConf]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(3 + quotes.len() * QUOTE_LEN);
+    data.extend_from_slice(&(max_conf.0 as u16).to_le_bytes());
+    data.push(min_quotes);
+    for quote in quotes {
+        data.extend_from_slice(&quote.price.to_le_bytes());
+        data.extend_from_slice(&quote.conf.to_le_bytes());
+    }
+    data
+}
+
+/// Reads the return data of a successful aggregation.
+pub fn decode_aggregate(data: &[u8]) -> Option<Aggregate> {
+    if data.len() != 17 {
+        return None;
+    }
+    Some(Aggregate {
+        quote: PriceWithConf {
+            price: f64::from_le_bytes(data[0..8].try_into().unwrap()),
+            conf: f64::from_le_bytes(data[8..16].try_into().unwrap()),
+        },
+        accepted: data[16] as usize,
+    })
+}
+
+// Layout: see `encode_aggregate`
+// Return data: [price: f64][conf: f64][accepted: u8]
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 3 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let max_conf = Bps(u16::from_le_bytes(instruction_data[0..2].try_into().unwrap()) as u32);
+    let min_quotes = instruction_data[2] as usize;
+    let chunks = instruction_data[3..].chunks_exact(QUOTE_LEN);
+    if !chunks.remainder().is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let quotes: Vec<PriceWithConf> = chunks
+        .map(|quote| PriceWithConf {
+            price: f64::from_le_bytes(quote[0..8].try_into().unwrap()),
+            conf: f64::from_le_bytes(quote[8..16].try_into().unwrap()),
+        })
+        .collect();
+    if quotes.len() > u8::MAX as usize {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let Some(result) =
+        aggregate(&quotes, max_conf, min_quotes).map_err(|_| ProgramError::InvalidArgument)?
+    else {
+        msg!("Aggregate: fewer than {} usable quotes", min_quotes.max(1));
+        return Err(ProgramError::Custom(NOT_ENOUGH_QUOTES));
+    };
+    msg!(
+        "Aggregate: {} of {} quotes, price {} +/- {}",
+        result.accepted,
+        quotes.len(),
+        result.quote.price,
+        result.quote.conf
+    );
+    let mut return_data = result.quote.price.to_le_bytes().to_vec();
+    return_data.extend_from_slice(&result.quote.conf.to_le_bytes());
+    return_data.push(result.accepted as u8);
+    set_return_data(&return_data);
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use oracle_aggregator::*;
+use solana_floats::bps::Bps;
+use solana_floats::error::FloatError;
+use solana_floats::portfolio::PriceWithConf;
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+#[cfg(test)]
+mod oracle_aggregator_tests {
+    use super::*;
+
+    fn quote(price: f64, conf: f64) -> PriceWithConf {
+        PriceWithConf { price, conf }
+    }
+
+    async fn process(instruction_data: Vec<u8>) -> Result<Aggregate, TransactionError> {
+        let program_id = Pubkey::new_unique();
+        let program_test = ProgramTest::new(
+            "oracle_aggregator",
+            program_id,
+            processor!(process_instruction),
+        );
+        let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        let instruction = Instruction::new_with_bytes(program_id, &instruction_data, vec![]);
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        let result = banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        result.result?;
+        Ok(decode_aggregate(&result.metadata.unwrap().return_data.unwrap().data).unwrap())
+    }
+
+    #[test]
+    fn test_median_ignores_an_outlier() {
+        let quotes = [
+            quote(150.10, 0.05),
+            quote(150.00, 0.10),
+            quote(149.95, 0.08),
+            quote(9_999.0, 0.5),
+        ];
+        let result = aggregate(&quotes, Bps(50), 3).unwrap().unwrap();
+        assert_eq!(result.accepted, 4);
+        assert_eq!(result.quote.price, (150.00 + 150.10) / 2.0);
+        assert_eq!(result.quote.conf, (0.08 + 0.10) / 2.0);
+    }
+
+    #[test]
+    fn test_wide_quotes_are_dropped() {
+        // 1.5 is 1% of 150, over the 50 bps limit
+        let quotes = [quote(150.0, 0.1), quote(140.0, 1.5), quote(151.0, 0.2)];
+        let result = aggregate(&quotes, Bps(50), 2).unwrap().unwrap();
+        assert_eq!(result.accepted, 2);
+        assert_eq!(result.quote.price, 150.5);
+        assert_eq!(aggregate(&quotes, Bps(50), 3), Ok(None));
+        assert_eq!(aggregate(&quotes, Bps(0), 0), Ok(None));
+    }
+
+    #[test]
+    fn test_invalid_quotes() {
+        assert_eq!(
+            aggregate(&[quote(f64::NAN, 0.1)], Bps(50), 1),
+            Err(FloatError::NaNOperand)
+        );
+        for bad in [
+            quote(150.0, -0.1),
+            quote(0.0, 0.0),
+            quote(f64::INFINITY, 0.1),
+        ] {
+            assert_eq!(aggregate(&[bad], Bps(50), 1), Err(FloatError::OutOfRange));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_program_returns_the_aggregate() {
+        let quotes = [
+            quote(0.1 + 0.2, 0.001),
+            quote(0.3, 0.001),
+            quote(0.31, 0.0001),
+        ];
+        let result = process(encode_aggregate(Bps(100), 3, &quotes))
+            .await
+            .unwrap();
+        assert_eq!(result, aggregate(&quotes, Bps(100), 3).unwrap().unwrap());
+        assert_eq!(result.quote.price.to_bits(), (0.1_f64 + 0.2).to_bits());
+    }
+
+    #[tokio::test]
+    async fn test_program_errors() {
+        let quotes = [quote(150.0, 0.1), quote(140.0, 1.5)];
+        assert_eq!(
+            process(encode_aggregate(Bps(50), 2, &quotes)).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(NOT_ENOUGH_QUOTES)
+            ))
+        );
+        assert_eq!(
+            process(encode_aggregate(Bps(50), 1, &[quote(-1.0, 0.1)])).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidArgument
+            ))
+        );
+        let mut truncated = encode_aggregate(Bps(50), 1, &quotes);
+        truncated.pop();
+        assert_eq!(
+            process(truncated).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! Quotes a swap through a route of constant-product pools and rejects it if
+//! the output falls below the caller's minimum.
+//!
+//! The quote comes from [`estimate_route_out`], which works in integer token
+//! units and rounds down at every hop, so a quote that passes the minimum
+//! never promises more than the pools would pay. Its error bound is returned
+//! alongside, for clients that want to tell rounding from slippage.
+
+use solana_floats::amm::{PoolState, RouteEstimate, estimate_route_out};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program::set_return_data,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+
+#[cfg(not(feature = "no-entrypoint"))]
+use solana_program::entrypoint;
+
+#[cfg(not(feature = "no-entrypoint"))]
+entrypoint!(process_instruction);
+
+/// `ProgramError::Custom` code for a quote below `min_out`.
+pub const SLIPPAGE_EXCEEDED: u32 = 0;
+
+/// Size of one hop in the instruction data:
+/// `reserve_in: u64 | reserve_out: u64 | fee_bps: u16`.
+pub const HOP_LEN: usize = 18;
+
+/// Instruction data for a quote: `amount_in: u64 | min_out: u64 | hop*`, all
+/// little-endian.
+pub fn encode_quote(amount_in: u64, min_out: u64, hops: &[PoolState]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(16 + hops.len() * HOP_LEN);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_out.to_le_bytes());
+    for hop in hops {
+        data.extend_from_slice(&hop.reserve_in.to_le_bytes());
+        data.extend_from_slice(&hop.reserve_out.to_le_bytes());
+        data.extend_from_slice(&hop.fee_bps.to_le_bytes());
+    }
+    data
+}
+
+/// Reads the return data of a successful quote.
+pub fn decode_estimate(data: &[u8]) -> Option<RouteEstimate> {
+    if data.len() != 16 {
+        return None;
+    }
+    Some(RouteEstimate {
+        amount_out: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+        error_bound: u64::from_le_bytes(data[8..16].try_into().unwrap()),
+    })
+}
+
+// Layout: see `encode_quote`
+// Return data: [amount_out: u64][error_bound: u64]
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount_in = u64::from_le_bytes(instruction_data[0..8].try_into().unwrap());
+    let min_out = u64::from_le_bytes(instruction_data[8..16].try_into().unwrap());
+    let chunks = instruction_data[16..].chunks_exact(HOP_LEN);
+    if !chunks.remainder().is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let hops: Vec<PoolState> = chunks
+        .map(|hop| PoolState {
+            reserve_in: u64::from_le_bytes(hop[0..8].try_into().unwrap()),
+            reserve_out: u64::from_le_bytes(hop[8..16].try_into().unwrap()),
+            fee_bps: u16::from_le_bytes(hop[16..18].try_into().unwrap()),
+        })
+        .collect();
+
+    let estimate =
+        estimate_route_out(&hops, amount_in).map_err(|_| ProgramError::InvalidArgument)?;
+    if estimate.amount_out < min_out {
+        msg!(
+            "Quote: {} out is below the minimum of {}",
+            estimate.amount_out,
+            min_out
+        );
+        return Err(ProgramError::Custom(SLIPPAGE_EXCEEDED));
+    }
+    msg!(
+        "Quote: {} in, {} out over {} hops (up to {} lost to rounding)",
+        amount_in,
+        estimate.amount_out,
+        hops.len(),
+        estimate.error_bound
+    );
+    let mut return_data = estimate.amount_out.to_le_bytes().to_vec();
+    return_data.extend_from_slice(&estimate.error_bound.to_le_bytes());
+    set_return_data(&return_data);
+
+    Ok(())
+}
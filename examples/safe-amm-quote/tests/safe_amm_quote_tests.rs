@@ -0,0 +1,119 @@
+use safe_amm_quote::*;
+use solana_floats::amm::{PoolState, RouteEstimate, estimate_route_out, swap_output};
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+#[cfg(test)]
+mod safe_amm_quote_tests {
+    use super::*;
+
+    const SOL_USDC: PoolState = PoolState {
+        reserve_in: 1_000_000_000_000,
+        reserve_out: 150_000_000_000,
+        fee_bps: 30,
+    };
+    const USDC_BONK: PoolState = PoolState {
+        reserve_in: 2_000_000_000_000,
+        reserve_out: 90_000_000_000_000_000,
+        fee_bps: 25,
+    };
+
+    async fn quote(instruction_data: Vec<u8>) -> Result<RouteEstimate, TransactionError> {
+        let program_id = Pubkey::new_unique();
+        let program_test = ProgramTest::new(
+            "safe_amm_quote",
+            program_id,
+            processor!(process_instruction),
+        );
+        let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        let instruction = Instruction::new_with_bytes(program_id, &instruction_data, vec![]);
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        let result = banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        result.result?;
+        Ok(decode_estimate(&result.metadata.unwrap().return_data.unwrap().data).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_single_hop_matches_swap_output() {
+        let estimate = quote(encode_quote(2_000_000_000, 0, &[SOL_USDC]))
+            .await
+            .unwrap();
+        assert_eq!(
+            estimate.amount_out,
+            swap_output(&SOL_USDC, 2_000_000_000).unwrap()
+        );
+        assert_eq!(estimate.amount_out, 298_504_781);
+    }
+
+    #[tokio::test]
+    async fn test_route_matches_library_estimate() {
+        let hops = [SOL_USDC, USDC_BONK];
+        let estimate = quote(encode_quote(5_000_000_000, 1, &hops)).await.unwrap();
+        assert_eq!(estimate, estimate_route_out(&hops, 5_000_000_000).unwrap());
+        assert!(estimate.error_bound > 0);
+    }
+
+    #[tokio::test]
+    async fn test_quote_below_minimum_fails() {
+        let expected = swap_output(&SOL_USDC, 2_000_000_000).unwrap();
+        assert!(
+            quote(encode_quote(2_000_000_000, expected, &[SOL_USDC]))
+                .await
+                .is_ok()
+        );
+        assert_eq!(
+            quote(encode_quote(2_000_000_000, expected + 1, &[SOL_USDC])).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(SLIPPAGE_EXCEEDED)
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_quotes_are_rejected() {
+        let mut truncated = encode_quote(1_000, 0, &[SOL_USDC]);
+        truncated.pop();
+        assert_eq!(
+            quote(truncated).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+        // No hops, and a fee above 100%
+        for data in [
+            encode_quote(1_000, 0, &[]),
+            encode_quote(
+                1_000,
+                0,
+                &[PoolState {
+                    fee_bps: 10_001,
+                    ..SOL_USDC
+                }],
+            ),
+        ] {
+            assert_eq!(
+                quote(data).await,
+                Err(TransactionError::InstructionError(
+                    0,
+                    InstructionError::InvalidArgument
+                ))
+            );
+        }
+    }
+}
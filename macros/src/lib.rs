@@ -0,0 +1,282 @@
+//! Compile-time checks companion to `solana-floats`.
+//!
+//! `#[deny_float_eq]` rejects `==` and `!=` where either side is recognisably a
+//! float, since exact float equality is almost always a logic error in
+//! financial code (`0.1 + 0.2 != 0.3`). Compare with a tolerance, with
+//! `total_cmp`, or on `to_bits()` when exact bit equality is really intended.
+//!
+//! A proc macro sees tokens, not types, so an operand counts as a float when it
+//! is:
+//!
+//! - a float literal (`0.3`, `1e-9`, `2f32`), possibly negated;
+//! - a cast `as f32` / `as f64`;
+//! - a path through `f32::` / `f64::` (`f64::EPSILON`, `f64::from(x)`);
+//! - a binding declared in the annotated item as `f32`/`f64` (or a reference
+//!   to one), or initialised from one of the above;
+//! - arithmetic, parentheses, blocks or `if`/`match` over any of these.
+//!
+//! Float-typed struct fields and function results are not visible to the
+//! macro and pass unchecked.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use std::collections::HashSet;
+use syn::visit::{self, Visit};
+use syn::{BinOp, Expr, Item, Lit, Pat, Type};
+
+/// Rejects `==`/`!=` between float-typed expressions in the annotated
+/// function, impl block or inline module.
+#[proc_macro_attribute]
+pub fn deny_float_eq(attr: TokenStream, item: TokenStream) -> TokenStream {
+    expand(attr.into(), item.into()).into()
+}
+
+fn expand(attr: TokenStream2, item: TokenStream2) -> TokenStream2 {
+    if !attr.is_empty() {
+        return syn::Error::new_spanned(attr, "#[deny_float_eq] takes no arguments")
+            .to_compile_error();
+    }
+    let parsed: Item = match syn::parse2(item.clone()) {
+        Ok(parsed) => parsed,
+        Err(error) => return error.to_compile_error(),
+    };
+
+    let mut bindings = FloatBindings::default();
+    bindings.visit_item(&parsed);
+    let mut checker = EqualityChecker {
+        floats: bindings.names,
+        errors: Vec::new(),
+    };
+    checker.visit_item(&parsed);
+
+    let mut output = item;
+    for error in checker.errors {
+        output.extend(error.to_compile_error());
+    }
+    output
+}
+
+fn is_float_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path.qself.is_none() && is_float_path(&path.path),
+        Type::Reference(reference) => is_float_type(&reference.elem),
+        Type::Paren(paren) => is_float_type(&paren.elem),
+        Type::Group(group) => is_float_type(&group.elem),
+        _ => false,
+    }
+}
+
+fn is_float_path(path: &syn::Path) -> bool {
+    path.segments
+        .first()
+        .is_some_and(|segment| segment.ident == "f32" || segment.ident == "f64")
+}
+
+// Collects every binding in the item that is declared or initialised as a
+// float. Scopes are ignored, so a shadowing non-float binding of the same name
+// is still treated as a float.
+#[derive(Default)]
+struct FloatBindings {
+    names: HashSet<String>,
+}
+
+impl FloatBindings {
+    fn bind(&mut self, pat: &Pat) {
+        match pat {
+            Pat::Ident(ident) => {
+                self.names.insert(ident.ident.to_string());
+            }
+            Pat::Type(typed) => self.bind(&typed.pat),
+            Pat::Reference(reference) => self.bind(&reference.pat),
+            _ => {}
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for FloatBindings {
+    fn visit_pat_type(&mut self, node: &'ast syn::PatType) {
+        if is_float_type(&node.ty) {
+            self.bind(&node.pat);
+        }
+        visit::visit_pat_type(self, node);
+    }
+
+    fn visit_local(&mut self, node: &'ast syn::Local) {
+        let init = node.init.as_ref();
+        if init.is_some_and(|init| is_float_expr(&init.expr, &self.names)) {
+            self.bind(&node.pat);
+        }
+        visit::visit_local(self, node);
+    }
+}
+
+fn is_float_expr(expr: &Expr, floats: &HashSet<String>) -> bool {
+    match expr {
+        Expr::Lit(lit) => matches!(lit.lit, Lit::Float(_)),
+        Expr::Cast(cast) => is_float_type(&cast.ty),
+        Expr::Path(path) => {
+            is_float_path(&path.path)
+                || path
+                    .path
+                    .get_ident()
+                    .is_some_and(|ident| floats.contains(&ident.to_string()))
+        }
+        Expr::Call(call) => match &*call.func {
+            Expr::Path(path) => is_float_path(&path.path),
+            _ => false,
+        },
+        Expr::Unary(unary) => is_float_expr(&unary.expr, floats),
+        Expr::Paren(paren) => is_float_expr(&paren.expr, floats),
+        Expr::Group(group) => is_float_expr(&group.expr, floats),
+        Expr::Reference(reference) => is_float_expr(&reference.expr, floats),
+        Expr::Binary(binary) => {
+            is_arithmetic(&binary.op)
+                && (is_float_expr(&binary.left, floats) || is_float_expr(&binary.right, floats))
+        }
+        Expr::Block(block) => block_is_float(&block.block, floats),
+        Expr::If(expr_if) => {
+            block_is_float(&expr_if.then_branch, floats)
+                || expr_if
+                    .else_branch
+                    .as_ref()
+                    .is_some_and(|(_, branch)| is_float_expr(branch, floats))
+        }
+        Expr::Match(expr_match) => expr_match
+            .arms
+            .iter()
+            .any(|arm| is_float_expr(&arm.body, floats)),
+        _ => false,
+    }
+}
+
+fn block_is_float(block: &syn::Block, floats: &HashSet<String>) -> bool {
+    match block.stmts.last() {
+        Some(syn::Stmt::Expr(expr, None)) => is_float_expr(expr, floats),
+        _ => false,
+    }
+}
+
+fn is_arithmetic(op: &BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::Add(_) | BinOp::Sub(_) | BinOp::Mul(_) | BinOp::Div(_) | BinOp::Rem(_)
+    )
+}
+
+struct EqualityChecker {
+    floats: HashSet<String>,
+    errors: Vec<syn::Error>,
+}
+
+impl<'ast> Visit<'ast> for EqualityChecker {
+    fn visit_expr_binary(&mut self, node: &'ast syn::ExprBinary) {
+        let operator = match node.op {
+            BinOp::Eq(_) => Some("=="),
+            BinOp::Ne(_) => Some("!="),
+            _ => None,
+        };
+        let operator = operator.filter(|_| {
+            is_float_expr(&node.left, &self.floats) || is_float_expr(&node.right, &self.floats)
+        });
+        if let Some(operator) = operator {
+            self.errors.push(syn::Error::new_spanned(
+                node.to_token_stream(),
+                format!(
+                    "float comparison with `{}`; compare within a tolerance, with \
+                     `total_cmp`, or on `to_bits()` instead",
+                    operator
+                ),
+            ));
+        }
+        visit::visit_expr_binary(self, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    fn error_count(item: TokenStream2) -> usize {
+        expand(TokenStream2::new(), item)
+            .to_string()
+            .matches("compile_error")
+            .count()
+    }
+
+    #[test]
+    fn test_float_literals_are_rejected() {
+        assert_eq!(
+            error_count(quote! { fn f(a: u64) -> bool { a as f64 == 0.3 } }),
+            1
+        );
+        assert_eq!(
+            error_count(quote! { fn f(x: u8) -> bool { 1e-9 != -2f32 } }),
+            1
+        );
+    }
+
+    #[test]
+    fn test_float_bindings_are_tracked() {
+        assert_eq!(
+            error_count(quote! { fn f(a: f64, b: &f32) -> bool { a == *b } }),
+            1
+        );
+        assert_eq!(
+            error_count(quote! {
+                fn f(n: u64) -> bool {
+                    let price = n as f64 / 100.0;
+                    let total: f32 = 0.0;
+                    let scaled = price * 2;
+                    scaled == 3 || total != 1
+                }
+            }),
+            2
+        );
+    }
+
+    #[test]
+    fn test_float_constants_and_constructors_are_rejected() {
+        assert_eq!(
+            error_count(quote! { fn f(x: u32) -> bool { f64::from(x) == f64::EPSILON } }),
+            1
+        );
+    }
+
+    #[test]
+    fn test_non_float_comparisons_pass() {
+        assert_eq!(
+            error_count(quote! {
+                fn f(a: u64, b: f64) -> bool {
+                    let bits = b.to_bits();
+                    a == 3 && bits != 0 && b.total_cmp(&1.0).is_eq() && b < 2.0
+                }
+            }),
+            0
+        );
+    }
+
+    #[test]
+    fn test_impl_blocks_and_modules_are_checked() {
+        assert_eq!(
+            error_count(quote! {
+                impl Pool {
+                    fn a(&self, x: f64) -> bool { x == 1.0 }
+                    fn b(&self, y: f32) -> bool { y != 0.0 }
+                }
+            }),
+            2
+        );
+        assert_eq!(
+            error_count(quote! { mod m { fn f(x: f64) -> bool { x == 0.0 } } }),
+            1
+        );
+    }
+
+    #[test]
+    fn test_arguments_are_rejected() {
+        let output = expand(quote! { strict }, quote! { fn f() {} });
+        assert!(output.to_string().contains("takes no arguments"));
+    }
+}
@@ -0,0 +1,9 @@
+//! First data byte of each kind of program-owned account whose contents the
+//! program trusts.
+//!
+//! Every such account is a PDA checked against its seeds, and the tag is
+//! checked again before its data is read or written, so an instruction that
+//! takes one kind of account can never be pointed at another.
+
+pub const CHAIN_CACHE: u8 = 1;
+pub const RESULTS_LOG: u8 = 2;
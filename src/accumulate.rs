@@ -0,0 +1,106 @@
+//! Running sums with bounded rounding error.
+//!
+//! Adding 0.1 a thousand times in f64 ends at 99.9999999999986: each addition
+//! rounds, and the errors pile up in proportion to the number of terms. A
+//! [`CompensatedSum`] carries the low-order part every addition drops in a
+//! second f64 and feeds it back in, so the error stays within a couple of
+//! roundings of the exact sum however long the loop runs. The arithmetic is
+//! plain IEEE 754 addition in a fixed order, so the result is as
+//! deterministic as the naive loop.
+
+use crate::error::FloatError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compensation {
+    /// Kahan's original scheme. Exact for terms no larger than the running
+    /// sum, but loses the correction when a term is much larger than it.
+    Kahan,
+    /// Neumaier's variant, which also handles terms larger than the running
+    /// sum (e.g. `[1.0, 1e100, 1.0, -1e100]` sums to 2, where Kahan gives 0).
+    #[default]
+    Neumaier,
+}
+
+/// A compensated running sum of finite values.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CompensatedSum {
+    method: Compensation,
+    sum: f64,
+    compensation: f64,
+}
+
+impl CompensatedSum {
+    pub fn new(method: Compensation) -> Self {
+        Self {
+            method,
+            sum: 0.0,
+            compensation: 0.0,
+        }
+    }
+
+    pub fn kahan() -> Self {
+        Self::new(Compensation::Kahan)
+    }
+
+    pub fn neumaier() -> Self {
+        Self::new(Compensation::Neumaier)
+    }
+
+    pub fn method(&self) -> Compensation {
+        self.method
+    }
+
+    /// Adds a value. Rejects NaN with `NaNOperand` and infinities with
+    /// `OutOfRange`; fails with `Overflow`, leaving the sum unchanged, if it
+    /// would no longer be finite.
+    pub fn push(&mut self, value: f64) -> Result<(), FloatError> {
+        if value.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if value.is_infinite() {
+            return Err(FloatError::OutOfRange);
+        }
+        let (sum, compensation) = match self.method {
+            Compensation::Kahan => kahan_add(self.sum, self.compensation, value),
+            Compensation::Neumaier => neumaier_add(self.sum, self.compensation, value),
+        };
+        if !sum.is_finite() || !compensation.is_finite() {
+            return Err(FloatError::Overflow);
+        }
+        self.sum = sum;
+        self.compensation = compensation;
+        Ok(())
+    }
+
+    /// Pushes every value, stopping at the first error.
+    pub fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) -> Result<(), FloatError> {
+        values.into_iter().try_for_each(|value| self.push(value))
+    }
+
+    /// The compensated total.
+    pub fn value(&self) -> f64 {
+        match self.method {
+            // Kahan's compensation is the error of the last step, already
+            // subtracted from the next term rather than added at the end
+            Compensation::Kahan => self.sum,
+            Compensation::Neumaier => self.sum + self.compensation,
+        }
+    }
+}
+
+// Here the compensation is the amount the sum overshot by in the last step
+fn kahan_add(sum: f64, compensation: f64, value: f64) -> (f64, f64) {
+    let corrected = value - compensation;
+    let total = sum + corrected;
+    (total, (total - sum) - corrected)
+}
+
+pub(crate) fn neumaier_add(sum: f64, compensation: f64, value: f64) -> (f64, f64) {
+    let total = sum + value;
+    let correction = if sum.abs() >= value.abs() {
+        (sum - total) + value
+    } else {
+        (value - total) + sum
+    };
+    (total, compensation + correction)
+}
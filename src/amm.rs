@@ -0,0 +1,84 @@
+//! Constant-product swap math in integer token units.
+//!
+//! Outputs are rounded down at every step, as the pools themselves do, so an
+//! estimate never promises more than a pool would pay. Each hop of a route also
+//! reports how far below the exact (real-valued) result the rounding may have
+//! pushed it, so routers can tell a quantization shortfall from slippage.
+
+use crate::bps::BPS_DENOMINATOR;
+use crate::error::FloatError;
+use crate::mul_div::mul_div_u128;
+use crate::quantize::RoundingMode;
+
+/// One constant-product pool, oriented in the direction of the swap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolState {
+    pub reserve_in: u64,
+    pub reserve_out: u64,
+    /// Fee taken from the input, in basis points.
+    pub fee_bps: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteEstimate {
+    pub amount_out: u64,
+    /// The exact output lies in `amount_out..amount_out + error_bound`.
+    pub error_bound: u64,
+}
+
+/// Output of swapping `amount_in` through `pool`, rounded down.
+///
+/// The fee is deducted from the input (rounding the remainder down) before the
+/// constant-product formula `reserve_out * x / (reserve_in + x)` is applied.
+pub fn swap_output(pool: &PoolState, amount_in: u64) -> Result<u64, FloatError> {
+    if pool.reserve_in == 0 || pool.reserve_out == 0 || pool.fee_bps as u64 > BPS_DENOMINATOR {
+        return Err(FloatError::OutOfRange);
+    }
+    let after_fee = mul_div_u128(
+        amount_in as u128,
+        (BPS_DENOMINATOR - pool.fee_bps as u64) as u128,
+        BPS_DENOMINATOR as u128,
+        RoundingMode::Floor,
+    )?;
+    let amount_out = mul_div_u128(
+        pool.reserve_out as u128,
+        after_fee,
+        pool.reserve_in as u128 + after_fee,
+        RoundingMode::Floor,
+    )?;
+    // Always below reserve_out, so it fits
+    Ok(amount_out as u64)
+}
+
+/// Swaps `amount_in` through each pool in turn, feeding each output into the
+/// next hop.
+///
+/// The exact output of a hop moves by at most `reserve_out / reserve_in` per
+/// unit of input, so a hop turns an inherited shortfall `e` into at most
+/// `(e + 1) * reserve_out / reserve_in + 1`: the `+ 1` inside covers the
+/// rounded fee and the one outside the rounded output. Fails with `OutOfRange`
+/// for an empty route or an invalid pool.
+pub fn estimate_route_out(hops: &[PoolState], amount_in: u64) -> Result<RouteEstimate, FloatError> {
+    if hops.is_empty() {
+        return Err(FloatError::OutOfRange);
+    }
+    let mut estimate = RouteEstimate {
+        amount_out: amount_in,
+        error_bound: 0,
+    };
+    for pool in hops {
+        let amount_out = swap_output(pool, estimate.amount_out)?;
+        let propagated = mul_div_u128(
+            estimate.error_bound as u128 + 1,
+            pool.reserve_out as u128,
+            pool.reserve_in as u128,
+            RoundingMode::Ceil,
+        )?;
+        let error_bound = u64::try_from(propagated + 1).map_err(|_| FloatError::Overflow)?;
+        estimate = RouteEstimate {
+            amount_out,
+            error_bound,
+        };
+    }
+    Ok(estimate)
+}
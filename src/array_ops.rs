@@ -0,0 +1,413 @@
+//! Reductions over f64 slices with a fixed left-to-right evaluation order.
+//!
+//! NaN policy: `sum_doubles`, `sum_doubles_compensated` and `product_doubles`
+//! propagate NaN exactly like the scalar operators. `min_total` and `max_total`
+//! order values with `f64::total_cmp`, so `-NaN < -inf < ... < -0.0 < +0.0 < ... < +inf < +NaN`
+//! and a NaN in the input is returned rather than silently skipped.
+//!
+//! Sorting follows the same split: the `_total` variants accept anything and use
+//! total ordering, the `_finite` variants reject NaN and infinities up front.
+//!
+//! Rust never reassociates float arithmetic, so vectorized and scalar code
+//! evaluate these loops in the same order and round identically. The
+//! `scalar-audit` feature checks that claim: every element read goes through
+//! [`scalar`], which hides it from the optimizer and so rules out
+//! vectorization, and `tests/scalar_determinism_tests.rs` must produce the same
+//! digest with and without the feature.
+
+use crate::accumulate::neumaier_add;
+use crate::error::FloatError;
+use crate::quantize::pow10;
+use crate::transcendental::{exp_deterministic, ln_deterministic};
+
+/// Passes `value` through unchanged; with the `scalar-audit` feature it is
+/// also hidden from the optimizer, so loops using it stay scalar.
+#[inline(always)]
+pub(crate) fn scalar(value: f64) -> f64 {
+    #[cfg(feature = "scalar-audit")]
+    {
+        std::hint::black_box(value)
+    }
+    #[cfg(not(feature = "scalar-audit"))]
+    {
+        value
+    }
+}
+
+/// Sums `values` left to right.
+pub fn sum_doubles(values: &[f64]) -> f64 {
+    values.iter().fold(0.0, |acc, &value| acc + scalar(value))
+}
+
+/// Sums `values` left to right with Neumaier compensation.
+///
+/// The result is within one rounding of the exact sum for well-conditioned inputs,
+/// instead of accumulating an error proportional to the slice length.
+pub fn sum_doubles_compensated(values: &[f64]) -> f64 {
+    let (sum, compensation) = values
+        .iter()
+        .fold((0.0, 0.0), |(sum, compensation), &value| {
+            neumaier_add(sum, compensation, scalar(value))
+        });
+    compensated_total(sum, compensation)
+}
+
+/// Prefix sums of `values` (`out[i] == sum(values[..=i])`), each carried with
+/// Neumaier compensation so later entries do not inherit the rounding error of
+/// every addition before them.
+pub fn cumsum(values: &[f64]) -> Vec<f64> {
+    cumsum_compensated(values)
+        .into_iter()
+        .map(|(sum, compensation)| compensated_total(sum, compensation))
+        .collect()
+}
+
+/// Prefix sums of `values` as unrounded `(sum, compensation)` pairs, for
+/// `range_sum` queries.
+pub fn cumsum_compensated(values: &[f64]) -> Vec<(f64, f64)> {
+    let mut sum = 0.0_f64;
+    let mut compensation = 0.0_f64;
+    values
+        .iter()
+        .map(|&value| {
+            (sum, compensation) = neumaier_add(sum, compensation, scalar(value));
+            (sum, compensation)
+        })
+        .collect()
+}
+
+/// Sum of `values[start..end]` from the output of `cumsum_compensated`, in
+/// constant time.
+///
+/// Differencing rounded `cumsum` entries would cancel against the rounding error
+/// of the whole prefix, which can exceed the range sum itself. The running sums
+/// are differenced exactly instead and their compensation terms applied after.
+pub fn range_sum(prefix: &[(f64, f64)], start: usize, end: usize) -> Result<f64, FloatError> {
+    if start > end || end > prefix.len() {
+        return Err(FloatError::OutOfRange);
+    }
+    if start == end {
+        return Ok(0.0);
+    }
+    let (upper_sum, upper_compensation) = prefix[end - 1];
+    let (lower_sum, lower_compensation) = if start == 0 {
+        (0.0, 0.0)
+    } else {
+        prefix[start - 1]
+    };
+    let (difference, error) = two_sum(upper_sum, -lower_sum);
+    Ok(compensated_total(
+        difference,
+        error + (upper_compensation - lower_compensation),
+    ))
+}
+
+// Once the running sum overflows or hits NaN the compensation term is meaningless
+fn compensated_total(sum: f64, compensation: f64) -> f64 {
+    if sum.is_finite() {
+        sum + compensation
+    } else {
+        sum
+    }
+}
+
+/// Multiplies `values` left to right.
+pub fn product_doubles(values: &[f64]) -> f64 {
+    values.iter().fold(1.0, |acc, &value| acc * scalar(value))
+}
+
+/// Smallest value under `f64::total_cmp`, or `None` for an empty slice.
+pub fn min_total(values: &[f64]) -> Option<f64> {
+    values.iter().copied().min_by(f64::total_cmp)
+}
+
+/// Largest value under `f64::total_cmp`, or `None` for an empty slice.
+pub fn max_total(values: &[f64]) -> Option<f64> {
+    values.iter().copied().max_by(f64::total_cmp)
+}
+
+/// Sorts `values` ascending under `f64::total_cmp`.
+///
+/// Values that compare equal under total ordering are bit-identical, so the
+/// result does not depend on the sort being unstable.
+pub fn sort_total(values: &mut [f64]) {
+    values.sort_unstable_by(f64::total_cmp);
+}
+
+/// Sorts `values` ascending, failing without modifying the slice if any value is
+/// NaN (`NaNOperand`) or infinite (`OutOfRange`).
+pub fn sort_finite(values: &mut [f64]) -> Result<(), FloatError> {
+    check_finite(values)?;
+    sort_total(values);
+    Ok(())
+}
+
+/// Indices that would sort `values` under `f64::total_cmp`; ties keep input order.
+pub fn argsort_total(values: &[f64]) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..values.len()).collect();
+    indices.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    indices
+}
+
+/// Like `argsort_total`, but rejects NaN and infinities like `sort_finite`.
+pub fn argsort_finite(values: &[f64]) -> Result<Vec<usize>, FloatError> {
+    check_finite(values)?;
+    Ok(argsort_total(values))
+}
+
+/// Scales non-negative `weights` so they sum to one.
+///
+/// Weights are first divided by the largest one, so huge weights cannot overflow
+/// the intermediate sum. Fails on empty input, negative or non-finite weights, and
+/// with `DivisionByZero` when every weight is zero.
+pub fn normalize(weights: &[f64]) -> Result<Vec<f64>, FloatError> {
+    check_weights(weights)?;
+    let largest = max_total(weights).unwrap_or(0.0);
+    if largest == 0.0 {
+        return Err(FloatError::DivisionByZero);
+    }
+    let scaled: Vec<f64> = weights
+        .iter()
+        .map(|&weight| scalar(weight) / largest)
+        .collect();
+    let total = sum_doubles_compensated(&scaled);
+    Ok(scaled
+        .iter()
+        .map(|&weight| scalar(weight) / total)
+        .collect())
+}
+
+/// Softmax of `scores`, shifting by the maximum before exponentiating so large
+/// scores cannot overflow `exp`.
+pub fn softmax(scores: &[f64]) -> Result<Vec<f64>, FloatError> {
+    let (_, exps) = shifted_exps(scores)?;
+    // The largest score contributes exp(0) = 1, so the total is never zero
+    let total = sum_doubles_compensated(&exps);
+    Ok(exps.iter().map(|&value| scalar(value) / total).collect())
+}
+
+/// `ln(exp(x_1) + ... + exp(x_n))`, computed as `max + ln(sum(exp(x_i - max)))`
+/// so that neither huge nor very negative values overflow or vanish.
+///
+/// Uses the deterministic `exp` and `ln`, so the result is the same on every
+/// target. Fails on empty input and non-finite values.
+pub fn log_sum_exp(values: &[f64]) -> Result<f64, FloatError> {
+    let (largest, exps) = shifted_exps(values)?;
+    Ok(largest + ln_deterministic(sum_doubles_compensated(&exps)))
+}
+
+// The maximum and exp(x - maximum) of every value.
+fn shifted_exps(values: &[f64]) -> Result<(f64, Vec<f64>), FloatError> {
+    if values.is_empty() {
+        return Err(FloatError::OutOfRange);
+    }
+    check_finite(values)?;
+    let largest = max_total(values).unwrap_or(0.0);
+    let exps = values
+        .iter()
+        .map(|&value| exp_deterministic(scalar(value) - largest))
+        .collect();
+    Ok((largest, exps))
+}
+
+/// Normalizes `weights` into integer units of `10^-decimals` that sum to exactly
+/// `10^decimals`.
+///
+/// Each weight is floored to whole units and the leftover units go to the largest
+/// remainders (ties to the lower index), so the allocation never depends on how the
+/// individual float quotients happened to round.
+pub fn quantize_weights(weights: &[f64], decimals: u32) -> Result<Vec<u64>, FloatError> {
+    let total = pow10(decimals)?;
+    let normalized = normalize(weights)?;
+    Ok(largest_remainder(&normalized, total))
+}
+
+/// Splits `total` base units in proportion to `weights`, e.g. an epoch's
+/// rewards across stakers, so that the allocations sum to exactly `total`.
+///
+/// Uses the same largest-remainder apportionment as [`quantize_weights`], and
+/// fails on the same weights as [`normalize`].
+pub fn distribute(total: u64, weights: &[f64]) -> Result<Vec<u64>, FloatError> {
+    let normalized = normalize(weights)?;
+    Ok(largest_remainder(&normalized, total))
+}
+
+// Apportions `total` units according to `fractions` (which sum to ~1).
+pub(crate) fn largest_remainder(fractions: &[f64], total: u64) -> Vec<u64> {
+    if fractions.is_empty() {
+        return Vec::new();
+    }
+    let scaled: Vec<f64> = fractions
+        .iter()
+        .map(|&fraction| scalar(fraction) * total as f64)
+        .collect();
+    let mut units: Vec<u64> = scaled.iter().map(|&value| value.floor() as u64).collect();
+    let remainders: Vec<f64> = scaled
+        .iter()
+        .zip(&units)
+        .map(|(&value, &floor)| value - floor as f64)
+        .collect();
+
+    // Largest remainders first; the stable sort keeps ties in index order
+    let mut order: Vec<usize> = (0..remainders.len()).collect();
+    order.sort_by(|&a, &b| remainders[b].total_cmp(&remainders[a]));
+
+    let allocated: u128 = units.iter().map(|&unit| unit as u128).sum();
+    let total = total as u128;
+    if allocated < total {
+        let mut missing = total - allocated;
+        for &index in order.iter().cycle() {
+            if missing == 0 {
+                break;
+            }
+            units[index] += 1;
+            missing -= 1;
+        }
+    } else {
+        // Float rounding can overshoot by a few units; take them back from the
+        // smallest remainders
+        let mut excess = allocated - total;
+        for &index in order.iter().rev().cycle() {
+            if excess == 0 {
+                break;
+            }
+            if units[index] > 0 {
+                units[index] -= 1;
+                excess -= 1;
+            }
+        }
+    }
+    units
+}
+
+fn check_weights(weights: &[f64]) -> Result<(), FloatError> {
+    if weights.is_empty() {
+        return Err(FloatError::OutOfRange);
+    }
+    check_finite(weights)?;
+    if weights.iter().any(|&weight| weight < 0.0) {
+        return Err(FloatError::OutOfRange);
+    }
+    Ok(())
+}
+
+/// Dot product of two equal-length slices, accumulated left to right.
+pub fn dot(xs: &[f64], ys: &[f64]) -> Result<f64, FloatError> {
+    check_same_len(xs, ys)?;
+    Ok(xs
+        .iter()
+        .zip(ys)
+        .fold(0.0, |acc, (&x, &y)| acc + scalar(x) * scalar(y)))
+}
+
+/// Dot product computed as if in twice the working precision (Ogita-Rump-Oishi Dot2).
+///
+/// Products are split exactly with Dekker's algorithm rather than FMA, so the
+/// result does not depend on whether the target has a fused multiply-add.
+pub fn dot_compensated(xs: &[f64], ys: &[f64]) -> Result<f64, FloatError> {
+    check_same_len(xs, ys)?;
+    let mut sum = 0.0_f64;
+    let mut compensation = 0.0_f64;
+    for (&x, &y) in xs.iter().zip(ys) {
+        let (product, product_error) = two_product(scalar(x), scalar(y));
+        let (total, sum_error) = two_sum(sum, product);
+        sum = total;
+        compensation += sum_error + product_error;
+    }
+    if sum.is_finite() {
+        Ok(sum + compensation)
+    } else {
+        Ok(sum)
+    }
+}
+
+/// Euclidean norm, scaling as it goes so squares cannot overflow or underflow.
+pub fn norm2(xs: &[f64]) -> f64 {
+    let mut scale = 0.0_f64;
+    let mut sum_of_squares = 1.0_f64;
+    for &x in xs {
+        let x = scalar(x);
+        if x.is_nan() {
+            return f64::NAN;
+        }
+        if x == 0.0 {
+            continue;
+        }
+        let magnitude = x.abs();
+        if scale < magnitude {
+            let ratio = scale / magnitude;
+            sum_of_squares = 1.0 + sum_of_squares * ratio * ratio;
+            scale = magnitude;
+        } else {
+            let ratio = magnitude / scale;
+            sum_of_squares += ratio * ratio;
+        }
+    }
+    if scale.is_infinite() {
+        return scale;
+    }
+    scale * sum_of_squares.sqrt()
+}
+
+/// Euclidean norm with the sum of squares taken through `dot_compensated`.
+pub fn norm2_compensated(xs: &[f64]) -> f64 {
+    if xs.iter().any(|x| x.is_nan()) {
+        return f64::NAN;
+    }
+    let largest = xs.iter().fold(0.0_f64, |acc, x| acc.max(x.abs()));
+    if largest == 0.0 || largest.is_infinite() {
+        return largest;
+    }
+    let scaled: Vec<f64> = xs.iter().map(|&x| scalar(x) / largest).collect();
+    let sum_of_squares = dot_compensated(&scaled, &scaled).unwrap_or(f64::NAN);
+    largest * sum_of_squares.sqrt()
+}
+
+// Knuth's TwoSum: `a + b == sum + error` exactly.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_virtual = sum - a;
+    let error = (a - (sum - b_virtual)) + (b - b_virtual);
+    (sum, error)
+}
+
+// Dekker's TwoProduct: `a * b == product + error` exactly (barring overflow).
+pub(crate) fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let product = a * b;
+    let (a_high, a_low) = split(a);
+    let (b_high, b_low) = split(b);
+    let error = a_low * b_low - (((product - a_high * b_high) - a_low * b_high) - a_high * b_low);
+    // `split` overflows above about 1.3e300, leaving a NaN error term for a finite
+    // product; the product is then returned uncompensated
+    if product.is_finite() && !error.is_finite() {
+        return (product, 0.0);
+    }
+    (product, error)
+}
+
+// Splits a double into two halves with at most 26 significant bits each.
+fn split(a: f64) -> (f64, f64) {
+    const SPLITTER: f64 = 134_217_729.0; // 2^27 + 1
+    let c = SPLITTER * a;
+    let high = c - (c - a);
+    (high, a - high)
+}
+
+fn check_same_len(xs: &[f64], ys: &[f64]) -> Result<(), FloatError> {
+    if xs.len() != ys.len() {
+        return Err(FloatError::OutOfRange);
+    }
+    Ok(())
+}
+
+pub(crate) fn check_finite(values: &[f64]) -> Result<(), FloatError> {
+    for value in values {
+        if value.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if value.is_infinite() {
+            return Err(FloatError::OutOfRange);
+        }
+    }
+    Ok(())
+}
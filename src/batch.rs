@@ -0,0 +1,108 @@
+//! Return-data encoding for batch instructions, plus client-side chunking.
+//!
+//! Batch results are returned as raw f64 bit patterns so callers receive exactly
+//! what the program computed:
+//!
+//! `[truncated: u8][total: u32 LE][result bits: u64 LE]*`
+//!
+//! Return data is capped at `MAX_RETURN_DATA` bytes, which leaves room for
+//! `MAX_BATCH_RESULTS` values. Larger batches return the first values with the
+//! truncation flag set and `total` holding the full count; clients should split
+//! work with the chunking helpers so that never happens.
+
+use solana_program::program::MAX_RETURN_DATA;
+use solana_program::program_error::ProgramError;
+
+use crate::error::FloatError;
+use crate::operands::{AccountOperands, OPERAND_SIZE};
+
+pub const BATCH_RESULT_HEADER_LEN: usize = 5;
+pub const MAX_BATCH_RESULTS: usize = (MAX_RETURN_DATA - BATCH_RESULT_HEADER_LEN) / 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchResults {
+    /// Number of results the instruction computed.
+    pub total: u32,
+    /// The results that fit in return data, in input order.
+    pub values: Vec<f64>,
+    /// Set when `values` holds fewer than `total` results.
+    pub truncated: bool,
+}
+
+impl BatchResults {
+    /// Decodes return data written by a batch instruction.
+    pub fn decode(return_data: &[u8]) -> Result<Self, ProgramError> {
+        if return_data.len() < BATCH_RESULT_HEADER_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (header, body) = return_data.split_at(BATCH_RESULT_HEADER_LEN);
+        let truncated = match header[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let total = u32::from_le_bytes(header[1..5].try_into().unwrap());
+        let chunks = body.chunks_exact(8);
+        if !chunks.remainder().is_empty() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let values: Vec<f64> = chunks
+            .map(|chunk| f64::from_bits(u64::from_le_bytes(chunk.try_into().unwrap())))
+            .collect();
+        if (values.len() < total as usize) != truncated || values.len() > total as usize {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            total,
+            values,
+            truncated,
+        })
+    }
+}
+
+/// Encodes as many of `results` as fit into return data.
+pub fn encode_batch_results(results: &[f64]) -> Vec<u8> {
+    let returned = results.len().min(MAX_BATCH_RESULTS);
+    encode_batch_results_prefix(&results[..returned], results.len())
+}
+
+/// Encodes the first results of a batch of `total`, for instructions that
+/// stream their results and only keep the ones that fit.
+pub fn encode_batch_results_prefix(returned: &[f64], total: usize) -> Vec<u8> {
+    let returned = &returned[..returned.len().min(MAX_BATCH_RESULTS).min(total)];
+    let truncated = returned.len() < total;
+
+    let mut return_data = Vec::with_capacity(BATCH_RESULT_HEADER_LEN + returned.len() * 8);
+    return_data.push(truncated as u8);
+    return_data.extend_from_slice(&(total as u32).to_le_bytes());
+    for result in returned {
+        return_data.extend_from_slice(&result.to_bits().to_le_bytes());
+    }
+    return_data
+}
+
+/// Splits inline batch operands into pieces whose results fit in return data.
+pub fn chunk_values(values: &[f64]) -> std::slice::Chunks<'_, f64> {
+    values.chunks(MAX_BATCH_RESULTS)
+}
+
+/// Splits an account operand range into consecutive ranges whose results fit in
+/// return data.
+///
+/// Fails with `Overflow` if the range extends past `u32::MAX` bytes.
+pub fn chunk_account_operands(
+    operands: AccountOperands,
+) -> Result<Vec<AccountOperands>, FloatError> {
+    let mut chunks = Vec::new();
+    let mut offset = operands.offset;
+    let mut remaining = operands.count;
+    while remaining > 0 {
+        let count = remaining.min(MAX_BATCH_RESULTS as u32);
+        chunks.push(AccountOperands { offset, count });
+        offset = offset
+            .checked_add(count * OPERAND_SIZE as u32)
+            .ok_or(FloatError::Overflow)?;
+        remaining -= count;
+    }
+    Ok(chunks)
+}
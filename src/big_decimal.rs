@@ -0,0 +1,554 @@
+//! Wide decimal arithmetic for intermediate results.
+//!
+//! Summing or multiplying many quantities in f64 (an invariant across many
+//! pools, a product of rates) loses digits at every step. A
+//! [`BigDecimal256`] carries a 256-bit coefficient, so intermediates keep 75
+//! significant digits and are rounded back to a token amount or an f64 only
+//! once, with [`BigDecimal256::to_u64`] or [`BigDecimal256::to_f64`].
+//!
+//! Sums, differences and products are exact while they fit in 75 digits;
+//! beyond that they, like quotients, are rounded half-even to 75 digits. A
+//! u256 holds 77 digits; the other two leave room to align operands so that
+//! each result is rounded only once.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::decimal64::write_scientific;
+use crate::error::FloatError;
+use crate::quantize::RoundingMode;
+use crate::u256::U256;
+
+/// Significant digits a [`BigDecimal256`] keeps.
+pub const MAX_DIGITS: u32 = 75;
+
+// Digits an aligned operand may have during addition
+const WORKING_DIGITS: u32 = 77;
+
+/// `±coefficient * 10^exponent`. Equality and ordering compare values, so
+/// `1.0 == 1.00`.
+#[derive(Clone, Copy)]
+pub struct BigDecimal256 {
+    negative: bool,
+    coefficient: U256,
+    exponent: i32,
+}
+
+impl BigDecimal256 {
+    pub const ZERO: BigDecimal256 = BigDecimal256 {
+        negative: false,
+        coefficient: U256::ZERO,
+        exponent: 0,
+    };
+    pub const ONE: BigDecimal256 = BigDecimal256 {
+        negative: false,
+        coefficient: U256::ONE,
+        exponent: 0,
+    };
+
+    /// Fails with `OutOfRange` if `coefficient` has more than 75 digits.
+    pub fn from_parts(
+        negative: bool,
+        coefficient: U256,
+        exponent: i32,
+    ) -> Result<Self, FloatError> {
+        if coefficient >= pow10(MAX_DIGITS) {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(Self {
+            negative,
+            coefficient,
+            exponent,
+        })
+    }
+
+    /// `raw / 10^decimals`, e.g. a token amount in its smallest units.
+    pub fn from_raw(raw: u64, decimals: u32) -> Result<Self, FloatError> {
+        let exponent = i32::try_from(decimals).map_err(|_| FloatError::OutOfRange)?;
+        Ok(Self {
+            negative: false,
+            coefficient: U256::from(raw),
+            exponent: -exponent,
+        })
+    }
+
+    /// Sign, coefficient and exponent.
+    pub fn parts(&self) -> (bool, U256, i32) {
+        (self.negative, self.coefficient, self.exponent)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficient.is_zero()
+    }
+
+    pub fn is_sign_negative(&self) -> bool {
+        self.negative && !self.is_zero()
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Result<Self, FloatError> {
+        add(self, rhs)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, FloatError> {
+        add(
+            self,
+            Self {
+                negative: !rhs.negative,
+                ..rhs
+            },
+        )
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, FloatError> {
+        let negative = self.negative != rhs.negative;
+        let exponent = self.exponent as i64 + rhs.exponent as i64;
+        // The product has as many digits as the two operands together, or one
+        // fewer; drop the excess over 75 for the first case, and one digit less
+        // if that leaves only 74
+        let mut drop =
+            (digits(self.coefficient) + digits(rhs.coefficient)).saturating_sub(MAX_DIGITS);
+        let mut divisor = pow10(drop);
+        let (mut quotient, mut remainder) =
+            U256::mul_div_rem(self.coefficient, rhs.coefficient, divisor).unwrap();
+        if drop > 0 && quotient < pow10(MAX_DIGITS - 1) {
+            drop -= 1;
+            divisor = pow10(drop);
+            (quotient, remainder) =
+                U256::mul_div_rem(self.coefficient, rhs.coefficient, divisor).unwrap();
+        }
+        let coefficient = round_quotient(
+            quotient,
+            remainder,
+            divisor,
+            negative,
+            RoundingMode::HalfEven,
+        );
+        normalize(negative, coefficient, exponent + drop as i64)
+    }
+
+    /// Rounded half-even to 75 digits unless exact.
+    pub fn checked_div(self, rhs: Self) -> Result<Self, FloatError> {
+        if rhs.is_zero() {
+            return Err(FloatError::DivisionByZero);
+        }
+        let negative = self.negative != rhs.negative;
+        let preferred = self.exponent as i64 - rhs.exponent as i64;
+        if self.is_zero() {
+            return normalize(negative, U256::ZERO, preferred);
+        }
+        // Scale the dividend to 75 digits and then by another 10^(divisor
+        // digits + 1), so the quotient has 76 or 77 digits
+        let widen = MAX_DIGITS - digits(self.coefficient);
+        let dividend = self.coefficient.checked_mul(pow10(widen)).unwrap();
+        let extra = digits(rhs.coefficient) + 1;
+        let (mut quotient, remainder) =
+            U256::mul_div_rem(dividend, pow10(extra), rhs.coefficient).unwrap();
+        let mut exponent = preferred - (widen + extra) as i64;
+        if remainder.is_zero() {
+            // Exact: drop trailing zeros down to the preferred exponent
+            let ten = U256::from(10u64);
+            while exponent < preferred {
+                let (shorter, digit) = quotient.div_rem(ten).unwrap();
+                if !digit.is_zero() {
+                    break;
+                }
+                quotient = shorter;
+                exponent += 1;
+            }
+        } else {
+            quotient = sticky(quotient);
+        }
+        normalize(negative, quotient, exponent)
+    }
+
+    /// The value rounded to `decimals` fractional digits with `rounding`.
+    pub fn round_to_decimals(
+        self,
+        decimals: u32,
+        rounding: RoundingMode,
+    ) -> Result<Self, FloatError> {
+        let target = -(i32::try_from(decimals).map_err(|_| FloatError::OutOfRange)? as i64);
+        if self.exponent as i64 >= target {
+            return Ok(self);
+        }
+        let drop = (target - self.exponent as i64).min(u32::MAX as i64) as u32;
+        let coefficient = shift_right(self.coefficient, drop, self.negative, rounding);
+        normalize(self.negative, coefficient, target)
+    }
+
+    /// `self * 10^decimals` rounded with `rounding`, e.g. back to a token
+    /// amount in its smallest units.
+    ///
+    /// Fails with `OutOfRange` for negative results and `Overflow` if the
+    /// result does not fit in a u64.
+    pub fn to_u64(self, decimals: u32, rounding: RoundingMode) -> Result<u64, FloatError> {
+        let exponent = self.exponent as i64 + decimals as i64;
+        let magnitude = if self.is_zero() {
+            U256::ZERO
+        } else if exponent >= 0 {
+            u32::try_from(exponent)
+                .ok()
+                .and_then(U256::pow10)
+                .and_then(|scale| self.coefficient.checked_mul(scale))
+                .unwrap_or(U256::MAX)
+        } else {
+            let drop = (-exponent).min(u32::MAX as i64) as u32;
+            shift_right(self.coefficient, drop, self.negative, rounding)
+        };
+        if self.negative && !magnitude.is_zero() {
+            return Err(FloatError::OutOfRange);
+        }
+        magnitude
+            .try_to_u128()
+            .and_then(|magnitude| u64::try_from(magnitude).ok())
+            .ok_or(FloatError::Overflow)
+    }
+
+    /// The nearest f64. Fails with `Overflow` beyond `f64::MAX`.
+    pub fn to_f64(self) -> Result<f64, FloatError> {
+        let sign = if self.negative { "-" } else { "" };
+        // Float parsing is correctly rounded for any number of digits
+        let value: f64 = format!("{}{}e{}", sign, self.coefficient, self.exponent)
+            .parse()
+            .unwrap();
+        if value.is_infinite() {
+            return Err(FloatError::Overflow);
+        }
+        Ok(value)
+    }
+}
+
+fn add(lhs: BigDecimal256, rhs: BigDecimal256) -> Result<BigDecimal256, FloatError> {
+    // Adding a zero with a larger exponent changes nothing
+    if rhs.is_zero() && !lhs.is_zero() && lhs.exponent <= rhs.exponent {
+        return Ok(lhs);
+    }
+    if lhs.is_zero() && !rhs.is_zero() && rhs.exponent <= lhs.exponent {
+        return Ok(rhs);
+    }
+    // Order so that `high` has the larger exponent
+    let (high, low) = if lhs.exponent >= rhs.exponent {
+        (lhs, rhs)
+    } else {
+        (rhs, lhs)
+    };
+    let gap = high.exponent as i64 - low.exponent as i64;
+    let high_digits = digits(high.coefficient) as i64;
+    let (aligned, low_coefficient, exponent, inexact) =
+        if high_digits + gap <= WORKING_DIGITS as i64 {
+            // Exact: below 10^77 even after adding `low`
+            let aligned = high.coefficient.checked_mul(pow10(gap as u32)).unwrap();
+            (aligned, low.coefficient, low.exponent as i64, false)
+        } else {
+            // `low` is far below `high`: widen `high` to 77 digits and truncate
+            // `low` to the same unit. The sum then has at least 76 digits, so
+            // `low`'s discarded digits only decide the rounding.
+            let widen = WORKING_DIGITS - high_digits as u32;
+            let aligned = high.coefficient.checked_mul(pow10(widen)).unwrap();
+            let truncate = gap - widen as i64;
+            let (truncated, remainder) = match U256::pow10(truncate.min(u32::MAX as i64) as u32) {
+                Some(divisor) => low.coefficient.div_rem(divisor).unwrap(),
+                None => (U256::ZERO, low.coefficient),
+            };
+            let exponent = high.exponent as i64 - widen as i64;
+            (aligned, truncated, exponent, !remainder.is_zero())
+        };
+    let (negative, mut coefficient) = if high.negative == low.negative {
+        (high.negative, aligned.checked_add(low_coefficient).unwrap())
+    } else {
+        match aligned.cmp(&low_coefficient) {
+            Ordering::Greater => {
+                let difference = aligned.overflowing_sub(low_coefficient).0;
+                // `low` was truncated toward zero, so the exact difference lies
+                // just below this one
+                let difference = if inexact {
+                    difference.overflowing_sub(U256::ONE).0
+                } else {
+                    difference
+                };
+                (high.negative, difference)
+            }
+            Ordering::Less => (low.negative, low_coefficient.overflowing_sub(aligned).0),
+            // An exact zero from opposite signs is positive
+            Ordering::Equal => (false, U256::ZERO),
+        }
+    };
+    if inexact {
+        coefficient = sticky(coefficient);
+    }
+    normalize(negative, coefficient, exponent)
+}
+
+// The exact value lies strictly between `value` and `value + 1`. Rounding
+// away at least one digit only ever needs to tell which side of a multiple of
+// 5 it falls on, so pick whichever of the two is not one.
+fn sticky(value: U256) -> U256 {
+    let next = value.checked_add(U256::ONE).unwrap();
+    let (_, digit) = next.div_rem(U256::from(5u64)).unwrap();
+    if digit.is_zero() { value } else { next }
+}
+
+// Rounds half-even to 75 digits and checks the exponent range.
+fn normalize(
+    negative: bool,
+    coefficient: U256,
+    exponent: i64,
+) -> Result<BigDecimal256, FloatError> {
+    let drop = digits(coefficient).saturating_sub(MAX_DIGITS);
+    let mut coefficient = shift_right(coefficient, drop, negative, RoundingMode::HalfEven);
+    let mut exponent = exponent + drop as i64;
+    if coefficient == pow10(MAX_DIGITS) {
+        // Rounded up to 10^75
+        coefficient = pow10(MAX_DIGITS - 1);
+        exponent += 1;
+    }
+    let exponent = match i32::try_from(exponent) {
+        Ok(exponent) => exponent,
+        Err(_) if coefficient.is_zero() => 0,
+        Err(_) if exponent > 0 => return Err(FloatError::Overflow),
+        Err(_) => return Err(FloatError::PrecisionLoss),
+    };
+    Ok(BigDecimal256 {
+        negative,
+        coefficient,
+        exponent,
+    })
+}
+
+// `coefficient / 10^drop`, rounded with `rounding` for a value of the given
+// sign.
+fn shift_right(coefficient: U256, drop: u32, negative: bool, rounding: RoundingMode) -> U256 {
+    if drop == 0 || coefficient.is_zero() {
+        return coefficient;
+    }
+    match U256::pow10(drop) {
+        Some(divisor) => {
+            let (quotient, remainder) = coefficient.div_rem(divisor).unwrap();
+            round_quotient(quotient, remainder, divisor, negative, rounding)
+        }
+        // The divisor exceeds 2^256: a non-zero remainder under half a unit
+        None => {
+            let away = match rounding {
+                RoundingMode::Floor => negative,
+                RoundingMode::Ceil => !negative,
+                _ => false,
+            };
+            if away { U256::ONE } else { U256::ZERO }
+        }
+    }
+}
+
+fn round_quotient(
+    quotient: U256,
+    remainder: U256,
+    divisor: U256,
+    negative: bool,
+    rounding: RoundingMode,
+) -> U256 {
+    if remainder.is_zero() {
+        return quotient;
+    }
+    // Compare the remainder with the rest of the divisor rather than doubling it
+    let rest = divisor.overflowing_sub(remainder).0;
+    let odd = quotient.limbs()[0] & 1 == 1;
+    let away = match rounding {
+        RoundingMode::Floor => negative,
+        RoundingMode::Ceil => !negative,
+        RoundingMode::TowardZero => false,
+        RoundingMode::HalfUp => remainder >= rest,
+        RoundingMode::HalfEven => remainder > rest || (remainder == rest && odd),
+    };
+    if away {
+        quotient.checked_add(U256::ONE).unwrap()
+    } else {
+        quotient
+    }
+}
+
+fn pow10(exponent: u32) -> U256 {
+    U256::pow10(exponent).unwrap()
+}
+
+fn digits(value: U256) -> u32 {
+    let ten = U256::from(10u64);
+    let mut count = 1;
+    let mut bound = ten;
+    while value >= bound {
+        count += 1;
+        match bound.checked_mul(ten) {
+            Some(next) => bound = next,
+            None => break,
+        }
+    }
+    count
+}
+
+// Compares magnitudes.
+fn cmp_magnitude(lhs: &BigDecimal256, rhs: &BigDecimal256) -> Ordering {
+    if lhs.is_zero() || rhs.is_zero() {
+        return lhs.coefficient.cmp(&rhs.coefficient);
+    }
+    // Compare the position of the leading digit first, then the digits
+    let lhs_digits = digits(lhs.coefficient);
+    let rhs_digits = digits(rhs.coefficient);
+    let lhs_top = lhs.exponent as i64 + lhs_digits as i64;
+    let rhs_top = rhs.exponent as i64 + rhs_digits as i64;
+    if lhs_top != rhs_top {
+        return lhs_top.cmp(&rhs_top);
+    }
+    // Same leading position: pad the shorter coefficient to the longer
+    let width = lhs_digits.max(rhs_digits);
+    let lhs_aligned = lhs
+        .coefficient
+        .checked_mul(pow10(width - lhs_digits))
+        .unwrap();
+    let rhs_aligned = rhs
+        .coefficient
+        .checked_mul(pow10(width - rhs_digits))
+        .unwrap();
+    lhs_aligned.cmp(&rhs_aligned)
+}
+
+impl PartialEq for BigDecimal256 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for BigDecimal256 {}
+
+impl Ord for BigDecimal256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.is_sign_negative(), other.is_sign_negative()) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(self, other),
+            (true, true) => cmp_magnitude(other, self),
+        }
+    }
+}
+
+impl PartialOrd for BigDecimal256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Default for BigDecimal256 {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl From<u64> for BigDecimal256 {
+    fn from(value: u64) -> Self {
+        Self {
+            negative: false,
+            coefficient: U256::from(value),
+            exponent: 0,
+        }
+    }
+}
+
+impl From<i128> for BigDecimal256 {
+    fn from(value: i128) -> Self {
+        Self {
+            negative: value < 0,
+            coefficient: U256::from(value.unsigned_abs()),
+            exponent: 0,
+        }
+    }
+}
+
+/// The shortest decimal that reads back as `value`, as `Display` for f64
+/// prints it: `0.1` becomes exactly 0.1, not the binary value
+/// 0.1000000000000000055511151231257827... Fails with `NaNOperand` for NaN
+/// and `Overflow` for infinities.
+impl TryFrom<f64> for BigDecimal256 {
+    type Error = FloatError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if value.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if value.is_infinite() {
+            return Err(FloatError::Overflow);
+        }
+        format!("{:e}", value).parse()
+    }
+}
+
+/// Parses plain (`-12.340`) or exponent (`1.5E-7`, `2e3`) notation exactly.
+///
+/// Fails with `PrecisionLoss` for more than 75 significant digits (after
+/// moving trailing zeros into the exponent), `OutOfRange` if the exponent
+/// does not fit in i32, and `NaNOperand` for anything that is not a number.
+impl FromStr for BigDecimal256 {
+    type Err = FloatError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (negative, unsigned) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+            Some(index) => {
+                let exponent: i64 = unsigned[index + 1..]
+                    .parse()
+                    .map_err(|_| FloatError::NaNOperand)?;
+                (&unsigned[..index], exponent)
+            }
+            None => (unsigned, 0),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let all_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if integer.len() + fraction.len() == 0 || !all_digits(integer) || !all_digits(fraction) {
+            return Err(FloatError::NaNOperand);
+        }
+        let digits = format!("{}{}", integer, fraction);
+        let mut significant = digits.trim_start_matches('0');
+        let mut exponent = exponent
+            .checked_sub(fraction.len() as i64)
+            .ok_or(FloatError::OutOfRange)?;
+        while significant.len() > MAX_DIGITS as usize && significant.ends_with('0') {
+            significant = &significant[..significant.len() - 1];
+            exponent += 1;
+        }
+        if significant.len() > MAX_DIGITS as usize {
+            return Err(FloatError::PrecisionLoss);
+        }
+        let ten = U256::from(10u64);
+        let coefficient = significant.bytes().fold(U256::ZERO, |acc, byte| {
+            acc.wrapping_mul(ten)
+                .overflowing_add(U256::from((byte - b'0') as u64))
+                .0
+        });
+        let exponent = i32::try_from(exponent).map_err(|_| FloatError::OutOfRange)?;
+        Ok(Self {
+            negative,
+            coefficient,
+            exponent,
+        })
+    }
+}
+
+/// Scientific-string form, as for [`Decimal64`](crate::decimal64::Decimal64):
+/// `12.5`, `-1.5E+80`, `1E-9`.
+impl fmt::Display for BigDecimal256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_scientific(
+            f,
+            self.negative,
+            &self.coefficient.to_string(),
+            self.exponent,
+        )
+    }
+}
+
+impl fmt::Debug for BigDecimal256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BigDecimal256({})", self)
+    }
+}
@@ -0,0 +1,85 @@
+//! Basis points: hundredths of a percent, the usual unit for fees and slippage.
+
+use crate::error::FloatError;
+use crate::mul_div::mul_div;
+use crate::quantize::RoundingMode;
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Bps(pub u32);
+
+impl Bps {
+    /// `bps` of `amount`, rounded down. Use it for amounts paid out.
+    pub const fn of_floor(self, amount: u64) -> Result<u64, FloatError> {
+        mul_div(amount, self.0 as u64, BPS_DENOMINATOR, RoundingMode::Floor)
+    }
+
+    /// `bps` of `amount`, rounded up. Use it for fees charged.
+    pub const fn of_ceil(self, amount: u64) -> Result<u64, FloatError> {
+        mul_div(amount, self.0 as u64, BPS_DENOMINATOR, RoundingMode::Ceil)
+    }
+
+    /// The fraction this many basis points represents, e.g. `0.0001` for one.
+    pub fn as_fraction(self) -> f64 {
+        self.0 as f64 / BPS_DENOMINATOR as f64
+    }
+}
+
+impl From<u32> for Bps {
+    fn from(bps: u32) -> Self {
+        Bps(bps)
+    }
+}
+
+/// Splits `amount` among recipients by their basis-point shares, e.g. a fee
+/// between the protocol, the LPs and a referrer, so the parts add up to exactly
+/// `amount`.
+///
+/// Each part is rounded down, and the leftover units (fewer than the number
+/// of recipients) go one each to the parts with the largest dropped
+/// remainders, ties to the lower index. Fails with `OutOfRange` unless the
+/// shares sum to exactly [`BPS_DENOMINATOR`].
+pub fn split_exact(amount: u64, parts_bps: &[u16]) -> Result<Vec<u64>, FloatError> {
+    let total_bps: u64 = parts_bps.iter().map(|&bps| bps as u64).sum();
+    if total_bps != BPS_DENOMINATOR {
+        return Err(FloatError::OutOfRange);
+    }
+    let mut parts = Vec::with_capacity(parts_bps.len());
+    let mut remainders = Vec::with_capacity(parts_bps.len());
+    for &bps in parts_bps {
+        parts.push(mul_div(
+            amount,
+            bps as u64,
+            BPS_DENOMINATOR,
+            RoundingMode::Floor,
+        )?);
+        remainders.push(amount as u128 * bps as u128 % BPS_DENOMINATOR as u128);
+    }
+    let leftover = amount - parts.iter().sum::<u64>();
+    // Stable sort, so equal remainders keep index order
+    let mut order: Vec<usize> = (0..parts.len()).collect();
+    order.sort_by(|&a, &b| remainders[b].cmp(&remainders[a]));
+    for &index in order.iter().take(leftover as usize) {
+        parts[index] += 1;
+    }
+    Ok(parts)
+}
+
+/// Whether `a` and `b` differ by at most `bps` of the larger magnitude.
+pub fn within_bps(a: f64, b: f64, bps: Bps) -> bool {
+    (a - b).abs() <= bps.as_fraction() * a.abs().max(b.abs())
+}
+
+/// Relative difference `|a - b| / max(|a|, |b|)` in basis points, or
+/// `NaNOperand` for NaN operands. Two zeros are zero basis points apart.
+pub fn difference_bps(a: f64, b: f64) -> Result<f64, FloatError> {
+    if a.is_nan() || b.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    let scale = a.abs().max(b.abs());
+    if scale == 0.0 {
+        return Ok(0.0);
+    }
+    Ok((a - b).abs() / scale * BPS_DENOMINATOR as f64)
+}
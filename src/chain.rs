@@ -0,0 +1,351 @@
+//! Chaining f64 operations across instructions without losing bits.
+//!
+//! A client that computes `(a * b) / c` over two instructions would otherwise
+//! read the product back from logs or return data and send it again, and any
+//! decimal formatting on the way rounds it. [`OpChain`] instead builds
+//! `ChainStep` instructions whose operands are either inline values or the
+//! results of earlier steps, kept in a result-cache account: each step writes
+//! its result to its own slot there, so every intermediate keeps its exact
+//! bits from one instruction to the next.
+//!
+//! The cache is a program-owned PDA at `[CHAIN_CACHE_SEED, authority]`,
+//! created by an `InitChainCache` instruction, and every step must be signed
+//! by that authority. Its data starts with the [`account_tag::CHAIN_CACHE`]
+//! tag, so no other account the program owns (the feature gate, a results
+//! log) can be written through a step. Slot `i` is the f64 at bytes
+//! `CACHE_HEADER_LEN + 8 * i..CACHE_HEADER_LEN + 8 * i + 8`.
+
+use borsh::io::{self, Read, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::account_tag;
+use crate::double_ops::BinaryOp;
+use crate::error::FloatError;
+use crate::instruction::{FloatInstruction, Opcode, encode};
+use crate::verify::recompute;
+
+pub const CHAIN_CACHE_SEED: &[u8] = b"chain_cache";
+
+/// Bytes before the first slot: `[tag: u8][0; 7]`, which keeps slots 8-byte
+/// aligned.
+pub const CACHE_HEADER_LEN: usize = 8;
+
+/// Size of one result slot in the cache account.
+pub const SLOT_SIZE: usize = 8;
+
+/// Size of a `ChainStep` payload:
+/// `[op: u8][lhs: operand][rhs: operand][destination slot: u16]`, where an
+/// operand is `[0][value: f64]` or `[1][slot: u16][0; 6]`.
+pub const STEP_LEN: usize = 21;
+
+const OPERAND_VALUE: u8 = 0;
+const OPERAND_SLOT: u8 = 1;
+
+/// The result of an earlier step of an [`OpChain`], usable as an operand of
+/// later ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepResult(u16);
+
+impl StepResult {
+    /// The cache slot holding this result.
+    pub fn slot(self) -> u16 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand {
+    Value(f64),
+    Result(StepResult),
+}
+
+impl From<f64> for Operand {
+    fn from(value: f64) -> Self {
+        Operand::Value(value)
+    }
+}
+
+impl From<StepResult> for Operand {
+    fn from(result: StepResult) -> Self {
+        Operand::Result(result)
+    }
+}
+
+/// One `ChainStep` instruction: `destination = op(lhs, rhs)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChainStep {
+    pub op: BinaryOp,
+    pub lhs: Operand,
+    pub rhs: Operand,
+    pub destination: u16,
+}
+
+impl ChainStep {
+    pub fn pack(&self) -> [u8; STEP_LEN] {
+        let mut dst = [0u8; STEP_LEN];
+        dst[0] = match self.op {
+            BinaryOp::Add => 0,
+            BinaryOp::Multiply => 1,
+            BinaryOp::Divide => 2,
+        };
+        pack_operand(self.lhs, &mut dst[1..10]);
+        pack_operand(self.rhs, &mut dst[10..19]);
+        dst[19..21].copy_from_slice(&self.destination.to_le_bytes());
+        dst
+    }
+
+    pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != STEP_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self {
+            op: BinaryOp::try_from(src[0]).map_err(|_| ProgramError::InvalidInstructionData)?,
+            lhs: unpack_operand(&src[1..10])?,
+            rhs: unpack_operand(&src[10..19])?,
+            destination: u16::from_le_bytes(src[19..21].try_into().unwrap()),
+        })
+    }
+
+    /// Reads the operands from the cache account data `cache`, computes the
+    /// result as [`recompute`] does and writes it to the destination slot.
+    ///
+    /// Fails with `InvalidAccountData` if `cache` is not tagged as a chain
+    /// cache, `AccountDataTooSmall` for a slot past its end and the
+    /// [`FloatError`] of [`recompute`] for NaN operands or results and
+    /// division by zero.
+    pub fn apply(&self, cache: &mut [u8]) -> Result<f64, ProgramError> {
+        check_cache_tag(cache)?;
+        let lhs = read_operand(self.lhs, cache)?;
+        let rhs = read_operand(self.rhs, cache)?;
+        let result = recompute(self.op, lhs, rhs)?;
+        let offset = slot_offset(self.destination);
+        cache
+            .get_mut(offset..offset + SLOT_SIZE)
+            .ok_or(ProgramError::AccountDataTooSmall)?
+            .copy_from_slice(&result.to_le_bytes());
+        Ok(result)
+    }
+}
+
+impl BorshSerialize for ChainStep {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.pack())
+    }
+}
+
+impl BorshDeserialize for ChainStep {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut src = [0u8; STEP_LEN];
+        reader.read_exact(&mut src)?;
+        Self::unpack(&src).map_err(|_| io::ErrorKind::InvalidData.into())
+    }
+}
+
+/// Canonical cache address and bump of `authority` for `program_id`.
+pub fn find_cache_address(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CHAIN_CACHE_SEED, authority.as_ref()], program_id)
+}
+
+/// Bytes a cache account with `slots` result slots holds.
+pub fn cache_len(slots: usize) -> usize {
+    CACHE_HEADER_LEN + slots * SLOT_SIZE
+}
+
+/// Writes the header of a new cache account's data.
+pub fn init_cache(cache: &mut [u8]) -> Result<(), ProgramError> {
+    let header = cache
+        .get_mut(..CACHE_HEADER_LEN)
+        .ok_or(ProgramError::AccountDataTooSmall)?;
+    header.fill(0);
+    header[0] = account_tag::CHAIN_CACHE;
+    Ok(())
+}
+
+/// Fails with `InvalidAccountData` unless `cache` starts with the chain cache
+/// tag.
+pub fn check_cache_tag(cache: &[u8]) -> Result<(), ProgramError> {
+    match cache.first() {
+        Some(&account_tag::CHAIN_CACHE) => Ok(()),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Reads result slot `slot` of a cache account's data.
+pub fn read_slot(cache: &[u8], slot: u16) -> Result<f64, ProgramError> {
+    check_cache_tag(cache)?;
+    let offset = slot_offset(slot);
+    let bytes = cache
+        .get(offset..offset + SLOT_SIZE)
+        .ok_or(ProgramError::AccountDataTooSmall)?;
+    Ok(f64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Builds the instructions of a chain of operations over one cache account.
+///
+/// ```
+/// use solana_floats::chain::OpChain;
+/// use solana_floats::double_ops::BinaryOp;
+/// use solana_program::pubkey::Pubkey;
+///
+/// let mut chain = OpChain::new(Pubkey::new_unique(), Pubkey::new_unique());
+/// let product = chain.push(BinaryOp::Multiply, 100.0, 1.01).unwrap();
+/// let half = chain.push(BinaryOp::Divide, product, 2.0).unwrap();
+/// assert_eq!(chain.simulate().unwrap()[half.slot() as usize], 100.0 * 1.01 / 2.0);
+/// // `InitChainCache`, then one `ChainStep` per step
+/// assert_eq!(chain.instructions().len(), 3);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpChain {
+    program_id: Pubkey,
+    authority: Pubkey,
+    steps: Vec<ChainStep>,
+}
+
+impl OpChain {
+    /// A chain over the cache of `authority`, which signs every instruction
+    /// and pays for the cache's creation.
+    pub fn new(program_id: Pubkey, authority: Pubkey) -> Self {
+        Self {
+            program_id,
+            authority,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Address of the cache account holding the results.
+    pub fn cache(&self) -> Pubkey {
+        find_cache_address(&self.program_id, &self.authority).0
+    }
+
+    /// Appends `op(lhs, rhs)` and returns a handle to its result.
+    ///
+    /// Fails with `OutOfRange` once the chain has `u16::MAX + 1` steps, or if
+    /// an operand is the result of a step this chain does not have yet.
+    pub fn push(
+        &mut self,
+        op: BinaryOp,
+        lhs: impl Into<Operand>,
+        rhs: impl Into<Operand>,
+    ) -> Result<StepResult, FloatError> {
+        let destination = u16::try_from(self.steps.len()).map_err(|_| FloatError::OutOfRange)?;
+        let (lhs, rhs) = (lhs.into(), rhs.into());
+        for operand in [lhs, rhs] {
+            if matches!(operand, Operand::Result(result) if result.0 >= destination) {
+                return Err(FloatError::OutOfRange);
+            }
+        }
+        self.steps.push(ChainStep {
+            op,
+            lhs,
+            rhs,
+            destination,
+        });
+        Ok(StepResult(destination))
+    }
+
+    pub fn steps(&self) -> &[ChainStep] {
+        &self.steps
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Bytes the cache account needs to hold every result.
+    pub fn cache_len(&self) -> usize {
+        cache_len(self.steps.len())
+    }
+
+    /// An `InitChainCache` instruction sized for this chain, then one
+    /// `ChainStep` instruction per step, in order. They may be split across
+    /// transactions as long as the order is kept. Initializing succeeds
+    /// without changes if the cache already exists and is large enough, so a
+    /// cache can be reused by later chains.
+    pub fn instructions(&self) -> Vec<Instruction> {
+        let cache = self.cache();
+        let init = FloatInstruction::InitChainCache {
+            slots: self.steps.len() as u16,
+        };
+        let mut instructions = vec![Instruction::new_with_bytes(
+            self.program_id,
+            &init.pack(),
+            vec![
+                AccountMeta::new(cache, false),
+                AccountMeta::new(self.authority, true),
+                AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+            ],
+        )];
+        instructions.extend(self.steps.iter().map(|step| {
+            Instruction::new_with_bytes(
+                self.program_id,
+                &encode(Opcode::ChainStep, &step.pack()),
+                vec![
+                    AccountMeta::new(cache, false),
+                    AccountMeta::new_readonly(self.authority, true),
+                ],
+            )
+        }));
+        instructions
+    }
+
+    /// The result of every step, computed here exactly as the program will,
+    /// so a client knows the bits before sending anything.
+    ///
+    /// Fails like [`recompute`]: `NaNOperand` for NaN operands or results and
+    /// `DivisionByZero`.
+    pub fn simulate(&self) -> Result<Vec<f64>, FloatError> {
+        let mut results: Vec<f64> = Vec::with_capacity(self.steps.len());
+        for step in &self.steps {
+            let operand = |operand| match operand {
+                Operand::Value(value) => value,
+                Operand::Result(result) => results[result.0 as usize],
+            };
+            let result = recompute(step.op, operand(step.lhs), operand(step.rhs))?;
+            results.push(result);
+        }
+        Ok(results)
+    }
+}
+
+fn slot_offset(slot: u16) -> usize {
+    CACHE_HEADER_LEN + slot as usize * SLOT_SIZE
+}
+
+fn pack_operand(operand: Operand, dst: &mut [u8]) {
+    match operand {
+        Operand::Value(value) => {
+            dst[0] = OPERAND_VALUE;
+            dst[1..9].copy_from_slice(&value.to_le_bytes());
+        }
+        Operand::Result(result) => {
+            dst[0] = OPERAND_SLOT;
+            dst[1..3].copy_from_slice(&result.0.to_le_bytes());
+        }
+    }
+}
+
+fn unpack_operand(src: &[u8]) -> Result<Operand, ProgramError> {
+    match src[0] {
+        OPERAND_VALUE => Ok(Operand::Value(f64::from_le_bytes(
+            src[1..9].try_into().unwrap(),
+        ))),
+        OPERAND_SLOT if src[3..9].iter().all(|&byte| byte == 0) => Ok(Operand::Result(StepResult(
+            u16::from_le_bytes(src[1..3].try_into().unwrap()),
+        ))),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn read_operand(operand: Operand, cache: &[u8]) -> Result<f64, ProgramError> {
+    match operand {
+        Operand::Value(value) => Ok(value),
+        Operand::Result(result) => read_slot(cache, result.0),
+    }
+}
@@ -0,0 +1,112 @@
+//! Error types with context for off-chain integrations.
+//!
+//! On-chain code deals in bare [`FloatError`]s, which keep the program small.
+//! Clients can afford to say more: [`FloatResultExt`] attaches the operation
+//! name, the offending values and, where one applies, the policy that was
+//! violated, and [`ClientError::within`] chains them into a `source()` trail
+//! from the outermost step down to the original `FloatError`.
+
+use std::fmt;
+
+use crate::error::FloatError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("{operation} failed for {value:?}: {source}")]
+    Value {
+        operation: &'static str,
+        value: f64,
+        #[source]
+        source: FloatError,
+    },
+    #[error("{operation} failed for ({lhs:?}, {rhs:?}): {source}")]
+    Operands {
+        operation: &'static str,
+        lhs: f64,
+        rhs: f64,
+        #[source]
+        source: FloatError,
+    },
+    #[error("{operation} violated {policy} with {value:?}: {source}")]
+    Policy {
+        operation: &'static str,
+        value: f64,
+        policy: String,
+        #[source]
+        source: FloatError,
+    },
+    #[error("{operation} failed")]
+    Within {
+        operation: &'static str,
+        #[source]
+        source: Box<ClientError>,
+    },
+}
+
+impl ClientError {
+    /// Wraps this error as a step of the larger `operation`.
+    pub fn within(self, operation: &'static str) -> Self {
+        ClientError::Within {
+            operation,
+            source: Box::new(self),
+        }
+    }
+
+    /// The `FloatError` at the bottom of the chain.
+    pub fn float_error(&self) -> FloatError {
+        match self {
+            ClientError::Value { source, .. }
+            | ClientError::Operands { source, .. }
+            | ClientError::Policy { source, .. } => *source,
+            ClientError::Within { source, .. } => source.float_error(),
+        }
+    }
+}
+
+/// Adds context to the crate's `Result<T, FloatError>`s.
+pub trait FloatResultExt<T> {
+    fn for_value(self, operation: &'static str, value: f64) -> Result<T, ClientError>;
+
+    fn for_operands(self, operation: &'static str, lhs: f64, rhs: f64) -> Result<T, ClientError>;
+
+    /// `policy` is rendered with `Display`, e.g. a decimals/rounding setting.
+    fn for_policy(
+        self,
+        operation: &'static str,
+        value: f64,
+        policy: impl fmt::Display,
+    ) -> Result<T, ClientError>;
+}
+
+impl<T> FloatResultExt<T> for Result<T, FloatError> {
+    fn for_value(self, operation: &'static str, value: f64) -> Result<T, ClientError> {
+        self.map_err(|source| ClientError::Value {
+            operation,
+            value,
+            source,
+        })
+    }
+
+    fn for_operands(self, operation: &'static str, lhs: f64, rhs: f64) -> Result<T, ClientError> {
+        self.map_err(|source| ClientError::Operands {
+            operation,
+            lhs,
+            rhs,
+            source,
+        })
+    }
+
+    fn for_policy(
+        self,
+        operation: &'static str,
+        value: f64,
+        policy: impl fmt::Display,
+    ) -> Result<T, ClientError> {
+        self.map_err(|source| ClientError::Policy {
+            operation,
+            value,
+            policy: policy.to_string(),
+            source,
+        })
+    }
+}
@@ -0,0 +1,175 @@
+//! Tick and sqrt-price math for concentrated-liquidity pools.
+//!
+//! Prices are `sqrt(1.0001^tick)` in Q64.64 fixed point, as in Orca Whirlpools
+//! and Raydium CLMM. The two programs compute them from different constant
+//! tables, so their results differ in the low bits; [`SqrtPriceConvention`]
+//! selects which program to reproduce bit for bit. Neither path touches floats.
+
+use crate::error::FloatError;
+use crate::u256::U256;
+
+pub const MIN_TICK: i32 = -443_636;
+pub const MAX_TICK: i32 = 443_636;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqrtPriceConvention {
+    /// Orca Whirlpools: floored Q96 factors for positive ticks, floored Q64
+    /// factors for negative ticks.
+    Whirlpool,
+    /// Raydium CLMM: Q64 factors for `|tick|`, inverted for positive ticks.
+    Raydium,
+}
+
+// floor(1.0001^(2^i / 2) * 2^96)
+const WHIRLPOOL_POSITIVE_X96: [u128; 19] = [
+    79232123823359799118286999567,
+    79236085330515764027303304731,
+    79244008939048815603706035061,
+    79259858533276714757314932305,
+    79291567232598584799939703904,
+    79355022692464371645785046466,
+    79482085999252804386437311141,
+    79736823300114093921829183326,
+    80248749790819932309965073892,
+    81282483887344747381513967011,
+    83390072131320151908154831281,
+    87770609709833776024991924138,
+    97234110755111693312479820773,
+    119332217159966728226237229890,
+    179736315981702064433883588727,
+    407748233172238350107850275304,
+    2098478828474011932436660412517,
+    55581415166113811149459800483533,
+    38992368544603139932233054999993551,
+];
+
+// floor(1.0001^(-2^i / 2) * 2^64)
+const WHIRLPOOL_NEGATIVE_X64: [u128; 19] = [
+    18445821805675392311,
+    18444899583751176498,
+    18443055278223354162,
+    18439367220385604838,
+    18431993317065449817,
+    18417254355718160513,
+    18387811781193591352,
+    18329067761203520168,
+    18212142134806087854,
+    17980523815641551639,
+    17526086738831147013,
+    16651378430235024244,
+    15030750278693429944,
+    12247334978882834399,
+    8131365268884726200,
+    3584323654723342297,
+    696457651847595233,
+    26294789957452057,
+    37481735321082,
+];
+
+// Raydium's table, which was generated in double precision.
+const RAYDIUM_X64: [u128; 19] = [
+    0xfffcb933bd6fb800,
+    0xfff97272373d4000,
+    0xfff2e50f5f657000,
+    0xffe5caca7e10f000,
+    0xffcb9843d60f7000,
+    0xff973b41fa98e800,
+    0xff2ea16466c9b000,
+    0xfe5dee046a9a3800,
+    0xfcbe86c7900bb000,
+    0xf987a7253ac65800,
+    0xf3392b0822bb6000,
+    0xe7159475a2caf000,
+    0xd097f3bdfd2f2000,
+    0xa9f746462d9f8000,
+    0x70d869a156f31c00,
+    0x31be135f97ed3200,
+    0x9aa508b5b85a500,
+    0x5d6af8dedc582c,
+    0x2216e584f5fa,
+];
+
+impl SqrtPriceConvention {
+    /// Sqrt price at `MIN_TICK`, the smallest valid price.
+    pub fn min_sqrt_price_x64(self) -> u128 {
+        match self {
+            SqrtPriceConvention::Whirlpool | SqrtPriceConvention::Raydium => 4295048016,
+        }
+    }
+
+    /// Sqrt price at `MAX_TICK`, the largest valid price.
+    pub fn max_sqrt_price_x64(self) -> u128 {
+        match self {
+            SqrtPriceConvention::Whirlpool => 79226673515401279992447579055,
+            SqrtPriceConvention::Raydium => 79226673521066979257578248091,
+        }
+    }
+}
+
+/// `sqrt(1.0001^tick)` in Q64.64, computed exactly as `convention` does.
+///
+/// Fails with `OutOfRange` outside `MIN_TICK..=MAX_TICK`.
+pub fn sqrt_price_from_tick(
+    tick: i32,
+    convention: SqrtPriceConvention,
+) -> Result<u128, FloatError> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(FloatError::OutOfRange);
+    }
+    let abs_tick = tick.unsigned_abs();
+    Ok(match convention {
+        SqrtPriceConvention::Whirlpool if tick >= 0 => {
+            product_of_factors(abs_tick, &WHIRLPOOL_POSITIVE_X96, 96) >> 32
+        }
+        SqrtPriceConvention::Whirlpool => product_of_factors(abs_tick, &WHIRLPOOL_NEGATIVE_X64, 64),
+        SqrtPriceConvention::Raydium => {
+            let ratio = product_of_factors(abs_tick, &RAYDIUM_X64, 64);
+            if tick > 0 { u128::MAX / ratio } else { ratio }
+        }
+    })
+}
+
+/// The greatest tick whose sqrt price is at most `sqrt_price_x64`.
+///
+/// Fails with `OutOfRange` for prices outside
+/// `min_sqrt_price_x64()..=max_sqrt_price_x64()`. Found by bisection over
+/// [`sqrt_price_from_tick`], so it is the exact inverse for either convention.
+pub fn tick_from_sqrt_price(
+    sqrt_price_x64: u128,
+    convention: SqrtPriceConvention,
+) -> Result<i32, FloatError> {
+    if sqrt_price_x64 < convention.min_sqrt_price_x64()
+        || sqrt_price_x64 > convention.max_sqrt_price_x64()
+    {
+        return Err(FloatError::OutOfRange);
+    }
+    // Invariant: price(low) <= sqrt_price_x64 < price(high + 1)
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_from_tick(mid, convention)? <= sqrt_price_x64 {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+// Multiplies the factors for each set bit of `abs_tick`, starting from 1.0 (or
+// the first factor) in Q`fraction_bits`. The Whirlpool Q96 ratio approaches
+// 2^128 at the top of the range, so each product is taken at 256 bits.
+fn product_of_factors(abs_tick: u32, factors: &[u128; 19], fraction_bits: u32) -> u128 {
+    let mut ratio = if abs_tick & 1 != 0 {
+        factors[0]
+    } else {
+        1 << fraction_bits
+    };
+    for (bit, factor) in factors.iter().enumerate().skip(1) {
+        if abs_tick >> bit & 1 != 0 {
+            ratio =
+                (U256::from(ratio).wrapping_mul(U256::from(*factor)) >> fraction_bits).low_u128();
+        }
+    }
+    ratio
+}
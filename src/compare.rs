@@ -0,0 +1,235 @@
+//! Consensus-safe float comparisons.
+//!
+//! Every check here is built from subtraction, `abs`, `max`, multiplication
+//! and comparison, each of which IEEE 754 defines exactly, so a given pair of
+//! inputs gets the same answer on every validator and in every client. NaN
+//! never compares approximately equal to anything, itself included.
+
+use std::cmp::Ordering;
+
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+
+use crate::bps::Bps;
+use crate::error::FloatError;
+use crate::instruction::{APPROX_EQ_FLAG_REQUIRE, F64Operands, FloatInstruction};
+
+/// How far apart two values may be and still count as equal.
+///
+/// Values match when `|a - b| <= max(abs, rel * max(|a|, |b|))`: `abs` covers
+/// results near zero, where a relative bound shrinks to nothing, and `rel`
+/// scales with the magnitude of the operands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    abs: f64,
+    rel: f64,
+}
+
+impl Tolerance {
+    /// Both bounds must be finite and non-negative.
+    pub fn new(abs: f64, rel: f64) -> Result<Self, FloatError> {
+        if abs.is_nan() || rel.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if !abs.is_finite() || !rel.is_finite() || abs < 0.0 || rel < 0.0 {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(Self { abs, rel })
+    }
+
+    /// Starts from an exact (zero) tolerance.
+    pub fn builder() -> ToleranceBuilder {
+        ToleranceBuilder::default()
+    }
+
+    /// Absolute bound.
+    pub fn abs(&self) -> f64 {
+        self.abs
+    }
+
+    /// Relative bound, as a fraction of the larger operand.
+    pub fn rel(&self) -> f64 {
+        self.rel
+    }
+
+    /// Whether `a` and `b` are within tolerance. NaN never is.
+    pub fn matches(&self, a: f64, b: f64) -> bool {
+        approx_eq_abs(a, b, self.abs) || approx_eq_rel(a, b, self.rel)
+    }
+}
+
+/// The combined absolute/relative bound taken by [`approx_eq`].
+pub type AbsRel = Tolerance;
+
+/// Whether `|a - b| <= abs`. Suits values of known scale, such as amounts
+/// already rounded to a token's decimals.
+///
+/// Infinities are never within a finite tolerance of anything, including
+/// themselves, since their difference is NaN or infinite.
+pub fn approx_eq_abs(a: f64, b: f64, abs: f64) -> bool {
+    (a - b).abs() <= abs
+}
+
+/// Whether `|a - b| <= rel * max(|a|, |b|)`, i.e. `a` and `b` agree to a
+/// fraction `rel` of the larger. Near zero this shrinks to nothing, so `0.0`
+/// matches only zero; combine it with an absolute bound in [`approx_eq`] for
+/// results that may cancel.
+pub fn approx_eq_rel(a: f64, b: f64, rel: f64) -> bool {
+    (a - b).abs() <= rel * a.abs().max(b.abs())
+}
+
+/// Whether `a` and `b` satisfy either bound of `tolerance`; the same test as
+/// [`Tolerance::matches`].
+///
+/// ```
+/// use solana_floats::compare::{AbsRel, approx_eq};
+///
+/// let tolerance = AbsRel::new(1e-12, 1e-9).unwrap();
+/// assert!(approx_eq(0.1 + 0.2, 0.3, tolerance));
+/// assert!(approx_eq(1e-15, 0.0, tolerance));
+/// assert!(!approx_eq(1.0, 1.000001, tolerance));
+/// ```
+pub fn approx_eq(a: f64, b: f64, tolerance: AbsRel) -> bool {
+    tolerance.matches(a, b)
+}
+
+/// Fluent construction of a [`Tolerance`]; `build` applies the same checks as
+/// `Tolerance::new`.
+///
+/// ```
+/// use solana_floats::compare::Tolerance;
+///
+/// let tolerance = Tolerance::builder().abs(1e-12).rel_bps(1).build().unwrap();
+/// assert!(tolerance.matches(10_000.0, 10_000.9));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ToleranceBuilder {
+    abs: f64,
+    rel: f64,
+}
+
+impl ToleranceBuilder {
+    pub fn abs(mut self, abs: f64) -> Self {
+        self.abs = abs;
+        self
+    }
+
+    pub fn rel(mut self, rel: f64) -> Self {
+        self.rel = rel;
+        self
+    }
+
+    /// Relative tolerance given in basis points.
+    pub fn rel_bps(self, bps: u32) -> Self {
+        self.rel(Bps(bps).as_fraction())
+    }
+
+    pub fn build(self) -> Result<Tolerance, FloatError> {
+        Tolerance::new(self.abs, self.rel)
+    }
+}
+
+impl From<Bps> for Tolerance {
+    fn from(bps: Bps) -> Self {
+        Tolerance {
+            abs: 0.0,
+            rel: bps.as_fraction(),
+        }
+    }
+}
+
+/// Orders `a` and `b` with `f64::total_cmp`, treating them as equal when they are
+/// within `tolerance` of each other.
+///
+/// `tolerance` must be finite and non-negative. With zero tolerance only
+/// numerically equal values compare equal, so `-0.0` and `+0.0` are `Equal` even
+/// though total ordering separates them. NaN operands are never within
+/// tolerance, so they fall through to total ordering and compare
+/// deterministically instead of returning `false` for everything.
+pub fn total_cmp_with_tolerance(a: f64, b: f64, tolerance: f64) -> Result<Ordering, FloatError> {
+    if tolerance.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    if !tolerance.is_finite() || tolerance < 0.0 {
+        return Err(FloatError::OutOfRange);
+    }
+    if (a - b).abs() <= tolerance {
+        return Ok(Ordering::Equal);
+    }
+    Ok(a.total_cmp(&b))
+}
+
+/// Units in the last place between `a` and `b`: the number of representable
+/// f64 values from one to the other, counting across zero. `-0.0` and `+0.0`
+/// are 0 apart, and `f64::MAX` is 1 from infinity. `None` if either is NaN.
+///
+/// Unlike an epsilon, this bound means the same thing at every magnitude,
+/// and it is computed on the bit patterns alone, so it is exact.
+pub fn ulp_distance_f64(a: f64, b: f64) -> Option<u64> {
+    if a.is_nan() || b.is_nan() {
+        return None;
+    }
+    // Map the sign-magnitude bit patterns onto a line ordered like the values
+    fn ordered(value: f64) -> i64 {
+        let bits = value.to_bits() as i64;
+        if bits < 0 { i64::MIN - bits } else { bits }
+    }
+    Some(ordered(a).abs_diff(ordered(b)))
+}
+
+/// [`ulp_distance_f64`] for f32 values.
+pub fn ulp_distance_f32(a: f32, b: f32) -> Option<u32> {
+    if a.is_nan() || b.is_nan() {
+        return None;
+    }
+    fn ordered(value: f32) -> i32 {
+        let bits = value.to_bits() as i32;
+        if bits < 0 { i32::MIN - bits } else { bits }
+    }
+    Some(ordered(a).abs_diff(ordered(b)))
+}
+
+/// Whether `a` and `b` are at most `max_ulps` apart. NaN never is.
+pub fn approx_eq_ulps(a: f64, b: f64, max_ulps: u64) -> bool {
+    ulp_distance_f64(a, b).is_some_and(|distance| distance <= max_ulps)
+}
+
+/// [`approx_eq_ulps`] for f32 values.
+pub fn approx_eq_ulps_f32(a: f32, b: f32, max_ulps: u32) -> bool {
+    ulp_distance_f32(a, b).is_some_and(|distance| distance <= max_ulps)
+}
+
+/// Encodes an `Ordering` as a single byte: `Less = 0xFF` (-1 as i8), `Equal = 0`,
+/// `Greater = 1`.
+pub fn encode_ordering(ordering: Ordering) -> u8 {
+    ordering as i8 as u8
+}
+
+/// Inverse of `encode_ordering`.
+pub fn decode_ordering(byte: u8) -> Option<Ordering> {
+    match byte as i8 {
+        -1 => Some(Ordering::Less),
+        0 => Some(Ordering::Equal),
+        1 => Some(Ordering::Greater),
+        _ => None,
+    }
+}
+
+/// An `ApproxEq` instruction for this program at `program_id`, taking no
+/// accounts. It returns `[1]` or `[0]` in return data, as
+/// [`Tolerance::matches`] would for `a` and `b`; with `require` it fails with
+/// `InvalidArgument` instead of returning `[0]`.
+pub fn approx_eq_instruction(
+    program_id: Pubkey,
+    a: f64,
+    b: f64,
+    tolerance: Tolerance,
+    require: bool,
+) -> Instruction {
+    let instruction = FloatInstruction::ApproxEq {
+        flags: if require { APPROX_EQ_FLAG_REQUIRE } else { 0 },
+        operands: F64Operands { a, b },
+        tolerance,
+    };
+    Instruction::new_with_bytes(program_id, &instruction.pack(), vec![])
+}
@@ -0,0 +1,172 @@
+//! Curated float values that break naive code.
+//!
+//! Each set collects inputs behind a known class of bug: integers past the
+//! last exactly representable one, decimal amounts with no binary form, ties
+//! that a rounding step can send the wrong way, and subnormals. Testing new
+//! code against these shared sets, rather than against values picked for each
+//! test, means every feature faces the same adversarial inputs, and a value
+//! that catches a bug once is added here for all of them.
+
+/// Zeros, infinities, NaN, the extremes and the neighbours of one.
+pub const F64_SPECIALS: [f64; 13] = [
+    0.0,
+    -0.0,
+    f64::INFINITY,
+    f64::NEG_INFINITY,
+    f64::NAN,
+    f64::MAX,
+    f64::MIN,
+    f64::MIN_POSITIVE,
+    f64::EPSILON,
+    1.0,
+    -1.0,
+    // 1 - 2^-53 and 1 + 2^-52
+    f64::from_bits(0x3fef_ffff_ffff_ffff),
+    f64::from_bits(0x3ff0_0000_0000_0001),
+];
+
+/// [`F64_SPECIALS`] for f32.
+pub const F32_SPECIALS: [f32; 13] = [
+    0.0,
+    -0.0,
+    f32::INFINITY,
+    f32::NEG_INFINITY,
+    f32::NAN,
+    f32::MAX,
+    f32::MIN,
+    f32::MIN_POSITIVE,
+    f32::EPSILON,
+    1.0,
+    -1.0,
+    f32::from_bits(0x3f7f_ffff),
+    f32::from_bits(0x3f80_0001),
+];
+
+/// Where the spacing of f64 reaches 1 and 2, and where integer conversions
+/// saturate.
+///
+/// Above 2^53 not every integer is representable, so a u64 amount converted
+/// to f64 can silently change; at 2^63 and 2^64 `as i64` and `as u64`
+/// saturate.
+pub const F64_PRECISION_BOUNDARIES: [f64; 11] = [
+    // 2^52 - 0.5, the last value with a fractional part
+    4_503_599_627_370_495.5,
+    // 2^52
+    4_503_599_627_370_496.0,
+    // 2^53 - 1, the largest integer whose successor is also exact
+    9_007_199_254_740_991.0,
+    // 2^53
+    9_007_199_254_740_992.0,
+    // 2^53 + 2, the next value after 2^53
+    9_007_199_254_740_994.0,
+    -9_007_199_254_740_992.0,
+    // 2^63 - 1024, the largest value below i64::MAX
+    9_223_372_036_854_774_784.0,
+    // 2^63
+    9_223_372_036_854_775_808.0,
+    // 2^64 - 2048, the largest value below u64::MAX
+    18_446_744_073_709_549_568.0,
+    // 2^64
+    18_446_744_073_709_551_616.0,
+    -9_223_372_036_854_775_808.0,
+];
+
+/// [`F64_PRECISION_BOUNDARIES`] for f32, where integers stop being exact past
+/// 2^24.
+pub const F32_PRECISION_BOUNDARIES: [f32; 6] = [
+    // 2^23 - 0.5
+    8_388_607.5,
+    // 2^23
+    8_388_608.0,
+    // 2^24 - 1
+    16_777_215.0,
+    // 2^24
+    16_777_216.0,
+    // 2^24 + 2
+    16_777_218.0,
+    -16_777_216.0,
+];
+
+/// Common decimal amounts that have no exact binary form, so their stored
+/// value is slightly above or below what was written.
+pub const INEXACT_DECIMALS: [f64; 10] = [0.1, 0.2, 0.3, 0.7, 1.1, 0.01, 0.001, 1e-6, 1e-9, 100.01];
+
+/// `(value, decimals)` where the decimal literal is a tie at `decimals`
+/// places, but the stored value is not: `1.005` is stored as
+/// `1.00499999999999989...`, so rounding it half up to cents gives `1.0`.
+/// The first nine are stored below the tie, the last four above it.
+pub const DECIMAL_TIES: [(f64, u32); 13] = [
+    (1.005, 2),
+    (1.015, 2),
+    (2.675, 2),
+    (9.995, 2),
+    (0.045, 2),
+    (0.285, 2),
+    (0.15, 1),
+    (0.35, 1),
+    (1.45, 1),
+    (8.345, 2),
+    (0.000_001_5, 6),
+    (1.000_000_5, 6),
+    (0.000_015, 5),
+];
+
+/// Exact ties and values next to them that break `(x + 0.5).floor()` style
+/// rounding.
+pub const NEAR_TIES: [f64; 10] = [
+    0.5,
+    -0.5,
+    1.5,
+    2.5,
+    -2.5,
+    0.125,
+    0.375,
+    // The largest value below 0.5: adding 0.5 rounds up to 1.0
+    0.499_999_999_999_999_94,
+    // 2^52 + 1: adding 0.5 rounds to 2^52 + 2
+    4_503_599_627_370_497.0,
+    // 2^53 - 1: adding 0.5 rounds to 2^53
+    9_007_199_254_740_991.0,
+];
+
+/// The smallest and largest subnormals of each sign, and one in between.
+pub const F64_SUBNORMALS: [f64; 5] = [
+    f64::from_bits(0x0000_0000_0000_0001),
+    f64::from_bits(0x8000_0000_0000_0001),
+    f64::from_bits(0x0008_0000_0000_0000),
+    f64::from_bits(0x000f_ffff_ffff_ffff),
+    f64::from_bits(0x800f_ffff_ffff_ffff),
+];
+
+/// [`F64_SUBNORMALS`] for f32.
+pub const F32_SUBNORMALS: [f32; 5] = [
+    f32::from_bits(0x0000_0001),
+    f32::from_bits(0x8000_0001),
+    f32::from_bits(0x0040_0000),
+    f32::from_bits(0x007f_ffff),
+    f32::from_bits(0x807f_ffff),
+];
+
+/// Every f64 in this module, set by set in the order they are declared.
+pub fn f64_values() -> impl Iterator<Item = f64> + Clone {
+    F64_SPECIALS
+        .into_iter()
+        .chain(F64_PRECISION_BOUNDARIES)
+        .chain(INEXACT_DECIMALS)
+        .chain(DECIMAL_TIES.into_iter().map(|(value, _)| value))
+        .chain(NEAR_TIES)
+        .chain(F64_SUBNORMALS)
+}
+
+/// Every f32 in this module.
+pub fn f32_values() -> impl Iterator<Item = f32> + Clone {
+    F32_SPECIALS
+        .into_iter()
+        .chain(F32_PRECISION_BOUNDARIES)
+        .chain(F32_SUBNORMALS)
+}
+
+/// Every ordered pair of [`f64_values`], for binary operations.
+pub fn f64_pairs() -> impl Iterator<Item = (f64, f64)> {
+    f64_values().flat_map(|a| f64_values().map(move |b| (a, b)))
+}
@@ -0,0 +1,82 @@
+//! Deriving prices for currency pairs through a common quote.
+//!
+//! Oracles usually publish everything against USD, so a SOL/ETH price is
+//! SOL/USD divided by ETH/USD. Doing that division in f64 and then scaling to
+//! the output decimals rounds twice; [`cross_rate`] and [`invert_price`] work
+//! on the raw base units instead and round exactly once, in the direction the
+//! caller names.
+//!
+//! Near-zero prices are the dangerous case: a price that quantizes to zero
+//! base units would divide by zero or silently turn into a zero rate, so both
+//! functions reject it rather than return a huge or empty result.
+
+use crate::error::FloatError;
+use crate::mul_div::mul_div_u256;
+use crate::quantize::RoundingMode;
+use crate::u256::U256;
+use crate::units::Price;
+
+/// Computes the `A/B` price from `A/quote` and `B/quote` prices, e.g.
+/// SOL/ETH from SOL/USD and ETH/USD: `raw_a * 10^(B + OUT) / (raw_b * 10^A)`,
+/// rounded once with `rounding`.
+///
+/// Fails with `DivisionByZero` if `base_quote_b` is zero, `PrecisionLoss` if
+/// either input is non-zero but below one base unit or a non-zero cross rate
+/// rounds to zero, and the errors of [`Price::from_raw`] for the result.
+pub fn cross_rate<const A: u32, const B: u32, const OUT: u32>(
+    base_quote_a: Price<A>,
+    base_quote_b: Price<B>,
+    rounding: RoundingMode,
+) -> Result<Price<OUT>, FloatError> {
+    let raw_a = price_to_raw(base_quote_a)?;
+    let raw_b = price_to_raw(base_quote_b)?;
+    if raw_b == 0 {
+        return Err(FloatError::DivisionByZero);
+    }
+    let numerator_scale = scale(B + OUT)?;
+    let denominator = U256::from(raw_b)
+        .checked_mul(scale(A)?)
+        .ok_or(FloatError::Overflow)?;
+    let raw = mul_div_u256(U256::from(raw_a), numerator_scale, denominator, rounding)?;
+    raw_to_price(raw, raw_a != 0)
+}
+
+/// Computes the `quote/base` price from a `base/quote` price, e.g. SOL per
+/// USD from USD per SOL: `10^(D + OUT) / raw`, rounded once with `rounding`.
+///
+/// Fails with `DivisionByZero` for a zero price, `PrecisionLoss` for a
+/// non-zero price below one base unit or an inverse that rounds to zero, and
+/// the errors of [`Price::from_raw`] for the result.
+pub fn invert_price<const D: u32, const OUT: u32>(
+    price: Price<D>,
+    rounding: RoundingMode,
+) -> Result<Price<OUT>, FloatError> {
+    let raw_price = price_to_raw(price)?;
+    if raw_price == 0 {
+        return Err(FloatError::DivisionByZero);
+    }
+    let raw = mul_div_u256(U256::ONE, scale(D + OUT)?, U256::from(raw_price), rounding)?;
+    raw_to_price(raw, true)
+}
+
+// Raw base units of a price, refusing to round a non-zero price to zero.
+fn price_to_raw<const D: u32>(price: Price<D>) -> Result<u64, FloatError> {
+    let raw = price.to_raw()?;
+    if raw == 0 && price.value() != 0.0 {
+        return Err(FloatError::PrecisionLoss);
+    }
+    Ok(raw)
+}
+
+fn raw_to_price<const D: u32>(raw: U256, expect_non_zero: bool) -> Result<Price<D>, FloatError> {
+    if raw.is_zero() && expect_non_zero {
+        return Err(FloatError::PrecisionLoss);
+    }
+    let raw = raw.try_to_u128().ok_or(FloatError::Overflow)?;
+    let raw = u64::try_from(raw).map_err(|_| FloatError::Overflow)?;
+    Price::from_raw(raw)
+}
+
+fn scale(decimals: u32) -> Result<U256, FloatError> {
+    U256::pow10(decimals).ok_or(FloatError::OutOfRange)
+}
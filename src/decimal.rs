@@ -0,0 +1,238 @@
+//! Exact base-10 amounts with a configurable scale.
+//!
+//! A [`Decimal`] is `mantissa / 10^scale` with a u128 mantissa, so cents,
+//! satoshis or 18-decimal token amounts are held exactly and sums of them are
+//! exact. Products and quotients keep the scale of the left operand and are
+//! rounded once, with a [`RoundingMode`] the caller picks.
+//!
+//! Conversion from f64 starts from the shortest decimal that reads back as
+//! the float (so `0.1` is 0.1, not its binary expansion) and rounds that to
+//! the requested scale; conversion to f64 is correctly rounded.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::big_decimal::BigDecimal256;
+use crate::error::FloatError;
+use crate::mul_div::mul_div_u256;
+use crate::quantize::RoundingMode;
+use crate::u256::U256;
+
+/// Largest scale; `10^38` is the largest power of ten below `2^128`.
+pub const MAX_SCALE: u32 = 38;
+
+/// `mantissa / 10^scale`. Equality and ordering compare values, so `1.0`
+/// and `1.00` are equal.
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    mantissa: u128,
+    scale: u32,
+}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal {
+        mantissa: 0,
+        scale: 0,
+    };
+    pub const ONE: Decimal = Decimal {
+        mantissa: 1,
+        scale: 0,
+    };
+
+    /// `mantissa / 10^scale`, e.g. `Decimal::new(1999, 2)` for 19.99. Fails
+    /// with `OutOfRange` past [`MAX_SCALE`].
+    pub fn new(mantissa: u128, scale: u32) -> Result<Self, FloatError> {
+        if scale > MAX_SCALE {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(Self { mantissa, scale })
+    }
+
+    pub fn mantissa(self) -> u128 {
+        self.mantissa
+    }
+
+    pub fn scale(self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.mantissa == 0
+    }
+
+    /// The same value at `scale`, rounding with `rounding` if digits are
+    /// dropped. Fails with `OutOfRange` past [`MAX_SCALE`] and `Overflow` if
+    /// the mantissa no longer fits.
+    pub fn rescale(self, scale: u32, rounding: RoundingMode) -> Result<Self, FloatError> {
+        if scale > MAX_SCALE {
+            return Err(FloatError::OutOfRange);
+        }
+        let mantissa = if scale >= self.scale {
+            self.mantissa
+                .checked_mul(10u128.pow(scale - self.scale))
+                .ok_or(FloatError::Overflow)?
+        } else {
+            let divisor = U256::from(10u128.pow(self.scale - scale));
+            mul_div_u256(self.mantissa.into(), U256::ONE, divisor, rounding)?.low_u128()
+        };
+        Ok(Self { mantissa, scale })
+    }
+
+    /// The value rounded to at most `scale` digits, ties to even, e.g. 2.675
+    /// to 2.68 and 2.665 to 2.66. Keeps the current scale if it is already
+    /// `scale` or less. Fails with `OutOfRange` past [`MAX_SCALE`].
+    pub fn round_half_even_to_scale(self, scale: u32) -> Result<Self, FloatError> {
+        if scale > MAX_SCALE {
+            return Err(FloatError::OutOfRange);
+        }
+        if scale >= self.scale {
+            return Ok(self);
+        }
+        self.rescale(scale, RoundingMode::HalfEven)
+    }
+
+    /// The exact sum, at the larger of the two scales. Fails with `Overflow`.
+    pub fn checked_add(self, rhs: Self) -> Result<Self, FloatError> {
+        let (lhs, rhs) = align(self, rhs)?;
+        let mantissa = lhs
+            .mantissa
+            .checked_add(rhs.mantissa)
+            .ok_or(FloatError::Overflow)?;
+        Ok(Self { mantissa, ..lhs })
+    }
+
+    /// The exact difference, at the larger of the two scales. Fails with
+    /// `OutOfRange` if `rhs` is larger, as the type has no negative values.
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, FloatError> {
+        let (lhs, rhs) = align(self, rhs)?;
+        let mantissa = lhs
+            .mantissa
+            .checked_sub(rhs.mantissa)
+            .ok_or(FloatError::OutOfRange)?;
+        Ok(Self { mantissa, ..lhs })
+    }
+
+    /// `self * rhs` at `self`'s scale, e.g. an amount times a rate is still an
+    /// amount in the same units. Fails with `Overflow`.
+    pub fn checked_mul(self, rhs: Self, rounding: RoundingMode) -> Result<Self, FloatError> {
+        // (a / 10^sa) * (b / 10^sb) = (a * b / 10^sb) / 10^sa
+        let mantissa = mul_div_u256(
+            self.mantissa.into(),
+            rhs.mantissa.into(),
+            U256::from(10u128.pow(rhs.scale)),
+            rounding,
+        )?;
+        self.with_mantissa(mantissa)
+    }
+
+    /// `self / rhs` at `self`'s scale. Fails with `DivisionByZero` and
+    /// `Overflow`.
+    pub fn checked_div(self, rhs: Self, rounding: RoundingMode) -> Result<Self, FloatError> {
+        // (a / 10^sa) / (b / 10^sb) = (a * 10^sb / b) / 10^sa
+        let mantissa = mul_div_u256(
+            self.mantissa.into(),
+            U256::from(10u128.pow(rhs.scale)),
+            rhs.mantissa.into(),
+            rounding,
+        )?;
+        self.with_mantissa(mantissa)
+    }
+
+    /// `value` at `scale`, rounded with `rounding`.
+    ///
+    /// The float is read as the shortest decimal that prints as it, so
+    /// `from_f64(2.675, 2, HalfEven)` is 2.68 even though the binary value is
+    /// slightly below 2.675. Fails with `NaNOperand` for NaN, `Overflow` for
+    /// infinities and values too large for the mantissa, and `OutOfRange` for
+    /// negative values (unless they round to zero) and scales past
+    /// [`MAX_SCALE`].
+    pub fn from_f64(value: f64, scale: u32, rounding: RoundingMode) -> Result<Self, FloatError> {
+        if scale > MAX_SCALE {
+            return Err(FloatError::OutOfRange);
+        }
+        let rounded = BigDecimal256::try_from(value)?.round_to_decimals(scale, rounding)?;
+        if rounded.is_sign_negative() {
+            return Err(FloatError::OutOfRange);
+        }
+        let (_, coefficient, exponent) = rounded.parts();
+        // Rounding left at most `scale` fractional digits
+        let shift = u32::try_from(exponent as i64 + scale as i64).unwrap();
+        let mantissa = U256::pow10(shift)
+            .and_then(|factor| coefficient.checked_mul(factor))
+            .and_then(U256::try_to_u128)
+            .ok_or(FloatError::Overflow)?;
+        Ok(Self { mantissa, scale })
+    }
+
+    /// [`from_f64`](Self::from_f64), failing with `PrecisionLoss` instead of
+    /// rounding, so `0.1` converts at scale 1 and up but `0.125` needs 3.
+    pub fn from_f64_exact(value: f64, scale: u32) -> Result<Self, FloatError> {
+        let down = Self::from_f64(value, scale, RoundingMode::Floor)?;
+        if down != Self::from_f64(value, scale, RoundingMode::Ceil)? {
+            return Err(FloatError::PrecisionLoss);
+        }
+        Ok(down)
+    }
+
+    /// The nearest f64.
+    pub fn to_f64(self) -> f64 {
+        // Float parsing is correctly rounded for any number of digits
+        format!("{}e-{}", self.mantissa, self.scale)
+            .parse()
+            .unwrap()
+    }
+
+    fn with_mantissa(self, mantissa: U256) -> Result<Self, FloatError> {
+        let mantissa = mantissa.try_to_u128().ok_or(FloatError::Overflow)?;
+        Ok(Self { mantissa, ..self })
+    }
+}
+
+// Both operands at the larger scale
+fn align(a: Decimal, b: Decimal) -> Result<(Decimal, Decimal), FloatError> {
+    let scale = a.scale.max(b.scale);
+    Ok((
+        a.rescale(scale, RoundingMode::Floor)?,
+        b.rescale(scale, RoundingMode::Floor)?,
+    ))
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // a / 10^sa against b / 10^sb, both sides below 2^128 * 10^38 < 2^256
+        let lhs = U256::from(self.mantissa).wrapping_mul(U256::from(10u128.pow(other.scale)));
+        let rhs = U256::from(other.mantissa).wrapping_mul(U256::from(10u128.pow(self.scale)));
+        lhs.cmp(&rhs)
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Every digit of the scale, e.g. `19.90` for `Decimal::new(1990, 2)`.
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = format!(
+            "{:0>width$}",
+            self.mantissa,
+            width = self.scale as usize + 1
+        );
+        let (integer, fraction) = digits.split_at(digits.len() - self.scale as usize);
+        if fraction.is_empty() {
+            f.pad_integral(true, "", integer)
+        } else {
+            f.pad_integral(true, "", &format!("{}.{}", integer, fraction))
+        }
+    }
+}
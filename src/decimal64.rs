@@ -0,0 +1,460 @@
+//! IEEE 754-2008 decimal64 in the binary integer decimal (BID) encoding.
+//!
+//! Binary floats cannot represent most decimal fractions, which is where
+//! `0.1 + 0.2 != 0.3` comes from. A decimal64 is `coefficient * 10^exponent`
+//! with a coefficient of up to 16 decimal digits, so amounts quoted in decimal
+//! are exact and `0.1 + 0.2 == 0.3` holds.
+//!
+//! Arithmetic follows the standard: results are exact whenever they fit in 16
+//! digits and are otherwise rounded half-even, exponents follow the preferred
+//! exponent rules, and results below the smallest exponent round gradually to
+//! zero. Unlike the standard there are no infinities or NaNs: operations fail
+//! with `Overflow` or `DivisionByZero` instead, and `from_bits` rejects
+//! encoded specials with `NaNOperand`.
+//!
+//! The same value can have several encodings (`1.0` and `1.00` differ in
+//! exponent), so equality and ordering compare values, not bits.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::FloatError;
+
+pub const COEFFICIENT_DIGITS: u32 = 16;
+pub const MAX_EXPONENT: i32 = 369;
+pub const MIN_EXPONENT: i32 = -398;
+
+const MAX_COEFFICIENT: u64 = 9_999_999_999_999_999;
+const EXPONENT_BIAS: i32 = 398;
+const SIGN_BIT: u64 = 1 << 63;
+// Bits 62..61 both set selects the encoding for coefficients above 2^53
+const LARGE_FORM: u64 = 0b11 << 61;
+// Bits 62..59 all set encode infinity or NaN
+const SPECIAL: u64 = 0b1111 << 59;
+
+#[derive(Clone, Copy)]
+pub struct Decimal64(u64);
+
+// Unpacked form: sign, coefficient, exponent
+#[derive(Clone, Copy)]
+struct Parts {
+    negative: bool,
+    coefficient: u128,
+    exponent: i32,
+}
+
+impl Decimal64 {
+    pub const ZERO: Decimal64 = Decimal64((EXPONENT_BIAS as u64) << 53);
+    pub const ONE: Decimal64 = Decimal64(((EXPONENT_BIAS as u64) << 53) | 1);
+    /// 9999999999999999e369
+    pub const MAX: Decimal64 = Decimal64(0x77fb_86f2_6fc0_ffff);
+    pub const MIN: Decimal64 = Decimal64(SIGN_BIT | 0x77fb_86f2_6fc0_ffff);
+
+    /// `coefficient * 10^exponent`, e.g. `Decimal64::new(1, -1)` for 0.1.
+    ///
+    /// Fails with `OutOfRange` unless the value is exactly representable with
+    /// this coefficient and exponent.
+    pub fn new(coefficient: i64, exponent: i32) -> Result<Self, FloatError> {
+        Self::from_parts(coefficient < 0, coefficient.unsigned_abs(), exponent)
+    }
+
+    /// Like [`new`](Self::new), with the sign given separately so that `-0`
+    /// can be written.
+    pub fn from_parts(negative: bool, coefficient: u64, exponent: i32) -> Result<Self, FloatError> {
+        if coefficient > MAX_COEFFICIENT || !(MIN_EXPONENT..=MAX_EXPONENT).contains(&exponent) {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(Self::encode(negative, coefficient, exponent))
+    }
+
+    /// Sign, coefficient and exponent.
+    pub fn parts(self) -> (bool, u64, i32) {
+        let parts = self.unpack();
+        (parts.negative, parts.coefficient as u64, parts.exponent)
+    }
+
+    /// Reads a BID-encoded decimal64. Fails with `NaNOperand` for infinities
+    /// and NaNs, and with `OutOfRange` for non-canonical coefficients.
+    pub fn from_bits(bits: u64) -> Result<Self, FloatError> {
+        if bits & SPECIAL == SPECIAL {
+            return Err(FloatError::NaNOperand);
+        }
+        let decimal = Self(bits);
+        if decimal.unpack().coefficient > MAX_COEFFICIENT as u128 {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(decimal)
+    }
+
+    pub fn to_bits(self) -> u64 {
+        self.0
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.unpack().coefficient == 0
+    }
+
+    pub fn is_sign_negative(self) -> bool {
+        self.0 & SIGN_BIT != 0
+    }
+
+    /// The nearest f64.
+    pub fn to_f64(self) -> f64 {
+        let (negative, coefficient, exponent) = self.parts();
+        let sign = if negative { "-" } else { "" };
+        // Float parsing is correctly rounded, so this is the nearest f64
+        format!("{}{}e{}", sign, coefficient, exponent)
+            .parse()
+            .unwrap()
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Result<Self, FloatError> {
+        add(self.unpack(), rhs.unpack())
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, FloatError> {
+        let mut rhs = rhs.unpack();
+        rhs.negative = !rhs.negative;
+        add(self.unpack(), rhs)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, FloatError> {
+        let (a, b) = (self.unpack(), rhs.unpack());
+        round(Parts {
+            negative: a.negative != b.negative,
+            coefficient: a.coefficient * b.coefficient,
+            exponent: a.exponent + b.exponent,
+        })
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Result<Self, FloatError> {
+        let (a, b) = (self.unpack(), rhs.unpack());
+        if b.coefficient == 0 {
+            return Err(FloatError::DivisionByZero);
+        }
+        let negative = a.negative != b.negative;
+        let preferred = a.exponent - b.exponent;
+        if a.coefficient == 0 {
+            return round(Parts {
+                negative,
+                coefficient: 0,
+                exponent: preferred,
+            });
+        }
+        // Scale the dividend so that the quotient has at least 22 digits,
+        // 6 more than are kept
+        let shift = COEFFICIENT_DIGITS - digits(a.coefficient) + 22;
+        let dividend = a.coefficient * pow10(shift);
+        let mut quotient = dividend / b.coefficient;
+        let remainder = dividend % b.coefficient;
+        if remainder != 0 {
+            // The true quotient lies strictly between two integers and every
+            // rounding boundary is an even integer, so an odd stand-in rounds
+            // the same way
+            quotient |= 1;
+        }
+        let mut exponent = preferred - shift as i32;
+        // Exact quotients take the exponent closest to the preferred one
+        while remainder == 0 && exponent < preferred {
+            let (shorter, digit) = (quotient / 10, quotient % 10);
+            if digit != 0 {
+                break;
+            }
+            quotient = shorter;
+            exponent += 1;
+        }
+        round(Parts {
+            negative,
+            coefficient: quotient,
+            exponent,
+        })
+    }
+
+    fn encode(negative: bool, coefficient: u64, exponent: i32) -> Self {
+        let sign = if negative { SIGN_BIT } else { 0 };
+        let biased = (exponent + EXPONENT_BIAS) as u64;
+        if coefficient < 1 << 53 {
+            Self(sign | biased << 53 | coefficient)
+        } else {
+            Self(sign | LARGE_FORM | biased << 51 | (coefficient & ((1 << 51) - 1)))
+        }
+    }
+
+    fn unpack(self) -> Parts {
+        let bits = self.0;
+        let (biased, coefficient) = if bits & LARGE_FORM == LARGE_FORM {
+            (
+                (bits >> 51) & 0x3ff,
+                (0b100 << 51) | (bits & ((1 << 51) - 1)),
+            )
+        } else {
+            ((bits >> 53) & 0x3ff, bits & ((1 << 53) - 1))
+        };
+        Parts {
+            negative: bits & SIGN_BIT != 0,
+            coefficient: coefficient as u128,
+            exponent: biased as i32 - EXPONENT_BIAS,
+        }
+    }
+}
+
+fn add(a: Parts, b: Parts) -> Result<Decimal64, FloatError> {
+    // Order so that `high` has the larger exponent
+    let (high, low) = if a.exponent >= b.exponent {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let gap = (high.exponent - low.exponent) as u32;
+    let (high_coefficient, low_coefficient, exponent) = if high.coefficient == 0 && gap > 21 {
+        (0, low.coefficient, low.exponent)
+    } else if gap <= 21 {
+        // Exact: at most 16 + 21 digits
+        (high.coefficient * pow10(gap), low.coefficient, low.exponent)
+    } else {
+        // `low` only affects rounding. Keep `high` at 38 digits and `low`
+        // truncated to one digit below its last, with a 5 standing in for any
+        // discarded remainder so that no false tie appears.
+        let (truncated, remainder) = if gap - 21 > COEFFICIENT_DIGITS {
+            (0, low.coefficient)
+        } else {
+            let divisor = pow10(gap - 21);
+            (low.coefficient / divisor, low.coefficient % divisor)
+        };
+        let sticky = if remainder != 0 { 5 } else { 0 };
+        (
+            high.coefficient * pow10(22),
+            truncated * 10 + sticky,
+            high.exponent - 22,
+        )
+    };
+    let high_negative = high.negative;
+    let (negative, coefficient) = if high_negative == low.negative {
+        (high_negative, high_coefficient + low_coefficient)
+    } else {
+        match high_coefficient.cmp(&low_coefficient) {
+            Ordering::Greater => (high_negative, high_coefficient - low_coefficient),
+            Ordering::Less => (low.negative, low_coefficient - high_coefficient),
+            // An exact zero from opposite signs is +0
+            Ordering::Equal => (false, 0),
+        }
+    };
+    round(Parts {
+        negative,
+        coefficient,
+        exponent,
+    })
+}
+
+// Rounds half-even to 16 digits and the exponent range, then encodes.
+fn round(parts: Parts) -> Result<Decimal64, FloatError> {
+    let Parts {
+        negative,
+        mut coefficient,
+        mut exponent,
+    } = parts;
+    let excess_digits = digits(coefficient).saturating_sub(COEFFICIENT_DIGITS);
+    let below_range = (MIN_EXPONENT - exponent).max(0) as u32;
+    let drop = excess_digits.max(below_range);
+    if drop > 0 {
+        coefficient = if drop > 38 {
+            // Everything is discarded and less than half a unit remains
+            0
+        } else {
+            round_half_even(coefficient, pow10(drop))
+        };
+        exponent += drop as i32;
+        if coefficient > MAX_COEFFICIENT as u128 {
+            // Rounded up to 10^16
+            coefficient /= 10;
+            exponent += 1;
+        }
+    }
+    if exponent > MAX_EXPONENT {
+        if coefficient == 0 {
+            exponent = MAX_EXPONENT;
+        } else {
+            // Pad the coefficient with zeros if that brings the exponent in
+            // range
+            let pad = (exponent - MAX_EXPONENT) as u32;
+            if pad >= COEFFICIENT_DIGITS || coefficient * pow10(pad) > MAX_COEFFICIENT as u128 {
+                return Err(FloatError::Overflow);
+            }
+            coefficient *= pow10(pad);
+            exponent = MAX_EXPONENT;
+        }
+    }
+    Ok(Decimal64::encode(negative, coefficient as u64, exponent))
+}
+
+fn round_half_even(value: u128, divisor: u128) -> u128 {
+    let quotient = value / divisor;
+    let remainder = value % divisor;
+    let half = divisor / 2;
+    if remainder > half || (remainder == half && quotient % 2 == 1) {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
+
+fn pow10(exponent: u32) -> u128 {
+    10u128.pow(exponent)
+}
+
+fn digits(value: u128) -> u32 {
+    if value == 0 { 1 } else { value.ilog10() + 1 }
+}
+
+// Compares magnitudes of two coefficient/exponent pairs.
+fn cmp_magnitude(a: Parts, b: Parts) -> Ordering {
+    if a.coefficient == 0 || b.coefficient == 0 {
+        return a.coefficient.cmp(&b.coefficient);
+    }
+    // Compare the position of the leading digit first, then the digits
+    let a_top = a.exponent + digits(a.coefficient) as i32;
+    let b_top = b.exponent + digits(b.coefficient) as i32;
+    if a_top != b_top {
+        return a_top.cmp(&b_top);
+    }
+    // Same leading position: align to the smaller exponent (at most 15 digits
+    // apart, so this fits)
+    let exponent = a.exponent.min(b.exponent);
+    let a_aligned = a.coefficient * pow10((a.exponent - exponent) as u32);
+    let b_aligned = b.coefficient * pow10((b.exponent - exponent) as u32);
+    a_aligned.cmp(&b_aligned)
+}
+
+impl PartialEq for Decimal64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal64 {}
+
+impl Ord for Decimal64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.unpack(), other.unpack());
+        // -0 == +0
+        let a_negative = a.negative && a.coefficient != 0;
+        let b_negative = b.negative && b.coefficient != 0;
+        match (a_negative, b_negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(a, b),
+            (true, true) => cmp_magnitude(b, a),
+        }
+    }
+}
+
+impl PartialOrd for Decimal64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Default for Decimal64 {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+/// Parses plain (`-12.340`) or exponent (`1.5E-7`, `2e3`) notation exactly.
+///
+/// Fails with `PrecisionLoss` if the digits do not fit in 16 after dropping
+/// leading zeros (and trailing zeros that can move into the exponent),
+/// `OutOfRange` if the exponent does not fit, and `NaNOperand` for anything
+/// that is not a number.
+impl FromStr for Decimal64 {
+    type Err = FloatError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (negative, unsigned) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+            Some(index) => {
+                let exponent: i32 = unsigned[index + 1..]
+                    .parse()
+                    .map_err(|_| FloatError::NaNOperand)?;
+                (&unsigned[..index], exponent)
+            }
+            None => (unsigned, 0),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let all_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if integer.len() + fraction.len() == 0 || !all_digits(integer) || !all_digits(fraction) {
+            return Err(FloatError::NaNOperand);
+        }
+        let digits = format!("{}{}", integer, fraction);
+        let mut significant = digits.trim_start_matches('0');
+        let mut exponent = exponent
+            .checked_sub(fraction.len() as i32)
+            .ok_or(FloatError::OutOfRange)?;
+        while significant.len() > COEFFICIENT_DIGITS as usize && significant.ends_with('0') {
+            significant = &significant[..significant.len() - 1];
+            exponent += 1;
+        }
+        if significant.len() > COEFFICIENT_DIGITS as usize {
+            return Err(FloatError::PrecisionLoss);
+        }
+        let coefficient = if significant.is_empty() {
+            0
+        } else {
+            significant.parse().unwrap()
+        };
+        Decimal64::from_parts(negative, coefficient, exponent)
+    }
+}
+
+/// Scientific-string form of the General Decimal Arithmetic specification,
+/// e.g. `0.30`, `-1.5E+7`, `1E-9`. The exponent is preserved, so `1.0` and
+/// `1.00` print differently.
+impl fmt::Display for Decimal64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (negative, coefficient, exponent) = self.parts();
+        write_scientific(f, negative, &coefficient.to_string(), exponent)
+    }
+}
+
+// The specification's to-scientific-string: plain notation for exponents up
+// to zero with at most six leading zeros after the point, otherwise one digit
+// before the point and an explicit exponent.
+pub(crate) fn write_scientific(
+    f: &mut fmt::Formatter,
+    negative: bool,
+    digits: &str,
+    exponent: i32,
+) -> fmt::Result {
+    let len = digits.len() as i64;
+    let exponent = exponent as i64;
+    let adjusted = exponent + len - 1;
+    let sign = if negative { "-" } else { "" };
+    if exponent <= 0 && adjusted >= -6 {
+        if exponent == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let point = len + exponent;
+        if point > 0 {
+            let (integer, fraction) = digits.split_at(point as usize);
+            write!(f, "{}{}.{}", sign, integer, fraction)
+        } else {
+            let zeros = "0".repeat(-point as usize);
+            write!(f, "{}0.{}{}", sign, zeros, digits)
+        }
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let point = if rest.is_empty() { "" } else { "." };
+        write!(f, "{}{}{}{}E{:+}", sign, first, point, rest, adjusted)
+    }
+}
+
+impl fmt::Debug for Decimal64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Decimal64({})", self)
+    }
+}
@@ -0,0 +1,107 @@
+//! Off-chain simulation of the float environments a replica might run in.
+//!
+//! SBF and SSE2 compute every f64 operation with one correct rounding, but a
+//! replica of on-chain math can still run somewhere that does not: a 32-bit
+//! x87 build that rounds twice, or a process whose MXCSR has flush-to-zero
+//! set by a numerics library. [`compare`] runs a workload in two such
+//! [`Environment`]s and lists every operation whose bits differ, which turns
+//! "floats are non-deterministic" into concrete inputs a team can look at.
+//!
+//! Only the native environment touches the float hardware; the others are
+//! emulated in software, so the report is the same on every host.
+
+use crate::double_ops::BinaryOp;
+use crate::softref;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Environment {
+    /// This host's float unit.
+    Native,
+    /// The integer-only IEEE 754 reference in [`crate::softref`], which is
+    /// what SBF and any correctly rounding target must match.
+    SoftFloat,
+    /// SSE with FTZ and DAZ set: subnormal operands read as zero and
+    /// subnormal results are flushed to zero.
+    FlushToZero,
+    /// x87 with 64-bit precision control: each result is rounded to a 64-bit
+    /// significand and again to f64 when stored.
+    X87Extended,
+}
+
+impl Environment {
+    /// `op(a, b)` as this environment computes it. Division by zero follows
+    /// IEEE 754 and yields an infinity or NaN.
+    pub fn compute(self, op: BinaryOp, a: f64, b: f64) -> f64 {
+        match self {
+            Environment::Native => match op {
+                BinaryOp::Add => a + b,
+                BinaryOp::Multiply => a * b,
+                BinaryOp::Divide => a / b,
+            },
+            Environment::SoftFloat => match op {
+                BinaryOp::Add => softref::add_f64(a, b),
+                BinaryOp::Multiply => softref::mul_f64(a, b),
+                BinaryOp::Divide => softref::div_f64(a, b),
+            },
+            Environment::FlushToZero => {
+                let result = Environment::SoftFloat.compute(op, flush(a), flush(b));
+                flush(result)
+            }
+            Environment::X87Extended => match op {
+                BinaryOp::Add => softref::add_f64_x87(a, b),
+                BinaryOp::Multiply => softref::mul_f64_x87(a, b),
+                BinaryOp::Divide => softref::div_f64_x87(a, b),
+            },
+        }
+    }
+}
+
+/// One operation of a workload whose result bits differ between two
+/// environments.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Divergence {
+    /// Position in the workload.
+    pub index: usize,
+    pub op: BinaryOp,
+    pub a: f64,
+    pub b: f64,
+    pub baseline_bits: u64,
+    pub candidate_bits: u64,
+}
+
+/// Runs every `(op, a, b)` of `workload` in both environments and returns
+/// the operations whose results differ in any bit, in workload order.
+///
+/// NaN results are compared by bits too, so differing NaN signs or payloads
+/// are reported.
+pub fn compare(
+    workload: &[(BinaryOp, f64, f64)],
+    baseline: Environment,
+    candidate: Environment,
+) -> Vec<Divergence> {
+    workload
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &(op, a, b))| {
+            let baseline_bits = baseline.compute(op, a, b).to_bits();
+            let candidate_bits = candidate.compute(op, a, b).to_bits();
+            (baseline_bits != candidate_bits).then_some(Divergence {
+                index,
+                op,
+                a,
+                b,
+                baseline_bits,
+                candidate_bits,
+            })
+        })
+        .collect()
+}
+
+// Subnormals to zero of the same sign.
+fn flush(x: f64) -> f64 {
+    if x.is_subnormal() {
+        0.0_f64.copysign(x)
+    } else {
+        x
+    }
+}
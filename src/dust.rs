@@ -0,0 +1,92 @@
+//! Accounting for the remainders that quantization throws away.
+//!
+//! Every floor-rounded fee or proration drops a sub-unit remainder. One of them
+//! is negligible, but a protocol that drops one per swap leaks value steadily,
+//! and nothing in its books says where it went. A [`DustTracker`] collects
+//! those remainders in integer fine units and reports when they add up to a
+//! whole payable unit, so the dust can be swept to a treasury on a schedule
+//! every validator agrees on.
+
+use solana_program::program_error::ProgramError;
+
+use crate::error::FloatError;
+
+/// Sub-unit remainders accumulated in fine units, where `unit` fine units make
+/// one payable unit (e.g. `10^6` when fees are computed at 12 decimals and paid
+/// at 6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DustTracker {
+    unit: u64,
+    pending: u64,
+}
+
+impl DustTracker {
+    /// Size of the serialized form: `unit: u64 | pending: u64`.
+    pub const PACKED_LEN: usize = 16;
+
+    /// Fails with `OutOfRange` for a zero unit.
+    pub fn new(unit: u64) -> Result<Self, FloatError> {
+        if unit == 0 {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(Self { unit, pending: 0 })
+    }
+
+    pub fn unit(&self) -> u64 {
+        self.unit
+    }
+
+    /// Dust collected so far, in fine units.
+    pub fn pending(&self) -> u64 {
+        self.pending
+    }
+
+    /// Whole payable units the pending dust adds up to.
+    pub fn payable(&self) -> u64 {
+        self.pending / self.unit
+    }
+
+    /// Adds `dust` fine units and returns [`payable`](Self::payable), so a
+    /// non-zero result means there is something to sweep.
+    ///
+    /// Fails with `Overflow`, leaving the tracker unchanged, if the pending
+    /// dust would not fit in a u64.
+    pub fn record(&mut self, dust: u64) -> Result<u64, FloatError> {
+        self.pending = self.pending.checked_add(dust).ok_or(FloatError::Overflow)?;
+        Ok(self.payable())
+    }
+
+    /// Floors `amount` fine units to whole payable units, records the
+    /// remainder as dust and returns the whole units.
+    pub fn quantize(&mut self, amount: u64) -> Result<u64, FloatError> {
+        self.record(amount % self.unit)?;
+        Ok(amount / self.unit)
+    }
+
+    /// Removes and returns the whole payable units, keeping the sub-unit rest
+    /// for later.
+    pub fn sweep(&mut self) -> u64 {
+        let payable = self.payable();
+        self.pending %= self.unit;
+        payable
+    }
+
+    pub fn pack(&self) -> [u8; Self::PACKED_LEN] {
+        let mut dst = [0u8; Self::PACKED_LEN];
+        dst[0..8].copy_from_slice(&self.unit.to_le_bytes());
+        dst[8..16].copy_from_slice(&self.pending.to_le_bytes());
+        dst
+    }
+
+    /// Deserializes and validates a tracker written by `pack`.
+    pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::PACKED_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let unit = u64::from_le_bytes(src[0..8].try_into().unwrap());
+        let pending = u64::from_le_bytes(src[8..16].try_into().unwrap());
+        let mut tracker = Self::new(unit).map_err(|_| ProgramError::InvalidAccountData)?;
+        tracker.pending = pending;
+        Ok(tracker)
+    }
+}
@@ -0,0 +1,65 @@
+use solana_program::program_error::ProgramError;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatError {
+    DivisionByZero,
+    NaNOperand,
+    Overflow,
+    PrecisionLoss,
+    OutOfRange,
+}
+
+impl fmt::Display for FloatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            FloatError::DivisionByZero => "division by zero",
+            FloatError::NaNOperand => "NaN or non-finite operand",
+            FloatError::Overflow => "result overflows",
+            FloatError::PrecisionLoss => "result would lose precision",
+            FloatError::OutOfRange => "argument out of range",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for FloatError {}
+
+impl FloatError {
+    /// First `ProgramError::Custom` code, well above the ranges programs and
+    /// Anchor number their own errors from.
+    pub const CUSTOM_CODE_BASE: u32 = 0x5f10_0000;
+
+    const ALL: [FloatError; 5] = [
+        FloatError::DivisionByZero,
+        FloatError::NaNOperand,
+        FloatError::Overflow,
+        FloatError::PrecisionLoss,
+        FloatError::OutOfRange,
+    ];
+
+    /// The `ProgramError::Custom` code this error fails an instruction with.
+    /// Codes are part of the on-chain interface and never renumbered.
+    pub const fn code(self) -> u32 {
+        Self::CUSTOM_CODE_BASE
+            + match self {
+                FloatError::DivisionByZero => 0,
+                FloatError::NaNOperand => 1,
+                FloatError::Overflow => 2,
+                FloatError::PrecisionLoss => 3,
+                FloatError::OutOfRange => 4,
+            }
+    }
+
+    /// The error behind a `ProgramError::Custom` code, for clients reading a
+    /// failed transaction.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|error| error.code() == code)
+    }
+}
+
+impl From<FloatError> for ProgramError {
+    fn from(error: FloatError) -> Self {
+        ProgramError::Custom(error.code())
+    }
+}
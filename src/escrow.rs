@@ -0,0 +1,72 @@
+//! Splitting interest between the two sides of an escrow.
+//!
+//! Structured products and escrows hold principal from two parties and owe
+//! each a pro-rata part of the interest it earns. Both sides have to arrive at
+//! the same split to the base unit, so the rounding and the odd unit it can
+//! leave over are decided by an explicit [`SplitPolicy`].
+
+use crate::error::FloatError;
+use crate::mul_div::mul_div_u128;
+use crate::quantize::RoundingMode;
+
+/// Who receives the base unit left over when neither share divides evenly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitPolicy {
+    FavorA,
+    FavorB,
+    /// The party with the larger principal, `a` on a tie.
+    FavorLarger,
+    /// Nobody: the unit stays in the escrow and is reported as dust.
+    Retain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterestSplit {
+    pub a: u64,
+    pub b: u64,
+    /// Interest paid to neither side; `a + b + dust` is always the accrued
+    /// interest.
+    pub dust: u64,
+}
+
+/// Divides `accrued` interest in proportion to `principal_a` and
+/// `principal_b`.
+///
+/// Each share is rounded down, which leaves at most one base unit over; the
+/// policy decides where it goes. Fails with `DivisionByZero` if both
+/// principals are zero.
+pub fn split_interest(
+    accrued: u64,
+    principal_a: u64,
+    principal_b: u64,
+    policy: SplitPolicy,
+) -> Result<InterestSplit, FloatError> {
+    let total = principal_a as u128 + principal_b as u128;
+    if total == 0 {
+        return Err(FloatError::DivisionByZero);
+    }
+    let share = |principal: u64| {
+        // At most `accrued`, so it fits in a u64
+        mul_div_u128(
+            accrued as u128,
+            principal as u128,
+            total,
+            RoundingMode::Floor,
+        )
+        .map(|share| share as u64)
+    };
+    let mut split = InterestSplit {
+        a: share(principal_a)?,
+        b: share(principal_b)?,
+        dust: 0,
+    };
+    let leftover = accrued - split.a - split.b;
+    match policy {
+        SplitPolicy::FavorA => split.a += leftover,
+        SplitPolicy::FavorB => split.b += leftover,
+        SplitPolicy::FavorLarger if principal_a >= principal_b => split.a += leftover,
+        SplitPolicy::FavorLarger => split.b += leftover,
+        SplitPolicy::Retain => split.dust = leftover,
+    }
+    Ok(split)
+}
@@ -0,0 +1,324 @@
+//! Fixed-point arithmetic that reproduces the EVM math libraries bit for bit.
+//!
+//! Cross-chain protocols often keep the same balance on Solana and on an EVM
+//! chain. If both sides round differently the books drift apart, so these ports
+//! follow the upstream integer algorithms step by step, including where they
+//! truncate and which magic constants they multiply by:
+//!
+//! - [`Ud60x18`]: PRBMath v4 `UD60x18`, an unsigned `uint256` scaled by 10^18.
+//! - [`Fixed64x64`]: ABDK `ABDKMath64x64`, a signed `int128` with 64 fractional
+//!   bits.
+//!
+//! A Solidity revert maps to a `FloatError`. [`reconcile_amounts`] compares a
+//! balance held on both chains once each side's token decimals are accounted for.
+
+use std::cmp::Ordering;
+
+use crate::error::FloatError;
+use crate::u256::U256;
+
+/// PRBMath unsigned 60.18-decimal fixed-point number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Ud60x18(pub U256);
+
+/// ABDK signed 64.64-bit fixed-point number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fixed64x64(pub i128);
+
+const UNIT: u128 = 1_000_000_000_000_000_000;
+const HALF_UNIT: u128 = 500_000_000_000_000_000;
+const LOG2_E: u128 = 1_442_695_040_888_963_407;
+const EXP_MAX_INPUT: u128 = 133_084_258_667_509_499_440;
+const EXP2_MAX_INPUT: u128 = 192 * UNIT - 1;
+
+// 2^(2^-k) in 64.64 fixed point, rounded to nearest, for bits 63 down to 0.
+const EXP2_FACTORS: [u128; 64] = [
+    0x16A09E667F3BCC909,
+    0x1306FE0A31B7152DF,
+    0x1172B83C7D517ADCE,
+    0x10B5586CF9890F62A,
+    0x1059B0D31585743AE,
+    0x102C9A3E778060EE7,
+    0x10163DA9FB33356D8,
+    0x100B1AFA5ABCBED61,
+    0x10058C86DA1C09EA2,
+    0x1002C605E2E8CEC50,
+    0x100162F3904051FA1,
+    0x1000B175EFFDC76BA,
+    0x100058BA01FB9F96D,
+    0x10002C5CC37DA9492,
+    0x1000162E525EE0547,
+    0x10000B17255775C04,
+    0x1000058B91B5BC9AE,
+    0x100002C5C89D5EC6D,
+    0x10000162E43F4F831,
+    0x100000B1721BCFC9A,
+    0x10000058B90CF1E6E,
+    0x1000002C5C863B73F,
+    0x100000162E430E5A2,
+    0x1000000B172183551,
+    0x100000058B90C0B49,
+    0x10000002C5C8601CC,
+    0x1000000162E42FFF0,
+    0x10000000B17217FBB,
+    0x1000000058B90BFCE,
+    0x100000002C5C85FE3,
+    0x10000000162E42FF1,
+    0x100000000B17217F8,
+    0x10000000058B90BFC,
+    0x1000000002C5C85FE,
+    0x100000000162E42FF,
+    0x1000000000B17217F,
+    0x100000000058B90C0,
+    0x10000000002C5C860,
+    0x1000000000162E430,
+    0x10000000000B17218,
+    0x1000000000058B90C,
+    0x100000000002C5C86,
+    0x10000000000162E43,
+    0x100000000000B1721,
+    0x10000000000058B91,
+    0x1000000000002C5C8,
+    0x100000000000162E4,
+    0x1000000000000B172,
+    0x100000000000058B9,
+    0x10000000000002C5D,
+    0x1000000000000162E,
+    0x10000000000000B17,
+    0x1000000000000058C,
+    0x100000000000002C6,
+    0x10000000000000163,
+    0x100000000000000B1,
+    0x10000000000000059,
+    0x1000000000000002C,
+    0x10000000000000016,
+    0x1000000000000000B,
+    0x10000000000000006,
+    0x10000000000000003,
+    0x10000000000000001,
+    0x10000000000000001,
+];
+
+// ln(2) in 0.128 fixed point.
+const LN2_X128: u128 = 0xB17217F7D1CF79ABC9E3B39803F2F6AF;
+
+impl Ud60x18 {
+    pub const UNIT: Ud60x18 = Ud60x18(U256::from_u128(UNIT));
+
+    pub fn from_raw(raw: u128) -> Self {
+        Ud60x18(U256::from_u128(raw))
+    }
+
+    /// `x * y` rounded down (`mulDiv18`).
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, FloatError> {
+        U256::mul_div(self.0, rhs.0, U256::from_u128(UNIT))
+            .map(Ud60x18)
+            .ok_or(FloatError::Overflow)
+    }
+
+    /// `x / y` rounded down (`mulDiv(x, UNIT, y)`).
+    pub fn checked_div(self, rhs: Self) -> Result<Self, FloatError> {
+        if rhs.0.is_zero() {
+            return Err(FloatError::DivisionByZero);
+        }
+        U256::mul_div(self.0, U256::from_u128(UNIT), rhs.0)
+            .map(Ud60x18)
+            .ok_or(FloatError::Overflow)
+    }
+
+    /// Natural exponent, for inputs up to ~133.08.
+    pub fn exp(self) -> Result<Self, FloatError> {
+        let x = match self.0.try_to_u128() {
+            Some(x) if x <= EXP_MAX_INPUT => x,
+            _ => return Err(FloatError::Overflow),
+        };
+        // Cannot overflow: EXP_MAX_INPUT * LOG2_E < 2^128.
+        Ud60x18::from_raw(x * LOG2_E / UNIT).exp2()
+    }
+
+    /// Binary exponent, for inputs below 192.
+    pub fn exp2(self) -> Result<Self, FloatError> {
+        let x = match self.0.try_to_u128() {
+            Some(x) if x <= EXP2_MAX_INPUT => x,
+            _ => return Err(FloatError::Overflow),
+        };
+        // Convert to 192.64 binary fixed point.
+        let x_192x64 = (U256::from_u128(x) << 64)
+            .checked_div(U256::from_u128(UNIT))
+            .unwrap()
+            .low_u128();
+
+        // Start from 0.5 in 192.64 format; the running product stays below 2^192,
+        // so neither multiplication can wrap.
+        let mut result = U256::ONE << 191;
+        for (bit, factor) in (0..64).rev().zip(EXP2_FACTORS) {
+            if x_192x64 >> bit & 1 == 1 {
+                result = result.wrapping_mul(U256::from_u128(factor)) >> 64;
+            }
+        }
+        result = result.wrapping_mul(U256::from_u128(UNIT));
+        Ok(Ud60x18(result >> (191 - (x_192x64 >> 64) as u32)))
+    }
+
+    /// Binary logarithm, for inputs of at least 1.
+    pub fn log2(self) -> Result<Self, FloatError> {
+        if self.0 < U256::from_u128(UNIT) {
+            return Err(FloatError::OutOfRange);
+        }
+        let n = self.0.checked_div(U256::from_u128(UNIT)).unwrap().bits() - 1;
+        let mut result = n as u128 * UNIT;
+        // y = x / 2^n lies in [1, 2), so it and its square fit in a u128.
+        let mut y = (self.0 >> n).low_u128();
+        if y == UNIT {
+            return Ok(Ud60x18::from_raw(result));
+        }
+        let mut delta = HALF_UNIT;
+        while delta > 0 {
+            y = y * y / UNIT;
+            if y >= 2 * UNIT {
+                result += delta;
+                y >>= 1;
+            }
+            delta >>= 1;
+        }
+        Ok(Ud60x18::from_raw(result))
+    }
+
+    /// Natural logarithm, for inputs of at least 1.
+    pub fn ln(self) -> Result<Self, FloatError> {
+        // log2 is at most ~196.2, so the product fits in a u128.
+        let log2 = self.log2()?.0.low_u128();
+        Ok(Ud60x18::from_raw(log2 * UNIT / LOG2_E))
+    }
+}
+
+impl Fixed64x64 {
+    pub const ONE: Fixed64x64 = Fixed64x64(1 << 64);
+
+    /// `x * y`, rounded toward negative infinity (arithmetic shift).
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, FloatError> {
+        let magnitude = U256::from_u128(self.0.unsigned_abs())
+            .wrapping_mul(U256::from_u128(rhs.0.unsigned_abs()));
+        let negative = (self.0 < 0) != (rhs.0 < 0);
+        signed_shr(magnitude, negative, 64).map(Fixed64x64)
+    }
+
+    /// `x / y`, truncated toward zero like Solidity's signed division.
+    pub fn checked_div(self, rhs: Self) -> Result<Self, FloatError> {
+        if rhs.0 == 0 {
+            return Err(FloatError::DivisionByZero);
+        }
+        let quotient = (U256::from_u128(self.0.unsigned_abs()) << 64)
+            .checked_div(U256::from_u128(rhs.0.unsigned_abs()))
+            .unwrap();
+        let negative = (self.0 < 0) != (rhs.0 < 0);
+        to_i128(quotient, negative).map(Fixed64x64)
+    }
+
+    /// Binary logarithm of a positive value.
+    pub fn log2(self) -> Result<Self, FloatError> {
+        if self.0 <= 0 {
+            return Err(FloatError::OutOfRange);
+        }
+        let msb = 127 - self.0.leading_zeros() as i128;
+        let mut result = (msb - 64) << 64;
+        let mut ux = U256::from_u128(self.0 as u128) << (127 - msb) as u32;
+        let mut bit: i128 = 1 << 63;
+        while bit > 0 {
+            ux = ux.wrapping_mul(ux);
+            let b = (ux >> 255).low_u128();
+            ux = ux >> (127 + b as u32);
+            result += bit * b as i128;
+            bit >>= 1;
+        }
+        Ok(Fixed64x64(result))
+    }
+
+    /// Natural logarithm of a positive value.
+    pub fn ln(self) -> Result<Self, FloatError> {
+        let log2 = self.log2()?.0;
+        let magnitude =
+            U256::from_u128(log2.unsigned_abs()).wrapping_mul(U256::from_u128(LN2_X128));
+        signed_shr(magnitude, log2 < 0, 128).map(Fixed64x64)
+    }
+}
+
+// Arithmetic right shift of a sign-magnitude value: rounds toward negative infinity.
+fn signed_shr(magnitude: U256, negative: bool, shift: u32) -> Result<i128, FloatError> {
+    let mut shifted = magnitude >> shift;
+    if negative && shifted << shift != magnitude {
+        shifted = shifted.checked_add(U256::ONE).ok_or(FloatError::Overflow)?;
+    }
+    to_i128(shifted, negative)
+}
+
+fn to_i128(magnitude: U256, negative: bool) -> Result<i128, FloatError> {
+    let magnitude = magnitude.try_to_u128().ok_or(FloatError::Overflow)?;
+    if negative {
+        0i128
+            .checked_sub_unsigned(magnitude)
+            .ok_or(FloatError::Overflow)
+    } else {
+        i128::try_from(magnitude).map_err(|_| FloatError::Overflow)
+    }
+}
+
+/// Outcome of comparing the same balance as recorded on Solana and on an EVM chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reconciliation {
+    /// Decimals both amounts were scaled to: the larger of the two.
+    pub decimals: u8,
+    /// How the Solana amount compares to the EVM amount.
+    pub ordering: Ordering,
+    /// `|solana - evm|` in units of `decimals`.
+    pub residual: U256,
+    /// Part of the finer-grained amount below the coarser chain's smallest unit,
+    /// in units of `decimals`. It can never be bridged and is expected residue.
+    pub dust: U256,
+    /// Whether `residual <= tolerance`.
+    pub within_tolerance: bool,
+}
+
+/// Scales both amounts to the larger of the two decimals and reports the gap.
+///
+/// Scaling up is exact, so no rounding is involved. `tolerance` is in units of
+/// the larger decimals. Decimals above 77 (10^77 is the largest power of ten in
+/// a `uint256`) are `OutOfRange`, and an amount that no longer fits after
+/// scaling is `Overflow`.
+pub fn reconcile_amounts(
+    solana_amount: u64,
+    evm_amount: U256,
+    solana_decimals: u8,
+    evm_decimals: u8,
+    tolerance: U256,
+) -> Result<Reconciliation, FloatError> {
+    let decimals = solana_decimals.max(evm_decimals);
+    let scale = |amount: U256, from: u8| {
+        let factor = U256::pow10((decimals - from) as u32).ok_or(FloatError::OutOfRange)?;
+        amount.checked_mul(factor).ok_or(FloatError::Overflow)
+    };
+    let solana = scale(U256::from(solana_amount), solana_decimals)?;
+    let evm = scale(evm_amount, evm_decimals)?;
+
+    let coarse_unit = U256::pow10((decimals - solana_decimals.min(evm_decimals)) as u32)
+        .ok_or(FloatError::OutOfRange)?;
+    let finer = if solana_decimals > evm_decimals {
+        solana
+    } else {
+        evm
+    };
+    let (_, dust) = finer.div_rem(coarse_unit).unwrap();
+
+    let ordering = solana.cmp(&evm);
+    let residual = match ordering {
+        Ordering::Less => evm.checked_sub(solana).unwrap(),
+        _ => solana.checked_sub(evm).unwrap(),
+    };
+    Ok(Reconciliation {
+        decimals,
+        ordering,
+        residual,
+        dust,
+        within_tolerance: residual <= tolerance,
+    })
+}
@@ -0,0 +1,103 @@
+//! Runtime switch for the low-precision f32 opcodes.
+//!
+//! The gate lives in a program-owned PDA at `[FEATURE_GATE_SEED]`. While the
+//! PDA has not been created the f32 opcodes stay enabled, so existing
+//! deployments keep working until an admin opts in. The admin is the program's
+//! upgrade authority, read from its `ProgramData` account when the first
+//! `SetFeatures` call creates the gate; later calls must be signed by that
+//! admin.
+//!
+//! Every f32 instruction must pass the gate address as its first account.
+
+use solana_loader_v3_interface::get_program_data_address;
+use solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+use solana_sdk_ids::bpf_loader_upgradeable;
+
+pub const FEATURE_GATE_SEED: &[u8] = b"feature_gate";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeatureGate {
+    pub admin: Pubkey,
+    pub f32_enabled: bool,
+}
+
+impl FeatureGate {
+    /// Layout: [admin: Pubkey][f32_enabled: u8]
+    pub const LEN: usize = 32 + 1;
+
+    /// Canonical gate address and bump for `program_id`.
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[FEATURE_GATE_SEED], program_id)
+    }
+
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        dst[0..32].copy_from_slice(self.admin.as_ref());
+        dst[32] = self.f32_enabled as u8;
+        Ok(())
+    }
+
+    pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let admin = Pubkey::new_from_array(src[0..32].try_into().unwrap());
+        let f32_enabled = match src[32] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        Ok(Self { admin, f32_enabled })
+    }
+
+    /// Loads the gate from `account`, or `None` if it has not been created yet.
+    ///
+    /// `account` must be the canonical gate address; anything else is
+    /// `InvalidSeeds`, so callers cannot dodge the gate with a lookalike account.
+    pub fn load(program_id: &Pubkey, account: &AccountInfo) -> Result<Option<Self>, ProgramError> {
+        if *account.key != Self::find_address(program_id).0 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if account.owner != program_id {
+            return Ok(None);
+        }
+        Self::unpack(&account.try_borrow_data()?).map(Some)
+    }
+}
+
+/// Upgrade authority recorded in the `ProgramData` account of `program_id`, or
+/// `None` once the program has been made immutable.
+///
+/// `account` must be the program's canonical `ProgramData` address
+/// (`InvalidSeeds` otherwise) and owned by the upgradeable loader
+/// (`IncorrectProgramId` otherwise).
+pub fn upgrade_authority(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+) -> Result<Option<Pubkey>, ProgramError> {
+    if *account.key != get_program_data_address(program_id) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if *account.owner != bpf_loader_upgradeable::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    unpack_upgrade_authority(&account.try_borrow_data()?)
+}
+
+// `ProgramData` header as the loader serializes it:
+// [variant: u32 = 3][slot: u64][authority tag: u8][authority: Pubkey]
+fn unpack_upgrade_authority(src: &[u8]) -> Result<Option<Pubkey>, ProgramError> {
+    const PROGRAM_DATA: u32 = 3;
+    if src.len() < 45 || u32::from_le_bytes(src[0..4].try_into().unwrap()) != PROGRAM_DATA {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    match src[12] {
+        0 => Ok(None),
+        1 => Ok(Some(Pubkey::new_from_array(
+            src[13..45].try_into().unwrap(),
+        ))),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
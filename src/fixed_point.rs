@@ -0,0 +1,398 @@
+//! Binary fixed point: unsigned Q64.64 and signed I80F48.
+//!
+//! A [`Q64x64`] is a u128 read as `raw / 2^64`: 64 integer bits and 64
+//! fractional bits, the representation CLMM programs use for square-root
+//! prices. Sums are exact, and every operation that has to drop bits takes a
+//! [`RoundingMode`], so a program decides which side each rounding favours
+//! instead of inheriting round-to-nearest from f64.
+//!
+//! Every f64 in `[0, 2^64)` with no bits below `2^-64` converts exactly;
+//! [`Q64x64::from_f64`] and [`Q64x64::to_f64_exact`] fail with
+//! `PrecisionLoss` rather than round, and the `_rounded`/`to_f64` variants
+//! say how they round.
+//!
+//! An [`I80F48`] is an i128 read as `raw / 2^48`, the layout Mango and other
+//! lending programs store balances and rates in. It follows the same rules,
+//! with rounding modes applied to the signed value: `Floor` of a negative
+//! product moves away from zero.
+
+use crate::error::FloatError;
+use crate::mul_div::mul_div_u256;
+use crate::quantize::{RoundingMode, pow10};
+use crate::u256::U256;
+
+const FRACTION_BITS: u32 = 64;
+const SCALE: U256 = U256::from_u128(1 << FRACTION_BITS);
+// 2^64 and 2^-64, exact in f64
+const SCALE_F64: f64 = 18_446_744_073_709_551_616.0;
+const INV_SCALE_F64: f64 = 1.0 / SCALE_F64;
+
+const I80F48_FRACTION_BITS: u32 = 48;
+const I80F48_SCALE: U256 = U256::from_u128(1 << I80F48_FRACTION_BITS);
+// 2^48, 2^-48 and 2^79, exact in f64
+const I80F48_SCALE_F64: f64 = 281_474_976_710_656.0;
+const I80F48_INV_SCALE_F64: f64 = 1.0 / I80F48_SCALE_F64;
+const I80F48_LIMIT_F64: f64 = 604_462_909_807_314_587_353_088.0;
+// Q64.64 has 16 more fractional bits
+const Q64X64_EXTRA_BITS: u32 = FRACTION_BITS - I80F48_FRACTION_BITS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Q64x64(u128);
+
+impl Q64x64 {
+    pub const ZERO: Q64x64 = Q64x64(0);
+    pub const ONE: Q64x64 = Q64x64(1 << FRACTION_BITS);
+    /// `2^-64`, the smallest non-zero value.
+    pub const EPSILON: Q64x64 = Q64x64(1);
+    /// Just below `2^64`.
+    pub const MAX: Q64x64 = Q64x64(u128::MAX);
+
+    pub const fn from_bits(bits: u128) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> u128 {
+        self.0
+    }
+
+    pub const fn from_int(value: u64) -> Self {
+        Self((value as u128) << FRACTION_BITS)
+    }
+
+    /// The integer part, rounded toward zero.
+    pub const fn int_part(self) -> u64 {
+        (self.0 >> FRACTION_BITS) as u64
+    }
+
+    /// The fractional bits, as a numerator over `2^64`.
+    pub const fn frac_bits(self) -> u64 {
+        self.0 as u64
+    }
+
+    /// Fails with `Overflow` at `2^64` or above.
+    pub fn checked_add(self, rhs: Self) -> Result<Self, FloatError> {
+        self.0
+            .checked_add(rhs.0)
+            .map(Self)
+            .ok_or(FloatError::Overflow)
+    }
+
+    /// Fails with `OutOfRange` if `rhs` is larger, as the type has no
+    /// negative values.
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, FloatError> {
+        self.0
+            .checked_sub(rhs.0)
+            .map(Self)
+            .ok_or(FloatError::OutOfRange)
+    }
+
+    /// The product rounded to a multiple of `2^-64`. Fails with `Overflow` at
+    /// `2^64` or above.
+    pub fn checked_mul(self, rhs: Self, rounding: RoundingMode) -> Result<Self, FloatError> {
+        let product = mul_div_u256(self.into(), rhs.into(), SCALE, rounding)?;
+        product.try_to_u128().map(Self).ok_or(FloatError::Overflow)
+    }
+
+    /// The quotient rounded to a multiple of `2^-64`. Fails with
+    /// `DivisionByZero` and with `Overflow` at `2^64` or above.
+    pub fn checked_div(self, rhs: Self, rounding: RoundingMode) -> Result<Self, FloatError> {
+        let quotient = mul_div_u256(self.into(), SCALE, rhs.into(), rounding)?;
+        quotient.try_to_u128().map(Self).ok_or(FloatError::Overflow)
+    }
+
+    /// The nearest value to `self` rounded to `decimals` fractional digits,
+    /// ties to even. `10^-decimals` is not a multiple of `2^-64`, so the
+    /// rounded decimal is itself rounded to the nearest representable value.
+    /// Fails with `OutOfRange` past `MAX_DECIMALS` and `Overflow` if the value
+    /// rounds up to `2^64`.
+    pub fn round_half_even_to_scale(self, decimals: u32) -> Result<Self, FloatError> {
+        let rounded = round_half_even_raw(self.into(), SCALE, decimals)?;
+        rounded.try_to_u128().map(Self).ok_or(FloatError::Overflow)
+    }
+
+    /// The square root rounded to a multiple of `2^-64`. Exact roots are
+    /// returned as is; a root is never exactly halfway between two
+    /// representable values, so the three nearest modes agree.
+    pub fn sqrt(self, rounding: RoundingMode) -> Self {
+        // sqrt(raw / 2^64) * 2^64 = sqrt(raw * 2^64), below 2^96
+        let (root, remainder) = isqrt(U256::from(self.0) << FRACTION_BITS);
+        let round_up = match rounding {
+            RoundingMode::Floor | RoundingMode::TowardZero => false,
+            RoundingMode::Ceil => !remainder.is_zero(),
+            // (root + 1/2)^2 = root^2 + root + 1/4, and the remainder is an
+            // integer, so it is past the midpoint exactly when above root
+            RoundingMode::HalfUp | RoundingMode::HalfEven => remainder > root,
+        };
+        Self(root.low_u128() + round_up as u128)
+    }
+
+    /// `value` exactly. Fails with `NaNOperand` for NaN, `OutOfRange` for
+    /// infinities, negative values and values from `2^64` up, and
+    /// `PrecisionLoss` if `value` has bits below `2^-64`.
+    pub fn from_f64(value: f64) -> Result<Self, FloatError> {
+        let scaled = scale_f64(value)?;
+        if scaled.fract() != 0.0 {
+            return Err(FloatError::PrecisionLoss);
+        }
+        Ok(Self(scaled as u128))
+    }
+
+    /// `value` rounded to a multiple of `2^-64`. Fails like
+    /// [`from_f64`](Self::from_f64), except for the precision loss, and with
+    /// `OutOfRange` if the rounded value is `2^64`.
+    pub fn from_f64_rounded(value: f64, rounding: RoundingMode) -> Result<Self, FloatError> {
+        let scaled = rounding.round(scale_f64(value)?);
+        if scaled >= SCALE_F64 * SCALE_F64 {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(Self(scaled as u128))
+    }
+
+    /// The nearest f64, ties to even.
+    pub fn to_f64(self) -> f64 {
+        // Integer to float casts round to nearest even, and scaling by a power
+        // of two is exact
+        self.0 as f64 * INV_SCALE_F64
+    }
+
+    /// The value as an f64, failing with `PrecisionLoss` if it has more
+    /// significant bits than f64 holds.
+    pub fn to_f64_exact(self) -> Result<f64, FloatError> {
+        let raw = self.0 as f64;
+        // Float to integer casts saturate, so a raw value rounded up to 2^128
+        // is caught by the range check rather than the round trip
+        if raw >= SCALE_F64 * SCALE_F64 || raw as u128 != self.0 {
+            return Err(FloatError::PrecisionLoss);
+        }
+        Ok(raw * INV_SCALE_F64)
+    }
+}
+
+impl From<Q64x64> for U256 {
+    fn from(value: Q64x64) -> U256 {
+        U256::from(value.0)
+    }
+}
+
+// `value * 2^64`, after checking it fits the type before rounding
+fn scale_f64(value: f64) -> Result<f64, FloatError> {
+    if value.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    if !(0.0..SCALE_F64).contains(&value) {
+        return Err(FloatError::OutOfRange);
+    }
+    Ok(value * SCALE_F64)
+}
+
+// `raw / scale` rounded to a multiple of `10^-decimals`, ties to even, and
+// back to the nearest multiple of `1 / scale`
+fn round_half_even_raw(raw: U256, scale: U256, decimals: u32) -> Result<U256, FloatError> {
+    let unit = U256::from(pow10(decimals)? as u128);
+    let units = mul_div_u256(raw, unit, scale, RoundingMode::HalfEven)?;
+    mul_div_u256(units, scale, unit, RoundingMode::HalfEven)
+}
+
+// Floor square root and remainder, digit by digit in base 4
+fn isqrt(n: U256) -> (U256, U256) {
+    let mut remainder = n;
+    let mut root = U256::ZERO;
+    let mut bit = U256::ONE << 254;
+    while bit > n {
+        bit = bit >> 2;
+    }
+    while !bit.is_zero() {
+        let trial = root.checked_add(bit).unwrap();
+        root = root >> 1;
+        if remainder >= trial {
+            remainder = remainder.overflowing_sub(trial).0;
+            root = root.checked_add(bit).unwrap();
+        }
+        bit = bit >> 2;
+    }
+    (root, remainder)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct I80F48(i128);
+
+impl I80F48 {
+    pub const ZERO: I80F48 = I80F48(0);
+    pub const ONE: I80F48 = I80F48(1 << I80F48_FRACTION_BITS);
+    /// `2^-48`, the smallest positive value.
+    pub const EPSILON: I80F48 = I80F48(1);
+    /// `-2^79`.
+    pub const MIN: I80F48 = I80F48(i128::MIN);
+    /// Just below `2^79`.
+    pub const MAX: I80F48 = I80F48(i128::MAX);
+
+    pub const fn from_bits(bits: i128) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> i128 {
+        self.0
+    }
+
+    pub const fn from_int(value: i64) -> Self {
+        Self((value as i128) << I80F48_FRACTION_BITS)
+    }
+
+    /// The integer part, rounded toward negative infinity as an arithmetic
+    /// shift does.
+    pub const fn int_floor(self) -> i128 {
+        self.0 >> I80F48_FRACTION_BITS
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    /// Fails with `Overflow` outside `[-2^79, 2^79)`.
+    pub fn checked_add(self, rhs: Self) -> Result<Self, FloatError> {
+        self.0
+            .checked_add(rhs.0)
+            .map(Self)
+            .ok_or(FloatError::Overflow)
+    }
+
+    /// Fails with `Overflow` outside `[-2^79, 2^79)`.
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, FloatError> {
+        self.0
+            .checked_sub(rhs.0)
+            .map(Self)
+            .ok_or(FloatError::Overflow)
+    }
+
+    /// Fails with `Overflow` for [`I80F48::MIN`].
+    pub fn checked_neg(self) -> Result<Self, FloatError> {
+        self.0.checked_neg().map(Self).ok_or(FloatError::Overflow)
+    }
+
+    /// The product rounded to a multiple of `2^-48`. Fails with `Overflow`
+    /// outside `[-2^79, 2^79)`.
+    pub fn checked_mul(self, rhs: Self, rounding: RoundingMode) -> Result<Self, FloatError> {
+        let negative = (self.0 < 0) != (rhs.0 < 0);
+        let magnitude = mul_div_u256(
+            U256::from(self.0.unsigned_abs()),
+            U256::from(rhs.0.unsigned_abs()),
+            I80F48_SCALE,
+            rounding.for_magnitude(negative),
+        )?;
+        Self::from_magnitude(magnitude, negative)
+    }
+
+    /// The quotient rounded to a multiple of `2^-48`. Fails with
+    /// `DivisionByZero` and with `Overflow` outside `[-2^79, 2^79)`.
+    pub fn checked_div(self, rhs: Self, rounding: RoundingMode) -> Result<Self, FloatError> {
+        let negative = (self.0 < 0) != (rhs.0 < 0);
+        let magnitude = mul_div_u256(
+            U256::from(self.0.unsigned_abs()),
+            I80F48_SCALE,
+            U256::from(rhs.0.unsigned_abs()),
+            rounding.for_magnitude(negative),
+        )?;
+        Self::from_magnitude(magnitude, negative)
+    }
+
+    /// The nearest value to `self` rounded to `decimals` fractional digits,
+    /// ties to even, like [`Q64x64::round_half_even_to_scale`]. Ties to even
+    /// are symmetric, so `-x` rounds to the negation of `x`. Fails with
+    /// `OutOfRange` past `MAX_DECIMALS` and `Overflow` outside `[-2^79, 2^79)`.
+    pub fn round_half_even_to_scale(self, decimals: u32) -> Result<Self, FloatError> {
+        let magnitude =
+            round_half_even_raw(U256::from(self.0.unsigned_abs()), I80F48_SCALE, decimals)?;
+        Self::from_magnitude(magnitude, self.0 < 0)
+    }
+
+    /// `value` exactly. Fails with `NaNOperand` for NaN, `OutOfRange` for
+    /// infinities and values outside `[-2^79, 2^79)`, and `PrecisionLoss` if
+    /// `value` has bits below `2^-48`.
+    pub fn from_f64(value: f64) -> Result<Self, FloatError> {
+        let scaled = scale_f64_i80f48(value)?;
+        if scaled.fract() != 0.0 {
+            return Err(FloatError::PrecisionLoss);
+        }
+        Ok(Self(scaled as i128))
+    }
+
+    /// `value` rounded to a multiple of `2^-48`. Fails like
+    /// [`from_f64`](Self::from_f64), except for the precision loss, and with
+    /// `OutOfRange` if the rounded value is `2^79`.
+    pub fn from_f64_rounded(value: f64, rounding: RoundingMode) -> Result<Self, FloatError> {
+        let scaled = rounding.round(scale_f64_i80f48(value)?);
+        if scaled >= I80F48_LIMIT_F64 * I80F48_SCALE_F64 {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(Self(scaled as i128))
+    }
+
+    /// The nearest f64, ties to even.
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 * I80F48_INV_SCALE_F64
+    }
+
+    /// The value as an f64, failing with `PrecisionLoss` if it has more
+    /// significant bits than f64 holds.
+    pub fn to_f64_exact(self) -> Result<f64, FloatError> {
+        let raw = self.0 as f64;
+        // As for Q64x64: a raw value rounded up to 2^127 would saturate
+        if raw >= I80F48_LIMIT_F64 * I80F48_SCALE_F64 || raw as i128 != self.0 {
+            return Err(FloatError::PrecisionLoss);
+        }
+        Ok(raw * I80F48_INV_SCALE_F64)
+    }
+
+    /// `value` with its 16 extra fractional bits rounded off. Every Q64.64
+    /// integer part fits, so this cannot fail.
+    pub fn from_q64x64(value: Q64x64, rounding: RoundingMode) -> Self {
+        let shifted = mul_div_u256(
+            value.into(),
+            U256::ONE,
+            U256::ONE << Q64X64_EXTRA_BITS,
+            rounding,
+        )
+        .unwrap();
+        Self(shifted.low_u128() as i128)
+    }
+
+    /// `value` exactly, failing with `PrecisionLoss` if any of its lowest 16
+    /// fractional bits is set.
+    pub fn from_q64x64_exact(value: Q64x64) -> Result<Self, FloatError> {
+        let converted = Self::from_q64x64(value, RoundingMode::Floor);
+        if converted.to_q64x64() != Ok(value) {
+            return Err(FloatError::PrecisionLoss);
+        }
+        Ok(converted)
+    }
+
+    /// The same value as a Q64.64, which is always exact. Fails with
+    /// `OutOfRange` for negative values and values from `2^64` up.
+    pub fn to_q64x64(self) -> Result<Q64x64, FloatError> {
+        if self.0 < 0 || self.0 >> (I80F48_FRACTION_BITS + 64) != 0 {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(Q64x64::from_bits((self.0 as u128) << Q64X64_EXTRA_BITS))
+    }
+
+    fn from_magnitude(magnitude: U256, negative: bool) -> Result<Self, FloatError> {
+        let magnitude = magnitude.try_to_u128().ok_or(FloatError::Overflow)?;
+        let value = if negative {
+            0i128.checked_sub_unsigned(magnitude)
+        } else {
+            i128::try_from(magnitude).ok()
+        };
+        value.map(Self).ok_or(FloatError::Overflow)
+    }
+}
+
+// `value * 2^48`, after checking it fits the type before rounding
+fn scale_f64_i80f48(value: f64) -> Result<f64, FloatError> {
+    if value.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    if !(-I80F48_LIMIT_F64..I80F48_LIMIT_F64).contains(&value) {
+        return Err(FloatError::OutOfRange);
+    }
+    Ok(value * I80F48_SCALE_F64)
+}
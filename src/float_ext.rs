@@ -0,0 +1,82 @@
+//! Safety helpers as methods on the primitive float types.
+//!
+//! For code that keeps plain `f32`/`f64` fields rather than wrapping every
+//! value in [`crate::safe_f64::SafeF64`]. Rounding is half to even throughout;
+//! use [`crate::quantize`] directly to choose another mode.
+
+use crate::compare::Tolerance;
+use crate::error::FloatError;
+use crate::quantize::{RoundingMode, pow10, quantize};
+
+// 2^53 and 2^24: from here on consecutive values are more than one unit apart.
+const F64_EXACT_INTEGER_LIMIT: f64 = 9_007_199_254_740_992.0;
+const F32_EXACT_INTEGER_LIMIT: f32 = 16_777_216.0;
+
+// 2^64, the first scaled magnitude that no longer fits in a u64.
+const U64_LIMIT: f64 = 18_446_744_073_709_551_616.0;
+
+pub trait SolanaFloatExt: Copy {
+    /// Rounds to `decimals` fractional digits, half to even.
+    fn quantize(self, decimals: u32) -> Result<Self, FloatError>;
+
+    /// Whether `self` and `other` are within `tolerance`. NaN never is.
+    fn approx_eq(self, other: Self, tolerance: Tolerance) -> bool;
+
+    /// Whether the value is NaN, infinite, subnormal, or so large that whole
+    /// units are no longer exact: the values that silently corrupt amounts.
+    fn is_dangerous(self) -> bool;
+
+    /// `self * 10^decimals` rounded half to even, as integer base units.
+    ///
+    /// Negative results are `OutOfRange`, non-finite inputs `NaNOperand` and
+    /// results of 2^64 or more `Overflow`.
+    fn to_scaled(self, decimals: u32) -> Result<u64, FloatError>;
+}
+
+impl SolanaFloatExt for f64 {
+    fn quantize(self, decimals: u32) -> Result<Self, FloatError> {
+        quantize(self, decimals, RoundingMode::HalfEven)
+    }
+
+    fn approx_eq(self, other: Self, tolerance: Tolerance) -> bool {
+        tolerance.matches(self, other)
+    }
+
+    fn is_dangerous(self) -> bool {
+        !self.is_finite() || self.is_subnormal() || self.abs() > F64_EXACT_INTEGER_LIMIT
+    }
+
+    fn to_scaled(self, decimals: u32) -> Result<u64, FloatError> {
+        let scale = pow10(decimals)? as f64;
+        if !self.is_finite() {
+            return Err(FloatError::NaNOperand);
+        }
+        let scaled = RoundingMode::HalfEven.round(self * scale);
+        if scaled < 0.0 {
+            return Err(FloatError::OutOfRange);
+        }
+        if scaled >= U64_LIMIT {
+            return Err(FloatError::Overflow);
+        }
+        Ok(scaled as u64)
+    }
+}
+
+/// Computed in f64 and rounded back, so `quantize` may round twice.
+impl SolanaFloatExt for f32 {
+    fn quantize(self, decimals: u32) -> Result<Self, FloatError> {
+        (self as f64).quantize(decimals).map(|value| value as f32)
+    }
+
+    fn approx_eq(self, other: Self, tolerance: Tolerance) -> bool {
+        tolerance.matches(self as f64, other as f64)
+    }
+
+    fn is_dangerous(self) -> bool {
+        !self.is_finite() || self.is_subnormal() || self.abs() > F32_EXACT_INTEGER_LIMIT
+    }
+
+    fn to_scaled(self, decimals: u32) -> Result<u64, FloatError> {
+        (self as f64).to_scaled(decimals)
+    }
+}
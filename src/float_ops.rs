@@ -1,3 +1,6 @@
+use crate::error::FloatError;
+use crate::softref;
+
 pub fn add_floats(a: f32, b: f32) -> f32 {
     a + b
 }
@@ -6,9 +9,88 @@ pub fn multiply_floats(a: f32, b: f32) -> f32 {
     a * b
 }
 
-pub fn divide_floats(a: f32, b: f32) -> Result<f32, &'static str> {
+/// `a * b + c` with one rounding; see
+/// [`mul_add_doubles`](crate::double_ops::mul_add_doubles) for how this
+/// differs from a multiply followed by an add.
+pub fn mul_add_floats(a: f32, b: f32, c: f32) -> f32 {
+    softref::mul_add_f32(a, b, c)
+}
+
+pub fn sub_floats(a: f32, b: f32) -> f32 {
+    a - b
+}
+
+/// The f32 counterpart of [`DoubleDifference`](crate::double_ops::DoubleDifference).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloatDifference {
+    pub value: f32,
+    /// Leading significand bits the operands had in common, which cancelled.
+    /// [`f32::MANTISSA_DIGITS`] when equal non-zero operands cancel entirely.
+    pub cancelled_bits: u32,
+    /// Bound on the relative error of `value` against the difference of the
+    /// quantities the operands stand for, assuming each is within half an
+    /// ulp of its own. Infinite when the operands cancel entirely.
+    pub relative_error: f32,
+}
+
+/// [`sub_floats`] with an estimate of the cancellation.
+///
+/// Fails with `NaNOperand` for NaN or infinite operands and `Overflow` if the
+/// difference is infinite.
+pub fn sub_floats_with_error(a: f32, b: f32) -> Result<FloatDifference, FloatError> {
+    if !a.is_finite() || !b.is_finite() {
+        return Err(FloatError::NaNOperand);
+    }
+    let value = a - b;
+    if value.is_infinite() {
+        return Err(FloatError::Overflow);
+    }
+    let larger = a.abs().max(b.abs());
+    if value == 0.0 {
+        let (cancelled_bits, relative_error) = if larger == 0.0 {
+            (0, 0.0)
+        } else {
+            (f32::MANTISSA_DIGITS, f32::INFINITY)
+        };
+        return Ok(FloatDifference {
+            value,
+            cancelled_bits,
+            relative_error,
+        });
+    }
+    let cancelled = (exponent(larger) - exponent(value.abs())).max(0) as u32;
+    let condition = (a.abs() + b.abs()) / value.abs();
+    Ok(FloatDifference {
+        value,
+        cancelled_bits: cancelled.min(f32::MANTISSA_DIGITS),
+        relative_error: f32::EPSILON / 2.0 * (condition + 1.0),
+    })
+}
+
+/// [`sub_floats`], failing with `PrecisionLoss` when more than
+/// `max_cancelled_bits` leading bits cancel.
+pub fn sub_floats_checked(a: f32, b: f32, max_cancelled_bits: u32) -> Result<f32, FloatError> {
+    let difference = sub_floats_with_error(a, b)?;
+    if difference.cancelled_bits > max_cancelled_bits {
+        return Err(FloatError::PrecisionLoss);
+    }
+    Ok(difference.value)
+}
+
+// Unbiased binary exponent of a finite, positive value, subnormals included
+fn exponent(x: f32) -> i32 {
+    let bits = x.to_bits();
+    let biased = (bits >> 23) as i32;
+    if biased == 0 {
+        31 - bits.leading_zeros() as i32 - 149
+    } else {
+        biased - 127
+    }
+}
+
+pub fn divide_floats(a: f32, b: f32) -> Result<f32, FloatError> {
     if b == 0.0 {
-        Err("Division by zero")
+        Err(FloatError::DivisionByZero)
     } else {
         Ok(a / b)
     }
@@ -16,4 +98,43 @@ pub fn divide_floats(a: f32, b: f32) -> Result<f32, &'static str> {
 
 pub fn sqrt_float(a: f32) -> f32 {
     a.sqrt()
-}
\ No newline at end of file
+}
+
+/// Cube root through [`cbrt_double`](crate::double_ops::cbrt_double),
+/// within 1 ulp. Fails with `NaNOperand` for NaN and infinities.
+pub fn cbrt_float(a: f32) -> Result<f32, FloatError> {
+    crate::double_ops::cbrt_double(a as f64).map(|root| root as f32)
+}
+
+/// `sqrt(a² + b²)` without overflow in the squares, within 1 ulp.
+///
+/// The squares of f32 values are exact in f64 and cannot overflow there, so
+/// this only rounds the sum, its square root and the narrowing. Fails with
+/// `NaNOperand` for NaN and infinite operands and `Overflow` if the result
+/// exceeds `f32::MAX`.
+pub fn hypot_floats(a: f32, b: f32) -> Result<f32, FloatError> {
+    if !a.is_finite() || !b.is_finite() {
+        return Err(FloatError::NaNOperand);
+    }
+    let (a, b) = (a as f64, b as f64);
+    let result = (a * a + b * b).sqrt() as f32;
+    if result.is_infinite() {
+        return Err(FloatError::Overflow);
+    }
+    Ok(result)
+}
+
+/// `base^exponent` by exponentiation by squaring, carried out in f64 so the
+/// only rounding that matters is the final narrowing: the result is within
+/// 1 ulp for exponents below 2^28 in magnitude. See
+/// [`powi_doubles`](crate::double_ops::powi_doubles) for the special cases.
+///
+/// Fails with `NaNOperand` for a NaN or infinite base, `DivisionByZero` for
+/// zero to a negative power and `Overflow` if the result exceeds `f32::MAX`.
+pub fn powi_floats(base: f32, exponent: i32) -> Result<f32, FloatError> {
+    let power = crate::double_ops::powi_doubles(base as f64, exponent)? as f32;
+    if power.is_infinite() {
+        return Err(FloatError::Overflow);
+    }
+    Ok(power)
+}
@@ -0,0 +1,58 @@
+//! Deterministic fixed-decimals formatting of f64 values.
+//!
+//! Only the scaling multiply and the rounding step touch floating point; the
+//! digits themselves come from integer arithmetic, so every validator renders
+//! the same bit pattern to the same string.
+
+use std::fmt;
+
+use crate::error::FloatError;
+use crate::quantize::{RoundingMode, pow10};
+
+// 2^64: the first scaled magnitude whose digits no longer fit in a u64.
+const U64_LIMIT: f64 = 18_446_744_073_709_551_616.0;
+
+/// Renders `value` with exactly `decimals` fractional digits, rounding with `mode`.
+///
+/// The output is plain ASCII (`-?[0-9]+(\.[0-9]+)?`) with no exponent. A value
+/// that rounds to zero is printed without a sign. Non-finite values give
+/// `NaNOperand`; a scaled magnitude of 2^64 or more gives `Overflow`.
+pub fn format_fixed(value: f64, decimals: u32, mode: RoundingMode) -> Result<String, FloatError> {
+    let scale = pow10(decimals)?;
+    if !value.is_finite() {
+        return Err(FloatError::NaNOperand);
+    }
+    let scaled = mode.round(value * scale as f64);
+    if scaled.abs() >= U64_LIMIT {
+        return Err(FloatError::Overflow);
+    }
+
+    let digits = scaled.abs() as u64;
+    let sign = if scaled < 0.0 && digits != 0 { "-" } else { "" };
+    if decimals == 0 {
+        return Ok(format!("{}{}", sign, digits));
+    }
+    Ok(format!(
+        "{}{}.{:0width$}",
+        sign,
+        digits / scale,
+        digits % scale,
+        width = decimals as usize
+    ))
+}
+
+/// Writes `value` for a `Display` impl: `decimals` fractional digits (or the
+/// formatter's precision, if one is given) rounded half to even.
+///
+/// Values `format_fixed` cannot render, such as amounts past 2^64 base units,
+/// fall back to scientific notation, so the output stays short either way.
+pub(crate) fn write_fixed(f: &mut fmt::Formatter, value: f64, decimals: u32) -> fmt::Result {
+    let decimals = f.precision().map_or(decimals, |precision| precision as u32);
+    match format_fixed(value, decimals, RoundingMode::HalfEven) {
+        Ok(text) => match text.strip_prefix('-') {
+            Some(digits) => f.pad_integral(false, "", digits),
+            None => f.pad_integral(true, "", &text),
+        },
+        Err(_) => write!(f, "{:e}", value),
+    }
+}
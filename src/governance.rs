@@ -0,0 +1,152 @@
+//! Vote weights for token-lock governance.
+//!
+//! veToken-style programs give a lock more voting power the longer it has
+//! left to run, and let that power decay to zero at the unlock time. The
+//! curve is usually configured with a float (a half-life, a maximum lock),
+//! but the weight itself has to be an integer every validator agrees on, and
+//! it must never tick up as time passes, or a voter could gain power by
+//! waiting.
+
+use crate::bps::{BPS_DENOMINATOR, Bps};
+use crate::error::FloatError;
+use crate::float_ext::SolanaFloatExt;
+use crate::mul_div::mul_div;
+use crate::quantize::{RoundingMode, pow10};
+use crate::units::Amount;
+
+/// Fractional digits the decay factor is quantized to before it scales the
+/// initial weight.
+pub const DECAY_FACTOR_DECIMALS: u32 = 12;
+
+// ln(2)^k / k! for k = 16 down to 0, so that the series for 2^f has only
+// positive terms
+const EXP2_SERIES: [f64; 17] = [
+    1.3570247948755148e-16,
+    3.1324367070884287e-15,
+    6.778726354822545e-14,
+    1.3691488853904128e-12,
+    2.5678435993488206e-11,
+    4.4455382718708116e-10,
+    7.054911620801123e-09,
+    1.01780860092397e-07,
+    1.321548679014431e-06,
+    1.5252733804059841e-05,
+    0.0001540353039338161,
+    0.0013333558146428443,
+    0.009618129107628477,
+    0.05550410866482158,
+    0.24022650695910072,
+    std::f64::consts::LN_2,
+    1.0,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecayCurve {
+    /// Full weight with `max_lock` or more seconds left, then falling in a
+    /// straight line to zero at the unlock time.
+    Linear { max_lock: u64 },
+    /// `1 - 2^(-remaining / half_life)` of the full weight: half of it with
+    /// one half-life left, three quarters with two, approaching the full
+    /// weight for long locks.
+    Exponential { half_life: f64 },
+}
+
+/// The weight of a lock of `initial` that unlocks at `lock_end`, evaluated at
+/// `now` (both unix timestamps, as in `Clock::unix_timestamp`).
+///
+/// The result is rounded down and never exceeds `initial`, is zero from
+/// `lock_end` on, and never increases as `now` increases: every step of the
+/// evaluation, including the float ones, is monotonic in `now`. Fails with
+/// `NaNOperand` for a NaN half-life and `OutOfRange` for a zero `max_lock` or
+/// a half-life that is not positive and finite.
+pub fn decayed_weight(
+    initial: u64,
+    lock_end: i64,
+    now: i64,
+    curve: DecayCurve,
+) -> Result<u64, FloatError> {
+    curve.validate()?;
+    let remaining = lock_end.saturating_sub(now).max(0) as u64;
+    match curve {
+        DecayCurve::Linear { max_lock } => mul_div(
+            initial,
+            remaining.min(max_lock),
+            max_lock,
+            RoundingMode::Floor,
+        ),
+        DecayCurve::Exponential { half_life } => {
+            let scale = pow10(DECAY_FACTOR_DECIMALS)?;
+            let factor = 1.0 - exp2_neg(remaining as f64 / half_life);
+            // factor is within [0, 1], so the product is at most `scale`
+            let quantized = (factor * scale as f64).floor() as u64;
+            mul_div(initial, quantized, scale, RoundingMode::Floor)
+        }
+    }
+}
+
+/// Whether `votes` out of `total` reach `threshold_bps`, compared as
+/// `votes * 10_000 >= total * threshold_bps` in u128, so a vote exactly at the
+/// threshold passes and no division rounds it either way.
+///
+/// Fails with `DivisionByZero` for a zero `total` and `OutOfRange` if
+/// `votes` exceeds `total` or the threshold exceeds 100%.
+pub fn meets_quorum(votes: u64, total: u64, threshold_bps: Bps) -> Result<bool, FloatError> {
+    if total == 0 {
+        return Err(FloatError::DivisionByZero);
+    }
+    if votes > total || threshold_bps.0 as u64 > BPS_DENOMINATOR {
+        return Err(FloatError::OutOfRange);
+    }
+    Ok(votes as u128 * BPS_DENOMINATOR as u128 >= total as u128 * threshold_bps.0 as u128)
+}
+
+/// [`meets_quorum`] for float-configured programs: the amounts are converted
+/// to their stored base units and `threshold` (a fraction, `0.5` for half) to
+/// whole basis points, rounding half to even, before the exact comparison.
+pub fn meets_quorum_f64<const DECIMALS: u32>(
+    votes: Amount<DECIMALS>,
+    total: Amount<DECIMALS>,
+    threshold: f64,
+) -> Result<bool, FloatError> {
+    // Four decimals of a fraction are basis points
+    let threshold_bps =
+        u32::try_from(threshold.to_scaled(4)?).map_err(|_| FloatError::OutOfRange)?;
+    meets_quorum(votes.to_raw()?, total.to_raw()?, Bps(threshold_bps))
+}
+
+impl DecayCurve {
+    fn validate(self) -> Result<(), FloatError> {
+        match self {
+            DecayCurve::Linear { max_lock: 0 } => Err(FloatError::OutOfRange),
+            DecayCurve::Linear { .. } => Ok(()),
+            DecayCurve::Exponential { half_life } if half_life.is_nan() => {
+                Err(FloatError::NaNOperand)
+            }
+            DecayCurve::Exponential { half_life } => {
+                if half_life > 0.0 && half_life.is_finite() {
+                    Ok(())
+                } else {
+                    Err(FloatError::OutOfRange)
+                }
+            }
+        }
+    }
+}
+
+// 2^-x for x >= 0, non-increasing in x. Each operation is correctly rounded
+// and monotonic in its input, the series has positive terms, and capping it
+// at 2 keeps the value at the end of each unit interval above the value at
+// the start of the next.
+fn exp2_neg(x: f64) -> f64 {
+    let whole = x.floor();
+    if whole >= 64.0 {
+        // Below 2^-64, which cannot move 1 - 2^-x off 1.0
+        return 0.0;
+    }
+    let fraction = x - whole;
+    let mut series = 0.0;
+    for coefficient in EXP2_SERIES {
+        series = series * fraction + coefficient;
+    }
+    1.0 / (series.min(2.0) * (1u64 << whole as u32) as f64)
+}
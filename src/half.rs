@@ -0,0 +1,166 @@
+//! Half-precision operand encodings.
+//!
+//! Inputs that are inherently low precision (oracle confidences, fee tiers,
+//! weights quoted to three digits) can travel as 2-byte `f16` or `bf16` bit
+//! patterns instead of 8-byte f64s, which quarters the operand bytes of a batch
+//! instruction. Every f16 and bf16 value is exactly representable in f32 and
+//! f64, so widening never rounds: the program computes on exactly the values
+//! the client encoded.
+//!
+//! Narrowing is the lossy direction, so the encoders only accept values that
+//! survive the round trip and fail with `PrecisionLoss` (or `Overflow`)
+//! instead of rounding silently.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::error::FloatError;
+
+/// IEEE 754 binary16: 1 sign, 5 exponent and 10 mantissa bits.
+/// bfloat16: the top 16 bits of an f32, with 8 exponent and 7 mantissa bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum HalfFormat {
+    F16,
+    Bf16,
+}
+
+impl HalfFormat {
+    /// Widens `bits` in this format to f64, exactly.
+    pub fn to_f64(self, bits: u16) -> f64 {
+        match self {
+            HalfFormat::F16 => f16_to_f64(bits),
+            HalfFormat::Bf16 => bf16_to_f64(bits),
+        }
+    }
+
+    /// Encodes `value` in this format if that is exact.
+    pub fn from_f64(self, value: f64) -> Result<u16, FloatError> {
+        match self {
+            HalfFormat::F16 => f64_to_f16(value),
+            HalfFormat::Bf16 => f64_to_bf16(value),
+        }
+    }
+}
+
+impl TryFrom<u8> for HalfFormat {
+    type Error = FloatError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(HalfFormat::F16),
+            1 => Ok(HalfFormat::Bf16),
+            _ => Err(FloatError::OutOfRange),
+        }
+    }
+}
+
+const F16_EXPONENT_BIAS: i32 = 15;
+const F16_MANTISSA_BITS: u32 = 10;
+/// Largest finite f16.
+pub const F16_MAX: f64 = 65504.0;
+
+pub fn f16_to_f64(bits: u16) -> f64 {
+    let sign = ((bits >> 15) as u64) << 63;
+    let exponent = ((bits >> F16_MANTISSA_BITS) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as u64;
+    match exponent {
+        // Subnormal: mantissa * 2^-24, exact since the mantissa has 10 bits
+        0 => {
+            let magnitude = mantissa as f64 * f64::from_bits(0x3e70_0000_0000_0000);
+            f64::from_bits(sign | magnitude.to_bits())
+        }
+        // Infinity, or NaN with the payload carried over
+        0x1f => f64::from_bits(sign | 0x7ff0_0000_0000_0000 | mantissa << 42),
+        _ => {
+            let exponent = (exponent - F16_EXPONENT_BIAS + 1023) as u64;
+            f64::from_bits(sign | exponent << 52 | mantissa << 42)
+        }
+    }
+}
+
+pub fn f16_to_f32(bits: u16) -> f32 {
+    // Every f16 is an f32, so the narrowing cast is exact
+    f16_to_f64(bits) as f32
+}
+
+pub fn bf16_to_f32(bits: u16) -> f32 {
+    f32::from_bits((bits as u32) << 16)
+}
+
+pub fn bf16_to_f64(bits: u16) -> f64 {
+    bf16_to_f32(bits) as f64
+}
+
+/// Encodes `value` as f16. Fails with `NaNOperand` for NaN, `Overflow` beyond
+/// `F16_MAX`, and `PrecisionLoss` if the value is not exactly representable.
+pub fn f64_to_f16(value: f64) -> Result<u16, FloatError> {
+    if value.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    let sign = if value.is_sign_negative() { 0x8000 } else { 0 };
+    let magnitude = value.abs();
+    if magnitude == 0.0 {
+        return Ok(sign);
+    }
+    if magnitude.is_infinite() {
+        return Ok(sign | 0x7c00);
+    }
+    if magnitude > F16_MAX {
+        return Err(FloatError::Overflow);
+    }
+    let bits = magnitude.to_bits();
+    let exponent = (bits >> 52) as i32 - 1023;
+    if exponent < 1 - F16_EXPONENT_BIAS {
+        // Subnormal range: must be a whole multiple of 2^-24
+        let units = magnitude * f64::from_bits(0x4170_0000_0000_0000);
+        if units.fract() != 0.0 {
+            return Err(FloatError::PrecisionLoss);
+        }
+        return Ok(sign | units as u16);
+    }
+    let mantissa = bits & 0x000f_ffff_ffff_ffff;
+    if mantissa & ((1 << 42) - 1) != 0 {
+        return Err(FloatError::PrecisionLoss);
+    }
+    let exponent = (exponent + F16_EXPONENT_BIAS) as u16;
+    Ok(sign | exponent << F16_MANTISSA_BITS | (mantissa >> 42) as u16)
+}
+
+/// Encodes `value` as bf16. Fails with `NaNOperand` for NaN, `Overflow`
+/// beyond the f32 range, and `PrecisionLoss` if the value is not exactly
+/// representable.
+pub fn f64_to_bf16(value: f64) -> Result<u16, FloatError> {
+    if value.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    let narrowed = value as f32;
+    if narrowed.is_infinite() && value.is_finite() {
+        return Err(FloatError::Overflow);
+    }
+    let bits = narrowed.to_bits();
+    if narrowed as f64 != value || bits & 0xffff != 0 {
+        return Err(FloatError::PrecisionLoss);
+    }
+    Ok((bits >> 16) as u16)
+}
+
+/// Decodes little-endian 2-byte operands, widening each to f64. Fails with
+/// `OutOfRange` if `bytes` has an odd length.
+pub fn decode_operands(format: HalfFormat, bytes: &[u8]) -> Result<Vec<f64>, FloatError> {
+    let chunks = bytes.chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return Err(FloatError::OutOfRange);
+    }
+    Ok(chunks
+        .map(|chunk| format.to_f64(u16::from_le_bytes([chunk[0], chunk[1]])))
+        .collect())
+}
+
+/// Encodes `values` as little-endian 2-byte operands, failing on the first
+/// value that is not exactly representable.
+pub fn encode_operands(format: HalfFormat, values: &[f64]) -> Result<Vec<u8>, FloatError> {
+    let mut bytes = Vec::with_capacity(values.len() * 2);
+    for &value in values {
+        bytes.extend_from_slice(&format.from_f64(value)?.to_le_bytes());
+    }
+    Ok(bytes)
+}
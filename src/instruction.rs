@@ -0,0 +1,523 @@
+//! Instruction encoding.
+//!
+//! Two layouts are accepted:
+//!
+//! - Legacy: `[opcode: u8][payload]`, with the opcodes the program originally
+//!   shipped (`0..=2`). Legacy opcodes always stay below `0x80`.
+//! - Versioned: `[version: u8][discriminator: [u8; 8]][payload]`, where the
+//!   version byte has its high bit set and the discriminator is the first eight
+//!   bytes of `sha256("solana_floats:<instruction name>")`.
+//!
+//! New instructions are only added to the versioned layout. A payload change is
+//! shipped under a new instruction name, and therefore a new discriminator, so
+//! an old client can never have its bytes reinterpreted under a new layout.
+//!
+//! Payloads are the Borsh encoding of a [`FloatInstruction`] variant without
+//! its leading variant index, which the opcode or discriminator stands in for.
+//! Variants are declared in [`Opcode::ALL`] order, so for the legacy opcodes
+//! the whole legacy instruction is exactly the Borsh encoding of the variant.
+//! Floats are encoded as their bit patterns, NaN included, so a NaN operand
+//! reaches the handler and fails with the handler's error rather than as
+//! malformed data. Trailing bytes are rejected in both layouts.
+
+use borsh::io::{self, Read, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
+
+use crate::chain::ChainStep;
+use crate::compare::Tolerance;
+use crate::double_ops::{BinaryOp, UnaryOp};
+use crate::half::HalfFormat;
+use crate::operands::AccountOperands;
+use crate::quantize::RoundingMode;
+
+pub const VERSION_V1: u8 = 0x81;
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// `Evaluate` flag: append the worst-case error bound and operation count to
+/// the result in return data.
+pub const EVALUATE_FLAG_ANALYSIS: u8 = 0x01;
+
+/// `ApproxEq` flag: fail with `InvalidArgument` when the values are not within
+/// tolerance instead of returning `0`, so a program calling it through CPI can
+/// rely on the call succeeding alone.
+pub const APPROX_EQ_FLAG_REQUIRE: u8 = 0x01;
+
+/// `LogValue` styles: `msg!("{}")` formatting, or the hex bit pattern written
+/// by `logging::log_float_bits`.
+pub const LOG_STYLE_FORMATTED: u8 = 0;
+pub const LOG_STYLE_BITS: u8 = 1;
+
+/// `Sqrt` implementations: `double_ops::sqrt_double` or
+/// `double_ops::sqrt_double_fast`. Either way the instruction rejects
+/// operands as `Unary` with `UnaryOp::Sqrt` does: `OutOfRange` for negative
+/// values and `NaNOperand` for NaN and infinities.
+pub const SQRT_BUILTIN: u8 = 0;
+pub const SQRT_FAST: u8 = 1;
+
+/// `Subtract` operand widths: two f32 operands through
+/// `float_ops::sub_floats_with_error` (subject to the f32 feature gate) or two
+/// f64 operands through `double_ops::sub_doubles_with_error`.
+pub const SUBTRACT_F32: u8 = 0;
+pub const SUBTRACT_F64: u8 = 1;
+
+/// `Transcendental` functions and implementations, see the `transcendental`
+/// module.
+pub const TRANSCENDENTAL_EXP: u8 = 0;
+pub const TRANSCENDENTAL_LN: u8 = 1;
+pub const TRANSCENDENTAL_SERIES: u8 = 0;
+pub const TRANSCENDENTAL_LUT: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Add,
+    Multiply,
+    Divide,
+    BatchQuantize,
+    BatchQuantizeAccount,
+    SumAccount,
+    Cmp,
+    Format,
+    SetFeatures,
+    Verify,
+    Evaluate,
+    LogValue,
+    Sqrt,
+    Transcendental,
+    StatsAccount,
+    BatchQuantizeHalf,
+    ChainStep,
+    AddDoubles,
+    MultiplyDoubles,
+    DivideDoubles,
+    Subtract,
+    Unary,
+    RecordResult,
+    ApproxEq,
+    InitChainCache,
+}
+
+impl Opcode {
+    pub const ALL: [Opcode; 25] = [
+        Opcode::Add,
+        Opcode::Multiply,
+        Opcode::Divide,
+        Opcode::BatchQuantize,
+        Opcode::BatchQuantizeAccount,
+        Opcode::SumAccount,
+        Opcode::Cmp,
+        Opcode::Format,
+        Opcode::SetFeatures,
+        Opcode::Verify,
+        Opcode::Evaluate,
+        Opcode::LogValue,
+        Opcode::Sqrt,
+        Opcode::Transcendental,
+        Opcode::StatsAccount,
+        Opcode::BatchQuantizeHalf,
+        Opcode::ChainStep,
+        Opcode::AddDoubles,
+        Opcode::MultiplyDoubles,
+        Opcode::DivideDoubles,
+        Opcode::Subtract,
+        Opcode::Unary,
+        Opcode::RecordResult,
+        Opcode::ApproxEq,
+        Opcode::InitChainCache,
+    ];
+
+    /// Name hashed into the discriminator.
+    pub fn name(self) -> &'static str {
+        match self {
+            Opcode::Add => "add",
+            Opcode::Multiply => "multiply",
+            Opcode::Divide => "divide",
+            Opcode::BatchQuantize => "batch_quantize",
+            Opcode::BatchQuantizeAccount => "batch_quantize_account",
+            Opcode::SumAccount => "sum_account",
+            Opcode::Cmp => "cmp",
+            Opcode::Format => "format",
+            Opcode::SetFeatures => "set_features",
+            Opcode::Verify => "verify",
+            Opcode::Evaluate => "evaluate",
+            Opcode::LogValue => "log_value",
+            Opcode::Sqrt => "sqrt",
+            Opcode::Transcendental => "transcendental",
+            Opcode::StatsAccount => "stats_account",
+            Opcode::BatchQuantizeHalf => "batch_quantize_half",
+            Opcode::ChainStep => "chain_step",
+            Opcode::AddDoubles => "add_doubles",
+            Opcode::MultiplyDoubles => "multiply_doubles",
+            Opcode::DivideDoubles => "divide_doubles",
+            Opcode::Subtract => "subtract",
+            Opcode::Unary => "unary",
+            Opcode::RecordResult => "record_result",
+            Opcode::ApproxEq => "approx_eq",
+            Opcode::InitChainCache => "init_chain_cache",
+        }
+    }
+
+    pub fn discriminator(self) -> [u8; DISCRIMINATOR_LEN] {
+        match self {
+            Opcode::Add => [0xb5, 0xb9, 0x61, 0xe7, 0x61, 0x9a, 0x7f, 0xb1],
+            Opcode::Multiply => [0xae, 0x59, 0x5e, 0x4d, 0xbb, 0x98, 0xe6, 0xc0],
+            Opcode::Divide => [0x88, 0x12, 0xb5, 0x1d, 0xab, 0x26, 0xf0, 0xff],
+            Opcode::BatchQuantize => [0xb5, 0xc6, 0x74, 0x8e, 0x00, 0x47, 0x2c, 0x77],
+            Opcode::BatchQuantizeAccount => [0x7f, 0x03, 0x19, 0x9e, 0x16, 0xe5, 0xb7, 0x63],
+            Opcode::SumAccount => [0xde, 0xc2, 0xa5, 0xd4, 0x96, 0x3b, 0xdd, 0xf1],
+            Opcode::Cmp => [0x41, 0xc9, 0x6e, 0x23, 0x4d, 0xe1, 0x5d, 0x70],
+            Opcode::Format => [0xec, 0x5d, 0x5a, 0xe6, 0xca, 0xa6, 0x48, 0x92],
+            Opcode::SetFeatures => [0x75, 0xee, 0x14, 0x70, 0x70, 0x35, 0x82, 0x25],
+            Opcode::Verify => [0x1f, 0x02, 0x58, 0x4e, 0x6c, 0xb3, 0x04, 0xd1],
+            Opcode::Evaluate => [0xde, 0xd3, 0xeb, 0xbf, 0x69, 0xe7, 0x91, 0xf3],
+            Opcode::LogValue => [0xe1, 0x4e, 0x63, 0xec, 0x94, 0x19, 0x3f, 0x56],
+            Opcode::Sqrt => [0xba, 0x57, 0x87, 0x74, 0x94, 0xf5, 0xcd, 0x2d],
+            Opcode::Transcendental => [0x5a, 0x1a, 0x24, 0x7d, 0xdb, 0x3b, 0xf5, 0x15],
+            Opcode::StatsAccount => [0x0c, 0x24, 0xf3, 0x85, 0xce, 0xb7, 0xb1, 0xbe],
+            Opcode::BatchQuantizeHalf => [0x81, 0x39, 0xaf, 0x8f, 0x20, 0xdc, 0x4d, 0xfe],
+            Opcode::ChainStep => [0x5b, 0x91, 0xdf, 0x30, 0x99, 0x18, 0x4a, 0x4c],
+            Opcode::AddDoubles => [0x43, 0x0e, 0x48, 0x9b, 0xb2, 0x37, 0xae, 0xcc],
+            Opcode::MultiplyDoubles => [0xce, 0x36, 0x29, 0x64, 0x41, 0x1b, 0x58, 0x33],
+            Opcode::DivideDoubles => [0xb2, 0x96, 0x5d, 0xd4, 0x4a, 0xb8, 0x03, 0x36],
+            Opcode::Subtract => [0x7d, 0x96, 0xb1, 0x7a, 0x4b, 0x5c, 0xc3, 0xec],
+            Opcode::Unary => [0x40, 0xf3, 0x74, 0xfb, 0x49, 0x3d, 0xb7, 0x9a],
+            Opcode::RecordResult => [0xcb, 0x9d, 0xa1, 0x1e, 0x23, 0x95, 0x67, 0xef],
+            Opcode::ApproxEq => [0x8e, 0x90, 0xc9, 0x09, 0x07, 0x10, 0x13, 0x70],
+            Opcode::InitChainCache => [0xcb, 0x80, 0x40, 0x75, 0xf5, 0x82, 0x6c, 0x6c],
+        }
+    }
+
+    /// Opcode byte in the legacy layout, if this instruction has one.
+    pub fn legacy_opcode(self) -> Option<u8> {
+        match self {
+            Opcode::Add => Some(0),
+            Opcode::Multiply => Some(1),
+            Opcode::Divide => Some(2),
+            _ => None,
+        }
+    }
+
+    fn from_legacy_opcode(opcode: u8) -> Option<Opcode> {
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.legacy_opcode() == Some(opcode))
+    }
+
+    fn from_discriminator(discriminator: &[u8]) -> Option<Opcode> {
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.discriminator() == discriminator)
+    }
+}
+
+/// Splits instruction data into its opcode and payload, accepting both layouts.
+pub fn decode(instruction_data: &[u8]) -> Result<(Opcode, &[u8]), ProgramError> {
+    let (&first, rest) = instruction_data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    let opcode = match first {
+        VERSION_V1 => {
+            if rest.len() < DISCRIMINATOR_LEN {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let (discriminator, payload) = rest.split_at(DISCRIMINATOR_LEN);
+            let opcode = Opcode::from_discriminator(discriminator)
+                .ok_or(ProgramError::InvalidInstructionData)?;
+            return Ok((opcode, payload));
+        }
+        opcode if opcode < 0x80 => Opcode::from_legacy_opcode(opcode),
+        _ => None,
+    };
+    opcode
+        .map(|opcode| (opcode, rest))
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+/// Builds versioned (v1) instruction data for `opcode` with `payload`.
+pub fn encode(opcode: Opcode, payload: &[u8]) -> Vec<u8> {
+    let mut instruction_data = Vec::with_capacity(1 + DISCRIMINATOR_LEN + payload.len());
+    instruction_data.push(VERSION_V1);
+    instruction_data.extend_from_slice(&opcode.discriminator());
+    instruction_data.extend_from_slice(payload);
+    instruction_data
+}
+
+/// Two f32 operands: `[a: f32][b: f32]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct F32Operands {
+    pub a: f32,
+    pub b: f32,
+}
+
+/// Two f64 operands: `[a: f64][b: f64]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct F64Operands {
+    pub a: f64,
+    pub b: f64,
+}
+
+/// `Subtract` operands, tagged with `SUBTRACT_F32` or `SUBTRACT_F64`.
+#[derive(Debug, Clone, Copy, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum SubtractOperands {
+    F32(F32Operands),
+    F64(F64Operands),
+}
+
+/// A decoded instruction. Fields are listed in payload order; see the
+/// handlers in the crate root for the accounts and return data of each.
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum FloatInstruction {
+    Add(F32Operands),
+    Multiply(F32Operands),
+    Divide(F32Operands),
+    /// `values` runs to the end of the payload, with no length prefix.
+    BatchQuantize {
+        decimals: u8,
+        mode: RoundingMode,
+        #[borsh(serialize_with = "write_f64_tail", deserialize_with = "read_f64_tail")]
+        values: Vec<f64>,
+    },
+    BatchQuantizeAccount {
+        operands: AccountOperands,
+        decimals: u8,
+        mode: RoundingMode,
+    },
+    SumAccount(AccountOperands),
+    /// `tolerance` is either absent or the last 8 bytes of the payload.
+    Cmp {
+        operands: F64Operands,
+        #[borsh(
+            serialize_with = "write_optional_f64_tail",
+            deserialize_with = "read_optional_f64_tail"
+        )]
+        tolerance: Option<f64>,
+    },
+    Format {
+        decimals: u8,
+        mode: RoundingMode,
+        #[borsh(serialize_with = "write_f64", deserialize_with = "read_f64")]
+        value: f64,
+    },
+    SetFeatures {
+        f32_enabled: bool,
+    },
+    Verify {
+        op: BinaryOp,
+        operands: F64Operands,
+        claimed_bits: u64,
+    },
+    Evaluate {
+        flags: u8,
+        op: BinaryOp,
+        operands: F64Operands,
+    },
+    LogValue {
+        style: u8,
+        #[borsh(serialize_with = "write_f64", deserialize_with = "read_f64")]
+        value: f64,
+    },
+    Sqrt {
+        implementation: u8,
+        #[borsh(serialize_with = "write_f64", deserialize_with = "read_f64")]
+        value: f64,
+    },
+    Transcendental {
+        function: u8,
+        implementation: u8,
+        #[borsh(serialize_with = "write_f64", deserialize_with = "read_f64")]
+        value: f64,
+    },
+    StatsAccount(AccountOperands),
+    /// `values` are f16 or bf16 bit patterns running to the end of the
+    /// payload, with no length prefix.
+    BatchQuantizeHalf {
+        decimals: u8,
+        mode: RoundingMode,
+        format: HalfFormat,
+        #[borsh(serialize_with = "write_u16_tail", deserialize_with = "read_u16_tail")]
+        values: Vec<u16>,
+    },
+    ChainStep(ChainStep),
+    AddDoubles(F64Operands),
+    MultiplyDoubles(F64Operands),
+    DivideDoubles(F64Operands),
+    Subtract(SubtractOperands),
+    Unary {
+        op: UnaryOp,
+        #[borsh(serialize_with = "write_f64", deserialize_with = "read_f64")]
+        value: f64,
+    },
+    RecordResult {
+        op: BinaryOp,
+        operands: F64Operands,
+    },
+    /// `tolerance` is `[abs: f64][rel: f64]`; bounds `Tolerance::new` would
+    /// reject are invalid instruction data.
+    ApproxEq {
+        flags: u8,
+        operands: F64Operands,
+        tolerance: Tolerance,
+    },
+    InitChainCache {
+        slots: u16,
+    },
+}
+
+impl FloatInstruction {
+    pub fn opcode(&self) -> Opcode {
+        match self {
+            FloatInstruction::Add(_) => Opcode::Add,
+            FloatInstruction::Multiply(_) => Opcode::Multiply,
+            FloatInstruction::Divide(_) => Opcode::Divide,
+            FloatInstruction::BatchQuantize { .. } => Opcode::BatchQuantize,
+            FloatInstruction::BatchQuantizeAccount { .. } => Opcode::BatchQuantizeAccount,
+            FloatInstruction::SumAccount(_) => Opcode::SumAccount,
+            FloatInstruction::Cmp { .. } => Opcode::Cmp,
+            FloatInstruction::Format { .. } => Opcode::Format,
+            FloatInstruction::SetFeatures { .. } => Opcode::SetFeatures,
+            FloatInstruction::Verify { .. } => Opcode::Verify,
+            FloatInstruction::Evaluate { .. } => Opcode::Evaluate,
+            FloatInstruction::LogValue { .. } => Opcode::LogValue,
+            FloatInstruction::Sqrt { .. } => Opcode::Sqrt,
+            FloatInstruction::Transcendental { .. } => Opcode::Transcendental,
+            FloatInstruction::StatsAccount(_) => Opcode::StatsAccount,
+            FloatInstruction::BatchQuantizeHalf { .. } => Opcode::BatchQuantizeHalf,
+            FloatInstruction::ChainStep(_) => Opcode::ChainStep,
+            FloatInstruction::AddDoubles(_) => Opcode::AddDoubles,
+            FloatInstruction::MultiplyDoubles(_) => Opcode::MultiplyDoubles,
+            FloatInstruction::DivideDoubles(_) => Opcode::DivideDoubles,
+            FloatInstruction::Subtract(_) => Opcode::Subtract,
+            FloatInstruction::Unary { .. } => Opcode::Unary,
+            FloatInstruction::RecordResult { .. } => Opcode::RecordResult,
+            FloatInstruction::ApproxEq { .. } => Opcode::ApproxEq,
+            FloatInstruction::InitChainCache { .. } => Opcode::InitChainCache,
+        }
+    }
+
+    /// Decodes instruction data in either layout.
+    pub fn unpack(instruction_data: &[u8]) -> Result<Self, ProgramError> {
+        let (opcode, payload) = decode(instruction_data)?;
+        // The variant index the payload was encoded without
+        let index = [opcode as u8];
+        borsh::from_reader(&mut index.as_slice().chain(payload))
+            .map_err(|_| ProgramError::InvalidInstructionData)
+    }
+
+    /// Versioned (v1) instruction data.
+    pub fn pack(&self) -> Vec<u8> {
+        let data = borsh::to_vec(self).unwrap();
+        encode(self.opcode(), &data[1..])
+    }
+
+    /// Legacy instruction data, for the instructions that have a legacy
+    /// opcode.
+    pub fn pack_legacy(&self) -> Option<Vec<u8>> {
+        self.opcode()
+            .legacy_opcode()
+            .map(|_| borsh::to_vec(self).unwrap())
+    }
+}
+
+// Borsh refuses NaN floats, so operands go through their bit patterns
+
+impl BorshSerialize for F32Operands {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.a.to_le_bytes())?;
+        writer.write_all(&self.b.to_le_bytes())
+    }
+}
+
+impl BorshDeserialize for F32Operands {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(Self {
+            a: f32::from_bits(u32::deserialize_reader(reader)?),
+            b: f32::from_bits(u32::deserialize_reader(reader)?),
+        })
+    }
+}
+
+impl BorshSerialize for F64Operands {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_f64(&self.a, writer)?;
+        write_f64(&self.b, writer)
+    }
+}
+
+impl BorshDeserialize for F64Operands {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(Self {
+            a: read_f64(reader)?,
+            b: read_f64(reader)?,
+        })
+    }
+}
+
+impl BorshSerialize for Tolerance {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_f64(&self.abs(), writer)?;
+        write_f64(&self.rel(), writer)
+    }
+}
+
+impl BorshDeserialize for Tolerance {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let abs = read_f64(reader)?;
+        let rel = read_f64(reader)?;
+        Tolerance::new(abs, rel).map_err(|_| io::ErrorKind::InvalidData.into())
+    }
+}
+
+fn write_f64<W: Write>(value: &f64, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn read_f64<R: Read>(reader: &mut R) -> io::Result<f64> {
+    Ok(f64::from_bits(u64::deserialize_reader(reader)?))
+}
+
+fn write_f64_tail<W: Write>(values: &[f64], writer: &mut W) -> io::Result<()> {
+    values.iter().try_for_each(|value| write_f64(value, writer))
+}
+
+fn read_f64_tail<R: Read>(reader: &mut R) -> io::Result<Vec<f64>> {
+    read_tail(reader, f64::from_le_bytes)
+}
+
+fn write_u16_tail<W: Write>(values: &[u16], writer: &mut W) -> io::Result<()> {
+    values
+        .iter()
+        .try_for_each(|value| writer.write_all(&value.to_le_bytes()))
+}
+
+fn read_u16_tail<R: Read>(reader: &mut R) -> io::Result<Vec<u16>> {
+    read_tail(reader, u16::from_le_bytes)
+}
+
+fn write_optional_f64_tail<W: Write>(value: &Option<f64>, writer: &mut W) -> io::Result<()> {
+    value.iter().try_for_each(|value| write_f64(value, writer))
+}
+
+fn read_optional_f64_tail<R: Read>(reader: &mut R) -> io::Result<Option<f64>> {
+    let mut values = read_f64_tail(reader)?;
+    if values.len() > 1 {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+    Ok(values.pop())
+}
+
+// The rest of the payload as `N`-byte values
+fn read_tail<R: Read, T, const N: usize>(
+    reader: &mut R,
+    from_bytes: impl Fn([u8; N]) -> T,
+) -> io::Result<Vec<T>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let chunks = bytes.chunks_exact(N);
+    if !chunks.remainder().is_empty() {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+    Ok(chunks
+        .map(|chunk| from_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
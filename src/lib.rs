@@ -1,59 +1,233 @@
 pub mod float_ops;
 pub mod double_ops;
+pub mod error;
+pub mod quantize;
+pub mod ratio;
+pub mod scaled_decimal;
+pub mod mul_div;
+pub mod dust;
+pub mod reciprocal;
+pub mod shares;
+pub mod escrow;
+pub mod portfolio;
+pub mod stake_pool;
+pub mod clmm;
+pub mod amm;
+pub mod array_ops;
+pub mod accumulate;
+pub mod stats;
+pub mod stable;
+pub mod rng;
+pub mod rates;
+pub mod governance;
+pub mod instruction;
+pub mod operands;
+pub mod batch;
+pub mod chain;
+pub mod compare;
+pub mod bps;
+pub mod macros;
+pub mod format;
+pub mod half;
+pub mod logging;
+pub mod account_tag;
+pub mod feature_gate;
+pub mod results_log;
+pub mod verify;
+pub mod tracked;
+pub mod u256;
+pub mod fixed_point;
+pub mod evm_compat;
+pub mod corpus;
+pub mod softref;
+#[cfg(not(target_os = "solana"))]
+pub mod divergence;
+pub mod safe_f64;
+pub mod safe_f32;
+pub mod decimal64;
+pub mod decimal;
+pub mod big_decimal;
+pub mod float_ext;
+pub mod units;
+pub mod cross_rate;
+pub mod transcendental;
+pub mod stability;
+pub mod prelude;
+#[cfg(feature = "num-traits")]
+pub mod num_compat;
+#[cfg(feature = "client")]
+pub mod client;
 
+#[cfg(feature = "macros")]
+pub use solana_floats_macros::deny_float_eq;
+
+use feature_gate::{FEATURE_GATE_SEED, FeatureGate};
+use instruction::{F32Operands, F64Operands, FloatInstruction, Opcode, SubtractOperands};
+use operands::AccountOperands;
+use results_log::{RESULTS_LOG_SEED, ResultEntry, ResultsLog};
 use solana_program::{
-    account_info::AccountInfo,
-    entrypoint,
+    account_info::{AccountInfo, next_account_info},
     entrypoint::ProgramResult,
     msg,
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
     pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
 };
+use solana_system_interface::instruction as system_instruction;
+
+#[cfg(feature = "cu-report")]
+use solana_program::compute_units::sol_remaining_compute_units;
+#[cfg(not(feature = "no-entrypoint"))]
+use solana_program::entrypoint;
 
+#[cfg(not(feature = "no-entrypoint"))]
 entrypoint!(process_instruction);
 
 pub fn process_instruction(
-    _program_id: &Pubkey,
-    _accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    if instruction_data.is_empty() {
-        return Err(ProgramError::InvalidInstructionData);
+    let instruction = FloatInstruction::unpack(instruction_data)?;
+
+    #[cfg(feature = "cu-report")]
+    let (opcode, remaining_before) = (instruction.opcode(), sol_remaining_compute_units());
+    let result = dispatch(program_id, accounts, instruction);
+    #[cfg(feature = "cu-report")]
+    {
+        let consumed = remaining_before.saturating_sub(sol_remaining_compute_units());
+        logging::log_compute_units(opcode.name(), consumed);
     }
+    result
+}
 
-    let instruction_type = instruction_data[0];
-    
-    if instruction_data.len() < 9 {
-        return Err(ProgramError::InvalidInstructionData);
+fn dispatch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction: FloatInstruction,
+) -> ProgramResult {
+    let opcode = instruction.opcode();
+    match instruction {
+        FloatInstruction::Add(operands)
+        | FloatInstruction::Multiply(operands)
+        | FloatInstruction::Divide(operands) => {
+            check_f32_enabled(program_id, accounts)?;
+            process_f32_instruction(opcode, operands)
+        }
+        FloatInstruction::AddDoubles(operands)
+        | FloatInstruction::MultiplyDoubles(operands)
+        | FloatInstruction::DivideDoubles(operands) => process_f64_instruction(opcode, operands),
+        FloatInstruction::Subtract(operands) => process_subtract(program_id, accounts, operands),
+        FloatInstruction::BatchQuantize {
+            decimals,
+            mode,
+            values,
+        } => quantize_and_log(values, decimals.into(), mode),
+        FloatInstruction::BatchQuantizeHalf {
+            decimals,
+            mode,
+            format,
+            values,
+        } => process_batch_quantize_half(decimals, mode, format, &values),
+        FloatInstruction::BatchQuantizeAccount {
+            operands,
+            decimals,
+            mode,
+        } => process_batch_quantize_account(accounts, operands, decimals, mode),
+        FloatInstruction::SumAccount(operands) => process_sum_account(accounts, operands),
+        FloatInstruction::StatsAccount(operands) => process_stats_account(accounts, operands),
+        FloatInstruction::Cmp {
+            operands,
+            tolerance,
+        } => process_cmp(operands, tolerance),
+        FloatInstruction::Format {
+            decimals,
+            mode,
+            value,
+        } => process_format(decimals, mode, value),
+        FloatInstruction::SetFeatures { f32_enabled } => {
+            process_set_features(program_id, accounts, f32_enabled)
+        }
+        FloatInstruction::Verify {
+            op,
+            operands,
+            claimed_bits,
+        } => process_verify(op, operands, claimed_bits),
+        FloatInstruction::Evaluate {
+            flags,
+            op,
+            operands,
+        } => process_evaluate(flags, op, operands),
+        FloatInstruction::LogValue { style, value } => process_log_value(style, value),
+        FloatInstruction::Sqrt {
+            implementation,
+            value,
+        } => process_sqrt(implementation, value),
+        FloatInstruction::Unary { op, value } => process_unary(op, value),
+        FloatInstruction::Transcendental {
+            function,
+            implementation,
+            value,
+        } => process_transcendental(function, implementation, value),
+        FloatInstruction::InitChainCache { slots } => {
+            process_init_chain_cache(program_id, accounts, slots)
+        }
+        FloatInstruction::ChainStep(step) => process_chain_step(program_id, accounts, step),
+        FloatInstruction::RecordResult { op, operands } => {
+            process_record_result(program_id, accounts, op, operands)
+        }
+        FloatInstruction::ApproxEq {
+            flags,
+            operands,
+            tolerance,
+        } => process_approx_eq(flags, operands, tolerance),
     }
+}
 
-    let a_bytes: [u8; 4] = instruction_data[1..5].try_into()
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
-    let b_bytes: [u8; 4] = instruction_data[5..9].try_into()
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
-    
-    let a = f32::from_le_bytes(a_bytes);
-    let b = f32::from_le_bytes(b_bytes);
+// Accounts: [feature gate PDA]
+fn check_f32_enabled(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let gate_account = next_account_info(account_info_iter)?;
+    match FeatureGate::load(program_id, gate_account)? {
+        Some(gate) if !gate.f32_enabled => {
+            msg!("f32 operations are disabled");
+            Err(ProgramError::InvalidInstructionData)
+        }
+        _ => Ok(()),
+    }
+}
 
-    match instruction_type {
-        0 => {
+fn process_f32_instruction(opcode: Opcode, F32Operands { a, b }: F32Operands) -> ProgramResult {
+    match opcode {
+        Opcode::Add => {
             // Add
             let result = float_ops::add_floats(a, b);
+            #[cfg(not(feature = "no-onchain-format"))]
             msg!("Add: {} + {} = {}", a, b, result);
+            #[cfg(feature = "no-onchain-format")]
+            logging::log_f32_bits("Add", result);
         }
-        1 => {
+        Opcode::Multiply => {
             // Multiply
             let result = float_ops::multiply_floats(a, b);
+            #[cfg(not(feature = "no-onchain-format"))]
             msg!("Multiply: {} * {} = {}", a, b, result);
+            #[cfg(feature = "no-onchain-format")]
+            logging::log_f32_bits("Multiply", result);
         }
-        2 => {
+        Opcode::Divide => {
             // Divide
             match float_ops::divide_floats(a, b) {
                 Ok(result) => {
+                    #[cfg(not(feature = "no-onchain-format"))]
                     msg!("Divide: {} / {} = {}", a, b, result);
+                    #[cfg(feature = "no-onchain-format")]
+                    logging::log_f32_bits("Divide", result);
                 }
-                Err(_) => {
-                    return Err(ProgramError::InvalidArgument);
+                Err(err) => {
+                    return Err(err.into());
                 }
             }
         }
@@ -63,4 +237,532 @@ pub fn process_instruction(
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// Return data: [result: f64]
+fn process_f64_instruction(opcode: Opcode, F64Operands { a, b }: F64Operands) -> ProgramResult {
+    let result = match opcode {
+        Opcode::AddDoubles => {
+            let result = double_ops::add_doubles(a, b);
+            #[cfg(not(feature = "no-onchain-format"))]
+            msg!("AddDoubles: {} + {} = {}", a, b, result);
+            result
+        }
+        Opcode::MultiplyDoubles => {
+            let result = double_ops::multiply_doubles(a, b);
+            #[cfg(not(feature = "no-onchain-format"))]
+            msg!("MultiplyDoubles: {} * {} = {}", a, b, result);
+            result
+        }
+        Opcode::DivideDoubles => {
+            let result = double_ops::divide_doubles(a, b)?;
+            #[cfg(not(feature = "no-onchain-format"))]
+            msg!("DivideDoubles: {} / {} = {}", a, b, result);
+            result
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+    #[cfg(feature = "no-onchain-format")]
+    logging::log_float_bits(opcode.name(), result);
+    set_return_data(&result.to_le_bytes());
+
+    Ok(())
+}
+
+// Accounts: [feature gate PDA] for f32 operands
+// Return data: [result: f64][cancelled bits: u32][relative error: f64], f32
+// values widened exactly
+fn process_subtract(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    operands: SubtractOperands,
+) -> ProgramResult {
+    let (value, cancelled_bits, relative_error, digits) = match operands {
+        SubtractOperands::F32(F32Operands { a, b }) => {
+            check_f32_enabled(program_id, accounts)?;
+            let difference = float_ops::sub_floats_with_error(a, b)?;
+            #[cfg(not(feature = "no-onchain-format"))]
+            msg!("Subtract: {} - {} = {}", a, b, difference.value);
+            #[cfg(feature = "no-onchain-format")]
+            logging::log_f32_bits("Subtract", difference.value);
+            (
+                difference.value as f64,
+                difference.cancelled_bits,
+                difference.relative_error as f64,
+                f32::MANTISSA_DIGITS,
+            )
+        }
+        SubtractOperands::F64(F64Operands { a, b }) => {
+            let difference = double_ops::sub_doubles_with_error(a, b)?;
+            #[cfg(not(feature = "no-onchain-format"))]
+            msg!("Subtract: {} - {} = {}", a, b, difference.value);
+            #[cfg(feature = "no-onchain-format")]
+            logging::log_float_bits("Subtract", difference.value);
+            (
+                difference.value,
+                difference.cancelled_bits,
+                difference.relative_error,
+                f64::MANTISSA_DIGITS,
+            )
+        }
+    };
+
+    // Past half the significand, earlier rounding error in the operands
+    // dominates the difference
+    if cancelled_bits * 2 > digits {
+        msg!(
+            "Subtract: catastrophic cancellation, {} of {} significand bits cancelled",
+            cancelled_bits,
+            digits
+        );
+    }
+    let mut return_data = value.to_le_bytes().to_vec();
+    return_data.extend_from_slice(&cancelled_bits.to_le_bytes());
+    return_data.extend_from_slice(&relative_error.to_le_bytes());
+    set_return_data(&return_data);
+
+    Ok(())
+}
+
+// Values are widened exactly to f64.
+fn process_batch_quantize_half(
+    decimals: u8,
+    mode: quantize::RoundingMode,
+    format: half::HalfFormat,
+    values: &[u16],
+) -> ProgramResult {
+    let values = values.iter().map(|&bits| format.to_f64(bits)).collect();
+
+    quantize_and_log(values, decimals.into(), mode)
+}
+
+// Accounts: [operand account]
+fn process_batch_quantize_account(
+    accounts: &[AccountInfo],
+    operands: AccountOperands,
+    decimals: u8,
+    mode: quantize::RoundingMode,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let operand_account = next_account_info(account_info_iter)?;
+    let data = operand_account.try_borrow_data()?;
+    let policy = quantize::PrecisionPolicy::new(decimals.into(), mode)?;
+
+    // Streams the operands so that no copy of the account data is made; only
+    // the results that fit in return data are kept
+    let mut returned = [0.0; batch::MAX_BATCH_RESULTS];
+    let mut total = 0;
+    for value in operands.iter(&data)? {
+        let value = policy.quantize(value)?;
+        if let Some(slot) = returned.get_mut(total) {
+            *slot = value;
+        }
+        total += 1;
+    }
+    msg!("BatchQuantize: {} values to {} decimals", total, decimals);
+    let returned = &returned[..total.min(batch::MAX_BATCH_RESULTS)];
+    set_return_data(&batch::encode_batch_results_prefix(returned, total));
+
+    Ok(())
+}
+
+// Accounts: [operand account]
+// Return data: packed `stats::RunningStats`
+fn process_stats_account(accounts: &[AccountInfo], operands: AccountOperands) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let operand_account = next_account_info(account_info_iter)?;
+    let data = operand_account.try_borrow_data()?;
+
+    let mut stats = stats::RunningStats::new();
+    stats.extend(operands.iter(&data)?)?;
+    msg!("StatsAccount: {} values", stats.count());
+    set_return_data(&stats.pack());
+
+    Ok(())
+}
+
+// Accounts: [operand account]
+fn process_sum_account(accounts: &[AccountInfo], operands: AccountOperands) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let operand_account = next_account_info(account_info_iter)?;
+    let data = operand_account.try_borrow_data()?;
+    let values = operands.view(&data)?;
+
+    let result = array_ops::sum_doubles_compensated(values);
+    #[cfg(not(feature = "no-onchain-format"))]
+    msg!("SumAccount: {} values = {}", values.len(), result);
+    #[cfg(feature = "no-onchain-format")]
+    logging::log_float_bits("SumAccount", result);
+
+    Ok(())
+}
+
+// Return data: [ordering: i8] (-1 less, 0 equal, 1 greater)
+fn process_cmp(F64Operands { a, b }: F64Operands, tolerance: Option<f64>) -> ProgramResult {
+    let ordering = compare::total_cmp_with_tolerance(a, b, tolerance.unwrap_or(0.0))?;
+    msg!("Cmp: {:?}", ordering);
+    set_return_data(&[compare::encode_ordering(ordering)]);
+
+    Ok(())
+}
+
+// Return data: [equal: u8] (1 within tolerance, 0 not)
+fn process_approx_eq(
+    flags: u8,
+    F64Operands { a, b }: F64Operands,
+    tolerance: compare::Tolerance,
+) -> ProgramResult {
+    if flags & !instruction::APPROX_EQ_FLAG_REQUIRE != 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let equal = tolerance.matches(a, b);
+    if !equal && flags & instruction::APPROX_EQ_FLAG_REQUIRE != 0 {
+        msg!("ApproxEq: values are not within tolerance");
+        return Err(ProgramError::InvalidArgument);
+    }
+    msg!("ApproxEq: {}", equal);
+    set_return_data(&[equal as u8]);
+
+    Ok(())
+}
+
+// Return data: ASCII decimal string
+fn process_format(decimals: u8, mode: quantize::RoundingMode, value: f64) -> ProgramResult {
+    let text = format::format_fixed(value, decimals.into(), mode)?;
+    msg!("Format: {}", text);
+    set_return_data(text.as_bytes());
+
+    Ok(())
+}
+
+fn process_verify(
+    op: double_ops::BinaryOp,
+    F64Operands { a, b }: F64Operands,
+    claimed_bits: u64,
+) -> ProgramResult {
+    let matches = verify::verify(op, a, b, claimed_bits)?;
+    if !matches {
+        msg!("Verify: {:?} result does not match claim", op);
+        return Err(ProgramError::InvalidArgument);
+    }
+    msg!("Verify: {:?} result matches claim", op);
+
+    Ok(())
+}
+
+// Return data: [result bits: u64], then [error bound: f64][ops: u32] in analysis mode
+fn process_evaluate(
+    flags: u8,
+    op: double_ops::BinaryOp,
+    F64Operands { a, b }: F64Operands,
+) -> ProgramResult {
+    if flags & !instruction::EVALUATE_FLAG_ANALYSIS != 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let result = tracked::TrackedF64::exact(a)
+        .and_then(|a| a.apply(op, tracked::TrackedF64::exact(b)?))?;
+    let mut return_data = result.value().to_bits().to_le_bytes().to_vec();
+    if flags & instruction::EVALUATE_FLAG_ANALYSIS != 0 {
+        return_data.extend_from_slice(&result.error_bound().to_le_bytes());
+        return_data.extend_from_slice(&result.ops().to_le_bytes());
+        #[cfg(not(feature = "no-onchain-format"))]
+        msg!("Evaluate: {:?} = {} (error <= {})", op, result.value(), result.error_bound());
+        #[cfg(feature = "no-onchain-format")]
+        logging::log_float_bits("Evaluate error bound", result.error_bound());
+    } else {
+        #[cfg(not(feature = "no-onchain-format"))]
+        msg!("Evaluate: {:?} = {}", op, result.value());
+    }
+    #[cfg(feature = "no-onchain-format")]
+    logging::log_float_bits("Evaluate", result.value());
+    set_return_data(&return_data);
+
+    Ok(())
+}
+
+// Logs the value formatted (style 0) or as its bit pattern (style 1), so the
+// compute cost of the two styles can be compared on-chain. With
+// `no-onchain-format` both styles log the bit pattern.
+fn process_log_value(style: u8, value: f64) -> ProgramResult {
+    match style {
+        #[cfg(not(feature = "no-onchain-format"))]
+        instruction::LOG_STYLE_FORMATTED => msg!("value: {}", value),
+        #[cfg(feature = "no-onchain-format")]
+        instruction::LOG_STYLE_FORMATTED => logging::log_float_bits("value", value),
+        instruction::LOG_STYLE_BITS => logging::log_float_bits("value", value),
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+
+    Ok(())
+}
+
+// Return data: [root: f64]
+fn process_sqrt(implementation: u8, value: f64) -> ProgramResult {
+    double_ops::check_sqrt_operand(value)?;
+    let root = match implementation {
+        instruction::SQRT_BUILTIN => double_ops::sqrt_double(value),
+        instruction::SQRT_FAST => double_ops::sqrt_double_fast(value),
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+    set_return_data(&root.to_le_bytes());
+
+    Ok(())
+}
+
+// Return data: [result: f64]
+fn process_unary(op: double_ops::UnaryOp, value: f64) -> ProgramResult {
+    let result = double_ops::apply_unary(op, value)?;
+    #[cfg(not(feature = "no-onchain-format"))]
+    msg!("Unary: {:?}({}) = {}", op, value, result);
+    #[cfg(feature = "no-onchain-format")]
+    logging::log_float_bits("Unary", result);
+    set_return_data(&result.to_le_bytes());
+
+    Ok(())
+}
+
+// Return data: [result: f64]
+fn process_transcendental(function: u8, implementation: u8, value: f64) -> ProgramResult {
+    let result = match (function, implementation) {
+        (instruction::TRANSCENDENTAL_EXP, instruction::TRANSCENDENTAL_SERIES) => {
+            transcendental::exp_deterministic(value)
+        }
+        (instruction::TRANSCENDENTAL_EXP, instruction::TRANSCENDENTAL_LUT) => {
+            transcendental::exp_lut(value)
+        }
+        (instruction::TRANSCENDENTAL_LN, instruction::TRANSCENDENTAL_SERIES) => {
+            transcendental::ln_deterministic(value)
+        }
+        (instruction::TRANSCENDENTAL_LN, instruction::TRANSCENDENTAL_LUT) => {
+            transcendental::ln_lut(value)
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+    set_return_data(&result.to_le_bytes());
+
+    Ok(())
+}
+
+// Accounts: [chain cache PDA (writable), authority (signer, writable), system program]
+// Succeeds without changes if the cache already holds `slots` slots.
+fn process_init_chain_cache(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    slots: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let cache_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (address, bump) = chain::find_cache_address(program_id, authority.key);
+    if *cache_account.key != address {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let len = chain::cache_len(slots.into());
+    if cache_account.owner == program_id {
+        let data = cache_account.try_borrow_data()?;
+        chain::check_cache_tag(&data)?;
+        if data.len() < len {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        return Ok(());
+    }
+    let system_program = next_account_info(account_info_iter)?;
+    create_program_account(
+        program_id,
+        authority,
+        cache_account,
+        system_program,
+        len,
+        &[chain::CHAIN_CACHE_SEED, authority.key.as_ref(), &[bump]],
+    )?;
+    chain::init_cache(&mut cache_account.try_borrow_mut_data()?)?;
+    msg!("InitChainCache: {} slots", slots);
+
+    Ok(())
+}
+
+// Accounts: [chain cache PDA (writable), authority (signer)]
+// Return data: [result: f64]
+fn process_chain_step(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    step: chain::ChainStep,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let cache_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // Only the authority's own cache; its tag is checked by `apply`
+    if *cache_account.key != chain::find_cache_address(program_id, authority.key).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if cache_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let result = step.apply(&mut cache_account.try_borrow_mut_data()?)?;
+    #[cfg(not(feature = "no-onchain-format"))]
+    msg!("ChainStep: slot {} = {:?} = {}", step.destination, step.op, result);
+    #[cfg(feature = "no-onchain-format")]
+    logging::log_float_bits("ChainStep", result);
+    set_return_data(&result.to_le_bytes());
+
+    Ok(())
+}
+
+// Accounts: [results log PDA (writable), authority (signer, writable), system program]
+// Return data: [result: f64]
+fn process_record_result(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    op: double_ops::BinaryOp,
+    F64Operands { a, b }: F64Operands,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let log_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let result = verify::recompute(op, a, b)?;
+
+    let mut log = match ResultsLog::load(program_id, authority.key, log_account)? {
+        Some(log) => log,
+        None => {
+            let system_program = next_account_info(account_info_iter)?;
+            let (_, bump) = ResultsLog::find_address(program_id, authority.key);
+            create_program_account(
+                program_id,
+                authority,
+                log_account,
+                system_program,
+                ResultsLog::LEN,
+                &[RESULTS_LOG_SEED, authority.key.as_ref(), &[bump]],
+            )?;
+            ResultsLog::new(*authority.key)
+        }
+    };
+    log.push(ResultEntry::new(op, a, b, result));
+    log.pack(&mut log_account.try_borrow_mut_data()?)?;
+    #[cfg(not(feature = "no-onchain-format"))]
+    msg!("RecordResult: entry {} = {:?} = {}", log.recorded - 1, op, result);
+    #[cfg(feature = "no-onchain-format")]
+    logging::log_float_bits("RecordResult", result);
+    set_return_data(&result.to_le_bytes());
+
+    Ok(())
+}
+
+// Creates the PDA `account` with `space` bytes, owned by this program and
+// funded by `payer` for rent exemption. An address that already holds
+// lamports cannot go through `create_account`, so it is topped up, allocated
+// and assigned instead; anyone can send lamports to an address before it
+// exists.
+fn create_program_account<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent_exempt = Rent::get()?.minimum_balance(space);
+    if account.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                account.key,
+                rent_exempt,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), account.clone(), system_program.clone()],
+            &[seeds],
+        );
+    }
+
+    let shortfall = rent_exempt.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, shortfall),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, program_id),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+// Accounts: [feature gate PDA (writable), admin (signer, writable), system program,
+// program data]; the last two are only needed to create the gate.
+fn process_set_features(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    f32_enabled: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let gate_account = next_account_info(account_info_iter)?;
+    let admin = next_account_info(account_info_iter)?;
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let gate = match FeatureGate::load(program_id, gate_account)? {
+        Some(gate) => {
+            if gate.admin != *admin.key {
+                return Err(ProgramError::IncorrectAuthority);
+            }
+            FeatureGate { f32_enabled, ..gate }
+        }
+        None => {
+            let system_program = next_account_info(account_info_iter)?;
+            let program_data = next_account_info(account_info_iter)?;
+            if feature_gate::upgrade_authority(program_id, program_data)? != Some(*admin.key) {
+                return Err(ProgramError::IncorrectAuthority);
+            }
+            let (_, bump) = FeatureGate::find_address(program_id);
+            create_program_account(
+                program_id,
+                admin,
+                gate_account,
+                system_program,
+                FeatureGate::LEN,
+                &[FEATURE_GATE_SEED, &[bump]],
+            )?;
+            FeatureGate {
+                admin: *admin.key,
+                f32_enabled,
+            }
+        }
+    };
+    gate.pack(&mut gate_account.try_borrow_mut_data()?)?;
+    msg!("SetFeatures: f32 enabled = {}", f32_enabled);
+
+    Ok(())
+}
+
+fn quantize_and_log(
+    mut values: Vec<f64>,
+    decimals: u32,
+    mode: quantize::RoundingMode,
+) -> ProgramResult {
+    quantize::quantize_slice(&mut values, decimals, mode)?;
+    msg!("BatchQuantize: {} values to {} decimals", values.len(), decimals);
+    set_return_data(&batch::encode_batch_results(&values));
+
+    Ok(())
+}
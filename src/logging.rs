@@ -0,0 +1,81 @@
+//! Cheap float logging.
+//!
+//! Formatting an f64 with `msg!("{}", value)` pulls in the float-to-decimal
+//! machinery of `core::fmt` and is one of the more expensive things a program
+//! can do per log line. Logging the bit pattern as hex only formats an integer,
+//! and loses nothing: [`parse_float_bits`] and [`pretty_print_logs`] turn the
+//! lines back into exact values off-chain.
+//!
+//! Line format: `<label>: f64 0x<16 hex digits>` (or `f32 0x<8 hex digits>`).
+//! `cargo test-sbf --test logging_tests -- --nocapture` prints the compute
+//! units of both styles through the `LogValue` instruction.
+
+use solana_program::msg;
+
+const F64_TAG: &str = ": f64 0x";
+const F32_TAG: &str = ": f32 0x";
+const COMPUTE_UNITS_TAG: &str = ": compute_units ";
+
+/// Logs `value` as `<label>: f64 0x<bits>`.
+pub fn log_float_bits(label: &str, value: f64) {
+    msg!("{}{}{:016x}", label, F64_TAG, value.to_bits());
+}
+
+/// Logs `value` as `<label>: f32 0x<bits>`.
+pub fn log_f32_bits(label: &str, value: f32) {
+    msg!("{}{}{:08x}", label, F32_TAG, value.to_bits());
+}
+
+/// Logs the compute units an operation consumed as
+/// `<operation>: compute_units <n>`.
+///
+/// With the `cu-report` feature the program writes one such line per
+/// instruction, measured with `sol_remaining_compute_units` around the
+/// dispatch, so the cost of float math can be monitored from transaction logs
+/// in production. [`parse_compute_units`] reads the lines back.
+pub fn log_compute_units(operation: &str, consumed: u64) {
+    msg!("{}{}{}", operation, COMPUTE_UNITS_TAG, consumed);
+}
+
+/// Recovers the operation name and count from a line written by
+/// [`log_compute_units`], with or without the `Program log: ` prefix.
+pub fn parse_compute_units(line: &str) -> Option<(&str, u64)> {
+    let line = line.strip_prefix("Program log: ").unwrap_or(line);
+    let (operation, count) = line.rsplit_once(COMPUTE_UNITS_TAG)?;
+    if count.is_empty() || !count.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    Some((operation, count.parse().ok()?))
+}
+
+/// Recovers the label and exact value from a line written by
+/// [`log_float_bits`] or [`log_f32_bits`], with or without the runtime's
+/// `Program log: ` prefix. f32 values are widened to f64, which is exact.
+pub fn parse_float_bits(line: &str) -> Option<(&str, f64)> {
+    let line = line.strip_prefix("Program log: ").unwrap_or(line);
+    if let Some((label, hex)) = line.rsplit_once(F64_TAG) {
+        if hex.len() != 16 {
+            return None;
+        }
+        let bits = u64::from_str_radix(hex, 16).ok()?;
+        return Some((label, f64::from_bits(bits)));
+    }
+    let (label, hex) = line.rsplit_once(F32_TAG)?;
+    if hex.len() != 8 {
+        return None;
+    }
+    let bits = u32::from_str_radix(hex, 16).ok()?;
+    Some((label, f32::from_bits(bits) as f64))
+}
+
+/// Rewrites bit-pattern lines as `<label>: <value>` using the shortest decimal
+/// that round-trips, and passes every other line through unchanged.
+pub fn pretty_print_logs<S: AsRef<str>>(lines: &[S]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| match parse_float_bits(line.as_ref()) {
+            Some((label, value)) => format!("{}: {:?}", label, value),
+            None => line.as_ref().to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,120 @@
+//! Assertion macros for float comparisons.
+//!
+//! Failure messages print both operands with their bit patterns, so values that
+//! display identically but differ in the last bit (or in the sign of zero) can
+//! be told apart. The `debug_` variants compile to nothing in release builds,
+//! which makes them cheap enough for on-chain invariant checks.
+
+use std::fmt;
+
+use crate::bps::{Bps, difference_bps};
+use crate::compare::Tolerance;
+
+/// Asserts that two f64 values match within a [`Tolerance`] (or anything that
+/// converts into one, such as [`Bps`]).
+///
+/// ```
+/// use solana_floats::approx_assert_eq;
+/// use solana_floats::compare::Tolerance;
+///
+/// approx_assert_eq!(0.1 + 0.2, 0.3, Tolerance::new(1e-15, 0.0).unwrap());
+/// ```
+#[macro_export]
+macro_rules! approx_assert_eq {
+    ($left:expr, $right:expr, $tolerance:expr $(,)?) => {
+        $crate::approx_assert_eq!($left, $right, $tolerance, "")
+    };
+    ($left:expr, $right:expr, $tolerance:expr, $($arg:tt)+) => {{
+        let (left, right): (f64, f64) = ($left, $right);
+        let tolerance = $crate::compare::Tolerance::from($tolerance);
+        if !tolerance.matches(left, right) {
+            $crate::macros::approx_failed(left, right, &tolerance, format_args!($($arg)+));
+        }
+    }};
+}
+
+/// Asserts that two f64 values differ by at most the given basis points of the
+/// larger magnitude. The bound is a [`Bps`] or a plain `u32`.
+///
+/// ```
+/// use solana_floats::assert_within_bps;
+///
+/// assert_within_bps!(100.0, 100.05, 5);
+/// ```
+#[macro_export]
+macro_rules! assert_within_bps {
+    ($left:expr, $right:expr, $bps:expr $(,)?) => {
+        $crate::assert_within_bps!($left, $right, $bps, "")
+    };
+    ($left:expr, $right:expr, $bps:expr, $($arg:tt)+) => {{
+        let (left, right): (f64, f64) = ($left, $right);
+        let bps = $crate::bps::Bps::from($bps);
+        if !$crate::bps::within_bps(left, right, bps) {
+            $crate::macros::bps_failed(left, right, bps, format_args!($($arg)+));
+        }
+    }};
+}
+
+/// [`approx_assert_eq!`] that is only checked with debug assertions enabled.
+#[macro_export]
+macro_rules! debug_approx_assert_eq {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::approx_assert_eq!($($arg)*);
+        }
+    };
+}
+
+/// [`assert_within_bps!`] that is only checked with debug assertions enabled.
+#[macro_export]
+macro_rules! debug_assert_within_bps {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::assert_within_bps!($($arg)*);
+        }
+    };
+}
+
+// Kept out of the macros so each call site only expands to a comparison.
+#[doc(hidden)]
+#[cold]
+#[track_caller]
+pub fn approx_failed(left: f64, right: f64, tolerance: &Tolerance, message: fmt::Arguments) -> ! {
+    panic!(
+        "assertion `left ≈ right` failed{}\n  left: {:?} ({:#018x})\n right: {:?} ({:#018x})\n  diff: {:?}\n   tol: abs {:?}, rel {:?}",
+        separator(message),
+        left,
+        left.to_bits(),
+        right,
+        right.to_bits(),
+        (left - right).abs(),
+        tolerance.abs(),
+        tolerance.rel(),
+    )
+}
+
+#[doc(hidden)]
+#[cold]
+#[track_caller]
+pub fn bps_failed(left: f64, right: f64, bps: Bps, message: fmt::Arguments) -> ! {
+    let apart = difference_bps(left, right).unwrap_or(f64::NAN);
+    panic!(
+        "assertion `left within {} bps of right` failed{}\n  left: {:?} ({:#018x})\n right: {:?} ({:#018x})\n  diff: {:?} bps",
+        bps.0,
+        separator(message),
+        left,
+        left.to_bits(),
+        right,
+        right.to_bits(),
+        apart,
+    )
+}
+
+fn separator(message: fmt::Arguments) -> String {
+    let message = message.to_string();
+    if message.is_empty() {
+        message
+    } else {
+        format!(": {}", message)
+    }
+}
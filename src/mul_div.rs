@@ -0,0 +1,91 @@
+//! Overflow-safe `a * b / denominator` on integers with an explicit rounding direction.
+
+use crate::error::FloatError;
+use crate::quantize::RoundingMode;
+use crate::u256::U256;
+
+/// Computes `a * b / denominator` through a u128 intermediate.
+///
+/// `const`, so fee schedules and scale factors can be computed at compile time.
+pub const fn mul_div(
+    a: u64,
+    b: u64,
+    denominator: u64,
+    rounding: RoundingMode,
+) -> Result<u64, FloatError> {
+    match mul_div_u128(a as u128, b as u128, denominator as u128, rounding) {
+        Ok(result) if result <= u64::MAX as u128 => Ok(result as u64),
+        Ok(_) => Err(FloatError::Overflow),
+        Err(error) => Err(error),
+    }
+}
+
+/// Computes `a * b / denominator` for u128 operands, failing if the product overflows.
+pub const fn mul_div_u128(
+    a: u128,
+    b: u128,
+    denominator: u128,
+    rounding: RoundingMode,
+) -> Result<u128, FloatError> {
+    if denominator == 0 {
+        return Err(FloatError::DivisionByZero);
+    }
+    let product = match a.checked_mul(b) {
+        Some(product) => product,
+        None => return Err(FloatError::Overflow),
+    };
+    let quotient = product / denominator;
+    let remainder = product % denominator;
+    if remainder == 0 {
+        return Ok(quotient);
+    }
+
+    // The remainder is strictly below the denominator, so compare it against the
+    // other half without doubling (which could overflow for large denominators).
+    let above_half = remainder > denominator - remainder;
+    let at_half = remainder == denominator - remainder;
+    let round_up = match rounding {
+        RoundingMode::Floor | RoundingMode::TowardZero => false,
+        RoundingMode::Ceil => true,
+        RoundingMode::HalfUp => above_half || at_half,
+        RoundingMode::HalfEven => above_half || (at_half && quotient % 2 == 1),
+    };
+    if !round_up {
+        return Ok(quotient);
+    }
+    match quotient.checked_add(1) {
+        Some(rounded) => Ok(rounded),
+        None => Err(FloatError::Overflow),
+    }
+}
+
+/// Computes `a * b / denominator` for U256 operands through a 512-bit
+/// intermediate, failing if the quotient does not fit in 256 bits.
+pub fn mul_div_u256(
+    a: U256,
+    b: U256,
+    denominator: U256,
+    rounding: RoundingMode,
+) -> Result<U256, FloatError> {
+    if denominator.is_zero() {
+        return Err(FloatError::DivisionByZero);
+    }
+    let (quotient, remainder) = U256::mul_div_rem(a, b, denominator).ok_or(FloatError::Overflow)?;
+    if remainder.is_zero() {
+        return Ok(quotient);
+    }
+    // As above, compare the remainder with the rest of the denominator
+    let rest = denominator.overflowing_sub(remainder).0;
+    let above_half = remainder > rest;
+    let at_half = remainder == rest;
+    let round_up = match rounding {
+        RoundingMode::Floor | RoundingMode::TowardZero => false,
+        RoundingMode::Ceil => true,
+        RoundingMode::HalfUp => above_half || at_half,
+        RoundingMode::HalfEven => above_half || (at_half && quotient.limbs()[0] & 1 == 1),
+    };
+    if !round_up {
+        return Ok(quotient);
+    }
+    quotient.checked_add(U256::ONE).ok_or(FloatError::Overflow)
+}
@@ -0,0 +1,105 @@
+//! `num-traits` implementations for the checked float wrappers.
+//!
+//! Only the conversion and bounds traits are implemented. `Num` and `Float`
+//! require infallible `Add`/`Mul`/`Div` operators, which would have to either
+//! panic or hand back the NaN and infinity these types exist to rule out, so
+//! generic code that needs them should convert at its boundary with
+//! `NumCast::from` / `ToPrimitive::to_f64`.
+//!
+//! Conversions into a wrapper fail (`None`) for NaN and infinite inputs.
+
+use crate::safe_f64::SafeF64;
+use crate::tracked::TrackedF64;
+use num_traits::{Bounded, FromPrimitive, NumCast, ToPrimitive};
+
+impl Bounded for SafeF64 {
+    fn min_value() -> Self {
+        SafeF64::MIN
+    }
+
+    fn max_value() -> Self {
+        SafeF64::MAX
+    }
+}
+
+impl ToPrimitive for SafeF64 {
+    fn to_i64(&self) -> Option<i64> {
+        self.get().to_i64()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.get().to_u64()
+    }
+
+    fn to_i128(&self) -> Option<i128> {
+        self.get().to_i128()
+    }
+
+    fn to_u128(&self) -> Option<u128> {
+        self.get().to_u128()
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.get())
+    }
+}
+
+impl FromPrimitive for SafeF64 {
+    fn from_i64(n: i64) -> Option<Self> {
+        SafeF64::new(n as f64).ok()
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        SafeF64::new(n as f64).ok()
+    }
+
+    fn from_f64(n: f64) -> Option<Self> {
+        SafeF64::new(n).ok()
+    }
+}
+
+impl NumCast for SafeF64 {
+    fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+        n.to_f64().and_then(<Self as FromPrimitive>::from_f64)
+    }
+}
+
+/// Converts the tracked value, discarding its error bound.
+impl ToPrimitive for TrackedF64 {
+    fn to_i64(&self) -> Option<i64> {
+        self.value().to_i64()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.value().to_u64()
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.value())
+    }
+}
+
+/// Integers above 2^53 are rounded on the way in, and that rounding error is
+/// recorded in the bound.
+impl FromPrimitive for TrackedF64 {
+    fn from_i64(n: i64) -> Option<Self> {
+        let value = n as f64;
+        TrackedF64::with_error(value, (value as i128 - n as i128).unsigned_abs() as f64).ok()
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        let value = n as f64;
+        TrackedF64::with_error(value, (value as i128 - n as i128).unsigned_abs() as f64).ok()
+    }
+
+    fn from_f64(n: f64) -> Option<Self> {
+        TrackedF64::exact(n).ok()
+    }
+}
+
+/// Conversions go through f64, so integer sources keep no record of rounding.
+impl NumCast for TrackedF64 {
+    fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+        n.to_f64().and_then(<Self as FromPrimitive>::from_f64)
+    }
+}
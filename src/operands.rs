@@ -0,0 +1,197 @@
+//! Operand arrays stored in account data.
+//!
+//! Instructions that take their operands from an account carry an
+//! `[offset: u32][count: u32]` header (little endian) naming a run of `count`
+//! little-endian f64 values starting `offset` bytes into the account data. This
+//! lets a computation cover far more values than fit in a 1232-byte transaction.
+//!
+//! `view` reinterprets the operand bytes in place instead of decoding each value.
+//! That requires the run to start on an 8-byte boundary: `offset` must be a
+//! multiple of `OPERAND_SIZE` (account data itself is 8-byte aligned in the
+//! runtime's input buffer). Values are stored little endian, the byte order of
+//! every Solana target.
+//!
+//! `iter`, `chunks` and `map_in_place` stream over the operands instead: they
+//! decode one value at a time at any alignment and never allocate, so the
+//! program's 32 KiB heap and 4 KiB stack frames bound neither the size of the
+//! account nor the number of values.
+
+use std::iter::FusedIterator;
+use std::slice::ChunksExact;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
+
+#[cfg(not(target_endian = "little"))]
+compile_error!("account operand views assume little-endian f64 layout");
+
+pub const OPERAND_HEADER_LEN: usize = 8;
+pub const OPERAND_SIZE: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct AccountOperands {
+    pub offset: u32,
+    pub count: u32,
+}
+
+impl AccountOperands {
+    /// Parses the header from the front of `payload`, returning the rest.
+    pub fn unpack(payload: &[u8]) -> Result<(Self, &[u8]), ProgramError> {
+        if payload.len() < OPERAND_HEADER_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (header, rest) = payload.split_at(OPERAND_HEADER_LEN);
+        let offset = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let count = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        Ok((Self { offset, count }, rest))
+    }
+
+    pub fn pack(&self) -> [u8; OPERAND_HEADER_LEN] {
+        let mut header = [0u8; OPERAND_HEADER_LEN];
+        header[0..4].copy_from_slice(&self.offset.to_le_bytes());
+        header[4..8].copy_from_slice(&self.count.to_le_bytes());
+        header
+    }
+
+    /// Byte range of the operands, checked against an account of `data_len` bytes.
+    pub fn byte_range(&self, data_len: usize) -> Result<std::ops::Range<usize>, ProgramError> {
+        let start = self.offset as usize;
+        let end = (self.count as usize)
+            .checked_mul(OPERAND_SIZE)
+            .and_then(|len| start.checked_add(len))
+            .ok_or(ProgramError::AccountDataTooSmall)?;
+        if end > data_len {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        Ok(start..end)
+    }
+
+    /// Borrows the operands directly from `data` without copying or decoding.
+    ///
+    /// Fails with `InvalidInstructionData` if `offset` is not 8-byte aligned and
+    /// with `InvalidAccountData` if the account buffer itself is misaligned.
+    pub fn view<'a>(&self, data: &'a [u8]) -> Result<&'a [f64], ProgramError> {
+        let range = self.byte_range(data.len())?;
+        if self.offset as usize & (OPERAND_SIZE - 1) != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        bytemuck::try_cast_slice(&data[range]).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Decodes the operands one at a time, at any alignment.
+    pub fn iter<'a>(&self, data: &'a [u8]) -> Result<OperandIter<'a>, ProgramError> {
+        let range = self.byte_range(data.len())?;
+        Ok(OperandIter {
+            bytes: data[range].chunks_exact(OPERAND_SIZE),
+        })
+    }
+
+    /// Decodes the operands `N` at a time into a stack buffer; the last chunk
+    /// may be shorter.
+    pub fn chunks<'a, const N: usize>(
+        &self,
+        data: &'a [u8],
+    ) -> Result<OperandChunks<'a, N>, ProgramError> {
+        if N == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(OperandChunks {
+            values: self.iter(data)?,
+        })
+    }
+
+    /// Replaces each operand in `data` with `f(operand)`, at any alignment.
+    ///
+    /// Stops at the first error; operands before it have already been
+    /// rewritten.
+    pub fn map_in_place<F>(&self, data: &mut [u8], mut f: F) -> Result<(), ProgramError>
+    where
+        F: FnMut(f64) -> Result<f64, ProgramError>,
+    {
+        let range = self.byte_range(data.len())?;
+        for chunk in data[range].chunks_exact_mut(OPERAND_SIZE) {
+            let value = f(f64::from_le_bytes((&*chunk).try_into().unwrap()))?;
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    /// Copies the operands out of `data`, decoding each value; works at any alignment.
+    pub fn read(&self, data: &[u8]) -> Result<Vec<f64>, ProgramError> {
+        let range = self.byte_range(data.len())?;
+        Ok(data[range]
+            .chunks_exact(OPERAND_SIZE)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect())
+    }
+}
+
+/// Iterator returned by [`AccountOperands::iter`].
+#[derive(Debug, Clone)]
+pub struct OperandIter<'a> {
+    bytes: ChunksExact<'a, u8>,
+}
+
+impl Iterator for OperandIter<'_> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        self.bytes
+            .next()
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.bytes.size_hint()
+    }
+}
+
+impl ExactSizeIterator for OperandIter<'_> {}
+
+impl FusedIterator for OperandIter<'_> {}
+
+/// Up to `N` decoded operands, held on the stack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OperandChunk<const N: usize> {
+    values: [f64; N],
+    len: usize,
+}
+
+impl<const N: usize> std::ops::Deref for OperandChunk<N> {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        &self.values[..self.len]
+    }
+}
+
+/// Iterator returned by [`AccountOperands::chunks`].
+#[derive(Debug, Clone)]
+pub struct OperandChunks<'a, const N: usize> {
+    values: OperandIter<'a>,
+}
+
+impl<const N: usize> Iterator for OperandChunks<'_, N> {
+    type Item = OperandChunk<N>;
+
+    fn next(&mut self) -> Option<OperandChunk<N>> {
+        let mut chunk = OperandChunk {
+            values: [0.0; N],
+            len: 0,
+        };
+        for (slot, value) in chunk.values.iter_mut().zip(&mut self.values) {
+            *slot = value;
+            chunk.len += 1;
+        }
+        (chunk.len > 0).then_some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let chunks = self.values.len().div_ceil(N);
+        (chunks, Some(chunks))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for OperandChunks<'_, N> {}
+
+impl<const N: usize> FusedIterator for OperandChunks<'_, N> {}
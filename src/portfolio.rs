@@ -0,0 +1,143 @@
+//! Portfolio-level calculations over positions in several assets.
+
+use crate::array_ops::{check_finite, dot_compensated, normalize, sum_doubles_compensated};
+use crate::bps::{BPS_DENOMINATOR, Bps};
+use crate::error::FloatError;
+use crate::quantize::{RoundingMode, pow10, quantize};
+
+/// An oracle price with its confidence interval: the true price is expected
+/// within `price ± conf`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceWithConf {
+    pub price: f64,
+    pub conf: f64,
+}
+
+impl PriceWithConf {
+    /// Fails with `NaNOperand` for NaN inputs and `OutOfRange` for infinite
+    /// inputs or a negative confidence.
+    pub fn new(price: f64, conf: f64) -> Result<Self, FloatError> {
+        check_finite(&[price, conf])?;
+        if conf < 0.0 {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(Self { price, conf })
+    }
+
+    /// `price - conf`, the conservative value of collateral.
+    pub fn lower(self) -> f64 {
+        self.price - self.conf
+    }
+
+    /// `price + conf`, the conservative value of debt.
+    pub fn upper(self) -> f64 {
+        self.price + self.conf
+    }
+}
+
+/// Total value of `(raw_amount, decimals, price)` positions, with the
+/// confidences added up in proportion to the amounts.
+///
+/// Adding the confidences is the worst case, right when every feed errs in
+/// the same direction, which is what a lending or margin check has to assume.
+/// Amounts are read as `raw / 10^decimals` (correctly rounded for raw amounts
+/// up to 2^53) and both sums are compensated dot products, so the result
+/// does not depend on the order of the positions beyond the last bit.
+///
+/// Fails with `OutOfRange` for decimals above `MAX_DECIMALS` or a position
+/// built without [`PriceWithConf::new`] that has infinite fields or a
+/// negative confidence, `NaNOperand` for NaN fields, and `Overflow` if a
+/// total is not finite.
+pub fn portfolio_value(
+    positions: &[(u64, u32, PriceWithConf)],
+) -> Result<PriceWithConf, FloatError> {
+    let mut amounts = Vec::with_capacity(positions.len());
+    let mut prices = Vec::with_capacity(positions.len());
+    let mut confs = Vec::with_capacity(positions.len());
+    for &(raw, decimals, quote) in positions {
+        let quote = PriceWithConf::new(quote.price, quote.conf)?;
+        amounts.push(raw as f64 / pow10(decimals)? as f64);
+        prices.push(quote.price);
+        confs.push(quote.conf);
+    }
+    let value = PriceWithConf {
+        price: dot_compensated(&amounts, &prices)?,
+        conf: dot_compensated(&amounts, &confs)?,
+    };
+    if !value.price.is_finite() || !value.conf.is_finite() {
+        return Err(FloatError::Overflow);
+    }
+    Ok(value)
+}
+
+/// One position of a portfolio to rebalance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Holding {
+    /// Whole tokens held.
+    pub amount: f64,
+    /// Value of one whole token in the portfolio's quote currency.
+    pub price: f64,
+    /// Desired share of the portfolio value; weights need not sum to one.
+    pub target_weight: f64,
+    /// Decimals of the token, which trades are quantized to.
+    pub decimals: u32,
+}
+
+/// Token amounts to trade to bring every holding to its target weight:
+/// positive to buy, negative to sell, zero for holdings already within
+/// `tolerance` of their target (the no-trade band, measured in basis points
+/// of the total value).
+///
+/// The total value is a compensated sum, and each trade is rounded toward
+/// zero to the token's decimals, so a trade never overshoots its target.
+/// Fails with `NaNOperand` for NaN inputs, `OutOfRange` for negative or
+/// infinite inputs, decimals above `MAX_DECIMALS` or no holdings,
+/// `DivisionByZero` if every target weight or the price of a holding that
+/// must trade is zero, and `Overflow` if a value is not finite.
+pub fn rebalance(holdings: &[Holding], tolerance: Bps) -> Result<Vec<f64>, FloatError> {
+    let amounts: Vec<f64> = holdings.iter().map(|holding| holding.amount).collect();
+    let prices: Vec<f64> = holdings.iter().map(|holding| holding.price).collect();
+    check_finite(&amounts)?;
+    check_finite(&prices)?;
+    if amounts.iter().chain(&prices).any(|&input| input < 0.0) {
+        return Err(FloatError::OutOfRange);
+    }
+    for holding in holdings {
+        pow10(holding.decimals)?;
+    }
+    let weights: Vec<f64> = holdings
+        .iter()
+        .map(|holding| holding.target_weight)
+        .collect();
+    let targets = normalize(&weights)?;
+
+    let values: Vec<f64> = holdings
+        .iter()
+        .map(|holding| holding.amount * holding.price)
+        .collect();
+    let total = sum_doubles_compensated(&values);
+    if !total.is_finite() || values.iter().any(|value| !value.is_finite()) {
+        return Err(FloatError::Overflow);
+    }
+    let band = tolerance.0 as f64 / BPS_DENOMINATOR as f64;
+
+    let mut trades = Vec::with_capacity(holdings.len());
+    for ((holding, &value), &target) in holdings.iter().zip(&values).zip(&targets) {
+        let drift = if total == 0.0 {
+            0.0
+        } else {
+            value / total - target
+        };
+        if drift.abs() <= band {
+            trades.push(0.0);
+            continue;
+        }
+        if holding.price == 0.0 {
+            return Err(FloatError::DivisionByZero);
+        }
+        let trade = (target * total - value) / holding.price;
+        // Normalise -0.0 from a sale that rounds away
+        trades.push(quantize(trade, holding.decimals, RoundingMode::TowardZero)? + 0.0);
+    }
+    Ok(trades)
+}
@@ -0,0 +1,30 @@
+//! The safe-by-default API in one import.
+//!
+//! ```
+//! use solana_floats::prelude::*;
+//! ```
+//!
+//! Everything here either rejects NaN and infinities or works in integers, so
+//! reaching for the prelude first keeps raw float operators out of program
+//! logic. The lower-level and f32 helpers stay in their own modules.
+
+pub use crate::amm::{PoolState, RouteEstimate, estimate_route_out, swap_output};
+pub use crate::array_ops::{dot_compensated, sort_finite, sum_doubles_compensated};
+pub use crate::bps::{BPS_DENOMINATOR, Bps, within_bps};
+pub use crate::compare::{Tolerance, total_cmp_with_tolerance};
+pub use crate::double_ops::BinaryOp;
+pub use crate::error::FloatError;
+pub use crate::float_ext::SolanaFloatExt;
+pub use crate::format::format_fixed;
+pub use crate::mul_div::{mul_div, mul_div_u128};
+pub use crate::quantize::{PrecisionPolicy, RoundingMode, quantize, quantize_slice};
+pub use crate::ratio::{Ratio, approx_ratio};
+pub use crate::reciprocal::Reciprocal;
+pub use crate::safe_f32::SafeF32;
+pub use crate::safe_f64::SafeF64;
+pub use crate::shares::{FirstDeposit, SharePool, assets_for_shares, shares_for_deposit};
+pub use crate::stake_pool::{lamports_for_pool_tokens, pool_tokens_for_deposit};
+pub use crate::tracked::TrackedF64;
+pub use crate::units::{Amount, Price};
+pub use crate::verify::verify;
+pub use crate::{approx_assert_eq, assert_within_bps};
@@ -0,0 +1,387 @@
+//! Rounding modes and decimal quantization of f64 values.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::array_ops::scalar;
+use crate::error::FloatError;
+use crate::mul_div::{mul_div, mul_div_u128};
+
+/// Largest number of decimals whose power of ten is exact in both u64 and f64.
+pub const MAX_DECIMALS: u32 = 19;
+
+const POW10: [u64; 20] = [
+    1,
+    10,
+    100,
+    1_000,
+    10_000,
+    100_000,
+    1_000_000,
+    10_000_000,
+    100_000_000,
+    1_000_000_000,
+    10_000_000_000,
+    100_000_000_000,
+    1_000_000_000_000,
+    10_000_000_000_000,
+    100_000_000_000_000,
+    1_000_000_000_000_000,
+    10_000_000_000_000_000,
+    100_000_000_000_000_000,
+    1_000_000_000_000_000_000,
+    10_000_000_000_000_000_000,
+];
+
+// Beyond 2^53 every f64 is already an integer, so there is nothing left to round.
+const F64_INTEGER_LIMIT: f64 = 9_007_199_254_740_992.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum RoundingMode {
+    /// Toward negative infinity.
+    Floor,
+    /// Toward positive infinity.
+    Ceil,
+    /// Toward zero (truncation).
+    TowardZero,
+    /// To nearest, ties away from zero (what `f64::round` does).
+    HalfUp,
+    /// To nearest, ties to even (banker's rounding).
+    HalfEven,
+}
+
+impl TryFrom<u8> for RoundingMode {
+    type Error = FloatError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RoundingMode::Floor),
+            1 => Ok(RoundingMode::Ceil),
+            2 => Ok(RoundingMode::TowardZero),
+            3 => Ok(RoundingMode::HalfUp),
+            4 => Ok(RoundingMode::HalfEven),
+            _ => Err(FloatError::OutOfRange),
+        }
+    }
+}
+
+impl RoundingMode {
+    /// Rounds `x` to an integral value according to this mode.
+    pub fn round(self, x: f64) -> f64 {
+        match self {
+            RoundingMode::Floor => x.floor(),
+            RoundingMode::Ceil => x.ceil(),
+            RoundingMode::TowardZero => x.trunc(),
+            RoundingMode::HalfUp => x.round(),
+            RoundingMode::HalfEven => x.round_ties_even(),
+        }
+    }
+
+    /// The mode that rounds the magnitude of a value the way `self` rounds
+    /// the signed value: `Floor` of a negative value rounds its magnitude up.
+    pub(crate) const fn for_magnitude(self, negative: bool) -> Self {
+        match (self, negative) {
+            (RoundingMode::Floor, true) => RoundingMode::Ceil,
+            (RoundingMode::Ceil, true) => RoundingMode::Floor,
+            (rounding, _) => rounding,
+        }
+    }
+}
+
+/// A validated decimals/rounding-mode pair for quantizing amounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrecisionPolicy {
+    decimals: u32,
+    mode: RoundingMode,
+}
+
+impl PrecisionPolicy {
+    /// `decimals` must be at most `MAX_DECIMALS`.
+    pub fn new(decimals: u32, mode: RoundingMode) -> Result<Self, FloatError> {
+        pow10(decimals)?;
+        Ok(Self { decimals, mode })
+    }
+
+    /// Both the decimals and the rounding mode must be set before `build`.
+    pub fn builder() -> PrecisionPolicyBuilder {
+        PrecisionPolicyBuilder::default()
+    }
+
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    pub fn mode(&self) -> RoundingMode {
+        self.mode
+    }
+
+    pub fn quantize(&self, value: f64) -> Result<f64, FloatError> {
+        quantize(value, self.decimals, self.mode)
+    }
+
+    pub fn quantize_slice(&self, values: &mut [f64]) -> Result<(), FloatError> {
+        quantize_slice(values, self.decimals, self.mode)
+    }
+}
+
+/// Fluent construction of a [`PrecisionPolicy`].
+///
+/// There are no defaults: leaving out either setting fails with `OutOfRange`,
+/// so a policy never silently rounds in a direction nobody chose.
+///
+/// ```
+/// use solana_floats::quantize::{PrecisionPolicy, RoundingMode};
+///
+/// let policy = PrecisionPolicy::builder()
+///     .decimals(2)
+///     .rounding(RoundingMode::HalfEven)
+///     .build()
+///     .unwrap();
+/// assert_eq!(policy.quantize(0.125), Ok(0.12));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrecisionPolicyBuilder {
+    decimals: Option<u32>,
+    mode: Option<RoundingMode>,
+}
+
+impl PrecisionPolicyBuilder {
+    pub fn decimals(mut self, decimals: u32) -> Self {
+        self.decimals = Some(decimals);
+        self
+    }
+
+    pub fn rounding(mut self, mode: RoundingMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn build(self) -> Result<PrecisionPolicy, FloatError> {
+        match (self.decimals, self.mode) {
+            (Some(decimals), Some(mode)) => PrecisionPolicy::new(decimals, mode),
+            _ => Err(FloatError::OutOfRange),
+        }
+    }
+}
+
+/// Returns 10^decimals, or `OutOfRange` if it does not fit in a u64.
+pub const fn pow10(decimals: u32) -> Result<u64, FloatError> {
+    if decimals as usize >= POW10.len() {
+        return Err(FloatError::OutOfRange);
+    }
+    Ok(POW10[decimals as usize])
+}
+
+/// Rescales an integer amount from `from_decimals` to `to_decimals` without
+/// rounding: gaining decimals multiplies exactly (or fails with `Overflow`),
+/// and losing them fails with `PrecisionLoss` unless the dropped digits are
+/// all zero.
+pub const fn rescale(amount: u64, from_decimals: u32, to_decimals: u32) -> Result<u64, FloatError> {
+    if from_decimals > MAX_DECIMALS || to_decimals > MAX_DECIMALS {
+        return Err(FloatError::OutOfRange);
+    }
+    if to_decimals >= from_decimals {
+        let factor = POW10[(to_decimals - from_decimals) as usize];
+        match amount.checked_mul(factor) {
+            Some(scaled) => Ok(scaled),
+            None => Err(FloatError::Overflow),
+        }
+    } else {
+        let factor = POW10[(from_decimals - to_decimals) as usize];
+        let scaled = amount / factor;
+        if scaled * factor != amount {
+            return Err(FloatError::PrecisionLoss);
+        }
+        Ok(scaled)
+    }
+}
+
+/// A balance moved to new decimals by [`rescale_balance_with_dust`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RescaledBalance {
+    pub balance: u64,
+    /// Old base units that rounding took from the holder (positive) or
+    /// credited to them (negative). Summed over every migrated account, this
+    /// is how far the new total supply is off from the old one.
+    pub dust: i128,
+}
+
+/// Rescales a token balance from `old_decimals` to `new_decimals`, rounding
+/// any dropped digits with `rounding` (gaining decimals is always exact).
+///
+/// Fails with `OutOfRange` for more than `MAX_DECIMALS` and `Overflow` if the
+/// result does not fit in a u64.
+pub fn rescale_balance(
+    raw: u64,
+    old_decimals: u32,
+    new_decimals: u32,
+    rounding: RoundingMode,
+) -> Result<u64, FloatError> {
+    rescale_balance_with_dust(raw, old_decimals, new_decimals, rounding).map(|r| r.balance)
+}
+
+/// Like [`rescale_balance`], also reporting the rounding dust so a migration
+/// can reconcile the mint supply or sweep the dust to a treasury.
+pub fn rescale_balance_with_dust(
+    raw: u64,
+    old_decimals: u32,
+    new_decimals: u32,
+    rounding: RoundingMode,
+) -> Result<RescaledBalance, FloatError> {
+    if new_decimals >= old_decimals {
+        return Ok(RescaledBalance {
+            balance: rescale(raw, old_decimals, new_decimals)?,
+            dust: 0,
+        });
+    }
+    pow10(old_decimals)?;
+    let factor = pow10(old_decimals - new_decimals)?;
+    // Balances are non-negative, so Floor and TowardZero agree
+    let balance = mul_div(raw, 1, factor, rounding)?;
+    let dust = raw as i128 - balance as i128 * factor as i128;
+    Ok(RescaledBalance { balance, dust })
+}
+
+/// Rounds `value` to `decimals` fractional digits using `mode`.
+///
+/// Values too large to carry a fractional part at this scale are returned unchanged.
+pub fn quantize(value: f64, decimals: u32, mode: RoundingMode) -> Result<f64, FloatError> {
+    let scale = pow10(decimals)? as f64;
+    check_quantizable(value, scale)?;
+    Ok(quantize_scaled(value, scale, mode))
+}
+
+/// Rounds `x` to `decimals` fractional digits using `mode`, like [`quantize`],
+/// but never fails on magnitude: where `x * 10^decimals` would overflow, `x`
+/// is far past 2^53 and already integral, so it is returned unchanged.
+///
+/// Fails with `NaNOperand` for NaN and infinities and `OutOfRange` for more
+/// than `MAX_DECIMALS`.
+pub fn truncate_to_decimals(x: f64, decimals: u32, mode: RoundingMode) -> Result<f64, FloatError> {
+    let scale = pow10(decimals)? as f64;
+    if !x.is_finite() {
+        return Err(FloatError::NaNOperand);
+    }
+    // An overflowing product is infinite, which `quantize_scaled` passes through
+    Ok(quantize_scaled(x, scale, mode))
+}
+
+/// The `(x * scale).round() / scale` idiom, with `scale` a power of ten such
+/// as `1_000_000_000_000`. Bit-identical to the idiom while `x * scale` is
+/// below 2^53; past that there is no fraction left to round and `x` is
+/// returned unchanged, where the idiom would add the error of a multiply and
+/// divide (or turn the value into infinity or NaN).
+///
+/// Fails with `OutOfRange` if `scale` is not a power of ten up to
+/// `10^MAX_DECIMALS`, and `NaNOperand` for NaN and infinities.
+pub fn quantize_to_scale(x: f64, scale: u64) -> Result<f64, FloatError> {
+    truncate_to_decimals(x, scale_decimals(scale)?, RoundingMode::HalfUp)
+}
+
+/// [`quantize_to_scale`] with ties to even, the rounding settlement and
+/// accounting rules usually ask for: `round_half_even_to_scale(0.125, 100)`
+/// is 0.12 where `f64::round` would give 0.13.
+pub fn round_half_even_to_scale(x: f64, scale: u64) -> Result<f64, FloatError> {
+    truncate_to_decimals(x, scale_decimals(scale)?, RoundingMode::HalfEven)
+}
+
+/// [`round_half_even_to_scale`] for f32. The value is rounded in f64 and
+/// narrowed once; for scales up to `10^12` the scaled value is exact in f64,
+/// so a tie is detected exactly rather than after a rounding in f32.
+pub fn round_half_even_to_scale_f32(x: f32, scale: u64) -> Result<f32, FloatError> {
+    round_half_even_to_scale(x as f64, scale).map(|rounded| rounded as f32)
+}
+
+/// `value * 10^decimals` as an integer, rounded by `rounding` from the exact
+/// product rather than from its f64 approximation: `0.29` is just below 0.29,
+/// so it scales to 28 cents with `Floor` and 29 with `Ceil`.
+///
+/// Fails with `NaNOperand` for NaN and infinities, `OutOfRange` for more
+/// than `MAX_DECIMALS` and for negative values that do not round to zero,
+/// and `Overflow` if the result does not fit in a u64.
+pub fn scale_with_rounding(
+    value: f64,
+    decimals: u32,
+    rounding: RoundingMode,
+) -> Result<u64, FloatError> {
+    let scale = pow10(decimals)? as u128;
+    if !value.is_finite() {
+        return Err(FloatError::NaNOperand);
+    }
+    // value = ±mantissa * 2^exponent, so the product is exact in u128
+    let bits = value.to_bits();
+    let biased = (bits >> 52 & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mantissa, exponent) = if biased == 0 {
+        (fraction, -1074)
+    } else {
+        (fraction | 1 << 52, biased - 1075)
+    };
+    let product = mantissa as u128 * scale;
+    let rounding = rounding.for_magnitude(value.is_sign_negative());
+    let magnitude = if product == 0 {
+        0
+    } else if exponent >= 0 {
+        if exponent >= 64 {
+            return Err(FloatError::Overflow);
+        }
+        product
+            .checked_shl(exponent as u32)
+            .filter(|shifted| shifted >> exponent == product)
+            .ok_or(FloatError::Overflow)?
+    } else if exponent > -128 {
+        mul_div_u128(product, 1, 1 << -exponent, rounding)?
+    } else {
+        // product < 2^117, so the value is below a half
+        (rounding == RoundingMode::Ceil) as u128
+    };
+    if value.is_sign_negative() && magnitude != 0 {
+        return Err(FloatError::OutOfRange);
+    }
+    u64::try_from(magnitude).map_err(|_| FloatError::Overflow)
+}
+
+// The number of decimals `scale` stands for, if it is a power of ten
+fn scale_decimals(scale: u64) -> Result<u32, FloatError> {
+    POW10
+        .iter()
+        .position(|&power| power == scale)
+        .map(|decimals| decimals as u32)
+        .ok_or(FloatError::OutOfRange)
+}
+
+/// Quantizes every element of `values` in place with one decimals/mode policy.
+///
+/// All elements are validated before any is modified, so on error the slice is
+/// left untouched.
+pub fn quantize_slice(
+    values: &mut [f64],
+    decimals: u32,
+    mode: RoundingMode,
+) -> Result<(), FloatError> {
+    let scale = pow10(decimals)? as f64;
+    for &value in values.iter() {
+        check_quantizable(value, scale)?;
+    }
+    for value in values.iter_mut() {
+        *value = quantize_scaled(scalar(*value), scale, mode);
+    }
+    Ok(())
+}
+
+fn check_quantizable(value: f64, scale: f64) -> Result<(), FloatError> {
+    if !value.is_finite() {
+        return Err(FloatError::NaNOperand);
+    }
+    if !(value * scale).is_finite() {
+        return Err(FloatError::Overflow);
+    }
+    Ok(())
+}
+
+fn quantize_scaled(value: f64, scale: f64, mode: RoundingMode) -> f64 {
+    let scaled = value * scale;
+    if scaled.abs() >= F64_INTEGER_LIMIT {
+        return value;
+    }
+    mode.round(scaled) / scale
+}
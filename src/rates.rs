@@ -0,0 +1,150 @@
+//! Converting interest rates between time units.
+//!
+//! Mixing up per-slot, per-second and per-year rates, or converting a
+//! compounded rate as if it were simple, is a recurring source of interest
+//! bugs. [`rate_convert`] takes both units and the [`Compounding`] convention
+//! explicitly, so a call site states what it means.
+//!
+//! Compounded conversions go through `ln(1 + r)` and `exp(x) - 1` evaluated
+//! with the crate's deterministic `ln`/`exp`, so per-slot rates around 1e-10
+//! keep their precision instead of vanishing into `1.0 + rate`.
+
+use crate::error::FloatError;
+use crate::transcendental::{exp_deterministic, ln_deterministic, ln_one_plus};
+
+/// The year the runtime measures time in (365.242199 days, a tropical
+/// year), as `SECONDS_PER_YEAR` in the Solana SDK.
+pub const SECONDS_PER_YEAR: f64 = 365.242_199 * 24.0 * 60.0 * 60.0;
+
+/// Slots per year at the 400 ms target slot time (160 ticks per second, 64
+/// ticks per slot), about 78_892_314.98. This is the genesis default the
+/// inflation schedule is evaluated with. Real slot times drift, so programs
+/// that need accuracy should measure slots per year from recent history
+/// instead.
+pub const TARGET_SLOTS_PER_YEAR: f64 = SECONDS_PER_YEAR * 160.0 / 64.0;
+
+/// Slots per epoch on mainnet-beta; other clusters differ.
+pub const MAINNET_SLOTS_PER_EPOCH: u64 = 432_000;
+
+/// Annual inflation when the runtime's default schedule starts.
+pub const DEFAULT_INITIAL_INFLATION: f64 = 0.08;
+
+/// How much of itself the default schedule's rate loses each year.
+pub const DEFAULT_INFLATION_TAPER: f64 = 0.15;
+
+/// The rate the default schedule settles at.
+pub const DEFAULT_TERMINAL_INFLATION: f64 = 0.015;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateUnit {
+    PerSlot,
+    /// Per second, with a year of [`SECONDS_PER_YEAR`] seconds spread over
+    /// `slots_per_year` slots.
+    PerSecond,
+    /// Per epoch of the given number of slots.
+    PerEpoch(u64),
+    PerYear,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compounding {
+    /// Rates scale linearly with the period, like an APR:
+    /// `rate * to_length / from_length`.
+    Simple,
+    /// Interest is reinvested every period, like an APY:
+    /// `(1 + rate)^(to_length / from_length) - 1`.
+    Compounded,
+}
+
+impl RateUnit {
+    // Length of one period, in slots.
+    fn slots(self, slots_per_year: f64) -> Result<f64, FloatError> {
+        match self {
+            RateUnit::PerSlot => Ok(1.0),
+            RateUnit::PerSecond => Ok(slots_per_year / SECONDS_PER_YEAR),
+            RateUnit::PerEpoch(0) => Err(FloatError::OutOfRange),
+            RateUnit::PerEpoch(slots) => Ok(slots as f64),
+            RateUnit::PerYear => Ok(slots_per_year),
+        }
+    }
+}
+
+/// Converts `rate` per `from` period into a rate per `to` period.
+///
+/// Fails with `NaNOperand` for NaN inputs, `OutOfRange` for a
+/// `slots_per_year` that is not positive and finite, an empty epoch, or a
+/// compounded rate at or below -100%, and `Overflow` if the result is not
+/// finite.
+pub fn rate_convert(
+    rate: f64,
+    from: RateUnit,
+    to: RateUnit,
+    slots_per_year: f64,
+    compounding: Compounding,
+) -> Result<f64, FloatError> {
+    if rate.is_nan() || slots_per_year.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    if !rate.is_finite() {
+        return Err(FloatError::Overflow);
+    }
+    if slots_per_year <= 0.0 || slots_per_year.is_infinite() {
+        return Err(FloatError::OutOfRange);
+    }
+    let periods = to.slots(slots_per_year)? / from.slots(slots_per_year)?;
+    let converted = match compounding {
+        Compounding::Simple => rate * periods,
+        Compounding::Compounded => {
+            if rate <= -1.0 {
+                return Err(FloatError::OutOfRange);
+            }
+            exp_minus_one(periods * ln_one_plus(rate))
+        }
+    };
+    if !converted.is_finite() {
+        return Err(FloatError::Overflow);
+    }
+    Ok(converted)
+}
+
+/// The annual inflation rate `epoch` epochs after inflation was enabled,
+/// following the runtime's schedule: `initial_rate * (1 - taper)^year`, but
+/// never below `terminal_rate`, where `year` is the epoch's first slot over
+/// [`TARGET_SLOTS_PER_YEAR`] with [`MAINNET_SLOTS_PER_EPOCH`] slots per epoch.
+///
+/// The power goes through the deterministic `exp`/`ln`, so it can differ from
+/// the runtime's libm `powf` in the last bits but not between validators.
+/// Fails with `NaNOperand` for NaN parameters and `OutOfRange` for rates
+/// that are negative or infinite or a taper outside `[0, 1)`.
+pub fn inflation_at_epoch(
+    initial_rate: f64,
+    taper: f64,
+    terminal_rate: f64,
+    epoch: u64,
+) -> Result<f64, FloatError> {
+    if initial_rate.is_nan() || taper.is_nan() || terminal_rate.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    let valid_rate = |rate: f64| rate >= 0.0 && rate.is_finite();
+    if !valid_rate(initial_rate) || !valid_rate(terminal_rate) || !(0.0..1.0).contains(&taper) {
+        return Err(FloatError::OutOfRange);
+    }
+    let first_slot = epoch as f64 * MAINNET_SLOTS_PER_EPOCH as f64;
+    let year = first_slot / TARGET_SLOTS_PER_YEAR;
+    let tapered = initial_rate * exp_deterministic(year * ln_one_plus(-taper));
+    Ok(tapered.max(terminal_rate))
+}
+
+// exp(x) - 1, by Kahan's matching trick: (u - 1) * x / ln(u) for u = exp(x).
+fn exp_minus_one(x: f64) -> f64 {
+    let u = exp_deterministic(x);
+    if u == 1.0 {
+        x
+    } else if u - 1.0 == -1.0 {
+        -1.0
+    } else if u.is_infinite() {
+        u
+    } else {
+        (u - 1.0) * x / ln_deterministic(u)
+    }
+}
@@ -0,0 +1,399 @@
+//! Exact rational numbers over i128.
+//!
+//! Chaining exchange rates or price ratios in f64 rounds at every step. A
+//! [`Ratio`] keeps the exact value through any number of multiplications and
+//! divisions and defers rounding to one explicit step at the end
+//! ([`Ratio::to_scaled`], [`Ratio::round`] or [`Ratio::to_f64`]). Every
+//! operation that would overflow i128 fails with `Overflow` instead of
+//! wrapping.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::error::FloatError;
+use crate::mul_div::mul_div_u128;
+use crate::quantize::RoundingMode;
+use crate::u256::U256;
+
+/// `num / den`, always in lowest terms with a positive denominator, so equal
+/// values have equal fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    pub const ZERO: Ratio = Ratio { num: 0, den: 1 };
+    pub const ONE: Ratio = Ratio { num: 1, den: 1 };
+
+    /// `num / den` in lowest terms.
+    ///
+    /// Fails with `DivisionByZero` if `den` is zero, and with `Overflow` if the
+    /// sign cannot be moved to the numerator (`i128::MIN` in the denominator
+    /// of a value that does not reduce).
+    pub fn new(num: i128, den: i128) -> Result<Self, FloatError> {
+        if den == 0 {
+            return Err(FloatError::DivisionByZero);
+        }
+        let divisor = gcd(num.unsigned_abs(), den.unsigned_abs());
+        // The gcd divides both, so it is at most 2^127 and the quotients fit
+        let (num, den) = (reduce(num, divisor), reduce(den, divisor));
+        if den < 0 {
+            return Ok(Ratio {
+                num: num.checked_neg().ok_or(FloatError::Overflow)?,
+                den: den.checked_neg().ok_or(FloatError::Overflow)?,
+            });
+        }
+        Ok(Ratio { num, den })
+    }
+
+    pub const fn from_integer(value: i128) -> Self {
+        Ratio { num: value, den: 1 }
+    }
+
+    pub fn numer(self) -> i128 {
+        self.num
+    }
+
+    /// Always positive.
+    pub fn denom(self) -> i128 {
+        self.den
+    }
+
+    pub fn is_integer(self) -> bool {
+        self.den == 1
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Result<Self, FloatError> {
+        // Work over gcd(den, rhs.den) to keep intermediates small
+        let divisor = gcd(self.den as u128, rhs.den as u128) as i128;
+        let (lhs_den, rhs_den) = (self.den / divisor, rhs.den / divisor);
+        let num = self
+            .num
+            .checked_mul(rhs_den)
+            .and_then(|lhs| rhs.num.checked_mul(lhs_den)?.checked_add(lhs))
+            .ok_or(FloatError::Overflow)?;
+        let den = lhs_den.checked_mul(rhs.den).ok_or(FloatError::Overflow)?;
+        Ratio::new(num, den)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, FloatError> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, FloatError> {
+        // Cancel across the operands first, so the products are already in
+        // lowest terms
+        let lhs_divisor = gcd(self.num.unsigned_abs(), rhs.den as u128);
+        let rhs_divisor = gcd(rhs.num.unsigned_abs(), self.den as u128);
+        let num = reduce(self.num, lhs_divisor).checked_mul(reduce(rhs.num, rhs_divisor));
+        let den = reduce(self.den, rhs_divisor).checked_mul(reduce(rhs.den, lhs_divisor));
+        match (num, den) {
+            (Some(num), Some(den)) => Ok(Ratio { num, den }),
+            _ => Err(FloatError::Overflow),
+        }
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Result<Self, FloatError> {
+        self.checked_mul(rhs.checked_recip()?)
+    }
+
+    pub fn checked_neg(self) -> Result<Self, FloatError> {
+        Ok(Ratio {
+            num: self.num.checked_neg().ok_or(FloatError::Overflow)?,
+            den: self.den,
+        })
+    }
+
+    /// `den / num`. Fails with `DivisionByZero` for zero.
+    pub fn checked_recip(self) -> Result<Self, FloatError> {
+        Ratio::new(self.den, self.num)
+    }
+
+    /// `self * 10^decimals` rounded to an integer with `rounding`, e.g. an
+    /// amount in a token's smallest units.
+    ///
+    /// Fails with `Overflow` if the scaled value does not fit in i128.
+    pub fn to_scaled(self, decimals: u32, rounding: RoundingMode) -> Result<i128, FloatError> {
+        let scale = 10u128.checked_pow(decimals).ok_or(FloatError::Overflow)?;
+        let negative = self.num < 0;
+        let magnitude = mul_div_u128(
+            self.num.unsigned_abs(),
+            scale,
+            self.den as u128,
+            rounding.for_magnitude(negative),
+        )?;
+        if negative {
+            0i128
+                .checked_sub_unsigned(magnitude)
+                .ok_or(FloatError::Overflow)
+        } else {
+            i128::try_from(magnitude).map_err(|_| FloatError::Overflow)
+        }
+    }
+
+    /// The value rounded to an integer with `rounding`.
+    pub fn round(self, rounding: RoundingMode) -> Result<i128, FloatError> {
+        self.to_scaled(0, rounding)
+    }
+
+    /// The nearest f64 (ties to even), rounded once from the exact value.
+    ///
+    /// `num as f64 / den as f64` rounds up to three times once either side
+    /// exceeds 2^53.
+    pub fn to_f64(self) -> f64 {
+        self.to_f64_rounded().0
+    }
+
+    // Nearest f64 and whether it equals the ratio exactly.
+    fn to_f64_rounded(self) -> (f64, bool) {
+        if self.num == 0 {
+            return (0.0, true);
+        }
+        let (num, den) = (self.num.unsigned_abs(), self.den as u128);
+        // Shift so that the quotient has 54 or 55 bits: 53 to keep, a round
+        // bit and possibly one more
+        let shift = 54 + (128 - den.leading_zeros()) as i32 - (128 - num.leading_zeros()) as i32;
+        let (dividend, divisor) = if shift >= 0 {
+            (U256::from(num) << shift as u32, U256::from(den))
+        } else {
+            (U256::from(num), U256::from(den) << (-shift) as u32)
+        };
+        let (quotient, remainder) = dividend.div_rem(divisor).unwrap();
+        let quotient = quotient.low_u128();
+        let dropped = (128 - quotient.leading_zeros()) - 53;
+        let kept = quotient >> dropped;
+        let rest = quotient & ((1 << dropped) - 1);
+        let half = 1 << (dropped - 1);
+        let sticky = !remainder.is_zero();
+        let round_up = rest > half || (rest == half && (sticky || kept & 1 == 1));
+        let mantissa = (kept + round_up as u128) as f64;
+        let exponent = dropped as i32 - shift;
+        // |ratio| lies in [2^-127, 2^127], so the power of two is a normal f64
+        let scale = f64::from_bits(((1023 + exponent) as u64) << 52);
+        let magnitude = mantissa * scale;
+        let exact = rest == 0 && !sticky;
+        let value = if self.num < 0 { -magnitude } else { magnitude };
+        (value, exact)
+    }
+}
+
+/// The fraction nearest to `value` with a denominator of at most `max_den`,
+/// e.g. `355/113` for `PI` and `max_den = 1000`.
+///
+/// Works on the exact binary value of the float by continued fractions,
+/// checking the last semiconvergent as well, so no other fraction with a
+/// denominator up to `max_den` is closer. The sign is applied last, so
+/// `approx_ratio(-x, n)` is always `-approx_ratio(x, n)`, including on exact
+/// ties (where Python's `limit_denominator` leans toward negative infinity
+/// instead). Fails with `NaNOperand` for NaN, `OutOfRange` for a zero
+/// `max_den`, and `Overflow` for infinities and results whose numerator does
+/// not fit in i128.
+pub fn approx_ratio(value: f64, max_den: u64) -> Result<Ratio, FloatError> {
+    if value.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    if value.is_infinite() {
+        return Err(FloatError::Overflow);
+    }
+    if max_den == 0 {
+        return Err(FloatError::OutOfRange);
+    }
+    let bits = value.abs().to_bits();
+    let biased = (bits >> 52) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mantissa, exponent) = if biased == 0 {
+        (fraction, -1074)
+    } else {
+        (fraction | 1 << 52, biased - 1075)
+    };
+    let zeros = mantissa.trailing_zeros().min(63) as i32;
+    let (mantissa, exponent) = (mantissa >> zeros, exponent + zeros);
+    let (num, den) = if mantissa == 0 {
+        (0, 1)
+    } else if exponent >= 0 {
+        // Already an integer
+        if 64 - mantissa.leading_zeros() + exponent as u32 > 127 {
+            return Err(FloatError::Overflow);
+        }
+        ((mantissa as u128) << exponent, 1)
+    } else if exponent < -127 {
+        // Below 2^-74, nearer to 0 than to 1/max_den
+        (0, 1)
+    } else {
+        best_fraction(mantissa as u128, 1 << -exponent, max_den as u128)
+    };
+    let num = i128::try_from(num).map_err(|_| FloatError::Overflow)?;
+    Ok(Ratio {
+        num: if value < 0.0 { -num } else { num },
+        den: den as i128,
+    })
+}
+
+// Best approximation of `num / den` (in lowest terms) with a denominator of
+// at most `max_den`. Convergents and semiconvergents are always in lowest
+// terms.
+fn best_fraction(num: u128, den: u128, max_den: u128) -> (u128, u128) {
+    if den <= max_den {
+        return (num, den);
+    }
+    // Convergents h0/k0 (previous) and h1/k1 (latest)
+    let (mut h0, mut k0, mut h1, mut k1) = (0u128, 1u128, 1u128, 0u128);
+    let (mut n, mut d) = (num, den);
+    loop {
+        let term = n / d;
+        let k2 = term.saturating_mul(k1).saturating_add(k0);
+        if k2 > max_den {
+            break;
+        }
+        (h0, k0, h1, k1) = (h1, k1, h0 + term * h1, k2);
+        (n, d) = (d, n - term * d);
+    }
+    // The largest semiconvergent that still fits
+    let steps = (max_den - k0) / k1;
+    let (semi_num, semi_den) = (h0 + steps * h1, k0 + steps * k1);
+    let distance = |h: u128, k: u128| {
+        // |num/den - h/k| * den * k
+        let lhs = U256::from(num).wrapping_mul(U256::from(k));
+        let rhs = U256::from(h).wrapping_mul(U256::from(den));
+        if lhs >= rhs {
+            lhs.overflowing_sub(rhs).0
+        } else {
+            rhs.overflowing_sub(lhs).0
+        }
+    };
+    // Compare the distances over a common denominator
+    let convergent = distance(h1, k1).wrapping_mul(U256::from(semi_den));
+    let semiconvergent = distance(semi_num, semi_den).wrapping_mul(U256::from(k1));
+    if convergent <= semiconvergent {
+        (h1, k1)
+    } else {
+        (semi_num, semi_den)
+    }
+}
+
+// Both arguments are multiples of the gcd, so the division is exact.
+fn reduce(value: i128, divisor: u128) -> i128 {
+    match i128::try_from(divisor) {
+        Ok(divisor) => value / divisor,
+        // A divisor of 2^127 only divides i128::MIN and zero
+        Err(_) => -((value != 0) as i128),
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Exact: fails with `PrecisionLoss` unless the ratio is exactly an f64.
+impl TryFrom<Ratio> for f64 {
+    type Error = FloatError;
+
+    fn try_from(ratio: Ratio) -> Result<Self, Self::Error> {
+        match ratio.to_f64_rounded() {
+            (value, true) => Ok(value),
+            (_, false) => Err(FloatError::PrecisionLoss),
+        }
+    }
+}
+
+/// Exact: every finite f64 is a ratio of integers, but only those whose
+/// numerator and power-of-two denominator fit in i128 convert. Fails with
+/// `NaNOperand` for NaN, `Overflow` for infinities and values of 2^127 and
+/// above, and `PrecisionLoss` for values that need a denominator above 2^126.
+impl TryFrom<f64> for Ratio {
+    type Error = FloatError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if value.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if value.is_infinite() || value.abs() >= 2f64.powi(127) {
+            return Err(FloatError::Overflow);
+        }
+        if value == 0.0 {
+            return Ok(Ratio::ZERO);
+        }
+        let bits = value.to_bits();
+        let biased = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mantissa, exponent) = if biased == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | 1 << 52, biased - 1075)
+        };
+        // Strip trailing zero bits so the denominator is as small as possible
+        let zeros = mantissa.trailing_zeros() as i32;
+        let (mantissa, exponent) = (mantissa >> zeros, exponent + zeros);
+        let signed = if value < 0.0 {
+            -(mantissa as i128)
+        } else {
+            mantissa as i128
+        };
+        if exponent >= 0 {
+            // Below 2^127, checked above
+            Ok(Ratio::from_integer(signed << exponent))
+        } else if exponent >= -126 {
+            Ok(Ratio {
+                num: signed,
+                den: 1 << -exponent,
+            })
+        } else {
+            Err(FloatError::PrecisionLoss)
+        }
+    }
+}
+
+impl From<i128> for Ratio {
+    fn from(value: i128) -> Self {
+        Ratio::from_integer(value)
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Compare num * other.den with other.num * den at 256 bits
+        match (self.num.signum(), other.num.signum()) {
+            (lhs, rhs) if lhs != rhs => lhs.cmp(&rhs),
+            (0, _) => Ordering::Equal,
+            (sign, _) => {
+                let lhs =
+                    U256::from(self.num.unsigned_abs()).wrapping_mul(U256::from(other.den as u128));
+                let rhs =
+                    U256::from(other.num.unsigned_abs()).wrapping_mul(U256::from(self.den as u128));
+                if sign > 0 {
+                    lhs.cmp(&rhs)
+                } else {
+                    rhs.cmp(&lhs)
+                }
+            }
+        }
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Default for Ratio {
+    fn default() -> Self {
+        Ratio::ZERO
+    }
+}
+
+/// `num/den`, or just `num` for integers.
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
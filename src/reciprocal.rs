@@ -0,0 +1,86 @@
+//! Division by a repeated divisor as a multiplication.
+//!
+//! Dividing thousands of balances by the same total supply pays for a
+//! soft-float division each time. [`Reciprocal`] validates the divisor and
+//! rounds `1/d` once; every [`Reciprocal::apply`] after that is a single
+//! multiplication.
+//!
+//! `x * RN(1/d)` rounds twice, so it is not always the correctly rounded
+//! `x / d`:
+//!
+//! - If `d` is a power of two, `1/d` is exact and the product is `x / d` for
+//!   every `x`, including subnormal results. [`Reciprocal::is_exact`] reports
+//!   this case.
+//! - Otherwise `RN(1/d)` is within half an ulp of `1/d`, so the product is
+//!   within one ulp of `x / d`; it matches the division for most `x`, but
+//!   which ones depends on both operands. Use plain division where the result
+//!   must be bit-identical to another implementation that divides.
+
+use crate::array_ops::scalar;
+use crate::error::FloatError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reciprocal {
+    divisor: f64,
+    reciprocal: f64,
+}
+
+impl Reciprocal {
+    /// Fails with `DivisionByZero` for zero, `NaNOperand` for NaN or an
+    /// infinite divisor, and `Overflow` if `1/divisor` is not finite.
+    pub fn new(divisor: f64) -> Result<Self, FloatError> {
+        if divisor == 0.0 {
+            return Err(FloatError::DivisionByZero);
+        }
+        if !divisor.is_finite() {
+            return Err(FloatError::NaNOperand);
+        }
+        let reciprocal = 1.0 / divisor;
+        if reciprocal.is_infinite() {
+            return Err(FloatError::Overflow);
+        }
+        Ok(Self {
+            divisor,
+            reciprocal,
+        })
+    }
+
+    pub fn divisor(&self) -> f64 {
+        self.divisor
+    }
+
+    pub fn reciprocal(&self) -> f64 {
+        self.reciprocal
+    }
+
+    /// Whether [`apply`](Self::apply) always equals `x / divisor`, i.e. the
+    /// divisor is a power of two.
+    pub fn is_exact(&self) -> bool {
+        let bits = self.divisor.abs().to_bits();
+        // Normal with an all-zero mantissa, or subnormal with a single bit set
+        if self.divisor.is_normal() {
+            bits & 0x000f_ffff_ffff_ffff == 0
+        } else {
+            bits.is_power_of_two()
+        }
+    }
+
+    /// Most units in the last place [`apply`](Self::apply) can be from
+    /// `x / divisor`: 0 when [`is_exact`](Self::is_exact), otherwise 1.
+    pub fn max_ulps(&self) -> u64 {
+        if self.is_exact() { 0 } else { 1 }
+    }
+
+    /// `x * (1/divisor)`; see the module docs for how this relates to
+    /// `x / divisor`.
+    pub fn apply(&self, x: f64) -> f64 {
+        x * self.reciprocal
+    }
+
+    /// Applies the reciprocal to every value in place.
+    pub fn apply_slice(&self, values: &mut [f64]) {
+        for value in values.iter_mut() {
+            *value = scalar(*value) * self.reciprocal;
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! The most recent results of an authority, kept on-chain.
+//!
+//! `RecordResult` computes a binary f64 operation and appends the operation,
+//! its operands and its result, all as bit patterns, to a program-owned PDA at
+//! `[RESULTS_LOG_SEED, authority]`. Other programs can then read the last
+//! [`RESULTS_LOG_CAPACITY`] values from that account instead of recomputing
+//! them or trusting a client to relay them. The first `RecordResult` signed by
+//! an authority creates its log, funded by that authority for rent exemption;
+//! only that authority can append to it. The data starts with the
+//! [`account_tag::RESULTS_LOG`] tag, which every other instruction taking a
+//! program-owned account refuses.
+
+use solana_program::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::account_tag;
+use crate::double_ops::BinaryOp;
+use crate::instruction::{F64Operands, FloatInstruction};
+
+pub const RESULTS_LOG_SEED: &[u8] = b"results_log";
+
+/// Number of entries a log keeps; older entries are overwritten.
+pub const RESULTS_LOG_CAPACITY: usize = 16;
+
+// [tag: u8][authority: Pubkey][recorded: u64]
+const HEADER_LEN: usize = 1 + 32 + 8;
+
+/// One recorded operation, `result = op(a, b)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResultEntry {
+    pub op: BinaryOp,
+    pub a_bits: u64,
+    pub b_bits: u64,
+    pub result_bits: u64,
+}
+
+impl ResultEntry {
+    /// Layout: [op: u8][a bits: u64][b bits: u64][result bits: u64]
+    pub const LEN: usize = 1 + 3 * 8;
+
+    pub fn new(op: BinaryOp, a: f64, b: f64, result: f64) -> Self {
+        Self {
+            op,
+            a_bits: a.to_bits(),
+            b_bits: b.to_bits(),
+            result_bits: result.to_bits(),
+        }
+    }
+
+    pub fn a(&self) -> f64 {
+        f64::from_bits(self.a_bits)
+    }
+
+    pub fn b(&self) -> f64 {
+        f64::from_bits(self.b_bits)
+    }
+
+    pub fn result(&self) -> f64 {
+        f64::from_bits(self.result_bits)
+    }
+
+    fn pack(&self, dst: &mut [u8]) {
+        dst[0] = self.op as u8;
+        dst[1..9].copy_from_slice(&self.a_bits.to_le_bytes());
+        dst[9..17].copy_from_slice(&self.b_bits.to_le_bytes());
+        dst[17..25].copy_from_slice(&self.result_bits.to_le_bytes());
+    }
+
+    fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        Ok(Self {
+            op: BinaryOp::try_from(src[0]).map_err(|_| ProgramError::InvalidAccountData)?,
+            a_bits: u64::from_le_bytes(src[1..9].try_into().unwrap()),
+            b_bits: u64::from_le_bytes(src[9..17].try_into().unwrap()),
+            result_bits: u64::from_le_bytes(src[17..25].try_into().unwrap()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultsLog {
+    pub authority: Pubkey,
+    /// Entries ever recorded, including those since overwritten.
+    pub recorded: u64,
+    entries: Vec<ResultEntry>,
+}
+
+impl ResultsLog {
+    /// Layout: [tag: u8][authority: Pubkey][recorded: u64]
+    /// [entries: ResultEntry; CAPACITY], where entry `i` (counting from the
+    /// first ever recorded) is in slot `i % RESULTS_LOG_CAPACITY`.
+    pub const LEN: usize = HEADER_LEN + RESULTS_LOG_CAPACITY * ResultEntry::LEN;
+
+    pub fn new(authority: Pubkey) -> Self {
+        Self {
+            authority,
+            recorded: 0,
+            entries: Vec::with_capacity(RESULTS_LOG_CAPACITY),
+        }
+    }
+
+    /// Canonical log address and bump of `authority` for `program_id`.
+    pub fn find_address(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[RESULTS_LOG_SEED, authority.as_ref()], program_id)
+    }
+
+    /// The kept entries, oldest first.
+    pub fn entries(&self) -> &[ResultEntry] {
+        &self.entries
+    }
+
+    /// The most recent entry.
+    pub fn last(&self) -> Option<&ResultEntry> {
+        self.entries.last()
+    }
+
+    /// Appends `entry`, dropping the oldest one once the log is full.
+    pub fn push(&mut self, entry: ResultEntry) {
+        if self.entries.len() == RESULTS_LOG_CAPACITY {
+            self.entries.remove(0);
+        }
+        self.entries.push(entry);
+        self.recorded += 1;
+    }
+
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        dst[0] = account_tag::RESULTS_LOG;
+        dst[1..33].copy_from_slice(self.authority.as_ref());
+        dst[33..41].copy_from_slice(&self.recorded.to_le_bytes());
+        let first = self.recorded - self.entries.len() as u64;
+        for (index, entry) in (first..).zip(&self.entries) {
+            let offset = HEADER_LEN + slot(index) * ResultEntry::LEN;
+            entry.pack(&mut dst[offset..offset + ResultEntry::LEN]);
+        }
+        Ok(())
+    }
+
+    pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN || src[0] != account_tag::RESULTS_LOG {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let authority = Pubkey::new_from_array(src[1..33].try_into().unwrap());
+        let recorded = u64::from_le_bytes(src[33..41].try_into().unwrap());
+        let kept = recorded.min(RESULTS_LOG_CAPACITY as u64);
+        let entries = (recorded - kept..recorded)
+            .map(|index| {
+                let offset = HEADER_LEN + slot(index) * ResultEntry::LEN;
+                ResultEntry::unpack(&src[offset..offset + ResultEntry::LEN])
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            authority,
+            recorded,
+            entries,
+        })
+    }
+
+    /// Loads the log from `account`, or `None` if it has not been created yet.
+    ///
+    /// `account` must be the canonical log address of `authority`; anything
+    /// else is `InvalidSeeds`. Data without the results log tag is
+    /// `InvalidAccountData`.
+    pub fn load(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+        account: &AccountInfo,
+    ) -> Result<Option<Self>, ProgramError> {
+        if *account.key != Self::find_address(program_id, authority).0 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if account.owner != program_id {
+            return Ok(None);
+        }
+        let log = Self::unpack(&account.try_borrow_data()?)?;
+        if log.authority != *authority {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Some(log))
+    }
+
+    /// A `RecordResult` instruction appending `op(a, b)` to the log of
+    /// `authority`, which must sign and pays for the log's creation.
+    pub fn record_instruction(
+        program_id: Pubkey,
+        authority: Pubkey,
+        op: BinaryOp,
+        a: f64,
+        b: f64,
+    ) -> Instruction {
+        let (log, _) = Self::find_address(&program_id, &authority);
+        let instruction = FloatInstruction::RecordResult {
+            op,
+            operands: F64Operands { a, b },
+        };
+        Instruction::new_with_bytes(
+            program_id,
+            &instruction.pack(),
+            vec![
+                AccountMeta::new(log, false),
+                AccountMeta::new(authority, true),
+                AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+            ],
+        )
+    }
+}
+
+fn slot(index: u64) -> usize {
+    (index % RESULTS_LOG_CAPACITY as u64) as usize
+}
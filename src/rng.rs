@@ -0,0 +1,144 @@
+//! Seeded random numbers that replay bit for bit on every validator.
+//!
+//! The generator is xoshiro256** over the 32-byte seed (a slot hash, a VRF
+//! output, a commit-reveal secret), and every float it produces is built from
+//! integer bits and correctly rounded arithmetic: uniforms fill the 53-bit
+//! mantissa directly, and the exponential and normal transforms use
+//! [`ln_deterministic`] and IEEE `sqrt` rather than the platform libm. The
+//! same seed therefore yields the same sequence on SBF, native and wasm.
+//!
+//! Where the seed comes from decides whether the result can be predicted or
+//! influenced; this module only guarantees that it can be replayed.
+
+use crate::error::FloatError;
+use crate::transcendental::ln_deterministic;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeterministicRng {
+    state: [u64; 4],
+    // Second value of the last polar-method pair, in standard units
+    spare_normal: Option<f64>,
+}
+
+impl DeterministicRng {
+    /// Reads the seed as four little-endian u64 words. xoshiro cannot start
+    /// from all zeros, so an all-zero seed is replaced by a fixed non-zero
+    /// state.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        let mut state = [0u64; 4];
+        for (word, bytes) in state.iter_mut().zip(seed.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        if state == [0; 4] {
+            // splitmix64 outputs for seed 0
+            let mut mix = 0u64;
+            for word in state.iter_mut() {
+                *word = splitmix64(&mut mix);
+            }
+        }
+        Self {
+            state,
+            spare_normal: None,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let [s0, s1, s2, s3] = &mut self.state;
+        let result = s1.wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let shifted = *s1 << 17;
+        *s2 ^= *s0;
+        *s3 ^= *s1;
+        *s1 ^= *s2;
+        *s0 ^= *s3;
+        *s2 ^= shifted;
+        *s3 = s3.rotate_left(45);
+        result
+    }
+
+    /// Uniform in `[0, 1)`: the top 53 bits of the next word scaled by 2^-53,
+    /// so every multiple of 2^-53 is equally likely.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Uniform in `0..bound` without modulo bias (Lemire's method), e.g. to
+    /// draw a lottery winner. Fails with `OutOfRange` for a zero bound.
+    pub fn next_below(&mut self, bound: u64) -> Result<u64, FloatError> {
+        if bound == 0 {
+            return Err(FloatError::OutOfRange);
+        }
+        // Reject the low products that would make small results more likely
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let product = self.next_u64() as u128 * bound as u128;
+            if product as u64 >= threshold {
+                return Ok((product >> 64) as u64);
+            }
+        }
+    }
+
+    /// Exponentially distributed with the given rate (mean `1 / rate`), by
+    /// inversion: `-ln(1 - u) / rate`.
+    ///
+    /// Fails with `NaNOperand` for a NaN rate and `OutOfRange` unless the
+    /// rate is positive and finite.
+    pub fn next_exponential(&mut self, rate: f64) -> Result<f64, FloatError> {
+        if rate.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if rate <= 0.0 || rate.is_infinite() {
+            return Err(FloatError::OutOfRange);
+        }
+        // 1 - u is in (0, 1], so the logarithm is finite
+        Ok(-ln_deterministic(1.0 - self.next_f64()) / rate)
+    }
+
+    /// Normally distributed, by Marsaglia's polar method. Values come in
+    /// pairs, so every other call consumes no randomness.
+    ///
+    /// Fails with `NaNOperand` for NaN parameters, `OutOfRange` for a
+    /// negative or infinite standard deviation or an infinite mean, and
+    /// `Overflow` if scaling the sample overflows.
+    pub fn next_normal(&mut self, mean: f64, std_dev: f64) -> Result<f64, FloatError> {
+        if mean.is_nan() || std_dev.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if std_dev < 0.0 || std_dev.is_infinite() || mean.is_infinite() {
+            return Err(FloatError::OutOfRange);
+        }
+        let standard = match self.spare_normal.take() {
+            Some(spare) => spare,
+            None => {
+                let (first, second) = self.polar_pair();
+                self.spare_normal = Some(second);
+                first
+            }
+        };
+        let value = mean + std_dev * standard;
+        if !value.is_finite() {
+            return Err(FloatError::Overflow);
+        }
+        Ok(value)
+    }
+
+    // Two independent standard normals.
+    fn polar_pair(&mut self) -> (f64, f64) {
+        loop {
+            let x = 2.0 * self.next_f64() - 1.0;
+            let y = 2.0 * self.next_f64() - 1.0;
+            let radius_squared = x * x + y * y;
+            if radius_squared > 0.0 && radius_squared < 1.0 {
+                let scale = (-2.0 * ln_deterministic(radius_squared) / radius_squared).sqrt();
+                return (x * scale, y * scale);
+            }
+        }
+    }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
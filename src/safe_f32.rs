@@ -0,0 +1,159 @@
+//! An f32 that is always finite.
+//!
+//! The 4-byte counterpart of [`SafeF64`], for values that have to fit a
+//! packed instruction or account layout. Arithmetic stays in f32; widening to
+//! `SafeF64` is exact, and narrowing back fails rather than rounding silently.
+
+use crate::error::FloatError;
+use crate::format::write_fixed;
+use crate::safe_f64::SafeF64;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SafeF32(f32);
+
+impl SafeF32 {
+    pub const ZERO: SafeF32 = SafeF32(0.0);
+    pub const ONE: SafeF32 = SafeF32(1.0);
+    pub const MIN: SafeF32 = SafeF32(f32::MIN);
+    pub const MAX: SafeF32 = SafeF32(f32::MAX);
+    /// Fractional digits `Display` prints unless the format string sets a
+    /// precision.
+    pub const DISPLAY_DECIMALS: u32 = 6;
+
+    pub fn new(value: f32) -> Result<Self, FloatError> {
+        if value.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if value.is_infinite() {
+            return Err(FloatError::Overflow);
+        }
+        Ok(Self(value))
+    }
+
+    pub fn get(self) -> f32 {
+        self.0
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Result<Self, FloatError> {
+        Self::new(self.0 + rhs.0)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, FloatError> {
+        Self::new(self.0 - rhs.0)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, FloatError> {
+        Self::new(self.0 * rhs.0)
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Result<Self, FloatError> {
+        if rhs.0 == 0.0 {
+            return Err(FloatError::DivisionByZero);
+        }
+        Self::new(self.0 / rhs.0)
+    }
+
+    /// The same value as a `SafeF64`; every f32 is exactly an f64.
+    pub fn to_safe_f64(self) -> SafeF64 {
+        SafeF64::new(self.0 as f64).unwrap()
+    }
+
+    /// Narrows `value` only if f32 holds it exactly.
+    ///
+    /// Fails with `Overflow` beyond the f32 range and `PrecisionLoss` for
+    /// anything else that would round, including values that would become
+    /// subnormal and lose bits, or underflow to zero.
+    pub fn from_safe_f64(value: SafeF64) -> Result<Self, FloatError> {
+        let narrowed = Self::from_safe_f64_rounded(value)?;
+        if narrowed.0 as f64 != value.get() {
+            return Err(FloatError::PrecisionLoss);
+        }
+        Ok(narrowed)
+    }
+
+    /// Narrows `value` to the nearest f32, ties to even.
+    ///
+    /// Fails with `Overflow` if that is infinite.
+    pub fn from_safe_f64_rounded(value: SafeF64) -> Result<Self, FloatError> {
+        Self::new(value.get() as f32)
+    }
+}
+
+impl TryFrom<f32> for SafeF32 {
+    type Error = FloatError;
+
+    fn try_from(value: f32) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<SafeF32> for f32 {
+    fn from(value: SafeF32) -> f32 {
+        value.0
+    }
+}
+
+impl From<SafeF32> for SafeF64 {
+    fn from(value: SafeF32) -> SafeF64 {
+        value.to_safe_f64()
+    }
+}
+
+/// Exact narrowing, as [`SafeF32::from_safe_f64`].
+impl TryFrom<SafeF64> for SafeF32 {
+    type Error = FloatError;
+
+    fn try_from(value: SafeF64) -> Result<Self, Self::Error> {
+        Self::from_safe_f64(value)
+    }
+}
+
+macro_rules! impl_checked_op {
+    ($trait:ident, $method:ident, $checked:ident) => {
+        impl $trait for SafeF32 {
+            type Output = Result<SafeF32, FloatError>;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                self.$checked(rhs)
+            }
+        }
+    };
+}
+
+impl_checked_op!(Add, add, checked_add);
+impl_checked_op!(Sub, sub, checked_sub);
+impl_checked_op!(Mul, mul, checked_mul);
+impl_checked_op!(Div, div, checked_div);
+
+impl Neg for SafeF32 {
+    type Output = SafeF32;
+
+    fn neg(self) -> SafeF32 {
+        SafeF32(-self.0)
+    }
+}
+
+// NaN is excluded, so the partial order is total.
+impl Eq for SafeF32 {}
+
+impl Ord for SafeF32 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap()
+    }
+}
+
+impl PartialOrd for SafeF32 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Deterministic fixed-decimals output of the exact f32 value.
+impl fmt::Display for SafeF32 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_fixed(f, self.0 as f64, Self::DISPLAY_DECIMALS)
+    }
+}
@@ -0,0 +1,121 @@
+//! An f64 that is always finite.
+//!
+//! Construction rejects NaN and the infinities, and every operation returns a
+//! `Result` instead of producing one, so a `SafeF64` can be stored or compared
+//! without re-checking it.
+
+use crate::error::FloatError;
+use crate::format::write_fixed;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SafeF64(f64);
+
+impl SafeF64 {
+    pub const ZERO: SafeF64 = SafeF64(0.0);
+    pub const ONE: SafeF64 = SafeF64(1.0);
+    pub const MIN: SafeF64 = SafeF64(f64::MIN);
+    pub const MAX: SafeF64 = SafeF64(f64::MAX);
+    /// Fractional digits `Display` prints unless the format string sets a
+    /// precision.
+    pub const DISPLAY_DECIMALS: u32 = 9;
+
+    pub fn new(value: f64) -> Result<Self, FloatError> {
+        if value.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if value.is_infinite() {
+            return Err(FloatError::Overflow);
+        }
+        Ok(Self(value))
+    }
+
+    pub fn get(self) -> f64 {
+        self.0
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Result<Self, FloatError> {
+        Self::new(self.0 + rhs.0)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, FloatError> {
+        Self::new(self.0 - rhs.0)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, FloatError> {
+        Self::new(self.0 * rhs.0)
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Result<Self, FloatError> {
+        if rhs.0 == 0.0 {
+            return Err(FloatError::DivisionByZero);
+        }
+        Self::new(self.0 / rhs.0)
+    }
+}
+
+impl TryFrom<f64> for SafeF64 {
+    type Error = FloatError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<SafeF64> for f64 {
+    fn from(value: SafeF64) -> f64 {
+        value.0
+    }
+}
+
+// The operators are the checked methods, so `(a + b)? * c` keeps the invariant
+// without naming them.
+macro_rules! impl_checked_op {
+    ($trait:ident, $method:ident, $checked:ident) => {
+        impl $trait for SafeF64 {
+            type Output = Result<SafeF64, FloatError>;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                self.$checked(rhs)
+            }
+        }
+    };
+}
+
+impl_checked_op!(Add, add, checked_add);
+impl_checked_op!(Sub, sub, checked_sub);
+impl_checked_op!(Mul, mul, checked_mul);
+impl_checked_op!(Div, div, checked_div);
+
+/// Negating a finite value is always finite.
+impl Neg for SafeF64 {
+    type Output = SafeF64;
+
+    fn neg(self) -> SafeF64 {
+        SafeF64(-self.0)
+    }
+}
+
+// NaN is excluded, so the partial order is total.
+impl Eq for SafeF64 {}
+
+impl Ord for SafeF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap()
+    }
+}
+
+impl PartialOrd for SafeF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Deterministic fixed-decimals output, e.g. for `msg!` logs.
+impl fmt::Display for SafeF64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_fixed(f, self.0, Self::DISPLAY_DECIMALS)
+    }
+}
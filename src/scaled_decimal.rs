@@ -0,0 +1,107 @@
+//! Mantissa/exponent decimals in the shape oracles publish prices.
+//!
+//! Pyth and similar feeds report a price as an `i64` mantissa and a base-10
+//! exponent, and different feeds use different exponents (`-8` for SOL/USD,
+//! `-5` for BONK/USD, ...). Comparing or subtracting the mantissas directly is
+//! only meaningful once both sit on the same exponent; [`align`] gets them
+//! there exactly, and [`rescale`] moves a single value to a chosen exponent
+//! with an explicit rounding mode.
+
+use crate::error::FloatError;
+use crate::quantize::RoundingMode;
+
+/// `mantissa * 10^expo`.
+///
+/// Equality is structural: `100e-2` and `1e0` are equal values but different
+/// `ScaledDecimal`s. [`align`] them first to compare values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScaledDecimal {
+    mantissa: i64,
+    expo: i32,
+}
+
+impl ScaledDecimal {
+    pub const fn new(mantissa: i64, expo: i32) -> Self {
+        Self { mantissa, expo }
+    }
+
+    pub fn mantissa(self) -> i64 {
+        self.mantissa
+    }
+
+    pub fn expo(self) -> i32 {
+        self.expo
+    }
+}
+
+/// Moves `decimal` to `target_expo`.
+///
+/// Lowering the exponent multiplies the mantissa exactly and fails with
+/// `Overflow` if it no longer fits in an i64; raising it divides with
+/// `rounding`, relative to the signed value (so `Floor` of a negative price
+/// moves away from zero).
+pub fn rescale(
+    decimal: ScaledDecimal,
+    target_expo: i32,
+    rounding: RoundingMode,
+) -> Result<ScaledDecimal, FloatError> {
+    let shift = decimal.expo as i64 - target_expo as i64;
+    if shift >= 0 {
+        return scale_up(decimal, target_expo, shift);
+    }
+    // |mantissa| < 10^19, so dividing by anything from 10^20 up rounds the same
+    let divisor = 10i128.pow(shift.unsigned_abs().min(20) as u32);
+    let mantissa = round_div(decimal.mantissa as i128, divisor, rounding);
+    // The quotient is no larger in magnitude than the mantissa, or is ±1
+    Ok(ScaledDecimal::new(mantissa as i64, target_expo))
+}
+
+/// Brings `a` and `b` to the smaller of their exponents, so their mantissas
+/// can be compared, added or subtracted directly. Nothing is rounded; fails
+/// with `Overflow` if either mantissa does not fit at the common exponent.
+pub fn align(
+    a: ScaledDecimal,
+    b: ScaledDecimal,
+) -> Result<(ScaledDecimal, ScaledDecimal), FloatError> {
+    let expo = a.expo.min(b.expo);
+    Ok((
+        scale_up(a, expo, a.expo as i64 - expo as i64)?,
+        scale_up(b, expo, b.expo as i64 - expo as i64)?,
+    ))
+}
+
+fn scale_up(
+    decimal: ScaledDecimal,
+    target_expo: i32,
+    shift: i64,
+) -> Result<ScaledDecimal, FloatError> {
+    if decimal.mantissa == 0 {
+        return Ok(ScaledDecimal::new(0, target_expo));
+    }
+    let mantissa = u32::try_from(shift)
+        .ok()
+        .and_then(|shift| 10i64.checked_pow(shift))
+        .and_then(|factor| decimal.mantissa.checked_mul(factor))
+        .ok_or(FloatError::Overflow)?;
+    Ok(ScaledDecimal::new(mantissa, target_expo))
+}
+
+// numerator / divisor for a positive divisor, rounded on the signed value.
+fn round_div(numerator: i128, divisor: i128, rounding: RoundingMode) -> i128 {
+    let floor = numerator.div_euclid(divisor);
+    let remainder = numerator.rem_euclid(divisor);
+    if remainder == 0 {
+        return floor;
+    }
+    let doubled = 2 * remainder;
+    let round_up = match rounding {
+        RoundingMode::Floor => false,
+        RoundingMode::Ceil => true,
+        RoundingMode::TowardZero => numerator < 0,
+        RoundingMode::HalfUp => doubled > divisor || (doubled == divisor && numerator > 0),
+        RoundingMode::HalfEven => {
+            doubled > divisor || (doubled == divisor && floor.rem_euclid(2) == 1)
+        }
+    };
+    if round_up { floor + 1 } else { floor }
+}
@@ -0,0 +1,144 @@
+//! Share accounting for pooled funds such as insurance funds and vaults.
+//!
+//! Conversions always round in favour of the fund, and both totals carry a virtual
+//! offset of one unit. The offset makes the first deposit mint 1:1 and means an
+//! attacker who donates assets to inflate the share price loses at least as much
+//! as any depositor they manage to round down.
+//!
+//! [`SharePool`] is the general form, with the rounding direction and the
+//! empty-pool behaviour as parameters; the functions here and in
+//! [`crate::stake_pool`] are fixed configurations of it.
+
+use crate::error::FloatError;
+use crate::mul_div::mul_div_u128;
+use crate::quantize::RoundingMode;
+
+pub const VIRTUAL_ASSETS: u64 = 1;
+pub const VIRTUAL_SHARES: u64 = 1;
+
+/// How a [`SharePool`] prices conversions while it is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirstDeposit {
+    /// Both totals always carry these virtual units, so the first deposit
+    /// converts at `shares / assets` and donations cannot inflate the share
+    /// price for free (this module's functions use [`VIRTUAL_ASSETS`] and
+    /// [`VIRTUAL_SHARES`]). With zero offsets an empty pool fails with
+    /// `DivisionByZero`.
+    VirtualOffset { assets: u64, shares: u64 },
+    /// No offset: deposits convert 1:1 while either total is zero, and shares
+    /// convert 1:1 while none are outstanding, as in the SPL stake-pool
+    /// program. Shares with no assets behind them are worth nothing.
+    OneToOne,
+}
+
+/// The totals of a vault or liquid-staking pool, converting between assets
+/// and shares with the rounding the caller names.
+///
+/// To favour the pool, round down what it pays out (shares minted for a
+/// deposit, assets paid for a redemption) and round up what it charges
+/// (assets needed to mint a number of shares, shares burned to withdraw an
+/// amount of assets).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SharePool {
+    pub total_assets: u64,
+    pub total_shares: u64,
+    pub first_deposit: FirstDeposit,
+}
+
+impl SharePool {
+    /// `assets` in shares at the pool's current rate.
+    pub fn shares_for_assets(
+        &self,
+        assets: u64,
+        rounding: RoundingMode,
+    ) -> Result<u64, FloatError> {
+        let (total_assets, total_shares) = match self.first_deposit {
+            FirstDeposit::VirtualOffset { assets, shares } => (
+                self.total_assets as u128 + assets as u128,
+                self.total_shares as u128 + shares as u128,
+            ),
+            FirstDeposit::OneToOne if self.total_assets == 0 || self.total_shares == 0 => {
+                return Ok(assets);
+            }
+            FirstDeposit::OneToOne => (self.total_assets as u128, self.total_shares as u128),
+        };
+        convert(assets, total_shares, total_assets, rounding)
+    }
+
+    /// `shares` in assets at the pool's current rate.
+    pub fn assets_for_shares(
+        &self,
+        shares: u64,
+        rounding: RoundingMode,
+    ) -> Result<u64, FloatError> {
+        let (total_assets, total_shares) = match self.first_deposit {
+            FirstDeposit::VirtualOffset { assets, shares } => (
+                self.total_assets as u128 + assets as u128,
+                self.total_shares as u128 + shares as u128,
+            ),
+            FirstDeposit::OneToOne if self.total_shares == 0 => return Ok(shares),
+            FirstDeposit::OneToOne => (self.total_assets as u128, self.total_shares as u128),
+        };
+        convert(shares, total_assets, total_shares, rounding)
+    }
+}
+
+/// Shares minted for depositing `assets`, rounded down.
+///
+/// Fails with `PrecisionLoss` if a non-zero deposit would mint zero shares.
+pub fn shares_for_deposit(
+    assets: u64,
+    total_assets: u64,
+    total_shares: u64,
+) -> Result<u64, FloatError> {
+    let shares =
+        virtual_pool(total_assets, total_shares).shares_for_assets(assets, RoundingMode::Floor)?;
+    reject_zero(assets, shares)
+}
+
+/// Assets paid out for redeeming `shares`, rounded down.
+///
+/// Fails with `PrecisionLoss` if a non-zero redemption would pay out nothing.
+pub fn assets_for_shares(
+    shares: u64,
+    total_assets: u64,
+    total_shares: u64,
+) -> Result<u64, FloatError> {
+    if shares > total_shares {
+        return Err(FloatError::OutOfRange);
+    }
+    let assets =
+        virtual_pool(total_assets, total_shares).assets_for_shares(shares, RoundingMode::Floor)?;
+    reject_zero(shares, assets)
+}
+
+fn virtual_pool(total_assets: u64, total_shares: u64) -> SharePool {
+    SharePool {
+        total_assets,
+        total_shares,
+        first_deposit: FirstDeposit::VirtualOffset {
+            assets: VIRTUAL_ASSETS,
+            shares: VIRTUAL_SHARES,
+        },
+    }
+}
+
+fn reject_zero(amount: u64, converted: u64) -> Result<u64, FloatError> {
+    if amount != 0 && converted == 0 {
+        return Err(FloatError::PrecisionLoss);
+    }
+    Ok(converted)
+}
+
+fn convert(
+    amount: u64,
+    numerator: u128,
+    denominator: u128,
+    rounding: RoundingMode,
+) -> Result<u64, FloatError> {
+    if amount == 0 {
+        return Ok(0);
+    }
+    let result = mul_div_u128(amount as u128, numerator, denominator, rounding)?;
+    u64::try_from(result).map_err(|_| FloatError::Overflow)
+}
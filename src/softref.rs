@@ -0,0 +1,420 @@
+//! IEEE-754 arithmetic implemented with integer operations only.
+//!
+//! A reference for the hardware float unit: add, multiply, divide, fused
+//! multiply-add and square root for f32 and f64, rounding to nearest with
+//! ties to even, including subnormals, signed zeros and infinities. Every
+//! result is computed from the operands' bit patterns, so it does not depend
+//! on the target's float hardware, its flush-to-zero setting or the
+//! compiler's choice of instructions.
+//!
+//! NaN results are always the positive quiet NaN; hardware NaN payloads vary.
+//!
+//! [`div_f64_rounded`] also implements the directed IEEE rounding attributes,
+//! which hardware only offers through a global mode register.
+
+use crate::quantize::RoundingMode;
+
+#[derive(Clone, Copy)]
+struct Format {
+    mantissa_bits: u32,
+    exponent_bits: u32,
+    // Significand bits of a wider format every result is first rounded to,
+    // as the x87 unit does before storing to memory
+    intermediate_precision: Option<u32>,
+    // How the final result is rounded; intermediates are always rounded to
+    // nearest, ties to even
+    rounding: RoundingMode,
+}
+
+const F64: Format = Format {
+    mantissa_bits: 52,
+    exponent_bits: 11,
+    intermediate_precision: None,
+    rounding: RoundingMode::HalfEven,
+};
+
+const F32: Format = Format {
+    mantissa_bits: 23,
+    exponent_bits: 8,
+    intermediate_precision: None,
+    rounding: RoundingMode::HalfEven,
+};
+
+// x87 with its default 64-bit precision control, storing results to f64. The
+// extended exponent range is wide enough that the intermediate never
+// overflows or goes subnormal for f64 operands.
+const F64_X87: Format = Format {
+    intermediate_precision: Some(64),
+    ..F64
+};
+
+enum Class {
+    Nan,
+    Infinite(bool),
+    Zero(bool),
+    // sign, significand, exponent: the value is significand * 2^exponent
+    Finite(bool, u128, i32),
+}
+
+impl Format {
+    fn bias(self) -> i32 {
+        (1 << (self.exponent_bits - 1)) - 1
+    }
+
+    fn max_biased(self) -> u64 {
+        (1 << self.exponent_bits) - 1
+    }
+
+    fn precision(self) -> u32 {
+        self.mantissa_bits + 1
+    }
+
+    fn sign_bit(self) -> u64 {
+        1 << (self.mantissa_bits + self.exponent_bits)
+    }
+
+    fn nan(self) -> u64 {
+        self.max_biased() << self.mantissa_bits | 1 << (self.mantissa_bits - 1)
+    }
+
+    fn infinity(self, negative: bool) -> u64 {
+        self.signed(negative, self.max_biased() << self.mantissa_bits)
+    }
+
+    fn zero(self, negative: bool) -> u64 {
+        self.signed(negative, 0)
+    }
+
+    fn signed(self, negative: bool, magnitude: u64) -> u64 {
+        if negative {
+            magnitude | self.sign_bit()
+        } else {
+            magnitude
+        }
+    }
+
+    fn classify(self, bits: u64) -> Class {
+        let negative = bits & self.sign_bit() != 0;
+        let biased = bits >> self.mantissa_bits & self.max_biased();
+        let fraction = bits & ((1 << self.mantissa_bits) - 1);
+        let min_exponent = 1 - self.bias() - self.mantissa_bits as i32;
+        match (biased, fraction) {
+            (b, 0) if b == self.max_biased() => Class::Infinite(negative),
+            (b, _) if b == self.max_biased() => Class::Nan,
+            (0, 0) => Class::Zero(negative),
+            (0, f) => Class::Finite(negative, f as u128, min_exponent),
+            (b, f) => Class::Finite(
+                negative,
+                (f | 1 << self.mantissa_bits) as u128,
+                b as i32 - 1 + min_exponent,
+            ),
+        }
+    }
+
+    /// Rounds `(significand + sticky) * 2^exponent` with `self.rounding`.
+    ///
+    /// `sticky` means nonzero bits were discarded below the significand; callers
+    /// keep at least two bits beyond the target precision whenever it is set.
+    fn round(self, negative: bool, significand: u128, exponent: i32, sticky: bool) -> u64 {
+        if significand == 0 {
+            return self.zero(negative);
+        }
+        let (significand, exponent, sticky) = match self.intermediate_precision {
+            Some(precision) => round_to_precision(significand, exponent, sticky, precision),
+            None => (significand, exponent, sticky),
+        };
+        let precision = self.precision() as i32;
+        let leading = exponent + (128 - significand.leading_zeros()) as i32 - 1;
+        let min_lsb = 1 - self.bias() - self.mantissa_bits as i32;
+        let lsb = (leading - (precision - 1)).max(min_lsb);
+        let rounding = self.rounding.for_magnitude(negative);
+
+        let shift = lsb - exponent;
+        let mut quotient = if shift <= 0 {
+            significand << -shift
+        } else if shift >= 128 {
+            // Far below half an ulp of the smallest subnormal
+            (rounding == RoundingMode::Ceil) as u128
+        } else {
+            let kept = significand >> shift;
+            let rest = significand & ((1 << shift) - 1);
+            let half = 1 << (shift - 1);
+            let round_up = match rounding {
+                RoundingMode::Floor | RoundingMode::TowardZero => false,
+                RoundingMode::Ceil => rest != 0 || sticky,
+                RoundingMode::HalfUp => rest >= half,
+                RoundingMode::HalfEven => rest > half || rest == half && (sticky || kept & 1 == 1),
+            };
+            kept + round_up as u128
+        };
+
+        let mut lsb = lsb;
+        if quotient == 1 << precision {
+            quotient >>= 1;
+            lsb += 1;
+        }
+        if quotient >> (precision - 1) == 0 {
+            // Subnormal (or rounded to zero)
+            return self.signed(negative, quotient as u64);
+        }
+        let biased = lsb + (precision - 1) + self.bias();
+        if biased as u64 >= self.max_biased() {
+            // Rounding toward zero stops at the largest finite value
+            return match rounding {
+                RoundingMode::Floor | RoundingMode::TowardZero => {
+                    self.signed(negative, (self.max_biased() << self.mantissa_bits) - 1)
+                }
+                _ => self.infinity(negative),
+            };
+        }
+        let fraction = quotient as u64 & ((1 << self.mantissa_bits) - 1);
+        self.signed(negative, (biased as u64) << self.mantissa_bits | fraction)
+    }
+
+    fn add(self, a: u64, b: u64) -> u64 {
+        match (self.classify(a), self.classify(b)) {
+            (Class::Nan, _) | (_, Class::Nan) => self.nan(),
+            (Class::Infinite(x), Class::Infinite(y)) if x != y => self.nan(),
+            (Class::Infinite(x), _) | (_, Class::Infinite(x)) => self.infinity(x),
+            (Class::Zero(x), Class::Zero(y)) => self.zero(x && y),
+            (Class::Zero(_), _) => b,
+            (_, Class::Zero(_)) => a,
+            (Class::Finite(sa, ma, ea), Class::Finite(sb, mb, eb)) => {
+                self.add_finite((sa, ma, ea), (sb, mb, eb))
+            }
+        }
+    }
+
+    // Sum of two finite non-zero values given as (sign, significand,
+    // exponent). Significands may be up to 117 bits wide, so an exact product
+    // can be added without rounding it first.
+    fn add_finite(self, (sa, ma, ea): (bool, u128, i32), (sb, mb, eb): (bool, u128, i32)) -> u64 {
+        // Exponent just above the leading bit, and the significand
+        // normalized to bit 127: together they order magnitudes.
+        let top = |m: u128, e: i32| e + (128 - m.leading_zeros()) as i32;
+        let key = |m: u128, e: i32| (top(m, e), m << m.leading_zeros());
+        let ((s_big, m_big, e_big), (s_small, m_small, e_small)) = if key(ma, ea) >= key(mb, eb) {
+            ((sa, ma, ea), (sb, mb, eb))
+        } else {
+            ((sb, mb, eb), (sa, ma, ea))
+        };
+        // Put the larger operand's leading bit at position 116 and align
+        // the smaller one to it, folding anything shifted out into sticky.
+        let work = top(m_big, e_big) - 117;
+        let big = m_big << (e_big - work);
+        let shift = e_small - work;
+        let (small, sticky) = if shift >= 0 {
+            (m_small << shift, false)
+        } else if -shift >= 128 {
+            (0, true)
+        } else {
+            let small = m_small >> -shift;
+            (small, small << -shift != m_small)
+        };
+        if s_big == s_small {
+            self.round(s_big, big + small, work, sticky)
+        } else if big == small && !sticky {
+            self.zero(false)
+        } else {
+            // big - (small + δ) = (big - small - 1) + (1 - δ)
+            let difference = big - small - sticky as u128;
+            self.round(s_big, difference, work, sticky)
+        }
+    }
+
+    fn mul(self, a: u64, b: u64) -> u64 {
+        let negative = (a ^ b) & self.sign_bit() != 0;
+        match (self.classify(a), self.classify(b)) {
+            (Class::Nan, _) | (_, Class::Nan) => self.nan(),
+            (Class::Infinite(_), Class::Zero(_)) | (Class::Zero(_), Class::Infinite(_)) => {
+                self.nan()
+            }
+            (Class::Infinite(_), _) | (_, Class::Infinite(_)) => self.infinity(negative),
+            (Class::Zero(_), _) | (_, Class::Zero(_)) => self.zero(negative),
+            (Class::Finite(_, ma, ea), Class::Finite(_, mb, eb)) => {
+                self.round(negative, ma * mb, ea + eb, false)
+            }
+        }
+    }
+
+    fn div(self, a: u64, b: u64) -> u64 {
+        let negative = (a ^ b) & self.sign_bit() != 0;
+        match (self.classify(a), self.classify(b)) {
+            (Class::Nan, _) | (_, Class::Nan) => self.nan(),
+            (Class::Infinite(_), Class::Infinite(_)) | (Class::Zero(_), Class::Zero(_)) => {
+                self.nan()
+            }
+            (Class::Infinite(_), _) | (_, Class::Zero(_)) => self.infinity(negative),
+            (Class::Zero(_), _) | (_, Class::Infinite(_)) => self.zero(negative),
+            (Class::Finite(_, ma, ea), Class::Finite(_, mb, eb)) => {
+                // Widen the dividend to 127 bits so the quotient keeps at least
+                // 127 - 53 bits, well past the precision plus rounding bits.
+                let shift = ma.leading_zeros() - 1;
+                let dividend = ma << shift;
+                let quotient = dividend / mb;
+                let sticky = dividend % mb != 0;
+                self.round(negative, quotient, ea - shift as i32 - eb, sticky)
+            }
+        }
+    }
+
+    fn mul_add(self, a: u64, b: u64, c: u64) -> u64 {
+        let negative = (a ^ b) & self.sign_bit() != 0;
+        match (self.classify(a), self.classify(b), self.classify(c)) {
+            (Class::Nan, _, _) | (_, Class::Nan, _) | (_, _, Class::Nan) => self.nan(),
+            (Class::Infinite(_), Class::Zero(_), _) | (Class::Zero(_), Class::Infinite(_), _) => {
+                self.nan()
+            }
+            (Class::Infinite(_), _, Class::Infinite(y))
+            | (_, Class::Infinite(_), Class::Infinite(y))
+                if y != negative =>
+            {
+                self.nan()
+            }
+            (Class::Infinite(_), _, _) | (_, Class::Infinite(_), _) => self.infinity(negative),
+            (_, _, Class::Infinite(y)) => self.infinity(y),
+            (Class::Zero(_), _, Class::Zero(y)) | (_, Class::Zero(_), Class::Zero(y)) => {
+                self.zero(negative && y)
+            }
+            (Class::Zero(_), _, _) | (_, Class::Zero(_), _) => c,
+            (Class::Finite(_, ma, ea), Class::Finite(_, mb, eb), Class::Zero(_)) => {
+                self.round(negative, ma * mb, ea + eb, false)
+            }
+            (Class::Finite(_, ma, ea), Class::Finite(_, mb, eb), Class::Finite(sc, mc, ec)) => {
+                // The product is exact in at most 106 bits; only the sum rounds
+                self.add_finite((negative, ma * mb, ea + eb), (sc, mc, ec))
+            }
+        }
+    }
+
+    fn sqrt(self, a: u64) -> u64 {
+        match self.classify(a) {
+            Class::Nan => self.nan(),
+            Class::Zero(negative) => self.zero(negative),
+            Class::Infinite(false) => self.infinity(false),
+            Class::Infinite(true) | Class::Finite(true, _, _) => self.nan(),
+            Class::Finite(false, m, e) => {
+                // Widen to 125 or 126 bits with an even exponent, so the integer
+                // root has about 63 bits.
+                let mut shift = m.leading_zeros() as i32 - 2;
+                if (e - shift) % 2 != 0 {
+                    shift -= 1;
+                }
+                let radicand = m << shift;
+                let root = isqrt(radicand);
+                let sticky = root * root != radicand;
+                self.round(false, root, (e - shift) / 2, sticky)
+            }
+        }
+    }
+}
+
+// Rounds to nearest-even at `precision` significant bits, with no exponent
+// limits. The result is exact, so it carries no sticky bit.
+fn round_to_precision(
+    significand: u128,
+    exponent: i32,
+    sticky: bool,
+    precision: u32,
+) -> (u128, i32, bool) {
+    let width = 128 - significand.leading_zeros();
+    if width <= precision {
+        // Callers only set sticky on significands far wider than this
+        return (significand, exponent, sticky);
+    }
+    let shift = width - precision;
+    let kept = significand >> shift;
+    let rest = significand & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    let round_up = rest > half || rest == half && (sticky || kept & 1 == 1);
+    (kept + round_up as u128, exponent + shift as i32, false)
+}
+
+// Digit-by-digit integer square root: floor(sqrt(n)).
+fn isqrt(n: u128) -> u128 {
+    let mut remainder = n;
+    let mut root = 0u128;
+    let mut bit = 1u128 << 126;
+    while bit > n {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if remainder >= root + bit {
+            remainder -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+pub fn add_f64(a: f64, b: f64) -> f64 {
+    f64::from_bits(F64.add(a.to_bits(), b.to_bits()))
+}
+
+pub fn mul_f64(a: f64, b: f64) -> f64 {
+    f64::from_bits(F64.mul(a.to_bits(), b.to_bits()))
+}
+
+pub fn div_f64(a: f64, b: f64) -> f64 {
+    f64::from_bits(F64.div(a.to_bits(), b.to_bits()))
+}
+
+/// `a / b` rounded with `rounding` instead of to nearest: `Floor` and `Ceil`
+/// are IEEE roundTowardNegative and roundTowardPositive, and `HalfUp` is
+/// roundTiesToAway. As in IEEE, an overflowing quotient rounded toward zero
+/// is the largest finite value rather than infinity.
+pub fn div_f64_rounded(a: f64, b: f64, rounding: RoundingMode) -> f64 {
+    let format = Format { rounding, ..F64 };
+    f64::from_bits(format.div(a.to_bits(), b.to_bits()))
+}
+
+/// `a * b + c` with a single rounding, as IEEE fusedMultiplyAdd.
+pub fn mul_add_f64(a: f64, b: f64, c: f64) -> f64 {
+    f64::from_bits(F64.mul_add(a.to_bits(), b.to_bits(), c.to_bits()))
+}
+
+pub fn sqrt_f64(a: f64) -> f64 {
+    f64::from_bits(F64.sqrt(a.to_bits()))
+}
+
+/// `a + b` as the x87 unit computes it with 64-bit precision control: the
+/// exact sum rounded to a 64-bit significand, then again to f64 when stored.
+/// The two roundings differ from one correct rounding when the first lands
+/// exactly halfway between two f64 values.
+pub fn add_f64_x87(a: f64, b: f64) -> f64 {
+    f64::from_bits(F64_X87.add(a.to_bits(), b.to_bits()))
+}
+
+/// `a * b` rounded twice, as in [`add_f64_x87`].
+pub fn mul_f64_x87(a: f64, b: f64) -> f64 {
+    f64::from_bits(F64_X87.mul(a.to_bits(), b.to_bits()))
+}
+
+/// `a / b` rounded twice, as in [`add_f64_x87`].
+pub fn div_f64_x87(a: f64, b: f64) -> f64 {
+    f64::from_bits(F64_X87.div(a.to_bits(), b.to_bits()))
+}
+
+pub fn add_f32(a: f32, b: f32) -> f32 {
+    f32::from_bits(F32.add(a.to_bits() as u64, b.to_bits() as u64) as u32)
+}
+
+pub fn mul_f32(a: f32, b: f32) -> f32 {
+    f32::from_bits(F32.mul(a.to_bits() as u64, b.to_bits() as u64) as u32)
+}
+
+pub fn div_f32(a: f32, b: f32) -> f32 {
+    f32::from_bits(F32.div(a.to_bits() as u64, b.to_bits() as u64) as u32)
+}
+
+/// `a * b + c` with a single rounding, as IEEE fusedMultiplyAdd.
+pub fn mul_add_f32(a: f32, b: f32, c: f32) -> f32 {
+    f32::from_bits(F32.mul_add(a.to_bits() as u64, b.to_bits() as u64, c.to_bits() as u64) as u32)
+}
+
+pub fn sqrt_f32(a: f32) -> f32 {
+    f32::from_bits(F32.sqrt(a.to_bits() as u64) as u32)
+}
@@ -0,0 +1,255 @@
+//! Build-time bit stability self-check.
+//!
+//! Rust never contracts `a * b + c` into a fused multiply-add or reorders
+//! float operations on its own, but a build can still change results
+//! silently: `-C target-feature=+fma` with a hand-written `mul_add`, an
+//! `llvm-args` fast-math flag, a different libm, or a toolchain bump that
+//! changes how an intrinsic is lowered. [`check`] recomputes a fixed mix of
+//! the crate's operations and compares every result with the bits recorded
+//! when the mix was last reviewed, so such a change shows up in this crate's
+//! own tests (or a program's startup check) instead of as a consensus
+//! failure between validators built differently.
+//!
+//! Inputs go through [`black_box`] so the compiler cannot fold a case to a
+//! constant, which would check the compiler's const evaluator rather than
+//! the code it emits.
+
+use core::hint::black_box;
+
+use crate::array_ops::{
+    dot_compensated, log_sum_exp, norm2, softmax, sum_doubles, sum_doubles_compensated,
+};
+use crate::double_ops::{
+    add_doubles, divide_doubles, mul_add_doubles, multiply_doubles, sqrt_double, sqrt_double_fast,
+};
+use crate::float_ops::{add_floats, divide_floats, multiply_floats, sqrt_float};
+use crate::quantize::{RoundingMode, quantize};
+use crate::rates::{Compounding, RateUnit, TARGET_SLOTS_PER_YEAR, rate_convert};
+use crate::stable::{log_diff, stable_variance};
+use crate::transcendental::{
+    cos_deterministic, exp_deterministic, exp_lut, ln_deterministic, ln_lut, pow_deterministic,
+    sin_deterministic,
+};
+
+/// One operation of the mix with the bits it must produce. f32 results are
+/// widened to their `u32` bits.
+#[derive(Debug, Clone, Copy)]
+pub struct GoldenCase {
+    pub name: &'static str,
+    pub expected: u64,
+    pub compute: fn() -> u64,
+}
+
+/// A case whose result no longer matches its recorded bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    pub name: &'static str,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+fn bits(value: f64) -> u64 {
+    value.to_bits()
+}
+
+fn bits32(value: f32) -> u64 {
+    value.to_bits() as u64
+}
+
+/// The operation mix. Each case stays away from NaN results, whose payload
+/// bits IEEE 754 leaves open.
+pub const GOLDEN_CASES: &[GoldenCase] = &[
+    GoldenCase {
+        name: "add_doubles(0.1, 0.2)",
+        expected: 0x3fd3333333333334,
+        compute: || bits(add_doubles(black_box(0.1), black_box(0.2))),
+    },
+    GoldenCase {
+        name: "multiply_doubles(1.1, 1.1)",
+        expected: 0x3ff35c28f5c28f5d,
+        compute: || bits(multiply_doubles(black_box(1.1), black_box(1.1))),
+    },
+    GoldenCase {
+        name: "divide_doubles(1, 3)",
+        expected: 0x3fd5555555555555,
+        compute: || bits(divide_doubles(black_box(1.0), black_box(3.0)).unwrap()),
+    },
+    // 0.1 * 10 rounds to exactly 1, so this is 0 unless the multiply and
+    // subtract are fused
+    GoldenCase {
+        name: "0.1 * 10 - 1",
+        expected: 0,
+        compute: || bits(black_box(0.1_f64) * black_box(10.0) - black_box(1.0)),
+    },
+    // 1e16 + 1 rounds back to 1e16; reassociating gives 1
+    GoldenCase {
+        name: "(1e16 + 1) - 1e16",
+        expected: 0,
+        compute: || bits((black_box(1e16_f64) + black_box(1.0)) - black_box(1e16)),
+    },
+    GoldenCase {
+        name: "mul_add_doubles(0.1, 10, -1)",
+        expected: 0x3c90000000000000,
+        compute: || {
+            bits(mul_add_doubles(
+                black_box(0.1),
+                black_box(10.0),
+                black_box(-1.0),
+            ))
+        },
+    },
+    GoldenCase {
+        name: "sqrt_double(2)",
+        expected: 0x3ff6a09e667f3bcd,
+        compute: || bits(sqrt_double(black_box(2.0))),
+    },
+    GoldenCase {
+        name: "sqrt_double_fast(2)",
+        expected: 0x3ff6a09e667f3bcd,
+        compute: || bits(sqrt_double_fast(black_box(2.0))),
+    },
+    GoldenCase {
+        name: "add_floats(0.1, 0.2)",
+        expected: 0x3e99999a,
+        compute: || bits32(add_floats(black_box(0.1), black_box(0.2))),
+    },
+    GoldenCase {
+        name: "multiply_floats(1.1, 1.1)",
+        expected: 0x3f9ae148,
+        compute: || bits32(multiply_floats(black_box(1.1), black_box(1.1))),
+    },
+    GoldenCase {
+        name: "divide_floats(1, 3)",
+        expected: 0x3eaaaaab,
+        compute: || bits32(divide_floats(black_box(1.0), black_box(3.0)).unwrap()),
+    },
+    GoldenCase {
+        name: "sqrt_float(2)",
+        expected: 0x3fb504f3,
+        compute: || bits32(sqrt_float(black_box(2.0))),
+    },
+    GoldenCase {
+        name: "exp_deterministic(1)",
+        expected: 0x4005bf0a8b14576a,
+        compute: || bits(exp_deterministic(black_box(1.0))),
+    },
+    GoldenCase {
+        name: "exp_lut(0.5)",
+        expected: 0x3ffa61298e1e069c,
+        compute: || bits(exp_lut(black_box(0.5))),
+    },
+    GoldenCase {
+        name: "ln_deterministic(10)",
+        expected: 0x40026bb1bbb55516,
+        compute: || bits(ln_deterministic(black_box(10.0))),
+    },
+    GoldenCase {
+        name: "ln_lut(3)",
+        expected: 0x3ff193ea7aad030b,
+        compute: || bits(ln_lut(black_box(3.0))),
+    },
+    GoldenCase {
+        name: "pow_deterministic(1.05, 365.25)",
+        expected: 0x418a2adeb00d56fc,
+        compute: || bits(pow_deterministic(black_box(1.05), black_box(365.25))),
+    },
+    GoldenCase {
+        name: "sin_deterministic(1e22)",
+        expected: 0xbfeb453ab76bf397,
+        compute: || bits(sin_deterministic(black_box(1e22))),
+    },
+    GoldenCase {
+        name: "cos_deterministic(355)",
+        expected: 0xbfefffffffc18e4c,
+        compute: || bits(cos_deterministic(black_box(355.0))),
+    },
+    GoldenCase {
+        name: "sum_doubles([0.1; 10])",
+        expected: 0x3fefffffffffffff,
+        compute: || bits(sum_doubles(black_box(&[0.1; 10]))),
+    },
+    GoldenCase {
+        name: "sum_doubles_compensated([0.1; 10])",
+        expected: 0x3ff0000000000000,
+        compute: || bits(sum_doubles_compensated(black_box(&[0.1; 10]))),
+    },
+    GoldenCase {
+        name: "dot_compensated",
+        expected: 0x4018000000000000,
+        compute: || {
+            let xs = black_box([0.1, 0.2, 0.3, 1e8]);
+            let ys = black_box([3.0, 7.0, 11.0, 1e-8]);
+            bits(dot_compensated(&xs, &ys).unwrap())
+        },
+    },
+    GoldenCase {
+        name: "norm2([3e200, 4e200])",
+        expected: 0x699a20df0dcd3af0,
+        compute: || bits(norm2(black_box(&[3e200, 4e200]))),
+    },
+    GoldenCase {
+        name: "softmax([1, 2, 3])[0]",
+        expected: 0x3fb70c3e5f682bda,
+        compute: || bits(softmax(black_box(&[1.0, 2.0, 3.0])).unwrap()[0]),
+    },
+    GoldenCase {
+        name: "log_sum_exp([1, 2, 3])",
+        expected: 0x400b42c6ea778b94,
+        compute: || bits(log_sum_exp(black_box(&[1.0, 2.0, 3.0])).unwrap()),
+    },
+    GoldenCase {
+        name: "stable_variance",
+        expected: 0x4036800000000000,
+        compute: || {
+            let values = black_box([1e9 + 4.0, 1e9 + 7.0, 1e9 + 13.0, 1e9 + 16.0]);
+            bits(stable_variance(&values).unwrap())
+        },
+    },
+    GoldenCase {
+        name: "log_diff(1.000001, 1)",
+        expected: 0x3eb0c6f713f33a1d,
+        compute: || bits(log_diff(black_box(1.000001), black_box(1.0)).unwrap()),
+    },
+    GoldenCase {
+        name: "quantize(2.675, 2, HalfEven)",
+        expected: 0x400570a3d70a3d71,
+        compute: || bits(quantize(black_box(2.675), 2, RoundingMode::HalfEven).unwrap()),
+    },
+    GoldenCase {
+        name: "rate_convert(0.05 per year, per slot, compounded)",
+        expected: 0x3e053fdb1a45f3b3,
+        compute: || {
+            bits(
+                rate_convert(
+                    black_box(0.05),
+                    RateUnit::PerYear,
+                    RateUnit::PerSlot,
+                    TARGET_SLOTS_PER_YEAR,
+                    Compounding::Compounded,
+                )
+                .unwrap(),
+            )
+        },
+    },
+];
+
+/// Every case of [`GOLDEN_CASES`] whose result differs from its recorded
+/// bits; empty when this build computes the mix as reviewed.
+pub fn check() -> Vec<Mismatch> {
+    check_cases(GOLDEN_CASES)
+}
+
+/// [`check`] over a caller-chosen set of cases.
+pub fn check_cases(cases: &[GoldenCase]) -> Vec<Mismatch> {
+    cases
+        .iter()
+        .filter_map(|case| {
+            let actual = (case.compute)();
+            (actual != case.expected).then_some(Mismatch {
+                name: case.name,
+                expected: case.expected,
+                actual,
+            })
+        })
+        .collect()
+}
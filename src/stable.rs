@@ -0,0 +1,101 @@
+//! Numerically stable forms of formulas whose textbook versions cancel.
+//!
+//! Each function here replaces a formula that subtracts two nearly equal
+//! quantities, which keeps only their rounding errors:
+//!
+//! - variance as `E[x²] - E[x]²`, which for prices around 10^9 can come out
+//!   negative;
+//! - the quadratic formula's `-b + sqrt(b² - 4ac)` when `b² ≫ 4ac`;
+//! - `ln(a) - ln(b)` for `a ≈ b`;
+//! - `|x|² - 2 x·y + |y|²` for nearby vectors.
+
+use crate::array_ops::{check_finite, dot_compensated};
+use crate::error::FloatError;
+use crate::stats::RunningStats;
+use crate::transcendental::{ln_deterministic, ln_one_plus};
+
+/// Population variance by Welford's update, which never subtracts two large
+/// sums.
+///
+/// Fails with `OutOfRange` for an empty slice or infinite values,
+/// `NaNOperand` for NaN and `Overflow` if the variance is not finite.
+pub fn stable_variance(values: &[f64]) -> Result<f64, FloatError> {
+    let mut stats = RunningStats::new();
+    stats.extend(values.iter().copied())?;
+    stats.variance().ok_or(FloatError::OutOfRange)
+}
+
+/// The real roots of `a x² + b x + c`, smaller first.
+///
+/// Computes the root whose terms add, `q = -(b + sign(b) sqrt(b² - 4ac)) / 2`,
+/// and gets the other as `c / q` instead of subtracting. The discriminant
+/// itself is still rounded, so a double root may come out as two close ones.
+///
+/// Fails with `NaNOperand` for NaN coefficients, `OutOfRange` for infinite
+/// ones, `a == 0` or a negative discriminant, and `Overflow` if the
+/// discriminant or a root is not finite.
+pub fn quadratic_roots(a: f64, b: f64, c: f64) -> Result<(f64, f64), FloatError> {
+    check_finite(&[a, b, c])?;
+    if a == 0.0 {
+        return Err(FloatError::OutOfRange);
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if !discriminant.is_finite() {
+        return Err(FloatError::Overflow);
+    }
+    if discriminant < 0.0 {
+        return Err(FloatError::OutOfRange);
+    }
+    let q = -0.5 * (b + discriminant.sqrt().copysign(b));
+    if q == 0.0 {
+        // b and the discriminant are zero, so c is too
+        return Ok((0.0, 0.0));
+    }
+    let (first, second) = (q / a, c / q);
+    if !first.is_finite() || !second.is_finite() {
+        return Err(FloatError::Overflow);
+    }
+    Ok((first.min(second), first.max(second)))
+}
+
+/// `ln(a) - ln(b)` for positive `a` and `b`.
+///
+/// For `a` within a factor of two of `b` this is `ln(1 + (a - b) / b)`: the
+/// subtraction is exact there, and the logarithm does not pass through
+/// `1 + x`. Fails with `NaNOperand` for NaN and `OutOfRange` unless both are
+/// positive and finite.
+pub fn log_diff(a: f64, b: f64) -> Result<f64, FloatError> {
+    check_finite(&[a, b])?;
+    if a <= 0.0 || b <= 0.0 {
+        return Err(FloatError::OutOfRange);
+    }
+    if a >= 0.5 * b && a <= 2.0 * b {
+        return Ok(ln_one_plus((a - b) / b));
+    }
+    let ratio = a / b;
+    if ratio.is_normal() {
+        Ok(ln_deterministic(ratio))
+    } else {
+        // The logarithms are far apart, so their difference does not cancel
+        Ok(ln_deterministic(a) - ln_deterministic(b))
+    }
+}
+
+/// `|xs - ys|²`, squaring the differences instead of expanding the square,
+/// and summing them as a compensated dot product.
+///
+/// Fails with `OutOfRange` for slices of different lengths or infinite
+/// values, `NaNOperand` for NaN and `Overflow` if the result is not finite.
+pub fn norm_sq_diff(xs: &[f64], ys: &[f64]) -> Result<f64, FloatError> {
+    if xs.len() != ys.len() {
+        return Err(FloatError::OutOfRange);
+    }
+    check_finite(xs)?;
+    check_finite(ys)?;
+    let differences: Vec<f64> = xs.iter().zip(ys).map(|(x, y)| x - y).collect();
+    let result = dot_compensated(&differences, &differences)?;
+    if !result.is_finite() {
+        return Err(FloatError::Overflow);
+    }
+    Ok(result)
+}
@@ -0,0 +1,42 @@
+//! Exchange-rate math for stake pools, matching the SPL stake-pool program.
+//!
+//! Both directions round down, so neither a depositor nor a withdrawer can take
+//! more than their proportional share of the pool. Unlike [`crate::shares`]
+//! there is no virtual offset: an empty pool (no lamports or no pool tokens)
+//! mints 1:1, exactly as `StakePool::calc_pool_tokens_for_deposit` does.
+
+use crate::error::FloatError;
+use crate::quantize::RoundingMode;
+use crate::shares::{FirstDeposit, SharePool};
+
+/// Pool tokens minted for depositing `lamports`, rounded down.
+pub fn pool_tokens_for_deposit(
+    lamports: u64,
+    total_lamports: u64,
+    pool_supply: u64,
+) -> Result<u64, FloatError> {
+    pool(total_lamports, pool_supply).shares_for_assets(lamports, RoundingMode::Floor)
+}
+
+/// Lamports paid out for burning `pool_tokens`, rounded down.
+///
+/// Burning fewer tokens than one lamport is worth pays out zero, as in SPL;
+/// burning more than the supply is `OutOfRange`.
+pub fn lamports_for_pool_tokens(
+    pool_tokens: u64,
+    total_lamports: u64,
+    pool_supply: u64,
+) -> Result<u64, FloatError> {
+    if pool_tokens > pool_supply {
+        return Err(FloatError::OutOfRange);
+    }
+    pool(total_lamports, pool_supply).assets_for_shares(pool_tokens, RoundingMode::Floor)
+}
+
+fn pool(total_lamports: u64, pool_supply: u64) -> SharePool {
+    SharePool {
+        total_assets: total_lamports,
+        total_shares: pool_supply,
+        first_deposit: FirstDeposit::OneToOne,
+    }
+}
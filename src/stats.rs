@@ -0,0 +1,556 @@
+//! Order statistics and running aggregates over f64 data.
+
+use std::cmp::Ordering;
+
+use solana_program::program_error::ProgramError;
+
+use crate::accumulate::neumaier_add;
+use crate::array_ops::check_finite;
+use crate::error::FloatError;
+
+// Ranges this small are cheaper to sort than to keep partitioning.
+const SELECT_SORT_THRESHOLD: usize = 16;
+
+/// Median of `values`, reordering the slice in place.
+///
+/// Uses quickselect with a median-of-three pivot; no randomness is involved, so
+/// the same input always performs the same comparisons. Even-length inputs
+/// return the midpoint of the two middle values. Fails with `OutOfRange` for an
+/// empty slice and rejects NaN/infinite inputs like `sort_finite`.
+pub fn median_inplace(values: &mut [f64]) -> Result<f64, FloatError> {
+    if values.is_empty() {
+        return Err(FloatError::OutOfRange);
+    }
+    check_finite(values)?;
+
+    let middle = values.len() / 2;
+    let upper = select_nth(values, middle);
+    if values.len() % 2 == 1 {
+        return Ok(upper);
+    }
+    // After selection everything left of `middle` is <= upper, so the lower
+    // middle value is the largest of them.
+    let lower = values[..middle]
+        .iter()
+        .copied()
+        .max_by(f64::total_cmp)
+        .unwrap_or(upper);
+    Ok(midpoint(lower, upper))
+}
+
+/// How `quantile` picks a value when the requested rank falls between two samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// The sample just below the rank.
+    Lower,
+    /// The sample just above the rank.
+    Higher,
+    /// The closer of the two samples, ties to the even rank.
+    Nearest,
+    /// Linear interpolation between the two samples.
+    Linear,
+}
+
+/// The `q`-quantile of `values` (`0.0 <= q <= 1.0`), leaving the input untouched.
+///
+/// Ranks are computed as `q * (len - 1)`, matching NumPy's default definition,
+/// so off-chain analytics and on-chain checks agree on the same samples.
+pub fn quantile(values: &[f64], q: f64, interpolation: Interpolation) -> Result<f64, FloatError> {
+    let mut working = values.to_vec();
+    quantile_inplace(&mut working, q, interpolation)
+}
+
+/// Like `quantile`, but reorders `values` instead of allocating a copy.
+pub fn quantile_inplace(
+    values: &mut [f64],
+    q: f64,
+    interpolation: Interpolation,
+) -> Result<f64, FloatError> {
+    if q.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    if !(0.0..=1.0).contains(&q) || values.is_empty() {
+        return Err(FloatError::OutOfRange);
+    }
+    check_finite(values)?;
+
+    let rank = q * (values.len() - 1) as f64;
+    let lower_index = rank.floor() as usize;
+    let fraction = rank - rank.floor();
+
+    let lower = select_nth(values, lower_index);
+    if fraction == 0.0 {
+        return Ok(lower);
+    }
+    // Everything right of the lower rank is >= it, so the next sample is their minimum.
+    let upper = values[lower_index + 1..]
+        .iter()
+        .copied()
+        .min_by(f64::total_cmp)
+        .unwrap_or(lower);
+
+    Ok(match interpolation {
+        Interpolation::Lower => lower,
+        Interpolation::Higher => upper,
+        Interpolation::Nearest => {
+            if rank.round_ties_even() as usize == lower_index {
+                lower
+            } else {
+                upper
+            }
+        }
+        Interpolation::Linear => lerp(lower, upper, fraction),
+    })
+}
+
+/// Fixed-edge histogram with deterministic bucket assignment.
+///
+/// Bucket `i` covers `[edges[i], edges[i + 1])`; the last bucket also includes its
+/// upper edge. Edges and values are compared with `f64::total_cmp` after folding
+/// `-0.0` into `+0.0`, so a value sitting exactly on an edge always lands in the
+/// same bucket on every validator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    edges: Vec<f64>,
+    counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// Builds a histogram from at least two finite, strictly increasing edges.
+    pub fn new(edges: Vec<f64>) -> Result<Self, FloatError> {
+        if edges.len() < 2 {
+            return Err(FloatError::OutOfRange);
+        }
+        check_finite(&edges)?;
+        let edges: Vec<f64> = edges.into_iter().map(fold_negative_zero).collect();
+        if edges
+            .windows(2)
+            .any(|pair| pair[0].total_cmp(&pair[1]) != Ordering::Less)
+        {
+            return Err(FloatError::OutOfRange);
+        }
+        let counts = vec![0; edges.len() - 1];
+        Ok(Self { edges, counts })
+    }
+
+    /// Index of the bucket containing `value`, or `None` if it is NaN or outside the edges.
+    pub fn bucket_of(&self, value: f64) -> Option<usize> {
+        if value.is_nan() {
+            return None;
+        }
+        let value = fold_negative_zero(value);
+        let at_or_below = self
+            .edges
+            .partition_point(|edge| edge.total_cmp(&value) != Ordering::Greater);
+        match at_or_below {
+            0 => None,
+            n if n == self.edges.len() => {
+                (value.total_cmp(&self.edges[n - 1]) == Ordering::Equal).then_some(n - 2)
+            }
+            n => Some(n - 1),
+        }
+    }
+
+    /// Counts `value` into its bucket and returns the bucket index.
+    pub fn record(&mut self, value: f64) -> Result<usize, FloatError> {
+        if value.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        let bucket = self.bucket_of(value).ok_or(FloatError::OutOfRange)?;
+        self.counts[bucket] = self.counts[bucket]
+            .checked_add(1)
+            .ok_or(FloatError::Overflow)?;
+        Ok(bucket)
+    }
+
+    pub fn edges(&self) -> &[f64] {
+        &self.edges
+    }
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Total number of recorded values.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+/// Fixed-window moving average backed by a ring buffer.
+///
+/// The running sum is maintained with Neumaier compensation for both the sample
+/// entering and the sample leaving the window, and is re-summed from the stored
+/// samples every time the ring wraps. A long-lived average therefore never drifts
+/// away from the mean of the samples it actually holds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovingAverage {
+    samples: Vec<f64>,
+    len: usize,
+    head: usize,
+    sum: f64,
+    compensation: f64,
+}
+
+impl MovingAverage {
+    /// Size of the fixed header in the account layout:
+    /// `window: u32 | len: u32 | head: u32 | sum: f64 | compensation: f64`.
+    pub const HEADER_LEN: usize = 4 + 4 + 4 + 8 + 8;
+
+    pub fn new(window: usize) -> Result<Self, FloatError> {
+        if window == 0 || u32::try_from(window).is_err() {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(Self {
+            samples: vec![0.0; window],
+            len: 0,
+            head: 0,
+            sum: 0.0,
+            compensation: 0.0,
+        })
+    }
+
+    /// Adds a sample, evicting the oldest one once the window is full.
+    pub fn push(&mut self, value: f64) -> Result<(), FloatError> {
+        if value.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if value.is_infinite() {
+            return Err(FloatError::OutOfRange);
+        }
+        if self.len == self.samples.len() {
+            let evicted = self.samples[self.head];
+            self.accumulate(-evicted);
+        } else {
+            self.len += 1;
+        }
+        self.samples[self.head] = value;
+        self.head = (self.head + 1) % self.samples.len();
+        if self.head == 0 {
+            self.resum();
+        } else {
+            self.accumulate(value);
+        }
+        Ok(())
+    }
+
+    /// Mean of the samples currently in the window, or `None` before the first push.
+    pub fn value(&self) -> Option<f64> {
+        if self.len == 0 {
+            return None;
+        }
+        Some((self.sum + self.compensation) / self.len as f64)
+    }
+
+    pub fn window(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Account size needed to store a moving average over `window` samples.
+    pub fn packed_len(window: usize) -> usize {
+        Self::HEADER_LEN + window * 8
+    }
+
+    /// Serializes into `dst`, which must be exactly `packed_len(window)` bytes.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::packed_len(self.window()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (header, body) = dst.split_at_mut(Self::HEADER_LEN);
+        header[0..4].copy_from_slice(&(self.window() as u32).to_le_bytes());
+        header[4..8].copy_from_slice(&(self.len as u32).to_le_bytes());
+        header[8..12].copy_from_slice(&(self.head as u32).to_le_bytes());
+        header[12..20].copy_from_slice(&self.sum.to_le_bytes());
+        header[20..28].copy_from_slice(&self.compensation.to_le_bytes());
+        for (chunk, sample) in body.chunks_exact_mut(8).zip(&self.samples) {
+            chunk.copy_from_slice(&sample.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    /// Deserializes and validates a moving average written by `pack`.
+    pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::HEADER_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let read_u32 = |offset: usize| {
+            u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize
+        };
+        let read_f64 =
+            |offset: usize| f64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+
+        let window = read_u32(0);
+        let len = read_u32(4);
+        let head = read_u32(8);
+        let sum = read_f64(12);
+        let compensation = read_f64(20);
+        if window == 0
+            || src.len() != Self::packed_len(window)
+            || len > window
+            || head >= window
+            || !sum.is_finite()
+            || !compensation.is_finite()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let samples: Vec<f64> = src[Self::HEADER_LEN..]
+            .chunks_exact(8)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        if samples.iter().any(|sample| !sample.is_finite()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            samples,
+            len,
+            head,
+            sum,
+            compensation,
+        })
+    }
+
+    // Amortized O(1): runs once per `window` pushes.
+    fn resum(&mut self) {
+        self.sum = 0.0;
+        self.compensation = 0.0;
+        for index in 0..self.len {
+            self.accumulate(self.samples[index]);
+        }
+    }
+
+    fn accumulate(&mut self, value: f64) {
+        (self.sum, self.compensation) = neumaier_add(self.sum, self.compensation, value);
+    }
+}
+
+/// Count, sum, mean, variance and extremes of a stream, in constant space.
+///
+/// Built for data too large to copy out of an account: values are pushed one
+/// at a time (see `AccountOperands::iter`), and nothing is allocated. The sum
+/// uses Neumaier compensation and the mean and variance use Welford's update,
+/// so a long stream of similar values does not lose its low digits.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RunningStats {
+    count: u64,
+    sum: f64,
+    compensation: f64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl RunningStats {
+    /// Size of the serialized form:
+    /// `count: u64 | sum | compensation | mean | m2 | min | max` (f64s).
+    pub const PACKED_LEN: usize = 8 + 6 * 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value. Rejects NaN with `NaNOperand` and infinities with
+    /// `OutOfRange`; fails with `Overflow`, leaving the state unchanged, if an
+    /// aggregate would no longer be finite.
+    pub fn push(&mut self, value: f64) -> Result<(), FloatError> {
+        if value.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if value.is_infinite() {
+            return Err(FloatError::OutOfRange);
+        }
+        let mut next = *self;
+        next.count += 1;
+        let (sum, compensation) = neumaier_add(self.sum, self.compensation, value);
+        next.sum = sum;
+        next.compensation = compensation;
+        let delta = value - self.mean;
+        next.mean = self.mean + delta / next.count as f64;
+        next.m2 = self.m2 + delta * (value - next.mean);
+        if [sum, compensation, next.mean, next.m2]
+            .iter()
+            .any(|field| !field.is_finite())
+        {
+            return Err(FloatError::Overflow);
+        }
+        if self.count == 0 {
+            next.min = value;
+            next.max = value;
+        } else {
+            next.min = self.min.min(value);
+            next.max = self.max.max(value);
+        }
+        *self = next;
+        Ok(())
+    }
+
+    /// Pushes every value, stopping at the first error.
+    pub fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) -> Result<(), FloatError> {
+        values.into_iter().try_for_each(|value| self.push(value))
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum + self.compensation
+    }
+
+    /// `None` before the first push, as for the remaining accessors.
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    /// Population variance.
+    pub fn variance(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.m2 / self.count as f64)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    pub fn pack(&self) -> [u8; Self::PACKED_LEN] {
+        let mut dst = [0u8; Self::PACKED_LEN];
+        dst[0..8].copy_from_slice(&self.count.to_le_bytes());
+        let fields = [
+            self.sum,
+            self.compensation,
+            self.mean,
+            self.m2,
+            self.min,
+            self.max,
+        ];
+        for (chunk, field) in dst[8..].chunks_exact_mut(8).zip(fields) {
+            chunk.copy_from_slice(&field.to_le_bytes());
+        }
+        dst
+    }
+
+    /// Deserializes and validates state written by `pack`, e.g. the return
+    /// data of the `StatsAccount` instruction.
+    pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::PACKED_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let read_f64 =
+            |offset: usize| f64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        let stats = Self {
+            count: u64::from_le_bytes(src[0..8].try_into().unwrap()),
+            sum: read_f64(8),
+            compensation: read_f64(16),
+            mean: read_f64(24),
+            m2: read_f64(32),
+            min: read_f64(40),
+            max: read_f64(48),
+        };
+        let fields = [
+            stats.sum,
+            stats.compensation,
+            stats.mean,
+            stats.m2,
+            stats.min,
+            stats.max,
+        ];
+        if fields.iter().any(|field| !field.is_finite()) || stats.m2 < 0.0 || stats.min > stats.max
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(stats)
+    }
+}
+
+/// Moves the `k`-th smallest value (under `f64::total_cmp`) to index `k` and
+/// returns it. Everything before `k` compares <= it, everything after >= it.
+pub(crate) fn select_nth(values: &mut [f64], k: usize) -> f64 {
+    let mut lo = 0;
+    let mut hi = values.len();
+    // Bound the partitioning depth; adversarial inputs fall back to sorting the
+    // remaining range, which keeps the worst case at O(n log n).
+    let mut depth_budget = 2 * (usize::BITS - values.len().leading_zeros());
+
+    loop {
+        if hi - lo <= SELECT_SORT_THRESHOLD || depth_budget == 0 {
+            values[lo..hi].sort_unstable_by(f64::total_cmp);
+            return values[k];
+        }
+        depth_budget -= 1;
+
+        let last = hi - 1;
+        let pivot_index = median_of_three(values, lo, lo + (hi - lo) / 2, last);
+        values.swap(pivot_index, last);
+        let pivot = values[last];
+
+        let mut store = lo;
+        for i in lo..last {
+            if values[i].total_cmp(&pivot) == Ordering::Less {
+                values.swap(i, store);
+                store += 1;
+            }
+        }
+        values.swap(store, last);
+
+        match k.cmp(&store) {
+            Ordering::Equal => return values[store],
+            Ordering::Less => hi = store,
+            Ordering::Greater => lo = store + 1,
+        }
+    }
+}
+
+fn median_of_three(values: &[f64], a: usize, b: usize, c: usize) -> usize {
+    let less = |x: usize, y: usize| values[x].total_cmp(&values[y]) == Ordering::Less;
+    if less(a, b) {
+        if less(b, c) {
+            b
+        } else if less(a, c) {
+            c
+        } else {
+            a
+        }
+    } else if less(a, c) {
+        a
+    } else if less(b, c) {
+        c
+    } else {
+        b
+    }
+}
+
+// Adding +0.0 maps -0.0 to +0.0 and leaves every other value unchanged.
+fn fold_negative_zero(value: f64) -> f64 {
+    value + 0.0
+}
+
+// Midpoint of two finite values without overflowing when their sum would.
+fn midpoint(a: f64, b: f64) -> f64 {
+    let sum = a + b;
+    if sum.is_finite() {
+        sum / 2.0
+    } else {
+        a / 2.0 + b / 2.0
+    }
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    let span = b - a;
+    if span.is_finite() {
+        a + span * t
+    } else {
+        a * (1.0 - t) + b * t
+    }
+}
@@ -0,0 +1,109 @@
+//! f64 values that carry a worst-case rounding error bound.
+//!
+//! Each operation adds the error it inherits from its operands (first-order
+//! propagation) to its own rounding error of at most half an ulp, `|result| * u`
+//! with `u = 2^-53`. The bound is computed in f64 itself, so it is a sound
+//! estimate for well-conditioned inputs rather than a rigorous interval.
+
+use crate::double_ops::BinaryOp;
+use crate::error::FloatError;
+
+/// Unit roundoff for round-to-nearest f64 arithmetic.
+pub const UNIT_ROUNDOFF: f64 = f64::EPSILON / 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackedF64 {
+    value: f64,
+    error_bound: f64,
+    ops: u32,
+}
+
+impl TrackedF64 {
+    /// Wraps an input that is known exactly.
+    pub fn exact(value: f64) -> Result<Self, FloatError> {
+        Self::with_error(value, 0.0)
+    }
+
+    /// Wraps an input that is already off by at most `error_bound`.
+    pub fn with_error(value: f64, error_bound: f64) -> Result<Self, FloatError> {
+        if value.is_nan() || error_bound.is_nan() {
+            return Err(FloatError::NaNOperand);
+        }
+        if !value.is_finite() || !error_bound.is_finite() || error_bound < 0.0 {
+            return Err(FloatError::OutOfRange);
+        }
+        Ok(Self {
+            value,
+            error_bound,
+            ops: 0,
+        })
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Upper bound on `|value - exact result|`.
+    pub fn error_bound(&self) -> f64 {
+        self.error_bound
+    }
+
+    /// Number of rounded operations that produced this value.
+    pub fn ops(&self) -> u32 {
+        self.ops
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Result<Self, FloatError> {
+        let value = self.value + rhs.value;
+        self.finish(rhs, value, self.error_bound + rhs.error_bound)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, FloatError> {
+        let value = self.value - rhs.value;
+        self.finish(rhs, value, self.error_bound + rhs.error_bound)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Result<Self, FloatError> {
+        let value = self.value * rhs.value;
+        let inherited = self.value.abs() * rhs.error_bound
+            + rhs.value.abs() * self.error_bound
+            + self.error_bound * rhs.error_bound;
+        self.finish(rhs, value, inherited)
+    }
+
+    /// Divides, failing with `DivisionByZero` when the divisor's error interval
+    /// contains zero (the quotient would be unbounded).
+    pub fn checked_div(self, rhs: Self) -> Result<Self, FloatError> {
+        let divisor = rhs.value.abs();
+        if divisor <= rhs.error_bound {
+            return Err(FloatError::DivisionByZero);
+        }
+        let value = self.value / rhs.value;
+        let inherited = (self.value.abs() * rhs.error_bound + divisor * self.error_bound)
+            / (divisor * (divisor - rhs.error_bound));
+        self.finish(rhs, value, inherited)
+    }
+
+    pub fn apply(self, op: BinaryOp, rhs: Self) -> Result<Self, FloatError> {
+        match op {
+            BinaryOp::Add => self.checked_add(rhs),
+            BinaryOp::Multiply => self.checked_mul(rhs),
+            BinaryOp::Divide => self.checked_div(rhs),
+        }
+    }
+
+    fn finish(self, rhs: Self, value: f64, inherited: f64) -> Result<Self, FloatError> {
+        if !value.is_finite() {
+            return Err(FloatError::Overflow);
+        }
+        let error_bound = inherited + value.abs() * UNIT_ROUNDOFF;
+        if !error_bound.is_finite() {
+            return Err(FloatError::Overflow);
+        }
+        Ok(Self {
+            value,
+            error_bound,
+            ops: self.ops.saturating_add(rhs.ops).saturating_add(1),
+        })
+    }
+}
@@ -0,0 +1,807 @@
+//! Deterministic `exp`, `ln`, `pow` and trigonometry.
+//!
+//! `f64::exp`, `f64::ln`, `f64::powf`, `f64::sin` and the like call into
+//! the platform's libm, which differs between targets and versions, so an
+//! off-chain simulation can disagree with the program in the last bits. Everything here is plain `+ - * /` on f64,
+//! which is correctly rounded everywhere, so results are bit-identical across
+//! SBF, native and wasm.
+//!
+//! Two implementations are provided, chosen per call site:
+//!
+//! - [`exp_deterministic`] / [`ln_deterministic`] reduce the argument and
+//!   evaluate a long series, staying within [`EXP_MAX_ULPS`] / [`LN_MAX_ULPS`]
+//!   of the correctly rounded result.
+//! - [`exp_lut`] / [`ln_lut`] look up one of 64 precomputed values compiled
+//!   into the binary and only need a short polynomial for the remainder,
+//!   which costs far fewer soft-float operations on SBF in exchange for the
+//!   looser [`EXP_LUT_MAX_ULPS`] / [`LN_LUT_MAX_ULPS`].
+//!
+//! [`log2_deterministic`] and [`log10_deterministic`] add the exponent of
+//! the argument to the logarithm of its significand, staying within
+//! [`LOG2_MAX_ULPS`] / [`LOG10_MAX_ULPS`]; both are exact for powers of their
+//! base.
+//!
+//! [`pow_deterministic`] replaces `powf`: the integer part of the exponent
+//! is applied by exponentiation by squaring in double-double arithmetic, and
+//! only the fractional part goes through `exp` and `ln`, staying within
+//! [`POW_MAX_ULPS`].
+//!
+//! [`sin_deterministic`], [`cos_deterministic`] and [`tan_deterministic`]
+//! reduce the argument modulo `pi/2` (with a 1280-bit table of `2/pi` for
+//! arguments from about `1.6e6` up, so even huge arguments reduce exactly)
+//! and evaluate Taylor series on `[-pi/4, pi/4]`.
+//!
+//! The bounds hold for normal results; subnormal outputs of `exp` and `pow`
+//! lose precision with the format itself.
+
+use std::f64::consts::{FRAC_2_PI, FRAC_PI_2, LOG2_E, LOG10_E, SQRT_2};
+
+use crate::array_ops::two_product;
+use crate::u256::U256;
+
+/// Largest distance, in units in the last place, from the correctly rounded
+/// result.
+pub const EXP_MAX_ULPS: u64 = 1;
+pub const LN_MAX_ULPS: u64 = 1;
+pub const EXP_LUT_MAX_ULPS: u64 = 2;
+pub const LN_LUT_MAX_ULPS: u64 = 3;
+pub const LOG2_MAX_ULPS: u64 = 2;
+pub const LOG10_MAX_ULPS: u64 = 2;
+pub const POW_MAX_ULPS: u64 = 3;
+pub const SIN_MAX_ULPS: u64 = 1;
+pub const COS_MAX_ULPS: u64 = 1;
+pub const TAN_MAX_ULPS: u64 = 2;
+
+// exp(x) overflows above ln(f64::MAX) and is below half the smallest subnormal
+// under ln(2^-1075).
+const EXP_OVERFLOW: f64 = 709.782712893384;
+const EXP_UNDERFLOW: f64 = -745.1332191019412;
+
+// ln(2) split so that `k * LN2_HI` is exact for any exponent k.
+const LN2_HI: f64 = 0.6931471803691238;
+const LN2_LO: f64 = 1.9082149292705877e-10;
+
+// log10(2) split so that `k * LOG10_2_HI` is exact for any exponent k.
+const LOG10_2_HI: f64 = 0.30102999566361177;
+const LOG10_2_LO: f64 = 3.694239077158931e-13;
+
+// The largest power of ten an f64 holds exactly
+const MAX_EXACT_POWER_OF_TEN: i32 = 22;
+
+// 1/k! for k = 2..=17, highest first
+const EXP_COEFFICIENTS: [f64; 16] = [
+    2.8114572543455206e-15,
+    4.779477332387385e-14,
+    7.647163731819816e-13,
+    1.1470745597729725e-11,
+    1.6059043836821613e-10,
+    2.08767569878681e-09,
+    2.505210838544172e-08,
+    2.755731922398589e-07,
+    2.7557319223985893e-06,
+    2.48015873015873e-05,
+    0.0001984126984126984,
+    0.001388888888888889,
+    0.008333333333333333,
+    0.041666666666666664,
+    0.16666666666666666,
+    0.5,
+];
+
+// 2/(2k + 1) for k = 1..=11, highest first
+const LN_COEFFICIENTS: [f64; 11] = [
+    0.08695652173913043,
+    0.09523809523809523,
+    0.10526315789473684,
+    0.11764705882352941,
+    0.13333333333333333,
+    0.15384615384615385,
+    0.18181818181818182,
+    0.2222222222222222,
+    0.2857142857142857,
+    0.4,
+    0.6666666666666666,
+];
+
+// (-1)^k / (2k + 1)! for k = 2..=10, highest first; the k = 1 term is
+// applied separately
+const SIN_COEFFICIENTS: [f64; 9] = [
+    1.9572941063391263e-20,
+    -8.22063524662433e-18,
+    2.8114572543455206e-15,
+    -7.647163731819816e-13,
+    1.6059043836821613e-10,
+    -2.505210838544172e-08,
+    2.7557319223985893e-06,
+    -0.0001984126984126984,
+    0.008333333333333333,
+];
+const SIN_THIRD_ORDER: f64 = -0.16666666666666666;
+
+// (-1)^k / (2k)! for k = 2..=10, highest first
+const COS_COEFFICIENTS: [f64; 9] = [
+    4.110317623312165e-19,
+    -1.5619206968586225e-16,
+    4.779477332387385e-14,
+    -1.1470745597729725e-11,
+    2.08767569878681e-09,
+    -2.755731922398589e-07,
+    2.48015873015873e-05,
+    -0.001388888888888889,
+    0.041666666666666664,
+];
+
+// pi/2 split into 33-bit parts, each with the rounded remainder after it, so
+// that `n * PIO2_k` is exact for the quadrants of the medium range
+// (Cody and Waite, as in fdlibm's __ieee754_rem_pio2)
+const PIO2_1: f64 = 1.5707963267341256;
+const PIO2_1T: f64 = 6.077100506506192e-11;
+const PIO2_2: f64 = 6.077100506303966e-11;
+const PIO2_2T: f64 = 2.0222662487959506e-21;
+const PIO2_3: f64 = 2.0222662487111665e-21;
+const PIO2_3T: f64 = 8.4784276603689e-32;
+// pi/2 - FRAC_PI_2
+const PIO2_LO: f64 = 6.123233995736766e-17;
+// Past 2^20 * pi/2 the quadrant no longer fits the Cody-Waite parts
+const MEDIUM_REDUCTION_LIMIT: f64 = 1_048_576.0 * FRAC_PI_2;
+
+// The first 1280 bits of 2/pi after the binary point, enough to reduce any
+// f64 exactly
+const TWO_OVER_PI: [u64; 20] = [
+    0xa2f9836e4e441529,
+    0xfc2757d1f534ddc0,
+    0xdb6295993c439041,
+    0xfe5163abdebbc561,
+    0xb7246e3a424dd2e0,
+    0x06492eea09d1921c,
+    0xfe1deb1cb129a73e,
+    0xe88235f52ebb4484,
+    0xe99c7026b45f7e41,
+    0x3991d639835339f4,
+    0x9c845f8bbdf9283b,
+    0x1ff897ffde05980f,
+    0xef2f118b5a0a6d1f,
+    0x6d367ecf27cb09b7,
+    0x4f463f669e5fea2d,
+    0x7527bac7ebe5f17b,
+    0x3d0739f78a5292ea,
+    0x6bfb5fb11f8d5d08,
+    0x56033046fc7b6bab,
+    0xf0cfbc209af4361d,
+];
+
+const LUT_BITS: u32 = 6;
+const LUT_SIZE: i32 = 1 << LUT_BITS;
+
+// 2^(j/64), correctly rounded
+const EXP2_TABLE: [u64; 64] = [
+    0x3ff0000000000000,
+    0x3ff02c9a3e778061,
+    0x3ff059b0d3158574,
+    0x3ff0874518759bc8,
+    0x3ff0b5586cf9890f,
+    0x3ff0e3ec32d3d1a2,
+    0x3ff11301d0125b51,
+    0x3ff1429aaea92de0,
+    0x3ff172b83c7d517b,
+    0x3ff1a35beb6fcb75,
+    0x3ff1d4873168b9aa,
+    0x3ff2063b88628cd6,
+    0x3ff2387a6e756238,
+    0x3ff26b4565e27cdd,
+    0x3ff29e9df51fdee1,
+    0x3ff2d285a6e4030b,
+    0x3ff306fe0a31b715,
+    0x3ff33c08b26416ff,
+    0x3ff371a7373aa9cb,
+    0x3ff3a7db34e59ff7,
+    0x3ff3dea64c123422,
+    0x3ff4160a21f72e2a,
+    0x3ff44e086061892d,
+    0x3ff486a2b5c13cd0,
+    0x3ff4bfdad5362a27,
+    0x3ff4f9b2769d2ca7,
+    0x3ff5342b569d4f82,
+    0x3ff56f4736b527da,
+    0x3ff5ab07dd485429,
+    0x3ff5e76f15ad2148,
+    0x3ff6247eb03a5585,
+    0x3ff6623882552225,
+    0x3ff6a09e667f3bcd,
+    0x3ff6dfb23c651a2f,
+    0x3ff71f75e8ec5f74,
+    0x3ff75feb564267c9,
+    0x3ff7a11473eb0187,
+    0x3ff7e2f336cf4e62,
+    0x3ff82589994cce13,
+    0x3ff868d99b4492ed,
+    0x3ff8ace5422aa0db,
+    0x3ff8f1ae99157736,
+    0x3ff93737b0cdc5e5,
+    0x3ff97d829fde4e50,
+    0x3ff9c49182a3f090,
+    0x3ffa0c667b5de565,
+    0x3ffa5503b23e255d,
+    0x3ffa9e6b5579fdbf,
+    0x3ffae89f995ad3ad,
+    0x3ffb33a2b84f15fb,
+    0x3ffb7f76f2fb5e47,
+    0x3ffbcc1e904bc1d2,
+    0x3ffc199bdd85529c,
+    0x3ffc67f12e57d14b,
+    0x3ffcb720dcef9069,
+    0x3ffd072d4a07897c,
+    0x3ffd5818dcfba487,
+    0x3ffda9e603db3285,
+    0x3ffdfc97337b9b5f,
+    0x3ffe502ee78b3ff6,
+    0x3ffea4afa2a490da,
+    0x3ffefa1bee615a27,
+    0x3fff50765b6e4540,
+    0x3fffa7c1819e90d8,
+];
+
+// ln(c_j) for c_j = 1 + j/64 + 1/128, halved for j >= 32 so that c_j stays
+// within [0.75, 1.5); correctly rounded
+const LN_TABLE: [u64; 64] = [
+    0x3f7fe02a6b106789,
+    0x3f97b91b07d5b11b,
+    0x3fa39e87b9febd60,
+    0x3fab42dd711971bf,
+    0x3fb16536eea37ae1,
+    0x3fb51b073f06183f,
+    0x3fb8c345d6319b21,
+    0x3fbc5e548f5bc743,
+    0x3fbfec9131dbeabb,
+    0x3fc1b72ad52f67a0,
+    0x3fc371fc201e8f74,
+    0x3fc526e5e3a1b438,
+    0x3fc6d60fe719d21d,
+    0x3fc87fa06520c911,
+    0x3fca23bc1fe2b563,
+    0x3fcbc286742d8cd6,
+    0x3fcd5c216b4fbb91,
+    0x3fcef0adcbdc5936,
+    0x3fd0402594b4d041,
+    0x3fd1058bf9ae4ad5,
+    0x3fd1c898c16999fb,
+    0x3fd2895a13de86a3,
+    0x3fd347dd9a987d55,
+    0x3fd404308686a7e4,
+    0x3fd4be5f957778a1,
+    0x3fd5767717455a6c,
+    0x3fd62c82f2b9c795,
+    0x3fd6e08eaa2ba1e4,
+    0x3fd792a55fdd47a2,
+    0x3fd842d1da1e8b17,
+    0x3fd8f11e873662c7,
+    0x3fd99d958117e08b,
+    0xbfd214456d0eb8d4,
+    0xbfd16b5ccbacfb73,
+    0xbfd0c42d676162e3,
+    0xbfd01eae5626c691,
+    0xbfcef5ade4dcffe6,
+    0xbfcdb13db0d48940,
+    0xbfcc6ffbc6f00f71,
+    0xbfcb31d8575bce3d,
+    0xbfc9f6c407089664,
+    0xbfc8beafeb38fe8c,
+    0xbfc7898d85444c73,
+    0xbfc6574ebe8c133a,
+    0xbfc527e5e4a1b58d,
+    0xbfc3fb45a59928cc,
+    0xbfc2d1610c86813a,
+    0xbfc1aa2b7e23f72a,
+    0xbfc08598b59e3a07,
+    0xbfbec739830a1120,
+    0xbfbc885801bc4b23,
+    0xbfba4e7640b1bc38,
+    0xbfb8197e2f40e3f0,
+    0xbfb5e95a4d9791cb,
+    0xbfb3bdf5a7d1ee64,
+    0xbfb1973bd1465567,
+    0xbfaeea31c006b87c,
+    0xbfaaaef2d0fb10fc,
+    0xbfa67c94f2d4bb58,
+    0xbfa252f32f8d183f,
+    0xbf9c63d2ec14aaf2,
+    0xbf9432a925980cc1,
+    0xbf882448a388a2aa,
+    0xbf70080559588b35,
+];
+
+/// `e^x` to within [`EXP_MAX_ULPS`].
+pub fn exp_deterministic(x: f64) -> f64 {
+    if let Some(special) = exp_special_case(x) {
+        return special;
+    }
+    // x = k*ln2 + r with |r| <= ln2/2
+    let k = (x * LOG2_E).round();
+    let r = x - k * LN2_HI - k * LN2_LO;
+    let mut p = 0.0;
+    for coefficient in EXP_COEFFICIENTS {
+        p = coefficient + r * p;
+    }
+    // e^r - 1 accumulated separately so that adding 1 rounds only once
+    let expm1 = r + r * r * p;
+    scale_by_pow2(1.0 + expm1, k as i32)
+}
+
+/// `e^x` from a 64-entry table of `2^(j/64)`, to within [`EXP_LUT_MAX_ULPS`].
+pub fn exp_lut(x: f64) -> f64 {
+    if let Some(special) = exp_special_case(x) {
+        return special;
+    }
+    // x = (64k + j)*ln2/64 + r with |r| <= ln2/128
+    let n = (x * (LOG2_E * LUT_SIZE as f64)).round();
+    let r = x - n * (LN2_HI / LUT_SIZE as f64) - n * (LN2_LO / LUT_SIZE as f64);
+    let n = n as i32;
+    let j = (n & (LUT_SIZE - 1)) as usize;
+    let k = n >> LUT_BITS;
+    let expm1 = r + r * r * (0.5 + r * (1.0 / 6.0 + r * (1.0 / 24.0 + r * (1.0 / 120.0))));
+    let t = f64::from_bits(EXP2_TABLE[j]);
+    scale_by_pow2(t + t * expm1, k)
+}
+
+/// Natural logarithm to within [`LN_MAX_ULPS`].
+pub fn ln_deterministic(x: f64) -> f64 {
+    if let Some(special) = ln_special_case(x) {
+        return special;
+    }
+    let (mut m, mut k) = split_exponent(x);
+    if m > SQRT_2 {
+        m *= 0.5;
+        k += 1;
+    }
+    // ln(1 + f) = 2 atanh(s) with s = f / (2 + f), written as in fdlibm so
+    // that the leading term f is added last
+    let f = m - 1.0;
+    let s = f / (2.0 + f);
+    let z = s * s;
+    let mut r = 0.0;
+    for coefficient in LN_COEFFICIENTS {
+        r = z * (coefficient + r);
+    }
+    let half_f_squared = 0.5 * f * f;
+    let k = k as f64;
+    k * LN2_HI - ((half_f_squared - (s * (half_f_squared + r) + k * LN2_LO)) - f)
+}
+
+/// Natural logarithm from a 64-entry table, to within [`LN_LUT_MAX_ULPS`].
+pub fn ln_lut(x: f64) -> f64 {
+    if let Some(special) = ln_special_case(x) {
+        return special;
+    }
+    // Close to 1 every table entry would cancel against the remainder
+    if (x - 1.0).abs() < 1.0 / LUT_SIZE as f64 {
+        return ln_remainder(x, 1.0);
+    }
+    let (mut m, mut k) = split_exponent(x);
+    let j = ((m.to_bits() >> (52 - LUT_BITS)) & (LUT_SIZE as u64 - 1)) as usize;
+    let mut c = 1.0 + (j as f64 + 0.5) / LUT_SIZE as f64;
+    if j >= LUT_SIZE as usize / 2 {
+        m *= 0.5;
+        c *= 0.5;
+        k += 1;
+    }
+    let k = k as f64;
+    k * LN2_HI + (f64::from_bits(LN_TABLE[j]) + (k * LN2_LO + ln_remainder(m, c)))
+}
+
+/// Base-2 logarithm to within [`LOG2_MAX_ULPS`]; exact for powers of two.
+/// NaN for negative inputs, `-inf` for zero.
+pub fn log2_deterministic(x: f64) -> f64 {
+    if let Some(special) = ln_special_case(x) {
+        return special;
+    }
+    let (m, k) = split_centred(x);
+    // k is exact, so only the logarithm of the significand is rounded
+    k as f64 + ln_deterministic(m) * LOG2_E
+}
+
+/// Base-10 logarithm to within [`LOG10_MAX_ULPS`]; exact for the powers of
+/// ten an f64 holds exactly (1 to 1e22). NaN for negative inputs, `-inf`
+/// for zero.
+pub fn log10_deterministic(x: f64) -> f64 {
+    if let Some(special) = ln_special_case(x) {
+        return special;
+    }
+    if let Some(n) = exact_power_of_ten(x) {
+        return n as f64;
+    }
+    let (m, k) = split_centred(x);
+    let k = k as f64;
+    k * LOG10_2_HI + (k * LOG10_2_LO + ln_deterministic(m) * LOG10_E)
+}
+
+/// `base^exponent` to within [`POW_MAX_ULPS`], with the special cases of
+/// `f64::powf`: `x^0` and `1^y` are 1 even for NaN, a negative base needs an
+/// integral exponent (and keeps its sign for odd ones), and zero or infinite
+/// operands give zero or infinity.
+///
+/// Integral exponents never touch `exp` or `ln`, so `pow_deterministic(1.1,
+/// 2.0)` is `1.1 * 1.1` correctly rounded rather than an approximation of it.
+pub fn pow_deterministic(base: f64, exponent: f64) -> f64 {
+    if exponent == 0.0 || base == 1.0 {
+        return 1.0;
+    }
+    if base.is_nan() || exponent.is_nan() {
+        return f64::NAN;
+    }
+    let integral = exponent.trunc() == exponent;
+    // Integers from 2^53 up are all even
+    let odd = integral && exponent.abs() < 9_007_199_254_740_992.0 && exponent % 2.0 != 0.0;
+    if base < 0.0 && !integral {
+        return f64::NAN;
+    }
+    let negative = base.is_sign_negative() && odd;
+    let magnitude = base.abs();
+    let grows = (magnitude > 1.0) == (exponent > 0.0);
+    let result = if magnitude == 1.0 {
+        // -1 to an even or infinite power
+        1.0
+    } else if magnitude == 0.0 || magnitude.is_infinite() || exponent.is_infinite() {
+        if grows { f64::INFINITY } else { 0.0 }
+    } else if exponent.abs() >= 18_446_744_073_709_551_616.0 {
+        // At least 2^64 times |ln(base)| >= 2^-53 overflows any scale
+        if grows { f64::INFINITY } else { 0.0 }
+    } else {
+        pow_finite(magnitude, exponent)
+    };
+    if negative { -result } else { result }
+}
+
+// base^exponent for finite positive base other than 1 and |exponent| < 2^64
+fn pow_finite(base: f64, exponent: f64) -> f64 {
+    let (m, k) = split_exponent(base);
+    let whole = exponent.abs().trunc();
+    let fraction = exponent - exponent.trunc();
+
+    // m^|n| in double-double, renormalized to [1, 2) with the power of two
+    // tracked separately so that no intermediate overflows
+    let (mut high, mut low, mut scale) = (1.0, 0.0, 0_i64);
+    let (mut square_high, mut square_low, mut square_scale) = (m, 0.0, 0_i64);
+    let mut n = whole as u64;
+    while n != 0 {
+        if n & 1 == 1 {
+            (high, low) = dd_mul(high, low, square_high, square_low);
+            scale = scale.saturating_add(square_scale);
+            (high, low, scale) = dd_normalize(high, low, scale);
+        }
+        n >>= 1;
+        if n != 0 {
+            (square_high, square_low) = dd_mul(square_high, square_low, square_high, square_low);
+            square_scale = square_scale.saturating_mul(2);
+            (square_high, square_low, square_scale) =
+                dd_normalize(square_high, square_low, square_scale);
+        }
+    }
+    if exponent < 0.0 {
+        (high, low) = dd_recip(high, low);
+        scale = -scale;
+        (high, low, scale) = dd_normalize(high, low, scale);
+    }
+    // 2^(k * exponent), exactly split into an integer power and 2^g
+    let k = k as i64;
+    let whole_scale = if exponent < 0.0 {
+        -(whole as i64)
+    } else {
+        whole as i64
+    };
+    let integer_scale = k.saturating_mul(whole_scale);
+
+    // base^fraction = 2^(k * fraction) * m_c^fraction, with m_c = m or m / 2
+    // centred on 1 so that ln(m_c) is small and accurate
+    let (m_c, k_c) = if m > SQRT_2 { (m * 0.5, k + 1) } else { (m, k) };
+    let (p, e) = two_product(fraction, k_c as f64);
+    let j = p.round();
+    let g = (p - j) + e;
+    let t = g * LN2_HI + (g * LN2_LO + fraction * ln_deterministic(m_c));
+    let x = exp_deterministic(t);
+
+    let value = high * x + low * x;
+    let total = scale.saturating_add(integer_scale).saturating_add(j as i64);
+    // value is in [0.5, 4), so past these scales the result overflows or is
+    // below half the smallest subnormal
+    if total > 1024 {
+        f64::INFINITY
+    } else if total < -1076 {
+        0.0
+    } else {
+        scale_by_pow2(value, total as i32)
+    }
+}
+
+// (ah + al) * (bh + bl), dropping the al * bl term
+fn dd_mul(ah: f64, al: f64, bh: f64, bl: f64) -> (f64, f64) {
+    let (p, e) = two_product(ah, bh);
+    fast_two_sum(p, e + (ah * bl + al * bh))
+}
+
+// 1 / (h + l), with the residual of the first approximation as the low part
+fn dd_recip(h: f64, l: f64) -> (f64, f64) {
+    let r = 1.0 / h;
+    let (p, e) = two_product(h, r);
+    let residual = ((1.0 - p) - e) - l * r;
+    fast_two_sum(r, residual * r)
+}
+
+// Brings h into [1, 2) by exact halvings or doublings of both parts
+fn dd_normalize(mut h: f64, mut l: f64, mut scale: i64) -> (f64, f64, i64) {
+    while h >= 2.0 {
+        h *= 0.5;
+        l *= 0.5;
+        scale = scale.saturating_add(1);
+    }
+    while h < 1.0 {
+        h *= 2.0;
+        l *= 2.0;
+        scale = scale.saturating_sub(1);
+    }
+    (h, l, scale)
+}
+
+// a + b == sum + error exactly, for |a| >= |b|
+fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    (sum, b - (sum - a))
+}
+
+/// Sine of `x` radians to within [`SIN_MAX_ULPS`]; NaN for NaN and
+/// infinities.
+pub fn sin_deterministic(x: f64) -> f64 {
+    if !x.is_finite() {
+        return f64::NAN;
+    }
+    let (n, y0, y1) = rem_pio2(x);
+    match n & 3 {
+        0 => kernel_sin(y0, y1),
+        1 => kernel_cos(y0, y1),
+        2 => -kernel_sin(y0, y1),
+        _ => -kernel_cos(y0, y1),
+    }
+}
+
+/// Cosine of `x` radians to within [`COS_MAX_ULPS`]; NaN for NaN and
+/// infinities.
+pub fn cos_deterministic(x: f64) -> f64 {
+    if !x.is_finite() {
+        return f64::NAN;
+    }
+    let (n, y0, y1) = rem_pio2(x);
+    match n & 3 {
+        0 => kernel_cos(y0, y1),
+        1 => -kernel_sin(y0, y1),
+        2 => -kernel_cos(y0, y1),
+        _ => kernel_sin(y0, y1),
+    }
+}
+
+/// Tangent of `x` radians to within [`TAN_MAX_ULPS`], as the quotient of the
+/// sine and cosine kernels; NaN for NaN and infinities.
+pub fn tan_deterministic(x: f64) -> f64 {
+    if !x.is_finite() {
+        return f64::NAN;
+    }
+    let (n, y0, y1) = rem_pio2(x);
+    let (sin, cos) = (kernel_sin(y0, y1), kernel_cos(y0, y1));
+    if n & 1 == 0 { sin / cos } else { -cos / sin }
+}
+
+// sin(x + y) for |x + y| <= pi/4, with y a tail below half an ulp of x
+// (fdlibm's __kernel_sin, with Taylor coefficients)
+fn kernel_sin(x: f64, y: f64) -> f64 {
+    let z = x * x;
+    let v = z * x;
+    let mut r = 0.0;
+    for coefficient in SIN_COEFFICIENTS {
+        r = coefficient + z * r;
+    }
+    x - ((z * (0.5 * y - v * r) - y) - v * SIN_THIRD_ORDER)
+}
+
+// cos(x + y) for |x + y| <= pi/4 (fdlibm's __kernel_cos): 1 - z/2 is added
+// last, with its rounding error carried into the small terms
+fn kernel_cos(x: f64, y: f64) -> f64 {
+    let z = x * x;
+    let mut r = 0.0;
+    for coefficient in COS_COEFFICIENTS {
+        r = coefficient + z * r;
+    }
+    let half_z = 0.5 * z;
+    let w = 1.0 - half_z;
+    w + (((1.0 - w) - half_z) + (z * (z * r) - x * y))
+}
+
+// x = n * pi/2 + (y0 + y1) with |y0 + y1| <= pi/4 (slightly more in the
+// medium range), for finite x
+fn rem_pio2(x: f64) -> (i32, f64, f64) {
+    let magnitude = x.abs();
+    let (n, y0, y1) = if magnitude <= std::f64::consts::FRAC_PI_4 {
+        (0, magnitude, 0.0)
+    } else if magnitude < MEDIUM_REDUCTION_LIMIT {
+        rem_pio2_medium(magnitude)
+    } else {
+        rem_pio2_large(magnitude)
+    };
+    if x.is_sign_negative() {
+        (-n, -y0, -y1)
+    } else {
+        (n, y0, y1)
+    }
+}
+
+// Cody-Waite reduction, adding a part of pi/2 each time the result cancels
+// too many bits against the previous one
+fn rem_pio2_medium(x: f64) -> (i32, f64, f64) {
+    let n = (x * FRAC_2_PI).round();
+    let mut r = x - n * PIO2_1;
+    let mut w = n * PIO2_1T;
+    let mut y0 = r - w;
+    let exponent = biased_exponent(x);
+    if exponent - biased_exponent(y0) > 16 {
+        let t = r;
+        w = n * PIO2_2;
+        r = t - w;
+        w = n * PIO2_2T - ((t - r) - w);
+        y0 = r - w;
+        if exponent - biased_exponent(y0) > 49 {
+            let t = r;
+            w = n * PIO2_3;
+            r = t - w;
+            w = n * PIO2_3T - ((t - r) - w);
+            y0 = r - w;
+        }
+    }
+    (n as i32, y0, (r - y0) - w)
+}
+
+// Payne-Hanek reduction: with x = m * 2^e, only the bits of 2/pi from weight
+// 2^(1 - e) on affect x * 2/pi modulo 4, and 192 of them give the fraction to
+// well past the closest any f64 comes to a multiple of pi/2
+fn rem_pio2_large(x: f64) -> (i32, f64, f64) {
+    let bits = x.to_bits();
+    let e = (bits >> 52) as i32 - 1075;
+    let m = bits & 0x000f_ffff_ffff_ffff | 1 << 52;
+    let first = e - 1;
+    let window = U256::from_limbs([
+        two_over_pi_bits(first + 128),
+        two_over_pi_bits(first + 64),
+        two_over_pi_bits(first),
+        0,
+    ]);
+    // x * 2/pi = product / 2^190 modulo 4
+    let product = window.wrapping_mul(U256::from(m));
+    let quadrant = ((product >> 190).low_u128() & 3) as i32;
+    let fraction = ((product << 66) >> 128).low_u128();
+    // Fractions from a half up belong to the next quadrant
+    let (n, negative, fraction) = if fraction >> 127 == 1 {
+        (quadrant + 1, true, fraction.wrapping_neg())
+    } else {
+        (quadrant, false, fraction)
+    };
+    // Normalise so the head takes 53 significant bits even when x is close
+    // to a multiple of pi/2; x is never an exact multiple, so fraction != 0
+    let shift = fraction.leading_zeros() as i32;
+    let fraction = fraction << shift;
+    let high = (fraction >> 75) as f64 * pow2(-53 - shift);
+    let low = (fraction & ((1 << 75) - 1)) as f64 * pow2(-128 - shift);
+    let (p, error) = two_product(high, FRAC_PI_2);
+    let (y0, y1) = fast_two_sum(p, error + (high * PIO2_LO + low * FRAC_PI_2));
+    if negative {
+        (n & 3, -y0, -y1)
+    } else {
+        (n & 3, y0, y1)
+    }
+}
+
+// 64 bits of 2/pi starting at the bit of weight 2^-first; bits of weight 1
+// and above are zero
+fn two_over_pi_bits(first: i32) -> u64 {
+    let position = first - 1;
+    if position <= -64 {
+        return 0;
+    }
+    if position < 0 {
+        return TWO_OVER_PI[0] >> -position;
+    }
+    let (word, offset) = ((position / 64) as usize, position % 64);
+    let next = TWO_OVER_PI.get(word + 1).copied().unwrap_or(0);
+    if offset == 0 {
+        TWO_OVER_PI[word]
+    } else {
+        TWO_OVER_PI[word] << offset | next >> (64 - offset)
+    }
+}
+
+fn biased_exponent(x: f64) -> i32 {
+    (x.to_bits() >> 52 & 0x7ff) as i32
+}
+
+// ln(1 + x) without losing x to the rounding of 1 + x: the rounding error of
+// u = 1 + x cancels in ln(u) * x / (u - 1) (Goldberg, "What Every Computer
+// Scientist Should Know About Floating-Point Arithmetic", Theorem 4).
+pub(crate) fn ln_one_plus(x: f64) -> f64 {
+    let u = 1.0 + x;
+    if u == 1.0 {
+        x
+    } else {
+        ln_deterministic(u) * x / (u - 1.0)
+    }
+}
+
+// ln(m / c) = 2 atanh(t) with t = (m - c) / (m + c), for m within 1/64 of c
+fn ln_remainder(m: f64, c: f64) -> f64 {
+    let t = (m - c) / (m + c);
+    let z = t * t;
+    2.0 * t + 2.0 * t * z * (1.0 / 3.0 + z * (0.2 + z * (1.0 / 7.0)))
+}
+
+fn exp_special_case(x: f64) -> Option<f64> {
+    if x.is_nan() {
+        Some(x)
+    } else if x > EXP_OVERFLOW {
+        Some(f64::INFINITY)
+    } else if x < EXP_UNDERFLOW {
+        Some(0.0)
+    } else {
+        None
+    }
+}
+
+fn ln_special_case(x: f64) -> Option<f64> {
+    if x.is_nan() || x < 0.0 {
+        Some(f64::NAN)
+    } else if x == 0.0 {
+        Some(f64::NEG_INFINITY)
+    } else if x.is_infinite() {
+        Some(x)
+    } else {
+        None
+    }
+}
+
+// x = m * 2^k with m in (sqrt(1/2), sqrt(2)], so that ln(m) is small
+fn split_centred(x: f64) -> (f64, i32) {
+    let (m, k) = split_exponent(x);
+    if m > SQRT_2 { (m * 0.5, k + 1) } else { (m, k) }
+}
+
+// n when x is exactly 10^n
+pub(crate) fn exact_power_of_ten(x: f64) -> Option<i32> {
+    let mut power = 1.0;
+    for n in 0..=MAX_EXACT_POWER_OF_TEN {
+        if x == power {
+            return Some(n);
+        }
+        power *= 10.0;
+    }
+    None
+}
+
+// x = m * 2^k with m in [1, 2), for finite positive x
+fn split_exponent(x: f64) -> (f64, i32) {
+    let (x, bias) = if x < f64::MIN_POSITIVE {
+        (x * pow2(54), 1023 + 54)
+    } else {
+        (x, 1023)
+    };
+    let bits = x.to_bits();
+    let m = f64::from_bits(bits & 0x000f_ffff_ffff_ffff | 0x3ff0_0000_0000_0000);
+    (m, (bits >> 52) as i32 - bias)
+}
+
+// v * 2^k for v in [0.5, 4), in steps that stay representable
+fn scale_by_pow2(v: f64, k: i32) -> f64 {
+    if k > 1023 {
+        v * 2.0 * pow2(k - 1)
+    } else if k < -1022 {
+        v * pow2(k + 54) * pow2(-54)
+    } else {
+        v * pow2(k)
+    }
+}
+
+fn pow2(k: i32) -> f64 {
+    f64::from_bits(((k + 1023) as u64) << 52)
+}
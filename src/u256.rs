@@ -0,0 +1,275 @@
+//! Minimal 256-bit unsigned integer.
+//!
+//! Just enough arithmetic to reproduce EVM `uint256` semantics (wrapping
+//! products, floor division, full-width `mulDiv`) without pulling in a bignum
+//! dependency. Limbs are stored little endian.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Shl, Shr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    pub const fn from_limbs(limbs: [u64; 4]) -> Self {
+        U256(limbs)
+    }
+
+    pub const fn from_u128(value: u128) -> Self {
+        U256([value as u64, (value >> 64) as u64, 0, 0])
+    }
+
+    /// 10^exponent, or `None` past 10^77.
+    pub fn pow10(exponent: u32) -> Option<Self> {
+        let ten = U256::from(10u64);
+        (0..exponent).try_fold(U256::ONE, |acc, _| acc.checked_mul(ten))
+    }
+
+    /// Little-endian limbs.
+    pub fn limbs(&self) -> [u64; 4] {
+        self.0
+    }
+
+    pub fn try_to_u128(self) -> Option<u128> {
+        if self.0[2] != 0 || self.0[3] != 0 {
+            return None;
+        }
+        Some(self.low_u128())
+    }
+
+    /// Low 128 bits, discarding the rest.
+    pub fn low_u128(self) -> u128 {
+        self.0[0] as u128 | (self.0[1] as u128) << 64
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// Number of significant bits (zero for zero).
+    pub fn bits(&self) -> u32 {
+        for i in (0..4).rev() {
+            if self.0[i] != 0 {
+                return 64 * i as u32 + 64 - self.0[i].leading_zeros();
+            }
+        }
+        0
+    }
+
+    fn bit(&self, index: u32) -> bool {
+        self.0[(index / 64) as usize] >> (index % 64) & 1 == 1
+    }
+
+    pub fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+        let mut out = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in out.iter_mut().enumerate() {
+            let (sum, c1) = self.0[i].overflowing_add(rhs.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        (U256(out), carry)
+    }
+
+    pub fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        let mut out = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in out.iter_mut().enumerate() {
+            let (diff, b1) = self.0[i].overflowing_sub(rhs.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        (U256(out), borrow)
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_add(rhs) {
+            (sum, false) => Some(sum),
+            _ => None,
+        }
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_sub(rhs) {
+            (diff, false) => Some(diff),
+            _ => None,
+        }
+    }
+
+    /// Full 512-bit product as `(low, high)` halves.
+    pub fn widening_mul(self, rhs: Self) -> (Self, Self) {
+        let mut out = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let t = self.0[i] as u128 * rhs.0[j] as u128 + out[i + j] as u128 + carry;
+                out[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            out[i + 4] = carry as u64;
+        }
+        (
+            U256([out[0], out[1], out[2], out[3]]),
+            U256([out[4], out[5], out[6], out[7]]),
+        )
+    }
+
+    /// Product modulo 2^256, like an `unchecked` Solidity multiplication.
+    pub fn wrapping_mul(self, rhs: Self) -> Self {
+        self.widening_mul(rhs).0
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let (low, high) = self.widening_mul(rhs);
+        high.is_zero().then_some(low)
+    }
+
+    /// Floor quotient and remainder, or `None` when dividing by zero.
+    pub fn div_rem(self, rhs: Self) -> Option<(Self, Self)> {
+        let (quotient, remainder) = div_rem_wide(&self.0, rhs)?;
+        Some((
+            U256([quotient[0], quotient[1], quotient[2], quotient[3]]),
+            remainder,
+        ))
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.div_rem(rhs).map(|(quotient, _)| quotient)
+    }
+
+    /// `floor(a * b / denominator)` with a 512-bit intermediate, or `None` if the
+    /// denominator is zero or the quotient does not fit in 256 bits.
+    pub fn mul_div(a: Self, b: Self, denominator: Self) -> Option<Self> {
+        U256::mul_div_rem(a, b, denominator).map(|(quotient, _)| quotient)
+    }
+
+    /// Like [`mul_div`](Self::mul_div), also returning `a * b % denominator`.
+    pub fn mul_div_rem(a: Self, b: Self, denominator: Self) -> Option<(Self, Self)> {
+        let (low, high) = a.widening_mul(b);
+        let mut product = [0u64; 8];
+        product[..4].copy_from_slice(&low.0);
+        product[4..].copy_from_slice(&high.0);
+        let (quotient, remainder) = div_rem_wide(&product, denominator)?;
+        if quotient[4..].iter().any(|&limb| limb != 0) {
+            return None;
+        }
+        Some((
+            U256([quotient[0], quotient[1], quotient[2], quotient[3]]),
+            remainder,
+        ))
+    }
+}
+
+// Shift-subtract long division of an arbitrary-width numerator by a U256.
+fn div_rem_wide(numerator: &[u64], divisor: U256) -> Option<(Vec<u64>, U256)> {
+    if divisor.is_zero() {
+        return None;
+    }
+    let mut quotient = vec![0u64; numerator.len()];
+    let mut remainder = U256::ZERO;
+    for index in (0..numerator.len() as u32 * 64).rev() {
+        // The remainder stays below the divisor, so a bit shifted out of the top
+        // means the shifted value is certainly at least the divisor.
+        let carry = remainder.bit(255);
+        remainder = remainder << 1;
+        remainder.0[0] |= numerator[(index / 64) as usize] >> (index % 64) & 1;
+        if carry || remainder >= divisor {
+            remainder = remainder.overflowing_sub(divisor).0;
+            quotient[(index / 64) as usize] |= 1 << (index % 64);
+        }
+    }
+    Some((quotient, remainder))
+}
+
+impl From<u128> for U256 {
+    fn from(value: u128) -> Self {
+        U256::from_u128(value)
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(value: u64) -> Self {
+        U256([value, 0, 0, 0])
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Shl<u32> for U256 {
+    type Output = U256;
+
+    /// Shifts of 256 or more give zero, as in the EVM.
+    fn shl(self, shift: u32) -> U256 {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+        let (limb_shift, bit_shift) = ((shift / 64) as usize, shift % 64);
+        let mut out = [0u64; 4];
+        for (i, limb) in out.iter_mut().enumerate().skip(limb_shift) {
+            *limb = self.0[i - limb_shift] << bit_shift;
+            if bit_shift != 0 && i > limb_shift {
+                *limb |= self.0[i - limb_shift - 1] >> (64 - bit_shift);
+            }
+        }
+        U256(out)
+    }
+}
+
+impl Shr<u32> for U256 {
+    type Output = U256;
+
+    /// Shifts of 256 or more give zero, as in the EVM.
+    fn shr(self, shift: u32) -> U256 {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+        let (limb_shift, bit_shift) = ((shift / 64) as usize, shift % 64);
+        let mut out = [0u64; 4];
+        for (i, limb) in out.iter_mut().enumerate().take(4 - limb_shift) {
+            *limb = self.0[i + limb_shift] >> bit_shift;
+            if bit_shift != 0 && i + limb_shift + 1 < 4 {
+                *limb |= self.0[i + limb_shift + 1] << (64 - bit_shift);
+            }
+        }
+        U256(out)
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Peel off 19 decimal digits at a time, least significant first
+        let chunk = U256::from(10_000_000_000_000_000_000u64);
+        let mut chunks = Vec::new();
+        let mut rest = *self;
+        loop {
+            let (quotient, remainder) = rest.div_rem(chunk).unwrap();
+            chunks.push(remainder.0[0]);
+            if quotient.is_zero() {
+                break;
+            }
+            rest = quotient;
+        }
+        let mut digits = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:019}", chunk));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
@@ -0,0 +1,129 @@
+//! Unit-safe f64 newtypes for token amounts and prices.
+//!
+//! The number of decimals is part of the type, so an `Amount<9>` (lamports to
+//! SOL) cannot be mixed up with an `Amount<6>` (USDC) without an explicit
+//! conversion. Values are finite and non-negative. `TryFrom<u64>` reads raw
+//! base units as stored in accounts and `TryFrom<Amount<D>> for u64` writes them
+//! back; both fail instead of silently changing the stored integer.
+
+use crate::error::FloatError;
+use crate::float_ext::SolanaFloatExt;
+use crate::format::write_fixed;
+use crate::quantize::pow10;
+use std::fmt;
+
+// 2^53: raw integers above this are not exact in f64.
+const F64_EXACT_INTEGER_LIMIT: u64 = 1 << 53;
+
+/// Reads `raw` base units as a value with `decimals` fractional digits.
+///
+/// Fails with `PrecisionLoss` unless converting back with [`value_to_raw`]
+/// gives `raw` again, and with `OutOfRange` for more than `MAX_DECIMALS`.
+pub fn raw_to_value(raw: u64, decimals: u32) -> Result<f64, FloatError> {
+    let scale = pow10(decimals)?;
+    if raw > F64_EXACT_INTEGER_LIMIT {
+        return Err(FloatError::PrecisionLoss);
+    }
+    let value = raw as f64 / scale as f64;
+    if value_to_raw(value, decimals)? != raw {
+        return Err(FloatError::PrecisionLoss);
+    }
+    Ok(value)
+}
+
+/// Converts `value` to base units with `decimals` fractional digits, rounding
+/// half to even.
+pub fn value_to_raw(value: f64, decimals: u32) -> Result<u64, FloatError> {
+    value.to_scaled(decimals)
+}
+
+fn check_value(value: f64, decimals: u32) -> Result<f64, FloatError> {
+    pow10(decimals)?;
+    if value.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    if !value.is_finite() {
+        return Err(FloatError::Overflow);
+    }
+    if value < 0.0 {
+        return Err(FloatError::OutOfRange);
+    }
+    // Normalise -0.0 so equal amounts have equal bits
+    Ok(value + 0.0)
+}
+
+macro_rules! unit_newtype {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+        pub struct $name<const DECIMALS: u32>(f64);
+
+        impl<const DECIMALS: u32> $name<DECIMALS> {
+            pub const DECIMALS: u32 = DECIMALS;
+
+            /// Fails for NaN, infinite or negative values, and when
+            /// `DECIMALS` exceeds `MAX_DECIMALS`.
+            pub fn new(value: f64) -> Result<Self, FloatError> {
+                check_value(value, DECIMALS).map(Self)
+            }
+
+            pub fn value(self) -> f64 {
+                self.0
+            }
+
+            /// See [`raw_to_value`].
+            pub fn from_raw(raw: u64) -> Result<Self, FloatError> {
+                raw_to_value(raw, DECIMALS).map(Self)
+            }
+
+            /// See [`value_to_raw`].
+            pub fn to_raw(self) -> Result<u64, FloatError> {
+                value_to_raw(self.0, DECIMALS)
+            }
+
+            pub fn checked_add(self, rhs: Self) -> Result<Self, FloatError> {
+                Self::new(self.0 + rhs.0)
+            }
+
+            /// Fails with `OutOfRange` if `rhs` is larger.
+            pub fn checked_sub(self, rhs: Self) -> Result<Self, FloatError> {
+                Self::new(self.0 - rhs.0)
+            }
+        }
+
+        /// Prints `DECIMALS` fractional digits, like the stored base units.
+        impl<const DECIMALS: u32> fmt::Display for $name<DECIMALS> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write_fixed(f, self.0, DECIMALS)
+            }
+        }
+
+        impl<const DECIMALS: u32> TryFrom<u64> for $name<DECIMALS> {
+            type Error = FloatError;
+
+            fn try_from(raw: u64) -> Result<Self, Self::Error> {
+                Self::from_raw(raw)
+            }
+        }
+
+        impl<const DECIMALS: u32> TryFrom<$name<DECIMALS>> for u64 {
+            type Error = FloatError;
+
+            fn try_from(value: $name<DECIMALS>) -> Result<Self, Self::Error> {
+                value.to_raw()
+            }
+        }
+    };
+}
+
+unit_newtype!(
+    /// A token amount in whole tokens, stored on-chain as `DECIMALS`-scaled
+    /// base units (lamports for `Amount<9>` SOL).
+    Amount
+);
+
+unit_newtype!(
+    /// A price in quote tokens per whole base token, stored on-chain with
+    /// `DECIMALS` fractional digits.
+    Price
+);
@@ -0,0 +1,36 @@
+//! Bit-exact recomputation of off-chain float results.
+//!
+//! A client computes `op(a, b)` off-chain and submits the operands together with
+//! the claimed result's bit pattern; the program recomputes it with the same
+//! IEEE-754 operation and accepts only an identical bit pattern. Basic f64
+//! arithmetic is correctly rounded, so any conforming implementation produces
+//! the same bits for the same inputs.
+//!
+//! NaN is the exception: its sign and payload bits differ between targets, so
+//! NaN operands and NaN results are rejected rather than attested.
+
+use crate::double_ops::{self, BinaryOp};
+use crate::error::FloatError;
+
+/// Recomputes `op(a, b)` exactly as the program does.
+pub fn recompute(op: BinaryOp, a: f64, b: f64) -> Result<f64, FloatError> {
+    if a.is_nan() || b.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    let result = match op {
+        BinaryOp::Add => double_ops::add_doubles(a, b),
+        BinaryOp::Multiply => double_ops::multiply_doubles(a, b),
+        BinaryOp::Divide => double_ops::divide_doubles(a, b)?,
+    };
+    if result.is_nan() {
+        return Err(FloatError::NaNOperand);
+    }
+    Ok(result)
+}
+
+/// Returns whether `claimed_bits` is exactly the bit pattern of `op(a, b)`.
+///
+/// `-0.0` and `+0.0` have different bit patterns and do not match each other.
+pub fn verify(op: BinaryOp, a: f64, b: f64, claimed_bits: u64) -> Result<bool, FloatError> {
+    Ok(recompute(op, a, b)?.to_bits() == claimed_bits)
+}
@@ -0,0 +1,351 @@
+use solana_floats::batch::{BatchResults, MAX_BATCH_RESULTS};
+use solana_floats::instruction::{Opcode, encode};
+use solana_floats::operands::*;
+use solana_floats::quantize::{RoundingMode, quantize};
+use solana_floats::stats::RunningStats;
+use solana_program::program_error::ProgramError;
+use solana_program_test::*;
+use solana_sdk::{account::Account, instruction::AccountMeta, pubkey::Pubkey};
+
+mod common;
+
+#[cfg(test)]
+mod account_operands_tests {
+    use super::*;
+    use common::{PROGRAM_ID, process_in, program_test};
+
+    fn operand_data(values: &[f64], leading_padding: usize) -> Vec<u8> {
+        let mut data = vec![0xAA; leading_padding];
+        for value in values {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data
+    }
+
+    // The program next to a foreign account holding `data`, and that account
+    fn with_operand_account(data: Vec<u8>) -> (ProgramTest, Pubkey) {
+        let operand_account = Pubkey::new_unique();
+        let mut program_test = program_test(PROGRAM_ID);
+        program_test.add_account(
+            operand_account,
+            Account {
+                lamports: 1_000_000_000,
+                data,
+                owner: Pubkey::new_unique(),
+                ..Account::default()
+            },
+        );
+        (program_test, operand_account)
+    }
+
+    async fn process_with_operand_account(
+        data: Vec<u8>,
+        instruction_data: Vec<u8>,
+        pass_account: bool,
+    ) -> bool {
+        let (program_test, operand_account) = with_operand_account(data);
+        let accounts = if pass_account {
+            vec![AccountMeta::new_readonly(operand_account, false)]
+        } else {
+            vec![]
+        };
+        process_in(program_test, &instruction_data, accounts)
+            .await
+            .result
+            .is_ok()
+    }
+
+    // Return data of a successful instruction over a read-only operand account
+    async fn return_data_with_operand_account(data: Vec<u8>, instruction_data: Vec<u8>) -> Vec<u8> {
+        let (program_test, operand_account) = with_operand_account(data);
+        let result = process_in(
+            program_test,
+            &instruction_data,
+            vec![AccountMeta::new_readonly(operand_account, false)],
+        )
+        .await;
+        result.result.unwrap();
+        result.metadata.unwrap().return_data.unwrap().data
+    }
+
+    // A 10 KiB account of operands after a 4-byte misaligning prefix
+    fn ten_kib_dataset() -> (Vec<f64>, Vec<u8>, AccountOperands) {
+        let count = (10 * 1024 - 4) / OPERAND_SIZE;
+        let values: Vec<f64> = (0..count).map(|i| 0.001 * i as f64 + 0.1).collect();
+        let operands = AccountOperands {
+            offset: 4,
+            count: count as u32,
+        };
+        (values.clone(), operand_data(&values, 4), operands)
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let operands = AccountOperands {
+            offset: 16,
+            count: 300,
+        };
+        let mut payload = operands.pack().to_vec();
+        payload.push(0xFF);
+        let (decoded, rest) = AccountOperands::unpack(&payload).unwrap();
+        assert_eq!(decoded, operands);
+        assert_eq!(rest, &[0xFF]);
+        assert_eq!(
+            AccountOperands::unpack(&payload[..7]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_bounds_are_checked_strictly() {
+        let data = operand_data(&[1.0, 2.0, 3.0], 4);
+        let read = |offset, count| AccountOperands { offset, count }.read(&data);
+
+        assert_eq!(read(4, 3), Ok(vec![1.0, 2.0, 3.0]));
+        assert_eq!(read(12, 2), Ok(vec![2.0, 3.0]));
+        assert_eq!(read(28, 0), Ok(vec![]));
+        // One byte past the end
+        assert_eq!(read(5, 3), Err(ProgramError::AccountDataTooSmall));
+        assert_eq!(read(4, 4), Err(ProgramError::AccountDataTooSmall));
+        assert_eq!(read(29, 0), Err(ProgramError::AccountDataTooSmall));
+        // Offsets and counts that would overflow the range computation
+        assert_eq!(
+            read(u32::MAX, u32::MAX),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+    }
+
+    fn aligned_bytes(values: &[f64]) -> Vec<u64> {
+        // Backing the bytes with u64 storage guarantees 8-byte alignment
+        values.iter().map(|value| value.to_bits()).collect()
+    }
+
+    #[test]
+    fn test_view_matches_read_on_aligned_data() {
+        let storage = aligned_bytes(&[0.1, 0.2, 0.3, 0.4]);
+        let data: &[u8] = bytemuck::cast_slice(&storage);
+        let operands = AccountOperands {
+            offset: 8,
+            count: 3,
+        };
+
+        let view = operands.view(data).unwrap();
+        assert_eq!(view, &[0.2, 0.3, 0.4]);
+        assert_eq!(view.to_vec(), operands.read(data).unwrap());
+        // The view borrows the account bytes rather than copying them
+        assert_eq!(view.as_ptr() as usize, data[8..].as_ptr() as usize);
+    }
+
+    #[test]
+    fn test_view_rejects_misaligned_offset() {
+        let storage = aligned_bytes(&[1.0, 2.0, 3.0]);
+        let data: &[u8] = bytemuck::cast_slice(&storage);
+        let operands = AccountOperands {
+            offset: 4,
+            count: 2,
+        };
+        assert_eq!(
+            operands.view(data),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        // The copying reader still accepts it
+        assert_eq!(operands.read(data).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_view_rejects_misaligned_account_buffer() {
+        let storage = aligned_bytes(&[1.0, 2.0, 3.0]);
+        let bytes: &[u8] = bytemuck::cast_slice(&storage);
+        // Shift the whole buffer by one byte so its start is misaligned
+        let shifted = &bytes[1..];
+        let operands = AccountOperands {
+            offset: 0,
+            count: 1,
+        };
+        assert_eq!(
+            operands.view(shifted),
+            Err(ProgramError::InvalidAccountData)
+        );
+        // Bounds are still checked before alignment
+        let operands = AccountOperands {
+            offset: 8,
+            count: 3,
+        };
+        assert_eq!(operands.view(bytes), Err(ProgramError::AccountDataTooSmall));
+    }
+
+    #[tokio::test]
+    async fn test_program_rejects_misaligned_operand_offset() {
+        let data = operand_data(&[1.0, 2.0, 3.0], 4);
+        let header = AccountOperands {
+            offset: 4,
+            count: 3,
+        };
+        let instruction_data = encode(Opcode::SumAccount, &header.pack());
+        assert!(!process_with_operand_account(data, instruction_data, true).await);
+    }
+
+    #[tokio::test]
+    async fn test_sum_over_dataset_larger_than_a_transaction() {
+        // 1_000 values is 8_000 bytes: far more than fits in instruction data
+        let values: Vec<f64> = (0..1_000).map(|i| 0.1 * i as f64).collect();
+        let data = operand_data(&values, 8);
+        let header = AccountOperands {
+            offset: 8,
+            count: 1_000,
+        };
+
+        let instruction_data = encode(Opcode::SumAccount, &header.pack());
+        assert!(process_with_operand_account(data, instruction_data, true).await);
+    }
+
+    #[tokio::test]
+    async fn test_batch_quantize_from_account() {
+        let data = operand_data(&[1.234, 5.678, 9.1011], 0);
+        let header = AccountOperands {
+            offset: 0,
+            count: 3,
+        };
+        let mut payload = header.pack().to_vec();
+        payload.extend_from_slice(&[2, 4]); // 2 decimals, half-even
+
+        let instruction_data = encode(Opcode::BatchQuantizeAccount, &payload);
+        assert!(process_with_operand_account(data, instruction_data, true).await);
+    }
+
+    #[tokio::test]
+    async fn test_account_operand_errors() {
+        let data = operand_data(&[1.0, 2.0], 0);
+        let out_of_bounds = AccountOperands {
+            offset: 8,
+            count: 2,
+        };
+        let in_bounds = AccountOperands {
+            offset: 0,
+            count: 2,
+        };
+
+        let instruction_data = encode(Opcode::SumAccount, &out_of_bounds.pack());
+        assert!(!process_with_operand_account(data.clone(), instruction_data, true).await);
+
+        // Missing operand account
+        let instruction_data = encode(Opcode::SumAccount, &in_bounds.pack());
+        assert!(!process_with_operand_account(data.clone(), instruction_data, false).await);
+
+        // Trailing bytes after the header
+        let mut payload = in_bounds.pack().to_vec();
+        payload.push(0);
+        let instruction_data = encode(Opcode::SumAccount, &payload);
+        assert!(!process_with_operand_account(data, instruction_data, true).await);
+    }
+
+    #[test]
+    fn test_iter_matches_read_at_any_alignment() {
+        let data = operand_data(&[0.1, 0.2, 0.3, 0.4], 3);
+        for (offset, count) in [(3, 4), (11, 2), (35, 0)] {
+            let operands = AccountOperands { offset, count };
+            let iter = operands.iter(&data).unwrap();
+            assert_eq!(iter.len(), count as usize);
+            assert_eq!(iter.collect::<Vec<_>>(), operands.read(&data).unwrap());
+        }
+        let operands = AccountOperands {
+            offset: 4,
+            count: 4,
+        };
+        assert!(matches!(
+            operands.iter(&data),
+            Err(ProgramError::AccountDataTooSmall)
+        ));
+    }
+
+    #[test]
+    fn test_chunks_cover_every_operand_once() {
+        let (values, data, operands) = ten_kib_dataset();
+        let chunks = operands.chunks::<64>(&data).unwrap();
+        assert_eq!(chunks.len(), values.len().div_ceil(64));
+        let mut seen = Vec::new();
+        for chunk in chunks {
+            assert!(!chunk.is_empty() && chunk.len() <= 64);
+            seen.extend_from_slice(&chunk);
+        }
+        assert_eq!(seen, values);
+
+        assert!(matches!(
+            operands.chunks::<0>(&data),
+            Err(ProgramError::InvalidArgument)
+        ));
+        let empty = AccountOperands {
+            offset: 0,
+            count: 0,
+        };
+        assert_eq!(empty.chunks::<8>(&data).unwrap().next(), None);
+    }
+
+    #[test]
+    fn test_map_in_place_rewrites_operands_only() {
+        let mut data = operand_data(&[1.234, 5.678, 9.1011], 5);
+        let operands = AccountOperands {
+            offset: 5,
+            count: 3,
+        };
+        operands
+            .map_in_place(&mut data, |value| {
+                quantize(value, 2, RoundingMode::HalfEven)
+                    .map_err(|_| ProgramError::InvalidArgument)
+            })
+            .unwrap();
+        assert_eq!(&data[..5], &[0xAA; 5]);
+        assert_eq!(operands.read(&data).unwrap(), vec![1.23, 5.68, 9.1]);
+
+        let result = operands.map_in_place(&mut data, |_| Err(ProgramError::InvalidArgument));
+        assert_eq!(result, Err(ProgramError::InvalidArgument));
+    }
+
+    #[test]
+    fn test_running_stats_over_ten_kib_account() {
+        let (values, data, operands) = ten_kib_dataset();
+        let mut streamed = RunningStats::new();
+        streamed.extend(operands.iter(&data).unwrap()).unwrap();
+        let mut chunked = RunningStats::new();
+        for chunk in operands.chunks::<32>(&data).unwrap() {
+            chunked.extend(chunk.iter().copied()).unwrap();
+        }
+        assert_eq!(streamed, chunked);
+        assert_eq!(streamed.count(), values.len() as u64);
+        assert_eq!(streamed.min(), Some(0.1));
+        assert_eq!(streamed.max(), values.last().copied());
+    }
+
+    #[tokio::test]
+    async fn test_stats_account_instruction_over_ten_kib() {
+        let (values, data, operands) = ten_kib_dataset();
+        let instruction_data = encode(Opcode::StatsAccount, &operands.pack());
+        let return_data = return_data_with_operand_account(data, instruction_data).await;
+
+        let stats = RunningStats::unpack(&return_data).unwrap();
+        let mut expected = RunningStats::new();
+        expected.extend(values.iter().copied()).unwrap();
+        assert_eq!(stats, expected);
+    }
+
+    #[tokio::test]
+    async fn test_batch_quantize_streams_ten_kib_account() {
+        // Misaligned offset: the streaming path does not need a view
+        let (values, data, operands) = ten_kib_dataset();
+        let mut payload = operands.pack().to_vec();
+        payload.extend_from_slice(&[2, 4]); // 2 decimals, half-even
+        let instruction_data = encode(Opcode::BatchQuantizeAccount, &payload);
+        let return_data = return_data_with_operand_account(data, instruction_data).await;
+
+        let results = BatchResults::decode(&return_data).unwrap();
+        assert!(results.truncated);
+        assert_eq!(results.total as usize, values.len());
+        assert_eq!(results.values.len(), MAX_BATCH_RESULTS);
+        for (result, value) in results.values.iter().zip(&values) {
+            assert_eq!(
+                *result,
+                quantize(*value, 2, RoundingMode::HalfEven).unwrap()
+            );
+        }
+    }
+}
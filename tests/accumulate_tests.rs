@@ -0,0 +1,60 @@
+use solana_floats::accumulate::*;
+use solana_floats::array_ops::{sum_doubles, sum_doubles_compensated};
+use solana_floats::error::FloatError;
+
+#[cfg(test)]
+mod accumulate_tests {
+    use super::*;
+
+    #[test]
+    fn test_thousand_tenths() {
+        let naive = sum_doubles(&[0.1; 1000]);
+        assert_eq!(naive, 99.9999999999986);
+        for mut sum in [CompensatedSum::kahan(), CompensatedSum::neumaier()] {
+            sum.extend([0.1; 1000]).unwrap();
+            assert_eq!(sum.value(), 100.0, "{:?}", sum.method());
+        }
+    }
+
+    #[test]
+    fn test_neumaier_handles_large_terms() {
+        let values = [1.0, 1e100, 1.0, -1e100];
+        let mut kahan = CompensatedSum::kahan();
+        let mut neumaier = CompensatedSum::neumaier();
+        kahan.extend(values).unwrap();
+        neumaier.extend(values).unwrap();
+        assert_eq!(kahan.value(), 0.0);
+        assert_eq!(neumaier.value(), 2.0);
+        assert_eq!(neumaier.value(), sum_doubles_compensated(&values));
+    }
+
+    #[test]
+    fn test_matches_slice_sum_bit_for_bit() {
+        let values: Vec<f64> = (1..=500).map(|i| 1.0 / i as f64).collect();
+        let mut sum = CompensatedSum::default();
+        assert_eq!(sum.method(), Compensation::Neumaier);
+        sum.extend(values.iter().copied()).unwrap();
+        assert_eq!(
+            sum.value().to_bits(),
+            sum_doubles_compensated(&values).to_bits()
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_finite() {
+        let mut sum = CompensatedSum::kahan();
+        sum.push(1.5).unwrap();
+        assert_eq!(sum.push(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(sum.push(f64::NEG_INFINITY), Err(FloatError::OutOfRange));
+        sum.push(f64::MAX).unwrap();
+        assert_eq!(sum.push(f64::MAX), Err(FloatError::Overflow));
+        // Failed pushes leave the sum as it was
+        let mut expected = CompensatedSum::kahan();
+        expected.extend([1.5, f64::MAX]).unwrap();
+        assert_eq!(sum, expected);
+        assert_eq!(
+            CompensatedSum::neumaier().extend([1.0, f64::NAN, 2.0]),
+            Err(FloatError::NaNOperand)
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use solana_floats::amm::*;
+use solana_floats::error::FloatError;
+
+#[cfg(test)]
+mod amm_tests {
+    use super::*;
+
+    fn pool(reserve_in: u64, reserve_out: u64, fee_bps: u16) -> PoolState {
+        PoolState {
+            reserve_in,
+            reserve_out,
+            fee_bps,
+        }
+    }
+
+    // Real-valued route output; f64 is far more precise than one token unit at
+    // these sizes.
+    fn exact_route_out(hops: &[PoolState], amount_in: u64) -> f64 {
+        hops.iter().fold(amount_in as f64, |x, p| {
+            let x = x * (10_000.0 - p.fee_bps as f64) / 10_000.0;
+            p.reserve_out as f64 * x / (p.reserve_in as f64 + x)
+        })
+    }
+
+    #[test]
+    fn test_single_swap_matches_constant_product() {
+        // No fee: 1000 * 100 / (1000 + 100) = 90.9
+        assert_eq!(swap_output(&pool(1_000, 1_000, 0), 100), Ok(90));
+        // 30 bps fee leaves 997 of 1000 in: 1_000_000 * 997 / 1_000_997 = 996.00...
+        assert_eq!(swap_output(&pool(1_000_000, 1_000_000, 30), 1_000), Ok(996));
+        assert_eq!(swap_output(&pool(1_000, 1_000, 0), 0), Ok(0));
+        // A 100% fee swallows the input
+        assert_eq!(swap_output(&pool(1_000, 1_000, 10_000), 500), Ok(0));
+    }
+
+    #[test]
+    fn test_output_never_drains_the_pool() {
+        let out = swap_output(&pool(1, u64::MAX, 0), u64::MAX).unwrap();
+        assert!(out < u64::MAX);
+    }
+
+    #[test]
+    fn test_invalid_pools_are_rejected() {
+        assert_eq!(
+            swap_output(&pool(0, 1_000, 30), 10),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            swap_output(&pool(1_000, 0, 30), 10),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            swap_output(&pool(1_000, 1_000, 10_001), 10),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(estimate_route_out(&[], 10), Err(FloatError::OutOfRange));
+        assert_eq!(
+            estimate_route_out(&[pool(1_000, 1_000, 30), pool(0, 1, 0)], 10),
+            Err(FloatError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_single_hop_route_equals_swap_output() {
+        let p = pool(5_000_000, 2_000_000, 25);
+        let estimate = estimate_route_out(&[p], 12_345).unwrap();
+        assert_eq!(Ok(estimate.amount_out), swap_output(&p, 12_345));
+    }
+
+    #[test]
+    fn test_route_chains_hops_in_order() {
+        let hops = [
+            pool(1_000_000, 2_000_000, 30),
+            pool(4_000_000, 1_000_000, 5),
+        ];
+        let first = swap_output(&hops[0], 50_000).unwrap();
+        let second = swap_output(&hops[1], first).unwrap();
+        assert_eq!(
+            estimate_route_out(&hops, 50_000).unwrap().amount_out,
+            second
+        );
+    }
+
+    #[test]
+    fn test_exact_output_lies_within_bound() {
+        let routes: [&[PoolState]; 4] = [
+            &[pool(1_000_000_000, 3_000_000_000, 30)],
+            &[pool(10_000, 10_000_000, 100), pool(50_000_000, 7_000, 30)],
+            &[
+                pool(1_000_000, 1_000_003, 1),
+                pool(2_000_000, 999_999, 25),
+                pool(123_456, 654_321, 30),
+            ],
+            &[pool(7, 1_000_000_000_000, 0), pool(1_000_000_000_000, 9, 0)],
+        ];
+        for hops in routes {
+            for amount_in in [1_u64, 17, 999, 123_456, 5_000_000] {
+                let estimate = estimate_route_out(hops, amount_in).unwrap();
+                let exact = exact_route_out(hops, amount_in);
+                assert!(
+                    estimate.amount_out as f64 <= exact + 1e-6,
+                    "{:?} in {}: {} > {}",
+                    hops,
+                    amount_in,
+                    estimate.amount_out,
+                    exact
+                );
+                assert!(
+                    exact < (estimate.amount_out + estimate.error_bound) as f64,
+                    "{:?} in {}: {} outside bound {:?}",
+                    hops,
+                    amount_in,
+                    exact,
+                    estimate
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bound_grows_through_cheap_to_dear_hops() {
+        // A hop paying out many units per unit in magnifies earlier rounding
+        let balanced = estimate_route_out(&[pool(1_000, 1_000, 30); 3], 100).unwrap();
+        let magnifying =
+            estimate_route_out(&[pool(1_000, 1_000, 30), pool(1_000, 1_000_000, 30)], 100).unwrap();
+        assert!(balanced.error_bound <= 7);
+        assert!(magnifying.error_bound > 1_000);
+    }
+
+    #[test]
+    fn test_bound_overflow_is_reported() {
+        // One unit in is worth u64::MAX units out, so the bound exceeds u64
+        let hops = [pool(1, u64::MAX, 0)];
+        assert_eq!(estimate_route_out(&hops, 1_000), Err(FloatError::Overflow));
+    }
+}
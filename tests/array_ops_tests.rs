@@ -0,0 +1,292 @@
+use solana_floats::array_ops::*;
+use solana_floats::error::FloatError;
+
+#[cfg(test)]
+mod array_ops_tests {
+    use super::*;
+
+    #[test]
+    fn test_sum_doubles_accumulates_error() {
+        let values = [0.1_f64; 10];
+        let naive = sum_doubles(&values);
+        let compensated = sum_doubles_compensated(&values);
+
+        println!("naive: {:.17}, compensated: {:.17}", naive, compensated);
+
+        // ❌ The naive loop drifts away from 1.0
+        assert_ne!(naive, 1.0);
+        // ✅ The compensated sum recovers the correctly rounded result
+        assert_eq!(compensated, 1.0);
+    }
+
+    #[test]
+    fn test_compensated_sum_handles_cancellation() {
+        // Naive summation loses the 1.0 entirely
+        let values = [1e100_f64, 1.0, -1e100];
+        assert_eq!(sum_doubles(&values), 0.0);
+        assert_eq!(sum_doubles_compensated(&values), 1.0);
+    }
+
+    #[test]
+    fn test_sums_are_deterministic() {
+        let values: Vec<f64> = (1..=1000).map(|i| 1.0 / i as f64).collect();
+        let first = sum_doubles_compensated(&values);
+        for _ in 0..5 {
+            assert_eq!(sum_doubles_compensated(&values).to_bits(), first.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_nan_and_infinity_propagate() {
+        assert!(sum_doubles(&[1.0, f64::NAN, 2.0]).is_nan());
+        assert!(sum_doubles_compensated(&[1.0, f64::NAN, 2.0]).is_nan());
+        assert!(product_doubles(&[1.0, f64::NAN]).is_nan());
+        assert_eq!(
+            sum_doubles_compensated(&[1.0, f64::INFINITY]),
+            f64::INFINITY
+        );
+        assert!(sum_doubles_compensated(&[f64::INFINITY, f64::NEG_INFINITY]).is_nan());
+    }
+
+    #[test]
+    fn test_product_doubles() {
+        assert_eq!(product_doubles(&[]), 1.0);
+        assert_eq!(product_doubles(&[1.5, 2.0, -4.0]), -12.0);
+    }
+
+    #[test]
+    fn test_min_max_total_order() {
+        let values = [3.0, -0.0, 0.0, -7.5, 12.0];
+        assert_eq!(min_total(&values), Some(-7.5));
+        assert_eq!(max_total(&values), Some(12.0));
+        assert_eq!(min_total(&[]), None);
+
+        // -0.0 sorts below +0.0 under total ordering
+        assert!(min_total(&[0.0, -0.0]).unwrap().is_sign_negative());
+        assert!(max_total(&[-0.0, 0.0]).unwrap().is_sign_positive());
+    }
+
+    #[test]
+    fn test_min_max_total_surface_nan() {
+        // Positive NaN is the maximum, negative NaN the minimum: never skipped
+        let values = [1.0, f64::NAN, -1.0];
+        assert!(max_total(&values).unwrap().is_nan());
+        assert_eq!(min_total(&values), Some(-1.0));
+        assert!(min_total(&[1.0, -f64::NAN]).unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_normalize_index_weights() {
+        let weights = normalize(&[50.0, 30.0, 20.0]).unwrap();
+        assert_eq!(weights, vec![0.5, 0.3, 0.2]);
+    }
+
+    #[test]
+    fn test_normalize_does_not_overflow() {
+        let weights = normalize(&[f64::MAX, f64::MAX, f64::MAX / 2.0]).unwrap();
+        assert_eq!(weights, vec![0.4, 0.4, 0.2]);
+    }
+
+    #[test]
+    fn test_normalize_rejects_invalid_weights() {
+        assert_eq!(normalize(&[]), Err(FloatError::OutOfRange));
+        assert_eq!(normalize(&[1.0, -1.0]), Err(FloatError::OutOfRange));
+        assert_eq!(normalize(&[0.0, 0.0]), Err(FloatError::DivisionByZero));
+        assert_eq!(normalize(&[1.0, f64::NAN]), Err(FloatError::NaNOperand));
+    }
+
+    #[test]
+    fn test_softmax_large_scores_do_not_overflow() {
+        // A naive exp(1000.0) is infinity and the result would be NaN
+        assert!(((1000.0_f64).exp() / (1000.0_f64).exp()).is_nan());
+
+        let weights = softmax(&[1000.0, 1000.0]).unwrap();
+        assert_eq!(weights, vec![0.5, 0.5]);
+
+        let weights = softmax(&[1000.0, 0.0, -1000.0]).unwrap();
+        assert_eq!(weights[0], 1.0);
+        assert_eq!(weights[2], 0.0);
+    }
+
+    #[test]
+    fn test_softmax_routing_weights() {
+        let weights = softmax(&[1.0, 2.0, 3.0]).unwrap();
+        assert!(weights[0] < weights[1] && weights[1] < weights[2]);
+        assert!((sum_doubles(&weights) - 1.0).abs() <= 2.0 * f64::EPSILON);
+        assert_eq!(softmax(&[]), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_log_sum_exp_spans_large_ranges() {
+        // ln(e^1000 + e^1000) = 1000 + ln 2
+        assert_eq!(
+            log_sum_exp(&[1000.0, 1000.0]),
+            Ok(1000.0 + std::f64::consts::LN_2)
+        );
+        assert_eq!(
+            log_sum_exp(&[-1000.0, -1000.0]),
+            Ok(-1000.0 + std::f64::consts::LN_2)
+        );
+        // e^-1000 is far below the last bit of e^1000
+        assert_eq!(log_sum_exp(&[1000.0, -1000.0]), Ok(1000.0));
+        let value = log_sum_exp(&[1.0, 2.0, 3.0]).unwrap();
+        assert!((value - 3.40760596444438).abs() < 1e-14);
+        assert_eq!(log_sum_exp(&[]), Err(FloatError::OutOfRange));
+        assert_eq!(log_sum_exp(&[1.0, f64::NAN]), Err(FloatError::NaNOperand));
+        assert_eq!(log_sum_exp(&[f64::INFINITY]), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_quantize_weights_sum_exactly() {
+        // Thirds cannot be represented exactly; units must still add up
+        let units = quantize_weights(&[1.0, 1.0, 1.0], 6).unwrap();
+        assert_eq!(units, vec![333_334, 333_333, 333_333]);
+        assert_eq!(units.iter().sum::<u64>(), 1_000_000);
+
+        let units = quantize_weights(&[0.1, 0.2, 0.7], 2).unwrap();
+        assert_eq!(units, vec![10, 20, 70]);
+
+        // Many tiny weights against a coarse scale
+        let weights: Vec<f64> = (1..=37).map(|i| i as f64).collect();
+        let units = quantize_weights(&weights, 2).unwrap();
+        assert_eq!(units.iter().sum::<u64>(), 100);
+    }
+
+    #[test]
+    fn test_distribute_sums_to_total() {
+        let rewards = distribute(1_000_000_007, &[3.0, 3.0, 3.0]).unwrap();
+        assert_eq!(rewards, vec![333_333_336, 333_333_336, 333_333_335]);
+
+        // Stakes that do not divide the reward evenly, including a zero stake
+        let stakes = [1_500.5, 0.0, 42.25, 10_000_000.0, 7.0];
+        let rewards = distribute(987_654_321, &stakes).unwrap();
+        assert_eq!(rewards[1], 0);
+        assert_eq!(rewards.iter().sum::<u64>(), 987_654_321);
+
+        // Totals far beyond 2^53 still add up exactly
+        let rewards = distribute(u64::MAX, &[1.0, 2.0, 4.0]).unwrap();
+        assert_eq!(
+            rewards.iter().map(|&r| r as u128).sum::<u128>(),
+            u64::MAX as u128
+        );
+        assert_eq!(distribute(0, &[1.0, 2.0]), Ok(vec![0, 0]));
+    }
+
+    #[test]
+    fn test_distribute_rejects_bad_weights() {
+        assert_eq!(distribute(10, &[]), Err(FloatError::OutOfRange));
+        assert_eq!(distribute(10, &[1.0, -1.0]), Err(FloatError::OutOfRange));
+        assert_eq!(distribute(10, &[0.0, 0.0]), Err(FloatError::DivisionByZero));
+        assert!(distribute(10, &[1.0, f64::NAN]).is_err());
+    }
+
+    #[test]
+    fn test_dot_portfolio_valuation() {
+        let quantities = [10.0, 2.5, 1_000.0];
+        let prices = [150.25, 3_000.0, 0.75];
+        assert_eq!(dot(&quantities, &prices), Ok(1502.5 + 7500.0 + 750.0));
+        assert_eq!(dot(&quantities, &prices[..2]), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_dot_compensated_recovers_cancelled_terms() {
+        let xs = [1e16, 1.0, -1e16];
+        let ys = [1.0, 1.0, 1.0];
+        // The naive product sum loses the 1.0 to rounding
+        assert_eq!(dot(&xs, &ys), Ok(0.0));
+        assert_eq!(dot_compensated(&xs, &ys), Ok(1.0));
+
+        // Products whose low bits matter: (1 + 2^-30)^2 - 1 - 2^-29 = 2^-60
+        let a = 1.0 + 2.0_f64.powi(-30);
+        let xs = [a, -1.0, -(2.0_f64.powi(-29))];
+        let ys = [a, 1.0, 1.0];
+        assert_eq!(dot(&xs, &ys), Ok(0.0));
+        assert_eq!(dot_compensated(&xs, &ys), Ok(2.0_f64.powi(-60)));
+    }
+
+    #[test]
+    fn test_dot_compensated_handles_extreme_magnitudes() {
+        let pairs = [
+            (1e301, 1e-301),
+            (-1e301, 1e-301),
+            (1e-301, 1e301),
+            (f64::MAX, 0.5),
+            (1e300, 1e-300),
+        ];
+        for (x, y) in pairs {
+            let compensated = dot_compensated(&[x], &[y]).unwrap();
+            assert!(compensated.is_finite(), "{x:e} * {y:e}");
+            assert_eq!(compensated, dot(&[x], &[y]).unwrap(), "{x:e} * {y:e}");
+        }
+        let xs = [1e301, 1.0, -1e-301];
+        let ys = [1e-301, 2.0, 1e301];
+        let compensated = dot_compensated(&xs, &ys).unwrap();
+        assert!((compensated - 2.0).abs() <= 4.0 * f64::EPSILON);
+    }
+
+    #[test]
+    fn test_norm2_avoids_overflow_and_underflow() {
+        // Squaring 1e200 overflows; squaring 1e-200 underflows to zero
+        assert!((1e200_f64 * 1e200).is_infinite());
+        let big = norm2(&[3e200, 4e200]);
+        let tiny = norm2(&[3e-200, 4e-200]);
+        assert!((big / 5e200 - 1.0).abs() <= 2.0 * f64::EPSILON);
+        assert!((tiny / 5e-200 - 1.0).abs() <= 2.0 * f64::EPSILON);
+
+        let big = norm2_compensated(&[3e200, 4e200]);
+        assert!((big / 5e200 - 1.0).abs() <= 2.0 * f64::EPSILON);
+    }
+
+    #[test]
+    fn test_norm2_special_values() {
+        assert_eq!(norm2(&[]), 0.0);
+        assert_eq!(norm2(&[3.0, 4.0]), 5.0);
+        assert_eq!(norm2_compensated(&[3.0, 4.0]), 5.0);
+        assert!(norm2(&[1.0, f64::NAN]).is_nan());
+        assert!(norm2_compensated(&[1.0, f64::NAN]).is_nan());
+        assert_eq!(norm2(&[1.0, f64::NEG_INFINITY]), f64::INFINITY);
+        assert_eq!(norm2_compensated(&[f64::INFINITY]), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_cumsum_matches_compensated_sum_at_every_prefix() {
+        let volumes: Vec<f64> = (1..=500).map(|i| 0.1 * i as f64).collect();
+        let prefix = cumsum(&volumes);
+        assert_eq!(prefix.len(), volumes.len());
+        for end in [1, 10, 137, 500] {
+            let expected = sum_doubles_compensated(&volumes[..end]);
+            assert_eq!(prefix[end - 1].to_bits(), expected.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_cumsum_does_not_drift() {
+        let funding = [0.1_f64; 1_000];
+        let prefix = cumsum(&funding);
+        // A naive running sum ends at 99.9999999999986
+        let naive = funding.iter().fold(0.0, |acc, &rate| acc + rate);
+        assert_ne!(naive, 100.0);
+        assert_eq!(prefix[999], 100.0);
+        assert_eq!(prefix[9], 1.0);
+    }
+
+    #[test]
+    fn test_range_sum_queries() {
+        let prefix = cumsum_compensated(&[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(range_sum(&prefix, 0, 4), Ok(10.0));
+        assert_eq!(range_sum(&prefix, 1, 3), Ok(5.0));
+        assert_eq!(range_sum(&prefix, 2, 2), Ok(0.0));
+        assert_eq!(range_sum(&prefix, 3, 2), Err(FloatError::OutOfRange));
+        assert_eq!(range_sum(&prefix, 0, 5), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_range_sum_is_not_swamped_by_the_prefix() {
+        let values = [1e17, 0.1, 0.2, 0.3];
+        // The rounded prefix is 1e17 at every step, so differencing it loses the range
+        let rounded = cumsum(&values);
+        assert_eq!(rounded[3] - rounded[0], 0.0);
+        let range = range_sum(&cumsum_compensated(&values), 1, 4).unwrap();
+        assert!((range - 0.6).abs() <= f64::EPSILON);
+    }
+}
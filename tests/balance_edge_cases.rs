@@ -1,7 +1,10 @@
+#![allow(clippy::excessive_precision, unused_mut)]
+
+use solana_floats::feature_gate::FeatureGate;
 use solana_floats::float_ops::*;
 use solana_program_test::*;
 use solana_sdk::{
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     signature::Signer,
     transaction::Transaction,
@@ -196,7 +199,10 @@ mod balance_edge_cases {
         let instruction = Instruction::new_with_bytes(
             program_id,
             &instruction_data,
-            vec![],
+            vec![AccountMeta::new_readonly(
+                FeatureGate::find_address(&program_id).0,
+                false,
+            )],
         );
 
         let transaction = Transaction::new_signed_with_payer(
@@ -0,0 +1,94 @@
+use solana_floats::batch::*;
+use solana_floats::instruction::{Opcode, encode};
+use solana_program_test::*;
+
+mod common;
+
+#[cfg(test)]
+mod batch_quantize_tests {
+    use super::*;
+    use common::process_with_metadata;
+
+    const HALF_EVEN: u8 = 4;
+
+    fn batch_quantize_data(decimals: u8, mode: u8, values: &[f64]) -> Vec<u8> {
+        let mut payload = vec![decimals, mode];
+        for value in values {
+            payload.extend_from_slice(&value.to_le_bytes());
+        }
+        encode(Opcode::BatchQuantize, &payload)
+    }
+
+    #[tokio::test]
+    async fn test_batch_quantize_instruction() {
+        let values = [0.125, 0.135, 100.0 / 3.0];
+        let result =
+            process_with_metadata(&batch_quantize_data(2, HALF_EVEN, &values), vec![]).await;
+        assert!(result.result.is_ok());
+
+        let return_data = result.metadata.unwrap().return_data.unwrap();
+        let results = BatchResults::decode(&return_data.data).unwrap();
+        assert_eq!(results.total, 3);
+        assert!(!results.truncated);
+        assert_eq!(results.values, vec![0.12, 0.14, 33.33]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_quantize_truncates_oversized_results() {
+        let values: Vec<f64> = (0..MAX_BATCH_RESULTS + 3)
+            .map(|i| i as f64 * 0.001)
+            .collect();
+        let result =
+            process_with_metadata(&batch_quantize_data(2, HALF_EVEN, &values), vec![]).await;
+        assert!(result.result.is_ok());
+
+        let return_data = result.metadata.unwrap().return_data.unwrap();
+        assert!(return_data.data.len() <= 1024);
+        let results = BatchResults::decode(&return_data.data).unwrap();
+        assert!(results.truncated);
+        assert_eq!(results.total as usize, MAX_BATCH_RESULTS + 3);
+        assert_eq!(results.values.len(), MAX_BATCH_RESULTS);
+    }
+
+    #[tokio::test]
+    async fn test_chunked_batches_recover_full_result_set() {
+        let values: Vec<f64> = (0..300).map(|i| i as f64 / 7.0).collect();
+        let mut collected = Vec::new();
+        for chunk in chunk_values(&values) {
+            let result =
+                process_with_metadata(&batch_quantize_data(4, HALF_EVEN, chunk), vec![]).await;
+            let return_data = result.metadata.unwrap().return_data.unwrap();
+            let results = BatchResults::decode(&return_data.data).unwrap();
+            assert!(!results.truncated);
+            collected.extend(results.values);
+        }
+
+        let mut expected = values.clone();
+        solana_floats::quantize::quantize_slice(
+            &mut expected,
+            4,
+            solana_floats::quantize::RoundingMode::HalfEven,
+        )
+        .unwrap();
+        assert_eq!(collected, expected);
+    }
+
+    #[tokio::test]
+    async fn test_batch_quantize_rejects_bad_payloads() {
+        // Invalid rounding mode
+        let result = process_with_metadata(&batch_quantize_data(2, 9, &[1.0]), vec![]).await;
+        assert!(result.result.is_err());
+
+        // Truncated value
+        let mut instruction_data = batch_quantize_data(2, HALF_EVEN, &[1.0]);
+        instruction_data.pop();
+        let result = process_with_metadata(&instruction_data, vec![]).await;
+        assert!(result.result.is_err());
+
+        // NaN operand
+        let result =
+            process_with_metadata(&batch_quantize_data(2, HALF_EVEN, &[1.0, f64::NAN]), vec![])
+                .await;
+        assert!(result.result.is_err());
+    }
+}
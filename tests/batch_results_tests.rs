@@ -0,0 +1,112 @@
+use solana_floats::batch::*;
+use solana_floats::error::FloatError;
+use solana_floats::operands::AccountOperands;
+use solana_program::program_error::ProgramError;
+
+#[cfg(test)]
+mod batch_results_tests {
+    use super::*;
+
+    #[test]
+    fn test_results_fit_in_return_data() {
+        let results = vec![1.5_f64; MAX_BATCH_RESULTS];
+        let encoded = encode_batch_results(&results);
+        assert!(encoded.len() <= 1024);
+        assert_eq!(MAX_BATCH_RESULTS, 127);
+    }
+
+    #[test]
+    fn test_round_trip_preserves_bit_patterns() {
+        let results = [0.1 + 0.2, -0.0, f64::MIN_POSITIVE / 2.0, f64::MAX];
+        let decoded = BatchResults::decode(&encode_batch_results(&results)).unwrap();
+        assert_eq!(decoded.total, 4);
+        assert!(!decoded.truncated);
+        for (a, b) in decoded.values.iter().zip(&results) {
+            assert_eq!(a.to_bits(), b.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_truncation_is_explicit() {
+        let results: Vec<f64> = (0..200).map(|i| i as f64).collect();
+        let decoded = BatchResults::decode(&encode_batch_results(&results)).unwrap();
+        assert!(decoded.truncated);
+        assert_eq!(decoded.total, 200);
+        assert_eq!(decoded.values, results[..MAX_BATCH_RESULTS].to_vec());
+    }
+
+    #[test]
+    fn test_decode_rejects_inconsistent_data() {
+        let mut encoded = encode_batch_results(&[1.0, 2.0]);
+        // Flag says complete but a value is missing
+        encoded.truncate(encoded.len() - 8);
+        assert_eq!(
+            BatchResults::decode(&encoded),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        let mut encoded = encode_batch_results(&[1.0]);
+        encoded.push(0);
+        assert_eq!(
+            BatchResults::decode(&encoded),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        let mut encoded = encode_batch_results(&[1.0]);
+        encoded[0] = 2;
+        assert_eq!(
+            BatchResults::decode(&encoded),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_chunk_account_operands() {
+        let chunks = chunk_account_operands(AccountOperands {
+            offset: 16,
+            count: 300,
+        })
+        .unwrap();
+        assert_eq!(
+            chunks,
+            vec![
+                AccountOperands {
+                    offset: 16,
+                    count: 127
+                },
+                AccountOperands {
+                    offset: 16 + 127 * 8,
+                    count: 127
+                },
+                AccountOperands {
+                    offset: 16 + 254 * 8,
+                    count: 46
+                },
+            ]
+        );
+        assert!(
+            chunk_account_operands(AccountOperands {
+                offset: 0,
+                count: 0
+            })
+            .unwrap()
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_chunk_account_operands_rejects_ranges_past_u32() {
+        let last = AccountOperands {
+            offset: u32::MAX - 8 * 200,
+            count: 199,
+        };
+        assert_eq!(chunk_account_operands(last).unwrap().len(), 2);
+        assert_eq!(
+            chunk_account_operands(AccountOperands {
+                offset: u32::MAX - 8 * 200,
+                count: 201,
+            }),
+            Err(FloatError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,436 @@
+use solana_floats::big_decimal::{BigDecimal256, MAX_DIGITS};
+use solana_floats::error::FloatError;
+use solana_floats::quantize::RoundingMode;
+use solana_floats::u256::U256;
+
+#[cfg(test)]
+mod big_decimal_tests {
+    use super::*;
+
+    fn big(text: &str) -> BigDecimal256 {
+        text.parse().unwrap()
+    }
+
+    // Generated with Python's decimal module:
+    // Context(prec=75, Emax=999999999, Emin=-999999999, rounding=ROUND_HALF_EVEN)
+    const REFERENCE: &[(&str, char, &str, &str)] = &[
+        (
+            "9.560160006447786681984702254352360760663324077439642493952610773702609E+72",
+            '/',
+            "9.6E-10",
+            "9.958500006716444460400731514950375792357629247332960931200636222606884375E+81",
+        ),
+        ("0.002", '*', "0.00074814", "0.00000149628"),
+        (
+            "781670933928945.337",
+            '/',
+            "73810873845738507162209221702260973192878143094264766.61171115784161106",
+            "1.05901866920394865605427795203373046403098565616767026959835057952262448544E-38",
+        ),
+        (
+            "8.56581366590409672677047165421755480328964020229563930209429976977556559840E+118",
+            '-',
+            "10895173980947477133686148352451308004045951602305293519523.7792257918786102",
+            "8.56581366590409672677047165421755480328964020229563930209429868025816750365E+118",
+        ),
+        ("3E-19", '*', "-3.4981E+58", "-1.04943E+40"),
+        (
+            "1723050687140149212.11413267974",
+            '-',
+            "90728806943613857576938421929256.851747315931432246",
+            "-90728806943612134526251281780044.737614636191432246",
+        ),
+        (
+            "-364543581607987337366111309142743659554854689088660910227529460735003.09055",
+            '*',
+            "863033442126363598673502056656836129015070390606129409.14915806184729056897",
+            "-3.14613302040214245304803014726638450859791218303918227694869137717511063979E+122",
+        ),
+        (
+            "4745376845033298324788790094479712095547906211667279259235935348.73336405392",
+            '*',
+            "-11160332699318924848988990673971542650.716978953622",
+            "-5.29599843742159936316603540274595842213522212502070572400507160473031358267E+100",
+        ),
+        (
+            "8.2702E+7",
+            '/',
+            "3E-9",
+            "27567333333333333.3333333333333333333333333333333333333333333333333333333333",
+        ),
+        (
+            "-9710426455670731698099592371845769214.9674186949857",
+            '*',
+            "-83791128510801298897030099136073274581685573485047736102.49687817692662",
+            "8.13647591041791051969814319360033453315974833478593563764822334288166182252E+92",
+        ),
+        (
+            "4565192689914211473487858874257258769340215287856144786585158.82511415868813",
+            '+',
+            "-797282316427980847",
+            "4565192689914211473487858874257258769340214490573828358604311.82511415868813",
+        ),
+        (
+            "596060912560488408835905711226.51316292692767092357",
+            '-',
+            "8.7418461760578372798060620998424803856192123129089E+51",
+            "-8741846176057837279805466038929919897210376407197673.48683707307232907643",
+        ),
+        (
+            "4.9186311287499242587915620288128440457205420113688E+89",
+            '+',
+            "-8.68603188038399395E+42",
+            "4.9186311287499242587915620288128440457205420112819396811961600605E+89",
+        ),
+        (
+            "6423800.21810368188",
+            '-',
+            "-3.3501418224023386633516751069556260305069213495514617711367773861039966641E+76",
+            "3.35014182240233866335167510695562603050692134955146177113677738610399730648E+76",
+        ),
+        (
+            "453529552066108353",
+            '*',
+            "2.8837995118119619631502054291275828285331832190941310156732839792382049910E+328",
+            "1.30788830084054105349664602806270716359883013015508923382916061488505840082E+346",
+        ),
+        (
+            "-0.000002",
+            '-',
+            "-2.86574883087789555907686279032324592312483872059141684443383153512845428798E-58",
+            "-0.00000199999999999999999999999999999999999999999999999999971342511691221044409231",
+        ),
+        (
+            "5E-13",
+            '+',
+            "-3.34233967216136128E-41",
+            "4.999999999999999999999999999665766032783863872E-13",
+        ),
+        (
+            "574582147260891559.123363377514",
+            '*',
+            "5E+1",
+            "28729107363044577956.16816887570",
+        ),
+        (
+            "-41809740016801962585916065542797.400910340069449303",
+            '-',
+            "-5.0057E-312",
+            "-41809740016801962585916065542797.4009103400694493030000000000000000000000000",
+        ),
+        (
+            "7E+13",
+            '-',
+            "4437238874656637361472058269523476271066368477659965696936286380569.21240868",
+            "-4437238874656637361472058269523476271066368477659965626936286380569.21240868",
+        ),
+        (
+            "9801523926871977966529656659595460738473324834541453373449048832.76873897335",
+            '-',
+            "-12022909561541707862978734442299579273804539358042248892380.5408959503658104",
+            "9801535949781539508237519638329903038052598639080811415697941213.30963492372",
+        ),
+        (
+            "3.5033E-56",
+            '-',
+            "1.77279047432513696E-89",
+            "3.50329999999999999999999999999999822720952567486304E-56",
+        ),
+        (
+            "-238868350427906410803343658594892428329131191627602009149002126580347.660323",
+            '*',
+            "0.0026400",
+            "-630612445129672924520827258690516010788906345896869304153365614172.117823253",
+        ),
+        (
+            "7.56154366423151582937364369766E+30",
+            '*',
+            "7.73023642333004133364084549044005802177475738510922036160558325003894623021E+244",
+            "5.84525202498429679240516488508011689727856356076858756404067854439389197163E+275",
+        ),
+        (
+            "55885260221232842092480155302763302073150930236933052418843912382524595.2909",
+            '+',
+            "-2.7E+5",
+            "55885260221232842092480155302763302073150930236933052418843912382254595.2909",
+        ),
+        (
+            "-79537.7989925277265",
+            '-',
+            "-6.3E+12",
+            "6299999920462.2010074722735",
+        ),
+        (
+            "-7.02870205498821753E+113",
+            '/',
+            "-6.25841790726061642E+21",
+            "1.12307969188730057130165831248370277145464138391540192334109055013569750447E+92",
+        ),
+        ("-0E+111", '-', "3.3E-16", "-3.3E-16"),
+        (
+            "6.63179837138567889673918606355149955249261193387400689399726346250323453089E+251",
+            '+',
+            "-8.46690816564141045795997415659473038590671030854640489896090500227923443718E+178",
+            "6.63179837138567889673918606355149955249261193387400689399726346250323453004E+251",
+        ),
+        (
+            "-9.555124190982804877768357023575218391916186926002086228144736660508330E+71",
+            '*',
+            "8.3E+84",
+            "-7.93075307851572804854773632956743126529043514858173156936013142822191390E+156",
+        ),
+        (
+            "1830094054124083920904081765696214521335372021371973711317271337510.70332519",
+            '-',
+            "378070645659491792396457371931455761796096611912676589836415666714287533147",
+            "-378070643829397738272373451027373996099882090577304568464441955397016195636",
+        ),
+        (
+            "4.56147398694035947639952300033632169050611357312614988744129656691493353622E+77",
+            '+',
+            "2.1012037171953796302798490608412406650542251980586800986127075971385335551E+439",
+            "2.10120371719537963027984906084124066505422519805868009861270759713853355510E+439",
+        ),
+        ("-0.00045431", '*', "6.7744E-13", "-3.077677664E-16"),
+        (
+            "-596574929617409700973690964512554882466700797604161274556392563.160546164129",
+            '+',
+            "6694839146151456458931617590234416219929174730939110312016.704583951931",
+            "-596568234778263549517232032894964648050480868429430335446080546.455962212198",
+        ),
+        (
+            "506494192155390.451142466043818",
+            '/',
+            "8.88582254787471277352158164957792029476718349090079838659911821059647584039E+76",
+            "5.70002596187938025856978330229881743048095782855693063240340927606550612558E-63",
+        ),
+        (
+            "-6.5E-107",
+            '+',
+            "8.8312275738796144183601114486774406013863329032098323660527821086293908058E+214",
+            "8.83122757387961441836011144867744060138633290320983236605278210862939080580E+214",
+        ),
+        (
+            "-1.3E-14",
+            '/',
+            "4.5740360346753954487280575009154199710677760308348E+50",
+            "-2.84212889917089775965710973571318668657461677089366331686609103005695324591E-65",
+        ),
+        (
+            "73001794895511306662958806967987670281093264051820424723417396944367821113.2",
+            '-',
+            "-35555722725415838747131935560077558334467501256708",
+            "73001794895511306662958842523710395696932011183755984800975731411869077821.2",
+        ),
+        (
+            "-577128960789053754972322281711148194016609705629491966525437563908713270.55",
+            '/',
+            "-9.93104486634111542278412452478418207690200989133529036346879236698375177435E+95",
+            "5.81136193176503874178818258928297214185374468656464571216421651342734117458E-25",
+        ),
+        (
+            "8.54676870676226258834825476139259513476377536487123957630794416633009666289E-157",
+            '/',
+            "-69148912684746993965531279064493291030935810227112976207731180115773.0401059",
+            "-1.23599466353540017332708027967679331949524903496892416159902635532622386278E-224",
+        ),
+    ];
+
+    #[test]
+    fn test_matches_reference_implementation() {
+        for &(lhs, op, rhs, expected) in REFERENCE {
+            let (a, b) = (big(lhs), big(rhs));
+            let result = match op {
+                '+' => a.checked_add(b),
+                '-' => a.checked_sub(b),
+                '*' => a.checked_mul(b),
+                _ => a.checked_div(b),
+            }
+            .unwrap();
+            assert_eq!(result, big(expected), "{} {} {}", lhs, op, rhs);
+        }
+    }
+
+    #[test]
+    fn test_pool_invariants_sum_exactly() {
+        // x * y for 100 pools of 9-decimal tokens, summed in whole tokens
+        let mut exact = U256::ZERO;
+        let mut total = BigDecimal256::ZERO;
+        let mut float_total = 0.0f64;
+        for pool in 0..100u64 {
+            let reserve_x = 1_000_000_000_000_000_000 + pool * 7_919_000_001;
+            let reserve_y = 3_000_000_000_000_000_000 - pool * 104_729_000_003;
+            exact = exact
+                .checked_add(U256::from(reserve_x).wrapping_mul(U256::from(reserve_y)))
+                .unwrap();
+            let x = BigDecimal256::from_raw(reserve_x, 9).unwrap();
+            let y = BigDecimal256::from_raw(reserve_y, 9).unwrap();
+            total = total.checked_add(x.checked_mul(y).unwrap()).unwrap();
+            float_total += (reserve_x as f64 / 1e9) * (reserve_y as f64 / 1e9);
+        }
+        let (_, coefficient, exponent) = total.parts();
+        assert_eq!(
+            coefficient.checked_mul(U256::pow10((18 + exponent) as u32).unwrap()),
+            Some(exact)
+        );
+        assert_ne!(float_total, total.to_f64().unwrap());
+        // One controlled rounding at the end
+        let whole_tokens = total.round_to_decimals(0, RoundingMode::Floor).unwrap();
+        assert_eq!(whole_tokens.to_string(), "299999599188327683271");
+    }
+
+    #[test]
+    fn test_rounds_once_to_max_digits() {
+        let third = BigDecimal256::ONE.checked_div(big("3")).unwrap();
+        let (_, coefficient, exponent) = third.parts();
+        assert_eq!(coefficient.to_string(), "3".repeat(MAX_DIGITS as usize));
+        assert_eq!(exponent, -(MAX_DIGITS as i32));
+        assert_eq!(
+            big("2").checked_div(big("3")).unwrap().to_string().pop(),
+            Some('7')
+        );
+        // Exact quotients keep a short coefficient
+        assert_eq!(big("1").checked_div(big("8")).unwrap().to_string(), "0.125");
+        // A tiny addend still decides the rounding of a full-width sum
+        let nines = big(&"9".repeat(MAX_DIGITS as usize));
+        assert_eq!(
+            nines.checked_add(big("0.5")).unwrap().to_string(),
+            "1.00000000000000000000000000000000000000000000000000000000000000000000000000E+75"
+        );
+        assert_eq!(nines.checked_add(big("0.5E-300")).unwrap(), nines);
+        assert_eq!(
+            nines.checked_add(big("0.5")).unwrap(),
+            nines.checked_add(BigDecimal256::ONE).unwrap()
+        );
+        // The same far below the last kept digit: an exact tie rounds to even,
+        // anything past it decides the direction
+        let power = big("1E+80");
+        assert_eq!(power.checked_sub(big("5E+4")), Ok(power));
+        let past_tie = big(&format!("50000.{}1", "0".repeat(59)));
+        assert_eq!(
+            power.checked_sub(past_tie).unwrap().to_string(),
+            format!("9.{}E+79", "9".repeat(MAX_DIGITS as usize - 1))
+        );
+    }
+
+    #[test]
+    fn test_controlled_rounding() {
+        let value = big("-2.345");
+        assert_eq!(
+            value
+                .round_to_decimals(2, RoundingMode::HalfEven)
+                .unwrap()
+                .to_string(),
+            "-2.34"
+        );
+        assert_eq!(
+            value
+                .round_to_decimals(2, RoundingMode::HalfUp)
+                .unwrap()
+                .to_string(),
+            "-2.35"
+        );
+        assert_eq!(
+            value
+                .round_to_decimals(0, RoundingMode::Floor)
+                .unwrap()
+                .to_string(),
+            "-3"
+        );
+        assert_eq!(
+            value
+                .round_to_decimals(0, RoundingMode::Ceil)
+                .unwrap()
+                .to_string(),
+            "-2"
+        );
+        assert_eq!(value.round_to_decimals(5, RoundingMode::Floor), Ok(value));
+        let amount = big("12.3456789");
+        assert_eq!(amount.to_u64(6, RoundingMode::Floor), Ok(12_345_678));
+        assert_eq!(amount.to_u64(6, RoundingMode::Ceil), Ok(12_345_679));
+        assert_eq!(amount.to_u64(9, RoundingMode::Floor), Ok(12_345_678_900));
+        assert_eq!(big("1E-100").to_u64(6, RoundingMode::Ceil), Ok(1));
+        assert_eq!(big("-0.4").to_u64(0, RoundingMode::HalfEven), Ok(0));
+        assert_eq!(
+            big("-0.6").to_u64(0, RoundingMode::HalfEven),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            big("18446744073709551616").to_u64(0, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            big("1E+30").to_u64(0, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_f64_conversions() {
+        assert_eq!(BigDecimal256::try_from(0.1), Ok(big("0.1")));
+        assert_eq!(
+            BigDecimal256::try_from(-1.5e300).unwrap().to_string(),
+            "-1.5E+300"
+        );
+        assert_eq!(
+            BigDecimal256::try_from(f64::NAN),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            BigDecimal256::try_from(f64::INFINITY),
+            Err(FloatError::Overflow)
+        );
+        let third = BigDecimal256::ONE.checked_div(big("3")).unwrap();
+        assert_eq!(third.to_f64(), Ok(1.0 / 3.0));
+        assert_eq!(big("1E+400").to_f64(), Err(FloatError::Overflow));
+        assert_eq!(big("-1E-400").to_f64(), Ok(-0.0));
+    }
+
+    #[test]
+    fn test_parse_display_and_order() {
+        assert_eq!(big("+012.3400").to_string(), "12.3400");
+        assert_eq!(big("1.5e-7").to_string(), "1.5E-7");
+        assert_eq!(BigDecimal256::from(-42i128).to_string(), "-42");
+        assert_eq!(
+            BigDecimal256::from_raw(1_500_000, 6).unwrap().to_string(),
+            "1.500000"
+        );
+        assert_eq!(format!("{:?}", big("2.50")), "BigDecimal256(2.50)");
+        assert_eq!(big("1.0"), big("1.00"));
+        assert_eq!(big("-0"), BigDecimal256::ZERO);
+        assert!(big("-1E+100") < big("-1E+99"));
+        assert!(big("0.1") > big("0.0999999999"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            BigDecimal256::ONE.checked_div(big("-0.0")),
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(
+            big("1E+2147483647").checked_mul(big("1E+1")),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            big("1E-2147483648").checked_div(big("10")),
+            Err(FloatError::PrecisionLoss)
+        );
+        assert_eq!(
+            BigDecimal256::from_parts(false, U256::pow10(MAX_DIGITS).unwrap(), 0),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            format!("1.{}", "1".repeat(75)).parse::<BigDecimal256>(),
+            Err(FloatError::PrecisionLoss)
+        );
+        for text in ["", ".", "abc", "1.2.3", "1e", "--1", "NaN"] {
+            assert_eq!(
+                text.parse::<BigDecimal256>(),
+                Err(FloatError::NaNOperand),
+                "{}",
+                text
+            );
+        }
+    }
+}
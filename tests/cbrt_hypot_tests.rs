@@ -0,0 +1,127 @@
+use solana_floats::compare::{ulp_distance_f32, ulp_distance_f64};
+use solana_floats::double_ops::{CBRT_MAX_ULPS, HYPOT_MAX_ULPS, cbrt_double, hypot_doubles};
+use solana_floats::error::FloatError;
+use solana_floats::float_ops::{cbrt_float, hypot_floats};
+
+mod common;
+
+#[cfg(test)]
+mod cbrt_hypot_tests {
+    use super::*;
+    use common::xorshift;
+
+    fn ulps(a: f64, b: f64) -> u64 {
+        ulp_distance_f64(a, b).unwrap()
+    }
+
+    // Random finite values of either sign, with every exponent equally likely
+    fn random_doubles(count: usize) -> impl Iterator<Item = f64> {
+        xorshift(0x2545_f491_4f6c_dd1d)
+            .take(count)
+            .map(|word| f64::from_bits(word & 0xffef_ffff_ffff_ffff))
+    }
+
+    #[test]
+    fn test_cbrt_within_documented_bound() {
+        for x in random_doubles(200_000) {
+            assert!(
+                ulps(cbrt_double(x).unwrap(), x.cbrt()) <= CBRT_MAX_ULPS,
+                "{:e}",
+                x
+            );
+        }
+        for x in [
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            f64::from_bits(1),
+            -f64::MIN_POSITIVE / 3.0,
+        ] {
+            assert!(
+                ulps(cbrt_double(x).unwrap(), x.cbrt()) <= CBRT_MAX_ULPS,
+                "{:e}",
+                x
+            );
+        }
+    }
+
+    #[test]
+    fn test_cbrt_exact_cubes() {
+        for n in 1..=1000 {
+            let n = n as f64;
+            assert_eq!(cbrt_double(n * n * n), Ok(n));
+            assert_eq!(cbrt_double(-n * n * n), Ok(-n));
+        }
+        assert_eq!(cbrt_double(0.125), Ok(0.5));
+        // 2^-1071 is subnormal, its cube root 2^-357 is not
+        assert_eq!(
+            cbrt_double(f64::from_bits(1 << 3)),
+            Ok(f64::from_bits((1023 - 357) << 52))
+        );
+        assert_eq!(cbrt_float(27.0), Ok(3.0));
+        assert_eq!(cbrt_float(-0.001), Ok(-0.1));
+    }
+
+    #[test]
+    fn test_cbrt_special_values() {
+        assert_eq!(cbrt_double(0.0).unwrap().to_bits(), 0.0_f64.to_bits());
+        assert_eq!(cbrt_double(-0.0).unwrap().to_bits(), (-0.0_f64).to_bits());
+        assert_eq!(cbrt_double(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(cbrt_double(f64::INFINITY), Err(FloatError::NaNOperand));
+        assert_eq!(cbrt_float(f32::NEG_INFINITY), Err(FloatError::NaNOperand));
+    }
+
+    #[test]
+    fn test_hypot_within_documented_bound() {
+        let xs = random_doubles(200_000);
+        // Second operand within a few binades of the first, so neither is
+        // negligible
+        let ys = random_doubles(200_000).map(|y| y.abs() % 64.0);
+        for (x, ratio) in xs.zip(ys) {
+            let y = x * ratio;
+            let expected = x.hypot(y);
+            if expected.is_finite() && expected >= f64::MIN_POSITIVE {
+                let actual = hypot_doubles(x, y).unwrap();
+                assert!(ulps(actual, expected) <= HYPOT_MAX_ULPS, "{:e} {:e}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hypot_avoids_intermediate_overflow() {
+        // The squares overflow and underflow, the distance does not
+        let (large, small) = (2_f64.powi(600), 2_f64.powi(-600));
+        assert_eq!(hypot_doubles(3.0 * large, 4.0 * large), Ok(5.0 * large));
+        assert_eq!(hypot_doubles(-3.0 * small, 4.0 * small), Ok(5.0 * small));
+        // 3e200 and 4e200 are not exactly 3 and 4 times anything, and the
+        // correctly rounded distance between them is just under 5e200
+        assert_eq!(hypot_doubles(3e200, 4e200), Ok(4.999_999_999_999_999_5e200));
+        assert_eq!(hypot_doubles(3.0, 4.0), Ok(5.0));
+        assert_eq!(hypot_doubles(1e300, 1.0), Ok(1e300));
+        assert_eq!(hypot_doubles(0.0, -7.0), Ok(7.0));
+        assert_eq!(
+            hypot_floats(3.0 * 2_f32.powi(100), 4.0 * 2_f32.powi(100)),
+            Ok(5.0 * 2_f32.powi(100))
+        );
+        assert_eq!(hypot_floats(5.0, 12.0), Ok(13.0));
+        assert_eq!(hypot_doubles(f64::MAX, f64::MAX), Err(FloatError::Overflow));
+        assert_eq!(hypot_floats(f32::MAX, f32::MAX), Err(FloatError::Overflow));
+        assert_eq!(hypot_doubles(f64::NAN, 1.0), Err(FloatError::NaNOperand));
+        assert_eq!(
+            hypot_doubles(1.0, f64::INFINITY),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(hypot_floats(f32::NAN, 1.0), Err(FloatError::NaNOperand));
+    }
+
+    #[test]
+    fn test_f32_wrappers_match_native() {
+        for word in xorshift(0x9e37_79b9_7f4a_7c15).take(200_000) {
+            let x = f32::from_bits(word as u32 & 0xff7f_ffff);
+            let y = f32::from_bits((word >> 32) as u32 & 0xff7f_ffff);
+            assert!(ulp_distance_f32(cbrt_float(x).unwrap(), x.cbrt()).unwrap() <= 1);
+            if let Ok(distance) = hypot_floats(x, y) {
+                assert!(ulp_distance_f32(distance, x.hypot(y)).unwrap() <= 1);
+            }
+        }
+    }
+}
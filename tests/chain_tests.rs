@@ -0,0 +1,394 @@
+use solana_floats::account_tag;
+use solana_floats::chain::*;
+use solana_floats::double_ops::BinaryOp;
+use solana_floats::error::FloatError;
+use solana_floats::feature_gate::FeatureGate;
+use solana_floats::results_log::ResultsLog;
+use solana_program::program_error::ProgramError;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+mod common;
+
+#[cfg(test)]
+mod chain_tests {
+    use super::*;
+    use common::program_test;
+
+    // Sends `batches` of instructions, one transaction each signed by the
+    // payer and, where an instruction asks for it, `authority`, with
+    // `accounts` preloaded, and returns the data of the authority's cache
+    // afterwards
+    async fn run_chain(
+        program_id: Pubkey,
+        authority: &Keypair,
+        batches: Vec<Vec<Instruction>>,
+        accounts: Vec<(Pubkey, Account)>,
+    ) -> Result<Vec<u8>, TransactionError> {
+        let mut program_test = program_test(program_id);
+        program_test.add_account(
+            authority.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                owner: solana_system_interface::program::id(),
+                ..Account::default()
+            },
+        );
+        for (address, account) in accounts {
+            program_test.add_account(address, account);
+        }
+        let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        for instructions in batches {
+            let signs = instructions
+                .iter()
+                .flat_map(|ix| &ix.accounts)
+                .any(|meta| meta.pubkey == authority.pubkey() && meta.is_signer);
+            let signers: &[&Keypair] = if signs {
+                &[&payer, authority]
+            } else {
+                &[&payer]
+            };
+            let transaction = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                signers,
+                recent_blockhash,
+            );
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .map_err(|err| err.unwrap())?;
+        }
+        let (cache, _) = find_cache_address(&program_id, &authority.pubkey());
+        Ok(banks_client.get_account(cache).await.unwrap().unwrap().data)
+    }
+
+    // An account owned by `program_id` holding `data`
+    fn program_account(program_id: Pubkey, data: Vec<u8>) -> Account {
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: program_id,
+            ..Account::default()
+        }
+    }
+
+    // (0.1 * 3) / 3 + 0.1, and the product squared
+    fn sample_chain(program_id: Pubkey, cache: Pubkey) -> OpChain {
+        let mut chain = OpChain::new(program_id, cache);
+        let product = chain.push(BinaryOp::Multiply, 0.1, 3.0).unwrap();
+        let third = chain.push(BinaryOp::Divide, product, 3.0).unwrap();
+        chain.push(BinaryOp::Add, third, 0.1).unwrap();
+        chain.push(BinaryOp::Multiply, product, product).unwrap();
+        chain
+    }
+
+    #[test]
+    fn test_simulate_matches_native_ops() {
+        let chain = sample_chain(Pubkey::new_unique(), Pubkey::new_unique());
+        let product = 0.1_f64 * 3.0;
+        let expected = [
+            product,
+            product / 3.0,
+            product / 3.0 + 0.1,
+            product * product,
+        ];
+        let results = chain.simulate().unwrap();
+        for (result, expected) in results.iter().zip(expected) {
+            assert_eq!(result.to_bits(), expected.to_bits());
+        }
+        assert_eq!(chain.len(), 4);
+        assert_eq!(chain.cache_len(), CACHE_HEADER_LEN + 32);
+    }
+
+    #[test]
+    fn test_push_rejects_results_not_yet_computed() {
+        let mut chain = OpChain::new(Pubkey::new_unique(), Pubkey::new_unique());
+        let first = chain.push(BinaryOp::Add, 1.0, 2.0).unwrap();
+        let mut other = OpChain::new(Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(
+            other.push(BinaryOp::Add, first, 1.0),
+            Err(FloatError::OutOfRange)
+        );
+        assert!(other.is_empty());
+        assert_eq!(first.slot(), 0);
+    }
+
+    #[test]
+    fn test_simulate_errors() {
+        let mut chain = OpChain::new(Pubkey::new_unique(), Pubkey::new_unique());
+        let zero = chain.push(BinaryOp::Add, 1.0, -1.0).unwrap();
+        chain.push(BinaryOp::Divide, 1.0, zero).unwrap();
+        assert_eq!(chain.simulate(), Err(FloatError::DivisionByZero));
+
+        let mut chain = OpChain::new(Pubkey::new_unique(), Pubkey::new_unique());
+        chain.push(BinaryOp::Multiply, f64::INFINITY, 0.0).unwrap();
+        assert_eq!(chain.simulate(), Err(FloatError::NaNOperand));
+    }
+
+    #[test]
+    fn test_step_pack_round_trip() {
+        let chain = sample_chain(Pubkey::new_unique(), Pubkey::new_unique());
+        for step in chain.steps() {
+            let packed = step.pack();
+            assert_eq!(packed.len(), STEP_LEN);
+            assert_eq!(ChainStep::unpack(&packed), Ok(*step));
+        }
+
+        let mut packed = chain.steps()[1].pack();
+        // Padding of a slot operand must be zero
+        packed[5] = 1;
+        assert_eq!(
+            ChainStep::unpack(&packed),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        let mut packed = chain.steps()[0].pack();
+        packed[1] = 2;
+        assert_eq!(
+            ChainStep::unpack(&packed),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            ChainStep::unpack(&packed[..STEP_LEN - 1]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_apply_writes_destination_slot() {
+        let chain = sample_chain(Pubkey::new_unique(), Pubkey::new_unique());
+        let mut cache = vec![0u8; chain.cache_len()];
+        init_cache(&mut cache).unwrap();
+        for step in chain.steps() {
+            step.apply(&mut cache).unwrap();
+        }
+        let simulated = chain.simulate().unwrap();
+        for (slot, expected) in simulated.iter().enumerate() {
+            assert_eq!(read_slot(&cache, slot as u16), Ok(*expected));
+        }
+        let mut short = vec![0u8; cache_len(1)];
+        init_cache(&mut short).unwrap();
+        assert_eq!(
+            chain.steps()[1].apply(&mut short),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_apply_requires_chain_cache_tag() {
+        let chain = sample_chain(Pubkey::new_unique(), Pubkey::new_unique());
+        // Any other account's data, such as the feature gate's
+        let mut data = vec![0u8; chain.cache_len()];
+        let gate = FeatureGate {
+            admin: Pubkey::new_unique(),
+            f32_enabled: true,
+        };
+        gate.pack(&mut data[..FeatureGate::LEN]).unwrap();
+        assert_eq!(
+            chain.steps()[0].apply(&mut data),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(read_slot(&data, 0), Err(ProgramError::InvalidAccountData));
+        assert_eq!(
+            chain.steps()[0].apply(&mut []),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chain_keeps_exact_bits_across_instructions() {
+        let program_id = Pubkey::new_unique();
+        let authority = Keypair::new();
+        let chain = sample_chain(program_id, authority.pubkey());
+        let expected = chain.simulate().unwrap();
+
+        let mut instructions = chain.instructions();
+        let second_half = instructions.split_off(3);
+        for batches in [vec![chain.instructions()], vec![instructions, second_half]] {
+            let data = run_chain(program_id, &authority, batches, vec![])
+                .await
+                .unwrap();
+            assert_eq!(data.len(), chain.cache_len());
+            for (slot, value) in expected.iter().enumerate() {
+                assert_eq!(
+                    read_slot(&data, slot as u16).unwrap().to_bits(),
+                    value.to_bits()
+                );
+            }
+        }
+
+        // Re-submitting the product as a printed approximation changes the
+        // result that chaining keeps exact
+        let printed: f64 = format!("{:.6}", expected[0]).parse().unwrap();
+        assert_ne!((printed * printed).to_bits(), expected[3].to_bits());
+    }
+
+    #[tokio::test]
+    async fn test_chain_cache_can_be_reused() {
+        let program_id = Pubkey::new_unique();
+        let authority = Keypair::new();
+        let chain = sample_chain(program_id, authority.pubkey());
+        let mut shorter = OpChain::new(program_id, authority.pubkey());
+        shorter.push(BinaryOp::Add, 1.0, 2.0).unwrap();
+
+        let data = run_chain(
+            program_id,
+            &authority,
+            vec![chain.instructions(), shorter.instructions()],
+            vec![],
+        )
+        .await
+        .unwrap();
+        assert_eq!(data.len(), chain.cache_len());
+        assert_eq!(read_slot(&data, 0), Ok(3.0));
+    }
+
+    #[tokio::test]
+    async fn test_chain_step_refuses_other_program_accounts() {
+        let program_id = Pubkey::new_unique();
+        let authority = Keypair::new();
+        let chain = sample_chain(program_id, authority.pubkey());
+        let (cache, _) = find_cache_address(&program_id, &authority.pubkey());
+        let cache_data = || {
+            let mut data = vec![0u8; chain.cache_len()];
+            init_cache(&mut data).unwrap();
+            data
+        };
+
+        // The feature gate and a results log are program-owned too, but a step
+        // must never write to them
+        let (gate, _) = FeatureGate::find_address(&program_id);
+        let mut gate_data = vec![0u8; FeatureGate::LEN];
+        FeatureGate {
+            admin: Pubkey::new_unique(),
+            f32_enabled: true,
+        }
+        .pack(&mut gate_data)
+        .unwrap();
+        let (log, _) = ResultsLog::find_address(&program_id, &authority.pubkey());
+        let mut log_data = vec![0u8; ResultsLog::LEN];
+        ResultsLog::new(authority.pubkey())
+            .pack(&mut log_data)
+            .unwrap();
+        for (target, data) in [(gate, gate_data), (log, log_data)] {
+            let mut step = chain.instructions().remove(1);
+            step.accounts[0].pubkey = target;
+            assert_eq!(
+                run_chain(
+                    program_id,
+                    &authority,
+                    vec![vec![step]],
+                    vec![
+                        (cache, program_account(program_id, cache_data())),
+                        (target, program_account(program_id, data.clone())),
+                    ],
+                )
+                .await,
+                Err(TransactionError::InstructionError(
+                    0,
+                    InstructionError::InvalidSeeds
+                ))
+            );
+        }
+
+        // Another authority's cache
+        let other = Keypair::new();
+        let mut step = chain.instructions().remove(1);
+        step.accounts[0].pubkey = find_cache_address(&program_id, &other.pubkey()).0;
+        assert_eq!(
+            run_chain(program_id, &authority, vec![vec![step]], vec![]).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidSeeds
+            ))
+        );
+
+        // The cache address holding data without the chain cache tag, or
+        // with the results log tag
+        let mut untagged = cache_data();
+        untagged[0] = 0;
+        let mut log_tagged = cache_data();
+        log_tagged[0] = account_tag::RESULTS_LOG;
+        for data in [untagged, log_tagged] {
+            for instruction in [
+                chain.instructions().remove(0),
+                chain.instructions().remove(1),
+            ] {
+                assert_eq!(
+                    run_chain(
+                        program_id,
+                        &authority,
+                        vec![vec![instruction]],
+                        vec![(cache, program_account(program_id, data.clone()))],
+                    )
+                    .await,
+                    Err(TransactionError::InstructionError(
+                        0,
+                        InstructionError::InvalidAccountData
+                    ))
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chain_step_errors() {
+        let program_id = Pubkey::new_unique();
+        let authority = Keypair::new();
+        let chain = sample_chain(program_id, authority.pubkey());
+        let (cache, _) = find_cache_address(&program_id, &authority.pubkey());
+
+        // An existing cache too small for the chain
+        let mut short = vec![0u8; chain.cache_len() - SLOT_SIZE];
+        init_cache(&mut short).unwrap();
+        assert_eq!(
+            run_chain(
+                program_id,
+                &authority,
+                vec![chain.instructions()],
+                vec![(cache, program_account(program_id, short))],
+            )
+            .await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::AccountDataTooSmall
+            ))
+        );
+
+        // Unsigned authority
+        let mut instructions = chain.instructions();
+        instructions[1].accounts[1].is_signer = false;
+        let init = instructions.remove(0);
+        let step = instructions.remove(0);
+        assert_eq!(
+            run_chain(program_id, &authority, vec![vec![init], vec![step]], vec![]).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::MissingRequiredSignature
+            ))
+        );
+
+        let mut dividing = OpChain::new(program_id, authority.pubkey());
+        dividing.push(BinaryOp::Divide, 1.0, 0.0).unwrap();
+        assert_eq!(
+            run_chain(
+                program_id,
+                &authority,
+                vec![dividing.instructions()],
+                vec![]
+            )
+            .await,
+            Err(TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(FloatError::DivisionByZero.code())
+            ))
+        );
+    }
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "client")]
+
+use solana_floats::client::{ClientError, FloatResultExt};
+use solana_floats::error::FloatError;
+use solana_floats::quantize::PrecisionPolicy;
+use solana_floats::safe_f64::SafeF64;
+use std::error::Error;
+
+#[cfg(test)]
+mod client_tests {
+    use super::*;
+
+    #[test]
+    fn test_value_context_names_operation_and_value() {
+        let error = SafeF64::new(f64::NAN)
+            .for_value("SafeF64::new", f64::NAN)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "SafeF64::new failed for NaN: NaN or non-finite operand"
+        );
+        assert_eq!(error.float_error(), FloatError::NaNOperand);
+    }
+
+    #[test]
+    fn test_operand_context() {
+        let (a, b) = (SafeF64::ONE, SafeF64::ZERO);
+        let error = a
+            .checked_div(b)
+            .for_operands("checked_div", a.get(), b.get())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "checked_div failed for (1.0, 0.0): division by zero"
+        );
+    }
+
+    #[test]
+    fn test_policy_context() {
+        let error = PrecisionPolicy::builder()
+            .decimals(30)
+            .build()
+            .for_policy("build policy", 30.0, "decimals <= 19")
+            .unwrap_err();
+        assert!(matches!(error, ClientError::Policy { .. }));
+        assert!(
+            error
+                .to_string()
+                .starts_with("build policy violated decimals <= 19 with 30.0")
+        );
+    }
+
+    #[test]
+    fn test_source_chain_reaches_float_error() {
+        let error = SafeF64::MAX
+            .checked_add(SafeF64::MAX)
+            .for_operands("checked_add", f64::MAX, f64::MAX)
+            .map_err(|error| error.within("settle"))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "settle failed");
+        assert_eq!(error.float_error(), FloatError::Overflow);
+
+        let mut chain = vec![error.to_string()];
+        let mut source = error.source();
+        while let Some(next) = source {
+            chain.push(next.to_string());
+            source = next.source();
+        }
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[2], "result overflows");
+    }
+}
@@ -0,0 +1,173 @@
+use solana_floats::clmm::*;
+use solana_floats::error::FloatError;
+
+// (tick, Whirlpool sqrt price, Raydium sqrt price) published by the two
+// programs: their MIN/MAX_SQRT_PRICE_X64 constants, 1.0 at tick 0, and the
+// single-step values.
+const PUBLISHED_VECTORS: [(i32, u128, u128); 5] = [
+    (MIN_TICK, 4295048016, 4295048016),
+    (
+        MAX_TICK,
+        79226673515401279992447579055,
+        79226673521066979257578248091,
+    ),
+    (0, 1 << 64, 1 << 64),
+    (1, 18447666387855959850, 18447666387855957090),
+    (-1, 18445821805675392311, 18445821805675395072),
+];
+
+// (tick, floor(1.0001^(tick / 2) * 2^64)) evaluated to 120 significant digits.
+const EXACT_VECTORS: [(i32, u128); 16] = [
+    (10, 18455969290605290427),
+    (-10, 18437523468038800958),
+    (100, 18539204128674405812),
+    (-100, 18354745142194483563),
+    (1000, 19392480388906836277),
+    (-1000, 17547129613991598781),
+    (10000, 30412779051191548722),
+    (-10000, 11188795550323325957),
+    (12345, 34195943348800206620),
+    (-54321, 1220149845509316393),
+    (100000, 2737055259406582257880),
+    (-100000, 124324258982887574),
+    (300000, 60257519765924248467716150),
+    (-300000, 5647135299341),
+    (MAX_TICK, 79226673515401279992447579061),
+    (MIN_TICK, 4295048016),
+];
+
+const CONVENTIONS: [SqrtPriceConvention; 2] =
+    [SqrtPriceConvention::Whirlpool, SqrtPriceConvention::Raydium];
+
+#[cfg(test)]
+mod clmm_tests {
+    use super::*;
+
+    fn relative_difference(a: u128, b: u128) -> f64 {
+        a.abs_diff(b) as f64 / a.max(b) as f64
+    }
+
+    #[test]
+    fn test_published_vectors_match_bit_for_bit() {
+        for (tick, whirlpool, raydium) in PUBLISHED_VECTORS {
+            assert_eq!(
+                sqrt_price_from_tick(tick, SqrtPriceConvention::Whirlpool),
+                Ok(whirlpool),
+                "whirlpool tick {}",
+                tick
+            );
+            assert_eq!(
+                sqrt_price_from_tick(tick, SqrtPriceConvention::Raydium),
+                Ok(raydium),
+                "raydium tick {}",
+                tick
+            );
+        }
+    }
+
+    #[test]
+    fn test_range_constants_are_the_endpoint_prices() {
+        for convention in CONVENTIONS {
+            assert_eq!(
+                sqrt_price_from_tick(MIN_TICK, convention),
+                Ok(convention.min_sqrt_price_x64())
+            );
+            assert_eq!(
+                sqrt_price_from_tick(MAX_TICK, convention),
+                Ok(convention.max_sqrt_price_x64())
+            );
+        }
+    }
+
+    #[test]
+    fn test_both_conventions_stay_close_to_exact() {
+        for (tick, exact) in EXACT_VECTORS {
+            let whirlpool = sqrt_price_from_tick(tick, SqrtPriceConvention::Whirlpool).unwrap();
+            let raydium = sqrt_price_from_tick(tick, SqrtPriceConvention::Raydium).unwrap();
+            // Whirlpool floors every factor and product, so it only ever
+            // undershoots, by a few units in the last place
+            assert!(exact - whirlpool <= 8, "tick {}", tick);
+            // Raydium's double-precision table drifts further, but stays far
+            // below one tick (~5e-5)
+            assert!(
+                relative_difference(raydium, exact) < 1e-9,
+                "tick {}: {} vs {}",
+                tick,
+                raydium,
+                exact
+            );
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_ticks_are_rejected() {
+        for convention in CONVENTIONS {
+            assert_eq!(
+                sqrt_price_from_tick(MAX_TICK + 1, convention),
+                Err(FloatError::OutOfRange)
+            );
+            assert_eq!(
+                sqrt_price_from_tick(MIN_TICK - 1, convention),
+                Err(FloatError::OutOfRange)
+            );
+            assert_eq!(
+                tick_from_sqrt_price(convention.min_sqrt_price_x64() - 1, convention),
+                Err(FloatError::OutOfRange)
+            );
+            assert_eq!(
+                tick_from_sqrt_price(convention.max_sqrt_price_x64() + 1, convention),
+                Err(FloatError::OutOfRange)
+            );
+        }
+    }
+
+    #[test]
+    fn test_prices_are_strictly_increasing() {
+        for convention in CONVENTIONS {
+            let mut previous = 0;
+            for tick in (MIN_TICK..=MAX_TICK).step_by(997).chain([MAX_TICK]) {
+                let price = sqrt_price_from_tick(tick, convention).unwrap();
+                assert!(price > previous, "{:?} tick {}", convention, tick);
+                previous = price;
+            }
+            for tick in -2000..2000 {
+                assert!(
+                    sqrt_price_from_tick(tick + 1, convention).unwrap()
+                        > sqrt_price_from_tick(tick, convention).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_tick_from_sqrt_price_is_the_floor_inverse() {
+        for convention in CONVENTIONS {
+            for tick in [MIN_TICK, -300000, -54321, -1, 0, 1, 12345, 300000, MAX_TICK] {
+                let price = sqrt_price_from_tick(tick, convention).unwrap();
+                assert_eq!(tick_from_sqrt_price(price, convention), Ok(tick));
+                if tick < MAX_TICK {
+                    // One unit below the next tick's price still maps to `tick`
+                    let next = sqrt_price_from_tick(tick + 1, convention).unwrap();
+                    assert_eq!(tick_from_sqrt_price(next - 1, convention), Ok(tick));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_conventions_disagree_only_in_low_bits() {
+        for tick in (MIN_TICK..=MAX_TICK).step_by(4099) {
+            let whirlpool = sqrt_price_from_tick(tick, SqrtPriceConvention::Whirlpool).unwrap();
+            let raydium = sqrt_price_from_tick(tick, SqrtPriceConvention::Raydium).unwrap();
+            assert!(
+                relative_difference(whirlpool, raydium) < 1e-9,
+                "tick {}",
+                tick
+            );
+            // A price from one program lands on the same tick in the other
+            // unless it sits within that sliver of a tick boundary.
+            let crossed = tick_from_sqrt_price(whirlpool, SqrtPriceConvention::Raydium).unwrap();
+            assert!((crossed - tick).abs() <= 1, "tick {}", tick);
+        }
+    }
+}
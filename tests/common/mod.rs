@@ -0,0 +1,106 @@
+//! Random inputs, comparisons and the program harness shared by the
+//! integration tests.
+//!
+//! Each test crate compiles its own copy and uses only part of it.
+#![allow(dead_code)]
+
+use solana_program_test::{BanksTransactionResultWithMetadata, ProgramTest, processor};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::Transaction,
+};
+
+/// Where `process_with_metadata` deploys the program.
+pub const PROGRAM_ID: Pubkey = Pubkey::new_from_array([0xf1; 32]);
+
+/// The program deployed at `program_id`, ready for accounts to be added.
+pub fn program_test(program_id: Pubkey) -> ProgramTest {
+    ProgramTest::new(
+        "solana_floats",
+        program_id,
+        processor!(solana_floats::process_instruction),
+    )
+}
+
+/// Runs one instruction in its own transaction, paid and signed by the payer,
+/// against a fresh deployment at [`PROGRAM_ID`].
+pub async fn process_with_metadata(
+    instruction_data: &[u8],
+    accounts: Vec<AccountMeta>,
+) -> BanksTransactionResultWithMetadata {
+    process_in(program_test(PROGRAM_ID), instruction_data, accounts).await
+}
+
+/// [`process_with_metadata`] against `program_test`, which must deploy at
+/// [`PROGRAM_ID`], for instructions that need accounts to exist first.
+pub async fn process_in(
+    program_test: ProgramTest,
+    instruction_data: &[u8],
+    accounts: Vec<AccountMeta>,
+) -> BanksTransactionResultWithMetadata {
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let instruction = Instruction::new_with_bytes(PROGRAM_ID, instruction_data, accounts);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap()
+}
+
+/// xorshift64* over raw bit patterns, so every exponent range is exercised.
+pub fn bit_patterns(seed: u64, len: usize) -> Vec<u64> {
+    xorshift_star(seed).take(len).collect()
+}
+
+/// xorshift64* words from a non-zero `seed`, identical on every platform.
+pub fn xorshift_star(seed: u64) -> impl Iterator<Item = u64> {
+    let mut state = seed;
+    std::iter::repeat_with(move || {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    })
+}
+
+/// Same bit pattern, or both NaN.
+pub fn same_f64(x: f64, y: f64) -> bool {
+    x.to_bits() == y.to_bits() || x.is_nan() && y.is_nan()
+}
+
+/// Same bit pattern, or both NaN.
+pub fn same_f32(x: f32, y: f32) -> bool {
+    x.to_bits() == y.to_bits() || x.is_nan() && y.is_nan()
+}
+
+/// xorshift64 words from a non-zero `seed`, identical on every platform.
+pub fn xorshift(seed: u64) -> impl Iterator<Item = u64> {
+    let mut state = seed;
+    std::iter::repeat_with(move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    })
+}
+
+/// Uniform in `[low, high)`, the same `count` values on every run.
+pub fn uniform(low: f64, high: f64, count: usize) -> impl Iterator<Item = f64> {
+    xorshift(0x9e37_79b9_7f4a_7c15)
+        .take(count)
+        .map(move |word| low + (word >> 11) as f64 / (1_u64 << 53) as f64 * (high - low))
+}
+
+/// Positive finite values with every exponent equally likely.
+pub fn random_positive(seed: u64, count: usize) -> impl Iterator<Item = f64> {
+    xorshift(seed)
+        .take(count)
+        .map(|word| f64::from_bits(word & 0x7fef_ffff_ffff_ffff))
+}
@@ -0,0 +1,303 @@
+use solana_floats::compare::*;
+use solana_floats::error::FloatError;
+use solana_floats::instruction::{Opcode, encode};
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    transaction::TransactionError,
+};
+use std::cmp::Ordering;
+
+mod common;
+
+#[cfg(test)]
+mod compare_tests {
+    use super::*;
+    use common::{PROGRAM_ID, process_with_metadata};
+
+    async fn cmp_on_chain(a: f64, b: f64, tolerance: Option<f64>) -> Option<Ordering> {
+        let mut payload = a.to_le_bytes().to_vec();
+        payload.extend_from_slice(&b.to_le_bytes());
+        if let Some(tolerance) = tolerance {
+            payload.extend_from_slice(&tolerance.to_le_bytes());
+        }
+        let result = process_with_metadata(&encode(Opcode::Cmp, &payload), vec![]).await;
+        result.result.ok()?;
+        let return_data = result.metadata?.return_data?;
+        assert_eq!(return_data.data.len(), 1);
+        decode_ordering(return_data.data[0])
+    }
+
+    // The return data of `instruction`, or the error it failed with
+    async fn run_on_chain(instruction: Instruction) -> Result<Vec<u8>, TransactionError> {
+        let result = process_with_metadata(&instruction.data, instruction.accounts).await;
+        result.result?;
+        Ok(result.metadata.unwrap().return_data.unwrap().data)
+    }
+
+    #[test]
+    fn test_tolerance_absorbs_representation_error() {
+        // ❌ 0.1 + 0.2 is not 0.3 under strict ordering
+        assert_eq!(
+            total_cmp_with_tolerance(0.1 + 0.2, 0.3, 0.0),
+            Ok(Ordering::Greater)
+        );
+        // ✅ Within 1e-12 they compare equal
+        assert_eq!(
+            total_cmp_with_tolerance(0.1 + 0.2, 0.3, 1e-12),
+            Ok(Ordering::Equal)
+        );
+        assert_eq!(
+            total_cmp_with_tolerance(1.0, 2.0, 1e-12),
+            Ok(Ordering::Less)
+        );
+    }
+
+    #[test]
+    fn test_special_values_are_ordered_deterministically() {
+        assert_eq!(
+            total_cmp_with_tolerance(0.0, -0.0, 0.0),
+            Ok(Ordering::Equal)
+        );
+        assert_eq!(
+            total_cmp_with_tolerance(f64::INFINITY, f64::INFINITY, 1.0),
+            Ok(Ordering::Equal)
+        );
+        // NaN is never "within tolerance" but still has a fixed position
+        assert_eq!(
+            total_cmp_with_tolerance(f64::NAN, 1.0, 1e300),
+            Ok(Ordering::Greater)
+        );
+        assert_eq!(
+            total_cmp_with_tolerance(f64::NAN, f64::NAN, 1.0),
+            Ok(Ordering::Equal)
+        );
+    }
+
+    #[test]
+    fn test_invalid_tolerance() {
+        assert_eq!(
+            total_cmp_with_tolerance(1.0, 1.0, -1e-12),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            total_cmp_with_tolerance(1.0, 1.0, f64::INFINITY),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            total_cmp_with_tolerance(1.0, 1.0, f64::NAN),
+            Err(FloatError::NaNOperand)
+        );
+    }
+
+    #[test]
+    fn test_ordering_encoding_round_trip() {
+        for ordering in [Ordering::Less, Ordering::Equal, Ordering::Greater] {
+            assert_eq!(decode_ordering(encode_ordering(ordering)), Some(ordering));
+        }
+        assert_eq!(encode_ordering(Ordering::Less), 0xFF);
+        assert_eq!(decode_ordering(2), None);
+    }
+
+    #[tokio::test]
+    async fn test_cmp_instruction_returns_ordering() {
+        assert_eq!(cmp_on_chain(1.0, 2.0, None).await, Some(Ordering::Less));
+        assert_eq!(
+            cmp_on_chain(0.1 + 0.2, 0.3, None).await,
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            cmp_on_chain(0.1 + 0.2, 0.3, Some(1e-12)).await,
+            Some(Ordering::Equal)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cmp_instruction_rejects_bad_tolerance() {
+        assert_eq!(cmp_on_chain(1.0, 2.0, Some(-1.0)).await, None);
+    }
+
+    #[test]
+    fn test_tolerance_builder() {
+        let tolerance = Tolerance::builder().abs(1e-12).rel_bps(1).build().unwrap();
+        assert_eq!(tolerance, Tolerance::new(1e-12, 0.0001).unwrap());
+        assert_eq!(Tolerance::builder().build(), Tolerance::new(0.0, 0.0));
+        // Later settings replace earlier ones
+        assert_eq!(
+            Tolerance::builder()
+                .rel(0.5)
+                .rel_bps(10)
+                .build()
+                .unwrap()
+                .rel(),
+            0.001
+        );
+    }
+
+    #[test]
+    fn test_tolerance_builder_validates() {
+        assert_eq!(
+            Tolerance::builder().abs(f64::NAN).build(),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            Tolerance::builder().rel(-1e-9).build(),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            Tolerance::builder().abs(f64::INFINITY).build(),
+            Err(FloatError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_tolerance_matches_absolute_or_relative() {
+        let tolerance = Tolerance::builder().abs(1e-9).rel_bps(1).build().unwrap();
+        // Near zero only the absolute bound helps
+        assert!(tolerance.matches(0.0, 5e-10));
+        assert!(!tolerance.matches(0.0, 5e-9));
+        // Far from zero the relative bound dominates
+        assert!(tolerance.matches(1e6, 1e6 + 99.0));
+        assert!(!tolerance.matches(1e6, 1e6 + 101.0));
+        assert!(!tolerance.matches(f64::NAN, f64::NAN));
+    }
+
+    #[test]
+    fn test_ulp_distance() {
+        assert_eq!(ulp_distance_f64(1.0, 1.0), Some(0));
+        assert_eq!(ulp_distance_f64(0.1 + 0.2, 0.3), Some(1));
+        assert_eq!(ulp_distance_f64(1.0, 1.0 + f64::EPSILON), Some(1));
+        assert_eq!(ulp_distance_f64(-0.0, 0.0), Some(0));
+        // Across zero: one step from the smallest subnormal to each zero
+        let tiny = f64::from_bits(1);
+        assert_eq!(ulp_distance_f64(-tiny, tiny), Some(2));
+        assert_eq!(ulp_distance_f64(f64::MAX, f64::INFINITY), Some(1));
+        assert_eq!(ulp_distance_f64(f64::NAN, 1.0), None);
+
+        assert_eq!(ulp_distance_f32(0.1 + 0.2, 0.3), Some(0));
+        assert_eq!(
+            ulp_distance_f32(-f32::from_bits(3), f32::from_bits(2)),
+            Some(5)
+        );
+        assert_eq!(ulp_distance_f32(1.0, f32::NAN), None);
+    }
+
+    #[test]
+    fn test_approx_eq_ulps_scales_with_magnitude() {
+        // One ulp is 2^-52 at 1.0 but 2^-23 at 1e20; an epsilon can't be both
+        assert!(approx_eq_ulps(1.0, 1.0 + f64::EPSILON, 1));
+        assert!(approx_eq_ulps(1e20, 1e20 + 16_384.0, 1));
+        assert!(!approx_eq_ulps(1e20, 1e20 + 32_768.0, 1));
+        assert!(!approx_eq_ulps(0.1 + 0.2, 0.3, 0));
+        assert!(!approx_eq_ulps(f64::NAN, f64::NAN, u64::MAX));
+        assert!(approx_eq_ulps_f32(1.0, 1.0 + f32::EPSILON, 1));
+        assert!(!approx_eq_ulps_f32(f32::NAN, 1.0, u32::MAX));
+    }
+
+    #[test]
+    fn test_approx_eq_abs_and_rel() {
+        assert!(approx_eq_abs(0.1 + 0.2, 0.3, 1e-12));
+        assert!(!approx_eq_abs(1e6 + 0.5, 1e6, 0.1));
+        assert!(approx_eq_abs(-0.0, 0.0, 0.0));
+        // A relative bound scales with the operands but vanishes at zero
+        assert!(approx_eq_rel(1e6 + 0.5, 1e6, 1e-6));
+        assert!(!approx_eq_rel(1e-15, 0.0, 0.5));
+        assert!(approx_eq_rel(0.0, 0.0, 0.0));
+        for check in [approx_eq_abs, approx_eq_rel] {
+            assert!(!check(f64::NAN, f64::NAN, 1.0));
+            assert!(!check(f64::INFINITY, f64::INFINITY, 1.0));
+            assert!(!check(1.0, 1.0, f64::NAN));
+            assert!(!check(1.0, 1.0 + f64::EPSILON, -1.0));
+        }
+    }
+
+    #[test]
+    fn test_approx_eq_combines_bounds() {
+        let tolerance = AbsRel::new(1e-12, 1e-9).unwrap();
+        assert!(approx_eq(0.1 + 0.2, 0.3, tolerance));
+        assert!(approx_eq(1e-13, 0.0, tolerance));
+        assert!(approx_eq(1e9, 1e9 + 0.5, tolerance));
+        assert!(!approx_eq(1e9, 1e9 + 2.0, tolerance));
+        assert!(!approx_eq(f64::NAN, 0.0, tolerance));
+        for (a, b) in [(1.0, 1.1), (0.0, 1e-12), (5e8, 5e8 + 0.5)] {
+            assert_eq!(approx_eq(a, b, tolerance), tolerance.matches(a, b));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_approx_eq_instruction() {
+        let tolerance = Tolerance::new(1e-12, 1e-9).unwrap();
+        let cases = [
+            // Absolute bound near zero, relative bound at scale
+            (0.1 + 0.2, 0.3, Ok(vec![1])),
+            (1e-15, 0.0, Ok(vec![1])),
+            (1e9, 1e9 + 0.5, Ok(vec![1])),
+            (1.0, 1.000001, Ok(vec![0])),
+            // NaN is never within tolerance, of anything
+            (f64::NAN, f64::NAN, Ok(vec![0])),
+        ];
+        for (a, b, expected) in cases {
+            let instruction = approx_eq_instruction(PROGRAM_ID, a, b, tolerance, false);
+            assert_eq!(run_on_chain(instruction).await, expected, "{} vs {}", a, b);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_approx_eq_instruction_can_require_a_match() {
+        let tolerance = Tolerance::new(0.0, 1e-9).unwrap();
+        let matching = approx_eq_instruction(PROGRAM_ID, 1e9, 1e9 + 0.5, tolerance, true);
+        assert_eq!(run_on_chain(matching).await, Ok(vec![1]));
+
+        let mismatch = approx_eq_instruction(PROGRAM_ID, 1.0, 1.000001, tolerance, true);
+        assert_eq!(
+            run_on_chain(mismatch).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidArgument
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_approx_eq_instruction_rejects_bad_tolerance() {
+        // Tolerances that `Tolerance::new` rejects cannot be built, so patch the
+        // `[abs][rel]` tail of an encoded instruction
+        let cases = [(f64::NAN, 0.0), (0.0, -1e-9_f64), (f64::INFINITY, 0.0)];
+        for (abs, rel) in cases {
+            let mut instruction = approx_eq_instruction(
+                PROGRAM_ID,
+                1.0,
+                1.0,
+                Tolerance::new(0.0, 0.0).unwrap(),
+                false,
+            );
+            let tail = instruction.data.len() - 16;
+            instruction.data[tail..tail + 8].copy_from_slice(&abs.to_le_bytes());
+            instruction.data[tail + 8..].copy_from_slice(&rel.to_le_bytes());
+            assert_eq!(
+                run_on_chain(instruction).await,
+                Err(TransactionError::InstructionError(
+                    0,
+                    InstructionError::InvalidInstructionData
+                ))
+            );
+        }
+
+        // Unknown flags
+        let mut instruction = approx_eq_instruction(
+            PROGRAM_ID,
+            1.0,
+            1.0,
+            Tolerance::new(0.0, 0.0).unwrap(),
+            false,
+        );
+        instruction.data[1 + solana_floats::instruction::DISCRIMINATOR_LEN] = 0x02;
+        assert_eq!(
+            run_on_chain(instruction).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+    }
+}
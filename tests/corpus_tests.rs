@@ -0,0 +1,115 @@
+use solana_floats::corpus::*;
+use solana_floats::quantize::{RoundingMode, quantize};
+use solana_floats::softref;
+
+mod common;
+
+#[cfg(test)]
+mod corpus_tests {
+    use super::*;
+    use common::same_f64;
+
+    #[test]
+    fn test_precision_boundaries() {
+        let two_53 = F64_PRECISION_BOUNDARIES[3];
+        assert_eq!(two_53, 2f64.powi(53));
+        assert_eq!(two_53 + 1.0, two_53);
+        assert_eq!(F64_PRECISION_BOUNDARIES[2] + 1.0, two_53);
+        assert_eq!(F64_PRECISION_BOUNDARIES[4], two_53 + 2.0);
+        assert_eq!(F64_PRECISION_BOUNDARIES[0].fract(), 0.5);
+        assert_eq!(F64_PRECISION_BOUNDARIES[6] as i64, i64::MAX - 1023);
+        assert_eq!(F64_PRECISION_BOUNDARIES[7] as i64, i64::MAX);
+        assert_eq!(F64_PRECISION_BOUNDARIES[8] as u64, u64::MAX - 2047);
+        assert_eq!(F64_PRECISION_BOUNDARIES[9] as u64, u64::MAX);
+        assert_eq!(F64_PRECISION_BOUNDARIES[10] as i64, i64::MIN);
+
+        let two_24 = F32_PRECISION_BOUNDARIES[3];
+        assert_eq!(two_24, 2f32.powi(24));
+        assert_eq!(two_24 + 1.0, two_24);
+        assert_eq!(F32_PRECISION_BOUNDARIES[4], two_24 + 2.0);
+    }
+
+    #[test]
+    fn test_decimal_ties_are_off_the_tie() {
+        for (i, &(value, decimals)) in DECIMAL_TIES.iter().enumerate() {
+            // `{:.N}` prints the exact binary value, so the digits after the
+            // tie's final 5 say which side of the tie it is stored on
+            let exact = format!("{:.40}", value);
+            let (_, fraction) = exact.split_once('.').unwrap();
+            let tie_digit = decimals as usize;
+            assert_eq!(
+                &fraction[tie_digit..tie_digit + 1],
+                if i < 9 { "4" } else { "5" },
+                "{}",
+                exact
+            );
+            assert_ne!(fraction[tie_digit + 1..].trim_end_matches('0'), "");
+        }
+        assert_eq!(quantize(1.005, 2, RoundingMode::HalfUp).unwrap(), 1.0);
+        assert_eq!(quantize(8.345, 2, RoundingMode::HalfUp).unwrap(), 8.35);
+    }
+
+    #[test]
+    fn test_inexact_decimals() {
+        for value in INEXACT_DECIMALS {
+            let exact = format!("{:.80}", value);
+            assert!(exact.trim_end_matches('0').len() > 20, "{}", exact);
+        }
+        assert_ne!(
+            INEXACT_DECIMALS[0] + INEXACT_DECIMALS[1],
+            INEXACT_DECIMALS[2]
+        );
+    }
+
+    #[test]
+    fn test_near_ties_break_add_half_rounding() {
+        for value in NEAR_TIES {
+            if value.fract() != 0.5 && value.fract() != -0.5 && value.abs() >= 1.0 {
+                assert_eq!(value.round(), value);
+                assert_ne!((value + 0.5).floor(), value.round(), "{}", value);
+            }
+        }
+        let below_half = NEAR_TIES[7];
+        assert!(below_half < 0.5);
+        assert_eq!(below_half.round(), 0.0);
+        assert_eq!((below_half + 0.5).floor(), 1.0);
+    }
+
+    #[test]
+    fn test_subnormals() {
+        assert!(F64_SUBNORMALS.iter().all(|value| value.is_subnormal()));
+        assert!(F32_SUBNORMALS.iter().all(|value| value.is_subnormal()));
+        assert_eq!(F64_SUBNORMALS[3] + F64_SUBNORMALS[0], f64::MIN_POSITIVE);
+    }
+
+    #[test]
+    fn test_iterators_cover_every_set() {
+        let f64_count = F64_SPECIALS.len()
+            + F64_PRECISION_BOUNDARIES.len()
+            + INEXACT_DECIMALS.len()
+            + DECIMAL_TIES.len()
+            + NEAR_TIES.len()
+            + F64_SUBNORMALS.len();
+        assert_eq!(f64_values().count(), f64_count);
+        assert_eq!(f64_pairs().count(), f64_count * f64_count);
+        assert_eq!(
+            f32_values().count(),
+            F32_SPECIALS.len() + F32_PRECISION_BOUNDARIES.len() + F32_SUBNORMALS.len()
+        );
+    }
+
+    #[test]
+    fn test_native_ops_match_reference_on_corpus() {
+        for (a, b) in f64_pairs() {
+            assert!(same_f64(a + b, softref::add_f64(a, b)), "{} + {}", a, b);
+            assert!(same_f64(a * b, softref::mul_f64(a, b)), "{} * {}", a, b);
+            assert!(same_f64(a / b, softref::div_f64(a, b)), "{} / {}", a, b);
+        }
+        for a in f32_values() {
+            for b in f32_values() {
+                let soft = softref::add_f32(a, b);
+                assert!((soft.is_nan() && (a + b).is_nan()) || soft.to_bits() == (a + b).to_bits());
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use solana_floats::cross_rate::*;
+use solana_floats::error::FloatError;
+use solana_floats::quantize::RoundingMode;
+use solana_floats::units::Price;
+
+#[cfg(test)]
+mod cross_rate_tests {
+    use super::*;
+
+    fn sol_usd() -> Price<6> {
+        Price::new(150.123456).unwrap()
+    }
+
+    fn eth_usd() -> Price<8> {
+        Price::new(3000.12345678).unwrap()
+    }
+
+    #[test]
+    fn test_cross_rate_rounds_once() {
+        // 150.123456 / 3000.12345678 = 0.050039092778243...
+        let floor: Price<9> = cross_rate(sol_usd(), eth_usd(), RoundingMode::Floor).unwrap();
+        let ceil: Price<9> = cross_rate(sol_usd(), eth_usd(), RoundingMode::Ceil).unwrap();
+        let nearest: Price<9> = cross_rate(sol_usd(), eth_usd(), RoundingMode::HalfEven).unwrap();
+        assert_eq!(floor.to_raw(), Ok(50_039_092));
+        assert_eq!(ceil.to_raw(), Ok(50_039_093));
+        assert_eq!(nearest.to_raw(), Ok(50_039_093));
+    }
+
+    #[test]
+    fn test_cross_rate_of_equal_prices_is_one() {
+        let usdc: Price<6> = Price::new(1.000_123).unwrap();
+        let usdt: Price<8> = Price::new(1.000_123).unwrap();
+        let rate: Price<6> = cross_rate(usdc, usdt, RoundingMode::Floor).unwrap();
+        assert_eq!(rate.to_raw(), Ok(1_000_000));
+    }
+
+    #[test]
+    fn test_invert_price() {
+        // 1 / 150.123456 = 0.0066611842455...
+        let floor: Price<9> = invert_price(sol_usd(), RoundingMode::Floor).unwrap();
+        let ceil: Price<9> = invert_price(sol_usd(), RoundingMode::Ceil).unwrap();
+        assert_eq!(floor.to_raw(), Ok(6_661_184));
+        assert_eq!(ceil.to_raw(), Ok(6_661_185));
+        let two: Price<2> = Price::new(0.5).unwrap();
+        let inverse: Price<2> = invert_price(two, RoundingMode::Floor).unwrap();
+        assert_eq!(inverse.value(), 2.0);
+    }
+
+    #[test]
+    fn test_near_zero_prices() {
+        let zero: Price<8> = Price::new(0.0).unwrap();
+        let dust: Price<6> = Price::new(1e-9).unwrap();
+        assert_eq!(
+            cross_rate::<6, 8, 9>(sol_usd(), zero, RoundingMode::Floor),
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(
+            invert_price::<8, 9>(zero, RoundingMode::Floor),
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(
+            invert_price::<6, 9>(dust, RoundingMode::Floor),
+            Err(FloatError::PrecisionLoss)
+        );
+        assert_eq!(
+            cross_rate::<6, 8, 9>(dust, eth_usd(), RoundingMode::Floor),
+            Err(FloatError::PrecisionLoss)
+        );
+        // A zero numerator is a legitimate zero rate
+        let rate: Price<9> =
+            cross_rate(Price::<6>::new(0.0).unwrap(), eth_usd(), RoundingMode::Ceil).unwrap();
+        assert_eq!(rate.value(), 0.0);
+    }
+
+    #[test]
+    fn test_results_that_do_not_fit() {
+        let tiny: Price<6> = Price::new(0.000_001).unwrap();
+        // 1e-6 SOL/USD against 3000 ETH/USD is 3.3e-10 SOL/ETH
+        assert_eq!(
+            cross_rate::<6, 8, 9>(tiny, eth_usd(), RoundingMode::Floor),
+            Err(FloatError::PrecisionLoss)
+        );
+        assert_eq!(
+            cross_rate::<6, 8, 9>(tiny, eth_usd(), RoundingMode::Ceil).map(|p| p.to_raw()),
+            Ok(Ok(1))
+        );
+        // 1e6 * 1e18 base units exceeds u64
+        assert_eq!(
+            invert_price::<6, 18>(tiny, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+        // 1e18 base units fit in u64 but not exactly in f64
+        assert_eq!(
+            invert_price::<6, 12>(tiny, RoundingMode::Floor),
+            Err(FloatError::PrecisionLoss)
+        );
+        assert_eq!(
+            invert_price::<6, 9>(tiny, RoundingMode::Floor).map(|p| p.value()),
+            Ok(1_000_000.0)
+        );
+    }
+}
@@ -0,0 +1,759 @@
+use solana_floats::decimal64::Decimal64;
+use solana_floats::error::FloatError;
+
+#[cfg(test)]
+mod decimal64_tests {
+    use super::*;
+
+    fn dec(text: &str) -> Decimal64 {
+        text.parse().unwrap()
+    }
+
+    // Generated with Python's decimal module in the decimal64 context:
+    // Context(prec=16, Emax=384, Emin=-383, clamp=1, rounding=ROUND_HALF_EVEN)
+    const REFERENCE: &[(&str, char, &str, &str)] = &[
+        ("7E-20", '+', "8.93E-132", "7.000000000000000E-20"),
+        ("72357", '*', "6.5159E+32", "4.714709763E+37"),
+        (
+            "-5851670958835.113",
+            '+',
+            "-2.70827219462238E+15",
+            "-2714123865581215",
+        ),
+        ("6.07E+359", '/', "-0.00097", "-6.257731958762887E+362"),
+        (
+            "-3.88174200222175E+25",
+            '/',
+            "-3.1872837E+34",
+            "1.217884056641004E-9",
+        ),
+        ("0.0000011", '*', "7.6051E+5", "0.836561"),
+        (
+            "3.1060131E-349",
+            '/',
+            "9.25917828527825E+43",
+            "3.35452E-393",
+        ),
+        ("0.87895", '+', "2.8802E-35", "0.8789500000000000"),
+        (
+            "-9266113025.61122",
+            '*',
+            "6.51163336157E+12",
+            "-6.033753070964835E+22",
+        ),
+        ("0.05", '+', "400350757.098", "400350757.148"),
+        ("9934915981.74948", '*', "-2.6E-7", "-2583.078155254865"),
+        ("0.000169", '+', "2664462.5", "2664462.500169"),
+        ("-0.44", '/', "-0.00000912", "48245.61403508772"),
+        ("0.0040", '-', "269866918770", "-269866918769.9960"),
+        ("1.2273710E+37", '-', "70.038951", "1.227371000000000E+37"),
+        ("-0.1", '/', "1.79754372430E+13", "-5.563147012679318E-15"),
+        ("-3.5335436E+8", '-', "9E-7", "-353354360.0000009"),
+        (
+            "3332514509995.610",
+            '-',
+            "1.514831397820432E-92",
+            "3332514509995.610",
+        ),
+        ("-0.087", '/', "70075920.05450110", "-1.241510634927609E-9"),
+        ("-9.3401430", '/', "-7E+92", "1.334306142857143E-92"),
+        (
+            "2.706948397699224E+16",
+            '/',
+            "-6.58771956606E+14",
+            "-41.09082620403956",
+        ),
+        (
+            "-3633117528820.916",
+            '+',
+            "7907953056208634",
+            "7904319938679813",
+        ),
+        (
+            "5.902061452522924E+209",
+            '*',
+            "522048082835.3792",
+            "3.081159866066186E+221",
+        ),
+        ("-0.00008", '*', "4.4794340E+8", "-35835.4720"),
+        (
+            "5533185.29085",
+            '*',
+            "44095519041.30315",
+            "2.439886773517347E+17",
+        ),
+        ("9556784.8", '-', "-7691628511128.05", "7691638067912.85"),
+        ("-0.006", '+', "-0.000003", "-0.006003"),
+        ("4E+2", '+', "9E-7", "400.0000009"),
+        ("-9.72", '+', "6.5004568E+17", "6.500456800000000E+17"),
+        ("1E+1", '+', "0.00119", "10.00119"),
+        (
+            "4.230518209454904E-345",
+            '*',
+            "0.0001",
+            "4.230518209454904E-349",
+        ),
+        ("-2.8677316E+153", '*', "-4.22", "1.2101827352E+154"),
+        ("-4.5278407E-21", '-', "0.092351", "-0.09235100000000000"),
+        ("-4E-22", '+', "-358179465.5753601", "-358179465.5753601"),
+        ("1E+3", '/', "-0.00900", "-111111.1111111111"),
+        ("9.9048E+24", '*', "0.1", "9.9048E+23"),
+        ("2E+4", '+', "-7.1E+24", "-7.100000000000000E+24"),
+        ("168876103933.603", '/', "-3E+29", "-5.629203464453433E-19"),
+        ("2E+4", '+', "5E+2", "2.05E+4"),
+        (
+            "-8.55E-248",
+            '/',
+            "43754190954.8",
+            "-1.954098524832176E-258",
+        ),
+        ("-3337951.17768", '+', "50495995706.4", "50492657755.22232"),
+        (
+            "923174489.088777",
+            '*',
+            "3.003848171979132E+16",
+            "2.773076001467092E+25",
+        ),
+        ("-804547240985.0833", '-', "-6.2E+5", "-804546620985.0833"),
+        (
+            "9491368197047.97",
+            '+',
+            "4158803775174.126",
+            "13650171972222.10",
+        ),
+        ("9390164624355.534", '/', "-5829.1", "-1610911568.570711"),
+        (
+            "32560570.9704563",
+            '+',
+            "-78850873567.13684",
+            "-78818312996.16638",
+        ),
+        ("3765310202.484912", '*', "0.0019456", "7325787.529954645"),
+        ("-12403338.4314680", '+', "9.2045E+8", "908046661.5685320"),
+        (
+            "2425425066112.72",
+            '+',
+            "2.3411773E-23",
+            "2425425066112.720",
+        ),
+        (
+            "6.50586721845827E-316",
+            '/',
+            "9.31016819107399E+304",
+            "0E-398",
+        ),
+        ("0.0052638", '+', "92388710281112.60", "92388710281112.61"),
+        ("0.009", '+', "-0E+28", "0.009"),
+        ("7.1933541E+10", '+', "4.1609E+22", "4.160900000007193E+22"),
+        ("0.59448947", '+', "46.8", "47.39448947"),
+        ("1.9841986E+8", '+', "2E+30", "2.000000000000000E+30"),
+        (
+            "-1.04760568469E+13",
+            '/',
+            "7.497217596051207E+17",
+            "-0.00001397325969626085",
+        ),
+        ("-7", '*', "2E-7", "-0.0000014"),
+        ("-4.8566E+6", '*', "0.00029136", "-1415.018976"),
+        ("2071.4", '/', "-7E-12", "-295914285714285.7"),
+        ("-4E-11", '*', "9E+2", "-3.6E-8"),
+        (
+            "4.0032240E+14",
+            '*',
+            "0.000004527493517211441",
+            "1812457070.794525",
+        ),
+        ("7.0091891E+10", '/', "-27998.7557574", "-2503393.065296300"),
+        ("6E-7", '*', "-4774844808272.270", "-2864906.884963362"),
+        ("-6.9965065E-99", '*', "8.0E-20", "-5.597205200E-118"),
+        ("33212", '+', "-7.5659383E+8", "-756560618"),
+        ("4.9043E+8", '*', "-0.0736", "-36095648"),
+        (
+            "245420774383.9763",
+            '+',
+            "5.7553E+166",
+            "5.755300000000000E+166",
+        ),
+        (
+            "91294002589.63214",
+            '*',
+            "1.95238141524097E+17",
+            "1.782407139789588E+28",
+        ),
+        ("0.000008", '*', "9420.81506843", "0.07536652054744"),
+        ("91187969810610.25", '-', "0E+2", "91187969810610.25"),
+        ("-0.06", '+', "-7.10861241593E+14", "-710861241593000.1"),
+        (
+            "-4.7732482E-309",
+            '/',
+            "-669049885.977",
+            "7.134368154072282E-318",
+        ),
+        ("-9719974.76737", '-', "-88.4", "-9719886.36737"),
+        ("-0.7", '-', "-9.928812476474464E-12", "-0.6999999999900712"),
+        (
+            "493431839030.4619",
+            '/',
+            "1.85E-239",
+            "2.667199129894389E+250",
+        ),
+        ("2.1E+30", '-', "-0.013125301", "2.100000000000000E+30"),
+        ("6E-8", '*', "-7.8093517E-14", "-4.68561102E-21"),
+        ("7258.8407", '/', "8.1E+5", "0.008961531728395062"),
+        (
+            "-68760473.31038935",
+            '/',
+            "3285850601151.23",
+            "-0.00002092623239970145",
+        ),
+        ("-0.23", '*', "-0.000", "0.00000"),
+        (
+            "-42334988013412.27",
+            '-',
+            "-5.25577066934E+13",
+            "10222718679987.73",
+        ),
+        ("226759.52", '+', "7.38121740304477E+15", "7381217403271530"),
+        (
+            "7.8114056E+10",
+            '/',
+            "1.62393503132E+15",
+            "0.00004810171250293538",
+        ),
+        (
+            "-39270572990.70031",
+            '+',
+            "-6.4052E+8",
+            "-39911092990.70031",
+        ),
+        ("-5.5627450E+8", '+', "9.9E+14", "9.8999944372550E+14"),
+        (
+            "-8.5602104E-7",
+            '-',
+            "5.080352415248464E+17",
+            "-5.080352415248464E+17",
+        ),
+        ("0.00000552", '/', "624128.40", "8.844333954359391E-12"),
+        (
+            "3.32385965721E+15",
+            '/',
+            "7.493670978565900E+18",
+            "0.0004435555906734116",
+        ),
+        (
+            "7.122183332405359E+17",
+            '-',
+            "958138816.8143436",
+            "7.122183322823971E+17",
+        ),
+        ("5.3E-57", '*', "-1.0358376E+10", "-5.48993928E-47"),
+        ("-3.2715098E+11", '-', "-0.00195", "-327150979999.9980"),
+        (
+            "9.08244055274854E+102",
+            '/',
+            "168734703901.2899",
+            "5.382674898971455E+91",
+        ),
+        ("0.2", '+', "3.75155976428326E-333", "0.2000000000000000"),
+        ("5530001962780043", '*', "0.0044", "24332008636232.19"),
+        (
+            "0.00649270023949",
+            '/',
+            "164246825988602.7",
+            "3.953014130051157E-17",
+        ),
+        (
+            "-7.117860062650804E+19",
+            '-',
+            "-4.77903899782E+13",
+            "-7.117855283611806E+19",
+        ),
+        ("5E+259", '+', "4E+2", "5.000000000000000E+259"),
+        (
+            "-2.45169255709345E+17",
+            '+',
+            "1.3E-270",
+            "-2.451692557093450E+17",
+        ),
+        (
+            "3.32801188438100E-296",
+            '+',
+            "-564911163.692457",
+            "-564911163.6924570",
+        ),
+        ("-89931", '/', "2.64E+4", "-3.406477272727273"),
+        (
+            "-8.67850789604540E-20",
+            '/',
+            "8.70440457364554E+17",
+            "-9.970248766149327E-38",
+        ),
+        (
+            "-10.075684",
+            '+',
+            "6.910121789386892E+18",
+            "6.910121789386892E+18",
+        ),
+        ("0.0908", '-', "0.045680", "0.045120"),
+        (
+            "7.7970E+8",
+            '-',
+            "-3.7677240E+201",
+            "3.767724000000000E+201",
+        ),
+        ("190.81", '*', "-0.0089", "-1.698209"),
+        ("0.00005", '+', "90194180.2989418", "90194180.2989918"),
+        ("0.00085", '-', "89632218470.81746", "-89632218470.81661"),
+        ("0.0001", '+', "-3.6E-15", "0.0000999999999964"),
+        ("-0.799", '*', "0.0098", "-0.0078302"),
+        ("-3E+23", '-', "8E+253", "-8.000000000000000E+253"),
+        ("9.6", '*', "-6.7603E+5", "-6489888"),
+        ("549128021.901", '*', "8E-9", "4.393024175208"),
+        ("6E+8", '*', "-5183.66147891", "-3110196887346"),
+        (
+            "-5.231316943507235E+35",
+            '/',
+            "4.013242152542416E+43",
+            "-1.303513903389348E-8",
+        ),
+        (
+            "0.000005859081179771269",
+            '-',
+            "-5E-9",
+            "0.000005864081179771269",
+        ),
+        ("5.58E+5", '-', "688513005835757.7", "-688513005277757.7"),
+        ("8E-387", '*', "2.3326", "1.86608E-386"),
+        (
+            "3.49E+150",
+            '/',
+            "-6.540845381867003E+17",
+            "-5.335701726989643E+132",
+        ),
+        ("-6.7284", '-', "-0.00056", "-6.72784"),
+        ("-7.4418913E+202", '+', "0E-80", "-7.441891300000000E+202"),
+        ("-7.79E+14", '-', "-467353090.040749", "-778999532646910.0"),
+        ("2E-13", '/', "59093.225", "3.384482738926501E-18"),
+        ("0.0000912", '/', "0.00003", "3.04"),
+        (
+            "-3.80821614576E+13",
+            '*',
+            "5.544019603660470E-265",
+            "-2.111282496706976E-251",
+        ),
+        ("-0.0008", '*', "-8.66038033239E+13", "69283042659.12"),
+        (
+            "-5.588717499888946E+210",
+            '+',
+            "9605745.85939",
+            "-5.588717499888946E+210",
+        ),
+        (
+            "6.667352887010225E-12",
+            '*',
+            "3626.85116430",
+            "2.418149658105200E-8",
+        ),
+        ("2E-90", '*', "-5.6597E+6", "-1.13194E-83"),
+        (
+            "57662133789.81503",
+            '-',
+            "487214192093.1795",
+            "-429552058303.3645",
+        ),
+        ("-5E+3", '+', "1401366.7", "1396366.7"),
+        ("-5.7636E+40", '-', "0.000004", "-5.763600000000000E+40"),
+        (
+            "9.717383505485687E+34",
+            '/',
+            "0.868",
+            "1.119514228742591E+35",
+        ),
+        ("4.8079139E+28", '/', "-3.08E+6", "-1.561011006493506E+22"),
+        ("-7.9193E+8", '/', "38", "-20840263.15789474"),
+        ("131675737.161", '+', "9.56E+82", "9.560000000000000E+82"),
+        (
+            "-0.79680090",
+            '*',
+            "8.216784694516532E+17",
+            "-6.547141439696998E+17",
+        ),
+        ("0.5", '+', "-0.5", "0.0"),
+        ("-8E+2", '/', "4.88", "-163.9344262295082"),
+        (
+            "-0.08",
+            '+',
+            "3.642410283113326E-194",
+            "-0.08000000000000000",
+        ),
+        (
+            "-7.83691575199E+20",
+            '*',
+            "469081372815.156",
+            "-3.676151199580190E+32",
+        ),
+        (
+            "3.6698175E+292",
+            '/',
+            "30171021.9861",
+            "1.216338479250292E+285",
+        ),
+        ("7", '/', "-6.6E+6", "-0.000001060606060606061"),
+        (
+            "60337621146.2700",
+            '+',
+            "779422199.643667",
+            "61117043345.91367",
+        ),
+        ("0.41", '/', "-0.92", "-0.4456521739130435"),
+        (
+            "-24730997500.8",
+            '+',
+            "6.69077085249E+13",
+            "66882977527399.2",
+        ),
+        (
+            "9.4735774E-178",
+            '/',
+            "5.95493665825E+12",
+            "1.590877946094566E-190",
+        ),
+        (
+            "-35044787121665.1",
+            '+',
+            "6.8337E+16",
+            "6.830195521287833E+16",
+        ),
+        ("5868830506610818", '+', "-0.01", "5868830506610818"),
+        ("6285696561911803", '+', "7E-18", "6285696561911803"),
+        ("-1.6476E+227", '*', "0.00002", "-3.2952E+222"),
+        (
+            "1.94E+4",
+            '-',
+            "5.55026760947540E+40",
+            "-5.550267609475400E+40",
+        ),
+        (
+            "7.21086068308E+15",
+            '-',
+            "8.46031885854827E-256",
+            "7210860683080000",
+        ),
+        (
+            "-8E-8",
+            '*',
+            "-9.98804908146114E+152",
+            "7.990439265168912E+145",
+        ),
+        ("0.0001", '+', "6.2797177E+8", "627971770.0001"),
+        ("-9E-7", '*', "-4.3760E-69", "3.93840E-75"),
+        (
+            "-5.783659209668385E+21",
+            '*',
+            "177632734803.2133",
+            "-1.027367202583186E+33",
+        ),
+        ("5E-8", '*', "0.0010699", "5.3495E-11"),
+        ("27084807204296.67", '-', "0E+4", "27084807204296.67"),
+        ("7E+3", '-', "-6E-8", "7000.00000006"),
+        ("5.1494E+8", '-', "9.91131328343E-151", "514940000.0000000"),
+        ("5.73E+14", '-', "0.981", "572999999999999.0"),
+        (
+            "3.56533992356E+12",
+            '*',
+            "-857626.136353",
+            "-3.057728703427863E+18",
+        ),
+        (
+            "120158580982.989",
+            '/',
+            "11175056.60238463",
+            "10752.39126371391",
+        ),
+        ("-479882521.874", '*', "1050.0815", "-503915758393.2327"),
+        (
+            "-4.72879440167839E+16",
+            '+',
+            "-6E+23",
+            "-6.000000472879440E+23",
+        ),
+        ("4.7271553E+31", '*', "0.0009", "4.25443977E+28"),
+        (
+            "-3.248250713436498E+18",
+            '-',
+            "0.00000346",
+            "-3.248250713436498E+18",
+        ),
+        ("187446868885.4833", '-', "5.3E+2", "187446868355.4833"),
+        ("748994.2040824449", '-', "0.0090225", "748994.1950599449"),
+        (
+            "-8E-71",
+            '*',
+            "2.29111002285902E+142",
+            "-1.832888018287216E+72",
+        ),
+        ("-7E-34", '+', "0.00060136", "0.0006013600000000000"),
+        ("-0.005", '/', "76693343335.5", "-6.519470637923784E-14"),
+        (
+            "4.92E-27",
+            '*',
+            "23792574.55374552",
+            "1.170594668044280E-19",
+        ),
+        ("-4E+231", '+', "0.43", "-4.000000000000000E+231"),
+        ("-40.0", '-', "1.55E+273", "-1.550000000000000E+273"),
+        ("8.1905E+5", '*', "5E+1", "4.09525E+7"),
+        ("6.62070442752609E-118", '-', "9", "-9.000000000000000"),
+        (
+            "-0.0002",
+            '/',
+            "8.602178202136382E+18",
+            "-2.324992522827873E-23",
+        ),
+        ("25080819.5899", '+', "-5", "25080814.5899"),
+        ("8E-184", '/', "1.5362218E-7", "5.207581353161373E-177"),
+        (
+            "3577.14273581",
+            '+',
+            "321797357074721.8",
+            "321797357078298.9",
+        ),
+        (
+            "-7.8538E+25",
+            '/',
+            "6.65670198976397E-345",
+            "-1.179833498942391E+370",
+        ),
+        ("-5.74E-16", '*', "83", "-4.7642E-14"),
+        (
+            "2.646608420511297E+16",
+            '/',
+            "-3.6661E-51",
+            "-7.219138650094916E+66",
+        ),
+        (
+            "-7.983044614372166E+19",
+            '/',
+            "0.0070",
+            "-1.140434944910309E+22",
+        ),
+        ("-0.07", '-', "1", "-1.07"),
+        (
+            "-0.00001",
+            '-',
+            "0.001371498493846055",
+            "-0.001381498493846055",
+        ),
+        ("-1.04E-21", '-', "7.0E+2", "-700.0000000000000"),
+        ("8.5E-315", '/', "0.0000506", "1.679841897233202E-310"),
+        (
+            "2.7613E+196",
+            '-',
+            "-4.37460900714E+242",
+            "4.374609007140000E+242",
+        ),
+        (
+            "6.64090794908E+15",
+            '-',
+            "4.553757747001809E+41",
+            "-4.553757747001809E+41",
+        ),
+        ("73738180.48999126", '-', "910.33", "73737270.15999126"),
+        (
+            "-9.3154E+5",
+            '-',
+            "-7.192251340843811E+18",
+            "7.192251340842879E+18",
+        ),
+        ("-6.7534095", '/', "1E+10", "-6.7534095E-10"),
+        (
+            "9E-12",
+            '*',
+            "2.647696314759806E+313",
+            "2.382926683283825E+302",
+        ),
+        (
+            "37.9709328696",
+            '*',
+            "4.70298881297E+12",
+            "178576872503763.7",
+        ),
+        ("9.3057776E+10", '-', "5E-8", "93057776000.00000"),
+        (
+            "3.48981624895679E-144",
+            '+',
+            "0.000000",
+            "3.48981624895679E-144",
+        ),
+        ("-0.000006", '*', "-58", "0.000348"),
+        ("4E-7", '-', "6E+3", "-5999.9999996"),
+    ];
+
+    #[test]
+    fn test_decimal_fractions_are_exact() {
+        let sum = dec("0.1").checked_add(dec("0.2")).unwrap();
+        assert_eq!(sum, dec("0.3"));
+        assert_eq!(sum.to_string(), "0.3");
+        let total = (0..10).fold(Decimal64::ZERO, |total, _| {
+            total.checked_add(Decimal64::new(1, -1).unwrap()).unwrap()
+        });
+        assert_eq!(total, Decimal64::ONE);
+        assert_eq!(
+            dec("1.10").checked_mul(dec("3")).unwrap().to_string(),
+            "3.30"
+        );
+    }
+
+    #[test]
+    fn test_matches_reference_implementation() {
+        for &(lhs, op, rhs, expected) in REFERENCE {
+            let (a, b) = (dec(lhs), dec(rhs));
+            let result = match op {
+                '+' => a.checked_add(b),
+                '-' => a.checked_sub(b),
+                '*' => a.checked_mul(b),
+                _ => a.checked_div(b),
+            }
+            .unwrap();
+            // Compare the printed form so the exponent is checked too
+            assert_eq!(result.to_string(), expected, "{} {} {}", lhs, op, rhs);
+        }
+    }
+
+    #[test]
+    fn test_rounds_half_even() {
+        let one = Decimal64::ONE;
+        assert_eq!(
+            one.checked_div(dec("3")).unwrap().to_string(),
+            "0.3333333333333333"
+        );
+        assert_eq!(
+            dec("2").checked_div(dec("3")).unwrap().to_string(),
+            "0.6666666666666667"
+        );
+        let max_coefficient = dec("9999999999999999");
+        assert_eq!(
+            max_coefficient.checked_add(dec("0.5")).unwrap().to_string(),
+            "1.000000000000000E+16"
+        );
+        assert_eq!(
+            dec("1000000000000000")
+                .checked_add(dec("0.5"))
+                .unwrap()
+                .to_string(),
+            "1000000000000000"
+        );
+        // Below the smallest exponent the result rounds gradually to zero
+        let tiny = dec("1E-398");
+        assert_eq!(tiny.checked_div(dec("2")).unwrap(), Decimal64::ZERO);
+        assert_eq!(dec("3E-398").checked_div(dec("2")).unwrap(), dec("2E-398"));
+    }
+
+    #[test]
+    fn test_bid_encoding() {
+        assert_eq!(Decimal64::ONE.to_bits(), 0x31c0_0000_0000_0001);
+        assert_eq!(dec("0.1").to_bits(), 0x31a0_0000_0000_0001);
+        assert_eq!(dec("-7.50").to_bits(), 0xb180_0000_0000_02ee);
+        assert_eq!(dec("9999999999999999E+369"), Decimal64::MAX);
+        assert_eq!(Decimal64::MAX.to_bits(), 0x77fb_86f2_6fc0_ffff);
+        assert_eq!(
+            Decimal64::MIN.checked_sub(Decimal64::MIN),
+            Ok(Decimal64::ZERO)
+        );
+        for text in ["0.1", "-123.456", "9999999999999999", "1E-398", "-0"] {
+            let decimal = dec(text);
+            assert_eq!(
+                Decimal64::from_bits(decimal.to_bits()).unwrap().to_string(),
+                text
+            );
+        }
+        // Infinity, NaN and a coefficient of 10^16
+        assert_eq!(
+            Decimal64::from_bits(0x7800_0000_0000_0000),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            Decimal64::from_bits(0x7c00_0000_0000_0000),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            Decimal64::from_bits(0x6c73_86f2_6fc1_0000),
+            Err(FloatError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Decimal64::ONE.checked_div(dec("-0.00")),
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(
+            Decimal64::MAX.checked_add(Decimal64::MAX),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            Decimal64::MAX.checked_mul(dec("1.1")),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(Decimal64::new(1, 370), Err(FloatError::OutOfRange));
+        assert_eq!(
+            Decimal64::from_parts(false, 10_000_000_000_000_000, 0),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            "1.2345678901234567".parse::<Decimal64>(),
+            Err(FloatError::PrecisionLoss)
+        );
+        assert_eq!("1e400".parse::<Decimal64>(), Err(FloatError::OutOfRange));
+        for text in ["", ".", "abc", "1.2.3", "1e", "--1", "NaN", "inf"] {
+            assert_eq!(
+                text.parse::<Decimal64>(),
+                Err(FloatError::NaNOperand),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(dec("+012.3400").to_string(), "12.3400");
+        assert_eq!(dec("1.5e-7").to_string(), "1.5E-7");
+        assert_eq!(dec("0.000001").to_string(), "0.000001");
+        assert_eq!(dec("12e3").to_string(), "1.2E+4");
+        assert_eq!(dec(".5").to_string(), "0.5");
+        assert_eq!(dec("-0.00").to_string(), "-0.00");
+        // Excess trailing zeros move into the exponent
+        assert_eq!(
+            dec("12345678901234560000").to_string(),
+            "1.234567890123456E+19"
+        );
+        assert_eq!(format!("{:?}", dec("2.50")), "Decimal64(2.50)");
+    }
+
+    #[test]
+    fn test_value_equality_and_order() {
+        assert_eq!(dec("1.0"), dec("1.00"));
+        assert_ne!(dec("1.0").to_bits(), dec("1.00").to_bits());
+        assert_eq!(dec("-0"), dec("0E+5"));
+        let mut values = [
+            dec("2.5"),
+            dec("-1E+3"),
+            dec("0.25"),
+            dec("-0.001"),
+            dec("250E-2"),
+        ];
+        values.sort();
+        let sorted: Vec<String> = values.iter().map(Decimal64::to_string).collect();
+        assert_eq!(sorted, ["-1E+3", "-0.001", "0.25", "2.5", "2.50"]);
+        assert!(Decimal64::MIN < Decimal64::MAX);
+    }
+
+    #[test]
+    fn test_to_f64() {
+        assert_eq!(dec("0.1").to_f64(), 0.1);
+        assert_eq!(dec("-2.5E+300").to_f64(), -2.5e300);
+        assert_eq!(dec("1E-398").to_f64(), 0.0);
+    }
+}
@@ -0,0 +1,171 @@
+use solana_floats::decimal::*;
+use solana_floats::error::FloatError;
+use solana_floats::quantize::RoundingMode;
+
+#[cfg(test)]
+mod decimal_tests {
+    use super::*;
+
+    fn dec(mantissa: u128, scale: u32) -> Decimal {
+        Decimal::new(mantissa, scale).unwrap()
+    }
+
+    #[test]
+    fn test_sums_are_exact() {
+        // The f64 version of this loop ends at 99.9999999999986
+        let mut total = Decimal::ZERO;
+        for _ in 0..1000 {
+            total = total.checked_add(dec(1, 1)).unwrap();
+        }
+        assert_eq!(total, dec(100, 0));
+        assert_eq!(total.scale(), 1);
+        assert_eq!(dec(1, 1).checked_add(dec(2, 1)), Ok(dec(3, 1)));
+        assert_eq!(dec(30, 2).checked_sub(dec(1, 1)), Ok(dec(2, 1)));
+        assert_eq!(
+            dec(1, 1).checked_sub(dec(2, 1)),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            dec(u128::MAX, 0).checked_add(Decimal::ONE),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_mul_div_keep_left_scale() {
+        // 19.99 USDC at a 7.5% fee
+        let fee = dec(1999, 2)
+            .checked_mul(dec(75, 3), RoundingMode::Ceil)
+            .unwrap();
+        assert_eq!((fee.mantissa(), fee.scale()), (150, 2));
+        let fee = dec(1999, 2)
+            .checked_mul(dec(75, 3), RoundingMode::Floor)
+            .unwrap();
+        assert_eq!(fee.mantissa(), 149);
+
+        let third = dec(100, 2)
+            .checked_div(dec(3, 0), RoundingMode::HalfEven)
+            .unwrap();
+        assert_eq!((third.mantissa(), third.scale()), (33, 2));
+        assert_eq!(
+            dec(100, 2).checked_div(dec(3, 0), RoundingMode::Ceil),
+            Ok(dec(34, 2))
+        );
+        assert_eq!(
+            dec(1, 0).checked_div(Decimal::ZERO, RoundingMode::Floor),
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(
+            dec(u128::MAX, 0).checked_mul(dec(2, 0), RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_rescale() {
+        assert_eq!(
+            dec(12_345, 3).rescale(1, RoundingMode::HalfEven),
+            Ok(dec(123, 1))
+        );
+        assert_eq!(
+            dec(12_355, 3).rescale(2, RoundingMode::HalfEven),
+            Ok(dec(1236, 2))
+        );
+        assert_eq!(
+            dec(12_345, 3)
+                .rescale(5, RoundingMode::Floor)
+                .unwrap()
+                .mantissa(),
+            1_234_500
+        );
+        assert_eq!(
+            dec(1, 0).rescale(39, RoundingMode::Floor),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            dec(u128::MAX, 0).rescale(1, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(Decimal::new(1, MAX_SCALE + 1), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_from_f64_uses_shortest_decimal() {
+        assert_eq!(
+            Decimal::from_f64(0.1, 6, RoundingMode::Floor),
+            Ok(dec(1, 1))
+        );
+        assert_eq!(
+            Decimal::from_f64(2.675, 2, RoundingMode::HalfEven),
+            Ok(dec(268, 2))
+        );
+        assert_eq!(
+            Decimal::from_f64(2.675, 2, RoundingMode::Floor),
+            Ok(dec(267, 2))
+        );
+        assert_eq!(
+            Decimal::from_f64(1e20, 0, RoundingMode::Floor),
+            Ok(dec(10u128.pow(20), 0))
+        );
+        assert_eq!(Decimal::from_f64_exact(0.125, 3), Ok(dec(125, 3)));
+        assert_eq!(
+            Decimal::from_f64_exact(0.125, 2),
+            Err(FloatError::PrecisionLoss)
+        );
+        assert_eq!(
+            Decimal::from_f64(-0.004, 2, RoundingMode::HalfEven),
+            Ok(Decimal::ZERO)
+        );
+        assert_eq!(
+            Decimal::from_f64(-0.01, 2, RoundingMode::HalfEven),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            Decimal::from_f64(f64::NAN, 2, RoundingMode::Floor),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            Decimal::from_f64(1e40, 0, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_to_f64_is_nearest() {
+        assert_eq!(dec(1, 1).to_f64(), 0.1);
+        assert_eq!(dec(3, 1).to_f64(), 0.3);
+        assert_eq!(dec(u128::MAX, 38).to_f64(), 3.4028236692093845);
+        for value in [0.1, 19.99, 123_456.789, 1e-9] {
+            assert_eq!(
+                Decimal::from_f64(value, 12, RoundingMode::HalfEven)
+                    .unwrap()
+                    .to_f64(),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_value_equality_and_display() {
+        assert_eq!(dec(10, 1), dec(1000, 3));
+        assert!(dec(999, 3) < dec(1, 0));
+        assert!(dec(u128::MAX, 0) > dec(u128::MAX, 38));
+        assert_eq!(dec(1990, 2).to_string(), "19.90");
+        assert_eq!(dec(5, 3).to_string(), "0.005");
+        assert_eq!(dec(42, 0).to_string(), "42");
+        assert_eq!(format!("{:>8}", dec(5, 3)), "   0.005");
+    }
+
+    #[test]
+    fn test_round_half_even_to_scale() {
+        assert_eq!(dec(2675, 3).round_half_even_to_scale(2), Ok(dec(268, 2)));
+        assert_eq!(dec(2665, 3).round_half_even_to_scale(2), Ok(dec(266, 2)));
+        assert_eq!(dec(2666, 3).round_half_even_to_scale(2), Ok(dec(267, 2)));
+        let rounded = dec(1999, 2).round_half_even_to_scale(4).unwrap();
+        assert_eq!(rounded.scale(), 2);
+        assert_eq!(
+            dec(1, 0).round_half_even_to_scale(MAX_SCALE + 1),
+            Err(FloatError::OutOfRange)
+        );
+    }
+}
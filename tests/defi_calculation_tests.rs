@@ -1,3 +1,5 @@
+#![allow(clippy::clone_on_copy)]
+
 use solana_program::msg;
 
 #[cfg(test)]
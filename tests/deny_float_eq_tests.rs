@@ -0,0 +1,45 @@
+//! `#[deny_float_eq]` on code that passes the check. Rejections are covered by
+//! the unit tests in the macros crate. Run with
+//!
+//! ```text
+//! cargo test --features macros --test deny_float_eq_tests
+//! ```
+#![cfg(feature = "macros")]
+
+use solana_floats::compare::total_cmp_with_tolerance;
+use solana_floats::deny_float_eq;
+use std::cmp::Ordering;
+
+#[deny_float_eq]
+fn is_balanced(assets: f64, liabilities: f64, tolerance: f64) -> bool {
+    total_cmp_with_tolerance(assets, liabilities, tolerance) == Ok(Ordering::Equal)
+}
+
+#[deny_float_eq]
+fn same_bits(a: f64, b: f64) -> bool {
+    a.to_bits() == b.to_bits()
+}
+
+struct Vault {
+    shares: u64,
+}
+
+#[deny_float_eq]
+impl Vault {
+    fn is_empty(&self) -> bool {
+        self.shares == 0
+    }
+}
+
+#[cfg(test)]
+mod deny_float_eq_tests {
+    use super::*;
+
+    #[test]
+    fn test_annotated_code_is_unchanged() {
+        assert!(is_balanced(0.1 + 0.2, 0.3, 1e-15));
+        assert!(!same_bits(0.1 + 0.2, 0.3));
+        assert!(same_bits(-0.0, -0.0));
+        assert!(Vault { shares: 0 }.is_empty());
+    }
+}
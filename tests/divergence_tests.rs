@@ -0,0 +1,66 @@
+use solana_floats::divergence::*;
+use solana_floats::double_ops::BinaryOp;
+
+#[cfg(test)]
+mod divergence_tests {
+    use super::*;
+
+    // 1 + 2^-53 + 2^-64: just above halfway between 1 and the next f64, but
+    // exactly halfway once rounded to 64 bits
+    const DOUBLE_ROUNDING: (BinaryOp, f64, f64) = (BinaryOp::Add, 1.0, 1.1107651257113993e-16);
+
+    fn workload() -> Vec<(BinaryOp, f64, f64)> {
+        vec![
+            (BinaryOp::Add, 0.1, 0.2),
+            DOUBLE_ROUNDING,
+            (BinaryOp::Multiply, 1e-308, 0.1),
+            (BinaryOp::Divide, 1.0, 3.0),
+            (BinaryOp::Add, 5e-324, 0.0),
+        ]
+    }
+
+    #[test]
+    fn test_native_matches_the_reference() {
+        assert_eq!(
+            compare(&workload(), Environment::SoftFloat, Environment::Native),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_x87_double_rounding_is_reported() {
+        let (op, a, b) = DOUBLE_ROUNDING;
+        assert_eq!(b, f64::from_bits(0x3ca0_0200_0000_0000));
+        assert_eq!(Environment::SoftFloat.compute(op, a, b), 1.0000000000000002);
+        assert_eq!(Environment::X87Extended.compute(op, a, b), 1.0);
+        let divergences = compare(
+            &workload(),
+            Environment::SoftFloat,
+            Environment::X87Extended,
+        );
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].index, 1);
+        assert_eq!(
+            divergences[0].baseline_bits,
+            1.0000000000000002f64.to_bits()
+        );
+        assert_eq!(divergences[0].candidate_bits, 1.0f64.to_bits());
+    }
+
+    #[test]
+    fn test_flush_to_zero_is_reported() {
+        let divergences = compare(&workload(), Environment::Native, Environment::FlushToZero);
+        let indices: Vec<usize> = divergences.iter().map(|d| d.index).collect();
+        assert_eq!(indices, vec![2, 4]);
+        assert_eq!(divergences[1].candidate_bits, 0);
+        assert_eq!(
+            Environment::FlushToZero.compute(BinaryOp::Multiply, -1e-308, 0.1),
+            -0.0
+        );
+        assert!(
+            Environment::FlushToZero
+                .compute(BinaryOp::Multiply, -1e-308, 0.1)
+                .is_sign_negative()
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use solana_floats::dust::DustTracker;
+use solana_floats::error::FloatError;
+use solana_floats::mul_div::mul_div;
+use solana_floats::quantize::RoundingMode;
+use solana_program::program_error::ProgramError;
+
+#[cfg(test)]
+mod dust_tests {
+    use super::*;
+
+    #[test]
+    fn test_floored_fees_sweep_whole_units() {
+        // 0.3% fees computed in units of 10^-3 base units, paid in base units
+        let mut dust = DustTracker::new(1_000).unwrap();
+        let mut paid = 0;
+        let mut charged = 0;
+        for amount in [1_001u64, 2_503, 777, 10_000, 333] {
+            let fee = mul_div(amount, 3, 1, RoundingMode::Floor).unwrap();
+            charged += fee;
+            paid += dust.quantize(fee).unwrap();
+        }
+        assert_eq!(charged, 43_842);
+        assert_eq!(paid, 42);
+        assert_eq!(dust.pending(), 1_842);
+        assert_eq!(dust.payable(), 1);
+        assert_eq!(dust.sweep(), 1);
+        assert_eq!(dust.pending(), 842);
+        assert_eq!((paid + 1) * 1_000 + dust.pending(), charged);
+    }
+
+    #[test]
+    fn test_record_reports_payable_units() {
+        let mut dust = DustTracker::new(10).unwrap();
+        assert_eq!(dust.record(4), Ok(0));
+        assert_eq!(dust.record(5), Ok(0));
+        assert_eq!(dust.record(1), Ok(1));
+        assert_eq!(dust.record(25), Ok(3));
+        assert_eq!(dust.sweep(), 3);
+        assert_eq!(dust.sweep(), 0);
+        assert_eq!(dust.pending(), 5);
+    }
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(DustTracker::new(0), Err(FloatError::OutOfRange));
+        let mut dust = DustTracker::new(7).unwrap();
+        dust.record(u64::MAX - 1).unwrap();
+        let before = dust;
+        assert_eq!(dust.record(2), Err(FloatError::Overflow));
+        assert_eq!(dust, before);
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let mut dust = DustTracker::new(1_000_000).unwrap();
+        dust.record(123_456_789).unwrap();
+        let packed = dust.pack();
+        assert_eq!(DustTracker::unpack(&packed), Ok(dust));
+        assert_eq!(
+            DustTracker::unpack(&packed[..15]),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            DustTracker::unpack(&[0u8; DustTracker::PACKED_LEN]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
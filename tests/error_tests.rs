@@ -0,0 +1,58 @@
+use solana_floats::error::FloatError;
+use solana_program::program_error::ProgramError;
+
+#[cfg(test)]
+mod error_tests {
+    use super::*;
+
+    const ALL: [FloatError; 5] = [
+        FloatError::DivisionByZero,
+        FloatError::NaNOperand,
+        FloatError::Overflow,
+        FloatError::PrecisionLoss,
+        FloatError::OutOfRange,
+    ];
+
+    #[test]
+    fn test_codes_are_stable() {
+        let codes: Vec<u32> = ALL.iter().map(|error| error.code()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                0x5f10_0000,
+                0x5f10_0001,
+                0x5f10_0002,
+                0x5f10_0003,
+                0x5f10_0004
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_program_error() {
+        for error in ALL {
+            assert_eq!(
+                ProgramError::from(error),
+                ProgramError::Custom(error.code())
+            );
+            assert_eq!(FloatError::from_code(error.code()), Some(error));
+        }
+        assert_eq!(FloatError::from_code(0), None);
+        assert_eq!(
+            FloatError::from_code(FloatError::CUSTOM_CODE_BASE + 5),
+            None
+        );
+    }
+
+    #[test]
+    fn test_question_mark_converts() {
+        fn divide(a: f64, b: f64) -> Result<f64, ProgramError> {
+            Ok(solana_floats::double_ops::divide_doubles(a, b)?)
+        }
+        assert_eq!(divide(1.0, 4.0), Ok(0.25));
+        assert_eq!(
+            divide(1.0, 0.0),
+            Err(ProgramError::Custom(FloatError::DivisionByZero.code()))
+        );
+    }
+}
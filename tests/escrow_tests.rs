@@ -0,0 +1,60 @@
+use solana_floats::error::FloatError;
+use solana_floats::escrow::*;
+
+#[cfg(test)]
+mod escrow_tests {
+    use super::*;
+
+    fn split(a: u64, b: u64, dust: u64) -> InterestSplit {
+        InterestSplit { a, b, dust }
+    }
+
+    #[test]
+    fn test_policies_place_the_odd_unit() {
+        // 1_001 split 1:1 leaves 500.5 each
+        let cases = [
+            (SplitPolicy::FavorA, split(501, 500, 0)),
+            (SplitPolicy::FavorB, split(500, 501, 0)),
+            (SplitPolicy::FavorLarger, split(501, 500, 0)),
+            (SplitPolicy::Retain, split(500, 500, 1)),
+        ];
+        for (policy, expected) in cases {
+            assert_eq!(split_interest(1_001, 7_000, 7_000, policy), Ok(expected));
+        }
+        assert_eq!(
+            split_interest(1_001, 7_000, 7_001, SplitPolicy::FavorLarger),
+            Ok(split(500, 501, 0))
+        );
+    }
+
+    #[test]
+    fn test_even_and_one_sided_splits() {
+        // 3:1 principal divides 1_000 exactly
+        assert_eq!(
+            split_interest(1_000, 300, 100, SplitPolicy::Retain),
+            Ok(split(750, 250, 0))
+        );
+        assert_eq!(
+            split_interest(999, 0, 5, SplitPolicy::FavorA),
+            Ok(split(0, 999, 0))
+        );
+        assert_eq!(
+            split_interest(0, 1, 2, SplitPolicy::Retain),
+            Ok(split(0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_extremes_add_up() {
+        let result = split_interest(u64::MAX, u64::MAX, u64::MAX - 1, SplitPolicy::Retain).unwrap();
+        assert_eq!(
+            result.a as u128 + result.b as u128 + result.dust as u128,
+            u64::MAX as u128
+        );
+        assert!(result.dust <= 1);
+        assert_eq!(
+            split_interest(10, 0, 0, SplitPolicy::FavorA),
+            Err(FloatError::DivisionByZero)
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use solana_floats::error::FloatError;
+use solana_floats::evm_compat::*;
+use solana_floats::u256::U256;
+use std::cmp::Ordering;
+
+#[cfg(test)]
+mod evm_compat_tests {
+    use super::*;
+
+    const E: u128 = 2_718_281_828_459_045_235;
+
+    fn ud(raw: u128) -> Ud60x18 {
+        Ud60x18::from_raw(raw)
+    }
+
+    fn raw(value: Ud60x18) -> u128 {
+        value.0.try_to_u128().unwrap()
+    }
+
+    #[test]
+    fn test_ud60x18_mul_div_round_down() {
+        // 0.1 * 0.2 = 0.02 exactly in decimal fixed point
+        assert_eq!(
+            raw(ud(100_000_000_000_000_000)
+                .checked_mul(ud(200_000_000_000_000_000))
+                .unwrap()),
+            20_000_000_000_000_000
+        );
+        // 1 / 3 truncates
+        assert_eq!(
+            raw(Ud60x18::UNIT
+                .checked_div(ud(3_000_000_000_000_000_000))
+                .unwrap()),
+            333_333_333_333_333_333
+        );
+        // The product overflows 128 bits but the quotient does not
+        let big = ud(u128::MAX);
+        assert_eq!(big.checked_mul(Ud60x18::UNIT), Ok(big));
+        assert_eq!(
+            Ud60x18::UNIT.checked_div(ud(0)),
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(
+            Ud60x18(U256::MAX).checked_mul(ud(2_000_000_000_000_000_000)),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_ud60x18_exp_matches_prbmath() {
+        assert_eq!(
+            raw(ud(1_000_000_000_000_000_000).exp2().unwrap()),
+            2_000_000_000_000_000_000
+        );
+        assert_eq!(
+            raw(ud(500_000_000_000_000_000).exp2().unwrap()),
+            1_414_213_562_373_095_048
+        );
+        assert_eq!(raw(ud(0).exp().unwrap()), 1_000_000_000_000_000_000);
+        assert_eq!(
+            raw(ud(1_000_000_000_000_000_000).exp().unwrap()),
+            2_718_281_828_459_045_234
+        );
+        assert_eq!(
+            ud(133_084_258_667_509_499_441).exp(),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            ud(192_000_000_000_000_000_000).exp2(),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_ud60x18_ln_matches_prbmath() {
+        assert_eq!(raw(ud(1_000_000_000_000_000_000).ln().unwrap()), 0);
+        assert_eq!(
+            raw(ud(2_000_000_000_000_000_000).ln().unwrap()),
+            693_147_180_559_945_309
+        );
+        assert_eq!(raw(ud(E).ln().unwrap()), 999_999_999_999_999_990);
+        assert_eq!(
+            raw(ud(8_000_000_000_000_000_000).log2().unwrap()),
+            3_000_000_000_000_000_000
+        );
+        assert_eq!(
+            ud(999_999_999_999_999_999).ln(),
+            Err(FloatError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_fixed64x64_mul_div() {
+        let one = Fixed64x64::ONE;
+        let half = Fixed64x64(1 << 63);
+        let neg_three = Fixed64x64(-3 << 64);
+        assert_eq!(half.checked_mul(neg_three), Ok(Fixed64x64(-3 << 63)));
+        // Multiplication floors toward negative infinity
+        assert_eq!(Fixed64x64(-1).checked_mul(half), Ok(Fixed64x64(-1)));
+        assert_eq!(Fixed64x64(1).checked_mul(half), Ok(Fixed64x64(0)));
+        // Division truncates toward zero
+        assert_eq!(
+            Fixed64x64(-1).checked_div(Fixed64x64(2 << 64)),
+            Ok(Fixed64x64(0))
+        );
+        assert_eq!(
+            one.checked_div(neg_three),
+            Ok(Fixed64x64(-6_148_914_691_236_517_205))
+        );
+        assert_eq!(
+            one.checked_div(Fixed64x64(0)),
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(
+            Fixed64x64(i128::MAX).checked_mul(Fixed64x64(2 << 64)),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            Fixed64x64(i128::MIN).checked_mul(one),
+            Ok(Fixed64x64(i128::MIN))
+        );
+    }
+
+    #[test]
+    fn test_fixed64x64_logarithms() {
+        assert_eq!(Fixed64x64(8 << 64).log2(), Ok(Fixed64x64(3 << 64)));
+        assert_eq!(Fixed64x64(1 << 62).log2(), Ok(Fixed64x64(-2 << 64)));
+        assert_eq!(Fixed64x64::ONE.ln(), Ok(Fixed64x64(0)));
+        // ln(2) is the 128-bit constant truncated to 64 fractional bits
+        assert_eq!(Fixed64x64(2 << 64).ln(), Ok(Fixed64x64(0xB17217F7D1CF79AB)));
+        // ln(1/2) floors, landing one unit below -ln(2)
+        assert_eq!(
+            Fixed64x64(1 << 63).ln(),
+            Ok(Fixed64x64(-0xB17217F7D1CF79AC))
+        );
+        assert_eq!(Fixed64x64(0).ln(), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_results_track_f64() {
+        for x in [0.25_f64, 1.5, 10.0, 40.0] {
+            let fixed = Fixed64x64((x * 2f64.powi(64)) as i128);
+            let ln = fixed.ln().unwrap().0 as f64 / 2f64.powi(64);
+            assert!((ln - x.ln()).abs() < 1e-12);
+
+            let prb = ud((x * 1e18) as u128);
+            let exp = raw(prb.exp().unwrap()) as f64 / 1e18;
+            assert!((exp / x.exp() - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_reconcile_matching_balances() {
+        // 1.5 tokens: 6 decimals on Solana, 18 on the EVM side
+        let evm = U256::from(1_500_000_000_000_000_000u128);
+        let result = reconcile_amounts(1_500_000, evm, 6, 18, U256::ZERO).unwrap();
+        assert_eq!(result.decimals, 18);
+        assert_eq!(result.ordering, Ordering::Equal);
+        assert_eq!(result.residual, U256::ZERO);
+        assert_eq!(result.dust, U256::ZERO);
+        assert!(result.within_tolerance);
+    }
+
+    #[test]
+    fn test_reconcile_reports_unbridgeable_dust() {
+        // The EVM balance carries 123 wei that a 6-decimal mint cannot represent
+        let evm = U256::from(1_500_000_000_000_000_123u128);
+        let result = reconcile_amounts(1_500_000, evm, 6, 18, U256::from(1_000u64)).unwrap();
+        assert_eq!(result.ordering, Ordering::Less);
+        assert_eq!(result.residual, U256::from(123u64));
+        assert_eq!(result.dust, U256::from(123u64));
+        assert!(result.within_tolerance);
+
+        let strict = reconcile_amounts(1_500_000, evm, 6, 18, U256::from(100u64)).unwrap();
+        assert!(!strict.within_tolerance);
+    }
+
+    #[test]
+    fn test_reconcile_detects_missing_funds() {
+        // Solana has a whole unit more than the EVM side: residual beyond any dust
+        let evm = U256::from(1_000_000_000_000_000_000u128);
+        let result = reconcile_amounts(2_000_000, evm, 6, 18, U256::from(1_000u64)).unwrap();
+        assert_eq!(result.ordering, Ordering::Greater);
+        assert_eq!(result.residual, U256::from(1_000_000_000_000_000_000u128));
+        assert_eq!(result.dust, U256::ZERO);
+        assert!(!result.within_tolerance);
+    }
+
+    #[test]
+    fn test_reconcile_rejects_unscalable_decimals() {
+        assert_eq!(
+            reconcile_amounts(1, U256::ONE, 0, 78, U256::ZERO),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            reconcile_amounts(u64::MAX, U256::ONE, 0, 77, U256::ZERO),
+            Err(FloatError::Overflow)
+        );
+    }
+}
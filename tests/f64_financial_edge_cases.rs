@@ -1,5 +1,9 @@
+#![allow(clippy::excessive_precision, clippy::redundant_closure, clippy::useless_vec)]
+
 use solana_floats::double_ops::*;
+use solana_floats::error::FloatError;
 use solana_floats::float_ops::*;
+use solana_floats::quantize::RoundingMode;
 
 #[cfg(test)]
 mod f64_financial_edge_cases {
@@ -325,4 +329,26 @@ mod f64_financial_edge_cases {
             println!("  - The precision differences shown here are PREDICTABLE and IDENTICAL on all nodes");
         }
     }
+
+    #[test]
+    fn test_divide_doubles_with_rounding_against_the_user() {
+        // Constant product pool: the amount out is rounded down and the amount
+        // in rounded up, so every swap leaves k at least as large
+        let (x, y) = (1_000.0_f64, 3_000.0_f64);
+        let k = x * y;
+        let out_y = y - divide_doubles_with_rounding(k, x + 7.0, RoundingMode::Ceil).unwrap();
+        let nearest = divide_doubles(k, x + 7.0).unwrap();
+        assert!(divide_doubles_with_rounding(k, x + 7.0, RoundingMode::Floor).unwrap() <= nearest);
+        assert!(divide_doubles_with_rounding(k, x + 7.0, RoundingMode::Ceil).unwrap() >= nearest);
+        assert!((x + 7.0) * (y - out_y) >= k);
+
+        assert_eq!(
+            divide_doubles_with_rounding(1.0, 3.0, RoundingMode::HalfEven).unwrap(),
+            divide_doubles(1.0, 3.0).unwrap()
+        );
+        assert_eq!(
+            divide_doubles_with_rounding(1.0, 0.0, RoundingMode::Floor),
+            Err(FloatError::DivisionByZero)
+        );
+    }
 }
@@ -1,3 +1,5 @@
+#![allow(clippy::double_comparisons)]
+
 use solana_floats::double_ops::*;
 use solana_floats::float_ops::*;
 
@@ -0,0 +1,346 @@
+use solana_floats::feature_gate::*;
+use solana_floats::instruction::{Opcode, encode};
+use solana_loader_v3_interface::get_program_data_address;
+use solana_program::program_error::ProgramError;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use solana_sdk_ids::bpf_loader_upgradeable;
+
+mod common;
+
+#[cfg(test)]
+mod feature_gate_tests {
+    use super::*;
+
+    fn add_instruction(program_id: Pubkey, gate: Pubkey, a: f32, b: f32) -> Instruction {
+        let mut payload = a.to_le_bytes().to_vec();
+        payload.extend_from_slice(&b.to_le_bytes());
+        Instruction::new_with_bytes(
+            program_id,
+            &encode(Opcode::Add, &payload),
+            vec![AccountMeta::new_readonly(gate, false)],
+        )
+    }
+
+    fn set_features_instruction(
+        program_id: Pubkey,
+        admin: Pubkey,
+        f32_enabled: bool,
+    ) -> Instruction {
+        let (gate, _) = FeatureGate::find_address(&program_id);
+        Instruction::new_with_bytes(
+            program_id,
+            &encode(Opcode::SetFeatures, &[f32_enabled as u8]),
+            vec![
+                AccountMeta::new(gate, false),
+                AccountMeta::new(admin, true),
+                AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                AccountMeta::new_readonly(get_program_data_address(&program_id), false),
+            ],
+        )
+    }
+
+    // `ProgramData` header of an upgradeable program, with no program bytes
+    fn program_data(upgrade_authority: Option<Pubkey>) -> Account {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        match upgrade_authority {
+            Some(authority) => {
+                data.push(1);
+                data.extend_from_slice(authority.as_ref());
+            }
+            None => data.extend_from_slice(&[0; 33]),
+        }
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            ..Account::default()
+        }
+    }
+
+    // The program deployed with `program_data` at its `ProgramData` address,
+    // or with no account there when `None`
+    fn program_test(program_id: Pubkey, program_data: Option<Account>) -> ProgramTest {
+        let mut program_test = common::program_test(program_id);
+        if let Some(account) = program_data {
+            program_test.add_account(get_program_data_address(&program_id), account);
+        }
+        program_test
+    }
+
+    // The program upgradeable by `admin`, which is funded to pay fees and rent
+    fn program_test_with_admin(program_id: Pubkey, admin: &Keypair) -> ProgramTest {
+        let mut program_test = program_test(program_id, Some(program_data(Some(admin.pubkey()))));
+        program_test.add_account(
+            admin.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                owner: solana_system_interface::program::id(),
+                ..Account::default()
+            },
+        );
+        program_test
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let gate = FeatureGate {
+            admin: Pubkey::new_unique(),
+            f32_enabled: false,
+        };
+        let mut data = [0u8; FeatureGate::LEN];
+        gate.pack(&mut data).unwrap();
+        assert_eq!(FeatureGate::unpack(&data), Ok(gate));
+    }
+
+    #[test]
+    fn test_unpack_rejects_corrupt_data() {
+        let mut data = [0u8; FeatureGate::LEN];
+        data[32] = 2;
+        assert_eq!(
+            FeatureGate::unpack(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            FeatureGate::unpack(&data[..FeatureGate::LEN - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_f32_requires_canonical_gate_account() {
+        let program_id = Pubkey::new_unique();
+        let admin = Keypair::new();
+        let (banks_client, _, recent_blockhash) =
+            program_test_with_admin(program_id, &admin).start().await;
+        let (gate, _) = FeatureGate::find_address(&program_id);
+
+        let legacy = |a: f32, b: f32| {
+            let mut payload = a.to_le_bytes().to_vec();
+            payload.extend_from_slice(&b.to_le_bytes());
+            Instruction::new_with_bytes(program_id, &encode(Opcode::Add, &payload), vec![])
+        };
+        let lookalike = add_instruction(program_id, Pubkey::new_unique(), 1.0, 2.0);
+        let steps = [
+            // No gate account at all
+            (
+                legacy(1.0, 2.0),
+                Err(InstructionError::NotEnoughAccountKeys),
+            ),
+            (lookalike, Err(InstructionError::InvalidSeeds)),
+            (
+                set_features_instruction(program_id, admin.pubkey(), false),
+                Ok(()),
+            ),
+            (
+                add_instruction(program_id, gate, 3.0, 4.0),
+                Err(InstructionError::InvalidInstructionData),
+            ),
+            // Omitting the gate account does not get around a disabled gate
+            (
+                legacy(5.0, 6.0),
+                Err(InstructionError::NotEnoughAccountKeys),
+            ),
+        ];
+        for (instruction, expected) in steps {
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&admin.pubkey()),
+                &[&admin],
+                recent_blockhash,
+            );
+            let result = banks_client
+                .process_transaction(transaction)
+                .await
+                .map_err(|err| err.unwrap());
+            assert_eq!(
+                result,
+                expected.map_err(|err| TransactionError::InstructionError(0, err))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_admin_can_disable_and_reenable_f32() {
+        let program_id = Pubkey::new_unique();
+        let upgrade_authority = Keypair::new();
+        let (banks_client, _, recent_blockhash) =
+            program_test_with_admin(program_id, &upgrade_authority)
+                .start()
+                .await;
+        let (gate, _) = FeatureGate::find_address(&program_id);
+        let admin = upgrade_authority.pubkey();
+
+        let steps = [
+            // Uninitialized gate: f32 enabled by default
+            (add_instruction(program_id, gate, 1.0, 2.0), true),
+            (set_features_instruction(program_id, admin, false), true),
+            (add_instruction(program_id, gate, 3.0, 4.0), false),
+            (set_features_instruction(program_id, admin, true), true),
+            (add_instruction(program_id, gate, 5.0, 6.0), true),
+        ];
+        for (instruction, should_succeed) in steps {
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&admin),
+                &[&upgrade_authority],
+                recent_blockhash,
+            );
+            let result = banks_client.process_transaction(transaction).await;
+            assert_eq!(result.is_ok(), should_succeed);
+        }
+
+        let account = banks_client.get_account(gate).await.unwrap().unwrap();
+        assert_eq!(account.owner, program_id);
+        assert_eq!(
+            FeatureGate::unpack(&account.data),
+            Ok(FeatureGate {
+                admin,
+                f32_enabled: true,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_upgrade_authority_can_create_gate() {
+        let upgrade_authority = Pubkey::new_unique();
+        let mut not_loader_owned = program_data(Some(upgrade_authority));
+        not_loader_owned.owner = Pubkey::new_unique();
+        let cases = [
+            // The first caller is not the admin unless it is the upgrade authority
+            (
+                Some(program_data(Some(upgrade_authority))),
+                InstructionError::IncorrectAuthority,
+            ),
+            // Immutable program: nobody can create the gate
+            (
+                Some(program_data(None)),
+                InstructionError::IncorrectAuthority,
+            ),
+            (Some(not_loader_owned), InstructionError::IncorrectProgramId),
+            // No `ProgramData` account at the canonical address
+            (None, InstructionError::IncorrectProgramId),
+        ];
+        for (program_data, expected) in cases {
+            let program_id = Pubkey::new_unique();
+            let (banks_client, payer, recent_blockhash) =
+                program_test(program_id, program_data).start().await;
+            let transaction = Transaction::new_signed_with_payer(
+                &[set_features_instruction(program_id, payer.pubkey(), false)],
+                Some(&payer.pubkey()),
+                &[&payer],
+                recent_blockhash,
+            );
+            let err = banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap();
+            assert_eq!(err, TransactionError::InstructionError(0, expected));
+        }
+
+        // A `ProgramData` account other than the program's own
+        let program_id = Pubkey::new_unique();
+        let other_program = Pubkey::new_unique();
+        let mut program_test = program_test(program_id, None);
+        program_test.add_account(get_program_data_address(&other_program), program_data(None));
+        let (banks_client, payer, recent_blockhash) = program_test.start().await;
+        let mut instruction = set_features_instruction(program_id, payer.pubkey(), false);
+        instruction.accounts[3].pubkey = get_program_data_address(&other_program);
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        let err = banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(
+            err,
+            TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gate_into_prefunded_address() {
+        // Lamports sent to the gate address before it exists must not block
+        // its creation
+        let program_id = Pubkey::new_unique();
+        let admin = Keypair::new();
+        let (gate, _) = FeatureGate::find_address(&program_id);
+        let mut program_test = program_test_with_admin(program_id, &admin);
+        program_test.add_account(
+            gate,
+            Account {
+                lamports: 1_000,
+                owner: solana_system_interface::program::id(),
+                ..Account::default()
+            },
+        );
+        let (banks_client, _, recent_blockhash) = program_test.start().await;
+        let transaction = Transaction::new_signed_with_payer(
+            &[set_features_instruction(program_id, admin.pubkey(), false)],
+            Some(&admin.pubkey()),
+            &[&admin],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        let account = banks_client.get_account(gate).await.unwrap().unwrap();
+        assert_eq!(account.owner, program_id);
+        assert_eq!(
+            FeatureGate::unpack(&account.data),
+            Ok(FeatureGate {
+                admin: admin.pubkey(),
+                f32_enabled: false,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_admin_can_change_features() {
+        let program_id = Pubkey::new_unique();
+        let admin = Keypair::new();
+        let (banks_client, payer, recent_blockhash) =
+            program_test_with_admin(program_id, &admin).start().await;
+
+        let transaction = Transaction::new_signed_with_payer(
+            &[set_features_instruction(program_id, admin.pubkey(), true)],
+            Some(&admin.pubkey()),
+            &[&admin],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        let intruder = Keypair::new();
+        let transaction = Transaction::new_signed_with_payer(
+            &[set_features_instruction(
+                program_id,
+                intruder.pubkey(),
+                false,
+            )],
+            Some(&payer.pubkey()),
+            &[&payer, &intruder],
+            recent_blockhash,
+        );
+        let err = banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(
+            err,
+            TransactionError::InstructionError(0, InstructionError::IncorrectAuthority)
+        );
+    }
+}
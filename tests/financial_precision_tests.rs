@@ -1,8 +1,11 @@
+#![allow(clippy::double_comparisons, clippy::excessive_precision, unused_mut)]
+
+use solana_floats::feature_gate::FeatureGate;
 use solana_floats::float_ops::*;
 use solana_program::msg;
 use solana_program_test::*;
 use solana_sdk::{
-    instruction::{Instruction},
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     signature::Signer,
     transaction::Transaction,
@@ -172,7 +175,10 @@ mod financial_precision_tests {
         let instruction1 = Instruction::new_with_bytes(
             program_id,
             &instruction_data1,
-            vec![],
+            vec![AccountMeta::new_readonly(
+                FeatureGate::find_address(&program_id).0,
+                false,
+            )],
         );
 
         // Second instruction: divide result by 2.0 (conceptually)
@@ -183,7 +189,10 @@ mod financial_precision_tests {
         let instruction2 = Instruction::new_with_bytes(
             program_id,
             &instruction_data2,
-            vec![],
+            vec![AccountMeta::new_readonly(
+                FeatureGate::find_address(&program_id).0,
+                false,
+            )],
         );
 
         let transaction = Transaction::new_signed_with_payer(
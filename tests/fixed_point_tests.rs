@@ -0,0 +1,322 @@
+use solana_floats::error::FloatError;
+use solana_floats::fixed_point::{I80F48, Q64x64};
+use solana_floats::quantize::RoundingMode;
+
+#[cfg(test)]
+mod fixed_point_tests {
+    use super::*;
+
+    fn q(value: f64) -> Q64x64 {
+        Q64x64::from_f64(value).unwrap()
+    }
+
+    fn i(value: f64) -> I80F48 {
+        I80F48::from_f64(value).unwrap()
+    }
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(Q64x64::ONE.to_bits(), 1 << 64);
+        assert_eq!(Q64x64::from_int(3), q(3.0));
+        let value = q(5.75);
+        assert_eq!((value.int_part(), value.frac_bits()), (5, 3 << 62));
+        assert_eq!(Q64x64::EPSILON.to_f64(), 2f64.powi(-64));
+    }
+
+    #[test]
+    fn test_add_sub() {
+        // 0.1 and 0.2 are exact here (their f64 values), so the sum is too
+        let sum = q(0.1).checked_add(q(0.2)).unwrap();
+        assert_eq!(sum.to_bits(), q(0.1).to_bits() + q(0.2).to_bits());
+        assert_eq!(sum.checked_sub(q(0.2)), Ok(q(0.1)));
+        assert_eq!(q(1.0).checked_sub(q(2.0)), Err(FloatError::OutOfRange));
+        assert_eq!(
+            Q64x64::MAX.checked_add(Q64x64::EPSILON),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_mul_rounding() {
+        assert_eq!(q(1.5).checked_mul(q(2.5), RoundingMode::Floor), Ok(q(3.75)));
+        // 2^-64 * 0.5 lies halfway between 0 and 2^-64
+        let half = q(0.5);
+        let cases = [
+            (RoundingMode::Floor, 0),
+            (RoundingMode::TowardZero, 0),
+            (RoundingMode::Ceil, 1),
+            (RoundingMode::HalfUp, 1),
+            (RoundingMode::HalfEven, 0),
+        ];
+        for (mode, bits) in cases {
+            assert_eq!(
+                Q64x64::EPSILON.checked_mul(half, mode),
+                Ok(Q64x64::from_bits(bits)),
+                "{:?}",
+                mode
+            );
+        }
+        assert_eq!(
+            Q64x64::from_int(1 << 32).checked_mul(Q64x64::from_int(1 << 32), RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_div_rounding() {
+        let third_down = Q64x64::ONE
+            .checked_div(q(3.0), RoundingMode::Floor)
+            .unwrap();
+        let third_up = Q64x64::ONE.checked_div(q(3.0), RoundingMode::Ceil).unwrap();
+        assert_eq!(third_up.to_bits() - third_down.to_bits(), 1);
+        assert_eq!(third_down.to_bits(), u64::MAX as u128 / 3);
+        assert_eq!(
+            Q64x64::ONE.checked_div(q(3.0), RoundingMode::HalfEven),
+            Ok(third_down)
+        );
+        // Rounding up twice never undercounts the original
+        let back = third_up.checked_mul(q(3.0), RoundingMode::Ceil).unwrap();
+        assert!(back >= Q64x64::ONE);
+        assert_eq!(
+            Q64x64::ONE.checked_div(Q64x64::ZERO, RoundingMode::Floor),
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(
+            Q64x64::MAX.checked_div(q(0.5), RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_sqrt() {
+        assert_eq!(q(6.25).sqrt(RoundingMode::Floor), q(2.5));
+        assert_eq!(q(6.25).sqrt(RoundingMode::Ceil), q(2.5));
+        assert_eq!(Q64x64::ZERO.sqrt(RoundingMode::Ceil), Q64x64::ZERO);
+        let down = q(2.0).sqrt(RoundingMode::Floor);
+        let up = q(2.0).sqrt(RoundingMode::Ceil);
+        assert_eq!(up.to_bits() - down.to_bits(), 1);
+        // sqrt(2) * 2^64 = 26087635650665564424.699..., so nearest rounds up
+        assert_eq!(down.to_bits(), 26_087_635_650_665_564_424);
+        assert_eq!(q(2.0).sqrt(RoundingMode::HalfEven), up);
+        assert_eq!(down.to_f64(), 2f64.sqrt());
+        // Largest input: the root is just below 2^32
+        let root = Q64x64::MAX.sqrt(RoundingMode::Floor);
+        assert_eq!(root.int_part(), u32::MAX as u64);
+    }
+
+    #[test]
+    fn test_f64_conversion_reports_precision_loss() {
+        assert_eq!(Q64x64::from_f64(-0.0), Ok(Q64x64::ZERO));
+        assert_eq!(Q64x64::from_f64(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(Q64x64::from_f64(-1e-300), Err(FloatError::OutOfRange));
+        assert_eq!(Q64x64::from_f64(f64::INFINITY), Err(FloatError::OutOfRange));
+        assert_eq!(
+            Q64x64::from_f64(18_446_744_073_709_551_616.0),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(Q64x64::from_f64(1e-30), Err(FloatError::PrecisionLoss));
+        assert_eq!(
+            Q64x64::from_f64_rounded(1e-30, RoundingMode::Ceil),
+            Ok(Q64x64::EPSILON)
+        );
+        assert_eq!(
+            Q64x64::from_f64_rounded(1e-30, RoundingMode::HalfEven),
+            Ok(Q64x64::ZERO)
+        );
+        // Every f64 in range with no bits below 2^-64 round-trips
+        for value in [0.1, 1.0 / 3.0, 12_345.678, 2f64.powi(-64), 1.8e19] {
+            assert_eq!(q(value).to_f64_exact(), Ok(value));
+        }
+        // The third has 64 significant bits, which f64 cannot hold
+        let third = Q64x64::ONE
+            .checked_div(q(3.0), RoundingMode::Floor)
+            .unwrap();
+        assert_eq!(third.to_f64_exact(), Err(FloatError::PrecisionLoss));
+        assert_eq!(third.to_f64(), 1.0 / 3.0);
+        assert_eq!(Q64x64::MAX.to_f64_exact(), Err(FloatError::PrecisionLoss));
+        assert_eq!(Q64x64::MAX.to_f64(), 18_446_744_073_709_551_616.0);
+    }
+
+    #[test]
+    fn test_i80f48_layout() {
+        assert_eq!(I80F48::ONE.to_bits(), 1 << 48);
+        assert_eq!(I80F48::from_int(-3), i(-3.0));
+        assert_eq!(i(-2.5).int_floor(), -3);
+        assert_eq!(I80F48::MIN.to_f64(), -(2f64.powi(79)));
+        assert_eq!(I80F48::EPSILON.to_f64(), 2f64.powi(-48));
+    }
+
+    #[test]
+    fn test_i80f48_add_sub_neg() {
+        assert_eq!(i(1.5).checked_add(i(-2.75)), Ok(i(-1.25)));
+        assert_eq!(i(1.5).checked_sub(i(2.75)), Ok(i(-1.25)));
+        assert_eq!(i(-1.25).checked_neg(), Ok(i(1.25)));
+        assert_eq!(I80F48::MIN.checked_neg(), Err(FloatError::Overflow));
+        assert_eq!(
+            I80F48::MAX.checked_add(I80F48::EPSILON),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            I80F48::MIN.checked_sub(I80F48::EPSILON),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_i80f48_signed_rounding() {
+        // ±2^-48 * 0.5 lies halfway between 0 and ±2^-48
+        let half = i(0.5);
+        let tiny = I80F48::EPSILON;
+        let negative = tiny.checked_neg().unwrap();
+        let cases = [
+            (RoundingMode::Floor, 0, -1),
+            (RoundingMode::Ceil, 1, 0),
+            (RoundingMode::TowardZero, 0, 0),
+            (RoundingMode::HalfUp, 1, -1),
+            (RoundingMode::HalfEven, 0, 0),
+        ];
+        for (mode, positive_bits, negative_bits) in cases {
+            assert_eq!(
+                tiny.checked_mul(half, mode).map(I80F48::to_bits),
+                Ok(positive_bits),
+                "{:?}",
+                mode
+            );
+            assert_eq!(
+                negative.checked_mul(half, mode).map(I80F48::to_bits),
+                Ok(negative_bits),
+                "{:?}",
+                mode
+            );
+        }
+        assert_eq!(
+            i(-1.5).checked_mul(i(2.5), RoundingMode::Floor),
+            Ok(i(-3.75))
+        );
+        assert_eq!(
+            I80F48::from_int(1 << 40).checked_mul(I80F48::from_int(1 << 40), RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+        // -2^79 is representable, +2^79 is not
+        let min = I80F48::from_int(-(1 << 40))
+            .checked_mul(I80F48::from_int(1 << 39), RoundingMode::Floor);
+        assert_eq!(min, Ok(I80F48::MIN));
+        assert_eq!(
+            min.unwrap().checked_div(i(-1.0), RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_i80f48_div() {
+        let down = i(-1.0).checked_div(i(3.0), RoundingMode::Floor).unwrap();
+        let up = i(-1.0).checked_div(i(3.0), RoundingMode::Ceil).unwrap();
+        assert_eq!(up.to_bits() - down.to_bits(), 1);
+        assert_eq!(
+            i(-1.0).checked_div(i(3.0), RoundingMode::TowardZero),
+            Ok(up)
+        );
+        assert!((down.to_f64() + 1.0 / 3.0).abs() < I80F48::EPSILON.to_f64());
+        assert_eq!(
+            i(1.0).checked_div(I80F48::ZERO, RoundingMode::Floor),
+            Err(FloatError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_i80f48_f64_conversion() {
+        assert_eq!(I80F48::from_f64(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(I80F48::from_f64(2f64.powi(79)), Err(FloatError::OutOfRange));
+        assert_eq!(I80F48::from_f64(-(2f64.powi(79))), Ok(I80F48::MIN));
+        assert_eq!(I80F48::from_f64(0.1), Err(FloatError::PrecisionLoss));
+        let tenth = I80F48::from_f64_rounded(0.1, RoundingMode::HalfEven).unwrap();
+        assert_eq!(tenth.to_bits(), 28_147_497_671_066);
+        assert_eq!(
+            I80F48::from_f64_rounded(-0.1, RoundingMode::Floor).map(I80F48::to_bits),
+            Ok(-28_147_497_671_066)
+        );
+        assert_eq!(
+            I80F48::from_f64_rounded(-0.1, RoundingMode::Ceil).map(I80F48::to_bits),
+            Ok(-28_147_497_671_065)
+        );
+        assert_eq!(tenth.to_f64_exact(), Ok(tenth.to_f64()));
+        for value in [-12_345.678_9, 2f64.powi(-48), -1e20] {
+            let rounded = I80F48::from_f64_rounded(value, RoundingMode::HalfEven).unwrap();
+            assert_eq!(I80F48::from_f64(rounded.to_f64()), Ok(rounded));
+        }
+        assert_eq!(I80F48::MAX.to_f64_exact(), Err(FloatError::PrecisionLoss));
+    }
+
+    #[test]
+    fn test_i80f48_q64x64_conversion() {
+        assert_eq!(i(2.5).to_q64x64(), Ok(q(2.5)));
+        assert_eq!(i(-2.5).to_q64x64(), Err(FloatError::OutOfRange));
+        assert_eq!(
+            I80F48::from_int(1 << 62)
+                .checked_mul(i(4.0), RoundingMode::Floor)
+                .unwrap()
+                .to_q64x64(),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(I80F48::from_q64x64_exact(q(2.5)), Ok(i(2.5)));
+        assert_eq!(
+            I80F48::from_q64x64_exact(Q64x64::EPSILON),
+            Err(FloatError::PrecisionLoss)
+        );
+        // 2^-64 is 2^-16 of the smallest I80F48 step: nearest rounds to zero
+        assert_eq!(
+            I80F48::from_q64x64(Q64x64::EPSILON, RoundingMode::Ceil),
+            I80F48::EPSILON
+        );
+        assert_eq!(
+            I80F48::from_q64x64(Q64x64::EPSILON, RoundingMode::HalfUp),
+            I80F48::ZERO
+        );
+        let max = I80F48::from_q64x64(Q64x64::MAX, RoundingMode::Ceil);
+        assert_eq!(max.to_q64x64(), Err(FloatError::OutOfRange));
+        assert_eq!(
+            max,
+            I80F48::from_int(1 << 62)
+                .checked_mul(i(4.0), RoundingMode::Floor)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_q64x64_round_half_even_to_scale() {
+        assert_eq!(q(2.5).round_half_even_to_scale(0), Ok(q(2.0)));
+        assert_eq!(q(3.5).round_half_even_to_scale(0), Ok(q(4.0)));
+        // 0.125 ties to 0.12, which lands on the nearest multiple of 2^-64
+        let twelve_cents = Q64x64::from_int(12)
+            .checked_div(Q64x64::from_int(100), RoundingMode::HalfEven)
+            .unwrap();
+        assert_eq!(q(0.125).round_half_even_to_scale(2), Ok(twelve_cents));
+        assert_eq!(twelve_cents.round_half_even_to_scale(2), Ok(twelve_cents));
+        assert_eq!(
+            Q64x64::MAX.round_half_even_to_scale(0),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            Q64x64::ONE.round_half_even_to_scale(20),
+            Err(FloatError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_i80f48_round_half_even_to_scale_is_symmetric() {
+        let i = |value: f64| I80F48::from_f64_rounded(value, RoundingMode::HalfEven).unwrap();
+        assert_eq!(i(2.5).round_half_even_to_scale(0), Ok(i(2.0)));
+        assert_eq!(i(-2.5).round_half_even_to_scale(0), Ok(i(-2.0)));
+        assert_eq!(i(-3.5).round_half_even_to_scale(0), Ok(i(-4.0)));
+        for value in [0.125, 1.0 / 3.0, 12_345.678_9] {
+            let up = i(value).round_half_even_to_scale(2).unwrap();
+            let down = i(-value).round_half_even_to_scale(2).unwrap();
+            assert_eq!(down, up.checked_neg().unwrap());
+        }
+        assert_eq!(I80F48::MIN.round_half_even_to_scale(0), Ok(I80F48::MIN));
+        assert_eq!(
+            I80F48::MAX.round_half_even_to_scale(0),
+            Err(FloatError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use solana_floats::compare::Tolerance;
+use solana_floats::error::FloatError;
+use solana_floats::float_ext::SolanaFloatExt;
+
+#[cfg(test)]
+mod float_ext_tests {
+    use super::*;
+
+    #[test]
+    fn test_quantize_rounds_half_to_even() {
+        assert_eq!(0.125_f64.quantize(2), Ok(0.12));
+        assert_eq!(0.375_f64.quantize(2), Ok(0.38));
+        assert_eq!(2.5_f32.quantize(0), Ok(2.0));
+        assert_eq!(1.23456_f32.quantize(3), Ok(1.235));
+        assert_eq!(f64::NAN.quantize(2), Err(FloatError::NaNOperand));
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let tolerance = Tolerance::builder().abs(1e-12).build().unwrap();
+        assert!((0.1 + 0.2_f64).approx_eq(0.3, tolerance));
+        assert!(!1.0_f64.approx_eq(1.1, tolerance));
+        assert!(!f64::NAN.approx_eq(f64::NAN, tolerance));
+        let loose = Tolerance::builder().rel_bps(1).build().unwrap();
+        assert!((0.1_f32 + 0.2).approx_eq(0.3, loose));
+    }
+
+    #[test]
+    fn test_is_dangerous() {
+        for value in [
+            f64::NAN,
+            f64::INFINITY,
+            f64::MIN_POSITIVE / 2.0,
+            1e16,
+            -1e300,
+        ] {
+            assert!(value.is_dangerous(), "{}", value);
+        }
+        for value in [
+            0.0,
+            -0.0,
+            0.1,
+            1e15,
+            -9_007_199_254_740_992.0,
+            f64::MIN_POSITIVE,
+        ] {
+            assert!(!value.is_dangerous(), "{}", value);
+        }
+        assert!(16_777_218.0_f32.is_dangerous());
+        assert!(!16_777_216.0_f32.is_dangerous());
+        assert!(f32::NEG_INFINITY.is_dangerous());
+        assert!((f32::MIN_POSITIVE / 2.0).is_dangerous());
+    }
+
+    #[test]
+    fn test_to_scaled() {
+        assert_eq!(1.5_f64.to_scaled(9), Ok(1_500_000_000));
+        // 0.1 + 0.2 = 0.30000000000000004 still maps to 30 cents
+        assert_eq!((0.1 + 0.2_f64).to_scaled(2), Ok(30));
+        assert_eq!(0.005_f64.to_scaled(2), Ok(0));
+        // 0.015_f32 is 0.0149999996..., below the tie
+        assert_eq!(0.015_f32.to_scaled(2), Ok(1));
+        assert_eq!((-1.0_f64).to_scaled(2), Err(FloatError::OutOfRange));
+        // Negative values that round to zero are fine
+        assert_eq!((-0.001_f64).to_scaled(2), Ok(0));
+        assert_eq!(f64::NAN.to_scaled(2), Err(FloatError::NaNOperand));
+        assert_eq!(1e19_f64.to_scaled(1), Err(FloatError::Overflow));
+        assert_eq!(1.0_f64.to_scaled(20), Err(FloatError::OutOfRange));
+    }
+}
@@ -1,11 +1,15 @@
+#![allow(clippy::approx_constant, unused_imports, unused_mut)]
+
+use solana_floats::error::FloatError;
+use solana_floats::feature_gate::FeatureGate;
 use solana_floats::float_ops::*;
 use solana_program_test::*;
 use solana_sdk::{
     account::Account,
-    instruction::{AccountMeta, Instruction},
+    instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
     signature::Signer,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 #[cfg(test)]
@@ -34,7 +38,7 @@ mod tests {
     fn test_float_division_by_zero() {
         let result = divide_floats(10.0, 0.0);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Division by zero");
+        assert_eq!(result.unwrap_err(), FloatError::DivisionByZero);
     }
 
     #[test]
@@ -75,7 +79,10 @@ mod tests {
         let instruction = Instruction::new_with_bytes(
             program_id,
             &instruction_data,
-            vec![],
+            vec![AccountMeta::new_readonly(
+                FeatureGate::find_address(&program_id).0,
+                false,
+            )],
         );
 
         let transaction = Transaction::new_signed_with_payer(
@@ -107,7 +114,10 @@ mod tests {
         let instruction = Instruction::new_with_bytes(
             program_id,
             &instruction_data,
-            vec![],
+            vec![AccountMeta::new_readonly(
+                FeatureGate::find_address(&program_id).0,
+                false,
+            )],
         );
 
         let transaction = Transaction::new_signed_with_payer(
@@ -139,7 +149,10 @@ mod tests {
         let instruction = Instruction::new_with_bytes(
             program_id,
             &instruction_data,
-            vec![],
+            vec![AccountMeta::new_readonly(
+                FeatureGate::find_address(&program_id).0,
+                false,
+            )],
         );
 
         let transaction = Transaction::new_signed_with_payer(
@@ -171,7 +184,10 @@ mod tests {
         let instruction = Instruction::new_with_bytes(
             program_id,
             &instruction_data,
-            vec![],
+            vec![AccountMeta::new_readonly(
+                FeatureGate::find_address(&program_id).0,
+                false,
+            )],
         );
 
         let transaction = Transaction::new_signed_with_payer(
@@ -182,6 +198,12 @@ mod tests {
         );
 
         let result = banks_client.process_transaction(transaction).await;
-        assert!(result.is_err());
+        assert_eq!(
+            result.map_err(|err| err.unwrap()),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(FloatError::DivisionByZero.code())
+            ))
+        );
     }
 }
\ No newline at end of file
@@ -0,0 +1,106 @@
+use solana_floats::error::FloatError;
+use solana_floats::format::*;
+use solana_floats::instruction::{Opcode, encode};
+use solana_floats::quantize::RoundingMode;
+use solana_program_test::*;
+
+mod common;
+
+#[cfg(test)]
+mod format_tests {
+    use super::*;
+    use common::process_with_metadata;
+
+    async fn format_on_chain(value: f64, decimals: u8, mode: u8) -> Option<String> {
+        let mut payload = vec![decimals, mode];
+        payload.extend_from_slice(&value.to_bits().to_le_bytes());
+        let result = process_with_metadata(&encode(Opcode::Format, &payload), vec![]).await;
+        result.result.ok()?;
+        let return_data = result.metadata?.return_data?;
+        Some(String::from_utf8(return_data.data).unwrap())
+    }
+
+    #[test]
+    fn test_format_pads_fraction() {
+        assert_eq!(
+            format_fixed(1.5, 2, RoundingMode::HalfEven).unwrap(),
+            "1.50"
+        );
+        assert_eq!(
+            format_fixed(0.05, 4, RoundingMode::HalfEven).unwrap(),
+            "0.0500"
+        );
+        assert_eq!(format_fixed(42.0, 0, RoundingMode::HalfEven).unwrap(), "42");
+        assert_eq!(
+            format_fixed(-3.25, 2, RoundingMode::HalfEven).unwrap(),
+            "-3.25"
+        );
+    }
+
+    #[test]
+    fn test_format_rounding_modes() {
+        assert_eq!(format_fixed(2.5, 0, RoundingMode::HalfEven).unwrap(), "2");
+        assert_eq!(format_fixed(2.5, 0, RoundingMode::HalfUp).unwrap(), "3");
+        assert_eq!(format_fixed(-1.01, 1, RoundingMode::Floor).unwrap(), "-1.1");
+        assert_eq!(
+            format_fixed(-1.01, 1, RoundingMode::TowardZero).unwrap(),
+            "-1.0"
+        );
+        assert_eq!(format_fixed(1.01, 1, RoundingMode::Ceil).unwrap(), "1.1");
+    }
+
+    #[test]
+    fn test_format_hides_representation_error() {
+        // 0.1 + 0.2 is 0.30000000000000004 but renders cleanly at receipt precision
+        assert_eq!(
+            format_fixed(0.1 + 0.2, 6, RoundingMode::HalfEven).unwrap(),
+            "0.300000"
+        );
+    }
+
+    #[test]
+    fn test_format_negative_zero_has_no_sign() {
+        assert_eq!(
+            format_fixed(-0.0, 2, RoundingMode::HalfEven).unwrap(),
+            "0.00"
+        );
+        assert_eq!(
+            format_fixed(-0.001, 2, RoundingMode::HalfEven).unwrap(),
+            "0.00"
+        );
+    }
+
+    #[test]
+    fn test_format_rejects_unrepresentable() {
+        assert_eq!(
+            format_fixed(f64::NAN, 2, RoundingMode::HalfEven),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            format_fixed(f64::INFINITY, 2, RoundingMode::HalfEven),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            format_fixed(1e18, 2, RoundingMode::HalfEven),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            format_fixed(1.0, 20, RoundingMode::HalfEven),
+            Err(FloatError::OutOfRange)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_format_instruction_returns_ascii() {
+        assert_eq!(
+            format_on_chain(1234.5678, 2, 4).await.as_deref(),
+            Some("1234.57")
+        );
+        assert_eq!(format_on_chain(-0.1, 3, 4).await.as_deref(), Some("-0.100"));
+    }
+
+    #[tokio::test]
+    async fn test_format_instruction_rejects_nan() {
+        assert_eq!(format_on_chain(f64::NAN, 2, 4).await, None);
+    }
+}
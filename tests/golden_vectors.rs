@@ -0,0 +1,130 @@
+//! Golden bit patterns for the correctly rounded operations.
+//!
+//! The same tests run natively and on wasm32 so browser front-ends can rely on
+//! precomputing exactly what the program computes on-chain:
+//!
+//! ```text
+//! cargo test --target wasm32-unknown-unknown --features no-entrypoint --test golden_vectors
+//! ```
+//!
+//! (with `wasm-bindgen-test-runner` configured as the wasm32 runner). NaN results
+//! are deliberately absent: their sign and payload bits are not portable.
+
+use solana_floats::array_ops::{dot_compensated, sum_doubles, sum_doubles_compensated};
+use solana_floats::double_ops::BinaryOp;
+use solana_floats::float_ops::*;
+use solana_floats::quantize::{RoundingMode, quantize};
+use solana_floats::verify::recompute;
+
+// (op, a bits, b bits, result bits)
+const F64_VECTORS: [(u8, u64, u64, u64); 7] = [
+    (
+        0,
+        0x3fb999999999999a,
+        0x3fc999999999999a,
+        0x3fd3333333333334,
+    ), // 0.1 + 0.2
+    (
+        0,
+        0x4341c37937e08000,
+        0x3ff0000000000000,
+        0x4341c37937e08000,
+    ), // 1e16 + 1
+    (
+        1,
+        0x3ff199999999999a,
+        0x3ff199999999999a,
+        0x3ff35c28f5c28f5d,
+    ), // 1.1 * 1.1
+    (
+        1,
+        0x01a56e1fc2f8f359,
+        0x3ddb7cdfd9d7bdbb,
+        0x000012688b70e62b,
+    ), // subnormal product
+    (
+        2,
+        0x3ff0000000000000,
+        0x4008000000000000,
+        0x3fd5555555555555,
+    ), // 1 / 3
+    (
+        2,
+        0x4036000000000000,
+        0x401c000000000000,
+        0x4009249249249249,
+    ), // 22 / 7
+    (
+        2,
+        0x8000000000000000,
+        0x4014000000000000,
+        0x8000000000000000,
+    ), // -0 / 5
+];
+
+// (a bits, b bits, a + b, a * b, a / b)
+const F32_VECTORS: [(u32, u32, u32, u32, u32); 3] = [
+    (0x3dcccccd, 0x3e4ccccd, 0x3e99999a, 0x3ca3d70b, 0x3f000000), // 0.1, 0.2
+    (0x4048f5c3, 0x40370a3d, 0x40c00000, 0x410fafb8, 0x3f8c880f), // 3.14, 2.86
+    (0x3f800000, 0x40400000, 0x40800000, 0x40400000, 0x3eaaaaab), // 1, 3
+];
+
+// (x bits, sqrt(x) bits)
+const SQRT_VECTORS: [(u64, u64); 3] = [
+    (0x4000000000000000, 0x3ff6a09e667f3bcd), // 2
+    (0x000012688b70e62b, 0x1fc1297872d9cbae), // subnormal
+    (0x3fe0000000000000, 0x3fe6a09e667f3bcd), // 0.5
+];
+
+#[cfg(test)]
+mod golden_vectors {
+    use super::*;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    fn test_f64_arithmetic_vectors() {
+        for (op, a, b, expected) in F64_VECTORS {
+            let op = BinaryOp::try_from(op).unwrap();
+            let result = recompute(op, f64::from_bits(a), f64::from_bits(b)).unwrap();
+            assert_eq!(result.to_bits(), expected, "{:?}({:#x}, {:#x})", op, a, b);
+        }
+    }
+
+    #[test]
+    fn test_f64_sqrt_vectors() {
+        for (x, expected) in SQRT_VECTORS {
+            assert_eq!(
+                f64::from_bits(x).sqrt().to_bits(),
+                expected,
+                "sqrt({:#x})",
+                x
+            );
+        }
+    }
+
+    #[test]
+    fn test_f32_vectors() {
+        for (a, b, sum, product, quotient) in F32_VECTORS {
+            let (a, b) = (f32::from_bits(a), f32::from_bits(b));
+            assert_eq!(add_floats(a, b).to_bits(), sum);
+            assert_eq!(multiply_floats(a, b).to_bits(), product);
+            assert_eq!(divide_floats(a, b).unwrap().to_bits(), quotient);
+        }
+        assert_eq!(sqrt_float(2.0).to_bits(), 0x3fb504f3);
+    }
+
+    #[test]
+    fn test_library_vectors() {
+        let tenths = [0.1; 10];
+        assert_eq!(sum_doubles(&tenths).to_bits(), 0x3fefffffffffffff);
+        assert_eq!(
+            sum_doubles_compensated(&tenths).to_bits(),
+            0x3ff0000000000000
+        );
+        let dot = dot_compensated(&[1e16, 1.0, -1e16], &[1.0, 1.0, 1.0]).unwrap();
+        assert_eq!(dot.to_bits(), 0x3ff0000000000000);
+        let quantized = quantize(2.675, 2, RoundingMode::HalfEven).unwrap();
+        assert_eq!(quantized.to_bits(), 0x400570a3d70a3d71);
+    }
+}
@@ -0,0 +1,129 @@
+use solana_floats::bps::Bps;
+use solana_floats::error::FloatError;
+use solana_floats::governance::*;
+use solana_floats::units::Amount;
+
+#[cfg(test)]
+mod governance_tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+    const LOCK_END: i64 = 1_700_000_000;
+
+    #[test]
+    fn test_linear_decay() {
+        let curve = DecayCurve::Linear {
+            max_lock: 4 * 365 * DAY as u64,
+        };
+        let weight = |now| decayed_weight(1_000_000, LOCK_END, now, curve).unwrap();
+        assert_eq!(weight(LOCK_END - 8 * 365 * DAY), 1_000_000);
+        assert_eq!(weight(LOCK_END - 365 * DAY), 250_000);
+        // A third of a year left of four: 1/12
+        assert_eq!(weight(LOCK_END - 365 * DAY / 3), 83_333);
+        assert_eq!(weight(LOCK_END), 0);
+        assert_eq!(weight(i64::MAX), 0);
+    }
+
+    #[test]
+    fn test_exponential_decay() {
+        let curve = DecayCurve::Exponential {
+            half_life: 30.0 * DAY as f64,
+        };
+        let weight = |now| decayed_weight(1_000_000_000, LOCK_END, now, curve).unwrap();
+        assert_eq!(weight(LOCK_END - 30 * DAY), 500_000_000);
+        assert_eq!(weight(LOCK_END - 60 * DAY), 750_000_000);
+        // 1 - 2^-0.5 = 0.2928932188134...
+        assert_eq!(weight(LOCK_END - 15 * DAY), 292_893_218);
+        assert_eq!(weight(LOCK_END - 1), 267);
+        assert_eq!(weight(LOCK_END), 0);
+        assert_eq!(weight(i64::MIN), 1_000_000_000);
+    }
+
+    #[test]
+    fn test_weights_never_increase() {
+        let curves = [
+            DecayCurve::Linear { max_lock: 1_000 },
+            DecayCurve::Exponential { half_life: 0.7 },
+            DecayCurve::Exponential { half_life: 97.3 },
+            DecayCurve::Exponential { half_life: 1e-3 },
+        ];
+        for curve in curves {
+            let mut previous = u64::MAX;
+            for now in LOCK_END - 20_000..=LOCK_END + 1 {
+                let weight = decayed_weight(u64::MAX, LOCK_END, now, curve).unwrap();
+                assert!(weight <= previous, "{curve:?} increased at {now}");
+                previous = weight;
+            }
+            assert_eq!(previous, 0);
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_curves() {
+        let weight = |curve| decayed_weight(1, LOCK_END, 0, curve);
+        assert_eq!(
+            weight(DecayCurve::Linear { max_lock: 0 }),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            weight(DecayCurve::Exponential {
+                half_life: f64::NAN
+            }),
+            Err(FloatError::NaNOperand)
+        );
+        for half_life in [0.0, -1.0, f64::INFINITY] {
+            assert_eq!(
+                weight(DecayCurve::Exponential { half_life }),
+                Err(FloatError::OutOfRange)
+            );
+        }
+    }
+
+    #[test]
+    fn test_quorum_boundary_is_exact() {
+        // Exactly two thirds of 3_000_000 at 6_667 bps falls just short
+        assert_eq!(meets_quorum(2_000_000, 3_000_000, Bps(6_667)), Ok(false));
+        assert_eq!(meets_quorum(2_000_100, 3_000_000, Bps(6_667)), Ok(true));
+        assert_eq!(meets_quorum(2_000_099, 3_000_000, Bps(6_667)), Ok(false));
+        assert_eq!(meets_quorum(u64::MAX / 2, u64::MAX, Bps(5_000)), Ok(false));
+        assert_eq!(
+            meets_quorum(u64::MAX / 2 + 1, u64::MAX, Bps(5_000)),
+            Ok(true)
+        );
+        assert_eq!(meets_quorum(0, 1, Bps(0)), Ok(true));
+        assert_eq!(meets_quorum(7, 7, Bps(10_000)), Ok(true));
+    }
+
+    #[test]
+    fn test_quorum_rejects_bad_inputs() {
+        assert_eq!(
+            meets_quorum(0, 0, Bps(5_000)),
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(meets_quorum(2, 1, Bps(5_000)), Err(FloatError::OutOfRange));
+        assert_eq!(meets_quorum(1, 1, Bps(10_001)), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_quorum_f64_layer() {
+        let votes = Amount::<6>::new(0.3).unwrap();
+        let total = Amount::<6>::new(0.9).unwrap();
+        // 0.1 + 0.2 > 0.3 in f64, but the check runs on base units
+        let threshold = (0.1 + 0.2) / 0.9;
+        assert_eq!(meets_quorum_f64(votes, total, threshold), Ok(true));
+        assert_eq!(meets_quorum_f64(votes, total, 1.0 / 3.0), Ok(true));
+        assert_eq!(meets_quorum_f64(votes, total, 0.3334), Ok(false));
+        assert_eq!(
+            meets_quorum_f64(votes, total, -0.5),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            meets_quorum_f64(votes, total, 1.5),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            meets_quorum_f64(votes, total, f64::NAN),
+            Err(FloatError::NaNOperand)
+        );
+    }
+}
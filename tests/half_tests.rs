@@ -0,0 +1,149 @@
+use solana_floats::batch::BatchResults;
+use solana_floats::error::FloatError;
+use solana_floats::half::*;
+use solana_floats::instruction::{Opcode, encode};
+use solana_floats::quantize::{RoundingMode, quantize};
+use solana_program_test::*;
+
+mod common;
+
+#[cfg(test)]
+mod half_tests {
+    use super::*;
+    use common::process_with_metadata;
+
+    const HALF_EVEN: u8 = 4;
+
+    // Independent decoding straight from the binary16 definition
+    fn f16_by_definition(bits: u16) -> f64 {
+        let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+        let exponent = ((bits >> 10) & 0x1f) as i32;
+        let mantissa = (bits & 0x3ff) as f64;
+        if exponent == 0 {
+            sign * mantissa * 2_f64.powi(-24)
+        } else {
+            sign * (1.0 + mantissa / 1024.0) * 2_f64.powi(exponent - 15)
+        }
+    }
+
+    #[test]
+    fn test_every_f16_widens_exactly_and_round_trips() {
+        for bits in 0..=u16::MAX {
+            let value = f16_to_f64(bits);
+            if (bits >> 10) & 0x1f == 0x1f {
+                assert!(!value.is_finite());
+                assert_eq!(value.is_nan(), bits & 0x3ff != 0);
+                continue;
+            }
+            assert_eq!(
+                value.to_bits(),
+                f16_by_definition(bits).to_bits(),
+                "{:#06x}",
+                bits
+            );
+            assert_eq!(f16_to_f32(bits) as f64, value);
+            assert_eq!(f64_to_f16(value), Ok(bits), "{:#06x}", bits);
+        }
+        assert_eq!(f64_to_f16(f64::INFINITY), Ok(0x7c00));
+        assert_eq!(f64_to_f16(f64::NEG_INFINITY), Ok(0xfc00));
+        assert_eq!(f16_to_f64(0x7bff), F16_MAX);
+    }
+
+    #[test]
+    fn test_every_bf16_widens_exactly_and_round_trips() {
+        for bits in 0..=u16::MAX {
+            let value = bf16_to_f64(bits);
+            let expected = f32::from_bits((bits as u32) << 16);
+            assert_eq!(bf16_to_f32(bits).to_bits(), expected.to_bits());
+            if value.is_nan() {
+                continue;
+            }
+            assert_eq!(value, expected as f64);
+            assert_eq!(f64_to_bf16(value), Ok(bits), "{:#06x}", bits);
+        }
+    }
+
+    #[test]
+    fn test_narrowing_never_rounds() {
+        assert_eq!(f64_to_f16(0.1), Err(FloatError::PrecisionLoss));
+        assert_eq!(f64_to_f16(2049.0), Err(FloatError::PrecisionLoss));
+        assert_eq!(f64_to_f16(2_f64.powi(-25)), Err(FloatError::PrecisionLoss));
+        assert_eq!(f64_to_f16(65505.0), Err(FloatError::Overflow));
+        assert_eq!(f64_to_f16(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(f64_to_f16(0.375), Ok(0x3600));
+        assert_eq!(f64_to_f16(-0.0), Ok(0x8000));
+
+        assert_eq!(f64_to_bf16(0.1), Err(FloatError::PrecisionLoss));
+        assert_eq!(f64_to_bf16(257.0), Err(FloatError::PrecisionLoss));
+        assert_eq!(f64_to_bf16(1e39), Err(FloatError::Overflow));
+        assert_eq!(f64_to_bf16(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(f64_to_bf16(1.5e38), Err(FloatError::PrecisionLoss));
+        assert_eq!(f64_to_bf16(256.0), Ok(0x4380));
+    }
+
+    #[test]
+    fn test_operand_encoding() {
+        let values = [0.5, -1.25, 1024.0];
+        for format in [HalfFormat::F16, HalfFormat::Bf16] {
+            let bytes = encode_operands(format, &values).unwrap();
+            assert_eq!(bytes.len(), values.len() * 2);
+            assert_eq!(decode_operands(format, &bytes), Ok(values.to_vec()));
+            assert_eq!(
+                decode_operands(format, &bytes[..5]),
+                Err(FloatError::OutOfRange)
+            );
+        }
+        assert_eq!(
+            encode_operands(HalfFormat::F16, &[0.5, 0.1]),
+            Err(FloatError::PrecisionLoss)
+        );
+        assert_eq!(HalfFormat::try_from(1), Ok(HalfFormat::Bf16));
+        assert_eq!(HalfFormat::try_from(2), Err(FloatError::OutOfRange));
+    }
+
+    #[tokio::test]
+    async fn test_batch_quantize_half_instruction() {
+        // f16 has 10 fractional bits: 0.1 is not representable, 0.099609375 is
+        let values = [0.099609375, 3.140625, -2.5, 1000.0];
+        for format in [HalfFormat::F16, HalfFormat::Bf16] {
+            let values: Vec<f64> = values
+                .iter()
+                .copied()
+                .filter(|&value| format.from_f64(value).is_ok())
+                .collect();
+            let mut payload = vec![2, HALF_EVEN, format as u8];
+            payload.extend(encode_operands(format, &values).unwrap());
+            let result =
+                process_with_metadata(&encode(Opcode::BatchQuantizeHalf, &payload), vec![]).await;
+            assert!(result.result.is_ok());
+
+            let return_data = result.metadata.unwrap().return_data.unwrap();
+            let results = BatchResults::decode(&return_data.data).unwrap();
+            let expected: Vec<f64> = values
+                .iter()
+                .map(|&value| quantize(value, 2, RoundingMode::HalfEven).unwrap())
+                .collect();
+            assert_eq!(results.values, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_quantize_half_rejects_malformed_payloads() {
+        for payload in [
+            vec![2, HALF_EVEN],
+            vec![2, HALF_EVEN, 7, 0, 0],
+            vec![2, HALF_EVEN, 0, 0],
+        ] {
+            let result =
+                process_with_metadata(&encode(Opcode::BatchQuantizeHalf, &payload), vec![]).await;
+            assert!(result.result.is_err(), "{:?}", payload);
+        }
+    }
+
+    #[test]
+    fn test_half_operands_are_a_quarter_of_f64() {
+        let values = vec![0.25; 400];
+        let half = encode_operands(HalfFormat::F16, &values).unwrap();
+        assert_eq!(half.len() * 4, values.len() * 8);
+    }
+}
@@ -0,0 +1,515 @@
+use solana_floats::chain::{ChainStep, Operand};
+use solana_floats::compare::Tolerance;
+use solana_floats::double_ops::{BinaryOp, UnaryOp};
+use solana_floats::error::FloatError;
+use solana_floats::feature_gate::FeatureGate;
+use solana_floats::half::HalfFormat;
+use solana_floats::instruction::*;
+use solana_floats::operands::AccountOperands;
+use solana_floats::quantize::RoundingMode;
+use solana_program::hash::hash;
+use solana_program::program_error::ProgramError;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey, signature::Signer, transaction::{Transaction, TransactionError},
+};
+
+#[cfg(test)]
+mod instruction_tests {
+    use super::*;
+
+    fn f32_pair(a: f32, b: f32) -> Vec<u8> {
+        let mut payload = a.to_le_bytes().to_vec();
+        payload.extend_from_slice(&b.to_le_bytes());
+        payload
+    }
+
+    #[test]
+    fn test_discriminators_match_hashed_names() {
+        for opcode in Opcode::ALL {
+            let digest = hash(format!("solana_floats:{}", opcode.name()).as_bytes());
+            assert_eq!(
+                opcode.discriminator(),
+                digest.to_bytes()[..DISCRIMINATOR_LEN],
+                "stale discriminator for {}",
+                opcode.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_discriminators_are_unique() {
+        for (i, a) in Opcode::ALL.iter().enumerate() {
+            for b in &Opcode::ALL[i + 1..] {
+                assert_ne!(a.discriminator(), b.discriminator());
+            }
+        }
+    }
+
+    #[test]
+    fn test_legacy_layout_still_decodes() {
+        let mut instruction_data = vec![1u8];
+        instruction_data.extend_from_slice(&f32_pair(2.5, 4.0));
+        let (opcode, payload) = decode(&instruction_data).unwrap();
+        assert_eq!(opcode, Opcode::Multiply);
+        assert_eq!(payload, &instruction_data[1..]);
+    }
+
+    #[test]
+    fn test_versioned_layout_round_trip() {
+        for opcode in Opcode::ALL {
+            let instruction_data = encode(opcode, &[7, 8, 9]);
+            assert_eq!(instruction_data[0], VERSION_V1);
+            assert_eq!(decode(&instruction_data), Ok((opcode, &[7u8, 8, 9][..])));
+        }
+    }
+
+    #[test]
+    fn test_unknown_instructions_are_rejected() {
+        assert_eq!(decode(&[]), Err(ProgramError::InvalidInstructionData));
+        // Unassigned legacy opcodes; only 0..=2 ever shipped
+        assert_eq!(decode(&[0x7f]), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(decode(&[3, 2, 4]), Err(ProgramError::InvalidInstructionData));
+        // Unknown version byte
+        let mut instruction_data = encode(Opcode::Add, &[]);
+        instruction_data[0] = 0x82;
+        assert_eq!(
+            decode(&instruction_data),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        // Unknown discriminator
+        let mut instruction_data = encode(Opcode::Add, &[]);
+        instruction_data[1] ^= 0xff;
+        assert_eq!(
+            decode(&instruction_data),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        // Truncated discriminator
+        assert_eq!(
+            decode(&[VERSION_V1, 0xb5, 0xb9]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    // One instruction per opcode, in `Opcode::ALL` order
+    fn sample_instructions() -> Vec<FloatInstruction> {
+        let f32s = F32Operands { a: 0.1, b: 0.2 };
+        let f64s = F64Operands { a: 0.1, b: 0.2 };
+        let operands = AccountOperands { offset: 8, count: 3 };
+        vec![
+            FloatInstruction::Add(f32s),
+            FloatInstruction::Multiply(f32s),
+            FloatInstruction::Divide(f32s),
+            FloatInstruction::BatchQuantize {
+                decimals: 2,
+                mode: RoundingMode::HalfEven,
+                values: vec![2.675, -1.005],
+            },
+            FloatInstruction::BatchQuantizeAccount {
+                operands,
+                decimals: 6,
+                mode: RoundingMode::Floor,
+            },
+            FloatInstruction::SumAccount(operands),
+            FloatInstruction::Cmp {
+                operands: f64s,
+                tolerance: Some(1e-9),
+            },
+            FloatInstruction::Format {
+                decimals: 4,
+                mode: RoundingMode::HalfUp,
+                value: 1.0 / 3.0,
+            },
+            FloatInstruction::SetFeatures { f32_enabled: true },
+            FloatInstruction::Verify {
+                op: BinaryOp::Add,
+                operands: f64s,
+                claimed_bits: 0x3fd3333333333334,
+            },
+            FloatInstruction::Evaluate {
+                flags: EVALUATE_FLAG_ANALYSIS,
+                op: BinaryOp::Divide,
+                operands: f64s,
+            },
+            FloatInstruction::LogValue {
+                style: LOG_STYLE_BITS,
+                value: 0.3,
+            },
+            FloatInstruction::Sqrt {
+                implementation: SQRT_FAST,
+                value: 2.0,
+            },
+            FloatInstruction::Transcendental {
+                function: TRANSCENDENTAL_LN,
+                implementation: TRANSCENDENTAL_LUT,
+                value: 10.0,
+            },
+            FloatInstruction::StatsAccount(operands),
+            FloatInstruction::BatchQuantizeHalf {
+                decimals: 1,
+                mode: RoundingMode::Ceil,
+                format: HalfFormat::Bf16,
+                values: vec![0x3f80, 0x4049],
+            },
+            FloatInstruction::ChainStep(ChainStep {
+                op: BinaryOp::Multiply,
+                lhs: Operand::Value(1.01),
+                rhs: Operand::Value(100.0),
+                destination: 0,
+            }),
+            FloatInstruction::AddDoubles(f64s),
+            FloatInstruction::MultiplyDoubles(f64s),
+            FloatInstruction::DivideDoubles(f64s),
+            FloatInstruction::Subtract(SubtractOperands::F64(f64s)),
+            FloatInstruction::Unary {
+                op: UnaryOp::Log10,
+                value: 1000.0,
+            },
+            FloatInstruction::RecordResult {
+                op: BinaryOp::Divide,
+                operands: f64s,
+            },
+            FloatInstruction::ApproxEq {
+                flags: APPROX_EQ_FLAG_REQUIRE,
+                operands: f64s,
+                tolerance: Tolerance::new(1e-12, 1e-9).unwrap(),
+            },
+            FloatInstruction::InitChainCache { slots: 4 },
+        ]
+    }
+
+    #[test]
+    fn test_float_instruction_round_trip() {
+        let instructions = sample_instructions();
+        assert_eq!(instructions.len(), Opcode::ALL.len());
+        for (instruction, opcode) in instructions.into_iter().zip(Opcode::ALL) {
+            assert_eq!(instruction.opcode(), opcode);
+            let instruction_data = instruction.pack();
+            assert_eq!(decode(&instruction_data).unwrap().0, opcode);
+            assert_eq!(FloatInstruction::unpack(&instruction_data), Ok(instruction.clone()));
+
+            match (opcode.legacy_opcode(), instruction.pack_legacy()) {
+                (Some(legacy_opcode), Some(legacy)) => {
+                    assert_eq!(legacy[0], legacy_opcode);
+                    assert_eq!(legacy[1..], instruction_data[1 + DISCRIMINATOR_LEN..]);
+                    assert_eq!(FloatInstruction::unpack(&legacy), Ok(instruction));
+                }
+                (None, None) => {}
+                _ => panic!("legacy encoding of {} disagrees with its opcode", opcode.name()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_payloads_keep_their_byte_layout() {
+        let mut legacy = vec![1u8];
+        legacy.extend_from_slice(&f32_pair(2.5, 4.0));
+        assert_eq!(
+            FloatInstruction::unpack(&legacy),
+            Ok(FloatInstruction::Multiply(F32Operands { a: 2.5, b: 4.0 }))
+        );
+
+        let mut payload = vec![SUBTRACT_F32];
+        payload.extend_from_slice(&f32_pair(1.5, 0.5));
+        assert_eq!(
+            FloatInstruction::unpack(&encode(Opcode::Subtract, &payload)),
+            Ok(FloatInstruction::Subtract(SubtractOperands::F32(F32Operands {
+                a: 1.5,
+                b: 0.5
+            })))
+        );
+
+        // The tolerance of `Cmp` is optional
+        let mut payload = 1.0_f64.to_le_bytes().to_vec();
+        payload.extend_from_slice(&2.0_f64.to_le_bytes());
+        assert_eq!(
+            FloatInstruction::unpack(&encode(Opcode::Cmp, &payload)),
+            Ok(FloatInstruction::Cmp {
+                operands: F64Operands { a: 1.0, b: 2.0 },
+                tolerance: None,
+            })
+        );
+
+        let operands = AccountOperands { offset: 16, count: 4 };
+        let mut payload = operands.pack().to_vec();
+        payload.extend_from_slice(&[2, 4]);
+        assert_eq!(
+            FloatInstruction::unpack(&encode(Opcode::BatchQuantizeAccount, &payload)),
+            Ok(FloatInstruction::BatchQuantizeAccount {
+                operands,
+                decimals: 2,
+                mode: RoundingMode::HalfEven,
+            })
+        );
+    }
+
+    #[test]
+    fn test_nan_operands_are_decoded() {
+        // Borsh refuses NaN floats; operands must still reach the handlers
+        let nan = f64::from_bits(0x7ff8_0000_0000_0001);
+        let instruction = FloatInstruction::Unary {
+            op: UnaryOp::Sqrt,
+            value: nan,
+        };
+        match FloatInstruction::unpack(&instruction.pack()) {
+            Ok(FloatInstruction::Unary { op, value }) => {
+                assert_eq!(op, UnaryOp::Sqrt);
+                assert_eq!(value.to_bits(), nan.to_bits());
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let instruction = FloatInstruction::Add(F32Operands {
+            a: f32::NAN,
+            b: 1.0,
+        });
+        match FloatInstruction::unpack(&instruction.pack()) {
+            Ok(FloatInstruction::Add(F32Operands { a, b })) => {
+                assert_eq!(a.to_bits(), f32::NAN.to_bits());
+                assert_eq!(b, 1.0);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_malformed_payloads_are_rejected() {
+        let f64_bytes = |values: &[f64]| -> Vec<u8> {
+            values.iter().flat_map(|value| value.to_le_bytes()).collect()
+        };
+        let cases = [
+            // Trailing byte after a legacy instruction
+            [vec![0u8], f32_pair(1.0, 2.0), vec![0]].concat(),
+            // Truncated operand
+            encode(Opcode::AddDoubles, &f64_bytes(&[1.0])[..7]),
+            // Partial tolerance
+            encode(Opcode::Cmp, &f64_bytes(&[1.0, 2.0, 0.5])[..20]),
+            // Partial value in a batch
+            encode(Opcode::BatchQuantize, &[&[2, 4][..], &f64_bytes(&[1.0])[..7]].concat()),
+            // Unknown rounding mode
+            encode(Opcode::Format, &[&[2, 5][..], &f64_bytes(&[1.0])].concat()),
+            // Unknown half format
+            encode(Opcode::BatchQuantizeHalf, &[2, 4, 2]),
+            // Booleans are 0 or 1
+            encode(Opcode::SetFeatures, &[2]),
+            // Unknown width
+            encode(Opcode::Subtract, &[&[2][..], &f64_bytes(&[1.0, 2.0])].concat()),
+            // Unknown operation
+            encode(Opcode::Unary, &[&[8][..], &f64_bytes(&[1.0])].concat()),
+        ];
+        for instruction_data in cases {
+            assert_eq!(
+                FloatInstruction::unpack(&instruction_data),
+                Err(ProgramError::InvalidInstructionData),
+                "accepted {:?}",
+                instruction_data
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_program_accepts_both_layouts() {
+        let program_id = Pubkey::new_unique();
+        let program_test = ProgramTest::new(
+            "solana_floats",
+            program_id,
+            processor!(solana_floats::process_instruction),
+        );
+        let (banks_client, payer, recent_blockhash) = program_test.start().await;
+        let (gate, _) = FeatureGate::find_address(&program_id);
+
+        let mut legacy = vec![2u8];
+        legacy.extend_from_slice(&f32_pair(10.0, 4.0));
+        let versioned = encode(Opcode::Divide, &f32_pair(10.0, 4.0));
+        let versioned_by_zero = encode(Opcode::Divide, &f32_pair(10.0, 0.0));
+
+        for (instruction_data, should_succeed) in [
+            (legacy, true),
+            (versioned, true),
+            (versioned_by_zero, false),
+        ] {
+            let instruction = Instruction::new_with_bytes(
+                program_id,
+                &instruction_data,
+                vec![AccountMeta::new_readonly(gate, false)],
+            );
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer],
+                recent_blockhash,
+            );
+            let result = banks_client.process_transaction(transaction).await;
+            assert_eq!(result.is_ok(), should_succeed);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_f64_instructions() {
+        let program_id = Pubkey::new_unique();
+        let program_test = ProgramTest::new(
+            "solana_floats",
+            program_id,
+            processor!(solana_floats::process_instruction),
+        );
+        let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        let f64_pair = |a: f64, b: f64| {
+            let mut payload = a.to_le_bytes().to_vec();
+            payload.extend_from_slice(&b.to_le_bytes());
+            payload
+        };
+        let cases = [
+            (Opcode::AddDoubles, f64_pair(0.1, 0.2), Ok(0.1_f64 + 0.2)),
+            (Opcode::MultiplyDoubles, f64_pair(1.1, 1.1), Ok(1.1 * 1.1)),
+            (Opcode::DivideDoubles, f64_pair(1.0, 3.0), Ok(1.0 / 3.0)),
+            (
+                Opcode::DivideDoubles,
+                f64_pair(1.0, 0.0),
+                Err(InstructionError::Custom(FloatError::DivisionByZero.code())),
+            ),
+            // Two f32 operands are too short, and trailing bytes are rejected
+            (
+                Opcode::AddDoubles,
+                f32_pair(0.1, 0.2),
+                Err(InstructionError::InvalidInstructionData),
+            ),
+            (
+                Opcode::AddDoubles,
+                [f64_pair(0.1, 0.2), vec![0]].concat(),
+                Err(InstructionError::InvalidInstructionData),
+            ),
+        ];
+        for (opcode, payload, expected) in cases {
+            let instruction =
+                Instruction::new_with_bytes(program_id, &encode(opcode, &payload), vec![]);
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer],
+                recent_blockhash,
+            );
+            let result = banks_client
+                .process_transaction_with_metadata(transaction)
+                .await
+                .unwrap();
+            match expected {
+                Ok(value) => {
+                    result.result.unwrap();
+                    let data = result.metadata.unwrap().return_data.unwrap().data;
+                    assert_eq!(data, value.to_le_bytes());
+                }
+                Err(error) => assert_eq!(
+                    result.result,
+                    Err(TransactionError::InstructionError(0, error))
+                ),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_float_errors_keep_their_codes() {
+        let program_id = Pubkey::new_unique();
+        let operand_account = Pubkey::new_unique();
+        let mut program_test = ProgramTest::new(
+            "solana_floats",
+            program_id,
+            processor!(solana_floats::process_instruction),
+        );
+        program_test.add_account(
+            operand_account,
+            Account {
+                lamports: 1_000_000_000,
+                data: [1.5_f64, f64::NAN].iter().flat_map(|v| v.to_le_bytes()).collect(),
+                owner: Pubkey::new_unique(),
+                ..Account::default()
+            },
+        );
+        let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        let nan = F64Operands { a: f64::NAN, b: 1.0 };
+        let both = AccountOperands { offset: 0, count: 2 };
+        let cases = [
+            (
+                FloatInstruction::BatchQuantize {
+                    decimals: 2,
+                    mode: RoundingMode::HalfEven,
+                    values: vec![1.0, f64::INFINITY],
+                },
+                FloatError::NaNOperand,
+            ),
+            (
+                FloatInstruction::BatchQuantizeAccount {
+                    operands: AccountOperands { offset: 0, count: 1 },
+                    decimals: u8::MAX,
+                    mode: RoundingMode::Floor,
+                },
+                FloatError::OutOfRange,
+            ),
+            (
+                FloatInstruction::BatchQuantizeAccount {
+                    operands: both,
+                    decimals: 2,
+                    mode: RoundingMode::Floor,
+                },
+                FloatError::NaNOperand,
+            ),
+            (FloatInstruction::StatsAccount(both), FloatError::NaNOperand),
+            (
+                FloatInstruction::Cmp {
+                    operands: F64Operands { a: 1.0, b: 2.0 },
+                    tolerance: Some(-1.0),
+                },
+                FloatError::OutOfRange,
+            ),
+            (
+                FloatInstruction::Format {
+                    decimals: 2,
+                    mode: RoundingMode::HalfUp,
+                    value: 1e18,
+                },
+                FloatError::Overflow,
+            ),
+            (
+                FloatInstruction::Verify {
+                    op: BinaryOp::Divide,
+                    operands: F64Operands { a: 1.0, b: 0.0 },
+                    claimed_bits: 0,
+                },
+                FloatError::DivisionByZero,
+            ),
+            (
+                FloatInstruction::Evaluate {
+                    flags: 0,
+                    op: BinaryOp::Add,
+                    operands: nan,
+                },
+                FloatError::NaNOperand,
+            ),
+        ];
+        for (instruction, expected) in cases {
+            let instruction = Instruction::new_with_bytes(
+                program_id,
+                &instruction.pack(),
+                vec![AccountMeta::new_readonly(operand_account, false)],
+            );
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer],
+                recent_blockhash,
+            );
+            let err = banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap();
+            assert_eq!(
+                err,
+                TransactionError::InstructionError(0, InstructionError::Custom(expected.code()))
+            );
+        }
+    }
+}
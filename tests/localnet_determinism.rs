@@ -0,0 +1,208 @@
+//! End-to-end determinism check on a local validator.
+//!
+//! Starts `solana-test-validator` with the SBF build of the program, sends a
+//! fixed workload of Evaluate, Sqrt and Transcendental instructions and reads
+//! back the result bits each transaction returned. The workload runs three
+//! times: on a fresh ledger, after restarting the validator on that same
+//! ledger (which replays it on startup), and on another fresh ledger. Every
+//! run must return the same bits, and the basic operations must match the
+//! host's correctly rounded results. Needs the Solana CLI tools on `PATH`:
+//!
+//! ```text
+//! cargo build-sbf
+//! cargo test --features localnet --test localnet_determinism -- --nocapture
+//! ```
+#![cfg(feature = "localnet")]
+
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread::sleep;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use solana_floats::corpus::{DECIMAL_TIES, INEXACT_DECIMALS, NEAR_TIES};
+use solana_floats::double_ops::BinaryOp;
+use solana_floats::instruction::*;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::config::RpcTransactionConfig;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use solana_transaction_status_client_types::UiTransactionEncoding;
+
+const RPC_PORT: u16 = 18_899;
+const FAUCET_PORT: u16 = 19_900;
+
+// Fixed, so restarted validators load the program at the same address
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([0x5f; 32]);
+
+/// A running `solana-test-validator`, killed when dropped.
+struct Validator(Child);
+
+impl Validator {
+    fn start(ledger: &Path, reset: bool) -> (Self, RpcClient) {
+        let program = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/deploy/solana_floats.so");
+        assert!(
+            program.exists(),
+            "{} not found: run `cargo build-sbf` first",
+            program.display()
+        );
+        let mut command = Command::new("solana-test-validator");
+        command
+            .arg("--ledger")
+            .arg(ledger)
+            .args(["--rpc-port", &RPC_PORT.to_string()])
+            .args(["--faucet-port", &FAUCET_PORT.to_string()])
+            .arg("--quiet")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        if reset {
+            command
+                .arg("--reset")
+                .arg("--bpf-program")
+                .arg(PROGRAM_ID.to_string())
+                .arg(&program);
+        }
+        let validator = Validator(
+            command
+                .spawn()
+                .expect("solana-test-validator must be on PATH"),
+        );
+
+        let client = RpcClient::new_with_commitment(
+            format!("http://127.0.0.1:{}", RPC_PORT),
+            CommitmentConfig::confirmed(),
+        );
+        for _ in 0..240 {
+            if client.get_health().is_ok() {
+                return (validator, client);
+            }
+            sleep(Duration::from_millis(500));
+        }
+        panic!("validator did not become healthy");
+    }
+}
+
+impl Drop for Validator {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Instruction data of the workload, with the bits IEEE 754 requires of the
+/// result where it fixes them.
+fn workload() -> Vec<(Vec<u8>, Option<u64>)> {
+    let mut calls = Vec::new();
+    let lefts = INEXACT_DECIMALS.into_iter().chain(NEAR_TIES);
+    let rights = DECIMAL_TIES.into_iter().map(|(value, _)| value).cycle();
+    for (a, b) in lefts.zip(rights) {
+        for (code, op) in [
+            (0u8, BinaryOp::Add),
+            (1, BinaryOp::Multiply),
+            (2, BinaryOp::Divide),
+        ] {
+            let mut payload = vec![0, code];
+            payload.extend_from_slice(&a.to_le_bytes());
+            payload.extend_from_slice(&b.to_le_bytes());
+            let expected = match op {
+                BinaryOp::Add => a + b,
+                BinaryOp::Multiply => a * b,
+                BinaryOp::Divide => a / b,
+            };
+            calls.push((encode(Opcode::Evaluate, &payload), Some(expected.to_bits())));
+        }
+    }
+    for (value, _) in DECIMAL_TIES {
+        for implementation in [SQRT_BUILTIN, SQRT_FAST] {
+            let mut payload = vec![implementation];
+            payload.extend_from_slice(&value.to_le_bytes());
+            let expected = (implementation == SQRT_BUILTIN).then(|| value.sqrt().to_bits());
+            calls.push((encode(Opcode::Sqrt, &payload), expected));
+        }
+        for function in [TRANSCENDENTAL_EXP, TRANSCENDENTAL_LN] {
+            for implementation in [TRANSCENDENTAL_SERIES, TRANSCENDENTAL_LUT] {
+                let mut payload = vec![function, implementation];
+                payload.extend_from_slice(&value.to_le_bytes());
+                calls.push((encode(Opcode::Transcendental, &payload), None));
+            }
+        }
+    }
+    calls
+}
+
+/// Sends every workload instruction in its own transaction and returns the
+/// first 8 bytes of each one's return data, as recorded in the ledger.
+fn run_workload(client: &RpcClient) -> Vec<u64> {
+    let payer = Keypair::new();
+    let airdrop = client
+        .request_airdrop(&payer.pubkey(), 10_000_000_000)
+        .unwrap();
+    while !client.confirm_transaction(&airdrop).unwrap() {
+        sleep(Duration::from_millis(200));
+    }
+
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+    };
+    workload()
+        .into_iter()
+        .map(|(instruction_data, _)| {
+            let instruction = Instruction::new_with_bytes(PROGRAM_ID, &instruction_data, vec![]);
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer],
+                client.get_latest_blockhash().unwrap(),
+            );
+            let signature = client.send_and_confirm_transaction(&transaction).unwrap();
+            let confirmed = client
+                .get_transaction_with_config(&signature, config)
+                .unwrap();
+            let return_data = confirmed.transaction.meta.unwrap().return_data.unwrap();
+            let bytes = STANDARD.decode(return_data.data.0).unwrap();
+            u64::from_le_bytes(bytes[0..8].try_into().unwrap())
+        })
+        .collect()
+}
+
+fn ledger_dir(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("target/localnet")
+        .join(name)
+}
+
+#[test]
+fn localnet_results_are_identical_across_runs() {
+    let first_ledger = ledger_dir("first");
+    let fresh = {
+        let (_validator, client) = Validator::start(&first_ledger, true);
+        run_workload(&client)
+    };
+    let replayed = {
+        let (_validator, client) = Validator::start(&first_ledger, false);
+        run_workload(&client)
+    };
+    let second_fresh = {
+        let (_validator, client) = Validator::start(&ledger_dir("second"), true);
+        run_workload(&client)
+    };
+
+    let calls = workload();
+    assert_eq!(fresh.len(), calls.len());
+    for (i, (_, expected)) in calls.iter().enumerate() {
+        assert_eq!(fresh[i], replayed[i], "call {} after restart", i);
+        assert_eq!(fresh[i], second_fresh[i], "call {} on a new ledger", i);
+        if let Some(expected) = expected {
+            assert_eq!(fresh[i], *expected, "call {} against the host", i);
+        }
+    }
+    println!("{} results identical over 3 validator runs", calls.len());
+}
@@ -0,0 +1,122 @@
+use solana_floats::instruction::{LOG_STYLE_BITS, LOG_STYLE_FORMATTED, Opcode, encode};
+use solana_floats::logging::*;
+use solana_program_test::*;
+
+mod common;
+
+#[cfg(test)]
+mod logging_tests {
+    use super::*;
+    use common::process_with_metadata;
+
+    // Compute units used by one LogValue instruction, or None if it failed
+    async fn log_value_units(style: u8, value: f64) -> Option<u64> {
+        let mut payload = vec![style];
+        payload.extend_from_slice(&value.to_bits().to_le_bytes());
+        let result = process_with_metadata(&encode(Opcode::LogValue, &payload), vec![]).await;
+        result.result.ok()?;
+        Some(result.metadata?.compute_units_consumed)
+    }
+
+    #[test]
+    fn test_parse_round_trips_exactly() {
+        for value in [
+            0.1 + 0.2,
+            -0.0,
+            f64::MIN_POSITIVE / 3.0,
+            f64::MAX,
+            f64::NEG_INFINITY,
+        ] {
+            let line = format!("Program log: price: f64 0x{:016x}", value.to_bits());
+            let (label, parsed) = parse_float_bits(&line).unwrap();
+            assert_eq!(label, "price");
+            assert_eq!(parsed.to_bits(), value.to_bits());
+        }
+        let (_, nan) = parse_float_bits("x: f64 0x7ff8000000000001").unwrap();
+        assert_eq!(nan.to_bits(), 0x7ff8000000000001);
+    }
+
+    #[test]
+    fn test_parse_f32_lines() {
+        let line = format!("rate: f32 0x{:08x}", 0.1_f32.to_bits());
+        assert_eq!(parse_float_bits(&line), Some(("rate", 0.1_f32 as f64)));
+    }
+
+    #[test]
+    fn test_labels_may_contain_the_separator() {
+        let line = format!("a: b: f64 0x{:016x}", 1.5_f64.to_bits());
+        assert_eq!(parse_float_bits(&line), Some(("a: b", 1.5)));
+    }
+
+    #[test]
+    fn test_malformed_lines_are_not_parsed() {
+        for line in [
+            "Program log: value: 0.3",
+            "value: f64 0x3fd333",
+            "value: f64 0x3fd333333333333g",
+            "value: f32 0x3dcccccd00",
+            "",
+        ] {
+            assert_eq!(parse_float_bits(line), None, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_parse_compute_unit_lines() {
+        assert_eq!(
+            parse_compute_units("Program log: batch_quantize: compute_units 18421"),
+            Some(("batch_quantize", 18421))
+        );
+        assert_eq!(parse_compute_units("sqrt: compute_units 0"), Some(("sqrt", 0)));
+        for line in [
+            "sqrt: compute_units ",
+            "sqrt: compute_units -5",
+            "sqrt: compute_units 12 ",
+            "sqrt: compute_units 99999999999999999999",
+            "value: f64 0x3fd3333333333334",
+        ] {
+            assert_eq!(parse_compute_units(line), None, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_pretty_print_rewrites_only_bit_lines() {
+        let lines = [
+            "Program log: Instruction: deposit".to_string(),
+            format!(
+                "Program log: price: f64 0x{:016x}",
+                (0.1 + 0.2_f64).to_bits()
+            ),
+            format!("Program log: fee: f32 0x{:08x}", 0.25_f32.to_bits()),
+        ];
+        assert_eq!(
+            pretty_print_logs(&lines),
+            vec![
+                "Program log: Instruction: deposit",
+                "price: 0.30000000000000004",
+                "fee: 0.25",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_log_value_instruction_styles() {
+        let formatted = log_value_units(LOG_STYLE_FORMATTED, 0.1 + 0.2)
+            .await
+            .unwrap();
+        let bits = log_value_units(LOG_STYLE_BITS, 0.1 + 0.2).await.unwrap();
+        assert_eq!(log_value_units(2, 1.0).await, None);
+        println!(
+            "LogValue compute units: formatted {}, bits {}",
+            formatted, bits
+        );
+
+        // Compute units are only metered for the SBF build (cargo test-sbf),
+        // and with no-onchain-format both styles log bits
+        let sbf =
+            std::env::var_os("SBF_OUT_DIR").is_some() || std::env::var_os("BPF_OUT_DIR").is_some();
+        if sbf && !cfg!(feature = "no-onchain-format") {
+            assert!(bits < formatted);
+        }
+    }
+}
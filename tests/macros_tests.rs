@@ -0,0 +1,74 @@
+use solana_floats::bps::*;
+use solana_floats::compare::Tolerance;
+use solana_floats::{
+    approx_assert_eq, assert_within_bps, debug_approx_assert_eq, debug_assert_within_bps,
+};
+
+#[cfg(test)]
+mod macros_tests {
+    use super::*;
+
+    #[test]
+    fn test_approx_assert_eq_accepts_matches() {
+        approx_assert_eq!(0.1 + 0.2, 0.3, Tolerance::new(1e-15, 0.0).unwrap());
+        approx_assert_eq!(1e20 + 1e5, 1e20, Tolerance::new(0.0, 1e-12).unwrap());
+        approx_assert_eq!(100.0, 100.01, Bps(1), "price drifted");
+        debug_approx_assert_eq!(-0.0, 0.0, Tolerance::new(0.0, 0.0).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "left: 0.30000000000000004 (0x3fd3333333333334)")]
+    fn test_approx_assert_eq_reports_bit_patterns() {
+        approx_assert_eq!(0.1 + 0.2, 0.3, Tolerance::new(0.0, 0.0).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "failed: vault 7 balance")]
+    fn test_approx_assert_eq_appends_message() {
+        approx_assert_eq!(1.0, 2.0, Bps(10), "vault {} balance", 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "right: NaN")]
+    fn test_nan_never_matches() {
+        approx_assert_eq!(f64::NAN, f64::NAN, Tolerance::new(1.0, 1.0).unwrap());
+    }
+
+    #[test]
+    fn test_assert_within_bps_accepts_matches() {
+        assert_within_bps!(100.0, 100.05, 5);
+        assert_within_bps!(100.0, 100.05, Bps(5), "slippage");
+        assert_within_bps!(0.0, 0.0, 0);
+        debug_assert_within_bps!(-50.0, -50.0, 0_u32);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion `left within 5 bps of right` failed")]
+    fn test_assert_within_bps_rejects_mismatch() {
+        assert_within_bps!(100.0, 100.06, 5);
+    }
+
+    #[test]
+    fn test_tolerance_validation() {
+        use solana_floats::error::FloatError;
+        assert_eq!(Tolerance::new(f64::NAN, 0.0), Err(FloatError::NaNOperand));
+        assert_eq!(Tolerance::new(-1.0, 0.0), Err(FloatError::OutOfRange));
+        assert_eq!(
+            Tolerance::new(0.0, f64::INFINITY),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(Tolerance::from(Bps(25)).rel(), 0.0025);
+    }
+
+    #[test]
+    fn test_difference_bps() {
+        assert_eq!(difference_bps(0.0, 0.0), Ok(0.0));
+        assert!((difference_bps(100.0, 99.0).unwrap() - 100.0).abs() < 1e-9);
+        assert!(difference_bps(f64::NAN, 1.0).is_err());
+        assert!(within_bps(-100.0, -99.99, Bps(2)));
+        assert!(!within_bps(-100.0, -99.9, Bps(2)));
+        // Opposite signs are 200% apart
+        assert!(!within_bps(1.0, -1.0, Bps(10_000)));
+        assert!(within_bps(1.0, -1.0, Bps(20_000)));
+    }
+}
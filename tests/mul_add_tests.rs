@@ -0,0 +1,112 @@
+use solana_floats::double_ops::*;
+use solana_floats::float_ops::*;
+
+mod common;
+
+#[cfg(test)]
+mod mul_add_tests {
+    use super::*;
+    use common::{bit_patterns, same_f32, same_f64};
+
+    #[test]
+    fn test_fused_differs_from_separate_ops() {
+        assert_eq!(multiply_doubles(0.1, 10.0) - 1.0, 0.0);
+        assert_eq!(mul_add_doubles(0.1, 10.0, -1.0), 2f64.powi(-54));
+        // The fused result recovers the rounding error of the product
+        let product = multiply_doubles(0.1, 0.1);
+        assert_ne!(mul_add_doubles(0.1, 0.1, -product), 0.0);
+        assert_eq!(mul_add_floats(0.1, 10.0, -1.0), 1.490_116_1e-8);
+        assert_eq!(multiply_floats(0.1, 10.0) - 1.0, 0.0);
+    }
+
+    #[test]
+    fn test_fused_matches_separate_ops_for_exact_products() {
+        assert_eq!(mul_add_doubles(3.0, 7.0, 0.1), add_doubles(21.0, 0.1));
+        assert_eq!(mul_add_doubles(0.1, 0.5, 0.3), add_doubles(0.05, 0.3));
+        assert_eq!(mul_add_floats(1.5, 4.0, 0.2), add_floats(6.0, 0.2));
+    }
+
+    #[test]
+    fn test_special_values() {
+        assert!(mul_add_doubles(f64::INFINITY, 0.0, 1.0).is_nan());
+        assert!(mul_add_doubles(f64::INFINITY, 1.0, f64::NEG_INFINITY).is_nan());
+        // The exact product is not rounded, so it cannot overflow on its own
+        assert_eq!(mul_add_doubles(f64::MAX, 2.0, -f64::MAX), f64::MAX);
+        assert_eq!(f64::MAX * 2.0 - f64::MAX, f64::INFINITY);
+        assert_eq!(mul_add_doubles(f64::MAX, 2.0, 0.0), f64::INFINITY);
+        assert_eq!(
+            mul_add_doubles(1.0, 1.0, f64::NEG_INFINITY),
+            f64::NEG_INFINITY
+        );
+        assert_eq!(
+            mul_add_doubles(-0.0, 1.0, -0.0).to_bits(),
+            (-0.0_f64).to_bits()
+        );
+        assert_eq!(mul_add_doubles(-0.0, 1.0, 0.0).to_bits(), 0);
+        assert_eq!(mul_add_doubles(2.0, 3.0, -6.0).to_bits(), 0);
+        assert_eq!(mul_add_doubles(0.0, 5.0, 2.5), 2.5);
+    }
+
+    // The host's fma (an instruction or a correctly rounded libm routine) and
+    // the integer implementation agree bit for bit, NaN payloads aside
+    #[test]
+    fn test_f64_matches_native_fma() {
+        let bits = bit_patterns(0x3c6e_f372_fe94_f82b, 60_000);
+        for triple in bits.chunks_exact(3) {
+            let (a, b) = (f64::from_bits(triple[0]), f64::from_bits(triple[1]));
+            let c = f64::from_bits(triple[2]);
+            // Also cancel the product, so the result is its rounding error
+            for c in [c, -(a * b), -(a * b).next_up()] {
+                assert!(
+                    same_f64(mul_add_doubles(a, b, c), a.mul_add(b, c)),
+                    "{:e} * {:e} + {:e}",
+                    a,
+                    b,
+                    c
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_f32_matches_native_fma() {
+        let bits = bit_patterns(0xa54f_f53a_5f1d_36f1, 60_000);
+        for triple in bits.chunks_exact(3) {
+            let (a, b) = (
+                f32::from_bits(triple[0] as u32),
+                f32::from_bits(triple[1] as u32),
+            );
+            let c = f32::from_bits(triple[2] as u32);
+            for c in [c, -(a * b)] {
+                assert!(
+                    same_f32(mul_add_floats(a, b, c), a.mul_add(b, c)),
+                    "{:e} * {:e} + {:e}",
+                    a,
+                    b,
+                    c
+                );
+            }
+        }
+    }
+
+    // Where the product is exact, fusing cannot change anything; everywhere
+    // else it may, which is why the two must not be mixed in one computation
+    #[test]
+    fn test_fused_and_separate_agree_exactly_when_product_is_exact() {
+        let bits = bit_patterns(0x510e_527f_ade6_82d1, 30_000);
+        let mut differing = 0;
+        for triple in bits.chunks_exact(3) {
+            // Moderate magnitudes, so neither form overflows or underflows
+            let value = |bits: u64| f64::from_bits(bits >> 12 | 0x3ff0_0000_0000_0000) - 1.5;
+            let (a, b, c) = (value(triple[0]), value(triple[1]), value(triple[2]));
+            let separate = add_doubles(multiply_doubles(a, b), c);
+            let fused = mul_add_doubles(a, b, c);
+            if mul_add_doubles(a, b, -multiply_doubles(a, b)) == 0.0 {
+                assert_eq!(fused.to_bits(), separate.to_bits());
+            } else if fused != separate {
+                differing += 1;
+            }
+        }
+        assert!(differing > 0);
+    }
+}
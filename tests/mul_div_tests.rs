@@ -0,0 +1,153 @@
+use solana_floats::bps::{Bps, split_exact};
+use solana_floats::error::FloatError;
+use solana_floats::mul_div::*;
+use solana_floats::quantize::RoundingMode;
+use solana_floats::u256::U256;
+
+#[cfg(test)]
+mod mul_div_tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_division() {
+        assert_eq!(mul_div(6, 7, 2, RoundingMode::Floor), Ok(21));
+        assert_eq!(mul_div(6, 7, 2, RoundingMode::Ceil), Ok(21));
+    }
+
+    #[test]
+    fn test_rounding_directions() {
+        // 10 * 1 / 4 = 2.5
+        assert_eq!(mul_div(10, 1, 4, RoundingMode::Floor), Ok(2));
+        assert_eq!(mul_div(10, 1, 4, RoundingMode::TowardZero), Ok(2));
+        assert_eq!(mul_div(10, 1, 4, RoundingMode::Ceil), Ok(3));
+        assert_eq!(mul_div(10, 1, 4, RoundingMode::HalfUp), Ok(3));
+        assert_eq!(mul_div(10, 1, 4, RoundingMode::HalfEven), Ok(2));
+        // 14 * 1 / 4 = 3.5 rounds to even 4
+        assert_eq!(mul_div(14, 1, 4, RoundingMode::HalfEven), Ok(4));
+    }
+
+    #[test]
+    fn test_intermediate_product_exceeds_u64() {
+        // u64::MAX * u64::MAX would overflow u64 but fits in u128
+        let result = mul_div(u64::MAX, u64::MAX, u64::MAX, RoundingMode::Floor);
+        assert_eq!(result, Ok(u64::MAX));
+    }
+
+    #[test]
+    fn test_result_overflow() {
+        let result = mul_div(u64::MAX, 2, 1, RoundingMode::Floor);
+        assert_eq!(result, Err(FloatError::Overflow));
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        assert_eq!(
+            mul_div(1, 1, 0, RoundingMode::Floor),
+            Err(FloatError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_u128_half_rounding_near_max_denominator() {
+        let d = u128::MAX;
+        // remainder = d - 1 is well above half
+        assert_eq!(mul_div_u128(d - 1, 1, d, RoundingMode::HalfUp), Ok(1));
+        assert_eq!(mul_div_u128(1, 1, d, RoundingMode::HalfUp), Ok(0));
+        assert_eq!(
+            mul_div_u128(u128::MAX, 2, 3, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    // A fee schedule computed at compile time
+    const TRADE_SIZE: u64 = 1_000_000;
+    const PROTOCOL_FEE: u64 = match Bps(30).of_ceil(TRADE_SIZE) {
+        Ok(fee) => fee,
+        Err(_) => panic!("fee overflow"),
+    };
+    const REBATE: u64 = match mul_div(PROTOCOL_FEE, 1, 3, RoundingMode::Floor) {
+        Ok(rebate) => rebate,
+        Err(_) => panic!("division by zero"),
+    };
+
+    #[test]
+    fn test_const_fee_schedule() {
+        assert_eq!(PROTOCOL_FEE, 3_000);
+        assert_eq!(REBATE, 1_000);
+    }
+
+    #[test]
+    fn test_bps_rounding_directions() {
+        // 30 bps of 1_001 is 3.003
+        assert_eq!(Bps(30).of_floor(1_001), Ok(3));
+        assert_eq!(Bps(30).of_ceil(1_001), Ok(4));
+        assert_eq!(Bps(10_000).of_floor(u64::MAX), Ok(u64::MAX));
+        assert_eq!(Bps(20_000).of_floor(u64::MAX), Err(FloatError::Overflow));
+    }
+
+    #[test]
+    fn test_split_exact_hands_out_every_unit() {
+        // 1_001 split 1/3-ish three ways: each floors to 333, two units remain
+        assert_eq!(
+            split_exact(1_001, &[3_333, 3_333, 3_334]),
+            Ok(vec![334, 333, 334])
+        );
+        // Protocol 20%, LPs 75%, referrer 5%
+        let parts = split_exact(999_999, &[2_000, 7_500, 500]).unwrap();
+        assert_eq!(parts, vec![200_000, 749_999, 50_000]);
+        assert_eq!(
+            split_exact(u64::MAX, &[5_000, 5_000])
+                .unwrap()
+                .iter()
+                .map(|&p| p as u128)
+                .sum::<u128>(),
+            u64::MAX as u128
+        );
+        assert_eq!(split_exact(7, &[0, 10_000, 0]), Ok(vec![0, 7, 0]));
+        assert_eq!(split_exact(3, &[2_500; 4]), Ok(vec![1, 1, 1, 0]));
+    }
+
+    #[test]
+    fn test_split_exact_requires_full_shares() {
+        assert_eq!(split_exact(100, &[]), Err(FloatError::OutOfRange));
+        assert_eq!(
+            split_exact(100, &[5_000, 4_999]),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            split_exact(100, &[u16::MAX, 10_000]),
+            Err(FloatError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_u256_rounding_and_bounds() {
+        let big = U256::MAX;
+        // MAX * 3 / 6 = MAX / 2, with a remainder of exactly half
+        let half = U256::MAX >> 1;
+        let three = U256::from(3u64);
+        let six = U256::from(6u64);
+        assert_eq!(mul_div_u256(big, three, six, RoundingMode::Floor), Ok(half));
+        assert_eq!(
+            mul_div_u256(big, three, six, RoundingMode::HalfUp),
+            Ok(half.checked_add(U256::ONE).unwrap())
+        );
+        // The quotient MAX >> 1 is odd, so half-even also rounds up
+        assert_eq!(
+            mul_div_u256(big, three, six, RoundingMode::HalfEven),
+            Ok(half.checked_add(U256::ONE).unwrap())
+        );
+        assert_eq!(
+            mul_div_u256(big, big, big, RoundingMode::Ceil),
+            Ok(U256::MAX)
+        );
+        assert_eq!(
+            mul_div_u256(big, three, U256::from(2u64), RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            mul_div_u256(big, big, U256::ZERO, RoundingMode::Floor),
+            Err(FloatError::DivisionByZero)
+        );
+    }
+}
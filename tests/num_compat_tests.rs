@@ -0,0 +1,78 @@
+//! `num-traits` impls of the checked wrappers. Run with
+//!
+//! ```text
+//! cargo test --features num-traits --test num_compat_tests
+//! ```
+#![cfg(feature = "num-traits")]
+
+use num_traits::{Bounded, FromPrimitive, NumCast, ToPrimitive};
+use solana_floats::safe_f64::SafeF64;
+use solana_floats::tracked::TrackedF64;
+
+#[cfg(test)]
+mod num_compat_tests {
+    use super::*;
+
+    // Generic code written against num-traits, instantiated below with the
+    // crate's wrappers.
+    fn mean<T: NumCast + ToPrimitive>(values: &[T]) -> Option<T> {
+        let sum: f64 = values.iter().map(|v| v.to_f64()).sum::<Option<f64>>()?;
+        T::from(sum / values.len() as f64)
+    }
+
+    #[test]
+    fn test_generic_code_accepts_safe_f64() {
+        let values: Vec<SafeF64> = [1.0, 2.0, 4.5]
+            .iter()
+            .map(|&v| SafeF64::new(v).unwrap())
+            .collect();
+        assert_eq!(mean(&values).map(SafeF64::get), Some(2.5));
+    }
+
+    #[test]
+    fn test_generic_code_accepts_tracked_f64() {
+        let values = [
+            TrackedF64::exact(2.0).unwrap(),
+            TrackedF64::exact(3.0).unwrap(),
+        ];
+        assert_eq!(mean(&values).map(|t| t.value()), Some(2.5));
+    }
+
+    #[test]
+    fn test_non_finite_sources_do_not_convert() {
+        assert!(<SafeF64 as NumCast>::from(f64::NAN).is_none());
+        assert!(<SafeF64 as NumCast>::from(f64::INFINITY).is_none());
+        assert!(SafeF64::from_f64(f64::NEG_INFINITY).is_none());
+        assert!(TrackedF64::from_f64(f64::NAN).is_none());
+        assert!(<TrackedF64 as NumCast>::from(f32::INFINITY).is_none());
+        assert!(mean(&[SafeF64::MAX, SafeF64::MAX]).is_none());
+    }
+
+    #[test]
+    fn test_bounds_and_integer_conversions() {
+        assert_eq!(SafeF64::max_value().get(), f64::MAX);
+        assert_eq!(SafeF64::min_value().get(), f64::MIN);
+        assert_eq!(SafeF64::new(-2.75).unwrap().to_i64(), Some(-2));
+        assert_eq!(SafeF64::new(-2.75).unwrap().to_u64(), None);
+        assert_eq!(SafeF64::max_value().to_u128(), None);
+        assert_eq!(
+            SafeF64::from_u64(u64::MAX).map(SafeF64::get),
+            Some(u64::MAX as f64)
+        );
+    }
+
+    #[test]
+    fn test_tracked_from_integer_records_rounding() {
+        let exact = TrackedF64::from_u64(1 << 53).unwrap();
+        assert_eq!(exact.error_bound(), 0.0);
+
+        // 2^53 + 1 rounds to 2^53.
+        let rounded = TrackedF64::from_u64((1 << 53) + 1).unwrap();
+        assert_eq!(rounded.value(), 9_007_199_254_740_992.0);
+        assert_eq!(rounded.error_bound(), 1.0);
+
+        let negative = TrackedF64::from_i64(-(1 << 53) - 1).unwrap();
+        assert_eq!(negative.error_bound(), 1.0);
+        assert_eq!(TrackedF64::from_i64(i64::MIN).unwrap().error_bound(), 0.0);
+    }
+}
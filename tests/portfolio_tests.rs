@@ -0,0 +1,120 @@
+use solana_floats::bps::Bps;
+use solana_floats::error::FloatError;
+use solana_floats::portfolio::*;
+
+#[cfg(test)]
+mod portfolio_tests {
+    use super::*;
+
+    fn holding(amount: f64, price: f64, target_weight: f64, decimals: u32) -> Holding {
+        Holding {
+            amount,
+            price,
+            target_weight,
+            decimals,
+        }
+    }
+
+    fn portfolio() -> [Holding; 3] {
+        [
+            // SOL worth 1_500, USDC worth 1_000, ETH worth 1_500
+            holding(10.0, 150.0, 2.0, 9),
+            holding(1_000.0, 1.0, 1.0, 6),
+            holding(0.5, 3_000.0, 1.0, 8),
+        ]
+    }
+
+    #[test]
+    fn test_trades_toward_targets_without_overshooting() {
+        let trades = rebalance(&portfolio(), Bps(100)).unwrap();
+        // Buy 500 worth of SOL, sell 500 worth of ETH, leave USDC at 25%
+        assert_eq!(trades, vec![3.333_333_333, 0.0, -0.166_666_66]);
+    }
+
+    #[test]
+    fn test_no_trade_band() {
+        let mut holdings = portfolio();
+        // SOL at 1_950 of 4_450 is 43.8% against a 50% target
+        holdings[0].amount = 13.0;
+        let trades = rebalance(&holdings, Bps(1_000)).unwrap();
+        assert_eq!(trades, vec![0.0, 0.0, 0.0]);
+        let trades = rebalance(&holdings, Bps(500)).unwrap();
+        assert!(trades[0] > 0.0);
+        // An empty portfolio has nothing to trade
+        let empty = [holding(0.0, 150.0, 1.0, 9), holding(0.0, 1.0, 1.0, 6)];
+        assert_eq!(rebalance(&empty, Bps(0)), Ok(vec![0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_rejects_bad_holdings() {
+        assert_eq!(rebalance(&[], Bps(0)), Err(FloatError::OutOfRange));
+        let mut holdings = portfolio();
+        holdings[1].price = f64::NAN;
+        assert_eq!(rebalance(&holdings, Bps(0)), Err(FloatError::NaNOperand));
+        holdings[1].price = -1.0;
+        assert_eq!(rebalance(&holdings, Bps(0)), Err(FloatError::OutOfRange));
+        holdings[1].price = 0.0;
+        assert_eq!(
+            rebalance(&holdings, Bps(0)),
+            Err(FloatError::DivisionByZero)
+        );
+        let mut holdings = portfolio();
+        holdings[2].decimals = 20;
+        assert_eq!(rebalance(&holdings, Bps(0)), Err(FloatError::OutOfRange));
+        let unweighted = portfolio().map(|holding| Holding {
+            target_weight: 0.0,
+            ..holding
+        });
+        assert_eq!(
+            rebalance(&unweighted, Bps(0)),
+            Err(FloatError::DivisionByZero)
+        );
+        let mut holdings = portfolio();
+        holdings[0].amount = f64::MAX;
+        assert_eq!(rebalance(&holdings, Bps(0)), Err(FloatError::Overflow));
+    }
+
+    fn quote(price: f64, conf: f64) -> PriceWithConf {
+        PriceWithConf::new(price, conf).unwrap()
+    }
+
+    #[test]
+    fn test_portfolio_value_adds_values_and_confidences() {
+        let positions = [
+            (12_345_678_901, 9, quote(150.25, 0.12)),
+            (2_500_000_000, 6, quote(1.0001, 0.0002)),
+            (87_654_321, 8, quote(3_012.5, 1.75)),
+        ];
+        // Exact dot products of the f64 amounts, rounded once (Python fractions)
+        let value = portfolio_value(&positions).unwrap();
+        assert_eq!(value.price, 6995.77467500025);
+        assert_eq!(value.conf, 3.51543208562);
+        assert!(value.lower() < value.price && value.price < value.upper());
+        assert_eq!(portfolio_value(&[]), Ok(quote(0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_portfolio_value_rejects_bad_quotes() {
+        assert_eq!(PriceWithConf::new(1.0, -0.1), Err(FloatError::OutOfRange));
+        assert_eq!(
+            PriceWithConf::new(f64::NAN, 0.1),
+            Err(FloatError::NaNOperand)
+        );
+        let unchecked = PriceWithConf {
+            price: 1.0,
+            conf: f64::INFINITY,
+        };
+        assert_eq!(
+            portfolio_value(&[(1, 0, unchecked)]),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            portfolio_value(&[(1, 20, quote(1.0, 0.0))]),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            portfolio_value(&[(u64::MAX, 0, quote(f64::MAX, 0.0))]),
+            Err(FloatError::Overflow)
+        );
+    }
+}
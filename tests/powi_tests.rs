@@ -0,0 +1,113 @@
+use solana_floats::compare::{ulp_distance_f32, ulp_distance_f64};
+use solana_floats::double_ops::powi_doubles;
+use solana_floats::error::FloatError;
+use solana_floats::float_ops::powi_floats;
+use solana_floats::transcendental::{POW_MAX_ULPS, pow_deterministic};
+
+mod common;
+
+#[cfg(test)]
+mod powi_tests {
+    use super::*;
+    use common::uniform;
+
+    #[test]
+    fn test_exact_powers() {
+        let mut expected = 1.0;
+        for n in 0..=22 {
+            assert_eq!(powi_doubles(10.0, n), Ok(expected));
+            expected *= 10.0;
+        }
+        assert_eq!(powi_doubles(2.0, 1023), Ok(f64::MAX / (2.0 - f64::EPSILON)));
+        assert_eq!(powi_doubles(2.0, -1022), Ok(f64::MIN_POSITIVE));
+        assert_eq!(powi_doubles(-3.0, 3), Ok(-27.0));
+        assert_eq!(powi_doubles(-3.0, 4), Ok(81.0));
+        assert_eq!(powi_doubles(0.5, -3), Ok(8.0));
+        assert_eq!(powi_doubles(1.1, 2), Ok(1.1 * 1.1));
+        assert_eq!(powi_floats(1.5, 2), Ok(2.25));
+        assert_eq!(powi_floats(10.0, -1), Ok(0.1));
+    }
+
+    #[test]
+    fn test_zero_exponent_and_zero_base() {
+        assert_eq!(powi_doubles(0.0, 0), Ok(1.0));
+        assert_eq!(powi_doubles(f64::MAX, 0), Ok(1.0));
+        assert_eq!(powi_doubles(0.0, 5), Ok(0.0));
+        assert_eq!(
+            powi_doubles(-0.0, 3).unwrap().to_bits(),
+            (-0.0_f64).to_bits()
+        );
+        assert_eq!(powi_doubles(0.0, -1), Err(FloatError::DivisionByZero));
+        assert_eq!(powi_floats(-0.0, -2), Err(FloatError::DivisionByZero));
+    }
+
+    #[test]
+    fn test_overflow_detected() {
+        assert_eq!(powi_doubles(2.0, 1024), Err(FloatError::Overflow));
+        assert_eq!(powi_doubles(10.0, 309), Err(FloatError::Overflow));
+        assert_eq!(powi_doubles(-10.0, 309), Err(FloatError::Overflow));
+        // The positive power underflows, so its reciprocal is too large
+        assert_eq!(powi_doubles(1e-200, -2), Err(FloatError::Overflow));
+        assert_eq!(powi_floats(10.0, 39), Err(FloatError::Overflow));
+        assert_eq!(powi_floats(2.0, 128), Err(FloatError::Overflow));
+        // Large in f64 is fine until it no longer fits f32
+        assert_eq!(powi_floats(2.0, 127), Ok(2_f32.powi(127)));
+        // Squaring past the last exponent bit must not report an overflow
+        assert_eq!(powi_doubles(1e200, 1), Ok(1e200));
+        // Underflow is not an error
+        assert_eq!(powi_doubles(10.0, -400), Ok(0.0));
+        assert_eq!(powi_doubles(0.5, 1100), Ok(0.0));
+    }
+
+    #[test]
+    fn test_non_finite_base() {
+        assert_eq!(powi_doubles(f64::NAN, 0), Err(FloatError::NaNOperand));
+        assert_eq!(powi_doubles(f64::INFINITY, 2), Err(FloatError::NaNOperand));
+        assert_eq!(
+            powi_floats(f32::NEG_INFINITY, -1),
+            Err(FloatError::NaNOperand)
+        );
+    }
+
+    #[test]
+    fn test_error_grows_with_exponent_only() {
+        let bases = uniform(0.5, 2.0, 100_000);
+        let exponents = uniform(-1000.0, 1000.0, 100_000);
+        for (base, exponent) in bases.zip(exponents) {
+            let exponent = exponent as i32;
+            let expected = pow_deterministic(base, exponent as f64);
+            if let Ok(actual) = powi_doubles(base, exponent)
+                && expected.is_normal()
+            {
+                let bound = exponent.unsigned_abs() as u64 + POW_MAX_ULPS + 1;
+                let distance = ulp_distance_f64(actual, expected).unwrap();
+                assert!(distance <= bound, "{:e}^{}", base, exponent);
+            }
+        }
+    }
+
+    #[test]
+    fn test_floats_within_one_ulp() {
+        let bases = uniform(0.5, 2.0, 100_000);
+        let exponents = uniform(-120.0, 120.0, 100_000);
+        for (base, exponent) in bases.zip(exponents) {
+            let (base, exponent) = (base as f32, exponent as i32);
+            let expected = pow_deterministic(base as f64, exponent as f64) as f32;
+            if let Ok(actual) = powi_floats(base, exponent)
+                && expected.is_normal()
+            {
+                assert!(ulp_distance_f32(actual, expected).unwrap() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_daily_compounding() {
+        // 5% a year compounded daily, against the closed form
+        let daily = 1.0 + 0.05 / 365.0;
+        let growth = powi_doubles(daily, 365).unwrap();
+        let expected = pow_deterministic(daily, 365.0);
+        assert!((growth - expected).abs() <= 365.0 * f64::EPSILON * expected);
+        assert!((growth - 1.051_267_496_467_462).abs() < 1e-12);
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::excessive_precision, clippy::useless_vec)]
+
 use solana_floats::float_ops::*;
 use solana_floats::double_ops::*;
 
@@ -1,3 +1,6 @@
+#![allow(clippy::approx_constant, clippy::excessive_precision)]
+
+use solana_floats::quantize::quantize_to_scale;
 use solana_program::msg;
 
 #[cfg(test)]
@@ -34,9 +37,9 @@ mod precision_stability_tests {
         msg!("Differences are at level: ~10^{:.0}", diff_12.log10());
         
         // Now truncate to 10^-12 precision
-        let truncated1 = (result1 * 1e12).round() / 1e12;
-        let truncated2 = (result2 * 1e12).round() / 1e12;
-        let truncated3 = (result3 * 1e12).round() / 1e12;
+        let truncated1 = quantize_to_scale(result1, 1_000_000_000_000).unwrap();
+        let truncated2 = quantize_to_scale(result2, 1_000_000_000_000).unwrap();
+        let truncated3 = quantize_to_scale(result3, 1_000_000_000_000).unwrap();
         
         msg!("Truncated to 10^-12:");
         msg!("  truncated1: {:.12}", truncated1);
@@ -123,9 +126,9 @@ mod precision_stability_tests {
         msg!("  diff_13: {:.2e}", diff_13);
         
         // Truncate to 10^-12 precision as suggested by M0
-        let stable1 = (result1 * 1e12).round() / 1e12;
-        let stable2 = (result2 * 1e12).round() / 1e12;
-        let stable3 = (result3 * 1e12).round() / 1e12;
+        let stable1 = quantize_to_scale(result1, 1_000_000_000_000).unwrap();
+        let stable2 = quantize_to_scale(result2, 1_000_000_000_000).unwrap();
+        let stable3 = quantize_to_scale(result3, 1_000_000_000_000).unwrap();
         
         msg!("Stabilized to 10^-12:");
         msg!("  stable1: {:.12}", stable1);
@@ -0,0 +1,39 @@
+use solana_floats::prelude::*;
+
+#[cfg(test)]
+mod prelude_tests {
+    use super::*;
+
+    #[test]
+    fn test_prelude_covers_a_typical_flow() {
+        let price = SafeF64::new(0.1)
+            .unwrap()
+            .checked_add(SafeF64::new(0.2).unwrap());
+        let price = price.unwrap().get();
+        let policy = PrecisionPolicy::builder()
+            .decimals(2)
+            .rounding(RoundingMode::HalfEven)
+            .build()
+            .unwrap();
+        approx_assert_eq!(
+            policy.quantize(price).unwrap(),
+            0.3,
+            Tolerance::new(0.0, 0.0).unwrap()
+        );
+        assert_within_bps!(price, 0.3, Bps(1));
+
+        let shares = shares_for_deposit(1_000, 0, 0).unwrap();
+        assert_eq!(assets_for_shares(shares, 1_000, shares), Ok(1_000));
+        assert_eq!(
+            format_fixed(price, 2, RoundingMode::Floor).as_deref(),
+            Ok("0.30")
+        );
+        assert_eq!(mul_div(7, 3, 2, RoundingMode::Ceil), Ok(11));
+        assert_eq!(
+            TrackedF64::exact(1.0)
+                .unwrap()
+                .apply(BinaryOp::Divide, TrackedF64::exact(0.0).unwrap()),
+            Err(FloatError::DivisionByZero)
+        );
+    }
+}
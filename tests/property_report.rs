@@ -0,0 +1,267 @@
+//! Measures how far the crate's f64 ops stray from the algebraic laws of real
+//! arithmetic, and writes the result as JSON. Run with
+//!
+//! ```text
+//! cargo test --release --features property-report --test property_report -- --nocapture
+//! ```
+//!
+//! Each law is checked over several fixed-seed corpora, and for each pair the
+//! report gives how often the two evaluation orders disagree, by how many ulps
+//! at most and on average, and the worst triple found. The report goes to
+//! `target/property-report.json`, or to `$PROPERTY_REPORT_PATH` if set, and
+//! is the same on every run and every correctly rounding target, so it can be
+//! cited when choosing the evaluation order of a critical formula.
+#![cfg(feature = "property-report")]
+
+use solana_floats::compare::ulp_distance_f64;
+use solana_floats::double_ops::{add_doubles, multiply_doubles};
+
+mod common;
+
+use common::xorshift_star;
+
+const TRIALS: usize = 1_000_000;
+
+/// Random operands drawn from the shared xorshift64* stream.
+struct Rng<I>(I);
+
+impl<I: Iterator<Item = u64>> Rng<I> {
+    fn next(&mut self) -> u64 {
+        self.0.next().unwrap()
+    }
+
+    /// A random significand with a binary exponent in `min_exp..=max_exp`,
+    /// negated half of the time if `signed`.
+    fn f64_in(&mut self, min_exp: i64, max_exp: i64, signed: bool) -> f64 {
+        let r = self.next();
+        let span = (max_exp - min_exp + 1) as u64;
+        let exponent = (1023 + min_exp + (r % span) as i64) as u64;
+        let sign = if signed { self.next() >> 63 } else { 0 };
+        f64::from_bits(sign << 63 | exponent << 52 | (self.next() >> 12))
+    }
+}
+
+struct Corpus {
+    name: &'static str,
+    description: &'static str,
+    seed: u64,
+    min_exp: i64,
+    max_exp: i64,
+    signed: bool,
+}
+
+// Exponents stay far enough from the limits that no product of three
+// overflows or goes subnormal, so every ulp distance below is finite.
+const CORPORA: [Corpus; 4] = [
+    Corpus {
+        name: "unit",
+        description: "positive values in [1/16, 32)",
+        seed: 0x9e37_79b9_7f4a_7c15,
+        min_exp: -4,
+        max_exp: 4,
+        signed: false,
+    },
+    Corpus {
+        name: "prices",
+        description: "positive values in [2^-20, 2^21), amounts and prices at mixed scales",
+        seed: 0xd1b5_4a32_d192_ed03,
+        min_exp: -20,
+        max_exp: 20,
+        signed: false,
+    },
+    Corpus {
+        name: "signed",
+        description: "values of either sign in [1/16, 32) in magnitude, so sums cancel",
+        seed: 0x8cb9_2ba7_2f3d_8dd7,
+        min_exp: -4,
+        max_exp: 4,
+        signed: true,
+    },
+    Corpus {
+        name: "wide",
+        description: "values of either sign in [2^-200, 2^201) in magnitude",
+        seed: 0xa076_1d64_78bd_642f,
+        min_exp: -200,
+        max_exp: 200,
+        signed: true,
+    },
+];
+
+struct Property {
+    name: &'static str,
+    left: &'static str,
+    right: &'static str,
+    eval: fn(f64, f64, f64) -> (f64, f64),
+}
+
+const PROPERTIES: [Property; 3] = [
+    Property {
+        name: "add_associativity",
+        left: "(a + b) + c",
+        right: "a + (b + c)",
+        eval: |a, b, c| {
+            (
+                add_doubles(add_doubles(a, b), c),
+                add_doubles(a, add_doubles(b, c)),
+            )
+        },
+    },
+    Property {
+        name: "mul_associativity",
+        left: "(a * b) * c",
+        right: "a * (b * c)",
+        eval: |a, b, c| {
+            (
+                multiply_doubles(multiply_doubles(a, b), c),
+                multiply_doubles(a, multiply_doubles(b, c)),
+            )
+        },
+    },
+    Property {
+        name: "distributivity",
+        left: "a * (b + c)",
+        right: "a * b + a * c",
+        eval: |a, b, c| {
+            (
+                multiply_doubles(a, add_doubles(b, c)),
+                add_doubles(multiply_doubles(a, b), multiply_doubles(a, c)),
+            )
+        },
+    },
+];
+
+struct Measurement {
+    corpus: &'static str,
+    property: &'static str,
+    violations: usize,
+    max_ulps: u64,
+    total_ulps: u128,
+    // (a, b, c, left, right) of the largest distance
+    worst: Option<(f64, f64, f64, f64, f64)>,
+}
+
+fn measure(corpus: &Corpus, property: &Property) -> Measurement {
+    let mut rng = Rng(xorshift_star(corpus.seed));
+    let mut measurement = Measurement {
+        corpus: corpus.name,
+        property: property.name,
+        violations: 0,
+        max_ulps: 0,
+        total_ulps: 0,
+        worst: None,
+    };
+    for _ in 0..TRIALS {
+        let a = rng.f64_in(corpus.min_exp, corpus.max_exp, corpus.signed);
+        let b = rng.f64_in(corpus.min_exp, corpus.max_exp, corpus.signed);
+        let c = rng.f64_in(corpus.min_exp, corpus.max_exp, corpus.signed);
+        let (left, right) = (property.eval)(a, b, c);
+        assert!(left.is_finite() && right.is_finite());
+        let ulps = ulp_distance_f64(left, right).unwrap();
+        if ulps == 0 {
+            continue;
+        }
+        measurement.violations += 1;
+        measurement.total_ulps += ulps as u128;
+        if ulps > measurement.max_ulps {
+            measurement.max_ulps = ulps;
+            measurement.worst = Some((a, b, c, left, right));
+        }
+    }
+    measurement
+}
+
+// Rust's `{:?}` for finite f64 is the shortest round-tripping decimal, which
+// is also a valid JSON number.
+fn json_f64(v: f64) -> String {
+    format!(
+        "{{\"value\": {:?}, \"bits\": \"{:#018x}\"}}",
+        v,
+        v.to_bits()
+    )
+}
+
+fn to_json(measurements: &[Measurement]) -> String {
+    let mut out = String::from("{\n");
+    out += &format!("  \"trials_per_corpus\": {},\n", TRIALS);
+    out += "  \"corpora\": [\n";
+    for (i, corpus) in CORPORA.iter().enumerate() {
+        out += &format!(
+            "    {{\"name\": \"{}\", \"description\": \"{}\", \"seed\": \"{:#018x}\"}}{}\n",
+            corpus.name,
+            corpus.description,
+            corpus.seed,
+            if i + 1 < CORPORA.len() { "," } else { "" }
+        );
+    }
+    out += "  ],\n  \"results\": [\n";
+    for (i, m) in measurements.iter().enumerate() {
+        let property = PROPERTIES.iter().find(|p| p.name == m.property).unwrap();
+        let mean = if m.violations == 0 {
+            0.0
+        } else {
+            m.total_ulps as f64 / m.violations as f64
+        };
+        out += "    {\n";
+        out += &format!("      \"corpus\": \"{}\",\n", m.corpus);
+        out += &format!("      \"property\": \"{}\",\n", m.property);
+        out += &format!("      \"left\": \"{}\",\n", property.left);
+        out += &format!("      \"right\": \"{}\",\n", property.right);
+        out += &format!("      \"violations\": {},\n", m.violations);
+        out += &format!(
+            "      \"violation_rate\": {:?},\n",
+            m.violations as f64 / TRIALS as f64
+        );
+        out += &format!("      \"max_ulps\": {},\n", m.max_ulps);
+        out += &format!("      \"mean_ulps_when_violated\": {:?},\n", mean);
+        match m.worst {
+            Some((a, b, c, left, right)) => {
+                out += "      \"worst\": {\n";
+                out += &format!("        \"a\": {},\n", json_f64(a));
+                out += &format!("        \"b\": {},\n", json_f64(b));
+                out += &format!("        \"c\": {},\n", json_f64(c));
+                out += &format!("        \"left\": {},\n", json_f64(left));
+                out += &format!("        \"right\": {}\n", json_f64(right));
+                out += "      }\n";
+            }
+            None => out += "      \"worst\": null\n",
+        }
+        out += &format!(
+            "    }}{}\n",
+            if i + 1 < measurements.len() { "," } else { "" }
+        );
+    }
+    out += "  ]\n}\n";
+    out
+}
+
+#[test]
+fn algebraic_property_report() {
+    let measurements: Vec<Measurement> = CORPORA
+        .iter()
+        .flat_map(|corpus| {
+            PROPERTIES
+                .iter()
+                .map(move |property| measure(corpus, property))
+        })
+        .collect();
+
+    for m in &measurements {
+        println!(
+            "{:>7} {:<18} {:>7} of {} differ, max {} ulps",
+            m.corpus, m.property, m.violations, TRIALS, m.max_ulps
+        );
+        // With no overflow or underflow each product is within a factor
+        // (1 + 2^-53)^2 of the exact one, so the two orders are within 4 ulps
+        if m.property == "mul_associativity" {
+            assert!(m.max_ulps <= 4, "{}: {} ulps", m.corpus, m.max_ulps);
+        }
+    }
+
+    let path = std::env::var("PROPERTY_REPORT_PATH")
+        .unwrap_or_else(|_| format!("{}/target/property-report.json", env!("CARGO_MANIFEST_DIR")));
+    if let Some(parent) = std::path::Path::new(&path).parent() {
+        std::fs::create_dir_all(parent).unwrap();
+    }
+    std::fs::write(&path, to_json(&measurements)).unwrap();
+    println!("report written to {}", path);
+}
@@ -0,0 +1,370 @@
+use solana_floats::error::FloatError;
+use solana_floats::quantize::*;
+
+#[cfg(test)]
+mod quantize_tests {
+    use super::*;
+
+    #[test]
+    fn test_rounding_modes_on_ties() {
+        assert_eq!(RoundingMode::HalfUp.round(2.5), 3.0);
+        assert_eq!(RoundingMode::HalfEven.round(2.5), 2.0);
+        assert_eq!(RoundingMode::HalfEven.round(3.5), 4.0);
+        assert_eq!(RoundingMode::HalfUp.round(-2.5), -3.0);
+        assert_eq!(RoundingMode::HalfEven.round(-2.5), -2.0);
+        assert_eq!(RoundingMode::Floor.round(-2.5), -3.0);
+        assert_eq!(RoundingMode::Ceil.round(-2.5), -2.0);
+        assert_eq!(RoundingMode::TowardZero.round(-2.5), -2.0);
+    }
+
+    #[test]
+    fn test_quantize_matches_manual_truncation() {
+        let value = 1.05_f64.powf(365.25);
+        let manual = (value * 1e12).round() / 1e12;
+        let quantized = quantize(value, 12, RoundingMode::HalfUp).unwrap();
+        assert_eq!(quantized.to_bits(), manual.to_bits());
+    }
+
+    #[test]
+    fn test_quantize_cents() {
+        assert_eq!(quantize(123.456, 2, RoundingMode::Floor), Ok(123.45));
+        assert_eq!(quantize(123.451, 2, RoundingMode::Ceil), Ok(123.46));
+    }
+
+    #[test]
+    fn test_quantize_large_magnitude_is_unchanged() {
+        // value * 1e12 exceeds 2^53, where the multiply-by-scale trick would
+        // otherwise round away integer digits
+        let value = 123_456_789.123_456_78_f64;
+        assert_eq!(quantize(value, 12, RoundingMode::HalfUp), Ok(value));
+    }
+
+    #[test]
+    fn test_quantize_rejects_non_finite_and_overflow() {
+        assert_eq!(
+            quantize(f64::NAN, 2, RoundingMode::Floor),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            quantize(f64::INFINITY, 2, RoundingMode::Floor),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            quantize(f64::MAX, 2, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_pow10_range() {
+        assert_eq!(pow10(0), Ok(1));
+        assert_eq!(pow10(9), Ok(1_000_000_000));
+        assert_eq!(pow10(MAX_DECIMALS), Ok(10_000_000_000_000_000_000));
+        assert_eq!(pow10(MAX_DECIMALS + 1), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_quantize_slice_matches_scalar_calls() {
+        let mut values = [1.23456, -9.87654, 0.005, 1e20, 0.0];
+        let expected: Vec<f64> = values
+            .iter()
+            .map(|&value| quantize(value, 2, RoundingMode::HalfEven).unwrap())
+            .collect();
+
+        quantize_slice(&mut values, 2, RoundingMode::HalfEven).unwrap();
+        assert_eq!(values.to_vec(), expected);
+    }
+
+    #[test]
+    fn test_quantize_slice_is_all_or_nothing() {
+        let mut values = [1.234, 5.678, f64::NAN];
+        assert_eq!(
+            quantize_slice(&mut values, 1, RoundingMode::Floor),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(values[0], 1.234);
+        assert_eq!(values[1], 5.678);
+
+        let mut values = [1.234, f64::MAX];
+        assert_eq!(
+            quantize_slice(&mut values, 2, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(values[0], 1.234);
+    }
+
+    #[test]
+    fn test_rounding_mode_from_byte() {
+        assert_eq!(RoundingMode::try_from(0), Ok(RoundingMode::Floor));
+        assert_eq!(RoundingMode::try_from(4), Ok(RoundingMode::HalfEven));
+        assert_eq!(RoundingMode::try_from(5), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_precision_policy_builder() {
+        let policy = PrecisionPolicy::builder()
+            .decimals(6)
+            .rounding(RoundingMode::Floor)
+            .build()
+            .unwrap();
+        assert_eq!(
+            policy,
+            PrecisionPolicy::new(6, RoundingMode::Floor).unwrap()
+        );
+        assert_eq!(policy.decimals(), 6);
+        assert_eq!(policy.mode(), RoundingMode::Floor);
+        assert_eq!(policy.quantize(1.2345678), Ok(1.234567));
+
+        let mut values = [0.125, 0.375];
+        let half_even = PrecisionPolicy::new(2, RoundingMode::HalfEven).unwrap();
+        half_even.quantize_slice(&mut values).unwrap();
+        assert_eq!(values, [0.12, 0.38]);
+    }
+
+    #[test]
+    fn test_precision_policy_builder_requires_every_setting() {
+        let missing_mode = PrecisionPolicy::builder().decimals(2).build();
+        assert_eq!(missing_mode, Err(FloatError::OutOfRange));
+        let missing_decimals = PrecisionPolicy::builder()
+            .rounding(RoundingMode::Ceil)
+            .build();
+        assert_eq!(missing_decimals, Err(FloatError::OutOfRange));
+        let too_many_decimals = PrecisionPolicy::builder()
+            .decimals(MAX_DECIMALS + 1)
+            .rounding(RoundingMode::Ceil)
+            .build();
+        assert_eq!(too_many_decimals, Err(FloatError::OutOfRange));
+    }
+
+    // Evaluated at compile time
+    const USDC_SCALE: u64 = match pow10(6) {
+        Ok(scale) => scale,
+        Err(_) => panic!("bad decimals"),
+    };
+    const ONE_SOL_IN_18_DECIMALS: u64 = match rescale(1_000_000_000, 9, 18) {
+        Ok(amount) => amount,
+        Err(_) => panic!("overflow"),
+    };
+
+    #[test]
+    fn test_const_scaling() {
+        assert_eq!(USDC_SCALE, 1_000_000);
+        assert_eq!(ONE_SOL_IN_18_DECIMALS, 1_000_000_000_000_000_000);
+    }
+
+    #[test]
+    fn test_rescale_is_exact_or_fails() {
+        assert_eq!(rescale(1_500_000, 6, 9), Ok(1_500_000_000));
+        assert_eq!(rescale(1_500_000_000, 9, 6), Ok(1_500_000));
+        assert_eq!(rescale(1_500_000_001, 9, 6), Err(FloatError::PrecisionLoss));
+        assert_eq!(rescale(42, 3, 3), Ok(42));
+        assert_eq!(rescale(u64::MAX, 0, 1), Err(FloatError::Overflow));
+        assert_eq!(rescale(1, 0, MAX_DECIMALS + 1), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_rescale_balance_rounds_and_reports_dust() {
+        // 1.234567891 SOL-like balance moved from 9 to 6 decimals
+        let raw = 1_234_567_891;
+        let floor = rescale_balance_with_dust(raw, 9, 6, RoundingMode::Floor).unwrap();
+        assert_eq!(
+            floor,
+            RescaledBalance {
+                balance: 1_234_567,
+                dust: 891
+            }
+        );
+        let ceil = rescale_balance_with_dust(raw, 9, 6, RoundingMode::Ceil).unwrap();
+        assert_eq!(
+            ceil,
+            RescaledBalance {
+                balance: 1_234_568,
+                dust: -109
+            }
+        );
+        assert_eq!(rescale_balance(1_500, 3, 0, RoundingMode::HalfEven), Ok(2));
+        assert_eq!(rescale_balance(2_500, 3, 0, RoundingMode::HalfEven), Ok(2));
+        assert_eq!(rescale_balance(2_500, 3, 0, RoundingMode::HalfUp), Ok(3));
+        assert_eq!(rescale_balance(u64::MAX, 19, 0, RoundingMode::Ceil), Ok(2));
+    }
+
+    #[test]
+    fn test_rescale_balance_dust_reconciles_supply() {
+        let balances = [1_000_000_499u64, 7, 999, 123_456_789_012, 500, 1_500];
+        let mut supply = 0u64;
+        let mut dust = 0i128;
+        for raw in balances {
+            let rescaled = rescale_balance_with_dust(raw, 9, 6, RoundingMode::HalfEven).unwrap();
+            supply += rescaled.balance;
+            dust += rescaled.dust;
+        }
+        let old_supply: u64 = balances.iter().sum();
+        assert_eq!(supply as i128 * 1_000 + dust, old_supply as i128);
+    }
+
+    #[test]
+    fn test_rescale_balance_gaining_decimals_is_exact() {
+        assert_eq!(
+            rescale_balance_with_dust(1_500_000, 6, 9, RoundingMode::Floor),
+            Ok(RescaledBalance {
+                balance: 1_500_000_000,
+                dust: 0
+            })
+        );
+        assert_eq!(
+            rescale_balance(u64::MAX, 0, 1, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            rescale_balance(1, MAX_DECIMALS + 1, 0, RoundingMode::Floor),
+            Err(FloatError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_quantize_to_scale_matches_idiom() {
+        for value in [
+            1.05_f64.powf(365.25),
+            0.1 + 0.2,
+            -2.5e-13,
+            1.0 / 3.0,
+            4_321.000_000_000_5,
+        ] {
+            let manual = (value * 1e12).round() / 1e12;
+            let quantized = quantize_to_scale(value, 1_000_000_000_000).unwrap();
+            assert_eq!(quantized.to_bits(), manual.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_truncate_to_decimals_large_magnitude_is_unchanged() {
+        // 1e300 * 1e12 overflows, which quantize reports and these pass through
+        assert_eq!(
+            quantize(1e300, 12, RoundingMode::HalfUp),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            truncate_to_decimals(1e300, 12, RoundingMode::HalfUp),
+            Ok(1e300)
+        );
+        assert_eq!(
+            truncate_to_decimals(-f64::MAX, 19, RoundingMode::Floor),
+            Ok(-f64::MAX)
+        );
+        assert_eq!(quantize_to_scale(f64::MAX, 1_000_000_000_000), Ok(f64::MAX));
+    }
+
+    #[test]
+    fn test_truncate_to_decimals_modes() {
+        assert_eq!(
+            truncate_to_decimals(123.456, 2, RoundingMode::TowardZero),
+            Ok(123.45)
+        );
+        assert_eq!(
+            truncate_to_decimals(-123.456, 2, RoundingMode::TowardZero),
+            Ok(-123.45)
+        );
+        assert_eq!(
+            truncate_to_decimals(-123.451, 2, RoundingMode::Floor),
+            Ok(-123.46)
+        );
+    }
+
+    #[test]
+    fn test_truncate_and_quantize_to_scale_reject_invalid_input() {
+        assert_eq!(
+            truncate_to_decimals(f64::NAN, 2, RoundingMode::Floor),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            truncate_to_decimals(1.0, MAX_DECIMALS + 1, RoundingMode::Floor),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            quantize_to_scale(f64::NEG_INFINITY, 100),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(quantize_to_scale(1.0, 0), Err(FloatError::OutOfRange));
+        assert_eq!(quantize_to_scale(1.0, 250), Err(FloatError::OutOfRange));
+        assert_eq!(quantize_to_scale(1.25, 1), Ok(1.0));
+    }
+
+    #[test]
+    fn test_round_half_even_to_scale() {
+        assert_eq!(round_half_even_to_scale(0.125, 100), Ok(0.12));
+        assert_eq!(round_half_even_to_scale(0.375, 100), Ok(0.38));
+        assert_eq!(round_half_even_to_scale(-0.125, 100), Ok(-0.12));
+        assert_eq!(round_half_even_to_scale(2.5, 1), Ok(2.0));
+        assert_eq!(round_half_even_to_scale(1e300, 100), Ok(1e300));
+        assert_eq!(
+            round_half_even_to_scale(1.0, 30),
+            Err(FloatError::OutOfRange)
+        );
+
+        assert_eq!(round_half_even_to_scale_f32(0.125, 100), Ok(0.12));
+        assert_eq!(round_half_even_to_scale_f32(-2.5, 1), Ok(-2.0));
+        assert_eq!(round_half_even_to_scale_f32(f32::MAX, 100), Ok(f32::MAX));
+        assert_eq!(
+            round_half_even_to_scale_f32(f32::NAN, 100),
+            Err(FloatError::NaNOperand)
+        );
+    }
+
+    #[test]
+    fn test_scale_with_rounding_uses_exact_product() {
+        // 0.29 is just below 0.29, and 0.29 * 100 rounds to just below 29
+        assert_eq!(scale_with_rounding(0.29, 2, RoundingMode::Floor), Ok(28));
+        assert_eq!(scale_with_rounding(0.29, 2, RoundingMode::Ceil), Ok(29));
+        assert_eq!(scale_with_rounding(0.29, 2, RoundingMode::HalfEven), Ok(29));
+        // 0.1 is just above 0.1
+        assert_eq!(scale_with_rounding(0.1, 1, RoundingMode::Floor), Ok(1));
+        assert_eq!(scale_with_rounding(0.1, 1, RoundingMode::Ceil), Ok(2));
+        assert_eq!(scale_with_rounding(2.5, 0, RoundingMode::HalfEven), Ok(2));
+        assert_eq!(scale_with_rounding(2.5, 0, RoundingMode::HalfUp), Ok(3));
+        assert_eq!(
+            scale_with_rounding(1.5, 9, RoundingMode::Floor),
+            Ok(1_500_000_000)
+        );
+        assert_eq!(
+            scale_with_rounding(f64::from_bits(1), 19, RoundingMode::Ceil),
+            Ok(1)
+        );
+        assert_eq!(
+            scale_with_rounding(f64::from_bits(1), 19, RoundingMode::Floor),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn test_scale_with_rounding_errors() {
+        assert_eq!(
+            scale_with_rounding(-0.001, 2, RoundingMode::TowardZero),
+            Ok(0)
+        );
+        assert_eq!(scale_with_rounding(-0.0, 2, RoundingMode::Floor), Ok(0));
+        assert_eq!(
+            scale_with_rounding(-0.001, 2, RoundingMode::Floor),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            scale_with_rounding(18_446_744_073_709_551_616.0, 0, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            scale_with_rounding(1e10, 10, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            scale_with_rounding(1e300, 2, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            scale_with_rounding(f64::NAN, 2, RoundingMode::Floor),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            scale_with_rounding(1.0, MAX_DECIMALS + 1, RoundingMode::Floor),
+            Err(FloatError::OutOfRange)
+        );
+    }
+}
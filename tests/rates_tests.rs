@@ -0,0 +1,278 @@
+use solana_floats::error::FloatError;
+use solana_floats::rates::*;
+
+#[cfg(test)]
+mod rates_tests {
+    use super::*;
+
+    const SLOTS: f64 = TARGET_SLOTS_PER_YEAR;
+    const EPOCH: RateUnit = RateUnit::PerEpoch(MAINNET_SLOTS_PER_EPOCH);
+
+    fn relative_error(actual: f64, expected: f64) -> f64 {
+        ((actual - expected) / expected).abs()
+    }
+
+    #[test]
+    fn test_simple_conversion_is_linear() {
+        let apr = 0.05;
+        let per_slot = rate_convert(
+            apr,
+            RateUnit::PerYear,
+            RateUnit::PerSlot,
+            SLOTS,
+            Compounding::Simple,
+        )
+        .unwrap();
+        assert_eq!(per_slot, apr / SLOTS);
+        let per_second = rate_convert(
+            apr,
+            RateUnit::PerYear,
+            RateUnit::PerSecond,
+            SLOTS,
+            Compounding::Simple,
+        )
+        .unwrap();
+        assert!(relative_error(per_second, apr / SECONDS_PER_YEAR) < 1e-15);
+        let per_epoch = rate_convert(
+            per_slot,
+            RateUnit::PerSlot,
+            EPOCH,
+            SLOTS,
+            Compounding::Simple,
+        )
+        .unwrap();
+        assert!(relative_error(per_epoch, apr * 432_000.0 / SLOTS) < 1e-15);
+        assert_eq!(
+            rate_convert(apr, EPOCH, EPOCH, SLOTS, Compounding::Simple),
+            Ok(apr)
+        );
+    }
+
+    #[test]
+    fn test_per_second_uses_the_runtime_year() {
+        // 31_556_925.993_6 seconds, not a Julian year's 31_557_600
+        assert_eq!(
+            rate_convert(
+                31_556_925.993_6,
+                RateUnit::PerYear,
+                RateUnit::PerSecond,
+                SLOTS,
+                Compounding::Simple
+            ),
+            Ok(1.0)
+        );
+        // 400 ms slots at the target rate
+        let per_second = rate_convert(
+            1e-9,
+            RateUnit::PerSlot,
+            RateUnit::PerSecond,
+            SLOTS,
+            Compounding::Simple,
+        )
+        .unwrap();
+        assert!(relative_error(per_second, 2.5e-9) < 1e-15);
+    }
+
+    #[test]
+    fn test_compounded_matches_high_precision_reference() {
+        // Expected values from Python's decimal module at 60 digits
+        let cases = [
+            (
+                1e-10,
+                RateUnit::PerSlot,
+                RateUnit::PerYear,
+                0.007920433484077442,
+            ),
+            (
+                0.05,
+                RateUnit::PerYear,
+                RateUnit::PerSlot,
+                6.184400114816499e-10,
+            ),
+            (0.0003, EPOCH, RateUnit::PerYear, 0.056306208629337176),
+            (
+                -0.2,
+                RateUnit::PerYear,
+                RateUnit::PerSecond,
+                -7.07114344947683e-09,
+            ),
+        ];
+        for (rate, from, to, expected) in cases {
+            let converted = rate_convert(rate, from, to, SLOTS, Compounding::Compounded).unwrap();
+            assert!(
+                relative_error(converted, expected) < 1e-14,
+                "{:?} -> {:?}: {}",
+                from,
+                to,
+                converted
+            );
+        }
+    }
+
+    #[test]
+    fn test_compounded_keeps_tiny_rates() {
+        // (1 + 1e-10) rounds 1e-10 to the nearest multiple of 2^-52, so the
+        // naive power is off from the seventh digit
+        let naive = (1.0f64 + 1e-10).powf(SLOTS) - 1.0;
+        assert!(relative_error(naive, 0.007920433484077442) > 1e-8);
+        let per_slot = rate_convert(
+            0.05,
+            RateUnit::PerYear,
+            RateUnit::PerSlot,
+            SLOTS,
+            Compounding::Compounded,
+        )
+        .unwrap();
+        let round_trip = rate_convert(
+            per_slot,
+            RateUnit::PerSlot,
+            RateUnit::PerYear,
+            SLOTS,
+            Compounding::Compounded,
+        )
+        .unwrap();
+        assert!(relative_error(round_trip, 0.05) < 1e-13, "{}", round_trip);
+    }
+
+    #[test]
+    fn test_compounding_differs_from_simple() {
+        let per_slot = 1e-9;
+        let simple = rate_convert(
+            per_slot,
+            RateUnit::PerSlot,
+            RateUnit::PerYear,
+            SLOTS,
+            Compounding::Simple,
+        )
+        .unwrap();
+        let compounded = rate_convert(
+            per_slot,
+            RateUnit::PerSlot,
+            RateUnit::PerYear,
+            SLOTS,
+            Compounding::Compounded,
+        )
+        .unwrap();
+        assert!(compounded > simple);
+        assert!(relative_error(simple, 0.078892314984) < 1e-15);
+    }
+
+    #[test]
+    fn test_errors() {
+        let convert = |rate, slots, compounding| {
+            rate_convert(
+                rate,
+                RateUnit::PerYear,
+                RateUnit::PerSlot,
+                slots,
+                compounding,
+            )
+        };
+        assert_eq!(
+            convert(f64::NAN, SLOTS, Compounding::Simple),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            convert(0.05, f64::NAN, Compounding::Simple),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            convert(f64::INFINITY, SLOTS, Compounding::Simple),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            convert(0.05, 0.0, Compounding::Simple),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            convert(0.05, f64::INFINITY, Compounding::Simple),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            convert(-1.0, SLOTS, Compounding::Compounded),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            rate_convert(
+                0.05,
+                RateUnit::PerEpoch(0),
+                RateUnit::PerYear,
+                SLOTS,
+                Compounding::Simple
+            ),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            rate_convert(
+                1.0,
+                RateUnit::PerSlot,
+                RateUnit::PerYear,
+                SLOTS,
+                Compounding::Compounded
+            ),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            rate_convert(
+                f64::MAX,
+                RateUnit::PerSlot,
+                RateUnit::PerYear,
+                SLOTS,
+                Compounding::Simple
+            ),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_inflation_follows_default_schedule() {
+        let inflation = |epoch| {
+            inflation_at_epoch(
+                DEFAULT_INITIAL_INFLATION,
+                DEFAULT_INFLATION_TAPER,
+                DEFAULT_TERMINAL_INFLATION,
+                epoch,
+            )
+            .unwrap()
+        };
+        assert_eq!(inflation(0), 0.08);
+        // Python: 0.08 * math.pow(0.85, epoch * 432_000 / 78_892_314.984)
+        let expected = [
+            (1, 0.07992883773599962),
+            (183, 0.06797707476097353),
+            (1_000, 0.0328549516784845),
+            (1_800, 0.01612165077991525),
+        ];
+        for (epoch, rate) in expected {
+            assert!(
+                relative_error(inflation(epoch), rate) < 1e-15,
+                "epoch {epoch}"
+            );
+        }
+        // The taper crosses 1.5% after about 10.3 years
+        assert_eq!(inflation(1_900), 0.015);
+        assert_eq!(inflation(u64::MAX), 0.015);
+    }
+
+    #[test]
+    fn test_inflation_rejects_bad_schedules() {
+        assert_eq!(
+            inflation_at_epoch(f64::NAN, 0.15, 0.015, 1),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            inflation_at_epoch(0.08, 1.0, 0.015, 1),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            inflation_at_epoch(0.08, -0.1, 0.015, 1),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            inflation_at_epoch(0.08, 0.15, f64::INFINITY, 1),
+            Err(FloatError::OutOfRange)
+        );
+        // No taper keeps the initial rate
+        assert_eq!(inflation_at_epoch(0.08, 0.0, 0.015, 10_000), Ok(0.08));
+    }
+}
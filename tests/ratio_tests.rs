@@ -0,0 +1,308 @@
+use solana_floats::error::FloatError;
+use solana_floats::quantize::RoundingMode;
+use solana_floats::ratio::{Ratio, approx_ratio};
+
+#[cfg(test)]
+mod ratio_tests {
+    use super::*;
+
+    fn ratio(num: i128, den: i128) -> Ratio {
+        Ratio::new(num, den).unwrap()
+    }
+
+    // Correctly rounded with Python's `float(Fraction(num, den))`
+    const NEAREST_F64: &[(i128, i128, u64)] = &[
+        (
+            9020564830042702791798089,
+            845372478508733715,
+            0x41645a3721b6e8d3,
+        ),
+        (1, 34834589382058121453973, 0x3b415a35587961d0),
+        (2315984081687340041524871, 1, 0x44fea6dbfa6e5139),
+        (
+            14662393294958740167393587855649,
+            481742193102136027071882775228859783,
+            0x3effea26889313fe,
+        ),
+        (
+            1368480946926604238255211042016065,
+            32011607791,
+            0x44a21ae99a904873,
+        ),
+        (-3825667, 1290755, 0xc007b6107e9e8a84),
+        (7, 19632663286235468413641911, 0x3adb962c8be53a77),
+        (173041, 3596688711183, 0x3e69d45a43f0367b),
+        (
+            -6356570112439025705062982263393073,
+            8427250001477114005437,
+            0xc265f3e168605e44,
+        ),
+        (-7835160629989598855, 790893096447, 0xc162e544a8dd5262),
+        (
+            3474955237347072783127289,
+            1494684916300669,
+            0x41e15258c11b8d60,
+        ),
+        (-310241, 70693537, 0xbf71f9b629276111),
+        (
+            -14306046255875,
+            16814613566754693552980772126231154237,
+            0xbaf075022cf58104,
+        ),
+        (19, 3, 0x4019555555555555),
+        (475429, 3510703, 0x3fc155883bbc8dd8),
+        (-192589659, 42051311, 0xc01251ca32885f35),
+        (30906135899268344997, 73, 0x439780795c9e3cd8),
+        (-817541893, 309457191, 0xc00522865743960c),
+        (3675285, 1, 0x414c0a4a80000000),
+        (-6512881, 13793755669, 0xbf3ef18e65deda13),
+        (169854887, 421, 0x41189fff27866282),
+        (
+            391314005,
+            8577385882915045707960738161942811,
+            0x3aac3d07a8b22662,
+        ),
+        (
+            -3273560883402420731702746670412968663,
+            552661284525870049484066422872001,
+            0xc0b72344998160d0,
+        ),
+        (
+            -3799364889908990010352066707973,
+            1382582429689411929798767764309669,
+            0xbf66830448a3d603,
+        ),
+    ];
+
+    // Python's `Fraction(value).limit_denominator(max_den)`
+    const BEST_APPROXIMATIONS: &[(f64, u64, i128, i128)] = &[
+        (
+            506.7266214888966,
+            18446744073709551615,
+            2228607249722759,
+            4398046511104,
+        ),
+        (0.12182106045210761, 100, 5, 41),
+        (214.68579608308687, 10, 2147, 10),
+        (0.49899895028640173, 1000, 249, 499),
+        (0.4121080541482617, 1000000000, 292062279, 708703157),
+        (
+            0.442033633239453,
+            18446744073709551615,
+            1990742505942427,
+            4503599627370496,
+        ),
+        (1.7725650818140747e-21, 100, 0, 1),
+        (
+            602.3324948704692,
+            18446744073709551615,
+            2649086327589635,
+            4398046511104,
+        ),
+        (755.2338284066568, 1, 755, 1),
+        (221490168233206.8, 4294967296, 3543842691731309, 16),
+        (-844.9767133496869, 1, -845, 1),
+        (0.6589307880119014, 1000, 653, 991),
+        (874.1022772035687, 10, 8741, 10),
+        (680.7277129584295, 1000, 420009, 617),
+        (-500.9981856851833, 100, -501, 1),
+        (-617.256934402028, 100, -45677, 74),
+        (2.2557270306085425e-05, 10, 0, 1),
+        (0.46179662531207744, 1, 0, 1),
+        (1.1461393642992286e-10, 1000000000, 0, 1),
+        (0.9136726729377983, 2, 1, 1),
+        (0.5341697876299017, 1000000000, 248789671, 465750173),
+        (0.5345573056730614, 1000, 495, 926),
+        (0.013734152727076787, 4294967296, 45309527, 3299040567),
+        (0.6574255828767418, 1000000000, 382607857, 581978960),
+        (1.699585811559396e-21, 10, 0, 1),
+        (212.48624527963352, 10, 425, 2),
+        (0.3828997966010136, 2, 1, 2),
+        (4.4056127317940103e-07, 4294967296, 337, 764933326),
+        (0.23751012292887141, 2, 0, 1),
+        (0.7949973081302968, 1, 1, 1),
+    ];
+
+    #[test]
+    fn test_normalization() {
+        let half = ratio(-3, -6);
+        assert_eq!((half.numer(), half.denom()), (1, 2));
+        assert_eq!(ratio(4, -6), ratio(-2, 3));
+        assert_eq!(ratio(0, -5), Ratio::ZERO);
+        assert_eq!(ratio(i128::MIN, i128::MIN), Ratio::ONE);
+        assert_eq!(ratio(i128::MIN, 2).numer(), i128::MIN / 2);
+        assert_eq!(Ratio::new(1, 0), Err(FloatError::DivisionByZero));
+        assert_eq!(Ratio::new(1, i128::MIN), Err(FloatError::Overflow));
+        assert!(ratio(6, 3).is_integer());
+        assert_eq!(ratio(-7, 14).to_string(), "-1/2");
+        assert_eq!(Ratio::from(5).to_string(), "5");
+    }
+
+    #[test]
+    fn test_arithmetic_is_exact() {
+        let tenth = ratio(1, 10);
+        assert_eq!(tenth.checked_add(ratio(2, 10)), Ok(ratio(3, 10)));
+        assert_eq!(ratio(1, 6).checked_sub(ratio(1, 3)), Ok(ratio(-1, 6)));
+        assert_eq!(ratio(2, 3).checked_mul(ratio(9, 4)), Ok(ratio(3, 2)));
+        assert_eq!(ratio(2, 3).checked_div(ratio(-4, 9)), Ok(ratio(-3, 2)));
+        assert_eq!(ratio(5, 7).checked_recip(), Ok(ratio(7, 5)));
+        assert_eq!(Ratio::ZERO.checked_recip(), Err(FloatError::DivisionByZero));
+        assert_eq!(
+            Ratio::ONE.checked_div(Ratio::ZERO),
+            Err(FloatError::DivisionByZero)
+        );
+        // 1/3 three times is exactly 1
+        let third = ratio(1, 3);
+        let sum = third
+            .checked_add(third)
+            .and_then(|sum| sum.checked_add(third));
+        assert_eq!(sum, Ok(Ratio::ONE));
+    }
+
+    #[test]
+    fn test_chained_exchange_rates_round_once() {
+        // USD -> EUR at 0.9213, EUR -> JPY at 162.47, JPY -> USD at 1/149.68
+        let usd_eur = ratio(9213, 10_000);
+        let eur_jpy = ratio(16_247, 100);
+        let jpy_usd = ratio(100, 14_968);
+        let round_trip = usd_eur
+            .checked_mul(eur_jpy)
+            .and_then(|rate| rate.checked_mul(jpy_usd))
+            .unwrap();
+        assert_eq!(round_trip, ratio(149_683_611, 149_680_000));
+        // 1,000,000 USD in micro-dollars, rounded once at the end
+        let amount = Ratio::from(1_000_000).checked_mul(round_trip).unwrap();
+        assert_eq!(
+            amount.to_scaled(6, RoundingMode::Floor),
+            Ok(1_000_024_124_799)
+        );
+        assert_eq!(
+            amount.to_scaled(6, RoundingMode::Ceil),
+            Ok(1_000_024_124_800)
+        );
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let cases = [
+            (ratio(5, 2), [2, 3, 2, 3, 2]),
+            (ratio(-5, 2), [-3, -2, -2, -3, -2]),
+            (ratio(7, 2), [3, 4, 3, 4, 4]),
+            (ratio(-7, 3), [-3, -2, -2, -2, -2]),
+        ];
+        let modes = [
+            RoundingMode::Floor,
+            RoundingMode::Ceil,
+            RoundingMode::TowardZero,
+            RoundingMode::HalfUp,
+            RoundingMode::HalfEven,
+        ];
+        for (value, expected) in cases {
+            for (mode, expected) in modes.iter().zip(expected) {
+                assert_eq!(value.round(*mode), Ok(expected), "{} {:?}", value, mode);
+            }
+        }
+        assert_eq!(
+            Ratio::from(i128::MIN).round(RoundingMode::Floor),
+            Ok(i128::MIN)
+        );
+        assert_eq!(
+            Ratio::from(i128::MAX).to_scaled(1, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_overflow_is_reported() {
+        let big = Ratio::from(i128::MAX);
+        assert_eq!(big.checked_add(Ratio::ONE), Err(FloatError::Overflow));
+        assert_eq!(big.checked_mul(ratio(2, 1)), Err(FloatError::Overflow));
+        assert_eq!(
+            Ratio::from(i128::MIN).checked_neg(),
+            Err(FloatError::Overflow)
+        );
+        // Cross-cancellation keeps products that reduce in range
+        assert_eq!(big.checked_mul(ratio(1, i128::MAX)), Ok(Ratio::ONE));
+        let tiny = ratio(1, i128::MAX);
+        assert_eq!(tiny.checked_mul(tiny), Err(FloatError::Overflow));
+    }
+
+    #[test]
+    fn test_ordering_does_not_overflow() {
+        let a = ratio(i128::MAX, i128::MAX - 1);
+        let b = ratio(i128::MAX - 1, i128::MAX - 2);
+        assert!(a < b);
+        assert!(ratio(-1, 3) < ratio(-1, 4));
+        assert!(ratio(-1, 3) < Ratio::ZERO);
+        assert!(Ratio::from(i128::MIN) < Ratio::from(i128::MAX));
+        assert_eq!(ratio(2, 4).cmp(&ratio(1, 2)), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn test_to_f64_is_correctly_rounded() {
+        for &(num, den, bits) in NEAREST_F64 {
+            let value = ratio(num, den).to_f64();
+            assert_eq!(value.to_bits(), bits, "{}/{}", num, den);
+        }
+        assert_eq!(ratio(1, 3).to_f64(), 1.0 / 3.0);
+        assert_eq!(Ratio::from(i128::MIN).to_f64(), -(2f64.powi(127)));
+        // 2^64 + 2049 rounds to 2^64 + 4096 as an f64 before the division,
+        // which then rounds again to the wrong neighbour
+        let num = (1 << 64) + 2049;
+        assert_ne!(num as f64 / 3.0, ratio(num, 3).to_f64());
+    }
+
+    #[test]
+    fn test_f64_conversions_are_exact() {
+        assert_eq!(Ratio::try_from(0.1), Ok(ratio(3602879701896397, 1 << 55)));
+        assert_eq!(Ratio::try_from(-2.5), Ok(ratio(-5, 2)));
+        assert_eq!(Ratio::try_from(1e30).map(Ratio::to_f64), Ok(1e30));
+        assert_eq!(Ratio::try_from(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(Ratio::try_from(f64::INFINITY), Err(FloatError::Overflow));
+        assert_eq!(Ratio::try_from(1e39), Err(FloatError::Overflow));
+        assert_eq!(Ratio::try_from(1e-39), Err(FloatError::PrecisionLoss));
+        assert_eq!(f64::try_from(ratio(3, 8)), Ok(0.375));
+        assert_eq!(f64::try_from(ratio(1, 3)), Err(FloatError::PrecisionLoss));
+    }
+
+    #[test]
+    fn test_approx_ratio_finds_best_fraction() {
+        let pi = std::f64::consts::PI;
+        assert_eq!(approx_ratio(pi, 7), Ok(ratio(22, 7)));
+        assert_eq!(approx_ratio(pi, 1000), Ok(ratio(355, 113)));
+        assert_eq!(approx_ratio(-pi, 1000), Ok(ratio(-355, 113)));
+        // An oracle price of 0.1 is the float just above 1/10
+        assert_eq!(approx_ratio(0.1, 1_000_000), Ok(ratio(1, 10)));
+        assert_eq!(
+            approx_ratio(1.0 / 3.0, u64::MAX).map(|r| r.denom()),
+            Ok(1 << 54)
+        );
+        assert_eq!(approx_ratio(0.999, 10), Ok(Ratio::ONE));
+        // Ties are resolved the same way for either sign
+        assert_eq!(approx_ratio(2.5, 1), Ok(Ratio::from(2)));
+        assert_eq!(approx_ratio(-2.5, 1), Ok(Ratio::from(-2)));
+        assert_eq!(approx_ratio(1e-30, u64::MAX), Ok(Ratio::ZERO));
+        assert_eq!(approx_ratio(f64::MIN_POSITIVE, 1), Ok(Ratio::ZERO));
+        assert_eq!(
+            approx_ratio(2.5e20, 3),
+            Ok(Ratio::from(250_000_000_000_000_000_000))
+        );
+        for &(value, max_den, num, den) in BEST_APPROXIMATIONS {
+            assert_eq!(
+                approx_ratio(value, max_den),
+                Ok(ratio(num, den)),
+                "{} {}",
+                value,
+                max_den
+            );
+        }
+    }
+
+    #[test]
+    fn test_approx_ratio_errors() {
+        assert_eq!(approx_ratio(f64::NAN, 10), Err(FloatError::NaNOperand));
+        assert_eq!(approx_ratio(f64::INFINITY, 10), Err(FloatError::Overflow));
+        assert_eq!(approx_ratio(1e39, 10), Err(FloatError::Overflow));
+        assert_eq!(approx_ratio(0.5, 0), Err(FloatError::OutOfRange));
+    }
+}
@@ -0,0 +1,95 @@
+use solana_floats::error::FloatError;
+use solana_floats::reciprocal::Reciprocal;
+
+mod common;
+
+#[cfg(test)]
+mod reciprocal_tests {
+    use super::*;
+    use common::random_positive;
+
+    #[test]
+    fn test_rejects_invalid_divisors() {
+        assert_eq!(Reciprocal::new(0.0), Err(FloatError::DivisionByZero));
+        assert_eq!(Reciprocal::new(-0.0), Err(FloatError::DivisionByZero));
+        assert_eq!(Reciprocal::new(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(Reciprocal::new(f64::INFINITY), Err(FloatError::NaNOperand));
+        assert_eq!(
+            Reciprocal::new(f64::from_bits(1)),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_powers_of_two_are_exact() {
+        for exponent in -1022..=1023 {
+            for sign in [1.0, -1.0] {
+                let reciprocal = Reciprocal::new(sign * 2_f64.powi(exponent)).unwrap();
+                assert!(reciprocal.is_exact(), "2^{}", exponent);
+                assert_eq!(reciprocal.max_ulps(), 0);
+            }
+        }
+        // Subnormal powers of two whose reciprocal is still finite
+        assert!(Reciprocal::new(f64::MIN_POSITIVE / 2.0).unwrap().is_exact());
+
+        for d in [0.5, 1.0, 1024.0, 2_f64.powi(-30), f64::MIN_POSITIVE] {
+            let reciprocal = Reciprocal::new(d).unwrap();
+            for x in random_positive(0x9e37_79b9_7f4a_7c15, 20_000).chain([
+                f64::MAX,
+                f64::from_bits(1),
+                3.0,
+            ]) {
+                assert_eq!(reciprocal.apply(x).to_bits(), (x / d).to_bits());
+            }
+        }
+    }
+
+    #[test]
+    fn test_other_divisors_within_one_ulp() {
+        for d in [3.0, 10.0, 1e9, 0.1, 1_234_567_891.0, -7.0] {
+            let reciprocal = Reciprocal::new(d).unwrap();
+            assert!(!reciprocal.is_exact());
+            assert_eq!(reciprocal.max_ulps(), 1);
+            let mut identical = 0;
+            for x in random_positive(0x9e37_79b9_7f4a_7c15, 100_000) {
+                let quotient = x / d;
+                if !quotient.is_normal() {
+                    continue;
+                }
+                let product = reciprocal.apply(x);
+                assert!(
+                    product.to_bits().abs_diff(quotient.to_bits()) <= 1,
+                    "{} / {}",
+                    x,
+                    d
+                );
+                if product == quotient {
+                    identical += 1;
+                }
+            }
+            // Usually identical, occasionally one ulp away
+            assert!(identical > 50_000, "{} identical for {}", identical, d);
+        }
+    }
+
+    #[test]
+    fn test_not_always_identical() {
+        // RN(1/10) is slightly above 0.1, and 3 * RN(1/10) rounds up
+        let tenth = Reciprocal::new(10.0).unwrap();
+        assert_ne!(tenth.apply(3.0), 3.0 / 10.0);
+        assert_eq!(tenth.apply(3.0), 0.30000000000000004);
+    }
+
+    #[test]
+    fn test_apply_slice_divides_by_total_supply() {
+        let supply = 1_000_000_000.0;
+        let reciprocal = Reciprocal::new(supply).unwrap();
+        let mut balances = [250_000_000.0, 500_000_000.0, 123_456_789.0, 0.0];
+        let expected: Vec<f64> = balances.iter().map(|b| reciprocal.apply(*b)).collect();
+        reciprocal.apply_slice(&mut balances);
+        assert_eq!(balances.to_vec(), expected);
+        assert_eq!(balances[0], 0.25);
+        assert_eq!(reciprocal.divisor(), supply);
+        assert_eq!(reciprocal.reciprocal(), 1e-9);
+    }
+}
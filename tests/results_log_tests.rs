@@ -0,0 +1,296 @@
+use solana_floats::chain::{ChainStep, Operand, init_cache};
+use solana_floats::double_ops::BinaryOp;
+use solana_floats::error::FloatError;
+use solana_floats::results_log::*;
+use solana_program::program_error::ProgramError;
+use solana_program::rent::Rent;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+mod common;
+
+#[cfg(test)]
+mod results_log_tests {
+    use super::*;
+    use common::program_test;
+
+    fn entry(index: u64) -> ResultEntry {
+        let a = index as f64 + 0.1;
+        ResultEntry::new(BinaryOp::Multiply, a, 3.0, a * 3.0)
+    }
+
+    // Sends each instruction in its own transaction, signed by the payer and
+    // `signers`, and returns the first failure
+    async fn run(
+        program_test: ProgramTest,
+        instructions: impl Fn(&Keypair) -> Vec<Instruction>,
+        signers: &[&Keypair],
+    ) -> (BanksClient, Keypair, Result<(), TransactionError>) {
+        let (banks_client, payer, recent_blockhash) = program_test.start().await;
+        for instruction in instructions(&payer) {
+            let mut all_signers = vec![&payer];
+            all_signers.extend_from_slice(signers);
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &all_signers,
+                recent_blockhash,
+            );
+            if let Err(err) = banks_client.process_transaction(transaction).await {
+                return (banks_client, payer, Err(err.unwrap()));
+            }
+        }
+        (banks_client, payer, Ok(()))
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let mut log = ResultsLog::new(Pubkey::new_unique());
+        let mut data = [0u8; ResultsLog::LEN];
+        log.pack(&mut data).unwrap();
+        assert_eq!(ResultsLog::unpack(&data), Ok(log.clone()));
+
+        for index in 0..3 {
+            log.push(entry(index));
+        }
+        log.pack(&mut data).unwrap();
+        let unpacked = ResultsLog::unpack(&data).unwrap();
+        assert_eq!(unpacked, log);
+        assert_eq!(unpacked.entries(), &[entry(0), entry(1), entry(2)]);
+        assert_eq!(unpacked.last(), Some(&entry(2)));
+    }
+
+    #[test]
+    fn test_full_log_keeps_the_latest_entries() {
+        let mut log = ResultsLog::new(Pubkey::new_unique());
+        let total = RESULTS_LOG_CAPACITY as u64 * 2 + 5;
+        for index in 0..total {
+            log.push(entry(index));
+        }
+        assert_eq!(log.recorded, total);
+        let expected: Vec<_> = (total - RESULTS_LOG_CAPACITY as u64..total)
+            .map(entry)
+            .collect();
+        assert_eq!(log.entries(), expected.as_slice());
+
+        let mut data = [0u8; ResultsLog::LEN];
+        log.pack(&mut data).unwrap();
+        assert_eq!(ResultsLog::unpack(&data), Ok(log));
+    }
+
+    #[test]
+    fn test_unpack_rejects_corrupt_data() {
+        assert_eq!(
+            ResultsLog::unpack(&[0u8; ResultsLog::LEN - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let mut data = [0u8; ResultsLog::LEN];
+        ResultsLog::new(Pubkey::new_unique())
+            .pack(&mut data)
+            .unwrap();
+        data[33] = 1;
+        // Unknown operation in the recorded entry
+        data[41] = 3;
+        assert_eq!(
+            ResultsLog::unpack(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        // Any other account's data, such as a chain cache
+        let mut data = [0u8; ResultsLog::LEN];
+        init_cache(&mut data).unwrap();
+        assert_eq!(
+            ResultsLog::unpack(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_chain_step_refuses_results_log() {
+        let mut data = [0u8; ResultsLog::LEN];
+        let mut log = ResultsLog::new(Pubkey::new_unique());
+        log.push(entry(0));
+        log.pack(&mut data).unwrap();
+        // Overwriting the authority through slot 0 must not be possible
+        let step = ChainStep {
+            op: BinaryOp::Add,
+            lhs: Operand::Value(1.0),
+            rhs: Operand::Value(2.0),
+            destination: 0,
+        };
+        assert_eq!(step.apply(&mut data), Err(ProgramError::InvalidAccountData));
+        assert_eq!(ResultsLog::unpack(&data), Ok(log));
+    }
+
+    #[tokio::test]
+    async fn test_record_result_creates_and_appends() {
+        let program_id = Pubkey::new_unique();
+        let cases = [
+            (BinaryOp::Add, 0.1, 0.2),
+            (BinaryOp::Multiply, 1.1, 1.1),
+            (BinaryOp::Divide, 1.0, 3.0),
+        ];
+        let (banks_client, payer, result) = run(
+            program_test(program_id),
+            |payer| {
+                cases
+                    .iter()
+                    .map(|&(op, a, b)| {
+                        ResultsLog::record_instruction(program_id, payer.pubkey(), op, a, b)
+                    })
+                    .collect()
+            },
+            &[],
+        )
+        .await;
+        result.unwrap();
+
+        let (address, _) = ResultsLog::find_address(&program_id, &payer.pubkey());
+        let account = banks_client.get_account(address).await.unwrap().unwrap();
+        assert_eq!(account.owner, program_id);
+        assert!(Rent::default().is_exempt(account.lamports, ResultsLog::LEN));
+        let log = ResultsLog::unpack(&account.data).unwrap();
+        assert_eq!(log.authority, payer.pubkey());
+        assert_eq!(log.recorded, 3);
+        let expected = [
+            ResultEntry::new(BinaryOp::Add, 0.1, 0.2, 0.1 + 0.2),
+            ResultEntry::new(BinaryOp::Multiply, 1.1, 1.1, 1.1 * 1.1),
+            ResultEntry::new(BinaryOp::Divide, 1.0, 3.0, 1.0 / 3.0),
+        ];
+        assert_eq!(log.entries(), &expected);
+    }
+
+    #[tokio::test]
+    async fn test_record_result_wraps_around() {
+        let program_id = Pubkey::new_unique();
+        let total = RESULTS_LOG_CAPACITY as u64 + 3;
+        let (banks_client, payer, result) = run(
+            program_test(program_id),
+            |payer| {
+                (0..total)
+                    .map(|index| {
+                        let entry = entry(index);
+                        ResultsLog::record_instruction(
+                            program_id,
+                            payer.pubkey(),
+                            entry.op,
+                            entry.a(),
+                            entry.b(),
+                        )
+                    })
+                    .collect()
+            },
+            &[],
+        )
+        .await;
+        result.unwrap();
+
+        let (address, _) = ResultsLog::find_address(&program_id, &payer.pubkey());
+        let account = banks_client.get_account(address).await.unwrap().unwrap();
+        let log = ResultsLog::unpack(&account.data).unwrap();
+        assert_eq!(log.recorded, total);
+        assert_eq!(log.entries().len(), RESULTS_LOG_CAPACITY);
+        assert_eq!(log.entries()[0], entry(3));
+        assert_eq!(log.last(), Some(&entry(total - 1)));
+    }
+
+    #[tokio::test]
+    async fn test_record_result_into_prefunded_address() {
+        // Lamports sent to the address before the log exists must not block
+        // its creation, whether or not they already cover rent
+        let rent_exempt = Rent::default().minimum_balance(ResultsLog::LEN);
+        for lamports in [1_000, rent_exempt + 1] {
+            let program_id = Pubkey::new_unique();
+            let authority = Keypair::new();
+            let (address, _) = ResultsLog::find_address(&program_id, &authority.pubkey());
+            let mut program_test = program_test(program_id);
+            program_test.add_account(
+                address,
+                Account {
+                    lamports,
+                    owner: solana_system_interface::program::id(),
+                    ..Account::default()
+                },
+            );
+            program_test.add_account(
+                authority.pubkey(),
+                Account {
+                    lamports: 1_000_000_000,
+                    owner: solana_system_interface::program::id(),
+                    ..Account::default()
+                },
+            );
+            let (banks_client, _, result) = run(
+                program_test,
+                |_| {
+                    vec![ResultsLog::record_instruction(
+                        program_id,
+                        authority.pubkey(),
+                        BinaryOp::Add,
+                        1.0,
+                        2.0,
+                    )]
+                },
+                &[&authority],
+            )
+            .await;
+            result.unwrap();
+
+            let account = banks_client.get_account(address).await.unwrap().unwrap();
+            assert_eq!(account.owner, program_id);
+            assert_eq!(account.lamports, lamports.max(rent_exempt));
+            let log = ResultsLog::unpack(&account.data).unwrap();
+            assert_eq!(log.authority, authority.pubkey());
+            assert_eq!(log.last().unwrap().result(), 3.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_result_errors() {
+        let program_id = Pubkey::new_unique();
+        let authority = Keypair::new();
+        let other = Keypair::new();
+        let record =
+            |op, a, b| ResultsLog::record_instruction(program_id, authority.pubkey(), op, a, b);
+
+        let mut other_log = record(BinaryOp::Add, 1.0, 2.0);
+        other_log.accounts[0].pubkey = ResultsLog::find_address(&program_id, &other.pubkey()).0;
+        let mut unsigned = record(BinaryOp::Add, 1.0, 2.0);
+        unsigned.accounts[1] = AccountMeta::new(authority.pubkey(), false);
+
+        let cases = [
+            (
+                record(BinaryOp::Divide, 1.0, 0.0),
+                true,
+                InstructionError::Custom(FloatError::DivisionByZero.code()),
+            ),
+            (
+                record(BinaryOp::Add, f64::NAN, 1.0),
+                true,
+                InstructionError::Custom(FloatError::NaNOperand.code()),
+            ),
+            (other_log, true, InstructionError::InvalidSeeds),
+            (unsigned, false, InstructionError::MissingRequiredSignature),
+        ];
+        for (instruction, signed, expected) in cases {
+            let mut program_test = program_test(program_id);
+            program_test.add_account(
+                authority.pubkey(),
+                Account {
+                    lamports: 1_000_000_000,
+                    owner: solana_system_interface::program::id(),
+                    ..Account::default()
+                },
+            );
+            let signers: &[&Keypair] = if signed { &[&authority] } else { &[] };
+            let (_, _, result) = run(program_test, |_| vec![instruction.clone()], signers).await;
+            assert_eq!(result, Err(TransactionError::InstructionError(0, expected)));
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use solana_floats::error::FloatError;
+use solana_floats::rng::DeterministicRng;
+
+#[cfg(test)]
+mod rng_tests {
+    use super::*;
+
+    fn seeded() -> DeterministicRng {
+        let mut seed = [0u8; 32];
+        for (byte, value) in seed.iter_mut().zip(1..) {
+            *byte = value;
+        }
+        DeterministicRng::from_seed(seed)
+    }
+
+    const EXPONENTIAL_DIGEST: u64 = 0x104c_7132_50ca_261f;
+    const NORMAL_DIGEST: u64 = 0xca2f_86b5_f422_e4dd;
+
+    // FNV-1a over the bits of every sample
+    fn digest(values: impl Iterator<Item = f64>) -> u64 {
+        values.fold(0xcbf2_9ce4_8422_2325, |hash, value| {
+            (hash ^ value.to_bits()).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+
+    #[test]
+    fn test_matches_reference_xoshiro256starstar() {
+        let mut rng = seeded();
+        let words: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+        assert_eq!(
+            words,
+            [
+                0x52bc_258e_f861_cbe8,
+                0xbc25_8ef8_61cb_3280,
+                0x2d01_3c0e_e31e_1c12,
+                0x2506_e980_14e9_ac07
+            ]
+        );
+        let mut rng = seeded();
+        assert_eq!(rng.next_f64(), 0.32318339100346016);
+        assert_eq!(rng.next_f64(), 0.7349480968858131);
+    }
+
+    #[test]
+    fn test_same_seed_replays_identically() {
+        let (mut a, mut b) = (seeded(), seeded());
+        for _ in 0..1000 {
+            assert_eq!(a.next_f64().to_bits(), b.next_f64().to_bits());
+            assert_eq!(a.next_normal(0.0, 1.0), b.next_normal(0.0, 1.0));
+        }
+        let mut other = DeterministicRng::from_seed([7; 32]);
+        assert_ne!(seeded().next_u64(), other.next_u64());
+        // An all-zero seed still produces a usable stream
+        let mut zero = DeterministicRng::from_seed([0; 32]);
+        assert!((0..4).any(|_| zero.next_u64() != 0));
+    }
+
+    #[test]
+    fn test_transformed_samples_are_pinned() {
+        // Pins the exact bits, which must not change across targets or
+        // releases, since validators replay them
+        let mut rng = seeded();
+        let samples = (0..1000).map(|_| rng.next_exponential(2.0).unwrap());
+        assert_eq!(digest(samples), EXPONENTIAL_DIGEST);
+        let mut rng = seeded();
+        let samples = (0..1000).map(|_| rng.next_normal(10.0, 3.0).unwrap());
+        assert_eq!(digest(samples), NORMAL_DIGEST);
+    }
+
+    #[test]
+    fn test_distributions() {
+        let mut rng = seeded();
+        let count = 100_000;
+        let uniforms: Vec<f64> = (0..count).map(|_| rng.next_f64()).collect();
+        assert!(uniforms.iter().all(|u| (0.0..1.0).contains(u)));
+        let mean = uniforms.iter().sum::<f64>() / count as f64;
+        assert!((mean - 0.5).abs() < 0.005, "{}", mean);
+
+        let exponentials: Vec<f64> = (0..count)
+            .map(|_| rng.next_exponential(4.0).unwrap())
+            .collect();
+        assert!(exponentials.iter().all(|x| *x >= 0.0));
+        let mean = exponentials.iter().sum::<f64>() / count as f64;
+        assert!((mean - 0.25).abs() < 0.005, "{}", mean);
+
+        let normals: Vec<f64> = (0..count)
+            .map(|_| rng.next_normal(5.0, 2.0).unwrap())
+            .collect();
+        let mean = normals.iter().sum::<f64>() / count as f64;
+        let variance = normals.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / count as f64;
+        assert!((mean - 5.0).abs() < 0.03, "{}", mean);
+        assert!((variance - 4.0).abs() < 0.1, "{}", variance);
+    }
+
+    #[test]
+    fn test_next_below_is_in_range_and_fair() {
+        let mut rng = seeded();
+        let mut counts = [0u32; 6];
+        for _ in 0..60_000 {
+            counts[rng.next_below(6).unwrap() as usize] += 1;
+        }
+        assert!(
+            counts.iter().all(|count| (9_500..10_500).contains(count)),
+            "{:?}",
+            counts
+        );
+        assert_eq!(rng.next_below(1), Ok(0));
+        assert!(rng.next_below(u64::MAX).unwrap() < u64::MAX);
+        assert_eq!(rng.next_below(0), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let mut rng = seeded();
+        assert_eq!(rng.next_exponential(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(rng.next_exponential(0.0), Err(FloatError::OutOfRange));
+        assert_eq!(
+            rng.next_exponential(f64::INFINITY),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(rng.next_normal(f64::NAN, 1.0), Err(FloatError::NaNOperand));
+        assert_eq!(rng.next_normal(0.0, -1.0), Err(FloatError::OutOfRange));
+        assert_eq!(
+            rng.next_normal(f64::INFINITY, 1.0),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(rng.next_normal(0.0, 0.0), Ok(0.0));
+        assert_eq!(
+            rng.next_normal(f64::MAX, f64::MAX),
+            Err(FloatError::Overflow)
+        );
+    }
+}
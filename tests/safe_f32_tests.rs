@@ -0,0 +1,104 @@
+use solana_floats::error::FloatError;
+use solana_floats::safe_f32::SafeF32;
+use solana_floats::safe_f64::SafeF64;
+
+#[cfg(test)]
+mod safe_f32_tests {
+    use super::*;
+
+    fn safe(value: f32) -> SafeF32 {
+        SafeF32::new(value).unwrap()
+    }
+
+    fn safe64(value: f64) -> SafeF64 {
+        SafeF64::new(value).unwrap()
+    }
+
+    #[test]
+    fn test_rejects_non_finite_inputs() {
+        assert_eq!(SafeF32::new(f32::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(SafeF32::new(f32::INFINITY), Err(FloatError::Overflow));
+        assert_eq!(SafeF32::new(f32::NEG_INFINITY), Err(FloatError::Overflow));
+        assert_eq!(SafeF32::try_from(1.5).map(f32::from), Ok(1.5));
+    }
+
+    #[test]
+    fn test_arithmetic_matches_f32() {
+        assert_eq!((safe(0.1) + safe(0.2)).unwrap().get(), 0.1_f32 + 0.2);
+        assert_eq!((safe(1.0) - safe(0.9)).unwrap().get(), 1.0_f32 - 0.9);
+        assert_eq!((safe(1.1) * safe(1.1)).unwrap().get(), 1.1_f32 * 1.1);
+        assert_eq!((safe(1.0) / safe(3.0)).unwrap().get(), 1.0_f32 / 3.0);
+        assert_eq!(safe(1.0).checked_div(safe(3.0)), safe(1.0) / safe(3.0));
+        assert_eq!(-SafeF32::MAX, SafeF32::MIN);
+    }
+
+    #[test]
+    fn test_arithmetic_never_yields_non_finite() {
+        assert_eq!(SafeF32::MAX + SafeF32::MAX, Err(FloatError::Overflow));
+        assert_eq!(SafeF32::MIN * safe(2.0), Err(FloatError::Overflow));
+        assert_eq!(
+            SafeF32::ONE / SafeF32::ZERO,
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(
+            SafeF32::ONE.checked_div(safe(-0.0)),
+            Err(FloatError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_widening_is_exact() {
+        for value in [0.1, -2.5, f32::MAX, f32::MIN_POSITIVE, f32::from_bits(1)] {
+            let wide = safe(value).to_safe_f64();
+            assert_eq!(wide.get(), value as f64);
+            assert_eq!(SafeF64::from(safe(value)), wide);
+            assert_eq!(SafeF32::from_safe_f64(wide), Ok(safe(value)));
+        }
+    }
+
+    #[test]
+    fn test_narrowing_reports_precision_loss() {
+        assert_eq!(SafeF32::from_safe_f64(safe64(0.5)), Ok(safe(0.5)));
+        assert_eq!(
+            SafeF32::from_safe_f64(safe64(0.1)),
+            Err(FloatError::PrecisionLoss)
+        );
+        assert_eq!(
+            SafeF32::try_from(safe64(16_777_217.0)),
+            Err(FloatError::PrecisionLoss)
+        );
+        // Underflow to zero is a loss too
+        assert_eq!(
+            SafeF32::from_safe_f64(safe64(1e-50)),
+            Err(FloatError::PrecisionLoss)
+        );
+        assert_eq!(
+            SafeF32::from_safe_f64(safe64(1e39)),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(SafeF32::from_safe_f64_rounded(safe64(0.1)), Ok(safe(0.1)));
+        assert_eq!(
+            SafeF32::from_safe_f64_rounded(safe64(16_777_217.0)),
+            Ok(safe(16_777_216.0))
+        );
+        assert_eq!(
+            SafeF32::from_safe_f64_rounded(SafeF64::MAX),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_total_order() {
+        let mut values = vec![safe(3.0), safe(-1.0), SafeF32::MAX, safe(0.5)];
+        values.sort();
+        let sorted: Vec<f32> = values.into_iter().map(SafeF32::get).collect();
+        assert_eq!(sorted, vec![-1.0, 0.5, 3.0, f32::MAX]);
+    }
+
+    #[test]
+    fn test_display_is_fixed_decimals() {
+        assert_eq!(safe(0.1).to_string(), "0.100000");
+        assert_eq!(format!("{:.9}", safe(0.1)), "0.100000001");
+        assert_eq!(safe(-2.5).to_string(), "-2.500000");
+    }
+}
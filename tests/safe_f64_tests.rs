@@ -0,0 +1,108 @@
+use solana_floats::error::FloatError;
+use solana_floats::safe_f64::SafeF64;
+
+#[cfg(test)]
+mod safe_f64_tests {
+    use super::*;
+
+    fn safe(value: f64) -> SafeF64 {
+        SafeF64::new(value).unwrap()
+    }
+
+    #[test]
+    fn test_rejects_non_finite_inputs() {
+        assert_eq!(SafeF64::new(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(SafeF64::new(f64::INFINITY), Err(FloatError::Overflow));
+        assert_eq!(SafeF64::new(f64::NEG_INFINITY), Err(FloatError::Overflow));
+        assert_eq!(SafeF64::try_from(1.5).map(f64::from), Ok(1.5));
+    }
+
+    #[test]
+    fn test_arithmetic_matches_f64() {
+        assert_eq!(safe(0.1).checked_add(safe(0.2)).unwrap().get(), 0.1 + 0.2);
+        assert_eq!(safe(1.0).checked_sub(safe(0.9)).unwrap().get(), 1.0 - 0.9);
+        assert_eq!(safe(1.1).checked_mul(safe(1.1)).unwrap().get(), 1.1 * 1.1);
+        assert_eq!(safe(1.0).checked_div(safe(3.0)).unwrap().get(), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn test_arithmetic_never_yields_non_finite() {
+        assert_eq!(
+            SafeF64::MAX.checked_add(SafeF64::MAX),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            SafeF64::MIN.checked_mul(safe(2.0)),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            SafeF64::ONE.checked_div(SafeF64::ZERO),
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(
+            SafeF64::ONE.checked_div(safe(-0.0)),
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(
+            SafeF64::MAX.checked_div(safe(f64::MIN_POSITIVE)),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_operators_match_checked_methods() {
+        assert_eq!(safe(0.1) + safe(0.2), safe(0.1).checked_add(safe(0.2)));
+        assert_eq!(safe(1.0) - safe(0.9), safe(1.0).checked_sub(safe(0.9)));
+        assert_eq!(safe(1.1) * safe(1.1), safe(1.1).checked_mul(safe(1.1)));
+        assert_eq!(safe(1.0) / safe(3.0), safe(1.0).checked_div(safe(3.0)));
+        assert_eq!(SafeF64::MAX + SafeF64::MAX, Err(FloatError::Overflow));
+        assert_eq!(SafeF64::MIN - SafeF64::MAX, Err(FloatError::Overflow));
+        assert_eq!(
+            SafeF64::ONE / SafeF64::ZERO,
+            Err(FloatError::DivisionByZero)
+        );
+        assert_eq!(-SafeF64::MAX, SafeF64::MIN);
+    }
+
+    #[test]
+    fn test_operators_chain_with_question_mark() {
+        fn weighted(
+            price: SafeF64,
+            amount: SafeF64,
+            total: SafeF64,
+        ) -> Result<SafeF64, FloatError> {
+            (price * amount)? / total
+        }
+        assert_eq!(
+            weighted(safe(2.5), safe(4.0), safe(5.0)).map(SafeF64::get),
+            Ok(2.0)
+        );
+        assert_eq!(
+            weighted(SafeF64::MAX, safe(2.0), safe(5.0)),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            weighted(safe(2.5), safe(4.0), SafeF64::ZERO),
+            Err(FloatError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_total_order() {
+        let mut values = vec![safe(3.0), safe(-1.0), SafeF64::MAX, safe(0.5)];
+        values.sort();
+        let sorted: Vec<f64> = values.into_iter().map(SafeF64::get).collect();
+        assert_eq!(sorted, vec![-1.0, 0.5, 3.0, f64::MAX]);
+    }
+
+    #[test]
+    fn test_display_is_fixed_decimals() {
+        assert_eq!(safe(0.1 + 0.2).to_string(), "0.300000000");
+        assert_eq!(safe(-2.5).to_string(), "-2.500000000");
+        assert_eq!(safe(-1e-12).to_string(), "0.000000000");
+        assert_eq!(format!("{:.2}", safe(0.125)), "0.12");
+        assert_eq!(format!("{:+.1}", safe(2.25)), "+2.2");
+        assert_eq!(format!("{:08.1}", safe(-2.25)), "-00002.2");
+        assert_eq!(SafeF64::MAX.to_string(), "1.7976931348623157e308");
+    }
+}
@@ -0,0 +1,151 @@
+//! Slice operations must give bit-identical results whether or not the
+//! compiler vectorizes them. Run in release, where autovectorization is on,
+//! both ways:
+//!
+//! cargo test --release --test scalar_determinism_tests
+//! cargo test --release --features scalar-audit --test scalar_determinism_tests
+//!
+//! Both must match `GOLDEN_DIGEST`, and each reduction must match a reference
+//! loop that `black_box` keeps scalar.
+
+use solana_floats::array_ops::*;
+use solana_floats::quantize::{RoundingMode, quantize_slice};
+use solana_floats::reciprocal::Reciprocal;
+use std::hint::black_box;
+
+mod common;
+
+#[cfg(test)]
+mod scalar_determinism_tests {
+    use super::*;
+    use common::xorshift;
+
+    // FNV-1a over the bit patterns of every output below
+    const GOLDEN_DIGEST: u64 = 0x9307_af2d_a3b9_8c4b;
+
+    // Lengths around every plausible vector width, plus a long tail
+    const LENGTHS: [usize; 14] = [0, 1, 2, 3, 4, 5, 7, 8, 9, 15, 16, 17, 64, 1027];
+
+    // Magnitudes spread over many binades so that any change in the order of
+    // additions changes the rounded result
+    fn corpus(len: usize, seed: u64) -> Vec<f64> {
+        xorshift(seed | 1)
+            .take(len)
+            .map(|word| {
+                let mantissa = (word >> 11) as f64 / (1_u64 << 53) as f64;
+                let exponent = (word % 40) as i32 - 20;
+                mantissa * 2_f64.powi(exponent)
+            })
+            .collect()
+    }
+
+    fn reference_sum(values: &[f64]) -> f64 {
+        let mut sum = 0.0;
+        for &value in values {
+            sum = black_box(sum + black_box(value));
+        }
+        sum
+    }
+
+    fn reference_product(values: &[f64]) -> f64 {
+        let mut product = 1.0;
+        for &value in values {
+            product = black_box(product * black_box(value));
+        }
+        product
+    }
+
+    fn reference_dot(xs: &[f64], ys: &[f64]) -> f64 {
+        let mut sum = 0.0;
+        for (&x, &y) in xs.iter().zip(ys) {
+            sum = black_box(sum + black_box(x) * black_box(y));
+        }
+        sum
+    }
+
+    struct Digest(u64);
+
+    impl Digest {
+        fn add(&mut self, value: f64) {
+            for byte in value.to_bits().to_le_bytes() {
+                self.0 ^= byte as u64;
+                self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+            }
+        }
+
+        fn add_all(&mut self, values: &[f64]) {
+            values.iter().for_each(|&value| self.add(value));
+        }
+    }
+
+    fn assert_bits(actual: f64, expected: f64, what: &str, len: usize) {
+        assert_eq!(
+            actual.to_bits(),
+            expected.to_bits(),
+            "{} of {} values",
+            what,
+            len
+        );
+    }
+
+    #[test]
+    fn test_reductions_match_scalar_reference() {
+        for len in LENGTHS {
+            let xs = corpus(len, 0x9e37_79b9_7f4a_7c15);
+            let ys = corpus(len, 0x2545_f491_4f6c_dd1d);
+            assert_bits(sum_doubles(&xs), reference_sum(&xs), "sum", len);
+            assert_bits(product_doubles(&ys), reference_product(&ys), "product", len);
+            assert_bits(dot(&xs, &ys).unwrap(), reference_dot(&xs, &ys), "dot", len);
+        }
+    }
+
+    #[test]
+    fn test_elementwise_ops_match_scalar_reference() {
+        for len in LENGTHS {
+            let xs = corpus(len, 0x9e37_79b9_7f4a_7c15);
+
+            let mut quantized = xs.clone();
+            quantize_slice(&mut quantized, 6, RoundingMode::HalfEven).unwrap();
+            let mut one_by_one = xs.clone();
+            for value in one_by_one.iter_mut() {
+                quantize_slice(std::slice::from_mut(value), 6, RoundingMode::HalfEven).unwrap();
+            }
+            assert_eq!(quantized, one_by_one);
+
+            let reciprocal = Reciprocal::new(3.0).unwrap();
+            let mut scaled = xs.clone();
+            reciprocal.apply_slice(&mut scaled);
+            for (scaled, &x) in scaled.iter().zip(&xs) {
+                assert_bits(*scaled, reciprocal.apply(black_box(x)), "reciprocal", len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_outputs_match_golden_digest() {
+        let mut digest = Digest(0xcbf2_9ce4_8422_2325);
+        for len in LENGTHS {
+            let xs = corpus(len, 0x9e37_79b9_7f4a_7c15);
+            let ys = corpus(len, 0x2545_f491_4f6c_dd1d);
+            digest.add(sum_doubles(&xs));
+            digest.add(sum_doubles_compensated(&xs));
+            digest.add(product_doubles(&ys));
+            digest.add(dot(&xs, &ys).unwrap());
+            digest.add(dot_compensated(&xs, &ys).unwrap());
+            digest.add(norm2(&xs));
+            digest.add(norm2_compensated(&xs));
+            digest.add_all(&cumsum(&xs));
+            if len > 0 {
+                digest.add_all(&normalize(&xs).unwrap());
+            }
+
+            let mut quantized = xs.clone();
+            quantize_slice(&mut quantized, 6, RoundingMode::HalfEven).unwrap();
+            digest.add_all(&quantized);
+            let mut scaled = xs.clone();
+            Reciprocal::new(3.0).unwrap().apply_slice(&mut scaled);
+            digest.add_all(&scaled);
+        }
+        assert_eq!(digest.0, GOLDEN_DIGEST);
+    }
+}
@@ -0,0 +1,86 @@
+use solana_floats::error::FloatError;
+use solana_floats::quantize::RoundingMode;
+use solana_floats::scaled_decimal::*;
+
+#[cfg(test)]
+mod scaled_decimal_tests {
+    use super::*;
+
+    fn rescaled(mantissa: i64, expo: i32, target: i32, rounding: RoundingMode) -> i64 {
+        rescale(ScaledDecimal::new(mantissa, expo), target, rounding)
+            .unwrap()
+            .mantissa()
+    }
+
+    #[test]
+    fn test_rescale_rounding_is_signed() {
+        // 1.25 and -1.25 to one decimal
+        let cases = [
+            (RoundingMode::Floor, 12, -13),
+            (RoundingMode::Ceil, 13, -12),
+            (RoundingMode::TowardZero, 12, -12),
+            (RoundingMode::HalfUp, 13, -13),
+            (RoundingMode::HalfEven, 12, -12),
+        ];
+        for (rounding, positive, negative) in cases {
+            assert_eq!(rescaled(125, -2, -1, rounding), positive);
+            assert_eq!(rescaled(-125, -2, -1, rounding), negative);
+        }
+        assert_eq!(rescaled(135, -2, -1, RoundingMode::HalfEven), 14);
+        assert_eq!(rescaled(-126, -2, -1, RoundingMode::HalfEven), -13);
+    }
+
+    #[test]
+    fn test_rescale_far_beyond_the_mantissa() {
+        assert_eq!(rescaled(i64::MAX, -8, 100, RoundingMode::HalfUp), 0);
+        assert_eq!(rescaled(i64::MAX, -8, 100, RoundingMode::Ceil), 1);
+        assert_eq!(rescaled(i64::MIN, -8, i32::MAX, RoundingMode::Floor), -1);
+        assert_eq!(
+            rescaled(i64::MIN, i32::MIN, i32::MAX, RoundingMode::TowardZero),
+            0
+        );
+    }
+
+    #[test]
+    fn test_rescale_down_is_exact_or_overflows() {
+        assert_eq!(
+            rescaled(15_012_345_678, -8, -10, RoundingMode::Floor),
+            1_501_234_567_800
+        );
+        assert_eq!(rescaled(0, 5, i32::MIN, RoundingMode::Floor), 0);
+        assert_eq!(
+            rescaled(-9, 0, -18, RoundingMode::Floor),
+            -9_000_000_000_000_000_000
+        );
+        assert_eq!(
+            rescale(ScaledDecimal::new(10, 0), -18, RoundingMode::Floor),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            rescale(
+                ScaledDecimal::new(1, i32::MAX),
+                i32::MIN,
+                RoundingMode::Floor
+            ),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_align_uses_the_smaller_exponent() {
+        let sol_usd = ScaledDecimal::new(15_012_345_678, -8);
+        let bonk_usd = ScaledDecimal::new(2_345, -5);
+        let (sol, bonk) = align(sol_usd, bonk_usd).unwrap();
+        assert_eq!(sol, sol_usd);
+        assert_eq!(bonk, ScaledDecimal::new(2_345_000, -8));
+        assert!(sol.mantissa() > bonk.mantissa());
+        assert_eq!(
+            align(ScaledDecimal::new(1, 0), ScaledDecimal::new(1, -19)),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            align(ScaledDecimal::new(0, 0), ScaledDecimal::new(1, -19)),
+            Ok((ScaledDecimal::new(0, -19), ScaledDecimal::new(1, -19)))
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use solana_floats::error::FloatError;
+use solana_floats::quantize::RoundingMode;
+use solana_floats::shares::*;
+
+#[cfg(test)]
+mod share_accounting_tests {
+    use super::*;
+
+    #[test]
+    fn test_first_deposit_mints_one_to_one() {
+        let shares = shares_for_deposit(1_000_000, 0, 0).unwrap();
+        assert_eq!(shares, 1_000_000);
+    }
+
+    #[test]
+    fn test_deposit_and_redeem_round_trip_never_gains() {
+        // Fund with yield: 1_500_000 assets backing 1_000_000 shares
+        let total_assets = 1_500_000_u64;
+        let total_shares = 1_000_000_u64;
+
+        for deposit in [1_u64, 2, 3, 7, 999, 1_000_001, 123_456_789] {
+            let Ok(minted) = shares_for_deposit(deposit, total_assets, total_shares) else {
+                continue;
+            };
+            let redeemed = assets_for_shares(minted, total_assets + deposit, total_shares + minted)
+                .unwrap_or(0);
+
+            // Rounding must always favour the fund, never the depositor
+            assert!(
+                redeemed <= deposit,
+                "deposit {} redeemed {}",
+                deposit,
+                redeemed
+            );
+        }
+    }
+
+    #[test]
+    fn test_zero_share_mint_is_rejected() {
+        // One share is worth ~1000 assets; a deposit of 1 would mint nothing
+        let result = shares_for_deposit(1, 1_000_000, 1_000);
+        assert_eq!(result, Err(FloatError::PrecisionLoss));
+    }
+
+    #[test]
+    fn test_donation_inflation_attack_is_unprofitable() {
+        // Attacker mints 1 share, then donates a large amount directly to the fund
+        let attacker_shares = shares_for_deposit(1, 0, 0).unwrap();
+        let donation = 1_000_000_000_u64;
+        let total_assets = 1 + donation;
+        let total_shares = attacker_shares;
+
+        // A victim deposit that would round down to nothing is rejected rather
+        // than silently absorbed by the attacker's share
+        let victim = shares_for_deposit(400_000_000, total_assets, total_shares);
+        assert_eq!(victim, Err(FloatError::PrecisionLoss));
+
+        // A victim deposit that does mint gets rounded down, but the virtual
+        // offset means the attacker cannot walk away with more than they put in
+        let victim_deposit = 900_000_000_u64;
+        let victim_shares = shares_for_deposit(victim_deposit, total_assets, total_shares).unwrap();
+        let total_assets = total_assets + victim_deposit;
+        let total_shares = total_shares + victim_shares;
+        let attacker_out = assets_for_shares(attacker_shares, total_assets, total_shares).unwrap();
+        assert!(attacker_out < 1 + donation);
+    }
+
+    #[test]
+    fn test_drained_fund_still_accepts_deposits() {
+        // Insurance payout wiped out all assets; existing shares are worthless
+        let shares = shares_for_deposit(1_000, 0, 1_000_000).unwrap();
+        assert!(shares > 1_000_000);
+        assert_eq!(
+            assets_for_shares(1_000_000, 0, 1_000_000),
+            Err(FloatError::PrecisionLoss)
+        );
+    }
+
+    #[test]
+    fn test_redeeming_more_than_supply_fails() {
+        let result = assets_for_shares(11, 100, 10);
+        assert_eq!(result, Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_extreme_totals_report_overflow() {
+        let result = shares_for_deposit(u64::MAX, 0, u64::MAX);
+        assert_eq!(result, Err(FloatError::Overflow));
+    }
+
+    fn vault(total_assets: u64, total_shares: u64) -> SharePool {
+        SharePool {
+            total_assets,
+            total_shares,
+            first_deposit: FirstDeposit::VirtualOffset {
+                assets: 1,
+                shares: 1_000,
+            },
+        }
+    }
+
+    #[test]
+    fn test_pool_rounding_favors_the_pool() {
+        // 1_001 assets (with the offset) backing 3_000 shares
+        let pool = vault(1_000, 2_000);
+        // Depositing 7 assets mints 20.97 shares
+        assert_eq!(pool.shares_for_assets(7, RoundingMode::Floor), Ok(20));
+        // Withdrawing 7 assets burns 20.97 shares
+        assert_eq!(pool.shares_for_assets(7, RoundingMode::Ceil), Ok(21));
+        // Redeeming 100 shares pays 33.37 assets
+        assert_eq!(pool.assets_for_shares(100, RoundingMode::Floor), Ok(33));
+        // Minting 100 shares costs 33.37 assets, even beyond the supply
+        assert_eq!(pool.assets_for_shares(100, RoundingMode::Ceil), Ok(34));
+        assert_eq!(
+            pool.assets_for_shares(10_000, RoundingMode::Ceil),
+            Ok(3_337)
+        );
+    }
+
+    #[test]
+    fn test_pool_first_deposit_policies() {
+        // The offset sets the initial price: 1_000 shares per asset
+        assert_eq!(
+            vault(0, 0).shares_for_assets(5, RoundingMode::Floor),
+            Ok(5_000)
+        );
+        let spl = |total_assets, total_shares| SharePool {
+            total_assets,
+            total_shares,
+            first_deposit: FirstDeposit::OneToOne,
+        };
+        assert_eq!(spl(0, 0).shares_for_assets(5, RoundingMode::Floor), Ok(5));
+        assert_eq!(spl(0, 0).assets_for_shares(5, RoundingMode::Ceil), Ok(5));
+        // Shares with nothing behind them redeem for nothing
+        assert_eq!(spl(0, 10).assets_for_shares(5, RoundingMode::Ceil), Ok(0));
+        let unbacked = SharePool {
+            first_deposit: FirstDeposit::VirtualOffset {
+                assets: 0,
+                shares: 0,
+            },
+            ..spl(0, 0)
+        };
+        assert_eq!(
+            unbacked.shares_for_assets(5, RoundingMode::Floor),
+            Err(FloatError::DivisionByZero)
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Bit-for-bit comparison of the native float ops against a soft-float oracle.
+//!
+//! The oracle is `rustc_apfloat`, a pure-Rust port of LLVM's APFloat that
+//! implements IEEE-754 arithmetic with integer operations only. Run with
+//!
+//! ```text
+//! cargo test --release --features softfloat-reference --test softfloat_reference
+//! ```
+//!
+//! Inputs mix hand-picked special values with pseudo-random bit patterns from a
+//! fixed seed, so every run checks the same corpus. NaN results are only checked
+//! for being NaN: their payload bits are not specified.
+#![cfg(feature = "softfloat-reference")]
+
+use rustc_apfloat::ieee::{Double, Quad, Single};
+use rustc_apfloat::{Float, FloatConvert};
+use solana_floats::double_ops::*;
+use solana_floats::float_ops::*;
+use solana_floats::softref;
+
+mod common;
+
+const CORPUS_SIZE: usize = 200_000;
+
+const F64_SPECIALS: [u64; 12] = [
+    0x0000000000000000, // +0
+    0x8000000000000000, // -0
+    0x0000000000000001, // smallest subnormal
+    0x000fffffffffffff, // largest subnormal
+    0x0010000000000000, // smallest normal
+    0x3ff0000000000000, // 1
+    0xbff0000000000000, // -1
+    0x3fefffffffffffff, // 1 - ulp
+    0x3ff0000000000001, // 1 + ulp
+    0x7fefffffffffffff, // max
+    0x7ff0000000000000, // +inf
+    0xfff0000000000000, // -inf
+];
+
+const F32_SPECIALS: [u32; 12] = [
+    0x00000000, 0x80000000, 0x00000001, 0x007fffff, 0x00800000, 0x3f800000, 0xbf800000, 0x3f7fffff,
+    0x3f800001, 0x7f7fffff, 0x7f800000, 0xff800000,
+];
+
+/// Special values and structured bit patterns drawn from the shared xorshift64*
+/// stream.
+struct Corpus<I>(I);
+
+impl<I: Iterator<Item = u64>> Corpus<I> {
+    fn next(&mut self) -> u64 {
+        self.0.next().unwrap()
+    }
+
+    fn f64_bits(&mut self) -> u64 {
+        let r = self.next();
+        match r & 7 {
+            0 => F64_SPECIALS[(r >> 8) as usize % F64_SPECIALS.len()],
+            // Exponents near 0 so sums cancel and products stay in range
+            1 | 2 => (r >> 12) | (0x3fe + (r >> 3 & 3)) << 52 | (r & 0x10) << 59,
+            _ => self.next(),
+        }
+    }
+
+    fn f32_bits(&mut self) -> u32 {
+        let r = self.next();
+        match r & 7 {
+            0 => F32_SPECIALS[(r >> 8) as usize % F32_SPECIALS.len()],
+            1 | 2 => {
+                ((r >> 41) as u32) | (0x7e + (r >> 3 & 3) as u32) << 23 | ((r & 0x10) as u32) << 27
+            }
+            _ => self.next() as u32,
+        }
+    }
+}
+
+fn assert_same_f64(native: f64, reference: Double, what: &str, a: u64, b: u64) {
+    if reference.is_nan() {
+        assert!(
+            native.is_nan(),
+            "{}({:#x}, {:#x}): expected NaN",
+            what,
+            a,
+            b
+        );
+    } else {
+        assert_eq!(
+            native.to_bits(),
+            reference.to_bits() as u64,
+            "{}({:#x}, {:#x})",
+            what,
+            a,
+            b
+        );
+    }
+}
+
+fn assert_same_f32(native: f32, reference: Single, what: &str, a: u32, b: u32) {
+    if reference.is_nan() {
+        assert!(
+            native.is_nan(),
+            "{}({:#x}, {:#x}): expected NaN",
+            what,
+            a,
+            b
+        );
+    } else {
+        assert_eq!(
+            native.to_bits(),
+            reference.to_bits() as u32,
+            "{}({:#x}, {:#x})",
+            what,
+            a,
+            b
+        );
+    }
+}
+
+/// Whether `root` is the correctly rounded square root of `x`.
+///
+/// `T` must be wide enough to square a midpoint between neighbouring `F` values
+/// exactly (double the significand bits plus two).
+fn is_correct_sqrt<F, T>(x: F, root: F) -> bool
+where
+    F: Float + FloatConvert<T>,
+    T: Float,
+{
+    if x.is_nan() || x.is_negative() && !x.is_zero() {
+        return root.is_nan();
+    }
+    if x.is_zero() || x.is_infinite() {
+        return root.bitwise_eq(x);
+    }
+    let widen = |value: F| value.convert(&mut false).value;
+    let two = T::from_u128(2).value;
+    let x = widen(x);
+    let below = (widen(root.next_down().value) + widen(root)).value / two;
+    let above = (widen(root) + widen(root.next_up().value)).value / two;
+    // No midpoint squares exactly to x, so strict comparisons suffice.
+    (below.value * below.value).value < x && x < (above.value * above.value).value
+}
+
+#[cfg(test)]
+mod softfloat_reference {
+    use super::*;
+    use common::xorshift_star;
+
+    #[test]
+    fn test_f64_arithmetic_matches_reference() {
+        let mut corpus = Corpus(xorshift_star(0x9e37_79b9_7f4a_7c15));
+        for _ in 0..CORPUS_SIZE {
+            let (a, b) = (corpus.f64_bits(), corpus.f64_bits());
+            let (x, y) = (f64::from_bits(a), f64::from_bits(b));
+            let (rx, ry) = (Double::from_bits(a as u128), Double::from_bits(b as u128));
+
+            assert_same_f64(add_doubles(x, y), (rx + ry).value, "add", a, b);
+            assert_same_f64(multiply_doubles(x, y), (rx * ry).value, "mul", a, b);
+            assert_same_f64(softref::add_f64(x, y), (rx + ry).value, "softref add", a, b);
+            assert_same_f64(softref::mul_f64(x, y), (rx * ry).value, "softref mul", a, b);
+            assert_same_f64(softref::div_f64(x, y), (rx / ry).value, "softref div", a, b);
+            if y != 0.0 {
+                assert_same_f64(divide_doubles(x, y).unwrap(), (rx / ry).value, "div", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_f32_arithmetic_matches_reference() {
+        let mut corpus = Corpus(xorshift_star(0xd1b5_4a32_d192_ed03));
+        for _ in 0..CORPUS_SIZE {
+            let (a, b) = (corpus.f32_bits(), corpus.f32_bits());
+            let (x, y) = (f32::from_bits(a), f32::from_bits(b));
+            let (rx, ry) = (Single::from_bits(a as u128), Single::from_bits(b as u128));
+
+            assert_same_f32(add_floats(x, y), (rx + ry).value, "add", a, b);
+            assert_same_f32(multiply_floats(x, y), (rx * ry).value, "mul", a, b);
+            assert_same_f32(softref::add_f32(x, y), (rx + ry).value, "softref add", a, b);
+            assert_same_f32(softref::mul_f32(x, y), (rx * ry).value, "softref mul", a, b);
+            assert_same_f32(softref::div_f32(x, y), (rx / ry).value, "softref div", a, b);
+            if y != 0.0 {
+                assert_same_f32(divide_floats(x, y).unwrap(), (rx / ry).value, "div", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sqrt_is_correctly_rounded() {
+        let mut corpus = Corpus(xorshift_star(0x2545_f491_4f6c_dd1d));
+        for _ in 0..CORPUS_SIZE {
+            let a = corpus.f64_bits() & !(1 << 63);
+            let root = f64::from_bits(a).sqrt().to_bits();
+            let (x, r) = (
+                Double::from_bits(a as u128),
+                Double::from_bits(root as u128),
+            );
+            assert!(
+                is_correct_sqrt::<Double, Quad>(x, r),
+                "sqrt({:#x}) = {:#x}",
+                a,
+                root
+            );
+            let soft = softref::sqrt_f64(f64::from_bits(a)).to_bits();
+            assert!(is_correct_sqrt::<Double, Quad>(
+                x,
+                Double::from_bits(soft as u128)
+            ));
+
+            let a = corpus.f32_bits() & !(1 << 31);
+            let root = sqrt_float(f32::from_bits(a)).to_bits();
+            let (x, r) = (
+                Single::from_bits(a as u128),
+                Single::from_bits(root as u128),
+            );
+            assert!(
+                is_correct_sqrt::<Single, Double>(x, r),
+                "sqrtf({:#x}) = {:#x}",
+                a,
+                root
+            );
+            let soft = softref::sqrt_f32(f32::from_bits(a)).to_bits();
+            assert!(is_correct_sqrt::<Single, Double>(
+                x,
+                Single::from_bits(soft as u128)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_oracle_catches_a_wrong_result() {
+        // Guard against a harness that passes vacuously
+        let (x, y) = (0.1_f64, 0.2_f64);
+        let reference =
+            (Double::from_bits(x.to_bits() as u128) + Double::from_bits(y.to_bits() as u128)).value;
+        assert_eq!(reference.to_bits() as u64, (x + y).to_bits());
+        assert_ne!(reference.to_bits() as u64, 0.3_f64.to_bits());
+
+        let two = Double::from_bits(2.0_f64.to_bits() as u128);
+        let wrong_root = Double::from_bits((2.0_f64.sqrt().to_bits() + 1) as u128);
+        assert!(!is_correct_sqrt::<Double, Quad>(two, wrong_root));
+    }
+}
@@ -0,0 +1,192 @@
+use solana_floats::quantize::RoundingMode;
+use solana_floats::softref::*;
+
+mod common;
+
+#[cfg(test)]
+mod softref_tests {
+    use super::*;
+    use common::{bit_patterns, same_f32, same_f64};
+
+    #[test]
+    fn test_known_results() {
+        assert_eq!(add_f64(0.1, 0.2), 0.30000000000000004);
+        assert_eq!(mul_f64(1.1, 1.1), 1.2100000000000002);
+        assert_eq!(div_f64(1.0, 3.0), 1.0 / 3.0);
+        assert_eq!(sqrt_f64(2.0), std::f64::consts::SQRT_2);
+        assert_eq!(add_f32(0.1, 0.2), 0.3);
+        assert_eq!(sqrt_f32(16.0), 4.0);
+    }
+
+    #[test]
+    fn test_special_values() {
+        assert!(add_f64(f64::INFINITY, f64::NEG_INFINITY).is_nan());
+        assert!(mul_f64(f64::INFINITY, 0.0).is_nan());
+        assert!(div_f64(0.0, 0.0).is_nan());
+        assert!(sqrt_f64(-1.0).is_nan());
+        assert_eq!(div_f64(-1.0, 0.0), f64::NEG_INFINITY);
+        assert_eq!(add_f64(1.0, -1.0).to_bits(), 0.0_f64.to_bits());
+        assert_eq!(add_f64(-0.0, -0.0).to_bits(), (-0.0_f64).to_bits());
+        assert_eq!(sqrt_f64(-0.0).to_bits(), (-0.0_f64).to_bits());
+        assert_eq!(mul_f64(f64::MAX, 2.0), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_subnormals_and_ties() {
+        let tiny = f64::from_bits(1);
+        assert_eq!(mul_f64(tiny, 0.5), 0.0); // tie rounds to even (zero)
+        assert_eq!(mul_f64(tiny, 1.5).to_bits(), 2); // tie rounds to even (two)
+        assert_eq!(
+            add_f64(f64::MIN_POSITIVE, -tiny).to_bits(),
+            0x000fffffffffffff
+        );
+        assert_eq!(add_f64(1e16, 1.0), 1e16); // 1e16 + 1 ties to even
+        assert_eq!(sqrt_f64(tiny), 2f64.powi(-537));
+    }
+
+    #[test]
+    fn test_f64_matches_native() {
+        let bits = bit_patterns(0x9e37_79b9_7f4a_7c15, 40_000);
+        for pair in bits.chunks_exact(2) {
+            let (a, b) = (f64::from_bits(pair[0]), f64::from_bits(pair[1]));
+            // Bring b near a's exponent half the time so additions cancel
+            let near = f64::from_bits(pair[1] >> 12 | pair[0] & 0xfff0_0000_0000_0000);
+            for b in [b, near] {
+                assert!(same_f64(add_f64(a, b), a + b), "{:e} + {:e}", a, b);
+                assert!(same_f64(mul_f64(a, b), a * b), "{:e} * {:e}", a, b);
+                assert!(same_f64(div_f64(a, b), a / b), "{:e} / {:e}", a, b);
+            }
+            assert!(same_f64(sqrt_f64(a.abs()), a.abs().sqrt()), "sqrt({:e})", a);
+        }
+    }
+
+    #[test]
+    fn test_f32_matches_native() {
+        let bits = bit_patterns(0xd1b5_4a32_d192_ed03, 40_000);
+        for pair in bits.chunks_exact(2) {
+            let (a, b) = (
+                f32::from_bits(pair[0] as u32),
+                f32::from_bits(pair[1] as u32),
+            );
+            let near = f32::from_bits((pair[1] as u32) >> 9 | pair[0] as u32 & 0xff80_0000);
+            for b in [b, near] {
+                assert!(same_f32(add_f32(a, b), a + b), "{:e} + {:e}", a, b);
+                assert!(same_f32(mul_f32(a, b), a * b), "{:e} * {:e}", a, b);
+                assert!(same_f32(div_f32(a, b), a / b), "{:e} / {:e}", a, b);
+            }
+            assert!(same_f32(sqrt_f32(a.abs()), a.abs().sqrt()), "sqrt({:e})", a);
+        }
+    }
+
+    #[test]
+    fn test_div_rounded_known_results() {
+        let third = 1.0 / 3.0; // just below 1/3
+        assert_eq!(div_f64_rounded(1.0, 3.0, RoundingMode::Floor), third);
+        assert_eq!(
+            div_f64_rounded(1.0, 3.0, RoundingMode::Ceil),
+            third.next_up()
+        );
+        assert_eq!(
+            div_f64_rounded(-1.0, 3.0, RoundingMode::Floor),
+            -third.next_up()
+        );
+        assert_eq!(div_f64_rounded(-1.0, 3.0, RoundingMode::TowardZero), -third);
+        // 0.1 is just above a tenth
+        assert_eq!(
+            div_f64_rounded(1.0, 10.0, RoundingMode::Floor),
+            0.1_f64.next_down()
+        );
+        assert_eq!(div_f64_rounded(1.0, 10.0, RoundingMode::Ceil), 0.1);
+        assert_eq!(div_f64_rounded(6.0, 3.0, RoundingMode::Ceil), 2.0);
+    }
+
+    #[test]
+    fn test_div_rounded_overflow_and_underflow() {
+        assert_eq!(
+            div_f64_rounded(f64::MAX, 0.5, RoundingMode::Floor),
+            f64::MAX
+        );
+        assert_eq!(
+            div_f64_rounded(f64::MAX, 0.5, RoundingMode::Ceil),
+            f64::INFINITY
+        );
+        assert_eq!(
+            div_f64_rounded(-f64::MAX, 0.5, RoundingMode::Ceil),
+            -f64::MAX
+        );
+        let tiny = f64::from_bits(1);
+        assert_eq!(div_f64_rounded(tiny, 4.0, RoundingMode::Ceil), tiny);
+        assert_eq!(div_f64_rounded(tiny, 4.0, RoundingMode::Floor), 0.0);
+        assert_eq!(div_f64_rounded(tiny, 1e300, RoundingMode::Ceil), tiny);
+        assert_eq!(div_f64_rounded(-tiny, 1e300, RoundingMode::Floor), -tiny);
+        // Halfway between subnormals, the two nearest modes differ
+        assert_eq!(div_f64_rounded(tiny, 2.0, RoundingMode::HalfEven), 0.0);
+        assert_eq!(div_f64_rounded(tiny, 2.0, RoundingMode::HalfUp), tiny);
+        let five = f64::from_bits(5);
+        assert_eq!(
+            div_f64_rounded(five, 2.0, RoundingMode::HalfEven).to_bits(),
+            2
+        );
+        assert_eq!(
+            div_f64_rounded(five, 2.0, RoundingMode::HalfUp).to_bits(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_div_rounded_brackets_quotient() {
+        let bits = bit_patterns(0x6a09_e667_f3bc_c909, 20_000);
+        for pair in bits.chunks_exact(2) {
+            let (a, b) = (f64::from_bits(pair[0]), f64::from_bits(pair[1]));
+            let nearest = a / b;
+            if !nearest.is_finite() || nearest == 0.0 {
+                continue;
+            }
+            let floor = div_f64_rounded(a, b, RoundingMode::Floor);
+            let ceil = div_f64_rounded(a, b, RoundingMode::Ceil);
+            let toward_zero = div_f64_rounded(a, b, RoundingMode::TowardZero);
+            assert!(
+                same_f64(div_f64_rounded(a, b, RoundingMode::HalfEven), nearest),
+                "{:e} / {:e}",
+                a,
+                b
+            );
+            assert!(floor <= nearest && nearest <= ceil, "{:e} / {:e}", a, b);
+            assert!(
+                floor == ceil || ceil == floor.next_up(),
+                "{:e} / {:e}",
+                a,
+                b
+            );
+            assert!(nearest == floor || nearest == ceil, "{:e} / {:e}", a, b);
+            let expected = if nearest > 0.0 { floor } else { ceil };
+            assert!(same_f64(toward_zero, expected), "{:e} / {:e}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_x87_rounds_twice() {
+        // 1 + 2^-53 + 2^-64 rounds up once, but to the tie 1 + 2^-53 first
+        let b = f64::from_bits(0x3ca0_0200_0000_0000);
+        assert_eq!(add_f64(1.0, b), 1.0000000000000002);
+        assert_eq!(add_f64_x87(1.0, b), 1.0);
+        // Otherwise the second rounding can only move to a neighbour
+        let bits = bit_patterns(0x2545_f491_4f6c_dd1d, 20_000);
+        for pair in bits.chunks_exact(2) {
+            let (a, b) = (f64::from_bits(pair[0]), f64::from_bits(pair[1]));
+            let cases = [
+                (add_f64(a, b), add_f64_x87(a, b)),
+                (mul_f64(a, b), mul_f64_x87(a, b)),
+                (div_f64(a, b), div_f64_x87(a, b)),
+            ];
+            for (once, twice) in cases {
+                assert!(
+                    same_f64(once, twice) || twice == once.next_up() || twice == once.next_down(),
+                    "{:e} and {:e}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+}
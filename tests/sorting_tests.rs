@@ -0,0 +1,73 @@
+use solana_floats::array_ops::*;
+use solana_floats::error::FloatError;
+
+#[cfg(test)]
+mod sorting_tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_total_orders_oracle_prices() {
+        let mut prices = [101.25, 99.5, 100.0, 100.75, 98.0];
+        sort_total(&mut prices);
+        assert_eq!(prices, [98.0, 99.5, 100.0, 100.75, 101.25]);
+    }
+
+    #[test]
+    fn test_sort_total_places_nan_and_signed_zero() {
+        let mut values = [f64::NAN, 1.0, 0.0, -0.0, f64::NEG_INFINITY, -f64::NAN];
+        sort_total(&mut values);
+
+        assert!(values[0].is_nan() && values[0].is_sign_negative());
+        assert_eq!(values[1], f64::NEG_INFINITY);
+        assert!(values[2] == 0.0 && values[2].is_sign_negative());
+        assert!(values[3] == 0.0 && values[3].is_sign_positive());
+        assert_eq!(values[4], 1.0);
+        assert!(values[5].is_nan() && values[5].is_sign_positive());
+    }
+
+    #[test]
+    fn test_sort_finite_rejects_without_modifying() {
+        let mut values = [3.0, f64::NAN, 1.0];
+        assert_eq!(sort_finite(&mut values), Err(FloatError::NaNOperand));
+        assert_eq!(values[0], 3.0);
+        assert_eq!(values[2], 1.0);
+
+        let mut values = [3.0, f64::INFINITY, 1.0];
+        assert_eq!(sort_finite(&mut values), Err(FloatError::OutOfRange));
+        assert_eq!(values[0], 3.0);
+
+        let mut values = [3.0, 2.0, 1.0];
+        assert_eq!(sort_finite(&mut values), Ok(()));
+        assert_eq!(values, [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_argsort_ties_keep_input_order() {
+        // Two orders at the same price must be filled in arrival order
+        let bids = [100.5, 100.25, 100.5, 99.0];
+        assert_eq!(argsort_total(&bids), vec![3, 1, 0, 2]);
+        assert_eq!(argsort_finite(&bids), Ok(vec![3, 1, 0, 2]));
+    }
+
+    #[test]
+    fn test_argsort_finite_rejects_nan() {
+        assert_eq!(
+            argsort_finite(&[1.0, f64::NAN]),
+            Err(FloatError::NaNOperand)
+        );
+    }
+
+    #[test]
+    fn test_sort_is_deterministic_across_input_orders() {
+        let a = [0.3, 0.1 + 0.2, 0.30000000000000004, -0.0, 0.0];
+        let b = [0.0, 0.30000000000000004, -0.0, 0.3, 0.1 + 0.2];
+        let mut sorted_a = a;
+        let mut sorted_b = b;
+        sort_total(&mut sorted_a);
+        sort_total(&mut sorted_b);
+
+        let bits_a: Vec<u64> = sorted_a.iter().map(|v| v.to_bits()).collect();
+        let bits_b: Vec<u64> = sorted_b.iter().map(|v| v.to_bits()).collect();
+        assert_eq!(bits_a, bits_b);
+    }
+}
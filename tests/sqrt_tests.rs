@@ -0,0 +1,180 @@
+use solana_floats::compare::ulp_distance_f64;
+use solana_floats::double_ops::{
+    SQRT_FAST_MAX_ULPS, UnaryOp, apply_unary, sqrt_double, sqrt_double_fast,
+};
+use solana_floats::error::FloatError;
+use solana_floats::instruction::{Opcode, SQRT_BUILTIN, SQRT_FAST, encode};
+use solana_floats::softref;
+use solana_program_test::*;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+mod common;
+
+#[cfg(test)]
+mod sqrt_tests {
+    use super::*;
+    use common::{process_with_metadata, random_positive};
+
+    fn ulps(a: f64, b: f64) -> u64 {
+        ulp_distance_f64(a, b).unwrap()
+    }
+
+    fn assert_within_bound(value: f64) {
+        let exact = sqrt_double(value);
+        let fast = sqrt_double_fast(value);
+        assert!(
+            ulps(exact, fast) <= SQRT_FAST_MAX_ULPS,
+            "sqrt({:e}): builtin {:e}, fast {:e}",
+            value,
+            exact,
+            fast
+        );
+    }
+
+    // Root and compute units of one Sqrt instruction
+    async fn sqrt_instruction(
+        implementation: u8,
+        value: f64,
+    ) -> Result<(f64, u64), TransactionError> {
+        let mut payload = vec![implementation];
+        payload.extend_from_slice(&value.to_le_bytes());
+        let result = process_with_metadata(&encode(Opcode::Sqrt, &payload), vec![]).await;
+        result.result?;
+        let metadata = result.metadata.unwrap();
+        let data = metadata.return_data.unwrap().data;
+        Ok((
+            f64::from_le_bytes(data.try_into().unwrap()),
+            metadata.compute_units_consumed,
+        ))
+    }
+
+    #[test]
+    fn test_special_values_match_builtin() {
+        for value in [
+            0.0,
+            -0.0,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+            -1.0,
+            -f64::MIN_POSITIVE / 2.0,
+        ] {
+            let (exact, fast) = (sqrt_double(value), sqrt_double_fast(value));
+            assert!(
+                exact.to_bits() == fast.to_bits() || (exact.is_nan() && fast.is_nan()),
+                "sqrt({:e})",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_exponent() {
+        // Each binade, including the subnormals, at several mantissa patterns
+        let mantissas = [
+            0,
+            1,
+            0x000f_ffff_ffff_ffff,
+            0x0008_0000_0000_0000,
+            0x0005_5555_5555_5555,
+            0x000a_aaaa_aaaa_aaaa,
+            0x0006_a09e_667f_3bcd,
+        ];
+        for exponent in 0..0x7ff_u64 {
+            for mantissa in mantissas {
+                assert_within_bound(f64::from_bits(exponent << 52 | mantissa));
+            }
+        }
+        assert_within_bound(f64::MAX);
+        assert_within_bound(f64::MIN_POSITIVE);
+        assert_within_bound(f64::from_bits(1));
+    }
+
+    #[test]
+    fn test_random_bit_patterns() {
+        let mut identical = 0;
+        let count = 1_000_000;
+        for value in random_positive(0x9e37_79b9_7f4a_7c15, count) {
+            assert_within_bound(value);
+            if sqrt_double_fast(value).to_bits() == sqrt_double(value).to_bits() {
+                identical += 1;
+            }
+        }
+        // Most results are bit-identical; the rest are one ulp away
+        assert!(identical * 4 > count * 3, "{} of {}", identical, count);
+    }
+
+    #[test]
+    fn test_perfect_squares_are_exact() {
+        for root in (1..1_000_000_u64).chain((1_u64 << 26) - 1_000..1 << 26) {
+            let square = (root * root) as f64;
+            assert_eq!(sqrt_double_fast(square), root as f64);
+        }
+        for exponent in -537..=511 {
+            let root = 2_f64.powi(exponent);
+            assert_eq!(sqrt_double_fast(root * root), root);
+        }
+    }
+
+    #[test]
+    fn test_bound_against_soft_float_oracle() {
+        // The host builtin is the reference elsewhere; check it is correctly
+        // rounded over a corpus so the bound is not relative to a wrong answer
+        for step in 0..10_000_u64 {
+            let value =
+                f64::from_bits(step.wrapping_mul(0x0007_3f2d_9c4b_1a57) & 0x7fef_ffff_ffff_ffff);
+            assert_eq!(
+                sqrt_double(value).to_bits(),
+                softref::sqrt_f64(value).to_bits()
+            );
+            assert!(ulps(softref::sqrt_f64(value), sqrt_double_fast(value)) <= SQRT_FAST_MAX_ULPS);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqrt_instruction_compute_units() {
+        let value = 2.0e-3;
+        let (exact, builtin_units) = sqrt_instruction(SQRT_BUILTIN, value).await.unwrap();
+        let (fast, fast_units) = sqrt_instruction(SQRT_FAST, value).await.unwrap();
+        assert_eq!(exact.to_bits(), sqrt_double(value).to_bits());
+        assert_eq!(fast.to_bits(), sqrt_double_fast(value).to_bits());
+        println!(
+            "Sqrt compute units: builtin {}, fast {}",
+            builtin_units, fast_units
+        );
+
+        // Compute units are only metered for the SBF build (cargo test-sbf)
+        let sbf =
+            std::env::var_os("SBF_OUT_DIR").is_some() || std::env::var_os("BPF_OUT_DIR").is_some();
+        if sbf {
+            assert!(fast_units < builtin_units);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqrt_instruction_rejects_operands_like_unary() {
+        let cases = [
+            (-1.0, FloatError::OutOfRange),
+            (-f64::MIN_POSITIVE / 2.0, FloatError::OutOfRange),
+            (f64::NAN, FloatError::NaNOperand),
+            (f64::INFINITY, FloatError::NaNOperand),
+        ];
+        for implementation in [SQRT_BUILTIN, SQRT_FAST] {
+            for (value, expected) in cases {
+                assert_eq!(apply_unary(UnaryOp::Sqrt, value), Err(expected));
+                assert_eq!(
+                    sqrt_instruction(implementation, value).await,
+                    Err(TransactionError::InstructionError(
+                        0,
+                        InstructionError::Custom(expected.code())
+                    )),
+                    "sqrt({:e})",
+                    value
+                );
+            }
+            // -0.0 is not negative
+            let (root, _) = sqrt_instruction(implementation, -0.0).await.unwrap();
+            assert_eq!(root.to_bits(), (-0.0_f64).to_bits());
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use solana_floats::stability::*;
+
+#[cfg(test)]
+mod stability_tests {
+    use super::*;
+
+    #[test]
+    fn test_golden_mix_is_stable() {
+        assert_eq!(check(), vec![]);
+    }
+
+    #[test]
+    fn test_case_names_are_unique() {
+        for (i, case) in GOLDEN_CASES.iter().enumerate() {
+            assert!(
+                GOLDEN_CASES[..i]
+                    .iter()
+                    .all(|other| other.name != case.name),
+                "{}",
+                case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_changed_bits_are_reported() {
+        let mut cases = GOLDEN_CASES.to_vec();
+        // 2^-54 is what a build fusing the multiply and subtract gives
+        cases[3].expected = 0x3c90000000000000;
+        cases[16].expected ^= 1;
+        let mismatches = check_cases(&cases);
+        assert_eq!(
+            mismatches,
+            vec![
+                Mismatch {
+                    name: "0.1 * 10 - 1",
+                    expected: 0x3c90000000000000,
+                    actual: 0,
+                },
+                Mismatch {
+                    name: GOLDEN_CASES[16].name,
+                    expected: GOLDEN_CASES[16].expected ^ 1,
+                    actual: GOLDEN_CASES[16].expected,
+                },
+            ]
+        );
+    }
+}
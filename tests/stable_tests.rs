@@ -0,0 +1,73 @@
+use solana_floats::error::FloatError;
+use solana_floats::stable::*;
+
+#[cfg(test)]
+mod stable_tests {
+    use super::*;
+
+    fn relative_error(actual: f64, expected: f64) -> f64 {
+        ((actual - expected) / expected).abs()
+    }
+
+    #[test]
+    fn test_variance_of_large_close_values() {
+        let values = [1e9 + 4.0, 1e9 + 7.0, 1e9 + 13.0, 1e9 + 16.0];
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let naive = values.iter().map(|x| x * x).sum::<f64>() / n - mean * mean;
+        assert_eq!(naive, -128.0);
+        assert_eq!(stable_variance(&values), Ok(22.5));
+        assert_eq!(stable_variance(&[]), Err(FloatError::OutOfRange));
+        assert_eq!(
+            stable_variance(&[1.0, f64::NAN]),
+            Err(FloatError::NaNOperand)
+        );
+    }
+
+    #[test]
+    fn test_quadratic_small_root() {
+        // x² - 1e8 x + 1 has roots near 1e-8 and 1e8
+        let (a, b, c): (f64, f64, f64) = (1.0, -1e8, 1.0);
+        let naive = (-b - (b * b - 4.0 * a * c).sqrt()) / (2.0 * a);
+        assert!(relative_error(naive, 1e-8) > 0.25);
+        let (small, large) = quadratic_roots(a, b, c).unwrap();
+        assert!(relative_error(small, 1e-8) < 1e-15);
+        assert_eq!(large, 1e8);
+        assert_eq!(quadratic_roots(2.0, 0.0, -8.0), Ok((-2.0, 2.0)));
+        assert_eq!(quadratic_roots(1.0, 0.0, 0.0), Ok((0.0, 0.0)));
+        assert_eq!(quadratic_roots(1.0, 0.0, 1.0), Err(FloatError::OutOfRange));
+        assert_eq!(quadratic_roots(0.0, 1.0, 1.0), Err(FloatError::OutOfRange));
+        assert_eq!(quadratic_roots(1.0, 1e200, 1.0), Err(FloatError::Overflow));
+    }
+
+    #[test]
+    fn test_log_diff_of_near_equal_values() {
+        let (a, b): (f64, f64) = (1e10 + 1.0, 1e10);
+        // ln(1 + 1e-10) = 1e-10 - 5e-21 + ...
+        let expected = 9.9999999995e-11;
+        let naive = a.ln() - b.ln();
+        assert!(relative_error(naive, expected) > 1e-6);
+        assert!(relative_error(log_diff(a, b).unwrap(), expected) < 1e-15);
+        assert_eq!(log_diff(3.0, 3.0), Ok(0.0));
+        assert!(relative_error(log_diff(1e300, 1e-300).unwrap(), 600.0 * 10f64.ln()) < 1e-15);
+        assert_eq!(log_diff(0.0, 1.0), Err(FloatError::OutOfRange));
+        assert_eq!(log_diff(f64::NAN, 1.0), Err(FloatError::NaNOperand));
+    }
+
+    #[test]
+    fn test_norm_sq_diff_of_nearby_vectors() {
+        let (xs, ys) = ([1e8 + 1.0, 5.0], [1e8, 5.0]);
+        let naive: f64 = xs
+            .iter()
+            .zip(&ys)
+            .map(|(x, y)| x * x - 2.0 * x * y + y * y)
+            .sum();
+        assert_eq!(naive, 0.0);
+        assert_eq!(norm_sq_diff(&xs, &ys), Ok(1.0));
+        assert_eq!(norm_sq_diff(&[1.0], &[]), Err(FloatError::OutOfRange));
+        assert_eq!(
+            norm_sq_diff(&[f64::MAX], &[-f64::MAX]),
+            Err(FloatError::Overflow)
+        );
+    }
+}
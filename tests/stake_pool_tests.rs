@@ -0,0 +1,93 @@
+use solana_floats::error::FloatError;
+use solana_floats::stake_pool::*;
+
+#[cfg(test)]
+mod stake_pool_tests {
+    use super::*;
+
+    const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_empty_pool_mints_one_to_one() {
+        assert_eq!(
+            pool_tokens_for_deposit(5 * LAMPORTS_PER_SOL, 0, 0),
+            Ok(5 * LAMPORTS_PER_SOL)
+        );
+        // SPL treats either total being zero as an empty pool
+        assert_eq!(pool_tokens_for_deposit(7, 0, 100), Ok(7));
+        assert_eq!(pool_tokens_for_deposit(7, 100, 0), Ok(7));
+        assert_eq!(lamports_for_pool_tokens(0, 100, 0), Ok(0));
+    }
+
+    #[test]
+    fn test_rates_follow_pool_value() {
+        // 110 SOL staked backing 100 pool tokens after rewards
+        let total = 110 * LAMPORTS_PER_SOL;
+        let supply = 100 * LAMPORTS_PER_SOL;
+        assert_eq!(
+            pool_tokens_for_deposit(11 * LAMPORTS_PER_SOL, total, supply),
+            Ok(10 * LAMPORTS_PER_SOL)
+        );
+        assert_eq!(
+            lamports_for_pool_tokens(10 * LAMPORTS_PER_SOL, total, supply),
+            Ok(11 * LAMPORTS_PER_SOL)
+        );
+    }
+
+    #[test]
+    fn test_rounding_protects_the_pool() {
+        // 3 lamports backing 2 tokens: 1 lamport is worth 2/3 of a token
+        assert_eq!(pool_tokens_for_deposit(1, 3, 2), Ok(0));
+        assert_eq!(pool_tokens_for_deposit(2, 3, 2), Ok(1));
+        // 1 token is worth 1.5 lamports
+        assert_eq!(lamports_for_pool_tokens(1, 3, 2), Ok(1));
+        // Dust withdrawals pay nothing rather than failing, as in SPL
+        assert_eq!(lamports_for_pool_tokens(1, 1, 2), Ok(0));
+    }
+
+    #[test]
+    fn test_round_trip_never_gains() {
+        let total = 1_234_567_891_011_u64;
+        let supply = 987_654_321_000_u64;
+        for deposit in [
+            1_u64,
+            2,
+            3,
+            999,
+            LAMPORTS_PER_SOL + 1,
+            77 * LAMPORTS_PER_SOL,
+        ] {
+            let minted = pool_tokens_for_deposit(deposit, total, supply).unwrap();
+            let returned =
+                lamports_for_pool_tokens(minted, total + deposit, supply + minted).unwrap();
+            assert!(
+                returned <= deposit,
+                "deposit {} returned {}",
+                deposit,
+                returned
+            );
+        }
+    }
+
+    #[test]
+    fn test_large_values_use_wide_intermediate() {
+        // The product overflows u64 but the result does not
+        assert_eq!(
+            pool_tokens_for_deposit(u64::MAX / 2, u64::MAX, u64::MAX - 1),
+            Ok(u64::MAX / 2 - 1)
+        );
+        // A nearly worthless pool mints more tokens than fit in a u64
+        assert_eq!(
+            pool_tokens_for_deposit(u64::MAX, 1, 2),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_burning_more_than_supply_is_rejected() {
+        assert_eq!(
+            lamports_for_pool_tokens(101, 1_000, 100),
+            Err(FloatError::OutOfRange)
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use solana_floats::error::FloatError;
+use solana_floats::stats::*;
+use solana_program::program_error::ProgramError;
+
+mod common;
+
+#[cfg(test)]
+mod stats_tests {
+    use super::*;
+    use common::xorshift;
+
+    fn sorted_median(values: &[f64]) -> f64 {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len();
+        if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+        }
+    }
+
+    #[test]
+    fn test_median_of_oracle_panel() {
+        let mut prices = [101.2, 99.8, 100.1, 250.0, 100.0];
+        // A single outlier publisher does not move the median
+        assert_eq!(median_inplace(&mut prices), Ok(100.1));
+    }
+
+    #[test]
+    fn test_median_even_length_uses_midpoint() {
+        let mut prices = [4.0, 1.0, 3.0, 2.0];
+        assert_eq!(median_inplace(&mut prices), Ok(2.5));
+    }
+
+    #[test]
+    fn test_median_matches_full_sort_on_large_panels() {
+        // Deterministic pseudo-random panel, including duplicates
+        let mut words = xorshift(0x2545_f491_4f6c_dd1d);
+        for len in [1_usize, 2, 17, 100, 1_001, 4_096] {
+            let values: Vec<f64> = words
+                .by_ref()
+                .take(len)
+                .map(|word| (word % 1_000) as f64 / 8.0)
+                .collect();
+            let expected = sorted_median(&values);
+            let mut working = values.clone();
+            assert_eq!(
+                median_inplace(&mut working).unwrap().to_bits(),
+                expected.to_bits()
+            );
+        }
+    }
+
+    #[test]
+    fn test_median_handles_adversarial_orderings() {
+        // Sorted, reversed and all-equal inputs are classic quickselect worst cases
+        let mut ascending: Vec<f64> = (0..10_001).map(|i| i as f64).collect();
+        let mut descending: Vec<f64> = ascending.iter().rev().copied().collect();
+        let mut constant = vec![42.0_f64; 10_000];
+
+        assert_eq!(median_inplace(&mut ascending), Ok(5_000.0));
+        assert_eq!(median_inplace(&mut descending), Ok(5_000.0));
+        assert_eq!(median_inplace(&mut constant), Ok(42.0));
+    }
+
+    #[test]
+    fn test_median_does_not_overflow() {
+        let mut values = [f64::MAX, f64::MAX];
+        assert_eq!(median_inplace(&mut values), Ok(f64::MAX));
+    }
+
+    #[test]
+    fn test_median_rejects_empty_and_nan() {
+        assert_eq!(median_inplace(&mut []), Err(FloatError::OutOfRange));
+        assert_eq!(
+            median_inplace(&mut [1.0, f64::NAN, 2.0]),
+            Err(FloatError::NaNOperand)
+        );
+    }
+
+    #[test]
+    fn test_quantile_interpolation_policies() {
+        let values = [1.0, 2.0, 3.0, 4.0];
+        // rank = 0.5 * 3 = 1.5, between 2.0 and 3.0
+        assert_eq!(quantile(&values, 0.5, Interpolation::Lower), Ok(2.0));
+        assert_eq!(quantile(&values, 0.5, Interpolation::Higher), Ok(3.0));
+        // Ties go to the even rank: 1.5 rounds to rank 2
+        assert_eq!(quantile(&values, 0.5, Interpolation::Nearest), Ok(3.0));
+        assert_eq!(quantile(&values, 0.5, Interpolation::Linear), Ok(2.5));
+        // rank = 0.9 * 3 = 2.7
+        assert_eq!(quantile(&values, 0.9, Interpolation::Nearest), Ok(4.0));
+    }
+
+    #[test]
+    fn test_quantile_endpoints_are_min_and_max() {
+        let values = [5.0, -3.0, 8.5, 0.0];
+        for interpolation in [
+            Interpolation::Lower,
+            Interpolation::Higher,
+            Interpolation::Nearest,
+            Interpolation::Linear,
+        ] {
+            assert_eq!(quantile(&values, 0.0, interpolation), Ok(-3.0));
+            assert_eq!(quantile(&values, 1.0, interpolation), Ok(8.5));
+        }
+    }
+
+    #[test]
+    fn test_quantile_95th_percentile_price_move() {
+        // 101 daily moves: 0.00, 0.01, ..., 1.00 (in percent), shuffled
+        let mut moves: Vec<f64> = (0..=100).map(|i| i as f64 / 100.0).collect();
+        moves.reverse();
+        moves.swap(3, 70);
+
+        let p95 = quantile(&moves, 0.95, Interpolation::Linear).unwrap();
+        assert_eq!(p95, 0.95);
+        // The input is left untouched
+        assert_eq!(moves[0], 1.0);
+    }
+
+    #[test]
+    fn test_quantile_rejects_invalid_input() {
+        assert_eq!(
+            quantile(&[1.0], 1.5, Interpolation::Linear),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            quantile(&[1.0], f64::NAN, Interpolation::Linear),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            quantile(&[], 0.5, Interpolation::Linear),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            quantile(&[1.0, f64::NAN], 0.5, Interpolation::Linear),
+            Err(FloatError::NaNOperand)
+        );
+    }
+
+    #[test]
+    fn test_histogram_bucket_assignment() {
+        let histogram = Histogram::new(vec![0.0, 1.0, 10.0, 100.0]).unwrap();
+        assert_eq!(histogram.bucket_of(0.0), Some(0));
+        assert_eq!(histogram.bucket_of(0.999), Some(0));
+        // Values on an inner edge belong to the bucket that starts there
+        assert_eq!(histogram.bucket_of(1.0), Some(1));
+        assert_eq!(histogram.bucket_of(10.0), Some(2));
+        // The top edge is included in the last bucket
+        assert_eq!(histogram.bucket_of(100.0), Some(2));
+        assert_eq!(histogram.bucket_of(100.000001), None);
+        assert_eq!(histogram.bucket_of(-0.5), None);
+        assert_eq!(histogram.bucket_of(f64::NAN), None);
+    }
+
+    #[test]
+    fn test_histogram_negative_zero_lands_on_zero_edge() {
+        let histogram = Histogram::new(vec![-1.0, 0.0, 1.0]).unwrap();
+        assert_eq!(histogram.bucket_of(0.0), Some(1));
+        assert_eq!(histogram.bucket_of(-0.0), Some(1));
+    }
+
+    #[test]
+    fn test_histogram_edge_case_from_float_error() {
+        // 0.1 + 0.2 lands just above the 0.3 edge, and always on the same side
+        let mut histogram = Histogram::new(vec![0.0, 0.3, 0.6]).unwrap();
+        assert_eq!(histogram.record(0.1 + 0.2), Ok(1));
+        assert_eq!(histogram.record(0.3), Ok(1));
+        assert_eq!(histogram.record(0.2999999999999999), Ok(0));
+        assert_eq!(histogram.counts(), &[1, 2]);
+        assert_eq!(histogram.total(), 3);
+    }
+
+    #[test]
+    fn test_histogram_record_errors() {
+        let mut histogram = Histogram::new(vec![0.0, 1.0]).unwrap();
+        assert_eq!(histogram.record(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(histogram.record(2.0), Err(FloatError::OutOfRange));
+        assert_eq!(histogram.total(), 0);
+    }
+
+    #[test]
+    fn test_histogram_rejects_bad_edges() {
+        assert_eq!(Histogram::new(vec![1.0]), Err(FloatError::OutOfRange));
+        assert_eq!(Histogram::new(vec![0.0, 0.0]), Err(FloatError::OutOfRange));
+        assert_eq!(Histogram::new(vec![-0.0, 0.0]), Err(FloatError::OutOfRange));
+        assert_eq!(Histogram::new(vec![2.0, 1.0]), Err(FloatError::OutOfRange));
+        assert_eq!(
+            Histogram::new(vec![0.0, f64::NAN]),
+            Err(FloatError::NaNOperand)
+        );
+    }
+
+    #[test]
+    fn test_moving_average_window() {
+        let mut average = MovingAverage::new(3).unwrap();
+        assert_eq!(average.value(), None);
+
+        average.push(1.0).unwrap();
+        average.push(2.0).unwrap();
+        assert_eq!(average.value(), Some(1.5));
+
+        average.push(3.0).unwrap();
+        average.push(10.0).unwrap();
+        // Window now holds 2.0, 3.0, 10.0
+        assert_eq!(average.value(), Some(5.0));
+        assert_eq!(average.len(), 3);
+    }
+
+    #[test]
+    fn test_moving_average_does_not_drift() {
+        // A huge sample passes through the window; once it has left, the
+        // average must return to the small values, not retain residue
+        let mut average = MovingAverage::new(10).unwrap();
+        average.push(1e17).unwrap();
+        for _ in 0..100_000 {
+            average.push(0.1).unwrap();
+        }
+        assert_eq!(average.value(), Some(0.1));
+
+        let mut naive_sum = 1e17_f64;
+        for _ in 0..10 {
+            naive_sum += 0.1;
+        }
+        naive_sum -= 1e17;
+        // The uncompensated sum has lost the small samples entirely
+        assert_eq!(naive_sum, 0.0);
+    }
+
+    #[test]
+    fn test_moving_average_rejects_invalid() {
+        assert_eq!(MovingAverage::new(0), Err(FloatError::OutOfRange));
+        let mut average = MovingAverage::new(2).unwrap();
+        assert_eq!(average.push(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(average.push(f64::INFINITY), Err(FloatError::OutOfRange));
+        assert!(average.is_empty());
+    }
+
+    #[test]
+    fn test_moving_average_account_round_trip() {
+        let mut average = MovingAverage::new(4).unwrap();
+        for price in [100.1, 100.2, 99.9, 100.4, 100.0] {
+            average.push(price).unwrap();
+        }
+
+        let mut data = vec![0u8; MovingAverage::packed_len(4)];
+        average.pack(&mut data).unwrap();
+        let mut restored = MovingAverage::unpack(&data).unwrap();
+        assert_eq!(restored, average);
+
+        // Continuing from the stored state matches continuing in memory
+        average.push(101.0).unwrap();
+        restored.push(101.0).unwrap();
+        assert_eq!(
+            restored.value().unwrap().to_bits(),
+            average.value().unwrap().to_bits()
+        );
+    }
+
+    #[test]
+    fn test_moving_average_unpack_validates_layout() {
+        let average = MovingAverage::new(2).unwrap();
+        let mut data = vec![0u8; MovingAverage::packed_len(2)];
+        average.pack(&mut data).unwrap();
+
+        assert_eq!(
+            MovingAverage::unpack(&data[..data.len() - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            average.pack(&mut [0u8; 8]),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        let mut corrupt = data.clone();
+        corrupt[8..12].copy_from_slice(&5u32.to_le_bytes()); // head past the window
+        assert_eq!(
+            MovingAverage::unpack(&corrupt),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        let mut corrupt = data;
+        corrupt[12..20].copy_from_slice(&f64::NAN.to_le_bytes());
+        assert_eq!(
+            MovingAverage::unpack(&corrupt),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_running_stats_aggregates() {
+        let mut stats = RunningStats::new();
+        assert_eq!(stats.mean(), None);
+        assert_eq!(stats.variance(), None);
+        assert_eq!(stats.min(), None);
+        assert_eq!(stats.sum(), 0.0);
+
+        stats
+            .extend([2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0])
+            .unwrap();
+        assert_eq!(stats.count(), 8);
+        assert_eq!(stats.sum(), 40.0);
+        assert_eq!(stats.mean(), Some(5.0));
+        assert_eq!(stats.variance(), Some(4.0));
+        assert_eq!(stats.min(), Some(2.0));
+        assert_eq!(stats.max(), Some(9.0));
+    }
+
+    #[test]
+    fn test_running_stats_keeps_low_digits() {
+        // A naive sum of a million 0.1s drifts in the 10th significant digit
+        let mut stats = RunningStats::new();
+        stats.extend(std::iter::repeat_n(0.1, 1_000_000)).unwrap();
+        assert_eq!(stats.sum(), 100_000.0);
+        assert!((stats.mean().unwrap() - 0.1).abs() < 1e-15);
+        assert!(stats.variance().unwrap() < 1e-25);
+    }
+
+    #[test]
+    fn test_running_stats_rejects_invalid() {
+        let mut stats = RunningStats::new();
+        stats.push(1.0).unwrap();
+        assert_eq!(stats.push(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(stats.push(f64::INFINITY), Err(FloatError::OutOfRange));
+        // The spread of 1 and f64::MAX squared does not fit
+        let before = stats;
+        assert_eq!(stats.push(f64::MAX), Err(FloatError::Overflow));
+        assert_eq!(stats, before);
+
+        let mut stats = RunningStats::new();
+        stats.push(f64::MAX).unwrap();
+        assert_eq!(stats.push(f64::MAX), Err(FloatError::Overflow));
+        assert_eq!(stats.count(), 1);
+    }
+
+    #[test]
+    fn test_running_stats_pack_round_trip() {
+        let mut stats = RunningStats::new();
+        stats.extend([0.1, -3.5, 1e9]).unwrap();
+        assert_eq!(RunningStats::unpack(&stats.pack()), Ok(stats));
+        assert_eq!(
+            RunningStats::unpack(&RunningStats::new().pack()),
+            Ok(RunningStats::new())
+        );
+
+        let packed = stats.pack();
+        assert_eq!(
+            RunningStats::unpack(&packed[..RunningStats::PACKED_LEN - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let mut corrupted = packed;
+        corrupted[8..16].copy_from_slice(&f64::NAN.to_le_bytes());
+        assert_eq!(
+            RunningStats::unpack(&corrupted),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use solana_floats::double_ops::*;
+use solana_floats::error::FloatError;
+use solana_floats::feature_gate::FeatureGate;
+use solana_floats::float_ops::*;
+use solana_floats::instruction::{Opcode, SUBTRACT_F32, SUBTRACT_F64, encode};
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::{AccountMeta, InstructionError},
+    transaction::TransactionError,
+};
+
+mod common;
+
+#[cfg(test)]
+mod subtraction_tests {
+    use super::*;
+    use common::{PROGRAM_ID, process_with_metadata};
+
+    #[test]
+    fn test_sub_matches_native() {
+        assert_eq!(sub_doubles(0.3, 0.1), 0.3 - 0.1);
+        assert_eq!(sub_floats(0.3, 0.1), 0.3_f32 - 0.1_f32);
+        assert_eq!(sub_doubles(0.3, 0.1).to_bits(), 0x3fc9999999999999);
+    }
+
+    #[test]
+    fn test_cancellation_f32() {
+        // The scenario of test_catastrophic_cancellation_f32: only the last
+        // significand bit survives
+        let difference = sub_floats_with_error(1.0000001, 1.0).unwrap();
+        assert_eq!(difference.value, f32::EPSILON);
+        assert_eq!(difference.cancelled_bits, 23);
+        assert!(difference.relative_error >= 1.0);
+        assert_eq!(
+            sub_floats_checked(1.0000001, 1.0, 16),
+            Err(FloatError::PrecisionLoss)
+        );
+        assert_eq!(sub_floats_checked(1.0000001, 1.0, 23), Ok(f32::EPSILON));
+    }
+
+    #[test]
+    fn test_cancellation_f64() {
+        let difference = sub_doubles_with_error(1.0000000000000002, 1.0).unwrap();
+        assert_eq!(difference.value, f64::EPSILON);
+        assert_eq!(difference.cancelled_bits, 52);
+        assert!(difference.relative_error >= 1.0);
+
+        // 1.0000000000000001 is stored as 1.0: the difference is lost entirely
+        let difference = sub_doubles_with_error(1.0000000000000001, 1.0).unwrap();
+        assert_eq!(difference.value, 0.0);
+        assert_eq!(difference.cancelled_bits, f64::MANTISSA_DIGITS);
+        assert_eq!(difference.relative_error, f64::INFINITY);
+
+        // A balance change of one cent on a large balance keeps few bits
+        let difference = sub_doubles_with_error(1_000_000_000.01, 1_000_000_000.0).unwrap();
+        assert_eq!(difference.cancelled_bits, 36);
+        assert!(difference.relative_error > 1e-6);
+        assert_eq!(
+            sub_doubles_checked(1_000_000_000.01, 1_000_000_000.0, 24),
+            Err(FloatError::PrecisionLoss)
+        );
+    }
+
+    #[test]
+    fn test_no_cancellation() {
+        for (a, b) in [(3.0, 1.0), (1.0, -1.0), (-5.0, 2.5), (1e300, 1e-300)] {
+            let difference = sub_doubles_with_error(a, b).unwrap();
+            assert_eq!(difference.value, a - b);
+            assert_eq!(difference.cancelled_bits, 0);
+            assert!(difference.relative_error <= 2.0 * f64::EPSILON);
+            assert_eq!(sub_doubles_checked(a, b, 0), Ok(a - b));
+        }
+        let difference = sub_floats_with_error(0.0, 0.0).unwrap();
+        assert_eq!(
+            (difference.cancelled_bits, difference.relative_error),
+            (0, 0.0)
+        );
+        let difference = sub_doubles_with_error(0.0, -0.0).unwrap();
+        assert_eq!(
+            (difference.cancelled_bits, difference.relative_error),
+            (0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_subnormal_operands() {
+        let tiny = f64::from_bits(1);
+        let difference = sub_doubles_with_error(3.0 * tiny, 2.0 * tiny).unwrap();
+        assert_eq!(difference.value, tiny);
+        assert_eq!(difference.cancelled_bits, 1);
+        let tiny = f32::from_bits(1);
+        let difference = sub_floats_with_error(f32::MIN_POSITIVE, tiny).unwrap();
+        assert_eq!(difference.cancelled_bits, 1);
+    }
+
+    #[test]
+    fn test_sub_with_error_rejects_non_finite() {
+        assert_eq!(
+            sub_doubles_with_error(f64::NAN, 1.0),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            sub_floats_with_error(1.0, f32::INFINITY),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            sub_doubles_with_error(f64::MAX, -f64::MAX),
+            Err(FloatError::Overflow)
+        );
+        assert_eq!(
+            sub_floats_checked(f32::MAX, -f32::MAX, 24),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    // Return data of one Subtract instruction
+    async fn subtract_instruction(payload: Vec<u8>) -> Result<(f64, u32, f64), TransactionError> {
+        let (gate, _) = FeatureGate::find_address(&PROGRAM_ID);
+        let result = process_with_metadata(
+            &encode(Opcode::Subtract, &payload),
+            vec![AccountMeta::new_readonly(gate, false)],
+        )
+        .await;
+        result.result?;
+        let data = result.metadata.unwrap().return_data.unwrap().data;
+        Ok((
+            f64::from_le_bytes(data[0..8].try_into().unwrap()),
+            u32::from_le_bytes(data[8..12].try_into().unwrap()),
+            f64::from_le_bytes(data[12..20].try_into().unwrap()),
+        ))
+    }
+
+    fn operands_f64(a: f64, b: f64) -> Vec<u8> {
+        [
+            vec![SUBTRACT_F64],
+            a.to_le_bytes().to_vec(),
+            b.to_le_bytes().to_vec(),
+        ]
+        .concat()
+    }
+
+    fn operands_f32(a: f32, b: f32) -> Vec<u8> {
+        [
+            vec![SUBTRACT_F32],
+            a.to_le_bytes().to_vec(),
+            b.to_le_bytes().to_vec(),
+        ]
+        .concat()
+    }
+
+    // The program logs a catastrophic-cancellation warning when more than
+    // half the significand cancels; native test runs do not capture program
+    // logs, so these check the cancelled bits it decides on
+    #[tokio::test]
+    async fn test_subtract_instruction_reports_cancellation() {
+        let (value, cancelled_bits, relative_error) =
+            subtract_instruction(operands_f64(1.0000000000000002, 1.0))
+                .await
+                .unwrap();
+        assert_eq!(value, f64::EPSILON);
+        assert_eq!(cancelled_bits, 52);
+        assert!(relative_error >= 1.0);
+
+        let (value, cancelled_bits, relative_error) =
+            subtract_instruction(operands_f32(1.0000001, 1.0))
+                .await
+                .unwrap();
+        assert_eq!(value, f32::EPSILON as f64);
+        assert_eq!(cancelled_bits, 23);
+        assert!(relative_error >= 1.0);
+
+        let (value, cancelled_bits, relative_error) =
+            subtract_instruction(operands_f64(3.0, 1.0)).await.unwrap();
+        assert_eq!((value, cancelled_bits), (2.0, 0));
+        assert!(relative_error <= 2.0 * f64::EPSILON);
+        let (_, cancelled_bits, _) = subtract_instruction(operands_f32(1.5, 1.4995))
+            .await
+            .unwrap();
+        assert_eq!(cancelled_bits, 11);
+    }
+
+    #[tokio::test]
+    async fn test_subtract_instruction_rejects_bad_input() {
+        let invalid = Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidInstructionData,
+        ));
+        // Width byte that does not match the operand bytes
+        assert_eq!(
+            subtract_instruction(operands_f32(1.0, 2.0)[1..].to_vec()).await,
+            invalid
+        );
+        let mut mismatched = operands_f32(1.0, 2.0);
+        mismatched[0] = SUBTRACT_F64;
+        assert_eq!(subtract_instruction(mismatched).await, invalid);
+        assert_eq!(subtract_instruction(vec![]).await, invalid);
+        assert_eq!(
+            subtract_instruction(operands_f64(f64::NAN, 1.0)).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(FloatError::NaNOperand.code())
+            ))
+        );
+    }
+}
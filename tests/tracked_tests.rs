@@ -0,0 +1,101 @@
+use solana_floats::double_ops::BinaryOp;
+use solana_floats::error::FloatError;
+use solana_floats::instruction::{EVALUATE_FLAG_ANALYSIS, Opcode, encode};
+use solana_floats::tracked::*;
+use solana_program_test::*;
+
+mod common;
+
+#[cfg(test)]
+mod tracked_tests {
+    use super::*;
+    use common::process_with_metadata;
+
+    async fn evaluate_on_chain(flags: u8, op: u8, a: f64, b: f64) -> Option<Vec<u8>> {
+        let mut payload = vec![flags, op];
+        payload.extend_from_slice(&a.to_le_bytes());
+        payload.extend_from_slice(&b.to_le_bytes());
+        let result = process_with_metadata(&encode(Opcode::Evaluate, &payload), vec![]).await;
+        result.result.ok()?;
+        Some(result.metadata?.return_data?.data)
+    }
+
+    #[test]
+    fn test_exact_inputs_carry_half_ulp() {
+        let sum = TrackedF64::exact(0.1)
+            .unwrap()
+            .checked_add(TrackedF64::exact(0.2).unwrap())
+            .unwrap();
+        assert_eq!(sum.ops(), 1);
+        assert_eq!(sum.error_bound(), sum.value() * UNIT_ROUNDOFF);
+    }
+
+    #[test]
+    fn test_bound_covers_actual_error() {
+        // Summing 0.1 ten times drifts from 1.0; the tracked bound must cover it
+        let step = TrackedF64::exact(0.1).unwrap();
+        let mut total = TrackedF64::exact(0.0).unwrap();
+        for _ in 0..10 {
+            total = total.checked_add(step).unwrap();
+        }
+        assert_eq!(total.ops(), 10);
+        assert_ne!(total.value(), 1.0);
+        assert!((total.value() - 1.0).abs() <= total.error_bound() + 10.0 * 0.1 * UNIT_ROUNDOFF);
+    }
+
+    #[test]
+    fn test_errors_propagate_through_products() {
+        let a = TrackedF64::with_error(100.0, 0.5).unwrap();
+        let b = TrackedF64::with_error(2.0, 0.25).unwrap();
+        let product = a.checked_mul(b).unwrap();
+        // |a| * eb + |b| * ea + ea * eb = 25 + 1 + 0.125
+        assert!(product.error_bound() >= 26.125);
+        assert!(product.error_bound() < 26.126);
+        assert_eq!(product.ops(), 1);
+    }
+
+    #[test]
+    fn test_division_by_uncertain_zero() {
+        let one = TrackedF64::exact(1.0).unwrap();
+        let fuzzy_zero = TrackedF64::with_error(1e-9, 1e-6).unwrap();
+        assert_eq!(one.checked_div(fuzzy_zero), Err(FloatError::DivisionByZero));
+        assert_eq!(
+            one.checked_div(TrackedF64::exact(0.0).unwrap()),
+            Err(FloatError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        assert_eq!(TrackedF64::exact(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(
+            TrackedF64::with_error(1.0, -1.0),
+            Err(FloatError::OutOfRange)
+        );
+        let max = TrackedF64::exact(f64::MAX).unwrap();
+        assert_eq!(max.apply(BinaryOp::Add, max), Err(FloatError::Overflow));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_returns_result_bits() {
+        let data = evaluate_on_chain(0, 0, 0.1, 0.2).await.unwrap();
+        assert_eq!(data, (0.1 + 0.2_f64).to_bits().to_le_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_analysis_mode() {
+        let data = evaluate_on_chain(EVALUATE_FLAG_ANALYSIS, 2, 1.0, 3.0)
+            .await
+            .unwrap();
+        assert_eq!(data.len(), 20);
+        let value = f64::from_bits(u64::from_le_bytes(data[0..8].try_into().unwrap()));
+        let error_bound = f64::from_le_bytes(data[8..16].try_into().unwrap());
+        let ops = u32::from_le_bytes(data[16..20].try_into().unwrap());
+        assert_eq!(value, 1.0 / 3.0);
+        assert_eq!(error_bound, value * UNIT_ROUNDOFF);
+        assert_eq!(ops, 1);
+
+        assert_eq!(evaluate_on_chain(0x80, 0, 1.0, 1.0).await, None);
+        assert_eq!(evaluate_on_chain(0, 2, 1.0, 0.0).await, None);
+    }
+}
@@ -0,0 +1,437 @@
+use solana_floats::compare::ulp_distance_f64;
+use solana_floats::double_ops::{log2_doubles, log10_doubles};
+use solana_floats::error::FloatError;
+use solana_floats::instruction::{
+    Opcode, TRANSCENDENTAL_EXP, TRANSCENDENTAL_LN, TRANSCENDENTAL_LUT, TRANSCENDENTAL_SERIES,
+    encode,
+};
+use solana_floats::transcendental::*;
+use solana_program_test::*;
+
+mod common;
+
+#[cfg(test)]
+mod transcendental_tests {
+    use super::*;
+    use common::{process_with_metadata, random_positive, uniform};
+
+    fn ulps(a: f64, b: f64) -> u64 {
+        ulp_distance_f64(a, b).unwrap()
+    }
+
+    // The host libm is the accuracy reference; on-chain code cannot use it
+    // because other targets round differently
+    fn assert_exp_bound(f: fn(f64) -> f64, bound: u64, x: f64) {
+        let expected = x.exp();
+        if expected.is_normal() {
+            assert!(ulps(f(x), expected) <= bound, "exp({:e})", x);
+        }
+    }
+
+    fn assert_ln_bound(f: fn(f64) -> f64, bound: u64, x: f64) {
+        assert!(ulps(f(x), x.ln()) <= bound, "ln({:e})", x);
+    }
+
+    async fn run(function: u8, implementation: u8, value: f64) -> (f64, u64) {
+        let mut payload = vec![function, implementation];
+        payload.extend_from_slice(&value.to_le_bytes());
+        let result = process_with_metadata(&encode(Opcode::Transcendental, &payload), vec![]).await;
+        result.result.unwrap();
+        let metadata = result.metadata.unwrap();
+        let data = metadata.return_data.unwrap().data;
+        (
+            f64::from_le_bytes(data.try_into().unwrap()),
+            metadata.compute_units_consumed,
+        )
+    }
+
+    #[test]
+    fn test_exp_within_documented_bounds() {
+        let points = uniform(-745.0, 709.78, 500_000).chain(uniform(-1e-3, 1e-3, 100_000));
+        for x in points {
+            assert_exp_bound(exp_deterministic, EXP_MAX_ULPS, x);
+            assert_exp_bound(exp_lut, EXP_LUT_MAX_ULPS, x);
+        }
+    }
+
+    #[test]
+    fn test_ln_within_documented_bounds() {
+        let points = random_positive(0x2545_f491_4f6c_dd1d, 500_000)
+            .chain(uniform(0.9, 1.1, 100_000))
+            .chain([f64::MIN_POSITIVE, f64::from_bits(1), f64::MAX]);
+        for x in points {
+            assert_ln_bound(ln_deterministic, LN_MAX_ULPS, x);
+            assert_ln_bound(ln_lut, LN_LUT_MAX_ULPS, x);
+        }
+    }
+
+    #[test]
+    fn test_exact_points() {
+        for exp in [exp_deterministic, exp_lut] {
+            assert_eq!(exp(0.0), 1.0);
+            assert_eq!(exp(-0.0), 1.0);
+        }
+        for ln in [ln_deterministic, ln_lut] {
+            assert_eq!(ln(1.0), 0.0);
+            assert_eq!(ln(2.0), std::f64::consts::LN_2);
+        }
+    }
+
+    #[test]
+    fn test_special_values() {
+        for exp in [exp_deterministic, exp_lut] {
+            assert!(exp(f64::NAN).is_nan());
+            assert_eq!(exp(f64::INFINITY), f64::INFINITY);
+            assert_eq!(exp(f64::NEG_INFINITY), 0.0);
+            assert_eq!(exp(710.0), f64::INFINITY);
+            assert_eq!(exp(-746.0), 0.0);
+            assert!(exp(709.78).is_finite());
+            assert!(exp(-745.0) > 0.0);
+        }
+        for ln in [ln_deterministic, ln_lut] {
+            assert!(ln(f64::NAN).is_nan());
+            assert!(ln(-1.0).is_nan());
+            assert_eq!(ln(0.0), f64::NEG_INFINITY);
+            assert_eq!(ln(-0.0), f64::NEG_INFINITY);
+            assert_eq!(ln(f64::INFINITY), f64::INFINITY);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for x in uniform(-50.0, 50.0, 10_000) {
+            assert!((ln_deterministic(exp_deterministic(x)) - x).abs() <= 1e-13 * x.abs().max(1.0));
+            assert!((ln_lut(exp_lut(x)) - x).abs() <= 1e-13 * x.abs().max(1.0));
+        }
+    }
+
+    #[test]
+    fn test_log2_log10_within_documented_bounds() {
+        for x in uniform(0.5, 2.0, 200_000)
+            .chain(uniform(0.999, 1.001, 100_000))
+            .chain(random_positive(0x2545_f491_4f6c_dd1d, 200_000))
+        {
+            assert!(
+                ulps(log2_deterministic(x), x.log2()) <= LOG2_MAX_ULPS,
+                "{:e}",
+                x
+            );
+            assert!(
+                ulps(log10_deterministic(x), x.log10()) <= LOG10_MAX_ULPS,
+                "{:e}",
+                x
+            );
+        }
+    }
+
+    #[test]
+    fn test_log2_log10_exact_powers() {
+        for k in -1074..1024 {
+            let x = if k < -1022 {
+                f64::from_bits(1 << (k + 1074))
+            } else {
+                f64::from_bits(((k + 1023) as u64) << 52)
+            };
+            assert_eq!(log2_deterministic(x), k as f64);
+            assert_eq!(log2_doubles(x), Ok((k as f64, 0)));
+        }
+        let mut x = 1.0;
+        for n in 0..=22 {
+            assert_eq!(log10_deterministic(x), n as f64);
+            assert_eq!(log10_doubles(x), Ok((n as f64, 0)));
+            x *= 10.0;
+        }
+        assert_eq!(log2_deterministic(0.0), f64::NEG_INFINITY);
+        assert!(log10_deterministic(-1.0).is_nan());
+        assert_eq!(log10_deterministic(f64::INFINITY), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_log_doubles_report_bound() {
+        let (value, bound) = log2_doubles(3.0).unwrap();
+        assert_eq!(bound, LOG2_MAX_ULPS);
+        assert!(ulps(value, 3_f64.log2()) <= bound);
+        // 1e23 and 0.001 are not the powers of ten they are written as
+        let (value, bound) = log10_doubles(1e23).unwrap();
+        assert_eq!(bound, LOG10_MAX_ULPS);
+        assert!(ulps(value, 23.0) <= bound);
+        let (value, bound) = log10_doubles(0.001).unwrap();
+        assert_eq!(bound, LOG10_MAX_ULPS);
+        assert!(ulps(value, -3.0) <= bound);
+        // Rounding to a decade is safe once the value is clear of the bound
+        let (value, bound) = log10_doubles(2.5e-7).unwrap();
+        let decade = value.floor();
+        assert!(value - decade > bound as f64 * f64::EPSILON * value.abs());
+        assert_eq!(decade, -7.0);
+
+        assert_eq!(log2_doubles(0.0), Err(FloatError::OutOfRange));
+        assert_eq!(log10_doubles(-2.0), Err(FloatError::OutOfRange));
+        assert_eq!(log2_doubles(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(log10_doubles(f64::INFINITY), Err(FloatError::NaNOperand));
+    }
+
+    fn assert_pow_bound(base: f64, exponent: f64) {
+        let expected = base.powf(exponent);
+        if expected.is_normal() {
+            assert!(
+                ulps(pow_deterministic(base, exponent), expected) <= POW_MAX_ULPS,
+                "{:e}^{:e}",
+                base,
+                exponent
+            );
+        }
+    }
+
+    #[test]
+    fn test_pow_within_documented_bound() {
+        let bases = uniform(0.0, 10.0, 200_000);
+        let exponents = uniform(-100.0, 100.0, 200_000);
+        for (base, exponent) in bases.zip(exponents) {
+            assert_pow_bound(base, exponent);
+        }
+        // Rates close to 1 compounded over many periods
+        let bases = uniform(0.9995, 1.0005, 100_000);
+        let exponents = uniform(-5e5, 5e5, 100_000);
+        for (base, exponent) in bases.zip(exponents) {
+            assert_pow_bound(base, exponent);
+        }
+        let exponents = uniform(-2.0, 2.0, 200_000);
+        for (base, exponent) in random_positive(0x2545_f491_4f6c_dd1d, 200_000).zip(exponents) {
+            assert_pow_bound(base, exponent);
+        }
+        let exponents = uniform(-1000.0, 1000.0, 100_000);
+        for (base, exponent) in uniform(0.0, 3.0, 100_000).zip(exponents) {
+            assert_pow_bound(base, exponent.round());
+        }
+    }
+
+    #[test]
+    fn test_pow_integral_exponents() {
+        assert_eq!(pow_deterministic(1.1, 2.0), 1.1 * 1.1);
+        assert_eq!(pow_deterministic(2.0, 10.0), 1024.0);
+        assert_eq!(pow_deterministic(10.0, 15.0), 1e15);
+        assert_eq!(pow_deterministic(0.5, 3.0), 0.125);
+        assert_eq!(pow_deterministic(2.0, -1074.0), f64::from_bits(1));
+        assert_eq!(
+            pow_deterministic(2.0, 1023.0),
+            f64::MAX / (2.0 - f64::EPSILON)
+        );
+        assert_eq!(pow_deterministic(2.0, 1024.0), f64::INFINITY);
+        assert_eq!(pow_deterministic(2.0, -1075.0), 0.0);
+        assert_eq!(pow_deterministic(-3.0, 3.0), -27.0);
+        assert_eq!(pow_deterministic(4.0, 0.5), 2.0);
+    }
+
+    // The same results as f64::powf, bit for bit
+    #[test]
+    fn test_pow_special_values() {
+        let cases = [
+            (f64::NAN, 0.0),
+            (1.0, f64::NAN),
+            (f64::NAN, 1.0),
+            (2.0, f64::NAN),
+            (-1.0, f64::INFINITY),
+            (-8.0, 1.0 / 3.0),
+            (-0.0, 3.0),
+            (-0.0, -3.0),
+            (-0.0, 2.0),
+            (0.0, -2.0),
+            (f64::INFINITY, -1.0),
+            (f64::NEG_INFINITY, 3.0),
+            (f64::NEG_INFINITY, 2.0),
+            (0.5, f64::INFINITY),
+            (0.5, f64::NEG_INFINITY),
+            (2.0, 1e20),
+            (2.0, -1e20),
+            (-2.0, 1e20),
+            (1.0000000000000002, 1e19),
+            (-0.5, -1075.0),
+        ];
+        for (base, exponent) in cases {
+            let (actual, expected) = (pow_deterministic(base, exponent), base.powf(exponent));
+            assert!(
+                actual.to_bits() == expected.to_bits() || actual.is_nan() && expected.is_nan(),
+                "{:e}^{:e}",
+                base,
+                exponent
+            );
+        }
+    }
+
+    fn assert_trig_bounds(x: f64) {
+        let functions = [
+            (
+                sin_deterministic as fn(f64) -> f64,
+                f64::sin as fn(f64) -> f64,
+                SIN_MAX_ULPS,
+            ),
+            (cos_deterministic, f64::cos, COS_MAX_ULPS),
+            (tan_deterministic, f64::tan, TAN_MAX_ULPS),
+        ];
+        for (f, reference, bound) in functions {
+            assert!(ulps(f(x), reference(x)) <= bound, "{:e}", x);
+        }
+    }
+
+    #[test]
+    fn test_trig_within_documented_bounds() {
+        for x in uniform(-1.0, 1.0, 200_000) {
+            assert_trig_bounds(x);
+        }
+        for x in uniform(-100.0, 100.0, 200_000) {
+            assert_trig_bounds(x);
+        }
+        // Either side of the switch to the table-driven reduction
+        for x in uniform(1e6, 3e6, 100_000) {
+            assert_trig_bounds(x);
+        }
+        for x in random_positive(0x2545_f491_4f6c_dd1d, 200_000) {
+            assert_trig_bounds(x);
+            assert_trig_bounds(-x);
+        }
+    }
+
+    // Correctly rounded sin, cos and tan of each input, from a 3000-bit
+    // evaluation; the last case is the double closest to a multiple of pi/2
+    #[test]
+    fn test_trig_golden_vectors() {
+        let cases = [
+            (
+                0.5,
+                0x3fdeaee8744b05f0,
+                0x3fec1528065b7d50,
+                0x3fe17b4f5bf3474a,
+            ),
+            (
+                -2.0,
+                0xbfed18f6ead1b446,
+                0xbfdaa22657537205,
+                0x40017af62e0950f8,
+            ),
+            (
+                3.0,
+                0x3fc210386db6d55b,
+                0xbfefae04be85e5d2,
+                0xbfc23ef71254b86f,
+            ),
+            (
+                10.0,
+                0xbfe1689ef5f34f52,
+                0xbfead9ac890c6b1f,
+                0x3fe4bf5f34be3782,
+            ),
+            (
+                100.0,
+                0xbfe03425b78c4db8,
+                0x3feb981dbf665fdf,
+                0xbfe2ca74d62b5d38,
+            ),
+            (
+                355.0,
+                0xbeff9bd0307d1de3,
+                0xbfefffffffc18e4c,
+                0x3eff9bd030bacc16,
+            ),
+            (
+                1e6,
+                0xbfd6664b2568d867,
+                0x3fedf9df9906d32c,
+                0xbfd7e9768ab734c0,
+            ),
+            (
+                1e22,
+                0xbfeb453ab76bf397,
+                0x3fe0be2cef01c8f4,
+                0xbffa0f79c1b6b257,
+            ),
+            (
+                f64::MAX,
+                0x3f7452fc98b34e97,
+                0xbfefffe62ecfab75,
+                0xbf74530cfe729484,
+            ),
+            (
+                1e-300,
+                0x01a56e1fc2f8f359,
+                0x3ff0000000000000,
+                0x01a56e1fc2f8f359,
+            ),
+            (
+                std::f64::consts::PI,
+                0x3ca1a62633145c07,
+                0xbff0000000000000,
+                0xbca1a62633145c07,
+            ),
+            (
+                std::f64::consts::FRAC_PI_2,
+                0x3ff0000000000000,
+                0x3c91a62633145c07,
+                0x434d02967c31cdb5,
+            ),
+            (
+                6_381_956_970_095_103.0 * 2_f64.powi(797),
+                0x3ff0000000000000,
+                0xbc214ae72e6ba22f,
+                0xc3bd9ba9a7975636,
+            ),
+        ];
+        for (x, sin, cos, tan) in cases {
+            assert_eq!(sin_deterministic(x).to_bits(), sin, "sin {:e}", x);
+            assert_eq!(cos_deterministic(x).to_bits(), cos, "cos {:e}", x);
+            assert_eq!(tan_deterministic(x).to_bits(), tan, "tan {:e}", x);
+        }
+        // Within TAN_MAX_ULPS but not correctly rounded
+        assert_eq!(tan_deterministic(1.0).to_bits(), 0x3ff8eb245cbee3a5);
+        assert_eq!(tan_deterministic(103_993.0).to_bits(), 0xbef40efdf1fb5125);
+    }
+
+    #[test]
+    fn test_trig_special_values() {
+        for x in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(sin_deterministic(x).is_nan());
+            assert!(cos_deterministic(x).is_nan());
+            assert!(tan_deterministic(x).is_nan());
+        }
+        for x in [0.0, -0.0, f64::from_bits(1), -f64::MIN_POSITIVE] {
+            assert_eq!(sin_deterministic(x).to_bits(), x.to_bits());
+            assert_eq!(tan_deterministic(x).to_bits(), x.to_bits());
+            assert_eq!(cos_deterministic(x), 1.0);
+        }
+        // Odd and even exactly, including after the table-driven reduction
+        for x in uniform(-1e9, 1e9, 10_000).chain([1e22, 1e300]) {
+            assert_eq!(sin_deterministic(-x), -sin_deterministic(x));
+            assert_eq!(cos_deterministic(-x), cos_deterministic(x));
+            assert_eq!(tan_deterministic(-x), -tan_deterministic(x));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transcendental_instruction_compute_units() {
+        let mut units = Vec::new();
+        for (function, value) in [(TRANSCENDENTAL_EXP, 1.5), (TRANSCENDENTAL_LN, 1.5)] {
+            let (series, series_units) = run(function, TRANSCENDENTAL_SERIES, value).await;
+            let (lut, lut_units) = run(function, TRANSCENDENTAL_LUT, value).await;
+            let (expected_series, expected_lut) = if function == TRANSCENDENTAL_EXP {
+                (exp_deterministic(value), exp_lut(value))
+            } else {
+                (ln_deterministic(value), ln_lut(value))
+            };
+            assert_eq!(series.to_bits(), expected_series.to_bits());
+            assert_eq!(lut.to_bits(), expected_lut.to_bits());
+            units.push((series_units, lut_units));
+        }
+        println!(
+            "Transcendental compute units: exp series {}, exp lut {}, ln series {}, ln lut {}",
+            units[0].0, units[0].1, units[1].0, units[1].1
+        );
+
+        // Compute units are only metered for the SBF build (cargo test-sbf)
+        let sbf =
+            std::env::var_os("SBF_OUT_DIR").is_some() || std::env::var_os("BPF_OUT_DIR").is_some();
+        if sbf {
+            for (series_units, lut_units) in units {
+                assert!(lut_units < series_units);
+            }
+        }
+    }
+}
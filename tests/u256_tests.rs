@@ -0,0 +1,70 @@
+use solana_floats::u256::U256;
+
+#[cfg(test)]
+mod u256_tests {
+    use super::*;
+
+    #[test]
+    fn test_widening_mul() {
+        let (low, high) = U256::MAX.widening_mul(U256::MAX);
+        // (2^256 - 1)^2 = 2^512 - 2^257 + 1
+        assert_eq!(low, U256::ONE);
+        assert_eq!(high, U256::MAX.checked_sub(U256::ONE).unwrap());
+        assert_eq!(U256::MAX.checked_mul(U256::from(2u64)), None);
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let value = U256::from(u128::MAX) << 100;
+        let divisor = U256::from(12_345_678_901_234_567_890u128);
+        let (quotient, remainder) = value.div_rem(divisor).unwrap();
+        assert!(remainder < divisor);
+        let back = quotient
+            .checked_mul(divisor)
+            .unwrap()
+            .checked_add(remainder)
+            .unwrap();
+        assert_eq!(back, value);
+        assert_eq!(value.div_rem(U256::ZERO), None);
+    }
+
+    #[test]
+    fn test_mul_div_uses_wide_intermediate() {
+        let a = U256::MAX;
+        assert_eq!(U256::mul_div(a, a, a), Some(a));
+        assert_eq!(U256::mul_div(a, U256::from(2u64), U256::ONE), None);
+        assert_eq!(
+            U256::mul_div(U256::from(7u64), U256::from(3u64), U256::from(2u64)),
+            Some(U256::from(10u64))
+        );
+        assert_eq!(
+            U256::mul_div_rem(U256::from(7u64), U256::from(3u64), U256::from(2u64)),
+            Some((U256::from(10u64), U256::ONE))
+        );
+        assert_eq!(U256::mul_div_rem(a, a, U256::ONE), None);
+    }
+
+    #[test]
+    fn test_display_is_decimal() {
+        assert_eq!(U256::ZERO.to_string(), "0");
+        assert_eq!(
+            U256::pow10(40).unwrap().to_string(),
+            format!("1{}", "0".repeat(40))
+        );
+        assert_eq!(
+            U256::MAX.to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(format!("{:>5}", U256::from(42u64)), "   42");
+    }
+
+    #[test]
+    fn test_shifts_and_ordering() {
+        let one = U256::ONE;
+        assert_eq!((one << 255) >> 255, one);
+        assert_eq!(one << 256, U256::ZERO);
+        assert_eq!((one << 200).bits(), 201);
+        assert!(one << 64 > U256::from(u64::MAX));
+        assert_eq!((U256::from(u128::MAX) << 8).try_to_u128(), None);
+    }
+}
@@ -0,0 +1,116 @@
+use solana_floats::double_ops::{UnaryOp, apply_unary, cbrt_double};
+use solana_floats::error::FloatError;
+use solana_floats::instruction::{Opcode, encode};
+use solana_floats::transcendental::*;
+use solana_program_test::*;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+mod common;
+
+#[cfg(test)]
+mod unary_tests {
+    use super::*;
+    use common::process_with_metadata;
+
+    const ALL: [UnaryOp; 8] = [
+        UnaryOp::Sqrt,
+        UnaryOp::Cbrt,
+        UnaryOp::Exp,
+        UnaryOp::Ln,
+        UnaryOp::Log2,
+        UnaryOp::Log10,
+        UnaryOp::Sin,
+        UnaryOp::Cos,
+    ];
+
+    #[test]
+    fn test_op_codes() {
+        for (code, op) in ALL.into_iter().enumerate() {
+            assert_eq!(UnaryOp::try_from(code as u8), Ok(op));
+        }
+        assert_eq!(UnaryOp::try_from(8), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_apply_matches_functions() {
+        for x in [0.25, 1.0, 2.0, 10.0, 123.456] {
+            assert_eq!(apply_unary(UnaryOp::Sqrt, x), Ok(x.sqrt()));
+            assert_eq!(apply_unary(UnaryOp::Cbrt, -x), cbrt_double(-x));
+            assert_eq!(apply_unary(UnaryOp::Exp, x), Ok(exp_deterministic(x)));
+            assert_eq!(apply_unary(UnaryOp::Ln, x), Ok(ln_deterministic(x)));
+            assert_eq!(apply_unary(UnaryOp::Log2, x), Ok(log2_deterministic(x)));
+            assert_eq!(apply_unary(UnaryOp::Log10, x), Ok(log10_deterministic(x)));
+            assert_eq!(apply_unary(UnaryOp::Sin, x), Ok(sin_deterministic(x)));
+            assert_eq!(apply_unary(UnaryOp::Cos, x), Ok(cos_deterministic(x)));
+        }
+        assert_eq!(
+            apply_unary(UnaryOp::Sqrt, -0.0).unwrap().to_bits(),
+            (-0.0_f64).to_bits()
+        );
+        assert_eq!(apply_unary(UnaryOp::Exp, -1000.0), Ok(0.0));
+    }
+
+    #[test]
+    fn test_apply_rejects_instead_of_nan() {
+        for op in ALL {
+            assert_eq!(apply_unary(op, f64::NAN), Err(FloatError::NaNOperand));
+            assert_eq!(
+                apply_unary(op, f64::NEG_INFINITY),
+                Err(FloatError::NaNOperand)
+            );
+        }
+        assert_eq!(
+            apply_unary(UnaryOp::Sqrt, -1.0),
+            Err(FloatError::OutOfRange)
+        );
+        assert_eq!(
+            apply_unary(UnaryOp::Sqrt, -f64::from_bits(1)),
+            Err(FloatError::OutOfRange)
+        );
+        for op in [UnaryOp::Ln, UnaryOp::Log2, UnaryOp::Log10] {
+            assert_eq!(apply_unary(op, 0.0), Err(FloatError::OutOfRange));
+            assert_eq!(apply_unary(op, -2.0), Err(FloatError::OutOfRange));
+        }
+        assert_eq!(apply_unary(UnaryOp::Exp, 710.0), Err(FloatError::Overflow));
+    }
+
+    async fn unary_instruction(payload: &[u8]) -> Result<f64, TransactionError> {
+        let result = process_with_metadata(&encode(Opcode::Unary, payload), vec![]).await;
+        result.result?;
+        let data = result.metadata.unwrap().return_data.unwrap().data;
+        Ok(f64::from_le_bytes(data.try_into().unwrap()))
+    }
+
+    fn payload(op: u8, value: f64) -> Vec<u8> {
+        [vec![op], value.to_le_bytes().to_vec()].concat()
+    }
+
+    #[tokio::test]
+    async fn test_unary_instruction() {
+        assert_eq!(unary_instruction(&payload(0, 2.0)).await, Ok(2_f64.sqrt()));
+        assert_eq!(unary_instruction(&payload(4, 1024.0)).await, Ok(10.0));
+        assert_eq!(
+            unary_instruction(&payload(0, -4.0)).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(FloatError::OutOfRange.code())
+            ))
+        );
+        assert_eq!(
+            unary_instruction(&payload(3, f64::NAN)).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(FloatError::NaNOperand.code())
+            ))
+        );
+        for bad in [payload(8, 1.0), payload(0, 1.0)[..8].to_vec(), vec![]] {
+            assert_eq!(
+                unary_instruction(&bad).await,
+                Err(TransactionError::InstructionError(
+                    0,
+                    InstructionError::InvalidInstructionData
+                ))
+            );
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use solana_floats::error::FloatError;
+use solana_floats::units::*;
+
+#[cfg(test)]
+mod units_tests {
+    use super::*;
+
+    type Sol = Amount<9>;
+    type Usdc = Amount<6>;
+
+    #[test]
+    fn test_raw_round_trip() {
+        let sol = Sol::try_from(1_500_000_001).unwrap();
+        assert_eq!(sol.value(), 1.500000001);
+        assert_eq!(u64::try_from(sol), Ok(1_500_000_001));
+
+        for raw in [0_u64, 1, 999_999, 123_456_789_012, 1_000_000_000_000_001] {
+            assert_eq!(Usdc::from_raw(raw).and_then(Usdc::to_raw), Ok(raw));
+        }
+        let price = Price::<2>::try_from(12_345).unwrap();
+        assert_eq!(price.value(), 123.45);
+    }
+
+    #[test]
+    fn test_raw_beyond_f64_precision_is_rejected() {
+        assert_eq!(Sol::from_raw((1 << 53) + 1), Err(FloatError::PrecisionLoss));
+        assert_eq!(Sol::try_from(u64::MAX), Err(FloatError::PrecisionLoss));
+        // Just below 2^53 the scaled value may no longer round back exactly;
+        // that is reported rather than storing a different integer
+        for raw in (1_u64 << 53) - 1000..1 << 53 {
+            match Usdc::from_raw(raw) {
+                Ok(amount) => assert_eq!(amount.to_raw(), Ok(raw)),
+                Err(error) => assert_eq!(error, FloatError::PrecisionLoss),
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_raw_rounds_half_to_even() {
+        assert_eq!(Usdc::new(0.1 + 0.2).unwrap().to_raw(), Ok(300_000));
+        assert_eq!(Amount::<0>::new(2.5).unwrap().to_raw(), Ok(2));
+        assert_eq!(
+            Amount::<0>::new(1e20).unwrap().to_raw(),
+            Err(FloatError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        assert_eq!(Sol::new(f64::NAN), Err(FloatError::NaNOperand));
+        assert_eq!(Sol::new(f64::INFINITY), Err(FloatError::Overflow));
+        assert_eq!(Sol::new(-1.0), Err(FloatError::OutOfRange));
+        assert_eq!(Amount::<20>::new(1.0), Err(FloatError::OutOfRange));
+        assert_eq!(Amount::<20>::from_raw(1), Err(FloatError::OutOfRange));
+        assert_eq!(Sol::new(-0.0).unwrap().value().to_bits(), 0);
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let a = Usdc::new(10.0).unwrap();
+        let b = Usdc::new(2.5).unwrap();
+        assert_eq!(a.checked_add(b).unwrap().value(), 12.5);
+        assert_eq!(a.checked_sub(b).unwrap().value(), 7.5);
+        assert_eq!(b.checked_sub(a), Err(FloatError::OutOfRange));
+        assert_eq!(Usdc::DECIMALS, 6);
+    }
+
+    #[test]
+    fn test_runtime_decimals_helpers() {
+        assert_eq!(raw_to_value(250, 2), Ok(2.5));
+        assert_eq!(value_to_raw(2.5, 2), Ok(250));
+        assert_eq!(raw_to_value(1, 20), Err(FloatError::OutOfRange));
+    }
+
+    #[test]
+    fn test_display_uses_type_decimals() {
+        assert_eq!(
+            Sol::from_raw(1_500_000_001).unwrap().to_string(),
+            "1.500000001"
+        );
+        assert_eq!(Usdc::new(0.1 + 0.2).unwrap().to_string(), "0.300000");
+        assert_eq!(Price::<2>::new(0.125).unwrap().to_string(), "0.12");
+        assert_eq!(Amount::<0>::new(42.0).unwrap().to_string(), "42");
+        // Format-string precision and width still apply
+        assert_eq!(format!("{:.2}", Sol::new(1.0).unwrap()), "1.00");
+        assert_eq!(format!("{:>8}", Price::<2>::new(3.5).unwrap()), "    3.50");
+    }
+
+    #[test]
+    fn test_display_stays_bounded_past_u64() {
+        let huge = Sol::new(1e300).unwrap().to_string();
+        assert_eq!(huge, "1e300");
+        assert!(Amount::<18>::new(f64::MAX).unwrap().to_string().len() < 32);
+    }
+}
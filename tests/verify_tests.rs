@@ -0,0 +1,114 @@
+use solana_floats::double_ops::BinaryOp;
+use solana_floats::error::FloatError;
+use solana_floats::instruction::{Opcode, encode};
+use solana_floats::verify::*;
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, signature::Signer, transaction::Transaction,
+};
+
+#[cfg(test)]
+mod verify_tests {
+    use super::*;
+
+    fn verify_payload(op: u8, a: f64, b: f64, claimed: f64) -> Vec<u8> {
+        let mut payload = vec![op];
+        payload.extend_from_slice(&a.to_le_bytes());
+        payload.extend_from_slice(&b.to_le_bytes());
+        payload.extend_from_slice(&claimed.to_bits().to_le_bytes());
+        payload
+    }
+
+    #[test]
+    fn test_exact_claims_verify() {
+        assert_eq!(
+            verify(BinaryOp::Add, 0.1, 0.2, (0.1 + 0.2_f64).to_bits()),
+            Ok(true)
+        );
+        assert_eq!(
+            verify(BinaryOp::Multiply, 1.1, 1.1, (1.1 * 1.1_f64).to_bits()),
+            Ok(true)
+        );
+        assert_eq!(
+            verify(BinaryOp::Divide, 1.0, 3.0, (1.0 / 3.0_f64).to_bits()),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_off_by_one_ulp_is_rejected() {
+        let exact = 0.1 + 0.2_f64;
+        // ❌ The "expected" decimal answer is one ulp away from what IEEE-754 produces
+        assert_eq!(
+            verify(BinaryOp::Add, 0.1, 0.2, 0.3_f64.to_bits()),
+            Ok(false)
+        );
+        assert_eq!(
+            verify(BinaryOp::Add, 0.1, 0.2, exact.to_bits() + 1),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_signed_zero_is_distinguished() {
+        assert_eq!(
+            verify(BinaryOp::Multiply, -1.0, 0.0, (-0.0_f64).to_bits()),
+            Ok(true)
+        );
+        assert_eq!(
+            verify(BinaryOp::Multiply, -1.0, 0.0, 0.0_f64.to_bits()),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_nan_is_never_attested() {
+        assert_eq!(
+            recompute(BinaryOp::Add, f64::NAN, 1.0),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            recompute(BinaryOp::Add, f64::INFINITY, f64::NEG_INFINITY),
+            Err(FloatError::NaNOperand)
+        );
+        assert_eq!(
+            recompute(BinaryOp::Divide, 1.0, 0.0),
+            Err(FloatError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_op_decoding() {
+        assert_eq!(BinaryOp::try_from(2), Ok(BinaryOp::Divide));
+        assert_eq!(BinaryOp::try_from(3), Err(FloatError::OutOfRange));
+    }
+
+    #[tokio::test]
+    async fn test_verify_instruction() {
+        let program_id = Pubkey::new_unique();
+        let program_test = ProgramTest::new(
+            "solana_floats",
+            program_id,
+            processor!(solana_floats::process_instruction),
+        );
+        let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        for (payload, should_succeed) in [
+            (verify_payload(0, 0.1, 0.2, 0.1 + 0.2), true),
+            (verify_payload(0, 0.1, 0.2, 0.3), false),
+            (verify_payload(2, 1.0, 0.0, f64::INFINITY), false),
+            (verify_payload(7, 1.0, 1.0, 2.0), false),
+        ] {
+            let instruction =
+                Instruction::new_with_bytes(program_id, &encode(Opcode::Verify, &payload), vec![]);
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer],
+                recent_blockhash,
+            );
+            let result = banks_client.process_transaction(transaction).await;
+            assert_eq!(result.is_ok(), should_succeed);
+        }
+    }
+}